@@ -7,10 +7,10 @@ use ckb_core::transaction::{
     CellInput, CellOutput, OutPoint, ProposalShortId, Transaction, TransactionBuilder,
 };
 use ckb_core::{capacity_bytes, Bytes, Capacity};
-use ckb_db::{CacheDB, DBConfig, RocksDB};
+use ckb_db::{BackendDB, CacheDB, DBConfig};
 use ckb_notify::NotifyService;
 use ckb_shared::shared::{Shared, SharedBuilder};
-use ckb_store::ChainKVStore;
+use ckb_store::{ChainKVStore, StoreConfig};
 use ckb_traits::chain_provider::ChainProvider;
 use criterion::{criterion_group, criterion_main, Criterion};
 use numext_fixed_hash::H256;
@@ -147,7 +147,7 @@ fn new_chain(
     txs_size: usize,
 ) -> (
     ChainController,
-    Shared<ChainKVStore<CacheDB<RocksDB>>>,
+    Shared<ChainKVStore<CacheDB<BackendDB>>>,
     TempDir,
     H256,
     H256,
@@ -192,11 +192,14 @@ fn new_chain(
     consensus.cellbase_maturity = 0;
 
     let db_dir = tempdir().unwrap();
-    let shared = SharedBuilder::<CacheDB<RocksDB>>::default()
-        .db(&DBConfig {
-            path: db_dir.path().to_owned(),
-            options: None,
-        })
+    let shared = SharedBuilder::<CacheDB<BackendDB>>::default()
+        .db(
+            &DBConfig {
+                path: db_dir.path().to_owned(),
+                ..Default::default()
+            },
+            &StoreConfig::default(),
+        )
         .consensus(consensus)
         .build()
         .unwrap();
@@ -1,3 +1,4 @@
+use crate::pruner::Pruner;
 use ckb_chain_spec::consensus::Consensus;
 use ckb_core::block::Block;
 use ckb_core::cell::{
@@ -7,14 +8,15 @@ use ckb_core::cell::{
 use ckb_core::extras::BlockExt;
 use ckb_core::service::{Request, DEFAULT_CHANNEL_SIZE, SIGNAL_CHANNEL_SIZE};
 use ckb_core::transaction::{CellOutput, ProposalShortId};
-use ckb_core::{header::Header, BlockNumber};
+use ckb_core::{header::Header, BlockNumber, EpochNumber};
 use ckb_notify::NotifyController;
 use ckb_shared::cell_set::CellSetDiff;
 use ckb_shared::chain_state::ChainState;
 use ckb_shared::error::SharedError;
 use ckb_shared::shared::Shared;
-use ckb_store::{ChainStore, StoreBatch};
+use ckb_store::{insert_block_filter, ChainStore, StoreBatch};
 use ckb_traits::{BlockMedianTimeContext, ChainProvider};
+use ckb_util::Mutex;
 use ckb_verification::{BlockVerifier, TransactionsVerifier, Verifier};
 use crossbeam_channel::{self, select, Receiver, Sender};
 use failure::Error as FailureError;
@@ -23,6 +25,7 @@ use fnv::{FnvHashMap, FnvHashSet};
 use log::{self, debug, error, info, log_enabled, warn};
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde_derive::{Deserialize, Serialize};
 use std::cmp;
 use std::mem;
@@ -30,9 +33,16 @@ use std::sync::Arc;
 use std::thread;
 use stop_handler::{SignalSender, StopHandler};
 
+/// The smallest pruning depth accepted by the node, the blocks which may still
+/// be detached by a reorg or referenced by the uncles and proposals keep their bodies
+pub const MIN_PRUNE_DEPTH: BlockNumber = 1000;
+/// The smallest number of the recent epochs kept in the db when freezing the blocks
+pub const MIN_FREEZE_EPOCHS: EpochNumber = 1;
+
 #[derive(Clone)]
 pub struct ChainController {
-    process_block_sender: Sender<Request<Arc<Block>, Result<(), FailureError>>>,
+    process_block_sender: Sender<Request<(Arc<Block>, bool), Result<(), FailureError>>>,
+    truncate_sender: Sender<Request<H256, Result<(), FailureError>>>,
     stop: StopHandler<()>,
 }
 
@@ -44,12 +54,26 @@ impl Drop for ChainController {
 
 impl ChainController {
     pub fn process_block(&self, block: Arc<Block>) -> Result<(), FailureError> {
-        Request::call(&self.process_block_sender, block).expect("process_block() failed")
+        Request::call(&self.process_block_sender, (block, false)).expect("process_block() failed")
+    }
+
+    /// Processes a block known to be on a trusted chain, the scripts of its
+    /// transactions are not run.
+    pub fn process_assumed_valid_block(&self, block: Arc<Block>) -> Result<(), FailureError> {
+        Request::call(&self.process_block_sender, (block, true))
+            .expect("process_assumed_valid_block() failed")
+    }
+
+    /// Rolls the main chain back to the block `target_tip_hash`, for the tests
+    /// setting up the reorgs.
+    pub fn truncate(&self, target_tip_hash: H256) -> Result<(), FailureError> {
+        Request::call(&self.truncate_sender, target_tip_hash).expect("truncate() failed")
     }
 }
 
 struct ChainReceivers {
-    process_block_receiver: Receiver<Request<Arc<Block>, Result<(), FailureError>>>,
+    process_block_receiver: Receiver<Request<(Arc<Block>, bool), Result<(), FailureError>>>,
+    truncate_receiver: Receiver<Request<H256, Result<(), FailureError>>>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -134,12 +158,22 @@ impl<'a, CS: ChainStore> BlockMedianTimeContext for ForkContext<'a, CS> {
     fn timestamp(&self, number: BlockNumber) -> Option<u64> {
         self.get_header(number).map(|header| header.timestamp())
     }
+
+    fn epoch_number(&self, number: BlockNumber) -> Option<EpochNumber> {
+        self.get_header(number).map(|header| header.epoch())
+    }
 }
 
 pub struct ChainService<CS> {
     shared: Shared<CS>,
     notify: NotifyController,
     verification: bool,
+    verification_pool: Option<ThreadPool>,
+    prune_depth: Option<BlockNumber>,
+    freeze_epochs: Option<EpochNumber>,
+    // held while detaching blocks, which the pruner must not discard or freeze meanwhile
+    detach_lock: Arc<Mutex<()>>,
+    new_tip_sender: Option<Sender<()>>,
 }
 
 impl<CS: ChainStore + 'static> ChainService<CS> {
@@ -147,11 +181,30 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
         shared: Shared<CS>,
         notify: NotifyController,
         verification: bool,
+        prune_depth: Option<BlockNumber>,
+        freeze_epochs: Option<EpochNumber>,
     ) -> ChainService<CS> {
+        // 0 workers leaves transactions on the global rayon pool
+        let workers = shared.script_config().verification_workers;
+        let verification_pool = if workers > 0 {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(workers)
+                .thread_name(|index| format!("TxVerifier-{}", index))
+                .build()
+                .expect("build verification thread pool");
+            Some(pool)
+        } else {
+            None
+        };
         ChainService {
             shared,
             notify,
             verification,
+            verification_pool,
+            prune_depth,
+            freeze_epochs,
+            detach_lock: Arc::new(Mutex::new(())),
+            new_tip_sender: None,
         }
     }
 
@@ -160,6 +213,7 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
             crossbeam_channel::bounded::<()>(SIGNAL_CHANNEL_SIZE);
         let (process_block_sender, process_block_receiver) =
             crossbeam_channel::bounded(DEFAULT_CHANNEL_SIZE);
+        let (truncate_sender, truncate_receiver) = crossbeam_channel::bounded(SIGNAL_CHANNEL_SIZE);
 
         // Mainly for test: give a empty thread_name
         let mut thread_builder = thread::Builder::new();
@@ -169,36 +223,154 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
 
         let receivers = ChainReceivers {
             process_block_receiver,
+            truncate_receiver,
+        };
+        let pruner = if self.prune_depth.is_some() || self.freeze_epochs.is_some() {
+            let (new_tip_sender, new_tip_receiver) = crossbeam_channel::bounded(1);
+            self.new_tip_sender = Some(new_tip_sender);
+            let pruner = Pruner::new(
+                self.shared.clone(),
+                self.prune_depth,
+                self.freeze_epochs,
+                Arc::clone(&self.detach_lock),
+            );
+            Some(pruner.start(new_tip_receiver))
+        } else {
+            None
         };
         let thread = thread_builder
-            .spawn(move || loop {
-                select! {
-                    recv(signal_receiver) -> _ => {
-                        break;
-                    },
-                    recv(receivers.process_block_receiver) -> msg => match msg {
-                        Ok(Request { responder, arguments: block }) => {
-                            let _ = responder.send(self.process_block(block));
-                        },
-                        _ => {
-                            error!(target: "chain", "process_block_receiver closed");
+            .spawn(move || {
+                loop {
+                    select! {
+                        recv(signal_receiver) -> _ => {
                             break;
                         },
+                        recv(receivers.process_block_receiver) -> msg => match msg {
+                            Ok(Request { responder, arguments: (block, assume_valid) }) => {
+                                let _ = responder.send(self.process_block(block, assume_valid));
+                            },
+                            _ => {
+                                error!(target: "chain", "process_block_receiver closed");
+                                break;
+                            },
+                        },
+                        recv(receivers.truncate_receiver) -> msg => match msg {
+                            Ok(Request { responder, arguments: target_tip_hash }) => {
+                                let _ = responder.send(self.truncate(&target_tip_hash));
+                            },
+                            _ => {
+                                error!(target: "chain", "truncate_receiver closed");
+                                break;
+                            },
+                        }
                     }
                 }
+                // dropping the sender of the new tips stops the pruner
+                drop(self);
+                if let Some(pruner) = pruner {
+                    let _ = pruner.join();
+                }
             })
             .expect("Start ChainService failed");
         let stop = StopHandler::new(SignalSender::Crossbeam(signal_sender), thread);
 
         ChainController {
             process_block_sender,
+            truncate_sender,
             stop,
         }
     }
 
+    // Detaches the main chain blocks above the target, they are kept in the
+    // store as the blocks of a fork
+    pub(crate) fn truncate(&mut self, target_tip_hash: &H256) -> Result<(), FailureError> {
+        let mut chain_state = self.shared.chain_state().lock();
+        let _detach_guard = self.detach_lock.lock();
+        let target_header = self
+            .shared
+            .block_header(target_tip_hash)
+            .filter(|header| {
+                self.shared.block_hash(header.number()).as_ref() == Some(target_tip_hash)
+            })
+            .ok_or_else(|| {
+                SharedError::InvalidData(format!(
+                    "block {:#x} is not in the main chain",
+                    target_tip_hash
+                ))
+            })?;
+        let target_number = target_header.number();
+        if let Some(immutable_number) = self.last_immutable_number() {
+            if target_number < immutable_number {
+                Err(SharedError::InvalidData(format!(
+                    "the blocks above {} are pruned or frozen up to {}",
+                    target_number, immutable_number
+                )))?;
+            }
+        }
+
+        let mut fork = ForkChanges::default();
+        for number in (target_number + 1)..=chain_state.tip_number() {
+            let block = self
+                .shared
+                .block_hash(number)
+                .and_then(|hash| self.shared.block(&hash))
+                .expect("main chain block stored");
+            fork.detached_blocks.push(block);
+        }
+
+        let epoch = self
+            .shared
+            .get_epoch_ext(target_tip_hash)
+            .expect("target epoch stored");
+        let total_difficulty = self
+            .shared
+            .block_ext(target_tip_hash)
+            .expect("target ext stored")
+            .total_difficulty;
+
+        let cell_set_diff = self
+            .shared
+            .store()
+            .write(|batch| -> Result<_, FailureError> {
+                self.update_index(batch, &fork.detached_blocks, &fork.attached_blocks)?;
+                let cell_set_diff =
+                    self.reconcile_main_chain(batch, &mut fork, &mut chain_state, false)?;
+                batch.update_cell_set(&chain_state.cell_set().changes(&cell_set_diff))?;
+                batch.insert_tip_header(&target_header)?;
+                batch.insert_current_epoch_ext(&epoch)?;
+                Ok(cell_set_diff)
+            })?;
+
+        info!(
+            target: "chain",
+            "truncate to block: {}, hash: {:#x}, detached: {}",
+            target_number, target_tip_hash, fork.detached_blocks.len());
+        // the chain state is only updated once the batch is committed
+        self.update_proposal_ids(&mut chain_state, &fork);
+        fork.detached_proposal_id = chain_state.proposal_ids_finalize(target_number);
+        chain_state.update_current_epoch_ext(epoch);
+        chain_state.update_tip(target_header, total_difficulty, cell_set_diff);
+        chain_state.update_tx_pool_for_reorg(
+            fork.detached_blocks().iter(),
+            fork.attached_blocks().iter(),
+            fork.detached_proposal_id().iter(),
+        );
+        let target_block = self
+            .shared
+            .block(target_tip_hash)
+            .expect("target block stored");
+        self.notify.notify_new_tip(Arc::new(target_block));
+        Ok(())
+    }
+
     // process_block will do block verify
     // but invoker should guarantee block header be verified
-    pub(crate) fn process_block(&mut self, block: Arc<Block>) -> Result<(), FailureError> {
+    // the scripts of the transactions are not run when `assume_valid`
+    pub(crate) fn process_block(
+        &mut self,
+        block: Arc<Block>,
+        assume_valid: bool,
+    ) -> Result<(), FailureError> {
         debug!(target: "chain", "begin processing block: {}", block.header().hash());
         if block.header().number() < 1 {
             warn!(target: "chain", "receive 0 number block: {}-{:x}", block.header().number(), block.header().hash());
@@ -210,12 +382,16 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
                 e
             })?
         }
-        self.insert_block(block)?;
+        self.insert_block(block, assume_valid)?;
         debug!(target: "chain", "finish processing block");
         Ok(())
     }
 
-    pub(crate) fn insert_block(&self, block: Arc<Block>) -> Result<(), FailureError> {
+    pub(crate) fn insert_block(
+        &self,
+        block: Arc<Block>,
+        assume_valid: bool,
+    ) -> Result<(), FailureError> {
         let mut new_best_block = false;
         let mut total_difficulty = U256::zero();
 
@@ -223,7 +399,7 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
         let mut fork = ForkChanges::default();
         let mut chain_state = self.shared.chain_state().lock();
         let tip_number = chain_state.tip_number();
-        let tip_hash = chain_state.tip_hash();
+        let tip_hash = chain_state.tip_hash().to_owned();
         let parent_ext = self
             .shared
             .block_ext(&block.header().parent_hash())
@@ -253,55 +429,76 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
             total_difficulty: cannon_total_difficulty.clone(),
             total_uncles_count: parent_ext.total_uncles_count + block.uncles().len() as u64,
             txs_verified: None,
+            txs_fees: Vec::new(),
+            txs_cycles: None,
         };
 
-        let mut batch = self.shared.store().new_batch()?;
-        batch.insert_block(&block)?;
+        let mut detach_guard = None;
+        let store = self.shared.store();
+        let (new_epoch, epoch) = store.write(|batch| {
+            batch.insert_block(&block)?;
+            insert_block_filter(store.as_ref(), batch, &block)?;
 
-        let parent_header_epoch = self
-            .shared
-            .get_epoch_ext(&parent_header.hash())
-            .expect("parent epoch already store");
-
-        let next_epoch_ext = self
-            .shared
-            .next_epoch_ext(&parent_header_epoch, &parent_header);
-        let new_epoch = next_epoch_ext.is_some();
+            let parent_header_epoch = self
+                .shared
+                .get_epoch_ext(&parent_header.hash())
+                .expect("parent epoch already store");
 
-        let epoch = next_epoch_ext.unwrap_or(parent_header_epoch);
+            let next_epoch_ext = self
+                .shared
+                .next_epoch_ext(&parent_header_epoch, &parent_header);
+            let new_epoch = next_epoch_ext.is_some();
+            if let Some(ref epoch) = next_epoch_ext {
+                let states = self
+                    .shared
+                    .next_versionbits_states(&parent_header_epoch, &parent_header);
+                batch.insert_versionbits_states(
+                    epoch.last_block_hash_in_previous_epoch(),
+                    &states,
+                )?;
+            }
 
-        batch.insert_block_epoch_index(
-            &block.header().hash(),
-            epoch.last_block_hash_in_previous_epoch(),
-        )?;
-        batch.insert_epoch_ext(epoch.last_block_hash_in_previous_epoch(), &epoch)?;
+            let epoch = next_epoch_ext.unwrap_or(parent_header_epoch);
+
+            batch.insert_block_epoch_index(
+                &block.header().hash(),
+                epoch.last_block_hash_in_previous_epoch(),
+            )?;
+            batch.insert_epoch_ext(epoch.last_block_hash_in_previous_epoch(), &epoch)?;
+
+            if (cannon_total_difficulty > current_total_difficulty)
+                || ((current_total_difficulty == cannon_total_difficulty)
+                    && (block.header().hash() < &tip_hash))
+            {
+                debug!(
+                    target: "chain",
+                    "new best block found: {} => {}, difficulty diff = {}",
+                    block.header().number(), block.header().hash(),
+                    &cannon_total_difficulty - &current_total_difficulty
+                );
+                if block.header().parent_hash() != &tip_hash {
+                    detach_guard = Some(self.detach_lock.lock());
+                }
+                self.check_fork_point(&block)?;
+                self.find_fork(&mut fork, tip_number, &block, ext);
+                self.update_index(batch, &fork.detached_blocks, &fork.attached_blocks)?;
+                // MUST update index before reconcile_main_chain
+                cell_set_diff =
+                    self.reconcile_main_chain(batch, &mut fork, &mut chain_state, assume_valid)?;
+                batch.update_cell_set(&chain_state.cell_set().changes(&cell_set_diff))?;
+                batch.insert_tip_header(&block.header())?;
+                if new_epoch || fork.has_detached() {
+                    batch.insert_current_epoch_ext(&epoch)?;
+                }
+                new_best_block = true;
 
-        if (cannon_total_difficulty > current_total_difficulty)
-            || ((current_total_difficulty == cannon_total_difficulty)
-                && (block.header().hash() < tip_hash))
-        {
-            debug!(
-                target: "chain",
-                "new best block found: {} => {}, difficulty diff = {}",
-                block.header().number(), block.header().hash(),
-                &cannon_total_difficulty - &current_total_difficulty
-            );
-            self.find_fork(&mut fork, tip_number, &block, ext);
-            self.update_index(&mut batch, &fork.detached_blocks, &fork.attached_blocks)?;
-            // MUST update index before reconcile_main_chain
-            cell_set_diff = self.reconcile_main_chain(&mut batch, &mut fork, &mut chain_state)?;
-            self.update_proposal_ids(&mut chain_state, &fork);
-            batch.insert_tip_header(&block.header())?;
-            if new_epoch || fork.has_detached() {
-                batch.insert_current_epoch_ext(&epoch)?;
+                total_difficulty = cannon_total_difficulty;
+            } else {
+                batch.insert_block_ext(&block.header().hash(), &ext)?;
             }
-            new_best_block = true;
-
-            total_difficulty = cannon_total_difficulty;
-        } else {
-            batch.insert_block_ext(&block.header().hash(), &ext)?;
-        }
-        batch.commit()?;
+            Ok::<_, FailureError>((new_epoch, epoch))
+        })?;
+        drop(detach_guard);
 
         let tip_header = block.header();
         let tip_number = tip_header.number();
@@ -314,6 +511,8 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
                 "block: {}, hash: {:#x}, diff: {:#x}, txs: {}",
                 tip_number, tip_hash, total_difficulty, txs_cnt);
             let tip_header = block.header().to_owned();
+            // the chain state is only updated once the batch is committed
+            self.update_proposal_ids(&mut chain_state, &fork);
             // finalize proposal_id table change
             // then, update tx_pool
             let detached_proposal_id = chain_state.proposal_ids_finalize(tip_number);
@@ -327,9 +526,20 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
                 fork.attached_blocks().iter(),
                 fork.detached_proposal_id().iter(),
             );
+            let expired = chain_state.expire_txs();
             if log_enabled!(target: "chain", log::Level::Debug) {
                 self.print_chain(&chain_state, 10);
             }
+            // the subscribers are notified without holding the chain state
+            drop(chain_state);
+            if !expired.is_empty() {
+                self.notify.notify_expired_transaction(Arc::new(expired));
+            }
+            if let Some(ref new_tip_sender) = self.new_tip_sender {
+                // the pruner is already busy when the channel is full
+                let _ = new_tip_sender.try_send(());
+            }
+            self.notify.notify_new_tip(block);
         } else {
             info!(
                 target: "chain",
@@ -341,6 +551,41 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
         Ok(())
     }
 
+    // The highest main chain block which can not be detached, as it or its
+    // ancestors are pruned or frozen
+    fn last_immutable_number(&self) -> Option<BlockNumber> {
+        let store = self.shared.store();
+        let frozen_number = store
+            .get_frozen_number()
+            .and_then(|frozen_number| frozen_number.checked_sub(1));
+        cmp::max(store.get_pruned_number(), frozen_number)
+    }
+
+    // Switching to the fork detaches the main chain blocks above the fork point,
+    // their bodies must not be pruned or frozen
+    fn check_fork_point(&self, block: &Block) -> Result<(), FailureError> {
+        let immutable_number = match self.last_immutable_number() {
+            Some(immutable_number) => immutable_number,
+            None => return Ok(()),
+        };
+        let mut header = block.header().to_owned();
+        while header.number() >= immutable_number {
+            if self.shared.block_hash(header.number()).as_ref() == Some(header.hash()) {
+                return Ok(());
+            }
+            header = self
+                .shared
+                .block_header(header.parent_hash())
+                .expect("parent header stored");
+        }
+        Err(SharedError::InvalidData(format!(
+            "block {:#x} forks from the main chain below the pruned or frozen block {}",
+            block.header().hash(),
+            immutable_number
+        ))
+        .into())
+    }
+
     pub(crate) fn update_proposal_ids(&self, chain_state: &mut ChainState<CS>, fork: &ForkChanges) {
         for blk in fork.detached_blocks() {
             chain_state.remove_proposal_ids(&blk);
@@ -363,6 +608,13 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
         for block in attached_blocks {
             batch.attach_block(block)?;
         }
+        // written after detaching, which deletes the MMR nodes of the detached blocks
+        let mut headers: Vec<_> = attached_blocks
+            .iter()
+            .map(|block| block.header().to_owned())
+            .collect();
+        headers.sort_by_key(Header::number);
+        batch.insert_header_mmr_nodes(&self.shared.store().header_mmr_nodes(&headers))?;
         Ok(())
     }
 
@@ -483,6 +735,7 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
         batch: &mut StoreBatch,
         fork: &mut ForkChanges,
         chain_state: &mut ChainState<CS>,
+        assume_valid: bool,
     ) -> Result<CellSetDiff, FailureError> {
         let mut cell_set_diff = CellSetDiff::default();
         let mut outputs: FnvHashMap<H256, &[CellOutput]> = FnvHashMap::default();
@@ -513,10 +766,17 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
         }
 
         // The verify function
-        let txs_verifier = TransactionsVerifier::new(
+        let mut txs_verifier = TransactionsVerifier::new(
             self.shared.consensus().max_block_cycles(),
+            self.shared.consensus().max_tx_bytes(),
             self.shared.script_config(),
         );
+        if let Some(ref pool) = self.verification_pool {
+            txs_verifier = txs_verifier.with_thread_pool(pool);
+        }
+        if assume_valid {
+            txs_verifier = txs_verifier.skip_script();
+        }
 
         let mut found_error = None;
         // verify transaction
@@ -574,7 +834,7 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
                                 b.header().number(),
                                 cellbase_maturity,
                             ) {
-                                Ok(_) => {
+                                Ok(txs_cycles) => {
                                     cell_set_diff.push_new(b);
                                     outputs.extend(
                                         b.transactions()
@@ -582,6 +842,15 @@ impl<CS: ChainStore + 'static> ChainService<CS> {
                                             .map(|tx| (tx.hash().to_owned(), tx.outputs())),
                                     );
                                     ext.txs_verified = Some(true);
+                                    ext.txs_fees = resolved
+                                        .iter()
+                                        .skip(1)
+                                        .map(|rtx| rtx.fee().expect("fee verified"))
+                                        .collect();
+                                    if !assume_valid {
+                                        ext.txs_cycles =
+                                            Some(txs_cycles.into_iter().skip(1).collect());
+                                    }
                                 }
                                 Err(err) => {
                                     error!(target: "chain", "cell_set_diff {}", serde_json::to_string(&cell_set_diff).unwrap());
@@ -656,6 +925,8 @@ pub struct ChainBuilder<CS> {
     shared: Shared<CS>,
     notify: NotifyController,
     verification: bool,
+    prune_depth: Option<BlockNumber>,
+    freeze_epochs: Option<EpochNumber>,
 }
 
 impl<CS: ChainStore + 'static> ChainBuilder<CS> {
@@ -664,6 +935,8 @@ impl<CS: ChainStore + 'static> ChainBuilder<CS> {
             shared,
             notify,
             verification: true,
+            prune_depth: None,
+            freeze_epochs: None,
         }
     }
 
@@ -677,7 +950,26 @@ impl<CS: ChainStore + 'static> ChainBuilder<CS> {
         self
     }
 
+    /// Prunes the bodies of the main chain blocks deeper than `prune_depth`
+    pub fn prune_depth(mut self, prune_depth: Option<BlockNumber>) -> Self {
+        self.prune_depth = prune_depth;
+        self
+    }
+
+    /// Moves the main chain blocks older than `freeze_epochs` epochs into the
+    /// freezer of the store
+    pub fn freeze_epochs(mut self, freeze_epochs: Option<EpochNumber>) -> Self {
+        self.freeze_epochs = freeze_epochs;
+        self
+    }
+
     pub fn build(self) -> ChainService<CS> {
-        ChainService::new(self.shared, self.notify, self.verification)
+        ChainService::new(
+            self.shared,
+            self.notify,
+            self.verification,
+            self.prune_depth,
+            self.freeze_epochs,
+        )
     }
 }
@@ -8,6 +8,7 @@
 //!   implement `ChainProvider`
 
 pub mod chain;
+mod pruner;
 
 #[cfg(test)]
 mod tests;
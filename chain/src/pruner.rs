@@ -0,0 +1,127 @@
+use ckb_core::{BlockNumber, EpochNumber};
+use ckb_shared::shared::Shared;
+use ckb_store::{ChainStore, StoreBatch};
+use ckb_util::Mutex;
+use crossbeam_channel::Receiver;
+use failure::Error as FailureError;
+use log::{error, info};
+use std::cmp;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+// The blocks are pruned by batches
+const PRUNE_INTERVAL: BlockNumber = 100;
+const MAX_PRUNE_BATCH: BlockNumber = 1000;
+const MAX_FREEZE_BATCH: BlockNumber = 1000;
+
+/// Discards the bodies of the main chain blocks deeper than `prune_depth`,
+/// and moves the blocks older than `freeze_epochs` epochs into the freezer,
+/// on its own thread woken up by the chain service on each new tip.
+///
+/// The chain service holds `detach_lock` while switching to a fork, a batch of
+/// blocks is never pruned or frozen while they are being detached.
+pub(crate) struct Pruner<CS> {
+    shared: Shared<CS>,
+    prune_depth: Option<BlockNumber>,
+    freeze_epochs: Option<EpochNumber>,
+    detach_lock: Arc<Mutex<()>>,
+}
+
+impl<CS: ChainStore + 'static> Pruner<CS> {
+    pub fn new(
+        shared: Shared<CS>,
+        prune_depth: Option<BlockNumber>,
+        freeze_epochs: Option<EpochNumber>,
+        detach_lock: Arc<Mutex<()>>,
+    ) -> Pruner<CS> {
+        Pruner {
+            shared,
+            prune_depth,
+            freeze_epochs,
+            detach_lock,
+        }
+    }
+
+    /// The thread stops once the sender of the new tips is dropped
+    pub fn start(self, new_tip_receiver: Receiver<()>) -> JoinHandle<()> {
+        thread::Builder::new()
+            .name("ChainPruner".to_string())
+            .spawn(move || {
+                while new_tip_receiver.recv().is_ok() {
+                    if let Err(err) = self.prune() {
+                        error!(target: "chain", "prune blocks error {:?}", err);
+                    }
+                    if let Err(err) = self.freeze() {
+                        error!(target: "chain", "freeze blocks error {:?}", err);
+                    }
+                }
+            })
+            .expect("Start ChainPruner failed")
+    }
+
+    pub(crate) fn prune(&self) -> Result<(), FailureError> {
+        let prune_depth = match self.prune_depth {
+            Some(prune_depth) => prune_depth,
+            None => return Ok(()),
+        };
+        let _detach_guard = self.detach_lock.lock();
+        let store = self.shared.store();
+        // the genesis block is never pruned
+        let pruned_number = store.get_pruned_number().unwrap_or(0);
+        let tip_number = store.get_tip_header().expect("tip header stored").number();
+        let target_number = tip_number.saturating_sub(prune_depth);
+        if target_number < pruned_number + PRUNE_INTERVAL {
+            return Ok(());
+        }
+        let target_number = cmp::min(target_number, pruned_number + MAX_PRUNE_BATCH);
+
+        store.write(|batch| {
+            for number in (pruned_number + 1)..=target_number {
+                let block = store
+                    .get_block_hash(number)
+                    .and_then(|hash| store.get_block(&hash))
+                    .expect("main chain block stored");
+                batch.prune_block(&block)?;
+            }
+            Ok::<_, FailureError>(())
+        })?;
+        info!(
+            target: "chain",
+            "pruned blocks: {} to {}",
+            pruned_number + 1, target_number);
+        Ok(())
+    }
+
+    pub(crate) fn freeze(&self) -> Result<(), FailureError> {
+        let freeze_epochs = match self.freeze_epochs {
+            Some(freeze_epochs) => freeze_epochs,
+            None => return Ok(()),
+        };
+        let _detach_guard = self.detach_lock.lock();
+        let store = self.shared.store();
+        let frozen_number = match store.get_frozen_number() {
+            Some(frozen_number) => frozen_number,
+            None => return Ok(()),
+        };
+        let mut epoch = store.get_current_epoch_ext().expect("current epoch stored");
+        if epoch.number() < freeze_epochs {
+            return Ok(());
+        }
+        for _ in 0..freeze_epochs {
+            epoch = store
+                .get_epoch_ext(epoch.last_block_hash_in_previous_epoch())
+                .expect("previous epoch stored");
+        }
+        let target_number = cmp::min(epoch.start_number(), frozen_number + MAX_FREEZE_BATCH);
+        if target_number <= frozen_number {
+            return Ok(());
+        }
+
+        store.freeze(target_number)?;
+        info!(
+            target: "chain",
+            "frozen blocks: {} to {}",
+            frozen_number, target_number - 1);
+        Ok(())
+    }
+}
@@ -1,16 +1,26 @@
+use crate::chain::ChainBuilder;
 use crate::tests::util::{create_transaction, gen_block, start_chain};
 use ckb_chain_spec::consensus::Consensus;
 use ckb_core::block::Block;
 use ckb_core::block::BlockBuilder;
 use ckb_core::cell::{CellMeta, CellProvider, CellStatus, UnresolvableError};
+use ckb_core::extras::EpochExt;
 use ckb_core::header::HeaderBuilder;
 use ckb_core::script::Script;
 use ckb_core::transaction::{CellInput, CellOutPoint, CellOutput, OutPoint, TransactionBuilder};
 use ckb_core::{capacity_bytes, Bytes, Capacity};
+use ckb_db::MemoryKeyValueDB;
+use ckb_notify::NotifyService;
+use ckb_shared::chain_state::ChainState;
 use ckb_shared::error::SharedError;
+use ckb_shared::shared::SharedBuilder;
+use ckb_store::{ChainStore, COLUMN_BLOCK_BODY};
 use ckb_traits::ChainProvider;
+use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 #[test]
 fn test_genesis_transaction_spend() {
@@ -178,9 +188,16 @@ fn test_transaction_spend_in_same_block() {
             cellbase: false,
             capacity: tx2_output.capacity,
             data_hash: Some(tx2_output.data_hash()),
+            dep_group: false,
             block_number: Some(4),
         })
     );
+
+    let ext = shared
+        .block_ext(&chain.last().unwrap().header().hash())
+        .unwrap();
+    assert_eq!(ext.txs_fees, vec![Capacity::zero(); 2]);
+    assert_eq!(ext.txs_cycles.map(|cycles| cycles.len()), Some(2));
 }
 
 #[test]
@@ -743,3 +760,207 @@ fn test_next_epoch_ext() {
         assert_eq!(epoch.difficulty(), &U256::from(2000u64));
     }
 }
+
+#[test]
+fn test_truncate() {
+    let (chain_controller, shared) = start_chain(None, false);
+    let mut parent = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+    let mut blocks = Vec::new();
+    for _ in 0..5 {
+        let difficulty = parent.difficulty().to_owned();
+        let new_block = gen_block(
+            &parent,
+            difficulty + U256::from(100u64),
+            vec![],
+            vec![],
+            vec![],
+        );
+        chain_controller
+            .process_block(Arc::new(new_block.clone()))
+            .expect("process block ok");
+        parent = new_block.header().to_owned();
+        blocks.push(new_block);
+    }
+    assert_eq!(shared.chain_state().lock().tip_number(), 5);
+
+    let target = blocks[1].header();
+    chain_controller
+        .truncate(target.hash().to_owned())
+        .expect("truncate ok");
+    {
+        let chain_state = shared.chain_state().lock();
+        assert_eq!(chain_state.tip_number(), 2);
+        assert_eq!(chain_state.tip_hash(), target.hash());
+        assert_eq!(
+            chain_state.total_difficulty(),
+            &shared.block_ext(target.hash()).unwrap().total_difficulty
+        );
+    }
+    assert_eq!(shared.block_hash(3), None);
+    assert_eq!(shared.store().get_tip_header().as_ref(), Some(target));
+
+    // The detached blocks are no longer in the main chain
+    assert!(chain_controller
+        .truncate(blocks[3].header().hash().to_owned())
+        .is_err());
+}
+
+#[test]
+fn test_prune() {
+    let shared = SharedBuilder::<MemoryKeyValueDB>::new()
+        .consensus(Consensus::default().set_cellbase_maturity(0))
+        .build()
+        .unwrap();
+    let notify = NotifyService::default().start::<&str>(None);
+    let chain_controller = ChainBuilder::new(shared.clone(), notify)
+        .verification(false)
+        .prune_depth(Some(5))
+        .build()
+        .start::<&str>(None);
+
+    let mut parent = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+    let mut blocks = Vec::new();
+    for _ in 0..110 {
+        let difficulty = parent.difficulty().to_owned();
+        let new_block = gen_block(
+            &parent,
+            difficulty + U256::from(100u64),
+            vec![],
+            vec![],
+            vec![],
+        );
+        chain_controller
+            .process_block(Arc::new(new_block.clone()))
+            .expect("process block ok");
+        parent = new_block.header().to_owned();
+        blocks.push(new_block);
+    }
+
+    // The first batch of blocks deeper than the depth is pruned in the background
+    let store = shared.store();
+    for _ in 0..100 {
+        if store.get_pruned_number().is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(store.get_pruned_number(), Some(100));
+    let pruned = blocks[49].header();
+    assert!(store.is_block_pruned(pruned.hash()));
+    assert_eq!(shared.block(pruned.hash()), None);
+    assert_eq!(shared.block_header(pruned.hash()).as_ref(), Some(pruned));
+    assert!(shared.block_ext(pruned.hash()).is_some());
+    assert!(shared.block(shared.genesis_hash()).is_some());
+    assert_eq!(
+        shared.block(blocks[100].header().hash()).as_ref(),
+        Some(&blocks[100])
+    );
+    let cellbase = &blocks[49].transactions()[0];
+    assert_eq!(
+        store.get_cell_output(cellbase.hash(), 0).as_ref(),
+        cellbase.outputs().get(0)
+    );
+
+    // The cell set is restored from the store column kept up to date with the blocks
+    let chain_state = ChainState::init(
+        store,
+        Arc::new(shared.consensus().clone()),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        chain_state.cell_set(),
+        shared.chain_state().lock().cell_set()
+    );
+
+    // The pruned blocks can not be detached
+    assert!(chain_controller
+        .truncate(blocks[50].header().hash().to_owned())
+        .is_err());
+    assert!(chain_controller
+        .truncate(blocks[104].header().hash().to_owned())
+        .is_ok());
+}
+
+#[test]
+fn test_freeze() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("test_freeze")
+        .tempdir()
+        .unwrap();
+    let genesis_epoch_ext = EpochExt::new(
+        0,
+        capacity_bytes!(5_000),
+        Capacity::zero(),
+        H256::zero(),
+        0,
+        10,
+        U256::one(),
+    );
+    let consensus = Consensus::default()
+        .set_cellbase_maturity(0)
+        .set_genesis_epoch_ext(genesis_epoch_ext);
+    let shared = SharedBuilder::<MemoryKeyValueDB>::new()
+        .consensus(consensus)
+        .freezer_path(Some(tmp_dir.path().to_path_buf()))
+        .build()
+        .unwrap();
+    let notify = NotifyService::default().start::<&str>(None);
+    let chain_controller = ChainBuilder::new(shared.clone(), notify)
+        .verification(false)
+        .freeze_epochs(Some(1))
+        .build()
+        .start::<&str>(None);
+
+    // Without uncles the second epoch is the longest one, the blocks reach the third epoch
+    let mut parent = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+    let mut blocks = Vec::new();
+    for _ in 0..(10 + shared.consensus().max_epoch_length() + 1) {
+        let difficulty = parent.difficulty().to_owned();
+        let new_block = gen_block(
+            &parent,
+            difficulty + U256::from(100u64),
+            vec![],
+            vec![],
+            vec![],
+        );
+        chain_controller
+            .process_block(Arc::new(new_block.clone()))
+            .expect("process block ok");
+        parent = new_block.header().to_owned();
+        blocks.push(new_block);
+    }
+    assert_eq!(shared.chain_state().lock().current_epoch_ext().number(), 2);
+
+    // The blocks of the first epoch are frozen in the background
+    let store = shared.store();
+    for _ in 0..100 {
+        if store.get_frozen_number() != Some(0) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(store.get_frozen_number(), Some(10));
+    let frozen = blocks[4].header();
+    assert_eq!(store.get(COLUMN_BLOCK_BODY, frozen.hash().as_bytes()), None);
+    assert_eq!(shared.block(frozen.hash()).as_ref(), Some(&blocks[4]));
+    assert_eq!(
+        shared.block(shared.genesis_hash()).as_ref(),
+        Some(shared.consensus().genesis_block())
+    );
+    let cellbase = &blocks[4].transactions()[0];
+    assert_eq!(
+        store.get_transaction(cellbase.hash()),
+        Some((cellbase.to_owned(), frozen.hash().to_owned()))
+    );
+    assert!(!store.is_block_pruned(frozen.hash()));
+
+    // The frozen blocks can not be detached
+    assert!(chain_controller
+        .truncate(blocks[3].header().hash().to_owned())
+        .is_err());
+    assert!(chain_controller
+        .truncate(blocks[20].header().hash().to_owned())
+        .is_ok());
+}
@@ -67,6 +67,8 @@ fn test_find_fork_case1() {
         total_uncles_count: 0,
         // if txs in parent is invalid, txs in block is also invalid
         txs_verified: None,
+        txs_fees: Vec::new(),
+        txs_cycles: None,
     };
 
     let mut fork = ForkChanges::default();
@@ -145,6 +147,8 @@ fn test_find_fork_case2() {
         total_uncles_count: 0,
         // if txs in parent is invalid, txs in block is also invalid
         txs_verified: None,
+        txs_fees: Vec::new(),
+        txs_cycles: None,
     };
 
     let mut fork = ForkChanges::default();
@@ -216,6 +220,8 @@ fn test_find_fork_case3() {
         total_uncles_count: 0,
         // if txs in parent is invalid, txs in block is also invalid
         txs_verified: None,
+        txs_fees: Vec::new(),
+        txs_cycles: None,
     };
     let mut fork = ForkChanges::default();
 
@@ -286,6 +292,8 @@ fn test_find_fork_case4() {
         total_uncles_count: 0,
         // if txs in parent is invalid, txs in block is also invalid
         txs_verified: None,
+        txs_fees: Vec::new(),
+        txs_cycles: None,
     };
 
     let mut fork = ForkChanges::default();
@@ -15,6 +15,8 @@ pub struct CellMeta {
     pub cellbase: bool,
     pub capacity: Capacity,
     pub data_hash: Option<H256>,
+    /// Whether the cell is a dep group, whose data lists the out points of its members
+    pub dep_group: bool,
 }
 
 impl From<&CellOutput> for CellMeta {
@@ -22,6 +24,7 @@ impl From<&CellOutput> for CellMeta {
         CellMeta {
             cell_output: Some(output.clone()),
             capacity: output.capacity,
+            dep_group: output.is_dep_group(),
             ..Default::default()
         }
     }
@@ -39,6 +42,10 @@ impl CellMeta {
     pub fn data_hash(&self) -> Option<&H256> {
         self.data_hash.as_ref()
     }
+
+    pub fn is_dep_group(&self) -> bool {
+        self.dep_group
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -115,7 +122,7 @@ impl HeaderStatus {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ResolvedOutPoint {
     pub cell: Option<CellMeta>,
     pub header: Option<Box<Header>>,
@@ -156,12 +163,23 @@ impl ResolvedOutPoint {
 #[derive(Debug)]
 pub struct ResolvedTransaction<'a> {
     pub transaction: &'a Transaction,
+    // Dep groups are expanded in place, so the indices here are the ones
+    // scripts use when loading dep cells.
     pub resolved_deps: Vec<ResolvedOutPoint>,
     pub resolved_inputs: Vec<ResolvedOutPoint>,
+    // The dep group cells themselves, in the order they are referenced.
+    pub resolved_dep_groups: Vec<ResolvedOutPoint>,
 }
 
 pub trait CellProvider {
     fn cell(&self, out_point: &OutPoint) -> CellStatus;
+
+    // Loads the full output of a live cell, cell metas resolved from store
+    // don't carry it. Providers which always fill `cell_output` can rely on
+    // the default implementation.
+    fn load_cell_output(&self, cell_meta: &CellMeta) -> Option<CellOutput> {
+        cell_meta.cell_output.clone()
+    }
 }
 
 pub struct OverlayCellProvider<'a> {
@@ -187,6 +205,12 @@ impl<'a> CellProvider for OverlayCellProvider<'a> {
             CellStatus::Unspecified => CellStatus::Unspecified,
         }
     }
+
+    fn load_cell_output(&self, cell_meta: &CellMeta) -> Option<CellOutput> {
+        self.overlay
+            .load_cell_output(cell_meta)
+            .or_else(|| self.cell_provider.load_cell_output(cell_meta))
+    }
 }
 
 pub struct BlockCellProvider<'a> {
@@ -227,6 +251,7 @@ impl<'a> CellProvider for BlockCellProvider<'a> {
                             cell_output: Some(output.clone()),
                             out_point: out_point.to_owned(),
                             data_hash: None,
+                            dep_group: output.is_dep_group(),
                             capacity: output.capacity,
                             block_number: Some(self.block.header().number()),
                             cellbase: *i == 0,
@@ -335,10 +360,49 @@ pub enum UnresolvableError {
     // 2. OutPoint specifies both header and cell, but the specified cell
     // is not included in the specified block header.
     InvalidHeader(OutPoint),
+    // OutPoint references a dep group cell whose data is not a valid
+    // list of cell OutPoints
+    InvalidDepGroup(OutPoint),
+    // OutPoint is referenced twice by the deps once the dep groups are expanded
+    DuplicateDep(OutPoint),
     Dead(OutPoint),
     Unknown(Vec<OutPoint>),
 }
 
+// Resolves a single dep, returns None when the referenced cell is unknown.
+fn resolve_dep<CP: CellProvider, HP: HeaderProvider>(
+    out_point: &OutPoint,
+    cell_provider: &CP,
+    header_provider: &HP,
+) -> Result<Option<ResolvedOutPoint>, UnresolvableError> {
+    let cell_status = cell_provider.cell(out_point);
+    let header_status = header_provider.header(out_point);
+
+    match (cell_status, header_status) {
+        (CellStatus::Dead, _) => Err(UnresolvableError::Dead(out_point.clone())),
+        (CellStatus::Unknown, _) => Ok(None),
+        (_, HeaderStatus::Unknown) => {
+            // TODO: should we change transaction pool so transactions
+            // with unknown header can be included as orphans, waiting
+            // for the correct block header to enable it?
+            Err(UnresolvableError::InvalidHeader(out_point.clone()))
+        }
+        (_, HeaderStatus::InclusionFaliure) => {
+            Err(UnresolvableError::InvalidHeader(out_point.clone()))
+        }
+        (CellStatus::Live(cell_meta), HeaderStatus::Live(header)) => {
+            Ok(Some(ResolvedOutPoint::cell_and_header(*cell_meta, *header)))
+        }
+        (CellStatus::Live(cell_meta), HeaderStatus::Unspecified) => {
+            Ok(Some(ResolvedOutPoint::cell_only(*cell_meta)))
+        }
+        (CellStatus::Unspecified, HeaderStatus::Live(header)) => {
+            Ok(Some(ResolvedOutPoint::header_only(*header)))
+        }
+        (CellStatus::Unspecified, HeaderStatus::Unspecified) => Err(UnresolvableError::Empty),
+    }
+}
+
 pub fn resolve_transaction<'a, CP: CellProvider, HP: HeaderProvider>(
     transaction: &'a Transaction,
     seen_inputs: &mut FnvHashSet<OutPoint>,
@@ -350,6 +414,9 @@ pub fn resolve_transaction<'a, CP: CellProvider, HP: HeaderProvider>(
         Vec::with_capacity(transaction.inputs().len()),
         Vec::with_capacity(transaction.deps().len()),
     );
+    let mut resolved_dep_groups = Vec::new();
+    // The members of the dep groups may repeat each other or the listed deps
+    let mut seen_deps = FnvHashSet::default();
 
     // skip resolve input of cellbase
     if !transaction.is_cellbase() {
@@ -394,37 +461,40 @@ pub fn resolve_transaction<'a, CP: CellProvider, HP: HeaderProvider>(
     }
 
     for out_point in transaction.dep_pts() {
-        let cell_status = cell_provider.cell(&out_point);
-        let header_status = header_provider.header(&out_point);
-
-        match (cell_status, header_status) {
-            (CellStatus::Dead, _) => {
-                return Err(UnresolvableError::Dead(out_point.clone()));
-            }
-            (CellStatus::Unknown, _) => {
+        if !seen_deps.insert(out_point.clone()) {
+            return Err(UnresolvableError::DuplicateDep(out_point));
+        }
+        let resolved_dep = match resolve_dep(&out_point, cell_provider, header_provider)? {
+            Some(resolved_dep) => resolved_dep,
+            None => {
                 unknown_out_points.push(out_point.clone());
+                continue;
             }
-            (_, HeaderStatus::Unknown) => {
-                // TODO: should we change transaction pool so transactions
-                // with unknown header can be included as orphans, waiting
-                // for the correct block header to enable it?
-                return Err(UnresolvableError::InvalidHeader(out_point.clone()));
-            }
-            (_, HeaderStatus::InclusionFaliure) => {
-                return Err(UnresolvableError::InvalidHeader(out_point.clone()));
-            }
-            (CellStatus::Live(cell_meta), HeaderStatus::Live(header)) => {
-                resolved_deps.push(ResolvedOutPoint::cell_and_header(*cell_meta, *header));
-            }
-            (CellStatus::Live(cell_meta), HeaderStatus::Unspecified) => {
-                resolved_deps.push(ResolvedOutPoint::cell_only(*cell_meta));
-            }
-            (CellStatus::Unspecified, HeaderStatus::Live(header)) => {
-                resolved_deps.push(ResolvedOutPoint::header_only(*header));
-            }
-            (CellStatus::Unspecified, HeaderStatus::Unspecified) => {
-                return Err(UnresolvableError::Empty);
+        };
+        // Only the outputs of the groups are loaded, for their members
+        let dep_group = resolved_dep
+            .cell()
+            .filter(|cell_meta| cell_meta.is_dep_group())
+            .map(|cell_meta| cell_provider.load_cell_output(cell_meta));
+        match dep_group {
+            Some(output) => {
+                let group_out_points = output
+                    .and_then(|output| output.dep_group_out_points())
+                    .ok_or_else(|| UnresolvableError::InvalidDepGroup(out_point.clone()))?;
+                // Members of a dep group are resolved as plain deps, groups
+                // are never expanded recursively.
+                for group_out_point in group_out_points {
+                    if !seen_deps.insert(group_out_point.clone()) {
+                        return Err(UnresolvableError::DuplicateDep(group_out_point));
+                    }
+                    match resolve_dep(&group_out_point, cell_provider, header_provider)? {
+                        Some(resolved) => resolved_deps.push(resolved),
+                        None => unknown_out_points.push(group_out_point),
+                    }
+                }
+                resolved_dep_groups.push(resolved_dep);
             }
+            None => resolved_deps.push(resolved_dep),
         }
     }
 
@@ -435,6 +505,7 @@ pub fn resolve_transaction<'a, CP: CellProvider, HP: HeaderProvider>(
             transaction,
             resolved_inputs,
             resolved_deps,
+            resolved_dep_groups,
         })
     }
 }
@@ -472,7 +543,7 @@ impl<'a> ResolvedTransaction<'a> {
 #[cfg(test)]
 mod tests {
     use super::super::header::{Header, HeaderBuilder};
-    use super::super::script::Script;
+    use super::super::script::{Script, DEP_GROUP_HASH};
     use super::super::transaction::{CellInput, CellOutPoint, OutPoint, TransactionBuilder};
     use super::*;
     use crate::{capacity_bytes, Bytes, Capacity};
@@ -546,6 +617,7 @@ mod tests {
             block_number: Some(1),
             capacity: cell_output.capacity,
             data_hash: Some(cell_output.data_hash()),
+            dep_group: false,
             cell_output: Some(cell_output),
             out_point: CellOutPoint {
                 tx_hash: Default::default(),
@@ -707,6 +779,137 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn resolve_transaction_should_expand_dep_group() {
+        let mut cell_provider = CellMemoryDb::default();
+        let header_provider = HeaderMemoryDb::default();
+
+        let op_data = OutPoint::new_cell(h256!("0x2"), 0);
+        let op_code = OutPoint::new_cell(h256!("0x2"), 1);
+        let op_group = OutPoint::new_cell(h256!("0x3"), 0);
+        let op_plain = OutPoint::new_cell(h256!("0x4"), 0);
+
+        let group_output = CellOutput::new_dep_group(
+            capacity_bytes!(1000),
+            &[op_data.clone(), op_code.clone()],
+            Script::default(),
+        );
+        for out_point in &[&op_data, &op_code, &op_plain] {
+            let mut cell_meta = generate_dummy_cell_meta();
+            cell_meta.out_point = out_point.cell.clone().unwrap();
+            cell_provider
+                .cells
+                .insert(out_point.cell.clone().unwrap(), Some(cell_meta));
+        }
+        let mut group_meta = CellMeta::from(&group_output);
+        group_meta.out_point = op_group.cell.clone().unwrap();
+        cell_provider
+            .cells
+            .insert(op_group.cell.clone().unwrap(), Some(group_meta));
+
+        let transaction = TransactionBuilder::default()
+            .dep(op_plain.clone())
+            .dep(op_group.clone())
+            .build();
+
+        let mut seen_inputs = FnvHashSet::default();
+        let result = resolve_transaction(
+            &transaction,
+            &mut seen_inputs,
+            &cell_provider,
+            &header_provider,
+        )
+        .unwrap();
+
+        let dep_out_points: Vec<CellOutPoint> = result
+            .resolved_deps
+            .iter()
+            .map(|dep| dep.cell().unwrap().out_point.clone())
+            .collect();
+        assert_eq!(
+            dep_out_points,
+            vec![
+                op_plain.cell.unwrap(),
+                op_data.cell.unwrap(),
+                op_code.cell.unwrap()
+            ]
+        );
+        assert_eq!(result.resolved_dep_groups.len(), 1);
+        assert_eq!(
+            result.resolved_dep_groups[0].cell().unwrap().out_point,
+            op_group.cell.unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_transaction_should_reject_invalid_dep_group() {
+        let mut cell_provider = CellMemoryDb::default();
+        let header_provider = HeaderMemoryDb::default();
+
+        let op_group = OutPoint::new_cell(h256!("0x3"), 0);
+        let group_output = CellOutput::new(
+            capacity_bytes!(1000),
+            Bytes::from(vec![1; 10]),
+            Script::default(),
+            Some(Script::new(vec![], DEP_GROUP_HASH)),
+        );
+        cell_provider.cells.insert(
+            op_group.cell.clone().unwrap(),
+            Some(CellMeta::from(&group_output)),
+        );
+
+        let transaction = TransactionBuilder::default().dep(op_group.clone()).build();
+
+        let mut seen_inputs = FnvHashSet::default();
+        let result = resolve_transaction(
+            &transaction,
+            &mut seen_inputs,
+            &cell_provider,
+            &header_provider,
+        );
+
+        assert_eq!(
+            result.err(),
+            Some(UnresolvableError::InvalidDepGroup(op_group))
+        );
+    }
+
+    #[test]
+    fn resolve_transaction_should_reject_duplicate_dep_group_members() {
+        let mut cell_provider = CellMemoryDb::default();
+        let header_provider = HeaderMemoryDb::default();
+
+        let op_code = OutPoint::new_cell(h256!("0x2"), 0);
+        let op_group = OutPoint::new_cell(h256!("0x3"), 0);
+        let group_output =
+            CellOutput::new_dep_group(capacity_bytes!(1000), &[op_code.clone()], Script::default());
+        let mut code_meta = generate_dummy_cell_meta();
+        code_meta.out_point = op_code.cell.clone().unwrap();
+        cell_provider
+            .cells
+            .insert(op_code.cell.clone().unwrap(), Some(code_meta));
+        cell_provider.cells.insert(
+            op_group.cell.clone().unwrap(),
+            Some(CellMeta::from(&group_output)),
+        );
+
+        // the member is also listed as a plain dep
+        let transaction = TransactionBuilder::default()
+            .dep(op_code.clone())
+            .dep(op_group.clone())
+            .build();
+
+        let mut seen_inputs = FnvHashSet::default();
+        let result = resolve_transaction(
+            &transaction,
+            &mut seen_inputs,
+            &cell_provider,
+            &header_provider,
+        );
+
+        assert_eq!(result.err(), Some(UnresolvableError::DuplicateDep(op_code)));
+    }
+
     #[test]
     fn resolve_transaction_should_reject_empty_out_point() {
         let mut cell_provider = CellMemoryDb::default();
@@ -1,4 +1,4 @@
-use crate::{BlockNumber, Capacity, EpochNumber};
+use crate::{BlockNumber, Capacity, Cycle, EpochNumber};
 use failure::Error as FailureError;
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
@@ -10,12 +10,23 @@ pub struct BlockExt {
     pub total_difficulty: U256,
     pub total_uncles_count: u64,
     pub txs_verified: Option<bool>,
+    /// Fees of the transactions except the cellbase, empty until they are verified
+    pub txs_fees: Vec<Capacity>,
+    /// Cycles of the transactions except the cellbase, `None` until their
+    /// scripts are run
+    pub txs_cycles: Option<Vec<Cycle>>,
 }
 
+/// Location of a main chain transaction, kept when the body of its block is
+/// pruned
 #[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
 pub struct TransactionAddress {
     // Block hash
     pub block_hash: H256,
+    pub block_number: BlockNumber,
+    pub block_epoch: EpochNumber,
+    // Index of the transaction in the block
+    pub index: usize,
     // Offset of block transaction in serialized bytes
     pub offset: usize,
     pub length: usize,
@@ -1,3 +1,5 @@
+use crate::transaction::CellInput;
+use bincode::serialize;
 use bytes::Bytes;
 use faster_hex::hex_encode;
 use hash::blake2b_256;
@@ -8,6 +10,13 @@ use std::fmt;
 use std::io::Write;
 
 pub const ALWAYS_SUCCESS_HASH: H256 = h256!("0x1");
+// Reserved code hash marking a cell as a dep group, this type script is
+// verified natively instead of being run in CKB VM.
+pub const DEP_GROUP_HASH: H256 = h256!("0x2");
+// Reserved code hash ("TYPE_ID" in ascii) of the type id type script, which
+// gives a cell an unique identity kept across upgrades. It is verified
+// natively instead of being run in CKB VM.
+pub const TYPE_ID_CODE_HASH: H256 = h256!("0x545950455f4944");
 
 // TODO: when flatbuffer work is done, remove Serialize/Deserialize here and
 // implement proper From trait
@@ -55,6 +64,22 @@ impl Script {
         Self::new(vec![], ALWAYS_SUCCESS_HASH)
     }
 
+    /// Type id script of the cell created at `output_index` in a transaction
+    /// whose first input is `first_input`. Since an input can only be spent
+    /// once, the id can never be created twice.
+    pub fn type_id(first_input: &CellInput, output_index: u64) -> Self {
+        let mut bytes = serialize(first_input).expect("CellInput serialize should not fail");
+        bytes
+            .write_all(&output_index.to_le_bytes())
+            .expect(VEC_WRITE_ALL_EXPECT);
+        let id: H256 = blake2b_256(bytes).into();
+        Self::new(vec![Bytes::from(id.as_bytes())], TYPE_ID_CODE_HASH)
+    }
+
+    pub fn is_type_id(&self) -> bool {
+        self.code_hash == TYPE_ID_CODE_HASH
+    }
+
     pub fn destruct(self) -> ScriptTuple {
         let Script { args, code_hash } = self;
         (args, code_hash)
@@ -88,7 +113,8 @@ impl Script {
 
 #[cfg(test)]
 mod tests {
-    use super::{h256, Script, H256};
+    use super::{h256, Script, H256, TYPE_ID_CODE_HASH};
+    use crate::transaction::{CellInput, OutPoint};
     use crate::Bytes;
     use hash::blake2b_256;
     use occupied_capacity::OccupiedCapacity;
@@ -149,4 +175,19 @@ mod tests {
             expect_occupied_capacity
         );
     }
+
+    #[test]
+    fn type_id_script() {
+        let input = CellInput::new(OutPoint::new_cell(h256!("0x1"), 0), 0, vec![]);
+        let other_input = CellInput::new(OutPoint::new_cell(h256!("0x1"), 1), 0, vec![]);
+
+        let script = Script::type_id(&input, 0);
+        assert!(script.is_type_id());
+        assert_eq!(script.code_hash, TYPE_ID_CODE_HASH);
+        assert_eq!(script.args.len(), 1);
+        assert_eq!(script.args[0].len(), 32);
+        assert_eq!(script, Script::type_id(&input, 0));
+        assert_ne!(script, Script::type_id(&input, 1));
+        assert_ne!(script, Script::type_id(&other_input, 0));
+    }
 }
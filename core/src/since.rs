@@ -0,0 +1,97 @@
+//! The `since` field of a cell input, see RFC 0017.
+//!
+//! ```text
+//! bit 63     : 0 absolute lock, 1 relative lock
+//! bit 61..=62: metric type, 00 block number, 01 epoch number, 10 timestamp
+//! bit 56..=60: reserved, must be zero
+//! bit 0..=55 : value, timestamps are in seconds
+//! ```
+
+use crate::{BlockNumber, EpochNumber};
+
+pub const LOCK_TYPE_FLAG: u64 = 1 << 63;
+pub const METRIC_TYPE_FLAG_MASK: u64 = 0x6000_0000_0000_0000;
+pub const BLOCK_NUMBER_METRIC_FLAG: u64 = 0x0000_0000_0000_0000;
+pub const EPOCH_NUMBER_METRIC_FLAG: u64 = 0x2000_0000_0000_0000;
+pub const TIMESTAMP_METRIC_FLAG: u64 = 0x4000_0000_0000_0000;
+pub const REMAIN_FLAGS_BITS: u64 = 0x1f00_0000_0000_0000;
+pub const VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SinceMetric {
+    BlockNumber(BlockNumber),
+    EpochNumber(EpochNumber),
+    /// Timestamp in milliseconds, the same unit as header timestamps
+    Timestamp(u64),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Since(pub u64);
+
+impl Since {
+    pub fn new(relative: bool, metric: SinceMetric) -> Self {
+        let lock_type = if relative { LOCK_TYPE_FLAG } else { 0 };
+        let (metric_flag, value) = match metric {
+            SinceMetric::BlockNumber(number) => (BLOCK_NUMBER_METRIC_FLAG, number),
+            SinceMetric::EpochNumber(number) => (EPOCH_NUMBER_METRIC_FLAG, number),
+            SinceMetric::Timestamp(timestamp) => (TIMESTAMP_METRIC_FLAG, timestamp / 1000),
+        };
+        Since(lock_type | metric_flag | (value & VALUE_MASK))
+    }
+
+    pub fn is_absolute(self) -> bool {
+        self.0 & LOCK_TYPE_FLAG == 0
+    }
+
+    #[inline]
+    pub fn is_relative(self) -> bool {
+        !self.is_absolute()
+    }
+
+    /// Returns false if any reserved bit is set or the metric type is unknown
+    pub fn flags_is_valid(self) -> bool {
+        (self.0 & REMAIN_FLAGS_BITS == 0)
+            && ((self.0 & METRIC_TYPE_FLAG_MASK) != METRIC_TYPE_FLAG_MASK)
+    }
+
+    pub fn extract_metric(self) -> Option<SinceMetric> {
+        let value = self.0 & VALUE_MASK;
+        match self.0 & METRIC_TYPE_FLAG_MASK {
+            BLOCK_NUMBER_METRIC_FLAG => Some(SinceMetric::BlockNumber(value)),
+            EPOCH_NUMBER_METRIC_FLAG => Some(SinceMetric::EpochNumber(value)),
+            TIMESTAMP_METRIC_FLAG => Some(SinceMetric::Timestamp(value * 1000)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_layout() {
+        let since = Since::new(false, SinceMetric::BlockNumber(10));
+        assert_eq!(since.0, 10);
+        assert!(since.is_absolute());
+        assert_eq!(since.extract_metric(), Some(SinceMetric::BlockNumber(10)));
+
+        let since = Since::new(true, SinceMetric::EpochNumber(2));
+        assert_eq!(since.0, 0xa000_0000_0000_0002);
+        assert!(since.is_relative());
+        assert_eq!(since.extract_metric(), Some(SinceMetric::EpochNumber(2)));
+
+        let since = Since::new(false, SinceMetric::Timestamp(5_000));
+        assert_eq!(since.0, 0x4000_0000_0000_0005);
+        assert_eq!(since.extract_metric(), Some(SinceMetric::Timestamp(5_000)));
+    }
+
+    #[test]
+    fn test_since_invalid_flags() {
+        assert!(Since(0x8000_0000_0000_0001).flags_is_valid());
+        assert!(!Since(0x0100_0000_0000_0001).flags_is_valid());
+        // metric type 11 is not defined
+        assert!(!Since(0x6000_0000_0000_0001).flags_is_valid());
+        assert_eq!(Since(0x6000_0000_0000_0001).extract_metric(), None);
+    }
+}
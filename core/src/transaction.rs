@@ -1,6 +1,6 @@
 //! Transaction using Cell.
 //! It is similar to Bitcoin Tx <https://en.bitcoin.it/wiki/Protocol_documentation#tx/>
-use crate::script::Script;
+use crate::script::{Script, DEP_GROUP_HASH};
 pub use crate::Capacity;
 use crate::{BlockNumber, Version};
 use bincode::{deserialize, serialize};
@@ -182,6 +182,16 @@ impl CellOutput {
         }
     }
 
+    pub fn new_dep_group(capacity: Capacity, out_points: &[OutPoint], lock: Script) -> Self {
+        let data = serialize(out_points).expect("OutPoints serialize should not fail");
+        CellOutput::new(
+            capacity,
+            Bytes::from(data),
+            lock,
+            Some(Script::new(vec![], DEP_GROUP_HASH)),
+        )
+    }
+
     pub fn data_hash(&self) -> H256 {
         blake2b_256(&self.data).into()
     }
@@ -207,6 +217,29 @@ impl CellOutput {
         (capacity, data, lock, type_)
     }
 
+    pub fn is_dep_group(&self) -> bool {
+        self.type_
+            .as_ref()
+            .map(|type_| type_.code_hash == DEP_GROUP_HASH)
+            .unwrap_or(false)
+    }
+
+    // A dep group stores a serialized vector of OutPoints as its data, each
+    // of them must reference a cell. Returns None for malformed group data.
+    pub fn dep_group_out_points(&self) -> Option<Vec<OutPoint>> {
+        let out_points: Vec<OutPoint> = deserialize(&self.data).ok()?;
+        let canonical = serialize(&out_points)
+            .map(|bytes| bytes.len() == self.data.len())
+            .unwrap_or(false);
+        if !canonical
+            || out_points.is_empty()
+            || out_points.iter().any(|out_point| out_point.cell.is_none())
+        {
+            return None;
+        }
+        Some(out_points)
+    }
+
     pub fn is_occupied_capacity_overflow(&self) -> bool {
         if let Ok(cap) = self.occupied_capacity() {
             return cap > self.capacity;
@@ -0,0 +1,164 @@
+#[cfg(feature = "rocksdb")]
+use crate::rocksdb::RocksdbBatch;
+#[cfg(feature = "sled")]
+use crate::sleddb::SledBatch;
+use crate::version::{VERSION_KEY, VERSION_VALUE};
+#[cfg(feature = "rocksdb")]
+use crate::RocksDB;
+#[cfg(feature = "sled")]
+use crate::SledDB;
+use crate::{
+    Col, ColumnStats, DBBackend, DBConfig, DbBatch, Direction, Error, IterableKeyValueDB,
+    KeyValueDB, MaintenanceKeyValueDB, Result,
+};
+use std::ops::Range;
+use std::path::Path;
+
+/// The db of the backend selected by `DBConfig::backend`, among the ones
+/// enabled by the features of the crate
+pub enum BackendDB {
+    #[cfg(feature = "rocksdb")]
+    RocksDB(RocksDB),
+    #[cfg(feature = "sled")]
+    Sled(SledDB),
+}
+
+impl BackendDB {
+    /// Opens the db, failing when its version is not matched or another
+    /// process, such as a running node, holds it
+    pub fn open_with_check(config: &DBConfig, columns: u32) -> Result<Self> {
+        match config.backend {
+            #[cfg(feature = "rocksdb")]
+            DBBackend::RocksDB => {
+                RocksDB::open_with_check(config, columns, VERSION_KEY, VERSION_VALUE)
+                    .map(BackendDB::RocksDB)
+            }
+            #[cfg(feature = "sled")]
+            DBBackend::Sled => {
+                SledDB::open_with_check(config, VERSION_KEY, VERSION_VALUE).map(BackendDB::Sled)
+            }
+            #[allow(unreachable_patterns)]
+            backend => Err(Error::DBError(format!(
+                "the {:?} backend is not enabled in this build",
+                backend
+            ))),
+        }
+    }
+
+    // TODO Change `panic(...)` to `Result<...>`
+    pub fn open(config: &DBConfig, columns: u32) -> Self {
+        Self::open_with_check(config, columns).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl KeyValueDB for BackendDB {
+    type Batch = BackendBatch;
+
+    fn read(&self, col: Col, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.read(col, key),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.read(col, key),
+        }
+    }
+
+    fn partial_read(&self, col: Col, key: &[u8], range: &Range<usize>) -> Result<Option<Vec<u8>>> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.partial_read(col, key, range),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.partial_read(col, key, range),
+        }
+    }
+
+    fn batch(&self) -> Result<Self::Batch> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.batch().map(BackendBatch::RocksDB),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.batch().map(BackendBatch::Sled),
+        }
+    }
+
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.checkpoint(path),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.checkpoint(path),
+        }
+    }
+}
+
+impl IterableKeyValueDB for BackendDB {
+    fn iter<'a>(
+        &'a self,
+        col: Col,
+        from_key: &[u8],
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.iter(col, from_key, direction),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.iter(col, from_key, direction),
+        }
+    }
+}
+
+impl MaintenanceKeyValueDB for BackendDB {
+    fn compact(&self, col: Col) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.compact(col),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.compact(col),
+        }
+    }
+
+    fn column_stats(&self, col: Col) -> Result<ColumnStats> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendDB::RocksDB(db) => db.column_stats(col),
+            #[cfg(feature = "sled")]
+            BackendDB::Sled(db) => db.column_stats(col),
+        }
+    }
+}
+
+pub enum BackendBatch {
+    #[cfg(feature = "rocksdb")]
+    RocksDB(RocksdbBatch),
+    #[cfg(feature = "sled")]
+    Sled(SledBatch),
+}
+
+impl DbBatch for BackendBatch {
+    fn insert(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendBatch::RocksDB(batch) => batch.insert(col, key, value),
+            #[cfg(feature = "sled")]
+            BackendBatch::Sled(batch) => batch.insert(col, key, value),
+        }
+    }
+
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendBatch::RocksDB(batch) => batch.delete(col, key),
+            #[cfg(feature = "sled")]
+            BackendBatch::Sled(batch) => batch.delete(col, key),
+        }
+    }
+
+    fn commit(self) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            BackendBatch::RocksDB(batch) => batch.commit(),
+            #[cfg(feature = "sled")]
+            BackendBatch::Sled(batch) => batch.commit(),
+        }
+    }
+}
@@ -1,13 +1,44 @@
-use crate::{Col, DbBatch, KeyValueDB, Result};
+use crate::{CacheStats, Col, DbBatch, Direction, IterableKeyValueDB, KeyValueDB, Result};
 use ckb_util::Mutex;
 use fnv::FnvHashMap;
 use lru_cache::LruCache;
 use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-type CacheTable = FnvHashMap<Col, Mutex<LruCache<Vec<u8>, Vec<u8>>>>;
+type CacheTable = FnvHashMap<Col, Cache>;
 pub type CacheCols = (u32, usize);
 
+struct Cache {
+    entries: Mutex<LruCache<Vec<u8>, Vec<u8>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Cache {
+    fn new(capacity: usize) -> Self {
+        Cache {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn lookup<F, V>(&self, key: &[u8], f: F) -> Option<V>
+    where
+        F: FnOnce(&Vec<u8>) -> V,
+    {
+        let found = self.entries.lock().get_refresh(key).map(|value| f(value));
+        if found.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        found
+    }
+}
+
 enum BatchOperation {
     Insert {
         col: Col,
@@ -69,14 +100,12 @@ impl<T: DbBatch> DbBatch for CacheDBBatch<T> {
             match op {
                 BatchOperation::Insert { col, key, value } => {
                     if let Some(cache) = self.cache.get(&col) {
-                        let mut cache_guard = cache.lock();
-                        cache_guard.insert(key, value);
+                        cache.entries.lock().insert(key, value);
                     }
                 }
                 BatchOperation::Delete { col, key } => {
                     if let Some(cache) = self.cache.get(&col) {
-                        let mut cache_guard = cache.lock();
-                        cache_guard.remove(&key);
+                        cache.entries.lock().remove(&key);
                     }
                 }
             }
@@ -97,10 +126,12 @@ impl<T> CacheDB<T>
 where
     T: KeyValueDB,
 {
+    /// Caches the columns in LRU caches of the capacities, the columns of
+    /// capacity 0 are not cached
     pub fn new(db: T, cols: &[CacheCols]) -> Self {
         let mut table = FnvHashMap::with_capacity_and_hasher(cols.len(), Default::default());
-        for (idx, capacity) in cols {
-            table.insert(*idx, Mutex::new(LruCache::new(*capacity)));
+        for (idx, capacity) in cols.iter().filter(|(_, capacity)| *capacity > 0) {
+            table.insert(*idx, Cache::new(*capacity));
         }
         CacheDB {
             db,
@@ -119,7 +150,7 @@ where
         if let Some(value) = self
             .cache
             .get(&col)
-            .and_then(|cache| cache.lock().get_refresh(key).cloned())
+            .and_then(|cache| cache.lookup(key, Clone::clone))
         {
             return Ok(Some(value));
         }
@@ -127,11 +158,12 @@ where
     }
 
     fn partial_read(&self, col: Col, key: &[u8], range: &Range<usize>) -> Result<Option<Vec<u8>>> {
-        if let Some(cache) = self.cache.get(&col) {
-            let mut cache_guard = cache.lock();
-            if let Some(data) = cache_guard.get_refresh(key) {
-                return Ok(data.get(range.start..range.end).map(|slice| slice.to_vec()));
-            }
+        if let Some(data) = self.cache.get(&col).and_then(|cache| {
+            cache.lookup(key, |data| {
+                data.get(range.start..range.end).map(|slice| slice.to_vec())
+            })
+        }) {
+            return Ok(data);
         }
         self.db.partial_read(col, key, range)
     }
@@ -139,4 +171,65 @@ where
     fn batch(&self) -> Result<Self::Batch> {
         Ok(CacheDBBatch::new(self.db.batch()?, Arc::clone(&self.cache)))
     }
+
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        self.db.checkpoint(path)
+    }
+
+    fn cache_stats(&self, col: Col) -> Option<CacheStats> {
+        self.cache.get(&col).map(|cache| {
+            let entries = cache.entries.lock();
+            CacheStats {
+                capacity: entries.capacity(),
+                size: entries.len(),
+                hits: cache.hits.load(Ordering::Relaxed),
+                misses: cache.misses.load(Ordering::Relaxed),
+            }
+        })
+    }
+}
+
+// The batches write through the cache, the entries of the inner db are up to date
+impl<T> IterableKeyValueDB for CacheDB<T>
+where
+    T: IterableKeyValueDB,
+{
+    fn iter<'a>(
+        &'a self,
+        col: Col,
+        from_key: &[u8],
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>> {
+        self.db.iter(col, from_key, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryKeyValueDB;
+
+    #[test]
+    fn cache_stats() {
+        let db = CacheDB::new(MemoryKeyValueDB::open(2), &[(0, 2), (1, 0)]);
+        let mut batch = db.batch().unwrap();
+        batch.insert(0, &[0], &[0, 1, 2]).unwrap();
+        batch.insert(1, &[0], &[0]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(db.read(0, &[0]).unwrap(), Some(vec![0, 1, 2]));
+        assert_eq!(db.partial_read(0, &[0], &(1..2)).unwrap(), Some(vec![1]));
+        assert_eq!(db.read(0, &[1]).unwrap(), None);
+        assert_eq!(db.read(1, &[0]).unwrap(), Some(vec![0]));
+        assert_eq!(
+            db.cache_stats(0),
+            Some(CacheStats {
+                capacity: 2,
+                size: 1,
+                hits: 2,
+                misses: 1,
+            })
+        );
+        assert_eq!(db.cache_stats(1), None);
+    }
 }
@@ -1,10 +1,28 @@
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct DBConfig {
     #[serde(default)]
     pub path: PathBuf,
+    #[serde(default)]
+    pub backend: DBBackend,
     pub options: Option<HashMap<String, String>>,
 }
+
+/// The storage engine of the db, `options` only apply to RocksDB
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DBBackend {
+    RocksDB,
+    /// The pure Rust embedded store sled, for the platforms where RocksDB is
+    /// hard to build
+    Sled,
+}
+
+impl Default for DBBackend {
+    fn default() -> Self {
+        DBBackend::RocksDB
+    }
+}
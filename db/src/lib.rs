@@ -5,17 +5,30 @@
 
 use failure::Fail;
 use std::ops::Range;
+use std::path::Path;
 use std::result;
 
+pub mod backend;
 pub mod cachedb;
 pub mod config;
 pub mod memorydb;
+#[cfg(feature = "rocksdb")]
 pub mod rocksdb;
+#[cfg(feature = "sled")]
+pub mod sleddb;
+mod version;
 
+#[cfg(not(any(feature = "rocksdb", feature = "sled")))]
+compile_error!("enable the rocksdb or the sled feature of ckb-db");
+
+pub use crate::backend::BackendDB;
 pub use crate::cachedb::CacheDB;
-pub use crate::config::DBConfig;
+pub use crate::config::{DBBackend, DBConfig};
 pub use crate::memorydb::MemoryKeyValueDB;
+#[cfg(feature = "rocksdb")]
 pub use crate::rocksdb::RocksDB;
+#[cfg(feature = "sled")]
+pub use crate::sleddb::SledDB;
 
 pub type Col = u32;
 pub type Result<T> = result::Result<T, Error>;
@@ -26,11 +39,68 @@ pub enum Error {
     DBError(String),
 }
 
+/// Usage of the cache of a column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub capacity: usize,
+    pub size: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
 pub trait KeyValueDB: Sync + Send {
     type Batch: DbBatch;
     fn read(&self, col: Col, key: &[u8]) -> Result<Option<Vec<u8>>>;
     fn partial_read(&self, col: Col, key: &[u8], range: &Range<usize>) -> Result<Option<Vec<u8>>>;
     fn batch(&self) -> Result<Self::Batch>;
+    /// Usage of the cache of the column, None if it is not cached
+    fn cache_stats(&self, _col: Col) -> Option<CacheStats> {
+        None
+    }
+    /// Creates a point-in-time copy of the store in the directory, which must
+    /// not exist, while it keeps serving
+    fn checkpoint(&self, _path: &Path) -> Result<()> {
+        Err(Error::DBError(
+            "the db does not support checkpoints".to_owned(),
+        ))
+    }
+}
+
+/// Iteration direction of `IterableKeyValueDB::iter`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+/// Key-value store iterating the keys of a column in order
+pub trait IterableKeyValueDB: KeyValueDB {
+    /// Iterates the entries of `col` from `from_key`, included, in the order
+    /// of the keys or in the reverse order
+    fn iter<'a>(
+        &'a self,
+        col: Col,
+        from_key: &[u8],
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>>;
+}
+
+/// Storage used by a column
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnStats {
+    /// Estimated number of the keys
+    pub keys: u64,
+    /// Estimated bytes on the disk
+    pub size: u64,
+    /// Metrics of the storage engine, by name
+    pub properties: Vec<(String, String)>,
+}
+
+/// Key-value store whose storage can be inspected and reclaimed by the operator
+pub trait MaintenanceKeyValueDB: KeyValueDB {
+    /// Rewrites the storage of the column, dropping the deleted and overwritten entries
+    fn compact(&self, col: Col) -> Result<()>;
+    fn column_stats(&self, col: Col) -> Result<ColumnStats>;
 }
 
 pub trait DbBatch {
@@ -1,5 +1,5 @@
 // for unit test
-use crate::{Col, DbBatch, Error, KeyValueDB, Result};
+use crate::{Col, DbBatch, Direction, Error, IterableKeyValueDB, KeyValueDB, Result};
 use ckb_util::RwLock;
 use fnv::FnvHashMap;
 use std::ops::Range;
@@ -58,6 +58,36 @@ impl KeyValueDB for MemoryKeyValueDB {
     }
 }
 
+impl IterableKeyValueDB for MemoryKeyValueDB {
+    fn iter<'a>(
+        &'a self,
+        col: Col,
+        from_key: &[u8],
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>> {
+        let db = self.db.read();
+
+        match db.get(&col) {
+            None => Err(Error::DBError(format!("column {} not found ", col))),
+            Some(map) => {
+                let mut entries = map
+                    .iter()
+                    .filter(|(key, _)| match direction {
+                        Direction::Forward => key.as_slice() >= from_key,
+                        Direction::Reverse => key.as_slice() <= from_key,
+                    })
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| match direction {
+                    Direction::Forward => a.cmp(b),
+                    Direction::Reverse => b.cmp(a),
+                });
+                Ok(Box::new(entries.into_iter()))
+            }
+        }
+    }
+}
+
 pub struct MemoryDbBatch {
     operations: Vec<BatchOperation>,
     db: Arc<RwLock<MemoryTable>>,
@@ -153,4 +183,26 @@ mod tests {
             db.partial_read(0, &[0, 0], &(1..4)).unwrap()
         );
     }
+
+    #[test]
+    fn write_and_iter() {
+        let db = MemoryKeyValueDB::open(1);
+        let mut batch = db.batch().unwrap();
+        batch.insert(0, &[0, 1], &[1]).unwrap();
+        batch.insert(0, &[1, 1], &[2]).unwrap();
+        batch.insert(0, &[1, 2], &[3]).unwrap();
+        batch.insert(0, &[2, 1], &[4]).unwrap();
+        batch.commit().unwrap();
+
+        let values = |from_key: &[u8], direction| {
+            db.iter(0, from_key, direction)
+                .unwrap()
+                .map(|(_, value)| value[0])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&[1], Direction::Forward), vec![2, 3, 4]);
+        assert_eq!(values(&[1, 2], Direction::Forward), vec![3, 4]);
+        assert_eq!(values(&[1, 2], Direction::Reverse), vec![3, 2, 1]);
+        assert_eq!(values(&[2], Direction::Reverse), vec![3, 2, 1]);
+    }
 }
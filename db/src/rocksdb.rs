@@ -1,15 +1,28 @@
-use crate::{Col, DBConfig, DbBatch, Error, KeyValueDB, Result};
+use crate::version::{check_version, VERSION_KEY, VERSION_VALUE};
+use crate::{
+    Col, ColumnStats, DBConfig, DbBatch, Direction, Error, IterableKeyValueDB, KeyValueDB,
+    MaintenanceKeyValueDB, Result,
+};
 use log::{info, warn};
-use rocksdb::{ColumnFamily, Error as RdbError, Options, WriteBatch, DB};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{
+    ColumnFamily, Direction as RdbDirection, Error as RdbError, IteratorMode, Options, WriteBatch,
+    DB,
+};
 use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
 
-// If any data format in database was changed, we have to update this constant manually.
-//      - If the data can be migrated at startup automatically: update "x.y.z1" to "x.y.z2".
-//      - If the data can be migrated manually: update "x.y1.z" to "x.y2.0".
-//      - If the data can not be migrated: update "x1.y.z" to "x2.0.0".
-pub(crate) const VERSION_KEY: &str = "db-version";
-pub(crate) const VERSION_VALUE: &str = "0.2.0";
+// The properties dumped by `column_stats`, the deletions pending in the memtables
+// and the compaction debt tell whether a compaction is worth it
+const STATS_PROPERTIES: &[&str] = &[
+    "rocksdb.estimate-live-data-size",
+    "rocksdb.cur-size-all-mem-tables",
+    "rocksdb.num-entries-active-mem-table",
+    "rocksdb.num-deletes-active-mem-table",
+    "rocksdb.estimate-pending-compaction-bytes",
+    "rocksdb.num-live-versions",
+];
 
 pub struct RocksDB {
     inner: Arc<DB>,
@@ -78,26 +91,7 @@ impl RocksDB {
                 Error::DBError(format!("failed to check the version of database: {}", err))
             })?
             .ok_or_else(|| Error::DBError("version info about database is lost".to_owned()))?;
-        let version_str = unsafe { ::std::str::from_utf8_unchecked(&version_bytes) };
-        let version = semver::Version::parse(version_str)
-            .map_err(|err| Error::DBError(format!("database version is malformed: {}", err)))?;
-        let required_version = semver::Version::parse(ver_val).map_err(|err| {
-            Error::DBError(format!("required database version is malformed: {}", err))
-        })?;
-        if required_version.major != version.major
-            || required_version.minor != version.minor
-            || required_version.patch < version.patch
-        {
-            Err(Error::DBError(format!(
-                "the database version is not matched, require {} but it's {}",
-                required_version, version
-            )))?;
-        } else if required_version.patch > version.patch {
-            warn!(
-                "Migrating the data from {} to {} ...",
-                required_version, version
-            );
-            // Do data migration here.
+        if check_version(&version_bytes, ver_val)? {
             db.put(ver_key, ver_val).map_err(|err| {
                 Error::DBError(format!("Failed to update database version: {}", err))
             })?;
@@ -145,6 +139,64 @@ impl KeyValueDB for RocksDB {
             wb: WriteBatch::default(),
         })
     }
+
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        Checkpoint::new(&self.inner)?.create_checkpoint(path)?;
+        Ok(())
+    }
+}
+
+impl IterableKeyValueDB for RocksDB {
+    fn iter<'a>(
+        &'a self,
+        col: Col,
+        from_key: &[u8],
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>> {
+        let cf = cf_handle(&self.inner, col)?;
+        let direction = match direction {
+            Direction::Forward => RdbDirection::Forward,
+            Direction::Reverse => RdbDirection::Reverse,
+        };
+        self.inner
+            .iterator_cf(cf, IteratorMode::From(from_key, direction))
+            .map(|iter| {
+                Box::new(iter.map(|(key, value)| (key.to_vec(), value.to_vec())))
+                    as Box<dyn Iterator<Item = _>>
+            })
+            .map_err(Into::into)
+    }
+}
+
+impl MaintenanceKeyValueDB for RocksDB {
+    fn compact(&self, col: Col) -> Result<()> {
+        let cf = cf_handle(&self.inner, col)?;
+        self.inner
+            .compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>);
+        Ok(())
+    }
+
+    fn column_stats(&self, col: Col) -> Result<ColumnStats> {
+        let cf = cf_handle(&self.inner, col)?;
+        let int_property = |name| {
+            self.inner
+                .property_int_value_cf(cf, name)
+                .map(|value| value.unwrap_or(0))
+        };
+        let keys = int_property("rocksdb.estimate-num-keys")?;
+        let size = int_property("rocksdb.total-sst-files-size")?;
+        let mut properties = Vec::with_capacity(STATS_PROPERTIES.len());
+        for name in STATS_PROPERTIES {
+            if let Some(value) = self.inner.property_value_cf(cf, name)? {
+                properties.push((name.to_string(), value));
+            }
+        }
+        Ok(ColumnStats {
+            keys,
+            size,
+            properties,
+        })
+    }
 }
 
 pub struct RocksdbBatch {
@@ -215,6 +267,7 @@ mod tests {
                 opts.insert("disable_auto_compactions".to_owned(), "true".to_owned());
                 opts
             }),
+            ..Default::default()
         };
         RocksDB::open(&config, 2); // no panic
     }
@@ -233,6 +286,7 @@ mod tests {
                 opts.insert("letsrock".to_owned(), "true".to_owned());
                 opts
             }),
+            ..Default::default()
         };
         RocksDB::open(&config, 2); // panic
     }
@@ -253,6 +307,55 @@ mod tests {
         assert_eq!(Some(vec![1, 1, 1]), db.read(1, &[1, 1]).unwrap());
     }
 
+    #[test]
+    fn checkpoint() {
+        let db = setup_db("checkpoint", 2);
+        let mut batch = db.batch().unwrap();
+        batch.insert(1, &[1, 1], &[1, 1, 1]).unwrap();
+        batch.commit().unwrap();
+
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("checkpoint_target")
+            .tempdir()
+            .unwrap();
+        let path = tmp_dir.path().join("db");
+        db.checkpoint(&path).unwrap();
+        // the checkpoint is not affected by the later writes
+        let mut batch = db.batch().unwrap();
+        batch.insert(1, &[2, 2], &[2, 2, 2]).unwrap();
+        batch.commit().unwrap();
+
+        let config = DBConfig {
+            path,
+            ..Default::default()
+        };
+        let copy = RocksDB::open(&config, 2);
+        assert_eq!(Some(vec![1, 1, 1]), copy.read(1, &[1, 1]).unwrap());
+        assert_eq!(None, copy.read(1, &[2, 2]).unwrap());
+    }
+
+    #[test]
+    fn compact_and_stats() {
+        let db = setup_db("compact_and_stats", 2);
+        let mut batch = db.batch().unwrap();
+        for key in 0..4u8 {
+            batch.insert(1, &[key], &[key; 100]).unwrap();
+        }
+        batch.commit().unwrap();
+        let mut batch = db.batch().unwrap();
+        batch.delete(1, &[0]).unwrap();
+        batch.delete(1, &[1]).unwrap();
+        batch.commit().unwrap();
+
+        db.compact(1).unwrap();
+        let stats = db.column_stats(1).unwrap();
+        assert_eq!(stats.keys, 2);
+        assert!(stats.size > 0);
+        assert!(!stats.properties.is_empty());
+        assert_eq!(db.column_stats(0).unwrap().keys, 0);
+        assert!(db.compact(2).is_err());
+    }
+
     #[test]
     fn write_and_partial_read() {
         let db = setup_db("write_and_partial_read", 2);
@@ -278,6 +381,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_and_iter() {
+        let db = setup_db("write_and_iter", 1);
+
+        let mut batch = db.batch().unwrap();
+        batch.insert(0, &[0, 1], &[1]).unwrap();
+        batch.insert(0, &[1, 1], &[2]).unwrap();
+        batch.insert(0, &[1, 2], &[3]).unwrap();
+        batch.insert(0, &[2, 1], &[4]).unwrap();
+        batch.commit().unwrap();
+
+        let values = |from_key: &[u8], direction| {
+            db.iter(0, from_key, direction)
+                .unwrap()
+                .map(|(_, value)| value[0])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&[1], Direction::Forward), vec![2, 3, 4]);
+        assert_eq!(values(&[1, 2], Direction::Forward), vec![3, 4]);
+        assert_eq!(values(&[1, 2], Direction::Reverse), vec![3, 2, 1]);
+        assert_eq!(values(&[2], Direction::Reverse), vec![3, 2, 1]);
+    }
+
     #[test]
     #[should_panic]
     fn test_version_is_not_matched() {
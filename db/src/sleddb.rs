@@ -0,0 +1,235 @@
+use crate::version::{check_version, VERSION_KEY, VERSION_VALUE};
+use crate::{
+    Col, ColumnStats, DBConfig, DbBatch, Direction, Error, IterableKeyValueDB, KeyValueDB,
+    MaintenanceKeyValueDB, Result,
+};
+use log::info;
+use sled::{Batch, Db, IVec};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+// The columns share one tree, the keys are prefixed by their column. The version
+// key is not prefixed, its first bytes are beyond the columns of the store.
+const COLUMN_PREFIX_LEN: usize = 4;
+
+/// Pure Rust embedded store, for the platforms where RocksDB is hard to build
+pub struct SledDB {
+    inner: Arc<Db>,
+}
+
+impl SledDB {
+    pub(crate) fn open_with_check(config: &DBConfig, ver_key: &str, ver_val: &str) -> Result<Self> {
+        let db = sled::open(&config.path)
+            .map_err(|err| Error::DBError(format!("failed to open the database: {}", err)))?;
+        match db.get(ver_key)? {
+            Some(version) => {
+                if check_version(&version, ver_val)? {
+                    db.insert(ver_key, ver_val.as_bytes())?;
+                    db.flush()?;
+                }
+            }
+            None => {
+                if db.iter().next().is_some() {
+                    Err(Error::DBError(
+                        "version info about database is lost".to_owned(),
+                    ))?;
+                }
+                info!("Initialize a new database");
+                db.insert(ver_key, ver_val.as_bytes())?;
+                db.flush()?;
+            }
+        }
+        Ok(SledDB {
+            inner: Arc::new(db),
+        })
+    }
+
+    // TODO Change `panic(...)` to `Result<...>`
+    pub fn open(config: &DBConfig) -> Self {
+        Self::open_with_check(config, VERSION_KEY, VERSION_VALUE)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+fn column_key(col: Col, key: &[u8]) -> Vec<u8> {
+    let mut column_key = Vec::with_capacity(COLUMN_PREFIX_LEN + key.len());
+    column_key.extend_from_slice(&col.to_be_bytes());
+    column_key.extend_from_slice(key);
+    column_key
+}
+
+impl KeyValueDB for SledDB {
+    type Batch = SledBatch;
+
+    fn read(&self, col: Col, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.inner
+            .get(column_key(col, key))
+            .map(|v| v.map(|vi| vi.to_vec()))
+            .map_err(Into::into)
+    }
+
+    fn partial_read(&self, col: Col, key: &[u8], range: &Range<usize>) -> Result<Option<Vec<u8>>> {
+        self.inner
+            .get(column_key(col, key))
+            .map(|v| v.and_then(|vi| vi.get(range.start..range.end).map(|slice| slice.to_vec())))
+            .map_err(Into::into)
+    }
+
+    fn batch(&self) -> Result<Self::Batch> {
+        Ok(Self::Batch {
+            db: Arc::clone(&self.inner),
+            batch: Batch::default(),
+        })
+    }
+
+    fn checkpoint(&self, _path: &Path) -> Result<()> {
+        Err(Error::DBError(
+            "the sled backend does not support checkpoints".to_owned(),
+        ))
+    }
+}
+
+impl IterableKeyValueDB for SledDB {
+    fn iter<'a>(
+        &'a self,
+        col: Col,
+        from_key: &[u8],
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>> {
+        let prefix = col.to_be_bytes();
+        let from = column_key(col, from_key);
+        let strip = move |entry: sled::Result<(IVec, IVec)>| {
+            let (key, value) = entry.expect("db operation should be ok");
+            (key[COLUMN_PREFIX_LEN..].to_vec(), value.to_vec())
+        };
+        let iter: Box<dyn Iterator<Item = _>> = match direction {
+            Direction::Forward => Box::new(
+                self.inner
+                    .range(from..)
+                    .take_while(move |entry| match entry {
+                        Ok((key, _)) => key.starts_with(&prefix),
+                        Err(_) => true,
+                    })
+                    .map(strip),
+            ),
+            Direction::Reverse => {
+                Box::new(self.inner.range(prefix.to_vec()..=from).rev().map(strip))
+            }
+        };
+        Ok(iter)
+    }
+}
+
+// sled reclaims the space of the deleted entries by itself, the stats are
+// counted by a scan of the column
+impl MaintenanceKeyValueDB for SledDB {
+    fn compact(&self, _col: Col) -> Result<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    fn column_stats(&self, col: Col) -> Result<ColumnStats> {
+        let mut stats = ColumnStats::default();
+        for (key, value) in self.iter(col, &[], Direction::Forward)? {
+            stats.keys += 1;
+            stats.size += (COLUMN_PREFIX_LEN + key.len() + value.len()) as u64;
+        }
+        Ok(stats)
+    }
+}
+
+pub struct SledBatch {
+    db: Arc<Db>,
+    batch: Batch,
+}
+
+impl DbBatch for SledBatch {
+    fn insert(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        self.batch.insert(column_key(col, key), value);
+        Ok(())
+    }
+
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        self.batch.remove(column_key(col, key));
+        Ok(())
+    }
+
+    fn commit(self) -> Result<()> {
+        self.db.apply_batch(self.batch)?;
+        Ok(())
+    }
+}
+
+impl From<sled::Error> for Error {
+    fn from(err: sled::Error) -> Error {
+        Error::DBError(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile;
+
+    fn setup_db(prefix: &str) -> SledDB {
+        let tmp_dir = tempfile::Builder::new().prefix(prefix).tempdir().unwrap();
+        let config = DBConfig {
+            path: tmp_dir.as_ref().to_path_buf(),
+            ..Default::default()
+        };
+
+        SledDB::open(&config)
+    }
+
+    #[test]
+    fn write_and_read() {
+        let db = setup_db("sled_write_and_read");
+        let mut batch = db.batch().unwrap();
+        batch.insert(0, &[0, 0], &[0, 0, 0]).unwrap();
+        batch.insert(1, &[0, 0], &[1, 1, 1]).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(Some(vec![0, 0, 0]), db.read(0, &[0, 0]).unwrap());
+        assert_eq!(Some(vec![1, 1, 1]), db.read(1, &[0, 0]).unwrap());
+        assert_eq!(Some(vec![1]), db.partial_read(1, &[0, 0], &(1..2)).unwrap());
+        assert_eq!(None, db.read(2, &[0, 0]).unwrap());
+
+        let mut batch = db.batch().unwrap();
+        batch.delete(0, &[0, 0]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(None, db.read(0, &[0, 0]).unwrap());
+    }
+
+    #[test]
+    fn iter_column() {
+        let db = setup_db("sled_iter_column");
+        let mut batch = db.batch().unwrap();
+        for key in 0..3u8 {
+            batch.insert(0, &[key], &[key]).unwrap();
+            batch.insert(1, &[key], &[key + 10]).unwrap();
+        }
+        batch.commit().unwrap();
+
+        let forward: Vec<_> = db.iter(1, &[1], Direction::Forward).unwrap().collect();
+        assert_eq!(forward, vec![(vec![1], vec![11]), (vec![2], vec![12])]);
+        let reverse: Vec<_> = db.iter(1, &[1], Direction::Reverse).unwrap().collect();
+        assert_eq!(reverse, vec![(vec![1], vec![11]), (vec![0], vec![10])]);
+        assert_eq!(db.iter(0, &[], Direction::Forward).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn column_stats() {
+        let db = setup_db("sled_column_stats");
+        let mut batch = db.batch().unwrap();
+        batch.insert(1, &[0], &[0, 0]).unwrap();
+        batch.insert(1, &[1], &[1, 1]).unwrap();
+        batch.commit().unwrap();
+
+        db.compact(1).unwrap();
+        let stats = db.column_stats(1).unwrap();
+        assert_eq!(stats.keys, 2);
+        assert_eq!(stats.size, 14);
+        assert_eq!(db.column_stats(0).unwrap(), ColumnStats::default());
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Error, Result};
+use log::warn;
+
+// If any data format in database was changed, we have to update this constant manually.
+//      - If the data can be migrated at startup automatically: update "x.y.z1" to "x.y.z2".
+//      - If the data can be migrated manually: update "x.y1.z" to "x.y2.0".
+//      - If the data can not be migrated: update "x1.y.z" to "x2.0.0".
+pub(crate) const VERSION_KEY: &str = "db-version";
+pub(crate) const VERSION_VALUE: &str = "0.8.0";
+
+/// Checks the version stored in the db against the required one, true when
+/// the data is migrated at startup and the stored version must be updated
+pub(crate) fn check_version(stored: &[u8], required: &str) -> Result<bool> {
+    let version = std::str::from_utf8(stored)
+        .map_err(|err| err.to_string())
+        .and_then(|version| semver::Version::parse(version).map_err(|err| err.to_string()))
+        .map_err(|err| Error::DBError(format!("database version is malformed: {}", err)))?;
+    let required_version = semver::Version::parse(required).map_err(|err| {
+        Error::DBError(format!("required database version is malformed: {}", err))
+    })?;
+    if required_version.major != version.major
+        || required_version.minor != version.minor
+        || required_version.patch < version.patch
+    {
+        Err(Error::DBError(format!(
+            "the database version is not matched, require {} but it's {}",
+            required_version, version
+        )))?;
+    }
+    if required_version.patch > version.patch {
+        warn!(
+            "Migrating the data from {} to {} ...",
+            version, required_version
+        );
+        // Do data migration here.
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        assert_eq!(check_version(b"0.7.0", "0.7.0"), Ok(false));
+        assert_eq!(check_version(b"0.7.0", "0.7.1"), Ok(true));
+        assert!(check_version(b"0.7.1", "0.7.0").is_err());
+        assert!(check_version(b"0.6.0", "0.7.0").is_err());
+        assert!(check_version(b"1.7.0", "0.7.0").is_err());
+        assert!(check_version(b"0.7", "0.7.0").is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! # The Indexer Library
+//!
+//! Indexes the live cells and the transactions of the lock hashes registered
+//! by the wallets, in a database of its own.
+
+mod store;
+mod types;
+
+pub use crate::store::IndexerStore;
+pub use crate::types::{
+    CellTransaction, LiveCell, LockHashIndex, LockHashIndexState, TransactionPoint,
+};
+
+use ckb_db::Col;
+
+pub const COLUMNS: u32 = 4;
+pub const COLUMN_LOCK_HASH_INDEX_STATE: Col = 0;
+pub const COLUMN_LOCK_HASH_LIVE_CELL: Col = 1;
+pub const COLUMN_LOCK_HASH_TRANSACTION: Col = 2;
+pub const COLUMN_OUT_POINT_LOCK_HASH: Col = 3;
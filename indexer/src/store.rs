@@ -0,0 +1,829 @@
+use crate::types::{
+    CellTransaction, LiveCell, LockHashCellOutput, LockHashIndex, LockHashIndexState,
+    TransactionPoint,
+};
+use crate::{
+    COLUMNS, COLUMN_LOCK_HASH_INDEX_STATE, COLUMN_LOCK_HASH_LIVE_CELL,
+    COLUMN_LOCK_HASH_TRANSACTION, COLUMN_OUT_POINT_LOCK_HASH,
+};
+use bincode::{deserialize, serialize};
+use ckb_core::block::Block;
+use ckb_core::transaction::{CellOutPoint, CellOutput};
+use ckb_core::BlockNumber;
+use ckb_db::{Col, DBConfig, DbBatch, Direction, IterableKeyValueDB, KeyValueDB, RocksDB};
+use ckb_notify::NotifyController;
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_traits::ChainProvider;
+use ckb_util::Mutex;
+use crossbeam_channel::{select, Receiver, Sender};
+use fnv::{FnvHashMap, FnvHashSet};
+use log::{debug, error};
+use numext_fixed_hash::H256;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Blocks indexed by a round of synchronization, the rounds go on until the
+/// lock hashes reach the tip
+const SYNC_BLOCKS_PER_ROUND: u64 = 1000;
+const SUBSCRIBER_NAME: &str = "indexer";
+/// A stalled synchronization is retried after this interval, unless a new
+/// tip comes first
+const STALLED_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Outcome of a round of synchronization
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncProgress {
+    /// The index is at the tip
+    Synced,
+    /// More blocks are left to index, or the chain is reorganized during the
+    /// round, the next round goes on right away
+    Behind,
+    /// A block of the main chain is not stored yet, e.g. the tip moves under
+    /// the round, retrying right away would spin
+    Stalled,
+}
+
+/// Indexes the live cells and the transactions of the registered lock hashes,
+/// following the main chain in its own database
+pub struct IndexerStore<CS> {
+    db: Arc<RocksDB>,
+    shared: Shared<CS>,
+    // Serializes the writes, the synchronization and the registration of the
+    // lock hashes
+    sync_lock: Arc<Mutex<()>>,
+    sync_sender: Sender<()>,
+    sync_receiver: Receiver<()>,
+}
+
+impl<CS: ChainStore> Clone for IndexerStore<CS> {
+    fn clone(&self) -> Self {
+        IndexerStore {
+            db: Arc::clone(&self.db),
+            shared: self.shared.clone(),
+            sync_lock: Arc::clone(&self.sync_lock),
+            sync_sender: self.sync_sender.clone(),
+            sync_receiver: self.sync_receiver.clone(),
+        }
+    }
+}
+
+impl<CS: ChainStore + 'static> IndexerStore<CS> {
+    pub fn new(config: &DBConfig, shared: Shared<CS>) -> Self {
+        let (sync_sender, sync_receiver) = crossbeam_channel::bounded(1);
+        IndexerStore {
+            db: Arc::new(RocksDB::open(config, COLUMNS)),
+            shared,
+            sync_lock: Arc::new(Mutex::new(())),
+            sync_sender,
+            sync_receiver,
+        }
+    }
+
+    /// Synchronizes the index on every new tip
+    pub fn start<S: ToString>(&self, notify_controller: &NotifyController, thread_name: Option<S>) {
+        let new_tip_receiver = notify_controller.subscribe_new_tip(SUBSCRIBER_NAME);
+        let store = self.clone();
+
+        let mut thread_builder = thread::Builder::new();
+        if let Some(name) = thread_name {
+            thread_builder = thread_builder.name(name.to_string());
+        }
+        thread_builder
+            .spawn(move || {
+                let mut stalled = store.sync();
+                loop {
+                    select! {
+                        recv(new_tip_receiver) -> msg => match msg {
+                            Ok(_) => stalled = store.sync(),
+                            Err(_) => {
+                                error!(target: "indexer", "new tip channel is closed");
+                                break;
+                            }
+                        },
+                        recv(store.sync_receiver) -> _ => stalled = store.sync(),
+                        default(STALLED_RETRY_INTERVAL) => {
+                            if stalled {
+                                stalled = store.sync();
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("Start indexer failed");
+    }
+
+    // Returns true if the synchronization is stalled
+    fn sync(&self) -> bool {
+        match self.sync_index_states() {
+            SyncProgress::Synced => false,
+            SyncProgress::Behind => {
+                // Continues in another round, the registrations are not held
+                // back by a long synchronization
+                let _ = self.sync_sender.try_send(());
+                false
+            }
+            SyncProgress::Stalled => {
+                debug!(target: "indexer", "block to index is missing, retry later");
+                true
+            }
+        }
+    }
+
+    /// Live cells of the lock hash, by the order they are created
+    pub fn get_live_cells(
+        &self,
+        lock_hash: &H256,
+        skip: usize,
+        take: usize,
+        reverse_order: bool,
+    ) -> Vec<LiveCell> {
+        self.iter_lock_hash(COLUMN_LOCK_HASH_LIVE_CELL, lock_hash, reverse_order)
+            .skip(skip)
+            .take(take)
+            .map(|(key, value)| LiveCell {
+                created_by: LockHashIndex::from_slice(&key).transaction_point(),
+                cell_output: deserialize(&value).expect("deserialize CellOutput should be ok"),
+            })
+            .collect()
+    }
+
+    /// Cells of the lock hash, live or consumed, by the order they are created
+    pub fn get_transactions(
+        &self,
+        lock_hash: &H256,
+        skip: usize,
+        take: usize,
+        reverse_order: bool,
+    ) -> Vec<CellTransaction> {
+        self.iter_lock_hash(COLUMN_LOCK_HASH_TRANSACTION, lock_hash, reverse_order)
+            .skip(skip)
+            .take(take)
+            .map(|(key, value)| CellTransaction {
+                created_by: LockHashIndex::from_slice(&key).transaction_point(),
+                consumed_by: deserialize(&value)
+                    .expect("deserialize TransactionPoint should be ok"),
+            })
+            .collect()
+    }
+
+    pub fn get_lock_hash_index_states(&self) -> HashMap<H256, LockHashIndexState> {
+        self.db
+            .iter(COLUMN_LOCK_HASH_INDEX_STATE, &[], Direction::Forward)
+            .expect("db operation should be ok")
+            .map(|(key, value)| {
+                (
+                    H256::from_slice(&key).expect("lock hash"),
+                    deserialize(&value).expect("deserialize LockHashIndexState should be ok"),
+                )
+            })
+            .collect()
+    }
+
+    /// The highest block whose body is pruned, the blocks up to it can not be indexed
+    pub fn pruned_number(&self) -> Option<BlockNumber> {
+        self.shared.store().get_pruned_number()
+    }
+
+    /// Indexes the lock hash from the block `index_from`, or from the tip.
+    /// The past blocks are indexed in the background, `index_from` must be
+    /// above the `pruned_number`.
+    pub fn insert_lock_hash(
+        &self,
+        lock_hash: &H256,
+        index_from: Option<BlockNumber>,
+    ) -> LockHashIndexState {
+        let index_state = {
+            let _guard = self.sync_lock.lock();
+            if let Some(index_state) = self.get_lock_hash_index_state(lock_hash) {
+                return index_state;
+            }
+            let tip_number = self.tip_number();
+            let index_from = index_from.map(|number| number.min(tip_number + 1));
+            let mut batch = self.db.batch().expect("db operation should be ok");
+            let index_state = match index_from {
+                Some(0) => {
+                    let genesis = self
+                        .shared
+                        .block(self.shared.genesis_hash())
+                        .expect("genesis block stored");
+                    let mut lock_hashes = FnvHashSet::default();
+                    lock_hashes.insert(lock_hash.to_owned());
+                    self.attach_block(&mut batch, &genesis, &lock_hashes);
+                    LockHashIndexState {
+                        block_number: 0,
+                        block_hash: genesis.header().hash().to_owned(),
+                    }
+                }
+                Some(number) => self.main_chain_state(number - 1),
+                None => self.main_chain_state(tip_number),
+            };
+            insert_serialize(
+                &mut batch,
+                COLUMN_LOCK_HASH_INDEX_STATE,
+                lock_hash.as_bytes(),
+                &index_state,
+            );
+            batch.commit().expect("commit should be ok");
+            index_state
+        };
+        let _ = self.sync_sender.try_send(());
+        index_state
+    }
+
+    /// Stops indexing the lock hash and drops its index
+    pub fn remove_lock_hash(&self, lock_hash: &H256) -> bool {
+        let _guard = self.sync_lock.lock();
+        if self.get_lock_hash_index_state(lock_hash).is_none() {
+            return false;
+        }
+        let mut batch = self.db.batch().expect("db operation should be ok");
+        batch
+            .delete(COLUMN_LOCK_HASH_INDEX_STATE, lock_hash.as_bytes())
+            .expect("batch delete should be ok");
+        for (key, _) in self.iter_lock_hash(COLUMN_LOCK_HASH_LIVE_CELL, lock_hash, false) {
+            batch
+                .delete(COLUMN_LOCK_HASH_LIVE_CELL, &key)
+                .expect("batch delete should be ok");
+        }
+        for (key, _) in self.iter_lock_hash(COLUMN_LOCK_HASH_TRANSACTION, lock_hash, false) {
+            let index = LockHashIndex::from_slice(&key);
+            batch
+                .delete(
+                    COLUMN_OUT_POINT_LOCK_HASH,
+                    &out_point_key(&index.cell_out_point),
+                )
+                .expect("batch delete should be ok");
+            batch
+                .delete(COLUMN_LOCK_HASH_TRANSACTION, &key)
+                .expect("batch delete should be ok");
+        }
+        batch.commit().expect("commit should be ok");
+        true
+    }
+
+    /// Detaches the indexed blocks left off the main chain, then attaches the
+    /// blocks of the main chain up to the tip
+    pub fn sync_index_states(&self) -> SyncProgress {
+        let _guard = self.sync_lock.lock();
+        let mut states = self.get_lock_hash_index_states();
+        if states.is_empty() {
+            return SyncProgress::Synced;
+        }
+
+        // The highest blocks are detached first, until every state is on the
+        // main chain
+        while let Some(state) = states
+            .values()
+            .filter(|state| !self.is_main_chain(state))
+            .max_by_key(|state| state.block_number)
+            .cloned()
+        {
+            let block = self
+                .shared
+                .block(&state.block_hash)
+                .expect("indexed block stored");
+            let lock_hashes = states
+                .iter()
+                .filter(|(_, s)| **s == state)
+                .map(|(lock_hash, _)| lock_hash.to_owned())
+                .collect::<FnvHashSet<_>>();
+            let parent_state = LockHashIndexState {
+                block_number: state.block_number - 1,
+                block_hash: block.header().parent_hash().to_owned(),
+            };
+            let mut batch = self.db.batch().expect("db operation should be ok");
+            self.detach_block(&mut batch, &block, &lock_hashes);
+            for lock_hash in lock_hashes {
+                insert_serialize(
+                    &mut batch,
+                    COLUMN_LOCK_HASH_INDEX_STATE,
+                    lock_hash.as_bytes(),
+                    &parent_state,
+                );
+                states.insert(lock_hash, parent_state.clone());
+            }
+            batch.commit().expect("commit should be ok");
+            debug!(target: "indexer", "detach block {:#x}", state.block_hash);
+        }
+
+        let tip_number = self.tip_number();
+        let from = states
+            .values()
+            .map(|state| state.block_number + 1)
+            .min()
+            .expect("not empty");
+        let to = tip_number.min(from + SYNC_BLOCKS_PER_ROUND - 1);
+        for number in from..=to {
+            let block = match self
+                .shared
+                .block_hash(number)
+                .and_then(|hash| self.shared.block(&hash))
+            {
+                Some(block) => block,
+                None => return SyncProgress::Stalled,
+            };
+            let lock_hashes = states
+                .iter()
+                .filter(|(_, state)| state.block_number == number - 1)
+                .map(|(lock_hash, state)| {
+                    // The chain is reorganized during the round
+                    if &state.block_hash != block.header().parent_hash() {
+                        None
+                    } else {
+                        Some(lock_hash.to_owned())
+                    }
+                })
+                .collect::<Option<FnvHashSet<_>>>();
+            let lock_hashes = match lock_hashes {
+                Some(lock_hashes) => lock_hashes,
+                None => return SyncProgress::Behind,
+            };
+            let state = LockHashIndexState {
+                block_number: number,
+                block_hash: block.header().hash().to_owned(),
+            };
+            let mut batch = self.db.batch().expect("db operation should be ok");
+            self.attach_block(&mut batch, &block, &lock_hashes);
+            for lock_hash in lock_hashes {
+                insert_serialize(
+                    &mut batch,
+                    COLUMN_LOCK_HASH_INDEX_STATE,
+                    lock_hash.as_bytes(),
+                    &state,
+                );
+                states.insert(lock_hash, state.clone());
+            }
+            batch.commit().expect("commit should be ok");
+        }
+        if to == tip_number {
+            SyncProgress::Synced
+        } else {
+            SyncProgress::Behind
+        }
+    }
+
+    fn attach_block<B: DbBatch>(
+        &self,
+        batch: &mut B,
+        block: &Block,
+        lock_hashes: &FnvHashSet<H256>,
+    ) {
+        let block_number = block.header().number();
+        // Cells created by the block, they are not in the database yet
+        let mut created = FnvHashMap::<CellOutPoint, (H256, CellOutput)>::default();
+        for tx in block.transactions() {
+            let tx_hash = tx.hash();
+            if !tx.is_cellbase() {
+                for (index, input) in tx.inputs().iter().enumerate() {
+                    let cell_out_point = match &input.previous_output.cell {
+                        Some(cell_out_point) => cell_out_point,
+                        None => continue,
+                    };
+                    let (lock_hash, created_number, cell_output) = match created
+                        .remove(cell_out_point)
+                    {
+                        Some((lock_hash, cell_output)) => (lock_hash, block_number, cell_output),
+                        None => match self.get_lock_hash_cell_output(cell_out_point) {
+                            Some(LockHashCellOutput {
+                                lock_hash,
+                                block_number,
+                                cell_output: None,
+                            }) => {
+                                let key = LockHashIndex {
+                                    lock_hash: lock_hash.clone(),
+                                    block_number,
+                                    cell_out_point: cell_out_point.to_owned(),
+                                };
+                                match self.get(COLUMN_LOCK_HASH_LIVE_CELL, &key.to_vec()) {
+                                    Some(value) => (
+                                        lock_hash,
+                                        block_number,
+                                        deserialize(&value)
+                                            .expect("deserialize CellOutput should be ok"),
+                                    ),
+                                    None => continue,
+                                }
+                            }
+                            _ => continue,
+                        },
+                    };
+                    if !lock_hashes.contains(&lock_hash) {
+                        continue;
+                    }
+                    let key = LockHashIndex {
+                        lock_hash: lock_hash.clone(),
+                        block_number: created_number,
+                        cell_out_point: cell_out_point.to_owned(),
+                    }
+                    .to_vec();
+                    let consumed_by = TransactionPoint {
+                        tx_hash: tx_hash.to_owned(),
+                        block_number,
+                        index: index as u32,
+                    };
+                    batch
+                        .delete(COLUMN_LOCK_HASH_LIVE_CELL, &key)
+                        .expect("batch delete should be ok");
+                    insert_serialize(
+                        batch,
+                        COLUMN_LOCK_HASH_TRANSACTION,
+                        &key,
+                        &Some(consumed_by),
+                    );
+                    insert_serialize(
+                        batch,
+                        COLUMN_OUT_POINT_LOCK_HASH,
+                        &out_point_key(cell_out_point),
+                        &LockHashCellOutput {
+                            lock_hash,
+                            block_number: created_number,
+                            cell_output: Some(cell_output),
+                        },
+                    );
+                }
+            }
+
+            for (index, output) in tx.outputs().iter().enumerate() {
+                let lock_hash = output.lock.hash();
+                if !lock_hashes.contains(&lock_hash) {
+                    continue;
+                }
+                let index = LockHashIndex::new(
+                    lock_hash.clone(),
+                    block_number,
+                    tx_hash.to_owned(),
+                    index as u32,
+                );
+                let key = index.to_vec();
+                insert_serialize(batch, COLUMN_LOCK_HASH_LIVE_CELL, &key, output);
+                insert_serialize(
+                    batch,
+                    COLUMN_LOCK_HASH_TRANSACTION,
+                    &key,
+                    &None::<TransactionPoint>,
+                );
+                insert_serialize(
+                    batch,
+                    COLUMN_OUT_POINT_LOCK_HASH,
+                    &out_point_key(&index.cell_out_point),
+                    &LockHashCellOutput {
+                        lock_hash: lock_hash.clone(),
+                        block_number,
+                        cell_output: None,
+                    },
+                );
+                created.insert(index.cell_out_point, (lock_hash, output.to_owned()));
+            }
+        }
+    }
+
+    fn detach_block<B: DbBatch>(
+        &self,
+        batch: &mut B,
+        block: &Block,
+        lock_hashes: &FnvHashSet<H256>,
+    ) {
+        let block_number = block.header().number();
+        // In the reverse order, the cells consumed in the block are restored
+        // before the cells created in the block are removed
+        for tx in block.transactions().iter().rev() {
+            let tx_hash = tx.hash();
+            for index in 0..tx.outputs().len() {
+                let lock_hash = tx.outputs()[index].lock.hash();
+                if !lock_hashes.contains(&lock_hash) {
+                    continue;
+                }
+                let index =
+                    LockHashIndex::new(lock_hash, block_number, tx_hash.to_owned(), index as u32);
+                let key = index.to_vec();
+                batch
+                    .delete(COLUMN_LOCK_HASH_LIVE_CELL, &key)
+                    .expect("batch delete should be ok");
+                batch
+                    .delete(COLUMN_LOCK_HASH_TRANSACTION, &key)
+                    .expect("batch delete should be ok");
+                batch
+                    .delete(
+                        COLUMN_OUT_POINT_LOCK_HASH,
+                        &out_point_key(&index.cell_out_point),
+                    )
+                    .expect("batch delete should be ok");
+            }
+
+            if tx.is_cellbase() {
+                continue;
+            }
+            for input in tx.inputs() {
+                let cell_out_point = match &input.previous_output.cell {
+                    Some(cell_out_point) => cell_out_point,
+                    None => continue,
+                };
+                if let Some(LockHashCellOutput {
+                    lock_hash,
+                    block_number,
+                    cell_output: Some(cell_output),
+                }) = self.get_lock_hash_cell_output(cell_out_point)
+                {
+                    if !lock_hashes.contains(&lock_hash) {
+                        continue;
+                    }
+                    let key = LockHashIndex {
+                        lock_hash: lock_hash.clone(),
+                        block_number,
+                        cell_out_point: cell_out_point.to_owned(),
+                    }
+                    .to_vec();
+                    insert_serialize(batch, COLUMN_LOCK_HASH_LIVE_CELL, &key, &cell_output);
+                    insert_serialize(
+                        batch,
+                        COLUMN_LOCK_HASH_TRANSACTION,
+                        &key,
+                        &None::<TransactionPoint>,
+                    );
+                    insert_serialize(
+                        batch,
+                        COLUMN_OUT_POINT_LOCK_HASH,
+                        &out_point_key(cell_out_point),
+                        &LockHashCellOutput {
+                            lock_hash,
+                            block_number,
+                            cell_output: None,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn get(&self, col: Col, key: &[u8]) -> Option<Vec<u8>> {
+        self.db.read(col, key).expect("db operation should be ok")
+    }
+
+    fn get_lock_hash_index_state(&self, lock_hash: &H256) -> Option<LockHashIndexState> {
+        self.get(COLUMN_LOCK_HASH_INDEX_STATE, lock_hash.as_bytes())
+            .map(|value| deserialize(&value).expect("deserialize LockHashIndexState should be ok"))
+    }
+
+    fn get_lock_hash_cell_output(
+        &self,
+        cell_out_point: &CellOutPoint,
+    ) -> Option<LockHashCellOutput> {
+        self.get(COLUMN_OUT_POINT_LOCK_HASH, &out_point_key(cell_out_point))
+            .map(|value| deserialize(&value).expect("deserialize LockHashCellOutput should be ok"))
+    }
+
+    fn iter_lock_hash<'a>(
+        &'a self,
+        col: Col,
+        lock_hash: &'a H256,
+        reverse_order: bool,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a> {
+        let iter = if reverse_order {
+            let mut from_key = lock_hash.as_bytes().to_vec();
+            from_key.resize(LockHashIndex::SIZE, 0xff);
+            self.db.iter(col, &from_key, Direction::Reverse)
+        } else {
+            self.db.iter(col, lock_hash.as_bytes(), Direction::Forward)
+        };
+        Box::new(
+            iter.expect("db operation should be ok")
+                .take_while(move |(key, _)| key.starts_with(lock_hash.as_bytes())),
+        )
+    }
+
+    fn is_main_chain(&self, state: &LockHashIndexState) -> bool {
+        self.shared.block_hash(state.block_number).as_ref() == Some(&state.block_hash)
+    }
+
+    fn main_chain_state(&self, block_number: BlockNumber) -> LockHashIndexState {
+        LockHashIndexState {
+            block_number,
+            block_hash: self
+                .shared
+                .block_hash(block_number)
+                .expect("main chain block stored"),
+        }
+    }
+
+    fn tip_number(&self) -> BlockNumber {
+        self.shared
+            .store()
+            .get_tip_header()
+            .expect("tip header stored")
+            .number()
+    }
+}
+
+fn insert_serialize<B: DbBatch, T: Serialize + ?Sized>(
+    batch: &mut B,
+    col: Col,
+    key: &[u8],
+    item: &T,
+) {
+    let value = serialize(item).expect("serializing should be ok");
+    batch
+        .insert(col, key, &value)
+        .expect("batch insert should be ok");
+}
+
+fn out_point_key(cell_out_point: &CellOutPoint) -> Vec<u8> {
+    let mut key = Vec::with_capacity(36);
+    key.extend_from_slice(cell_out_point.tx_hash.as_bytes());
+    key.extend_from_slice(&cell_out_point.index.to_be_bytes());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_core::block::BlockBuilder;
+    use ckb_core::header::HeaderBuilder;
+    use ckb_core::script::Script;
+    use ckb_core::transaction::{CellInput, OutPoint, Transaction, TransactionBuilder};
+    use ckb_core::{capacity_bytes, Bytes, Capacity};
+    use ckb_db::MemoryKeyValueDB;
+    use ckb_shared::shared::SharedBuilder;
+    use ckb_store::{ChainKVStore, StoreBatch};
+
+    fn setup(
+        prefix: &str,
+    ) -> (
+        IndexerStore<ChainKVStore<MemoryKeyValueDB>>,
+        tempfile::TempDir,
+    ) {
+        let shared = SharedBuilder::<MemoryKeyValueDB>::new().build().unwrap();
+        let tmp_dir = tempfile::Builder::new().prefix(prefix).tempdir().unwrap();
+        let config = DBConfig {
+            path: tmp_dir.as_ref().to_path_buf(),
+            ..Default::default()
+        };
+        (IndexerStore::new(&config, shared), tmp_dir)
+    }
+
+    fn lock(arg: u8) -> Script {
+        Script::new(vec![Bytes::from(vec![arg])], H256::zero())
+    }
+
+    fn cellbase(number: BlockNumber, lock: Script) -> Transaction {
+        TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(number))
+            .output(CellOutput::new(
+                capacity_bytes!(500),
+                Bytes::new(),
+                lock,
+                None,
+            ))
+            .build()
+    }
+
+    fn spend(tx: &Transaction, lock: Script) -> Transaction {
+        TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(tx.hash().to_owned(), 0),
+                0,
+                vec![],
+            ))
+            .output(CellOutput::new(
+                capacity_bytes!(500),
+                Bytes::new(),
+                lock,
+                None,
+            ))
+            .build()
+    }
+
+    fn block(parent: &Block, timestamp: u64, transactions: Vec<Transaction>) -> Block {
+        let header_builder = HeaderBuilder::default()
+            .number(parent.header().number() + 1)
+            .parent_hash(parent.header().hash().to_owned())
+            .timestamp(timestamp);
+        BlockBuilder::from_header_builder(header_builder)
+            .transactions(transactions)
+            .build()
+    }
+
+    fn attach<CS: ChainStore>(shared: &Shared<CS>, block: &Block) {
+        let mut batch = shared.store().new_batch().unwrap();
+        batch.insert_block(block).unwrap();
+        batch.attach_block(block).unwrap();
+        batch.insert_tip_header(block.header()).unwrap();
+        batch.commit().unwrap();
+    }
+
+    fn detach<CS: ChainStore>(shared: &Shared<CS>, block: &Block, parent: &Block) {
+        let mut batch = shared.store().new_batch().unwrap();
+        batch.detach_block(block).unwrap();
+        batch.insert_tip_header(parent.header()).unwrap();
+        batch.commit().unwrap();
+    }
+
+    #[test]
+    fn test_index_live_cells_and_transactions() {
+        let (store, _tmp_dir) = setup("test_index_live_cells_and_transactions");
+        let shared = store.shared.clone();
+        let genesis = shared.block(shared.genesis_hash()).unwrap();
+        let lock_a = lock(1);
+        let lock_hash = lock_a.hash();
+
+        let cellbase1 = cellbase(1, lock_a.clone());
+        let block1 = block(&genesis, 1, vec![cellbase1.clone()]);
+        attach(&shared, &block1);
+
+        let state = store.insert_lock_hash(&lock_hash, Some(1));
+        assert_eq!(state.block_number, 0);
+        assert_eq!(store.sync_index_states(), SyncProgress::Synced);
+        assert_eq!(
+            store.get_lock_hash_index_states()[&lock_hash].block_number,
+            1
+        );
+        let live_cells = store.get_live_cells(&lock_hash, 0, 10, false);
+        assert_eq!(live_cells.len(), 1);
+        assert_eq!(
+            live_cells[0].created_by.tx_hash,
+            cellbase1.hash().to_owned()
+        );
+
+        // block2 spends the cell of block1
+        let cellbase2 = cellbase(2, lock_a.clone());
+        let spend_tx = spend(&cellbase1, lock(2));
+        let block2 = block(&block1, 2, vec![cellbase2.clone(), spend_tx.clone()]);
+        attach(&shared, &block2);
+        assert_eq!(store.sync_index_states(), SyncProgress::Synced);
+
+        let live_cells = store.get_live_cells(&lock_hash, 0, 10, false);
+        assert_eq!(live_cells.len(), 1);
+        assert_eq!(
+            live_cells[0].created_by.tx_hash,
+            cellbase2.hash().to_owned()
+        );
+        let transactions = store.get_transactions(&lock_hash, 0, 10, false);
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(
+            transactions[0].consumed_by,
+            Some(TransactionPoint {
+                tx_hash: spend_tx.hash().to_owned(),
+                block_number: 2,
+                index: 0,
+            })
+        );
+        assert_eq!(transactions[1].consumed_by, None);
+        let reversed = store.get_transactions(&lock_hash, 0, 1, true);
+        assert_eq!(reversed, vec![transactions[1].clone()]);
+        assert_eq!(store.get_transactions(&lock_hash, 1, 10, false).len(), 1);
+
+        // block2 is replaced by a fork block not spending the cell
+        detach(&shared, &block2, &block1);
+        let cellbase2_fork = cellbase(2, lock_a.clone());
+        let block2_fork = block(&block1, 3, vec![cellbase2_fork.clone()]);
+        attach(&shared, &block2_fork);
+        assert_eq!(store.sync_index_states(), SyncProgress::Synced);
+
+        let state = &store.get_lock_hash_index_states()[&lock_hash];
+        assert_eq!(&state.block_hash, block2_fork.header().hash());
+        let live_cells = store.get_live_cells(&lock_hash, 0, 10, false);
+        assert_eq!(live_cells.len(), 2);
+        assert_eq!(
+            live_cells[0].created_by.tx_hash,
+            cellbase1.hash().to_owned()
+        );
+        let transactions = store.get_transactions(&lock_hash, 0, 10, false);
+        assert!(transactions.iter().all(|tx| tx.consumed_by.is_none()));
+
+        assert!(store.remove_lock_hash(&lock_hash));
+        assert!(!store.remove_lock_hash(&lock_hash));
+        assert!(store.get_lock_hash_index_states().is_empty());
+        assert!(store.get_live_cells(&lock_hash, 0, 10, false).is_empty());
+        assert!(store.get_transactions(&lock_hash, 0, 10, false).is_empty());
+    }
+
+    #[test]
+    fn test_sync_stalled_on_missing_block() {
+        let (store, _tmp_dir) = setup("test_sync_stalled_on_missing_block");
+        let shared = store.shared.clone();
+        let genesis = shared.block(shared.genesis_hash()).unwrap();
+        let lock_a = lock(1);
+        let lock_hash = lock_a.hash();
+        store.insert_lock_hash(&lock_hash, Some(0));
+
+        // block1 is on the main chain but its body is not stored yet
+        let block1 = block(&genesis, 1, vec![cellbase(1, lock_a)]);
+        let mut batch = shared.store().new_batch().unwrap();
+        batch.attach_block(&block1).unwrap();
+        batch.insert_tip_header(block1.header()).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(store.sync_index_states(), SyncProgress::Stalled);
+        assert!(store.sync());
+        assert_eq!(
+            store.get_lock_hash_index_states()[&lock_hash].block_number,
+            0
+        );
+
+        attach(&shared, &block1);
+        assert_eq!(store.sync_index_states(), SyncProgress::Synced);
+        assert!(!store.sync());
+    }
+}
@@ -0,0 +1,95 @@
+use ckb_core::transaction::{CellOutPoint, CellOutput};
+use ckb_core::BlockNumber;
+use numext_fixed_hash::H256;
+use serde_derive::{Deserialize, Serialize};
+
+/// Key of the cells of a lock hash, ordered by block number
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LockHashIndex {
+    pub lock_hash: H256,
+    pub block_number: BlockNumber,
+    pub cell_out_point: CellOutPoint,
+}
+
+impl LockHashIndex {
+    pub const SIZE: usize = 32 + 8 + 32 + 4;
+
+    pub fn new(lock_hash: H256, block_number: BlockNumber, tx_hash: H256, index: u32) -> Self {
+        LockHashIndex {
+            lock_hash,
+            block_number,
+            cell_out_point: CellOutPoint { tx_hash, index },
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut key = Vec::with_capacity(Self::SIZE);
+        key.extend_from_slice(self.lock_hash.as_bytes());
+        key.extend_from_slice(&self.block_number.to_be_bytes());
+        key.extend_from_slice(self.cell_out_point.tx_hash.as_bytes());
+        key.extend_from_slice(&self.cell_out_point.index.to_be_bytes());
+        key
+    }
+
+    pub fn from_slice(key: &[u8]) -> Self {
+        assert_eq!(key.len(), Self::SIZE, "invalid lock hash index");
+        let mut block_number = [0u8; 8];
+        block_number.copy_from_slice(&key[32..40]);
+        let mut index = [0u8; 4];
+        index.copy_from_slice(&key[72..76]);
+        LockHashIndex {
+            lock_hash: H256::from_slice(&key[..32]).expect("lock hash"),
+            block_number: BlockNumber::from_be_bytes(block_number),
+            cell_out_point: CellOutPoint {
+                tx_hash: H256::from_slice(&key[40..72]).expect("tx hash"),
+                index: u32::from_be_bytes(index),
+            },
+        }
+    }
+
+    /// The output creating the cell
+    pub fn transaction_point(&self) -> TransactionPoint {
+        TransactionPoint {
+            tx_hash: self.cell_out_point.tx_hash.clone(),
+            block_number: self.block_number,
+            index: self.cell_out_point.index,
+        }
+    }
+}
+
+/// An output or an input of a committed transaction
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TransactionPoint {
+    pub tx_hash: H256,
+    pub block_number: BlockNumber,
+    pub index: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiveCell {
+    pub created_by: TransactionPoint,
+    pub cell_output: CellOutput,
+}
+
+/// A cell of the lock hash, with the transactions creating and consuming it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellTransaction {
+    pub created_by: TransactionPoint,
+    pub consumed_by: Option<TransactionPoint>,
+}
+
+/// The last block indexed for a lock hash
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHashIndexState {
+    pub block_number: BlockNumber,
+    pub block_hash: H256,
+}
+
+// The lock hash of an indexed cell, and the output of the cell once it is
+// consumed, to restore it when the consuming block is detached
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LockHashCellOutput {
+    pub lock_hash: H256,
+    pub block_number: BlockNumber,
+    pub cell_output: Option<CellOutput>,
+}
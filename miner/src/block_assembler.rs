@@ -1,47 +1,106 @@
 use crate::config::BlockAssemblerConfig;
 use crate::error::Error;
+use crate::miner::build_block;
+use crate::stats::MiningStats;
 use ckb_core::block::Block;
+use ckb_core::cell::{resolve_transaction, BlockCellProvider, OverlayCellProvider};
 use ckb_core::extras::EpochExt;
 use ckb_core::header::Header;
 use ckb_core::script::Script;
-use ckb_core::service::{Request, DEFAULT_CHANNEL_SIZE, SIGNAL_CHANNEL_SIZE};
 use ckb_core::transaction::{
     Capacity, CellInput, CellOutput, OutPoint, ProposalShortId, Transaction, TransactionBuilder,
 };
 use ckb_core::uncle::UncleBlock;
-use ckb_core::{Bytes, Cycle, Version};
+use ckb_core::{BlockNumber, Bytes, Cycle, EpochNumber, Version};
 use ckb_notify::NotifyController;
+use ckb_shared::error::SharedError;
 use ckb_shared::{shared::Shared, tx_pool::PoolEntry};
 use ckb_store::ChainStore;
-use ckb_traits::ChainProvider;
+use ckb_traits::{BlockMedianTimeContext, ChainProvider};
 use ckb_util::Mutex;
-use crossbeam_channel::{self, select, Receiver, Sender};
+use ckb_verification::{BlockVerifier, Error as VerificationError, TransactionsVerifier, Verifier};
 use failure::Error as FailureError;
 use faketime::unix_time_as_millis;
 use fnv::FnvHashMap;
 use fnv::FnvHashSet;
+use futures::future::{self, Either, Future, Loop};
+use futures::stream::{self, Stream};
+use futures::sync::{mpsc, oneshot};
 use jsonrpc_types::{
-    BlockTemplate, CellbaseTemplate, JsonBytes, TransactionTemplate, UncleTemplate,
+    BlockTemplate, BlockTemplateDelta, CellbaseTemplate, JsonBytes, TransactionTemplate,
+    UncleTemplate,
 };
 use log::error;
 use lru_cache::LruCache;
 use numext_fixed_hash::H256;
+use numext_fixed_uint::U256;
 use std::cmp;
 use std::sync::{atomic::AtomicU64, atomic::AtomicUsize, atomic::Ordering, Arc};
 use std::thread;
+use std::time::{Duration, Instant};
 use stop_handler::{SignalSender, StopHandler};
+use tokio::runtime::Builder as RuntimeBuilder;
+use tokio::timer::Delay;
+use tokio_threadpool::blocking;
 
 const MAX_CANDIDATE_UNCLES: usize = 42;
-type BlockTemplateParams = (Option<u64>, Option<u64>, Option<Version>);
+type BlockTemplateParams = (Option<u64>, Option<u64>, Option<Version>, Option<Script>);
 type BlockTemplateResult = Result<BlockTemplate, FailureError>;
+type BlockTemplateDeltaResult = Result<Option<BlockTemplateDelta>, FailureError>;
+pub type BlockTemplateFuture = Box<dyn Future<Item = BlockTemplate, Error = FailureError> + Send>;
+pub type BlockTemplateDeltaFuture =
+    Box<dyn Future<Item = Option<BlockTemplateDelta>, Error = FailureError> + Send>;
 const BLOCK_ASSEMBLER_SUBSCRIBER: &str = "block_assembler";
 const BLOCK_TEMPLATE_TIMEOUT: u64 = 3000;
 const TEMPLATE_CACHE_SIZE: usize = 10;
+// Number of issued templates kept to compute deltas against
+const ISSUED_TEMPLATES_SIZE: usize = 32;
+// Number of recent blocks the network hashrate is estimated from
+const HASHRATE_WINDOW: u64 = 100;
+
+/// Bumped by the assembler whenever a new tip or uncle arrives, so long-polling
+/// requests are woken up when the template may have changed.
+#[derive(Default)]
+struct TemplateSignal {
+    generation: Mutex<u64>,
+    waiters: Mutex<Vec<oneshot::Sender<()>>>,
+}
+
+impl TemplateSignal {
+    fn generation(&self) -> u64 {
+        *self.generation.lock()
+    }
+
+    fn notify(&self) {
+        let mut generation = self.generation.lock();
+        *generation += 1;
+        for waiter in self.waiters.lock().drain(..) {
+            let _ = waiter.send(());
+        }
+    }
+
+    /// Resolves once the generation differs from `generation`
+    fn changed(&self, generation: u64) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        let current = self.generation.lock();
+        if *current == generation {
+            let mut waiters = self.waiters.lock();
+            // the waiters which timed out
+            waiters.retain(|waiter| !waiter.is_canceled());
+            waiters.push(sender);
+        } else {
+            let _ = sender.send(());
+        }
+        receiver
+    }
+}
 
 struct TemplateCache {
     pub time: u64,
     pub uncles_updated_at: u64,
     pub txs_updated_at: u64,
+    pub staged_txs: u64,
+    pub staged_fees: Capacity,
     pub template: BlockTemplate,
 }
 
@@ -58,6 +117,26 @@ impl TemplateCache {
                 && current_time.saturating_sub(self.time) > BLOCK_TEMPLATE_TIMEOUT)
             || number != self.template.number
     }
+
+    // Whether enough txs or fees have been staged since the template was built
+    // to be worth rebuilding it before the timeout
+    fn is_surpassed(
+        &self,
+        config: &BlockAssemblerConfig,
+        staged_txs: u64,
+        staged_fees: Capacity,
+    ) -> bool {
+        let new_txs = staged_txs.saturating_sub(self.staged_txs);
+        let new_fees = staged_fees
+            .as_u64()
+            .saturating_sub(self.staged_fees.as_u64());
+        config
+            .new_txs_threshold
+            .map_or(false, |threshold| new_txs > threshold)
+            || config
+                .new_fees_threshold
+                .map_or(false, |threshold| new_fees > threshold)
+    }
 }
 
 struct FeeCalculator<'a> {
@@ -126,9 +205,23 @@ impl<'a> FeeCalculator<'a> {
     }
 }
 
+enum Message {
+    GetBlockTemplate(BlockTemplateParams, oneshot::Sender<BlockTemplateResult>),
+    GetBlockTemplateDelta(String, oneshot::Sender<BlockTemplateDeltaResult>),
+    // Answered once the template differs from the one of the work id, or at
+    // the deadline
+    WaitBlockTemplate(
+        BlockTemplateParams,
+        String,
+        Instant,
+        oneshot::Sender<BlockTemplateResult>,
+    ),
+}
+
 #[derive(Clone)]
 pub struct BlockAssemblerController {
-    get_block_template_sender: Sender<Request<BlockTemplateParams, BlockTemplateResult>>,
+    sender: mpsc::UnboundedSender<Message>,
+    stats: Arc<MiningStats>,
     stop: StopHandler<()>,
 }
 
@@ -138,33 +231,157 @@ impl Drop for BlockAssemblerController {
     }
 }
 
-struct BlockAssemblerReceivers {
-    get_block_template_receiver: Receiver<Request<BlockTemplateParams, BlockTemplateResult>>,
-}
-
 impl BlockAssemblerController {
+    pub fn mining_stats(&self) -> Arc<MiningStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Returns a template paying the cellbase to `lock`, or to the lock of
+    /// `BlockAssemblerConfig` if `None`.
     pub fn get_block_template(
         &self,
         bytes_limit: Option<u64>,
         proposals_limit: Option<u64>,
         max_version: Option<Version>,
+        lock: Option<Script>,
+    ) -> BlockTemplateFuture {
+        let (responder, response) = oneshot::channel();
+        let params = (bytes_limit, proposals_limit, max_version, lock);
+        self.send(Message::GetBlockTemplate(params, responder));
+        Box::new(
+            response
+                .map_err(|_| FailureError::from(Error::Stopped))
+                .flatten(),
+        )
+    }
+
+    /// Returns the changes of the current template relative to the one
+    /// issued as `work_id`, requested with the same arguments, or `None` if
+    /// that template is no longer known.
+    pub fn get_block_template_delta(&self, work_id: String) -> BlockTemplateDeltaFuture {
+        let (responder, response) = oneshot::channel();
+        self.send(Message::GetBlockTemplateDelta(work_id, responder));
+        Box::new(
+            response
+                .map_err(|_| FailureError::from(Error::Stopped))
+                .flatten(),
+        )
+    }
+
+    fn send(&self, message: Message) {
+        // The responder is dropped along with the message if the assembler
+        // has stopped, failing the response
+        let _ = self.sender.unbounded_send(message);
+    }
+
+    /// Long-polling variant of `get_block_template`, resolves as soon as the
+    /// template differs from the one identified by `work_id`, or to the
+    /// current template once `timeout` has elapsed.
+    ///
+    /// The assembler wakes the request up, no thread waits meanwhile.
+    pub fn poll_block_template(
+        &self,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<Version>,
+        lock: Option<Script>,
+        work_id: String,
+        timeout: Duration,
+    ) -> BlockTemplateFuture {
+        let (responder, response) = oneshot::channel();
+        let params = (bytes_limit, proposals_limit, max_version, lock);
+        let deadline = Instant::now() + timeout;
+        self.send(Message::WaitBlockTemplate(
+            params, work_id, deadline, responder,
+        ));
+        Box::new(
+            response
+                .map_err(|_| FailureError::from(Error::Stopped))
+                .flatten(),
+        )
+    }
+
+    /// Blocking `poll_block_template`, for callers running their own thread.
+    pub fn wait_block_template(
+        &self,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<Version>,
+        lock: Option<Script>,
+        work_id: &str,
+        timeout: Duration,
     ) -> BlockTemplateResult {
-        Request::call(
-            &self.get_block_template_sender,
-            (bytes_limit, proposals_limit, max_version),
+        self.poll_block_template(
+            bytes_limit,
+            proposals_limit,
+            max_version,
+            lock,
+            work_id.to_owned(),
+            timeout,
         )
-        .expect("get_block_template() failed")
+        .wait()
     }
 }
 
+// Builds templates until one differs from `work_id` or `deadline` is reached,
+// sleeping on the template signal in between.
+fn wait_template<CS: ChainStore + 'static>(
+    assembler: Arc<BlockAssembler<CS>>,
+    signal: Arc<TemplateSignal>,
+    params: BlockTemplateParams,
+    work_id: String,
+    deadline: Instant,
+) -> impl Future<Item = BlockTemplate, Error = FailureError> {
+    future::loop_fn((), move |()| {
+        let generation = signal.generation();
+        let assembler = Arc::clone(&assembler);
+        let signal = Arc::clone(&signal);
+        let work_id = work_id.clone();
+        let (bytes_limit, proposals_limit, max_version, lock) = params.clone();
+        future::poll_fn(move || {
+            blocking(|| {
+                assembler.get_block_template(
+                    bytes_limit,
+                    proposals_limit,
+                    max_version,
+                    lock.clone(),
+                )
+            })
+        })
+        .map_err(|_| FailureError::from(Error::Stopped))
+        .and_then(|template| template)
+        .and_then(move |template| {
+            let now = Instant::now();
+            if template.work_id != work_id || now >= deadline {
+                return Either::A(future::ok(Loop::Break(template)));
+            }
+            // New transactions only refresh the template once the cached one is
+            // older than BLOCK_TEMPLATE_TIMEOUT, and they are not signaled.
+            let wake_at = cmp::min(
+                deadline,
+                now + Duration::from_millis(BLOCK_TEMPLATE_TIMEOUT),
+            );
+            Either::B(
+                signal
+                    .changed(generation)
+                    .map_err(|_| ())
+                    .select(Delay::new(wake_at).map_err(|_| ()))
+                    .then(|_| -> Result<_, FailureError> { Ok(Loop::Continue(())) }),
+            )
+        })
+    })
+}
+
 pub struct BlockAssembler<CS> {
     shared: Shared<CS>,
-    candidate_uncles: LruCache<H256, Arc<Block>>,
+    candidate_uncles: Mutex<LruCache<H256, Arc<Block>>>,
     config: BlockAssemblerConfig,
     work_id: AtomicUsize,
     last_uncles_updated_at: AtomicU64,
-    template_caches: Mutex<LruCache<(Cycle, u64, Version), TemplateCache>>,
+    template_caches: Mutex<LruCache<(Cycle, u64, Version, Script), TemplateCache>>,
     proof_size: usize,
+    stats: Arc<MiningStats>,
+    issued_templates: Mutex<LruCache<String, (BlockTemplateParams, BlockTemplate)>>,
 }
 
 impl<CS: ChainStore + 'static> BlockAssembler<CS> {
@@ -173,71 +390,152 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
             proof_size: shared.consensus().pow_engine().proof_size(),
             shared,
             config,
-            candidate_uncles: LruCache::new(MAX_CANDIDATE_UNCLES),
+            candidate_uncles: Mutex::new(LruCache::new(MAX_CANDIDATE_UNCLES)),
             work_id: AtomicUsize::new(0),
             last_uncles_updated_at: AtomicU64::new(0),
             template_caches: Mutex::new(LruCache::new(TEMPLATE_CACHE_SIZE)),
+            stats: Arc::new(MiningStats::new()),
+            issued_templates: Mutex::new(LruCache::new(ISSUED_TEMPLATES_SIZE)),
         }
     }
 
+    /// Serves templates from a tokio runtime, requests are handled
+    /// concurrently so slow template generation does not hold up others.
     pub fn start<S: ToString>(
-        mut self,
+        self,
         thread_name: Option<S>,
         notify: &NotifyController,
     ) -> BlockAssemblerController {
-        let (signal_sender, signal_receiver) =
-            crossbeam_channel::bounded::<()>(SIGNAL_CHANNEL_SIZE);
-        let (get_block_template_sender, get_block_template_receiver) =
-            crossbeam_channel::bounded(DEFAULT_CHANNEL_SIZE);
+        let (sender, receiver) = mpsc::unbounded();
+        let (stop_sender, stop_receiver) = oneshot::channel();
 
         let mut thread_builder = thread::Builder::new();
+        let mut runtime_builder = RuntimeBuilder::new();
         // Mainly for test: give a empty thread_name
         if let Some(name) = thread_name {
             thread_builder = thread_builder.name(name.to_string());
+            runtime_builder.name_prefix(format!("{}-", name.to_string()));
         }
+        let mut runtime = runtime_builder
+            .build()
+            .expect("Start BlockAssembler runtime failed");
 
-        let receivers = BlockAssemblerReceivers {
-            get_block_template_receiver,
-        };
-
+        let template_signal = Arc::new(TemplateSignal::default());
+        let stats = Arc::clone(&self.stats);
         let new_uncle_receiver = notify.subscribe_new_uncle(BLOCK_ASSEMBLER_SUBSCRIBER);
+        let new_tip_receiver = notify.subscribe_new_tip(BLOCK_ASSEMBLER_SUBSCRIBER);
+        let block_assembler = Arc::new(self);
+
+        // Notifications are delivered over blocking channels, receive them on
+        // threads the pool marks as blocking
+        let assembler = Arc::clone(&block_assembler);
+        let signal = Arc::clone(&template_signal);
+        runtime.spawn(
+            stream::poll_fn(move || blocking(|| new_uncle_receiver.recv().ok()).map_err(|_| ()))
+                .for_each(move |uncle_block| {
+                    let hash = uncle_block.header().hash().to_owned();
+                    assembler.candidate_uncles.lock().insert(hash, uncle_block);
+                    assembler
+                        .last_uncles_updated_at
+                        .store(unix_time_as_millis(), Ordering::SeqCst);
+                    signal.notify();
+                    Ok(())
+                }),
+        );
+
+        let assembler = Arc::clone(&block_assembler);
+        let signal = Arc::clone(&template_signal);
+        runtime.spawn(
+            stream::poll_fn(move || blocking(|| new_tip_receiver.recv().ok()).map_err(|_| ()))
+                .for_each(move |block| {
+                    signal.notify();
+                    let hashrate = assembler.estimate_network_hashrate(block.header());
+                    assembler.stats.set_network_hashrate(hashrate);
+                    Ok(())
+                }),
+        );
+
+        let signal = Arc::clone(&template_signal);
+        runtime.spawn(receiver.for_each(move |message| {
+            let assembler = Arc::clone(&block_assembler);
+            if let Message::WaitBlockTemplate(params, work_id, deadline, responder) = message {
+                let signal = Arc::clone(&signal);
+                tokio::spawn(
+                    wait_template(assembler, signal, params, work_id, deadline).then(move |ret| {
+                        let _ = responder.send(ret);
+                        Ok(())
+                    }),
+                );
+                return Ok(());
+            }
+            let mut message = Some(message);
+            tokio::spawn(future::poll_fn(move || {
+                blocking(|| assembler.handle(message.take().expect("handled once")))
+                    .map_err(|_| error!(target: "miner", "block assembler runtime shut down"))
+            }));
+            Ok(())
+        }));
+
         let thread = thread_builder
-            .spawn(move || loop {
-                select! {
-                    recv(signal_receiver) -> _ => {
-                        break;
-                    }
-                    recv(new_uncle_receiver) -> msg => match msg {
-                        Ok(uncle_block) => {
-                            let hash = uncle_block.header().hash();
-                            self.candidate_uncles.insert(hash.to_owned(), uncle_block);
-                            self.last_uncles_updated_at
-                                .store(unix_time_as_millis(), Ordering::SeqCst);
-                        }
-                        _ => {
-                            error!(target: "miner", "new_uncle_receiver closed");
-                            break;
-                        }
-                    },
-                    recv(receivers.get_block_template_receiver) -> msg => match msg {
-                        Ok(Request { responder, arguments: (bytes_limit, proposals_limit,  max_version) }) => {
-                            let _ = responder.send(self.get_block_template(bytes_limit, proposals_limit, max_version));
-                        },
-                        _ => {
-                            error!(target: "miner", "get_block_template_receiver closed");
-                            break;
-                        },
-                    }
-                }
-            }).expect("Start MinerAgent failed");
-        let stop = StopHandler::new(SignalSender::Crossbeam(signal_sender), thread);
+            .spawn(move || {
+                let _ = runtime.block_on(stop_receiver);
+                // Do not wait for the threads blocked on the notify channels
+                let _ = runtime.shutdown_now();
+            })
+            .expect("Start BlockAssembler failed");
+        let stop = StopHandler::new(SignalSender::Future(stop_sender), thread);
 
         BlockAssemblerController {
-            get_block_template_sender,
+            sender,
+            stats,
             stop,
         }
     }
 
+    fn handle(&self, message: Message) {
+        match message {
+            Message::GetBlockTemplate(
+                (bytes_limit, proposals_limit, max_version, lock),
+                responder,
+            ) => {
+                let _ = responder.send(self.get_block_template(
+                    bytes_limit,
+                    proposals_limit,
+                    max_version,
+                    lock,
+                ));
+            }
+            Message::GetBlockTemplateDelta(work_id, responder) => {
+                let _ = responder.send(self.get_block_template_delta(&work_id));
+            }
+            Message::WaitBlockTemplate(..) => unreachable!("served by wait_template"),
+        }
+    }
+
+    // Total difficulty of the last HASHRATE_WINDOW blocks divided by the
+    // seconds they took
+    fn estimate_network_hashrate(&self, tip: &Header) -> U256 {
+        let mut total_difficulty = U256::zero();
+        let mut oldest = tip.to_owned();
+        for _ in 0..HASHRATE_WINDOW {
+            if oldest.number() == 0 {
+                break;
+            }
+            match self.shared.block_header(oldest.parent_hash()) {
+                Some(parent) => {
+                    total_difficulty = total_difficulty + oldest.difficulty();
+                    oldest = parent;
+                }
+                None => break,
+            }
+        }
+        let span = tip.timestamp().saturating_sub(oldest.timestamp());
+        if span == 0 {
+            return U256::zero();
+        }
+        total_difficulty * U256::from(1000u64) / U256::from(span)
+    }
+
     fn transform_params(
         &self,
         bytes_limit: Option<u64>,
@@ -258,12 +556,12 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
         (bytes_limit, proposals_limit, version)
     }
 
-    fn transform_uncle(uncle: UncleBlock) -> UncleTemplate {
+    fn transform_uncle(uncle: UncleBlock, required: bool) -> UncleTemplate {
         let UncleBlock { header, proposals } = uncle;
 
         UncleTemplate {
             hash: header.hash().to_owned(),
-            required: false,
+            required,
             proposals: proposals.into_iter().map(Into::into).collect(),
             header: (&header).into(),
         }
@@ -309,11 +607,13 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
     }
 
     fn get_block_template(
-        &mut self,
+        &self,
         bytes_limit: Option<u64>,
         proposals_limit: Option<u64>,
         max_version: Option<Version>,
+        lock: Option<Script>,
     ) -> Result<BlockTemplate, FailureError> {
+        let params = (bytes_limit, proposals_limit, max_version, lock.clone());
         let cycles_limit = self.shared.consensus().max_block_cycles();
         let (bytes_limit, proposals_limit, version) =
             self.transform_params(bytes_limit, proposals_limit, max_version);
@@ -322,23 +622,38 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
         let last_uncles_updated_at = self.last_uncles_updated_at.load(Ordering::SeqCst);
         let chain_state = self.shared.chain_state().lock();
         let last_txs_updated_at = chain_state.get_last_txs_updated_at();
+        let (staged_txs, staged_fees) = chain_state.get_staged_txs_stats();
 
         let header = chain_state.tip_header().to_owned();
         let number = chain_state.tip_number() + 1;
         let current_time = cmp::max(unix_time_as_millis(), header.timestamp() + 1);
 
-        let mut template_caches = self.template_caches.lock();
-
-        if let Some(template_cache) = template_caches.get(&(cycles_limit, bytes_limit, version)) {
+        let cellbase_lock = lock.unwrap_or_else(|| {
+            let args = self
+                .config
+                .args
+                .iter()
+                .cloned()
+                .map(JsonBytes::into_vec)
+                .map(Bytes::from)
+                .collect();
+            Script::new(args, self.config.code_hash.clone())
+        });
+        // Templates paying to different locks must not be served to each other
+        let cache_key = (cycles_limit, bytes_limit, version, cellbase_lock.clone());
+
+        if let Some(template_cache) = self.template_caches.lock().get(&cache_key) {
             if !template_cache.is_outdate(
                 last_uncles_updated_at,
                 last_txs_updated_at,
                 current_time,
                 number.to_string(),
-            ) {
+            ) && !template_cache.is_surpassed(&self.config, staged_txs, staged_fees)
+            {
                 return Ok(template_cache.template.clone());
             }
         }
+        let started_at = Instant::now();
         let last_epoch = chain_state.current_epoch_ext().clone();
 
         let next_epoch_ext = self.shared.next_epoch_ext(&last_epoch, &header);
@@ -346,8 +661,9 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
 
         let (uncles, bad_uncles) = self.prepare_uncles(&header, &current_epoch);
         if !bad_uncles.is_empty() {
+            let mut candidate_uncles = self.candidate_uncles.lock();
             for bad in bad_uncles {
-                self.candidate_uncles.remove(&bad);
+                candidate_uncles.remove(&bad);
             }
         }
 
@@ -359,17 +675,7 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
         // Release the lock as soon as possible, let other services do their work
         drop(chain_state);
 
-        let args = self
-            .config
-            .args
-            .iter()
-            .cloned()
-            .map(JsonBytes::into_vec)
-            .map(Bytes::from)
-            .collect();
-
         // dummy cellbase
-        let cellbase_lock = Script::new(args, self.config.code_hash.clone());
         let cellbase = self.create_cellbase_transaction(
             &header,
             &current_epoch,
@@ -389,7 +695,13 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
             cycles_limit: cycles_limit.to_string(),
             bytes_limit: bytes_limit.to_string(),
             uncles_count_limit,
-            uncles: uncles.into_iter().map(Self::transform_uncle).collect(),
+            uncles: uncles
+                .into_iter()
+                .map(|uncle| {
+                    let required = self.is_required_uncle(uncle.header.hash());
+                    Self::transform_uncle(uncle, required)
+                })
+                .collect(),
             transactions: transactions
                 .iter()
                 .map(|tx| Self::transform_tx(tx, false, None))
@@ -399,12 +711,22 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
             work_id: format!("{}", self.work_id.fetch_add(1, Ordering::SeqCst)),
         };
 
-        template_caches.insert(
-            (cycles_limit, bytes_limit, version),
+        if self.config.verify_template {
+            self.verify_template(&template)?;
+        }
+        self.stats.template_generated(started_at.elapsed());
+        self.issued_templates
+            .lock()
+            .insert(template.work_id.clone(), (params, template.clone()));
+
+        self.template_caches.lock().insert(
+            cache_key,
             TemplateCache {
                 time: current_time,
                 uncles_updated_at: last_uncles_updated_at,
                 txs_updated_at: last_txs_updated_at,
+                staged_txs,
+                staged_fees,
                 template: template.clone(),
             },
         );
@@ -412,6 +734,80 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
         Ok(template)
     }
 
+    fn get_block_template_delta(&self, work_id: &str) -> BlockTemplateDeltaResult {
+        let issued = self.issued_templates.lock().get(work_id).cloned();
+        let (params, base) = match issued {
+            Some(issued) => issued,
+            None => return Ok(None),
+        };
+        let (bytes_limit, proposals_limit, max_version, lock) = params;
+        let template = self.get_block_template(bytes_limit, proposals_limit, max_version, lock)?;
+        Ok(Some(BlockTemplateDelta::new(&base, &template)))
+    }
+
+    fn verify_template(&self, template: &BlockTemplate) -> Result<(), FailureError> {
+        let (_, block) = build_block(template.clone())?;
+        self.verify_block(&block).map_err(|err| {
+            error!(target: "miner", "invalid block template {}: {}", template.number, err);
+            err
+        })
+    }
+
+    // Runs the checks of the chain for a block extending the tip: the block
+    // checks, then its transactions resolved against the tip cell set. The
+    // scripts run after the chain state lock is released.
+    fn verify_block(&self, block: &Block) -> Result<(), FailureError> {
+        BlockVerifier::new(self.shared.clone()).verify(block)?;
+
+        let consensus = self.shared.consensus();
+        let resolved = {
+            let chain_state = self.shared.chain_state().lock();
+            if chain_state.tip_hash() != block.header().parent_hash() {
+                // The tip moved, the template is outdated anyway
+                return Ok(());
+            }
+            let block_cp = BlockCellProvider::new(block);
+            let cell_provider = OverlayCellProvider::new(&block_cp, &*chain_state);
+            let mut seen_inputs = FnvHashSet::default();
+            block
+                .transactions()
+                .iter()
+                .map(|tx| resolve_transaction(tx, &mut seen_inputs, &cell_provider, &*chain_state))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(SharedError::UnresolvableTransaction)?
+        };
+
+        let parent_hash = block.header().parent_hash();
+        let parent = self
+            .shared
+            .block_header(parent_hash)
+            .ok_or_else(|| VerificationError::UnknownParent(parent_hash.to_owned()))?;
+        let parent_ext = self
+            .shared
+            .get_epoch_ext(parent_hash)
+            .ok_or_else(|| VerificationError::UnknownParent(parent_hash.to_owned()))?;
+        let epoch = self
+            .shared
+            .next_epoch_ext(&parent_ext, &parent)
+            .unwrap_or(parent_ext);
+        TransactionsVerifier::new(
+            consensus.max_block_cycles(),
+            consensus.max_tx_bytes(),
+            self.shared.script_config(),
+        )
+        .verify(
+            &resolved,
+            Arc::clone(self.shared.store()),
+            epoch.block_reward(block.header().number())?,
+            StoreMedianTime {
+                shared: &self.shared,
+            },
+            block.header().number(),
+            consensus.cellbase_maturity(),
+        )?;
+        Ok(())
+    }
+
     fn create_cellbase_transaction(
         &self,
         tip: &Header,
@@ -435,11 +831,32 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
         }
 
         let output = CellOutput::new(block_reward.safe_add(fee)?, Bytes::new(), lock, None);
+        let message = self.cellbase_message(tip.number() + 1, current_epoch.number())?;
+
+        let builder = TransactionBuilder::default().input(input).output(output);
+        if message.is_empty() {
+            Ok(builder.build())
+        } else {
+            // The message is the only item of the only witness
+            Ok(builder.witness(vec![message]).build())
+        }
+    }
 
-        Ok(TransactionBuilder::default()
-            .input(input)
-            .output(output)
-            .build())
+    fn cellbase_message(
+        &self,
+        number: BlockNumber,
+        epoch: EpochNumber,
+    ) -> Result<Vec<u8>, FailureError> {
+        let message = self
+            .config
+            .message
+            .replace("{number}", &number.to_string())
+            .replace("{epoch}", &epoch.to_string())
+            .into_bytes();
+        if message.len() as u64 > self.shared.consensus().max_cellbase_message_bytes() {
+            Err(Error::MessageTooLong)?;
+        }
+        Ok(message)
     }
 
     fn prepare_uncles(
@@ -476,12 +893,16 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
 
         let current_number = tip.number() + 1;
 
-        let max_uncles_num = self.shared.consensus().max_uncles_num();
+        let policy = &self.config.uncles;
+        let max_uncles_num = policy
+            .max_uncles
+            .unwrap_or(usize::max_value())
+            .min(self.shared.consensus().max_uncles_num());
         let mut included = FnvHashSet::default();
         let mut uncles = Vec::with_capacity(max_uncles_num);
         let mut bad_uncles = Vec::new();
 
-        for (hash, block) in self.candidate_uncles.iter() {
+        for (hash, block) in self.candidate_uncles.lock().iter() {
             if uncles.len() == max_uncles_num {
                 break;
             }
@@ -501,6 +922,7 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
                 || depth < 1
                 || included.contains(hash)
                 || excluded.contains(hash)
+                || !self.is_allowed_uncle(block)
             {
                 bad_uncles.push(hash.clone());
             } else {
@@ -514,11 +936,74 @@ impl<CS: ChainStore + 'static> BlockAssembler<CS> {
         }
         (uncles, bad_uncles)
     }
+
+    fn is_allowed_uncle(&self, block: &Block) -> bool {
+        let policy = &self.config.uncles;
+        if policy.allowed_locks.is_empty() && policy.denied_locks.is_empty() {
+            return true;
+        }
+        match miner_lock_hash(block) {
+            Some(lock_hash) => {
+                (policy.allowed_locks.is_empty() || policy.allowed_locks.contains(&lock_hash))
+                    && !policy.denied_locks.contains(&lock_hash)
+            }
+            // Can not tell who mined it
+            None => policy.allowed_locks.is_empty(),
+        }
+    }
+
+    fn is_required_uncle(&self, hash: &H256) -> bool {
+        let required_locks = &self.config.uncles.required_locks;
+        if required_locks.is_empty() {
+            return false;
+        }
+        self.candidate_uncles
+            .lock()
+            .get(hash)
+            .and_then(|block| miner_lock_hash(block))
+            .map_or(false, |lock_hash| required_locks.contains(&lock_hash))
+    }
+}
+
+// The lock hash the cellbase of `block` pays the reward to
+fn miner_lock_hash(block: &Block) -> Option<H256> {
+    block
+        .transactions()
+        .first()
+        .and_then(|cellbase| cellbase.outputs().first())
+        .map(|output| output.lock.hash())
+}
+
+// The median time of the main chain ancestors, read from the store so no
+// lock is held while the scripts run
+struct StoreMedianTime<'a, CS> {
+    shared: &'a Shared<CS>,
+}
+
+impl<'a, CS: ChainStore> BlockMedianTimeContext for StoreMedianTime<'a, CS> {
+    fn median_block_count(&self) -> u64 {
+        self.shared.consensus().median_time_block_count() as u64
+    }
+
+    fn timestamp(&self, number: BlockNumber) -> Option<u64> {
+        self.shared
+            .block_hash(number)
+            .and_then(|hash| self.shared.block_header(&hash))
+            .map(|header| header.timestamp())
+    }
+
+    fn epoch_number(&self, number: BlockNumber) -> Option<EpochNumber> {
+        self.shared
+            .block_hash(number)
+            .and_then(|hash| self.shared.block_header(&hash))
+            .map(|header| header.epoch())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{block_assembler::BlockAssembler, config::BlockAssemblerConfig};
+    use crate::block_assembler::{BlockAssembler, TemplateCache};
+    use crate::config::{BlockAssemblerConfig, UnclePolicyConfig};
     use ckb_chain::chain::ChainBuilder;
     use ckb_chain::chain::ChainController;
     use ckb_chain_spec::consensus::Consensus;
@@ -528,7 +1013,7 @@ mod tests {
     use ckb_core::header::{Header, HeaderBuilder};
     use ckb_core::script::Script;
     use ckb_core::transaction::{
-        CellInput, CellOutput, ProposalShortId, Transaction, TransactionBuilder,
+        Capacity, CellInput, CellOutput, ProposalShortId, Transaction, TransactionBuilder,
     };
     use ckb_core::{BlockNumber, Bytes, EpochNumber};
     use ckb_db::memorydb::MemoryKeyValueDB;
@@ -539,10 +1024,13 @@ mod tests {
     use ckb_store::{ChainKVStore, ChainStore};
     use ckb_traits::ChainProvider;
     use ckb_verification::{BlockVerifier, HeaderResolverWrapper, HeaderVerifier, Verifier};
+    use futures::future::Future;
     use jsonrpc_types::{BlockTemplate, CellbaseTemplate};
     use numext_fixed_hash::H256;
     use std::convert::TryInto;
     use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
 
     fn start_chain(
         consensus: Option<Consensus>,
@@ -576,14 +1064,11 @@ mod tests {
     #[test]
     fn test_get_block_template() {
         let (_chain_controller, shared, _notify) = start_chain(None, None);
-        let config = BlockAssemblerConfig {
-            code_hash: H256::zero(),
-            args: vec![],
-        };
-        let mut block_assembler = setup_block_assembler(shared.clone(), config);
+        let config = BlockAssemblerConfig::default();
+        let block_assembler = setup_block_assembler(shared.clone(), config);
 
         let block_template = block_assembler
-            .get_block_template(None, None, None)
+            .get_block_template(None, None, None, None)
             .unwrap();
 
         let BlockTemplate {
@@ -655,6 +1140,107 @@ mod tests {
         assert!(block_verify.verify(&block).is_ok());
     }
 
+    #[test]
+    fn test_template_cache_surpassed() {
+        let (_chain_controller, shared, _notify) = start_chain(None, None);
+        let mut config = BlockAssemblerConfig::default();
+        let block_assembler = setup_block_assembler(shared.clone(), config.clone());
+        let template = block_assembler
+            .get_block_template(None, None, None, None)
+            .unwrap();
+        let cache = TemplateCache {
+            time: 0,
+            uncles_updated_at: 0,
+            txs_updated_at: 0,
+            staged_txs: 10,
+            staged_fees: Capacity::shannons(1_000),
+            template,
+        };
+
+        // Without thresholds only the timeout refreshes the template
+        assert!(!cache.is_surpassed(&config, 100, Capacity::shannons(100_000)));
+
+        config.new_txs_threshold = Some(5);
+        config.new_fees_threshold = Some(500);
+        assert!(!cache.is_surpassed(&config, 15, Capacity::shannons(1_500)));
+        assert!(cache.is_surpassed(&config, 16, Capacity::shannons(1_500)));
+        assert!(cache.is_surpassed(&config, 15, Capacity::shannons(1_501)));
+    }
+
+    #[test]
+    fn test_verify_template() {
+        let (_chain_controller, shared, _notify) = start_chain(None, None);
+        let config = BlockAssemblerConfig {
+            message: "verified".to_owned(),
+            verify_template: true,
+            ..Default::default()
+        };
+        let block_assembler = setup_block_assembler(shared.clone(), config);
+        let mut template = block_assembler
+            .get_block_template(None, None, None, None)
+            .unwrap();
+
+        // Only the transactions verification knows the cellbase overpays
+        let output = &mut template.cellbase.data.outputs[0];
+        let capacity = output.capacity.parse::<Capacity>().unwrap();
+        output.capacity = capacity.safe_add(Capacity::one()).unwrap().to_string();
+        let (_, block) = build_block(template.clone()).unwrap();
+        assert!(BlockVerifier::new(shared.clone()).verify(&block).is_ok());
+        assert!(block_assembler.verify_template(&template).is_err());
+    }
+
+    #[test]
+    fn test_lock_override() {
+        let (_chain_controller, shared, _notify) = start_chain(None, None);
+        let config = BlockAssemblerConfig::default();
+        let block_assembler = setup_block_assembler(shared.clone(), config);
+
+        let default_template = block_assembler
+            .get_block_template(None, None, None, None)
+            .unwrap();
+        let lock = Script::new(vec![Bytes::from(vec![1])], H256::zero());
+        let template = block_assembler
+            .get_block_template(None, None, None, Some(lock.clone()))
+            .unwrap();
+        // Not served from the cache of the default lock
+        assert_ne!(template.work_id, default_template.work_id);
+        let cellbase: Transaction = template.cellbase.data.try_into().unwrap();
+        assert_eq!(cellbase.outputs()[0].lock, lock);
+
+        let cached = block_assembler
+            .get_block_template(None, None, None, Some(lock))
+            .unwrap();
+        assert_eq!(cached.work_id, template.work_id);
+    }
+
+    #[test]
+    fn test_cellbase_message() {
+        let (_chain_controller, shared, _notify) = start_chain(None, None);
+        let config = BlockAssemblerConfig {
+            message: "pool block {number} epoch {epoch}".to_owned(),
+            ..Default::default()
+        };
+        let block_assembler = setup_block_assembler(shared.clone(), config);
+
+        let block_template = block_assembler
+            .get_block_template(None, None, None, None)
+            .unwrap();
+        let cellbase: Transaction = block_template.cellbase.data.try_into().unwrap();
+        assert_eq!(
+            cellbase.witnesses(),
+            &[vec![b"pool block 1 epoch 0".to_vec()]][..]
+        );
+
+        let config = BlockAssemblerConfig {
+            message: "x".repeat(shared.consensus().max_cellbase_message_bytes() as usize + 1),
+            ..Default::default()
+        };
+        let block_assembler = setup_block_assembler(shared.clone(), config);
+        assert!(block_assembler
+            .get_block_template(None, None, None, None)
+            .is_err());
+    }
+
     fn gen_block(parent_header: &Header, nonce: u64, epoch: &EpochExt) -> Block {
         let number = parent_header.number() + 1;
         let cellbase = create_cellbase(number, epoch);
@@ -693,10 +1279,7 @@ mod tests {
         let epoch = consensus.genesis_epoch_ext().clone();
 
         let (chain_controller, shared, notify) = start_chain(Some(consensus), None);
-        let config = BlockAssemblerConfig {
-            code_hash: H256::zero(),
-            args: vec![],
-        };
+        let config = BlockAssemblerConfig::default();
         let block_assembler = setup_block_assembler(shared.clone(), config);
         let new_uncle_receiver = notify.subscribe_new_uncle("test_prepare_uncles");
         let block_assembler_controller = block_assembler.start(Some("test"), &notify.clone());
@@ -726,7 +1309,8 @@ mod tests {
         // block number 3, epoch 0
         let _ = new_uncle_receiver.recv();
         let block_template = block_assembler_controller
-            .get_block_template(None, None, None)
+            .get_block_template(None, None, None, None)
+            .wait()
             .unwrap();
         assert_eq!(&block_template.uncles[0].hash, block0_0.header().hash());
 
@@ -741,9 +1325,149 @@ mod tests {
             .unwrap();
 
         let block_template = block_assembler_controller
-            .get_block_template(None, None, None)
+            .get_block_template(None, None, None, None)
+            .wait()
             .unwrap();
         // block number 4, epoch 1, block_template should not include last epoch uncles
         assert!(block_template.uncles.is_empty());
     }
+
+    #[test]
+    fn test_uncle_policy() {
+        let mut consensus = Consensus::default();
+        consensus.genesis_epoch_ext.set_length(4);
+        let epoch = consensus.genesis_epoch_ext().clone();
+
+        let (chain_controller, shared, notify) = start_chain(Some(consensus), None);
+        let miner_lock_hash = Script::default().hash();
+        let config = BlockAssemblerConfig {
+            uncles: UnclePolicyConfig {
+                required_locks: vec![miner_lock_hash.clone()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let block_assembler = setup_block_assembler(shared.clone(), config.clone());
+        let new_uncle_receiver = notify.subscribe_new_uncle("test_uncle_policy");
+        let block_assembler_controller = block_assembler.start(Some("test"), &notify.clone());
+
+        let genesis = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+        let block0_0 = gen_block(&genesis, 11, &epoch);
+        let block0_1 = gen_block(&genesis, 10, &epoch);
+        chain_controller
+            .process_block(Arc::new(block0_1.clone()))
+            .unwrap();
+        chain_controller
+            .process_block(Arc::new(block0_0.clone()))
+            .unwrap();
+
+        let _ = new_uncle_receiver.recv();
+        let block_template = block_assembler_controller
+            .get_block_template(None, None, None, None)
+            .wait()
+            .unwrap();
+        assert_eq!(&block_template.uncles[0].hash, block0_0.header().hash());
+        assert!(block_template.uncles[0].required);
+
+        let denied = BlockAssemblerConfig {
+            uncles: UnclePolicyConfig {
+                denied_locks: vec![miner_lock_hash.clone()],
+                ..Default::default()
+            },
+            ..config.clone()
+        };
+        let block_assembler = setup_block_assembler(shared.clone(), denied);
+        assert!(!block_assembler.is_allowed_uncle(&block0_0));
+
+        let allowed = BlockAssemblerConfig {
+            uncles: UnclePolicyConfig {
+                allowed_locks: vec![H256::zero()],
+                ..Default::default()
+            },
+            ..config
+        };
+        let block_assembler = setup_block_assembler(shared.clone(), allowed);
+        assert!(!block_assembler.is_allowed_uncle(&block0_0));
+    }
+
+    #[test]
+    fn test_get_block_template_delta() {
+        let consensus = Consensus::default();
+        let epoch = consensus.genesis_epoch_ext().clone();
+        let (chain_controller, shared, _notify) = start_chain(Some(consensus), None);
+        let config = BlockAssemblerConfig::default();
+        let block_assembler = setup_block_assembler(shared.clone(), config);
+
+        assert_eq!(block_assembler.get_block_template_delta("0").unwrap(), None);
+
+        let base = block_assembler
+            .get_block_template(None, None, None, None)
+            .unwrap();
+        let genesis = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+        let block = gen_block(&genesis, 0, &epoch);
+        chain_controller
+            .process_block(Arc::new(block.clone()))
+            .unwrap();
+
+        let delta = block_assembler
+            .get_block_template_delta(&base.work_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(delta.base_work_id, base.work_id);
+        assert_eq!(delta.number, Some("2".to_owned()));
+        assert_eq!(delta.parent_hash, Some(block.header().hash().to_owned()));
+        let template = block_assembler
+            .get_block_template(None, None, None, None)
+            .unwrap();
+        assert_eq!(delta.apply(&base), template);
+    }
+
+    #[test]
+    fn test_wait_block_template() {
+        let consensus = Consensus::default();
+        let epoch = consensus.genesis_epoch_ext().clone();
+        let (chain_controller, shared, notify) = start_chain(Some(consensus), None);
+        let config = BlockAssemblerConfig::default();
+        let block_assembler = setup_block_assembler(shared.clone(), config);
+        let block_assembler_controller = block_assembler.start(Some("test"), &notify);
+
+        let block_template = block_assembler_controller
+            .get_block_template(None, None, None, None)
+            .wait()
+            .unwrap();
+        // nothing changed, returns the same template once the timeout elapsed
+        let unchanged = block_assembler_controller
+            .wait_block_template(
+                None,
+                None,
+                None,
+                None,
+                &block_template.work_id,
+                Duration::from_millis(100),
+            )
+            .unwrap();
+        assert_eq!(unchanged.work_id, block_template.work_id);
+
+        let genesis = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+        let block = gen_block(&genesis, 0, &epoch);
+        let block_hash = block.header().hash().to_owned();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            chain_controller.process_block(Arc::new(block)).unwrap();
+        });
+
+        let changed = block_assembler_controller
+            .wait_block_template(
+                None,
+                None,
+                None,
+                None,
+                &block_template.work_id,
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        handle.join().unwrap();
+        assert_ne!(changed.work_id, block_template.work_id);
+        assert_eq!(changed.parent_hash, block_hash);
+    }
 }
@@ -1,6 +1,8 @@
 use crate::{MinerConfig, Work};
 use ckb_core::block::Block;
+use ckb_util::Mutex;
 use crossbeam_channel::Sender;
+use futures::future;
 use futures::sync::{mpsc, oneshot};
 use hyper::error::Error as HyperError;
 use hyper::header::{HeaderValue, CONTENT_TYPE};
@@ -12,16 +14,25 @@ use jsonrpc_types::{
     error::Error as RpcFail, id::Id, params::Params, request::MethodCall, response::Output,
     version::Version, Block as JsonBlock,
 };
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
+use lru_cache::LruCache;
 use numext_fixed_hash::H256;
 use serde_json::error::Error as JsonError;
 use serde_json::{self, json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::thread;
-use std::time;
+use std::time::{self, Instant};
 use stop_handler::{SignalSender, StopHandler};
 
 type RpcRequest = (oneshot::Sender<Result<Chunk, RpcError>>, MethodCall);
 
+// Interval between two checks whether the primary node is back while mining
+// on a backup one
+const PRIMARY_CHECK_INTERVAL: time::Duration = time::Duration::from_secs(10);
+// Number of recently submitted block hashes kept to suppress duplicates
+const SUBMITTED_BLOCKS_SIZE: usize = 64;
+
 #[derive(Debug)]
 pub enum RpcError {
     Http(HyperError),
@@ -102,28 +113,54 @@ impl Drop for Rpc {
     }
 }
 
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    rpc: Rpc,
+}
+
+/// Fetches work from the node at `rpc_url`, failing over to the nodes of
+/// `backup_rpc_urls` in turn while it is unreachable.
 #[derive(Debug, Clone)]
 pub struct Client {
     pub current_work: Work,
     pub new_work: Sender<()>,
     pub config: MinerConfig,
-    pub rpc: Rpc,
+    endpoints: Arc<Vec<Endpoint>>,
+    // Index of the endpoint work is fetched from
+    active: Arc<AtomicUsize>,
+    // Index of the endpoint the current work came from, work ids are only
+    // meaningful to the node which issued them
+    work_source: Arc<AtomicUsize>,
+    submitted: Arc<Mutex<LruCache<H256, ()>>>,
 }
 
 impl Client {
     pub fn new(current_work: Work, new_work: Sender<()>, config: MinerConfig) -> Client {
-        let uri: Uri = config.rpc_url.parse().expect("valid rpc url");
+        let endpoints = ::std::iter::once(&config.rpc_url)
+            .chain(config.backup_rpc_urls.iter())
+            .map(|url| {
+                let uri: Uri = url.parse().expect("valid rpc url");
+                Endpoint {
+                    url: url.to_owned(),
+                    rpc: Rpc::new(uri),
+                }
+            })
+            .collect();
 
         Client {
             current_work,
-            rpc: Rpc::new(uri),
             new_work,
             config,
+            endpoints: Arc::new(endpoints),
+            active: Arc::new(AtomicUsize::new(0)),
+            work_source: Arc::new(AtomicUsize::new(0)),
+            submitted: Arc::new(Mutex::new(LruCache::new(SUBMITTED_BLOCKS_SIZE))),
         }
     }
 
     fn send_submit_block_request(
-        &self,
+        rpc: &Rpc,
         work_id: &str,
         block: &Block,
     ) -> impl Future<Item = Output, Error = RpcError> {
@@ -131,21 +168,52 @@ impl Client {
         let method = "submit_block".to_owned();
         let params = vec![json!(work_id), json!(block)];
 
-        self.rpc.request(method, params)
+        rpc.request(method, params)
     }
 
-    pub fn submit_block(&self, work_id: &str, block: &Block) {
-        let future = self.send_submit_block_request(work_id, block);
-        if self.config.block_on_submit {
-            let ret: Result<Option<H256>, RpcError> = future.and_then(parse_response).wait();
+    /// Submits the block to every node, returns whether any of them accepted
+    /// it, only known when `block_on_submit` is set.
+    ///
+    /// A block already submitted, e.g. found twice by different workers, is
+    /// not submitted again and `None` is returned.
+    pub fn submit_block(&self, work_id: &str, block: &Block) -> Option<bool> {
+        let hash = block.header().hash().to_owned();
+        {
+            let mut submitted = self.submitted.lock();
+            if submitted.contains_key(&hash) {
+                debug!(target: "miner", "skip submitting duplicate block {:#x}", hash);
+                return None;
+            }
+            submitted.insert(hash.clone(), ());
+        }
+
+        if !self.config.block_on_submit {
+            // Requests are sent right away, the responses are ignored
+            for endpoint in self.endpoints.iter() {
+                let _ = Self::send_submit_block_request(&endpoint.rpc, work_id, block);
+            }
+            return None;
+        }
+
+        let futures = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                Self::send_submit_block_request(&endpoint.rpc, work_id, block)
+                    .and_then(parse_response::<Option<H256>>)
+                    .then(Ok::<_, ()>)
+            })
+            .collect::<Vec<_>>();
+        let results = future::join_all(futures).wait().expect("infallible");
+        let mut accepted = 0;
+        for (endpoint, ret) in self.endpoints.iter().zip(results.iter()) {
             match ret {
-                Ok(hash) => {
-                    if hash.is_none() {
-                        warn!(target: "miner", "submit_block failed {}", serde_json::to_string(block).unwrap());
-                    }
+                Ok(Some(_)) => accepted += 1,
+                Ok(None) => {
+                    warn!(target: "miner", "{} rejected block {:#x}", endpoint.url, hash);
                 }
                 Err(e) => {
-                    error!(target: "miner", "rpc call submit_block error: {:?}", e);
+                    error!(target: "miner", "rpc call submit_block to {} error: {:?}", endpoint.url, e);
                     sentry::capture_message(
                         &format!("rpc call submit_block error: {:?}", e),
                         sentry::Level::Error,
@@ -153,39 +221,130 @@ impl Client {
                 }
             }
         }
+        if accepted == 0 {
+            warn!(target: "miner", "submit_block failed {}", serde_json::to_string(block).unwrap());
+        } else if accepted < results.len() {
+            // Nodes lagging behind or restarting reject it, the accepting ones
+            // relay it to them
+            info!(
+                target: "miner",
+                "block {:#x} accepted by {} of {} nodes",
+                hash,
+                accepted,
+                results.len()
+            );
+        }
+        Some(accepted > 0)
     }
 
     pub fn poll_block_template(&self) {
+        let mut primary_checked_at = Instant::now();
         loop {
+            if primary_checked_at.elapsed() >= PRIMARY_CHECK_INTERVAL {
+                self.check_primary();
+                primary_checked_at = Instant::now();
+            }
             debug!(target: "miner", "poll block template...");
-            self.try_update_block_template();
-            thread::sleep(time::Duration::from_millis(self.config.poll_interval));
+            let ret = self.update_block_template();
+            if let Err(e) = &ret {
+                error!(target: "miner", "rpc call get_block_template error: {:?}", e);
+            }
+            // A long-polling request only returns once the template has changed
+            // or the server side timeout has elapsed, so poll again right away.
+            if !self.config.long_poll || ret.is_err() {
+                thread::sleep(time::Duration::from_millis(self.config.poll_interval));
+            }
         }
     }
 
     pub fn try_update_block_template(&self) -> bool {
-        let mut updated = false;
-        match self.get_block_template().wait() {
-            Ok(new) => {
-                let mut work = self.current_work.lock();
-                if work.as_ref().map_or(true, |old| old.work_id != new.work_id) {
-                    *work = Some(new);
-                    updated = true;
-                    let _ = self.new_work.send(());
-                }
-            }
+        match self.update_block_template() {
+            Ok(updated) => updated,
             Err(e) => {
                 error!(target: "miner", "rpc call get_block_template error: {:?}", e);
+                false
             }
         }
-        updated
     }
 
-    fn get_block_template(&self) -> impl Future<Item = BlockTemplate, Error = RpcError> {
+    fn update_block_template(&self) -> Result<bool, RpcError> {
+        let active = self.active.load(Ordering::SeqCst);
+        let same_source = self.work_source.load(Ordering::SeqCst) == active;
+        let work_id = if self.config.long_poll && same_source {
+            self.current_work
+                .lock()
+                .as_ref()
+                .map(|work| work.work_id.clone())
+        } else {
+            None
+        };
+        let new = match self.get_block_template(active, work_id).wait() {
+            Ok(new) => new,
+            Err(e) => {
+                self.failover(active);
+                return Err(e);
+            }
+        };
+        let mut work = self.current_work.lock();
+        if !same_source || work.as_ref().map_or(true, |old| old.work_id != new.work_id) {
+            *work = Some(new);
+            self.work_source.store(active, Ordering::SeqCst);
+            let _ = self.new_work.send(());
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // Moves on to the next endpoint unless another thread already did
+    fn failover(&self, failed: usize) {
+        let len = self.endpoints.len();
+        if len < 2 {
+            return;
+        }
+        let next = (failed + 1) % len;
+        if self.active.compare_and_swap(failed, next, Ordering::SeqCst) == failed {
+            warn!(
+                target: "miner",
+                "node {} unreachable, failing over to {}",
+                self.endpoints[failed].url,
+                self.endpoints[next].url
+            );
+        }
+    }
+
+    // Returns to the primary node once it answers again
+    fn check_primary(&self) {
+        let active = self.active.load(Ordering::SeqCst);
+        if active == 0 {
+            return;
+        }
+        let primary = &self.endpoints[0];
+        let ret: Result<String, RpcError> = primary
+            .rpc
+            .request("get_tip_block_number".to_owned(), vec![])
+            .and_then(parse_response)
+            .wait();
+        if ret.is_ok() && self.active.compare_and_swap(active, 0, Ordering::SeqCst) == active {
+            info!(target: "miner", "node {} is back, switching to it", primary.url);
+        }
+    }
+
+    fn get_block_template(
+        &self,
+        endpoint: usize,
+        work_id: Option<String>,
+    ) -> impl Future<Item = BlockTemplate, Error = RpcError> {
         let method = "get_block_template".to_owned();
-        let params = vec![];
+        let params = match work_id {
+            Some(work_id) => vec![Value::Null, Value::Null, Value::Null, json!(work_id)],
+            None => vec![],
+        };
 
-        self.rpc.request(method, params).and_then(parse_response)
+        self.endpoints[endpoint]
+            .rpc
+            .request(method, params)
+            .and_then(parse_response)
     }
 }
 
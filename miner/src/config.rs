@@ -1,16 +1,95 @@
 use jsonrpc_types::JsonBytes;
 use numext_fixed_hash::H256;
 use serde_derive::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MinerConfig {
     pub rpc_url: String,
+    /// Nodes to fetch work from while the one at `rpc_url` is unreachable,
+    /// found blocks are submitted to all of them
+    #[serde(default)]
+    pub backup_rpc_urls: Vec<String>,
     pub poll_interval: u64,
     pub block_on_submit: bool,
+    /// Wait on the node for the block template to change instead of polling
+    /// every `poll_interval` milliseconds
+    #[serde(default)]
+    pub long_poll: bool,
+    /// Solvers to run, a single `Engine` worker if empty
+    #[serde(default)]
+    pub workers: Vec<WorkerConfig>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "worker_type")]
+pub enum WorkerConfig {
+    /// Solves with the PoW engine of the chain spec
+    Engine { threads: usize },
+    /// Runs an external solver program, see `ExternalWorker`
+    External {
+        path: PathBuf,
+        #[serde(default)]
+        args: Vec<String>,
+        batch_size: u64,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct BlockAssemblerConfig {
     pub code_hash: H256,
     pub args: Vec<JsonBytes>,
+    /// Embedded in the cellbase witness as UTF-8, `{number}` and `{epoch}` are
+    /// replaced with the block number and epoch number
+    #[serde(default)]
+    pub message: String,
+    /// Rebuilds the cached template once more than this many txs have been
+    /// staged since it was built, without waiting for the cache timeout
+    #[serde(default)]
+    pub new_txs_threshold: Option<u64>,
+    /// Rebuilds the cached template once the txs staged since it was built
+    /// pay more than this many shannons in fees
+    #[serde(default)]
+    pub new_fees_threshold: Option<u64>,
+    /// Assembles and verifies the block of every new template before handing
+    /// it out, including its transactions and scripts against the tip, so an
+    /// invalid template fails the request instead of the mined block being
+    /// rejected
+    #[serde(default)]
+    pub verify_template: bool,
+    #[serde(default)]
+    pub uncles: UnclePolicyConfig,
+}
+
+/// Which uncles the block assembler includes, miners are identified by the
+/// lock hash of the first cellbase output of their blocks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnclePolicyConfig {
+    /// Includes at most this many uncles, capped by the consensus limit
+    #[serde(default)]
+    pub max_uncles: Option<usize>,
+    /// Only uses the blocks of these miners as uncles, any miner if empty
+    #[serde(default)]
+    pub allowed_locks: Vec<H256>,
+    /// Never uses the blocks of these miners as uncles
+    #[serde(default)]
+    pub denied_locks: Vec<H256>,
+    /// Marks the uncles mined by these miners `required` in the template
+    #[serde(default)]
+    pub required_locks: Vec<H256>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StratumConfig {
+    pub listen_address: SocketAddr,
+    /// Miners connected at the same time, further connections are closed
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+}
+
+const DEFAULT_MAX_SESSIONS: usize = 256;
+
+fn default_max_sessions() -> usize {
+    DEFAULT_MAX_SESSIONS
 }
@@ -6,4 +6,8 @@ pub enum Error {
     InvalidInput,
     #[fail(display = "InvalidOutput")]
     InvalidOutput,
+    #[fail(display = "MessageTooLong")]
+    MessageTooLong,
+    #[fail(display = "Stopped")]
+    Stopped,
 }
@@ -3,12 +3,20 @@ mod client;
 mod config;
 mod error;
 mod miner;
+mod stats;
+mod stratum;
+mod worker;
 
 pub use crate::block_assembler::{BlockAssembler, BlockAssemblerController};
 pub use crate::client::Client;
-pub use crate::config::{BlockAssemblerConfig, MinerConfig};
+pub use crate::config::{
+    BlockAssemblerConfig, MinerConfig, StratumConfig, UnclePolicyConfig, WorkerConfig,
+};
 pub use crate::error::Error;
 pub use crate::miner::Miner;
+pub use crate::stats::MiningStats;
+pub use crate::stratum::Stratum;
+pub use crate::worker::{EngineWorker, ExternalWorker, Worker, WorkerController};
 use ckb_util::Mutex;
 use jsonrpc_types::BlockTemplate;
 use std::sync::Arc;
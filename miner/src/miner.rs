@@ -1,22 +1,30 @@
 use crate::client::Client;
+use crate::config::WorkerConfig;
+use crate::stats::MiningStats;
+use crate::worker::WorkerController;
 use crate::Work;
 use ckb_core::block::{Block, BlockBuilder};
-use ckb_core::header::{HeaderBuilder, RawHeader, Seal};
+use ckb_core::header::{HeaderBuilder, Seal};
 use ckb_core::{BlockNumber, EpochNumber};
 use ckb_pow::PowEngine;
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{self, select, Receiver};
 use failure::Error;
 use jsonrpc_types::{BlockTemplate, CellbaseTemplate};
 use log::{debug, error, info};
-use rand::{thread_rng, Rng};
 use std::convert::TryInto;
+use std::io;
 use std::sync::Arc;
+use std::time::Duration;
+
+const HASHRATE_REPORT_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct Miner {
-    pub pow: Arc<dyn PowEngine>,
     pub new_work_rx: Receiver<()>,
     pub current_work: Work,
     pub client: Client,
+    pub workers: WorkerController,
+    pub stats: MiningStats,
+    seal_rx: Receiver<(String, Seal)>,
 }
 
 impl Miner {
@@ -25,111 +33,156 @@ impl Miner {
         pow: Arc<dyn PowEngine>,
         new_work_rx: Receiver<()>,
         client: Client,
-    ) -> Miner {
-        Miner {
-            pow,
+    ) -> Result<Miner, io::Error> {
+        let (seal_tx, seal_rx) = crossbeam_channel::unbounded();
+        let default_workers = [WorkerConfig::Engine { threads: 1 }];
+        let configs = if client.config.workers.is_empty() {
+            &default_workers[..]
+        } else {
+            &client.config.workers[..]
+        };
+        let workers = WorkerController::start(configs, &pow, &seal_tx)?;
+
+        Ok(Miner {
             new_work_rx,
             current_work,
             client,
-        }
+            workers,
+            stats: MiningStats::new(),
+            seal_rx,
+        })
     }
+
     pub fn run(&self) {
+        let hashrate_ticker = crossbeam_channel::tick(HASHRATE_REPORT_INTERVAL);
+        // The block being mined along with its work id
+        let mut mining: Option<(String, Block)> = None;
         loop {
-            match self.mine() {
-                Ok(result) => {
-                    if let Some((work_id, block)) = result {
-                        self.client.submit_block(&work_id, &block);
-                        self.client.try_update_block_template();
+            select! {
+                recv(self.new_work_rx) -> msg => match msg {
+                    Ok(()) => mining = self.dispatch_work(),
+                    Err(_) => {
+                        error!(target: "miner", "new_work_rx closed");
+                        break;
+                    }
+                },
+                recv(self.seal_rx) -> msg => match msg {
+                    Ok((work_id, seal)) => {
+                        // Other workers may find seals for a work already submitted
+                        match &mining {
+                            Some((id, _)) if *id == work_id => {
+                                if let Some((work_id, block)) = mining.take() {
+                                    // The workers idle after a seal, resume them on the
+                                    // current work since the template may not change
+                                    if !self.submit(&work_id, block, seal) {
+                                        mining = self.dispatch_work();
+                                    }
+                                }
+                            }
+                            Some(_) => self.stats.block_stale(),
+                            None => (),
+                        }
                     }
+                    Err(_) => {
+                        error!(target: "miner", "seal_rx closed");
+                        break;
+                    }
+                },
+                recv(hashrate_ticker) -> _ => {
+                    self.workers.report_hashrate();
+                    self.stats.log();
                 }
-                Err(e) => error!(target: "miner", "mining error encountered: {:?}", e),
-            };
+            }
         }
     }
 
-    fn mine(&self) -> Result<Option<(String, Block)>, Error> {
+    fn dispatch_work(&self) -> Option<(String, Block)> {
         let current_work = { self.current_work.lock().to_owned() };
-        if let Some(template) = current_work {
-            let BlockTemplate {
-                version,
-                difficulty,
-                current_time,
-                number,
-                epoch,
-                parent_hash,
-                uncles, // Vec<UncleTemplate>
-                transactions, // Vec<TransactionTemplate>
-                proposals, // Vec<ProposalShortId>
-                cellbase, // CellbaseTemplate
-                work_id,
-                ..
-                // cycles_limit,
-                // bytes_limit,
-                // uncles_count_limit,
-            } = template;
+        match build_block(current_work?) {
+            Ok((work_id, block)) => {
+                debug!(target: "miner", "mining header #{} of work {}", block.header().number(), work_id);
+                self.workers.new_work(&work_id, block.header().raw());
+                Some((work_id, block))
+            }
+            Err(e) => {
+                error!(target: "miner", "mining error encountered: {:?}", e);
+                None
+            }
+        }
+    }
 
-            let cellbase = {
-                let CellbaseTemplate { data, .. } = cellbase;
-                data
-            };
+    /// Returns whether the node accepted the block
+    fn submit(&self, work_id: &str, block: Block, seal: Seal) -> bool {
+        info!(target: "miner", "found seal: {:?}", seal);
+        self.stats.block_solved();
+        let raw_header = block.header().raw().to_owned();
+        let block = BlockBuilder::from_block(block)
+            .header(raw_header.with_seal(seal))
+            .build();
+        self.stats.block_submitted();
+        let accepted = self.client.submit_block(work_id, &block) == Some(true);
+        if accepted {
+            self.stats.block_accepted();
+        }
+        self.client.try_update_block_template();
+        accepted
+    }
+}
 
-            let header_builder = HeaderBuilder::default()
-                .version(version)
-                .number(number.parse::<BlockNumber>()?)
-                .epoch(epoch.parse::<EpochNumber>()?)
-                .difficulty(difficulty)
-                .timestamp(current_time.parse::<u64>()?)
-                .parent_hash(parent_hash);
+/// Builds the unsealed block of a template, returns it along with the work id
+pub(crate) fn build_block(template: BlockTemplate) -> Result<(String, Block), Error> {
+    let BlockTemplate {
+        version,
+        difficulty,
+        current_time,
+        number,
+        epoch,
+        parent_hash,
+        uncles, // Vec<UncleTemplate>
+        transactions, // Vec<TransactionTemplate>
+        proposals, // Vec<ProposalShortId>
+        cellbase, // CellbaseTemplate
+        work_id,
+        ..
+        // cycles_limit,
+        // bytes_limit,
+        // uncles_count_limit,
+    } = template;
 
-            let block = BlockBuilder::from_header_builder(header_builder)
-                .uncles(
-                    uncles
-                        .into_iter()
-                        .map(TryInto::try_into)
-                        .collect::<Result<_, _>>()?,
-                )
-                .transaction(cellbase.try_into()?)
-                .transactions(
-                    transactions
-                        .into_iter()
-                        .map(TryInto::try_into)
-                        .collect::<Result<_, _>>()?,
-                )
-                .proposals(
-                    proposals
-                        .into_iter()
-                        .map(TryInto::try_into)
-                        .collect::<Result<_, _>>()?,
-                )
-                .build();
+    let cellbase = {
+        let CellbaseTemplate { data, .. } = cellbase;
+        data
+    };
 
-            let raw_header = block.header().raw().to_owned();
+    let header_builder = HeaderBuilder::default()
+        .version(version)
+        .number(number.parse::<BlockNumber>()?)
+        .epoch(epoch.parse::<EpochNumber>()?)
+        .difficulty(difficulty)
+        .timestamp(current_time.parse::<u64>()?)
+        .parent_hash(parent_hash);
 
-            Ok(self
-                .mine_loop(&raw_header)
-                .map(|seal| {
-                    BlockBuilder::from_block(block)
-                        .header(raw_header.with_seal(seal))
-                        .build()
-                })
-                .map(|block| (work_id, block)))
-        } else {
-            Ok(None)
-        }
-    }
+    let block = BlockBuilder::from_header_builder(header_builder)
+        .uncles(
+            uncles
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        )
+        .transaction(cellbase.try_into()?)
+        .transactions(
+            transactions
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        )
+        .proposals(
+            proposals
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        )
+        .build();
 
-    fn mine_loop(&self, header: &RawHeader) -> Option<Seal> {
-        let mut nonce: u64 = thread_rng().gen();
-        loop {
-            if self.new_work_rx.try_recv().is_ok() {
-                break None;
-            }
-            debug!(target: "miner", "mining header #{} with nonce {}", header.number(), nonce);
-            if let Some(seal) = self.pow.solve_header(header, nonce) {
-                info!(target: "miner", "found seal: {:?}", seal);
-                break Some(seal);
-            }
-            nonce = nonce.wrapping_add(1);
-        }
-    }
+    Ok((work_id, block))
 }
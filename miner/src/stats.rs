@@ -0,0 +1,112 @@
+use ckb_util::Mutex;
+use jsonrpc_types::MiningInfo;
+use log::info;
+use numext_fixed_uint::U256;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Mining counters, collected by the block assembler and the submit_block RPC
+/// on the node side, and by the `Miner` on the client side.
+#[derive(Default)]
+pub struct MiningStats {
+    blocks_solved: AtomicU64,
+    blocks_submitted: AtomicU64,
+    blocks_accepted: AtomicU64,
+    blocks_stale: AtomicU64,
+    templates_generated: AtomicU64,
+    template_latency: AtomicU64,
+    network_hashrate: Mutex<U256>,
+}
+
+impl MiningStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn block_solved(&self) {
+        self.blocks_solved.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn block_submitted(&self) {
+        self.blocks_submitted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn block_accepted(&self) {
+        self.blocks_accepted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a block which no longer extends the tip, or a seal found for a
+    /// work that had already been replaced
+    pub fn block_stale(&self) {
+        self.blocks_stale.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn template_generated(&self, latency: Duration) {
+        let millis = latency.as_secs() * 1000 + u64::from(latency.subsec_millis());
+        self.templates_generated.fetch_add(1, Ordering::Relaxed);
+        self.template_latency.fetch_add(millis, Ordering::Relaxed);
+    }
+
+    /// Sets the network hashrate, in difficulty per second
+    pub fn set_network_hashrate(&self, hashrate: U256) {
+        *self.network_hashrate.lock() = hashrate;
+    }
+
+    pub fn info(&self) -> MiningInfo {
+        let templates_generated = self.templates_generated.load(Ordering::Relaxed);
+        let average_template_latency = self
+            .template_latency
+            .load(Ordering::Relaxed)
+            .checked_div(templates_generated)
+            .unwrap_or(0);
+        MiningInfo {
+            blocks_solved: self.blocks_solved.load(Ordering::Relaxed).to_string(),
+            blocks_submitted: self.blocks_submitted.load(Ordering::Relaxed).to_string(),
+            blocks_accepted: self.blocks_accepted.load(Ordering::Relaxed).to_string(),
+            blocks_stale: self.blocks_stale.load(Ordering::Relaxed).to_string(),
+            templates_generated: templates_generated.to_string(),
+            average_template_latency: average_template_latency.to_string(),
+            network_hashrate: self.network_hashrate.lock().clone(),
+        }
+    }
+
+    pub fn log(&self) {
+        let info = self.info();
+        info!(
+            target: "miner",
+            "solved {} blocks, submitted {}, accepted {}, stale {}",
+            info.blocks_solved,
+            info.blocks_submitted,
+            info.blocks_accepted,
+            info.blocks_stale
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mining_stats() {
+        let stats = MiningStats::new();
+        stats.block_solved();
+        stats.block_submitted();
+        stats.block_accepted();
+        stats.block_submitted();
+        stats.block_stale();
+        stats.block_stale();
+        stats.template_generated(Duration::from_millis(10));
+        stats.template_generated(Duration::from_millis(30));
+        stats.set_network_hashrate(U256::from(42u64));
+
+        let info = stats.info();
+        assert_eq!(info.blocks_solved, "1");
+        assert_eq!(info.blocks_submitted, "2");
+        assert_eq!(info.blocks_accepted, "1");
+        assert_eq!(info.blocks_stale, "2");
+        assert_eq!(info.templates_generated, "2");
+        assert_eq!(info.average_template_latency, "20");
+        assert_eq!(info.network_hashrate, U256::from(42u64));
+    }
+}
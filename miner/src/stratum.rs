@@ -0,0 +1,594 @@
+//! A Stratum-like mining server, pools hand out work derived from the block
+//! assembler templates to their miners without running a miner client each.
+//!
+//! Messages are line-delimited JSON over TCP:
+//!
+//! ```text
+//! -> {"id": 1, "method": "mining.subscribe", "params": []}
+//! <- {"id": 1, "result": [null, "00000001", 4], "error": null}
+//! -> {"id": 2, "method": "mining.authorize", "params": ["worker1", "x"]}
+//! <- {"id": 2, "result": true, "error": null}
+//! <- {"id": null, "method": "mining.notify", "params": [job_id, pow_hash, number, difficulty, true]}
+//! -> {"id": 3, "method": "mining.submit", "params": ["worker1", job_id, "0000abcd", "0x..."]}
+//! <- {"id": 3, "result": true, "error": null}
+//! ```
+//!
+//! Every connection is assigned a 4 bytes extranonce which is the high half of
+//! the header nonce, miners search the low half and submit it with the proof.
+//!
+//! Messages to a miner go through a bounded per-session queue drained by the
+//! session writer thread, a miner not reading its messages is disconnected
+//! instead of stalling the job broadcast.
+
+use crate::config::StratumConfig;
+use crate::miner::build_block;
+use crate::BlockAssemblerController;
+use ckb_chain::chain::ChainController;
+use ckb_core::block::{Block, BlockBuilder};
+use ckb_core::header::Seal;
+use ckb_pow::PowEngine;
+use ckb_util::Mutex;
+use crossbeam_channel::{bounded, Sender};
+use fnv::{FnvHashMap, FnvHashSet};
+use jsonrpc_types::JsonBytes;
+use log::{debug, error, info, warn};
+use lru_cache::LruCache;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const MAX_JOBS: usize = 8;
+const EXTRANONCE2_SIZE: usize = 4;
+const JOB_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+const JOB_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+// Longest request line accepted from a miner
+const MAX_LINE_BYTES: usize = 16 * 1024;
+// Messages queued for a session before it is considered stalled
+const SESSION_QUEUE_SIZE: usize = 64;
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Error codes of the Stratum protocol
+const ERROR_OTHER: i64 = 20;
+const ERROR_JOB_NOT_FOUND: i64 = 21;
+const ERROR_DUPLICATE_SHARE: i64 = 22;
+const ERROR_LOW_DIFFICULTY: i64 = 23;
+const ERROR_UNAUTHORIZED: i64 = 24;
+const ERROR_NOT_SUBSCRIBED: i64 = 25;
+
+type StratumResult = Result<Value, (i64, &'static str)>;
+type OnBlock = Box<dyn Fn(&Arc<Block>) + Send + Sync>;
+
+#[derive(Clone)]
+struct Writer {
+    sender: Sender<Vec<u8>>,
+    // shut down to disconnect a stalled session
+    stream: Arc<TcpStream>,
+}
+
+struct Job {
+    block: Block,
+    // nonces of the accepted solutions
+    nonces: FnvHashSet<u64>,
+}
+
+struct Session {
+    extranonce1: u32,
+    subscribed: bool,
+    worker: Option<String>,
+}
+
+struct StratumState {
+    block_assembler: BlockAssemblerController,
+    chain: ChainController,
+    pow: Arc<dyn PowEngine>,
+    on_block: Option<OnBlock>,
+    jobs: Mutex<LruCache<String, Job>>,
+    current_job: Mutex<Option<String>>,
+    subscribers: Mutex<FnvHashMap<u32, Writer>>,
+    next_extranonce1: AtomicU32,
+    sessions: AtomicUsize,
+}
+
+pub struct Stratum {
+    config: StratumConfig,
+    state: StratumState,
+}
+
+impl Stratum {
+    pub fn new(
+        config: StratumConfig,
+        block_assembler: BlockAssemblerController,
+        chain: ChainController,
+        pow: Arc<dyn PowEngine>,
+    ) -> Self {
+        Stratum {
+            config,
+            state: StratumState {
+                block_assembler,
+                chain,
+                pow,
+                on_block: None,
+                jobs: Mutex::new(LruCache::new(MAX_JOBS)),
+                current_job: Mutex::new(None),
+                subscribers: Mutex::new(FnvHashMap::default()),
+                next_extranonce1: AtomicU32::new(0),
+                sessions: AtomicUsize::new(0),
+            },
+        }
+    }
+
+    /// Called with every block found by a connected miner once the chain has
+    /// accepted it, e.g. to announce it to peers.
+    pub fn on_block<F>(mut self, on_block: F) -> Self
+    where
+        F: Fn(&Arc<Block>) + Send + Sync + 'static,
+    {
+        self.state.on_block = Some(Box::new(on_block));
+        self
+    }
+
+    /// Binds the listen address and serves miners in background threads,
+    /// returns the bound address.
+    pub fn start(self) -> Result<SocketAddr, io::Error> {
+        let listener = TcpListener::bind(self.config.listen_address)?;
+        let local_addr = listener.local_addr()?;
+        let max_sessions = self.config.max_sessions;
+        let state = Arc::new(self.state);
+
+        let job_state = Arc::clone(&state);
+        thread::Builder::new()
+            .name("StratumJobs".to_string())
+            .spawn(move || job_state.update_jobs())?;
+        thread::Builder::new()
+            .name("StratumServer".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            if state.sessions.fetch_add(1, Ordering::SeqCst) >= max_sessions {
+                                state.sessions.fetch_sub(1, Ordering::SeqCst);
+                                debug!(target: "miner", "stratum sessions limit {} reached", max_sessions);
+                                continue;
+                            }
+                            let session_state = Arc::clone(&state);
+                            let ret = thread::Builder::new()
+                                .name("StratumSession".to_string())
+                                .spawn(move || {
+                                    if let Err(err) = session_state.serve(stream) {
+                                        debug!(target: "miner", "stratum session closed: {}", err);
+                                    }
+                                    session_state.sessions.fetch_sub(1, Ordering::SeqCst);
+                                });
+                            if let Err(err) = ret {
+                                state.sessions.fetch_sub(1, Ordering::SeqCst);
+                                error!(target: "miner", "spawn stratum session error: {}", err);
+                            }
+                        }
+                        Err(err) => warn!(target: "miner", "stratum accept error: {}", err),
+                    }
+                }
+            })?;
+
+        info!(target: "miner", "stratum server listening on {}", local_addr);
+        Ok(local_addr)
+    }
+}
+
+impl StratumState {
+    fn update_jobs(&self) {
+        let mut work_id = String::new();
+        loop {
+            let template = match self.block_assembler.wait_block_template(
+                None,
+                None,
+                None,
+                None,
+                &work_id,
+                JOB_POLL_TIMEOUT,
+            ) {
+                Ok(template) => template,
+                Err(err) => {
+                    error!(target: "miner", "stratum get_block_template error: {:?}", err);
+                    thread::sleep(JOB_RETRY_INTERVAL);
+                    continue;
+                }
+            };
+            if template.work_id == work_id {
+                continue;
+            }
+            work_id = template.work_id.clone();
+            match build_block(template) {
+                Ok((job_id, block)) => self.new_job(job_id, block),
+                Err(err) => error!(target: "miner", "stratum build job error: {:?}", err),
+            }
+        }
+    }
+
+    fn new_job(&self, job_id: String, block: Block) {
+        debug!(target: "miner", "stratum new job {} #{}", job_id, block.header().number());
+        let notify = notify_message(&job_id, &block);
+        self.jobs.lock().insert(
+            job_id.clone(),
+            Job {
+                block,
+                nonces: FnvHashSet::default(),
+            },
+        );
+        *self.current_job.lock() = Some(job_id);
+        // Drop the subscribers whose connection has gone or which stalled,
+        // queuing never blocks so the lock is held briefly
+        self.subscribers.lock().retain(|_, writer| {
+            let ret = send(writer, &notify);
+            if ret.is_err() {
+                let _ = writer.stream.shutdown(Shutdown::Both);
+            }
+            ret.is_ok()
+        });
+    }
+
+    fn current_notify(&self) -> Option<Value> {
+        let current_job = self.current_job.lock().clone()?;
+        let mut jobs = self.jobs.lock();
+        jobs.get_mut(&current_job)
+            .map(|job| notify_message(&current_job, &job.block))
+    }
+
+    fn serve(&self, stream: TcpStream) -> Result<(), io::Error> {
+        let peer = stream.peer_addr()?;
+        let writer = spawn_writer(&stream)?;
+        let mut session = Session {
+            extranonce1: self.next_extranonce1.fetch_add(1, Ordering::SeqCst),
+            subscribed: false,
+            worker: None,
+        };
+        debug!(target: "miner", "stratum session {:08x} from {}", session.extranonce1, peer);
+
+        let ret = self.serve_requests(BufReader::new(stream), &writer, &mut session);
+        self.subscribers.lock().remove(&session.extranonce1);
+        let _ = writer.stream.shutdown(Shutdown::Both);
+        ret
+    }
+
+    fn serve_requests(
+        &self,
+        mut reader: BufReader<TcpStream>,
+        writer: &Writer,
+        session: &mut Session,
+    ) -> Result<(), io::Error> {
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let read = (&mut reader)
+                .take(MAX_LINE_BYTES as u64 + 1)
+                .read_until(b'\n', &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            if buf.len() > MAX_LINE_BYTES && buf.last() != Some(&b'\n') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stratum request line too long",
+                ));
+            }
+            let line = String::from_utf8_lossy(&buf);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => request,
+                Err(_) => {
+                    send(
+                        writer,
+                        &response(Value::Null, Err((ERROR_OTHER, "Parse error"))),
+                    )?;
+                    continue;
+                }
+            };
+            let id = request.get("id").cloned().unwrap_or(Value::Null);
+            let params = request
+                .get("params")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            match request.get("method").and_then(Value::as_str) {
+                Some("mining.subscribe") => {
+                    send(writer, &response(id, self.subscribe(session)))?;
+                    // Hand out the current job right away, registering under the
+                    // lock so no job broadcast in between is missed.
+                    let mut subscribers = self.subscribers.lock();
+                    if let Some(notify) = self.current_notify() {
+                        send(writer, &notify)?;
+                    }
+                    subscribers.insert(session.extranonce1, writer.clone());
+                }
+                Some("mining.authorize") => {
+                    send(writer, &response(id, authorize(session, params)))?;
+                }
+                Some("mining.submit") => {
+                    send(writer, &response(id, self.submit(session, params)))?;
+                }
+                _ => {
+                    send(
+                        writer,
+                        &response(id, Err((ERROR_OTHER, "Method not found"))),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn subscribe(&self, session: &mut Session) -> StratumResult {
+        session.subscribed = true;
+        Ok(json!([
+            Value::Null,
+            format!("{:08x}", session.extranonce1),
+            EXTRANONCE2_SIZE
+        ]))
+    }
+
+    fn submit(&self, session: &Session, params: &[Value]) -> StratumResult {
+        if !session.subscribed {
+            return Err((ERROR_NOT_SUBSCRIBED, "Not subscribed"));
+        }
+        let worker = params.get(0).and_then(Value::as_str);
+        if worker.is_none() || worker != session.worker.as_ref().map(String::as_str) {
+            return Err((ERROR_UNAUTHORIZED, "Unauthorized worker"));
+        }
+        let job_id = params
+            .get(1)
+            .and_then(Value::as_str)
+            .ok_or((ERROR_OTHER, "Invalid job id"))?;
+        let nonce2 = params
+            .get(2)
+            .and_then(Value::as_str)
+            .and_then(|nonce2| u32::from_str_radix(nonce2.trim_start_matches("0x"), 16).ok())
+            .ok_or((ERROR_OTHER, "Invalid nonce"))?;
+        let proof = params
+            .get(3)
+            .cloned()
+            .and_then(|proof| serde_json::from_value::<JsonBytes>(proof).ok())
+            .ok_or((ERROR_OTHER, "Invalid proof"))?;
+        let nonce = (u64::from(session.extranonce1) << 32) | u64::from(nonce2);
+
+        let block = {
+            let mut jobs = self.jobs.lock();
+            let job = jobs
+                .get_mut(job_id)
+                .ok_or((ERROR_JOB_NOT_FOUND, "Job not found"))?;
+            if job.nonces.contains(&nonce) {
+                return Err((ERROR_DUPLICATE_SHARE, "Duplicate share"));
+            }
+            let header = job
+                .block
+                .header()
+                .raw()
+                .to_owned()
+                .with_seal(Seal::new(nonce, proof.into_vec()));
+            if !self.pow.verify_header(&header) {
+                return Err((ERROR_LOW_DIFFICULTY, "Low difficulty share"));
+            }
+            job.nonces.insert(nonce);
+            Arc::new(
+                BlockBuilder::from_block(job.block.clone())
+                    .header(header)
+                    .build(),
+            )
+        };
+
+        let stats = self.block_assembler.mining_stats();
+        stats.block_submitted();
+        if self.current_job.lock().as_ref().map(String::as_str) != Some(job_id) {
+            stats.block_stale();
+        }
+        match self.chain.process_block(Arc::clone(&block)) {
+            Ok(()) => {
+                stats.block_accepted();
+                info!(
+                    target: "miner",
+                    "stratum worker {} found block #{} {:#x}",
+                    session.worker.as_ref().map(String::as_str).unwrap_or_default(),
+                    block.header().number(),
+                    block.header().hash()
+                );
+                if let Some(on_block) = &self.on_block {
+                    on_block(&block);
+                }
+                Ok(Value::Bool(true))
+            }
+            Err(err) => {
+                error!(target: "miner", "stratum process_block error: {:?}", err);
+                Err((ERROR_OTHER, "Block rejected"))
+            }
+        }
+    }
+}
+
+// No credentials are configured, any worker name is accepted
+fn authorize(session: &mut Session, params: &[Value]) -> StratumResult {
+    let worker = params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or((ERROR_OTHER, "Invalid worker name"))?;
+    session.worker = Some(worker.to_owned());
+    Ok(Value::Bool(true))
+}
+
+fn notify_message(job_id: &str, block: &Block) -> Value {
+    let header = block.header();
+    json!({
+        "id": Value::Null,
+        "method": "mining.notify",
+        "params": [
+            job_id,
+            header.pow_hash(),
+            header.number().to_string(),
+            header.difficulty(),
+            true
+        ],
+    })
+}
+
+fn response(id: Value, result: StratumResult) -> Value {
+    match result {
+        Ok(result) => json!({ "id": id, "result": result, "error": Value::Null }),
+        Err((code, message)) => json!({
+            "id": id,
+            "result": Value::Null,
+            "error": [code, message, Value::Null],
+        }),
+    }
+}
+
+// Queues the message for the session writer thread, fails if the session is
+// gone or has not drained its queue
+fn send(writer: &Writer, message: &Value) -> Result<(), io::Error> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer
+        .sender
+        .try_send(line)
+        .map_err(|_| io::Error::new(io::ErrorKind::WouldBlock, "stratum session stalled"))
+}
+
+// The thread exits once every sender of the session is dropped or a write
+// fails, which also shuts the connection down
+fn spawn_writer(stream: &TcpStream) -> Result<Writer, io::Error> {
+    let mut output = stream.try_clone()?;
+    output.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let (sender, receiver) = bounded::<Vec<u8>>(SESSION_QUEUE_SIZE);
+    thread::Builder::new()
+        .name("StratumWriter".to_string())
+        .spawn(move || {
+            for line in receiver {
+                if output.write_all(&line).is_err() {
+                    let _ = output.shutdown(Shutdown::Both);
+                    break;
+                }
+            }
+        })?;
+    Ok(Writer {
+        sender,
+        stream: Arc::new(stream.try_clone()?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockAssembler, BlockAssemblerConfig};
+    use ckb_chain::chain::ChainBuilder;
+    use ckb_db::memorydb::MemoryKeyValueDB;
+    use ckb_notify::NotifyService;
+    use ckb_shared::shared::SharedBuilder;
+    use ckb_traits::ChainProvider;
+
+    // Skips the job notifications which may arrive before the response
+    fn request(stream: &mut TcpStream, reader: &mut BufReader<TcpStream>, line: &str) -> Value {
+        stream.write_all(line.as_bytes()).unwrap();
+        stream.write_all(b"\n").unwrap();
+        loop {
+            let message = read(reader);
+            if message["method"] != json!("mining.notify") {
+                break message;
+            }
+        }
+    }
+
+    fn read(reader: &mut BufReader<TcpStream>) -> Value {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_stratum_submit() {
+        let shared = SharedBuilder::<MemoryKeyValueDB>::new().build().unwrap();
+        let notify = NotifyService::default().start::<&str>(None);
+        let chain_controller = ChainBuilder::new(shared.clone(), notify.clone())
+            .verification(false)
+            .build()
+            .start::<&str>(None);
+        let config = BlockAssemblerConfig::default();
+        let block_assembler =
+            BlockAssembler::new(shared.clone(), config).start::<&str>(None, &notify);
+
+        let config = StratumConfig {
+            listen_address: "127.0.0.1:0".parse().unwrap(),
+            max_sessions: 1,
+        };
+        let stratum = Stratum::new(
+            config,
+            block_assembler,
+            chain_controller,
+            shared.consensus().pow_engine(),
+        );
+        let addr = stratum.start().unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let ret = request(
+            &mut stream,
+            &mut reader,
+            r#"{"id": 1, "method": "mining.submit", "params": ["worker1", "0", "00000000", "0x"]}"#,
+        );
+        assert_eq!(ret["error"][0], json!(ERROR_NOT_SUBSCRIBED));
+
+        let ret = request(
+            &mut stream,
+            &mut reader,
+            r#"{"id": 2, "method": "mining.subscribe", "params": []}"#,
+        );
+        assert_eq!(ret["result"], json!([Value::Null, "00000000", 4]));
+        let notify = read(&mut reader);
+        assert_eq!(notify["method"], json!("mining.notify"));
+        assert_eq!(notify["params"][2], json!("1"));
+        let job_id = notify["params"][0].as_str().unwrap().to_owned();
+
+        let ret = request(
+            &mut stream,
+            &mut reader,
+            r#"{"id": 3, "method": "mining.authorize", "params": ["worker1", "x"]}"#,
+        );
+        assert_eq!(ret["result"], json!(true));
+
+        let submit = json!({
+            "id": 4,
+            "method": "mining.submit",
+            "params": ["worker1", job_id, "0000abcd", "0x"],
+        })
+        .to_string();
+        let ret = request(&mut stream, &mut reader, &submit);
+        assert_eq!(ret["result"], json!(true));
+        assert_eq!(shared.chain_state().lock().tip_number(), 1);
+
+        let ret = request(&mut stream, &mut reader, &submit);
+        assert_eq!(ret["error"][0], json!(ERROR_DUPLICATE_SHARE));
+
+        let ret = request(
+            &mut stream,
+            &mut reader,
+            r#"{"id": 5, "method": "mining.submit", "params": ["worker1", "unknown", "00000000", "0x"]}"#,
+        );
+        assert_eq!(ret["error"][0], json!(ERROR_JOB_NOT_FOUND));
+
+        // the sessions limit is reached, other miners are disconnected
+        let other = TcpStream::connect(addr).unwrap();
+        let mut line = String::new();
+        assert_eq!(BufReader::new(other).read_line(&mut line).unwrap(), 0);
+
+        // an overlong request line closes the session
+        let _ = stream.write_all(&vec![b' '; MAX_LINE_BYTES + 1]);
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+        }
+    }
+}
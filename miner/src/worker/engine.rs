@@ -0,0 +1,23 @@
+use super::Worker;
+use ckb_core::header::{RawHeader, Seal};
+use ckb_pow::PowEngine;
+use std::sync::Arc;
+
+/// Solves with the PoW engine of the chain spec, e.g. Dummy or Cuckoo
+pub struct EngineWorker {
+    pow: Arc<dyn PowEngine>,
+}
+
+impl EngineWorker {
+    pub fn new(pow: Arc<dyn PowEngine>) -> Self {
+        EngineWorker { pow }
+    }
+}
+
+impl Worker for EngineWorker {
+    fn solve(&mut self, header: &RawHeader, nonce: u64, count: u64) -> Option<Seal> {
+        (0..count)
+            .map(|i| nonce.wrapping_add(i))
+            .find_map(|nonce| self.pow.solve_header(header, nonce))
+    }
+}
@@ -0,0 +1,131 @@
+use super::Worker;
+use ckb_core::header::{RawHeader, Seal};
+use jsonrpc_types::JsonBytes;
+use log::error;
+use serde_derive::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Serialize)]
+struct SolveRequest {
+    pow_hash: String,
+    number: String,
+    difficulty: String,
+    nonce: String,
+    count: String,
+}
+
+#[derive(Deserialize)]
+struct SolveResponse {
+    nonce: String,
+    proof: JsonBytes,
+}
+
+/// Delegates solving to an external program, e.g. a GPU solver.
+///
+/// The worker writes one JSON request per line to the program's stdin:
+///
+/// ```text
+/// {"pow_hash": "0x...", "number": "1", "difficulty": "0x100", "nonce": "42", "count": "100000"}
+/// ```
+///
+/// and reads one line back, either `null` when none of the `count` nonces
+/// starting from `nonce` solves the header, or the found seal:
+///
+/// ```text
+/// {"nonce": "1042", "proof": "0x..."}
+/// ```
+pub struct ExternalWorker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    batch_size: u64,
+}
+
+impl ExternalWorker {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        args: &[String],
+        batch_size: u64,
+    ) -> Result<Self, io::Error> {
+        let mut child = Command::new(path.as_ref())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+        Ok(ExternalWorker {
+            child,
+            stdin,
+            stdout,
+            batch_size,
+        })
+    }
+
+    fn request(
+        &mut self,
+        header: &RawHeader,
+        nonce: u64,
+        count: u64,
+    ) -> Result<Option<Seal>, io::Error> {
+        let request = SolveRequest {
+            pow_hash: format!("{:#x}", header.pow_hash()),
+            number: header.number().to_string(),
+            difficulty: format!("{:#x}", header.difficulty()),
+            nonce: nonce.to_string(),
+            count: count.to_string(),
+        };
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        self.stdin.write_all(&line)?;
+        self.stdin.flush()?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "solver exited",
+            ));
+        }
+        let response: Option<SolveResponse> = serde_json::from_str(&line)?;
+        match response {
+            Some(SolveResponse { nonce, proof }) => {
+                let nonce = nonce
+                    .parse::<u64>()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                Ok(Some(Seal::new(nonce, proof.into_vec())))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl Worker for ExternalWorker {
+    fn solve(&mut self, header: &RawHeader, nonce: u64, count: u64) -> Option<Seal> {
+        match self.request(header, nonce, count) {
+            Ok(seal) => seal,
+            Err(err) => {
+                error!(target: "miner", "external worker error: {}", err);
+                // Do not spin on a broken solver
+                thread::sleep(ERROR_BACKOFF);
+                None
+            }
+        }
+    }
+
+    fn batch_size(&self) -> u64 {
+        self.batch_size
+    }
+}
+
+impl Drop for ExternalWorker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
@@ -0,0 +1,204 @@
+mod engine;
+mod external;
+
+pub use self::engine::EngineWorker;
+pub use self::external::ExternalWorker;
+
+use crate::config::WorkerConfig;
+use ckb_core::header::{RawHeader, Seal};
+use ckb_pow::PowEngine;
+use ckb_util::Mutex;
+use crossbeam_channel::{self, Receiver, Sender, TryRecvError};
+use log::{error, info};
+use rand::{thread_rng, Rng};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+/// A PoW solver, each worker runs in its own thread of the `WorkerController`.
+pub trait Worker: Send {
+    /// Tries `count` nonces on `header` starting from `nonce`, returns the
+    /// first seal solving it.
+    fn solve(&mut self, header: &RawHeader, nonce: u64, count: u64) -> Option<Seal>;
+
+    /// Number of nonces tried between two checks for new work
+    fn batch_size(&self) -> u64 {
+        1
+    }
+}
+
+// The work id and the header to solve
+type Job = (String, RawHeader);
+
+struct WorkerHandle {
+    name: String,
+    sender: Sender<Job>,
+    attempts: Arc<AtomicU64>,
+}
+
+/// Fans work out to the worker threads and collects the seals they find.
+pub struct WorkerController {
+    handles: Vec<WorkerHandle>,
+    last_report: Mutex<(Instant, Vec<u64>)>,
+}
+
+impl WorkerController {
+    /// Starts the workers described by `configs`, found seals are sent to
+    /// `seal_sender` along with the work id.
+    pub fn start(
+        configs: &[WorkerConfig],
+        pow: &Arc<dyn PowEngine>,
+        seal_sender: &Sender<(String, Seal)>,
+    ) -> Result<Self, io::Error> {
+        let mut workers: Vec<(String, Box<dyn Worker>)> = Vec::new();
+        for (index, config) in configs.iter().enumerate() {
+            match config {
+                WorkerConfig::Engine { threads } => {
+                    for number in 0..*threads {
+                        workers.push((
+                            format!("engine-{}-{}", index, number),
+                            Box::new(EngineWorker::new(Arc::clone(pow))),
+                        ));
+                    }
+                }
+                WorkerConfig::External {
+                    path,
+                    args,
+                    batch_size,
+                } => {
+                    workers.push((
+                        format!("external-{}", index),
+                        Box::new(ExternalWorker::new(path, args, *batch_size)?),
+                    ));
+                }
+            }
+        }
+
+        let mut handles = Vec::with_capacity(workers.len());
+        for (name, worker) in workers {
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            let attempts = Arc::new(AtomicU64::new(0));
+            let worker_attempts = Arc::clone(&attempts);
+            let seal_sender = seal_sender.clone();
+            thread::Builder::new()
+                .name(name.clone())
+                .spawn(move || run_worker(worker, &receiver, &seal_sender, &worker_attempts))?;
+            handles.push(WorkerHandle {
+                name,
+                sender,
+                attempts,
+            });
+        }
+
+        let last_report = Mutex::new((Instant::now(), vec![0; handles.len()]));
+        Ok(WorkerController {
+            handles,
+            last_report,
+        })
+    }
+
+    pub fn new_work(&self, work_id: &str, header: &RawHeader) {
+        for handle in &self.handles {
+            let _ = handle.sender.send((work_id.to_owned(), header.to_owned()));
+        }
+    }
+
+    /// Logs and returns the hashrate of every worker since the last report
+    pub fn report_hashrate(&self) -> Vec<(String, f64)> {
+        let mut last_report = self.last_report.lock();
+        let now = Instant::now();
+        let elapsed = now.duration_since(last_report.0);
+        let seconds = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_millis()) / 1000.0;
+        let mut hashrates = Vec::with_capacity(self.handles.len());
+        for (handle, last) in self.handles.iter().zip(last_report.1.iter_mut()) {
+            let attempts = handle.attempts.load(Ordering::Relaxed);
+            let hashrate = if seconds > 0.0 {
+                (attempts - *last) as f64 / seconds
+            } else {
+                0.0
+            };
+            *last = attempts;
+            info!(target: "miner", "worker {} hashrate: {:.2} nonces/s", handle.name, hashrate);
+            hashrates.push((handle.name.clone(), hashrate));
+        }
+        last_report.0 = now;
+        hashrates
+    }
+}
+
+fn run_worker(
+    mut worker: Box<dyn Worker>,
+    receiver: &Receiver<Job>,
+    seal_sender: &Sender<(String, Seal)>,
+    attempts: &AtomicU64,
+) {
+    let mut work: Option<Job> = None;
+    let mut nonce: u64 = 0;
+    loop {
+        // Block while idle, otherwise only check whether the work has changed
+        let new_work = if work.is_some() {
+            match receiver.try_recv() {
+                Ok(new_work) => Some(new_work),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        } else {
+            match receiver.recv() {
+                Ok(new_work) => Some(new_work),
+                Err(_) => break,
+            }
+        };
+        if new_work.is_some() {
+            work = new_work;
+            nonce = thread_rng().gen();
+        }
+
+        if let Some((work_id, header)) = &work {
+            let count = worker.batch_size();
+            let seal = worker.solve(header, nonce, count);
+            attempts.fetch_add(count, Ordering::Relaxed);
+            nonce = nonce.wrapping_add(count);
+            if let Some(seal) = seal {
+                if seal_sender.send((work_id.to_owned(), seal)).is_err() {
+                    error!(target: "miner", "seal receiver closed");
+                    break;
+                }
+                // Idle until the next work, the found seal is being submitted
+                work = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_core::header::HeaderBuilder;
+    use ckb_pow::{DummyPowEngine, DummyPowParams};
+
+    #[test]
+    fn test_worker_controller() {
+        let params: DummyPowParams =
+            serde_json::from_str(r#"{"delay": {"type": "constant", "value": 0}}"#).unwrap();
+        let pow: Arc<dyn PowEngine> = Arc::new(DummyPowEngine::new(params));
+        let (seal_sender, seal_receiver) = crossbeam_channel::unbounded();
+        let configs = vec![WorkerConfig::Engine { threads: 2 }];
+        let controller = WorkerController::start(&configs, &pow, &seal_sender).unwrap();
+
+        let header = HeaderBuilder::default().number(1).build();
+        controller.new_work("1", header.raw());
+        let (work_id, _seal) = seal_receiver.recv().unwrap();
+        assert_eq!(work_id, "1");
+
+        let hashrates = controller.report_hashrate();
+        assert_eq!(
+            hashrates
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["engine-0-0".to_owned(), "engine-0-1".to_owned()]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::errors::PeerError;
+use crate::ProtocolId;
+use ckb_util::Mutex;
+use fnv::FnvHashMap;
+use p2p::{bytes::Bytes, SessionId};
+use std::cmp;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Messages held back by the upload throttle of a peer, newer messages are
+/// dropped beyond this size
+pub const MAX_QUEUED_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Traffic {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl Traffic {
+    fn add_sent(&mut self, len: usize) {
+        self.bytes_sent = self.bytes_sent.saturating_add(len as u64);
+    }
+
+    fn add_received(&mut self, len: usize) {
+        self.bytes_received = self.bytes_received.saturating_add(len as u64);
+    }
+}
+
+/// Traffic of a connected peer, in total and by protocol
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerTraffic {
+    pub total: Traffic,
+    pub protocols: FnvHashMap<ProtocolId, Traffic>,
+}
+
+// Token bucket of an upload rate in bytes per second, holding up to one
+// second of the rate. A message is sent while the bucket is not empty, the
+// bucket may go below zero, so a message larger than the rate still goes.
+struct UploadBucket {
+    rate: u64,
+    tokens: i64,
+    updated_at: Instant,
+}
+
+impl UploadBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        UploadBucket {
+            rate,
+            tokens: rate as i64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if now <= self.updated_at {
+            return;
+        }
+        let elapsed = now - self.updated_at;
+        let micros = elapsed.as_secs() * 1_000_000 + u64::from(elapsed.subsec_micros());
+        let tokens = self.rate.saturating_mul(micros) / 1_000_000;
+        if tokens > 0 {
+            self.tokens = cmp::min(self.rate as i64, self.tokens.saturating_add(tokens as i64));
+            self.updated_at = now;
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.tokens > 0
+    }
+
+    fn take(&mut self, len: usize) {
+        self.tokens = self.tokens.saturating_sub(len as i64);
+    }
+}
+
+fn is_available(bucket: &mut Option<UploadBucket>, now: Instant) -> bool {
+    match bucket {
+        Some(bucket) => {
+            bucket.refill(now);
+            bucket.is_available()
+        }
+        None => true,
+    }
+}
+
+#[derive(Default)]
+struct PeerBandwidth {
+    traffic: PeerTraffic,
+    bucket: Option<UploadBucket>,
+    queue: VecDeque<(ProtocolId, Bytes)>,
+    queued_bytes: usize,
+    // Whitelisted peers are not throttled
+    exempt: bool,
+}
+
+impl PeerBandwidth {
+    fn record_sent(&mut self, proto_id: ProtocolId, len: usize) {
+        self.traffic.total.add_sent(len);
+        self.traffic
+            .protocols
+            .entry(proto_id)
+            .or_insert_with(Traffic::default)
+            .add_sent(len);
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    peers: FnvHashMap<SessionId, PeerBandwidth>,
+    // Peers with queued messages, in the order of their turns
+    queued: VecDeque<SessionId>,
+    total: Traffic,
+    bucket: Option<UploadBucket>,
+}
+
+/// Counts the bytes sent to and received from the peers, and throttles the
+/// upload to a rate per peer and a global rate. A rate of 0 is unlimited.
+pub struct Bandwidth {
+    peer_upload_rate: u64,
+    upload_rate: u64,
+    inner: Mutex<Inner>,
+}
+
+impl Bandwidth {
+    pub fn new(peer_upload_rate: u64, upload_rate: u64) -> Self {
+        let now = Instant::now();
+        let inner = Inner {
+            bucket: if upload_rate > 0 {
+                Some(UploadBucket::new(upload_rate, now))
+            } else {
+                None
+            },
+            ..Default::default()
+        };
+        Bandwidth {
+            peer_upload_rate,
+            upload_rate,
+            inner: Mutex::new(inner),
+        }
+    }
+
+    pub fn is_throttled(&self) -> bool {
+        self.peer_upload_rate > 0 || self.upload_rate > 0
+    }
+
+    pub fn record_received(&self, session_id: SessionId, proto_id: ProtocolId, len: usize) {
+        let mut inner = self.inner.lock();
+        inner.total.add_received(len);
+        let traffic = &mut inner.peers.entry(session_id).or_default().traffic;
+        traffic.total.add_received(len);
+        traffic
+            .protocols
+            .entry(proto_id)
+            .or_insert_with(Traffic::default)
+            .add_received(len);
+    }
+
+    /// The messages to the peer are never throttled, nor count against the
+    /// global rate
+    pub fn exempt_peer(&self, session_id: SessionId) {
+        self.inner
+            .lock()
+            .peers
+            .entry(session_id)
+            .or_default()
+            .exempt = true;
+    }
+
+    /// Counts a message sent bypassing the throttle
+    pub fn record_sent(&self, session_id: SessionId, proto_id: ProtocolId, len: usize) {
+        let mut inner = self.inner.lock();
+        inner.total.add_sent(len);
+        inner
+            .peers
+            .entry(session_id)
+            .or_default()
+            .record_sent(proto_id, len);
+    }
+
+    /// Returns the message if it can be sent now, counted as sent, or None
+    /// if it is queued until `flush` releases it. Returns an error if the
+    /// message is dropped because the queue of the peer is full.
+    pub fn send(
+        &self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        data: Bytes,
+        now: Instant,
+    ) -> Result<Option<Bytes>, PeerError> {
+        let peer_upload_rate = self.peer_upload_rate;
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        let peer = inner.peers.entry(session_id).or_default();
+        if peer.exempt {
+            peer.record_sent(proto_id, data.len());
+            inner.total.add_sent(data.len());
+            return Ok(Some(data));
+        }
+        if peer.bucket.is_none() && peer_upload_rate > 0 {
+            peer.bucket = Some(UploadBucket::new(peer_upload_rate, now));
+        }
+        if peer.queue.is_empty() && take_tokens(peer, &mut inner.bucket, data.len(), now) {
+            peer.record_sent(proto_id, data.len());
+            inner.total.add_sent(data.len());
+            return Ok(Some(data));
+        }
+        if peer.queued_bytes.saturating_add(data.len()) > MAX_QUEUED_BYTES {
+            return Err(PeerError::UploadQueueFull(session_id));
+        }
+        if peer.queue.is_empty() {
+            inner.queued.push_back(session_id);
+        }
+        peer.queued_bytes += data.len();
+        peer.queue.push_back((proto_id, data));
+        Ok(None)
+    }
+
+    /// Releases the queued messages allowed by the upload rates, in the
+    /// order they are queued for each peer. The peers take turns of one
+    /// message, so the global rate is shared among them.
+    pub fn flush(&self, now: Instant) -> Vec<(SessionId, ProtocolId, Bytes)> {
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        let mut released = Vec::new();
+        let mut turns = inner.queued.drain(..).collect::<VecDeque<_>>();
+        // Peers out of their own rate, they wait for the next flush
+        let mut waiting = Vec::new();
+        while let Some(session_id) = turns.pop_front() {
+            let peer = match inner.peers.get_mut(&session_id) {
+                Some(peer) => peer,
+                None => continue,
+            };
+            let len = match peer.queue.front() {
+                Some((_, data)) => data.len(),
+                None => continue,
+            };
+            if !is_available(&mut inner.bucket, now) {
+                turns.push_front(session_id);
+                break;
+            }
+            if !take_tokens(peer, &mut inner.bucket, len, now) {
+                waiting.push(session_id);
+                continue;
+            }
+            let (proto_id, data) = peer.queue.pop_front().expect("not empty");
+            peer.queued_bytes -= len;
+            peer.record_sent(proto_id, len);
+            inner.total.add_sent(len);
+            released.push((session_id, proto_id, data));
+            if !peer.queue.is_empty() {
+                turns.push_back(session_id);
+            }
+        }
+        // The peers stopped by the global rate go first next time
+        inner.queued = turns;
+        inner.queued.extend(waiting);
+        released
+    }
+
+    /// Forgets the peer and drops its queued messages
+    pub fn remove_peer(&self, session_id: SessionId) {
+        let mut inner = self.inner.lock();
+        inner.peers.remove(&session_id);
+        inner.queued.retain(|id| *id != session_id);
+    }
+
+    pub fn peer_traffic(&self, session_id: SessionId) -> Option<PeerTraffic> {
+        self.inner
+            .lock()
+            .peers
+            .get(&session_id)
+            .map(|peer| peer.traffic.clone())
+    }
+
+    pub fn total_traffic(&self) -> Traffic {
+        self.inner.lock().total
+    }
+}
+
+// Both the bucket of the peer and the global one must be available
+fn take_tokens(
+    peer: &mut PeerBandwidth,
+    global: &mut Option<UploadBucket>,
+    len: usize,
+    now: Instant,
+) -> bool {
+    for bucket in peer.bucket.iter_mut().chain(global.iter_mut()) {
+        bucket.refill(now);
+        if !bucket.is_available() {
+            return false;
+        }
+    }
+    for bucket in peer.bucket.iter_mut().chain(global.iter_mut()) {
+        bucket.take(len);
+    }
+    true
+}
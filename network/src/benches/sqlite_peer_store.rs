@@ -51,13 +51,21 @@ fn insert_peer_info_benchmark(c: &mut Criterion) {
     });
 }
 
+// Addresses of distinct network groups, the peer store selects at most one
+// address per group
+fn group_addr(i: u32) -> Multiaddr {
+    format!("/ip4/{}.{}.0.1", 1 + i / 256, i % 256)
+        .parse()
+        .unwrap()
+}
+
 fn random_order_benchmark(c: &mut Criterion) {
     {
         let mut peer_store = SqlitePeerStore::memory().expect("temp");
-        let addr = "/ip4/127.0.0.1".parse::<Multiaddr>().unwrap();
         {
-            for _ in 0..8000 {
+            for i in 0..8000 {
                 let peer_id = PeerId::random();
+                let addr = group_addr(i);
                 peer_store.add_connected_peer(&peer_id, addr.clone(), SessionType::Outbound);
                 let _ = peer_store.add_discovered_addr(&peer_id, addr.clone());
             }
@@ -72,10 +80,10 @@ fn random_order_benchmark(c: &mut Criterion) {
         });
 
         let mut peer_store = SqlitePeerStore::memory().expect("temp");
-        let addr = "/ip4/127.0.0.1".parse::<Multiaddr>().unwrap();
         {
-            for _ in 0..8000 {
+            for i in 0..8000 {
                 let peer_id = PeerId::random();
+                let addr = group_addr(i);
                 peer_store.add_connected_peer(&peer_id, addr.clone(), SessionType::Outbound);
                 let _ = peer_store.add_discovered_addr(&peer_id, addr.clone());
             }
@@ -96,9 +104,9 @@ fn random_order_benchmark(c: &mut Criterion) {
         move |b| {
             b.iter({
                 let mut peer_store = SqlitePeerStore::temp().expect("temp");
-                let addr = "/ip4/127.0.0.1".parse::<Multiaddr>().unwrap();
-                for _ in 0..8000 {
+                for i in 0..8000 {
                     let peer_id = PeerId::random();
+                    let addr = group_addr(i);
                     peer_store.add_connected_peer(&peer_id, addr.clone(), SessionType::Outbound);
                     let _ = peer_store.add_discovered_addr(&peer_id, addr.clone());
                 }
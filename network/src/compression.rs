@@ -0,0 +1,213 @@
+//! Snappy compression of the protocol messages.
+//!
+//! A protocol opts in from a version, once a session negotiates that version
+//! or a later one every message of the protocol is prefixed by a flag byte,
+//! and the messages above `COMPRESSION_THRESHOLD` are compressed.
+use crate::{ProtocolId, ProtocolVersion, MAX_FRAME_LENGTH};
+use ckb_util::{Mutex, RwLock};
+use fnv::{FnvHashMap, FnvHashSet};
+use p2p::{bytes::Bytes, SessionId};
+
+/// Messages below this size are sent raw, they hardly shrink
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+const FLAG_RAW: u8 = 0;
+const FLAG_SNAPPY: u8 = 1;
+
+/// Names the type of a message in the statistics
+pub type MessageType = fn(&[u8]) -> &'static str;
+
+/// Statistics of the messages sent on the compressing sessions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    pub messages: u64,
+    pub compressed_messages: u64,
+    /// Size of the messages before compression
+    pub raw_bytes: u64,
+    /// Size of the messages sent, flag byte included
+    pub sent_bytes: u64,
+}
+
+struct CompressedProtocol {
+    since_version: ProtocolVersion,
+    message_type: MessageType,
+}
+
+#[derive(Default)]
+pub struct Compression {
+    protocols: RwLock<FnvHashMap<ProtocolId, CompressedProtocol>>,
+    sessions: RwLock<FnvHashSet<(SessionId, ProtocolId)>>,
+    stats: Mutex<FnvHashMap<(ProtocolId, &'static str), CompressionStats>>,
+}
+
+impl Compression {
+    /// Compresses the messages of `proto_id` from `since_version`
+    pub fn register(
+        &self,
+        proto_id: ProtocolId,
+        since_version: ProtocolVersion,
+        message_type: MessageType,
+    ) {
+        self.protocols.write().insert(
+            proto_id,
+            CompressedProtocol {
+                since_version,
+                message_type,
+            },
+        );
+    }
+
+    pub fn is_registered(&self, proto_id: ProtocolId) -> bool {
+        self.protocols.read().contains_key(&proto_id)
+    }
+
+    /// Enables the compression of the session if the negotiated `version`
+    /// supports it
+    pub fn connected(&self, session_id: SessionId, proto_id: ProtocolId, version: &str) {
+        let enabled = self
+            .protocols
+            .read()
+            .get(&proto_id)
+            .map(|protocol| version_at_least(version, &protocol.since_version))
+            .unwrap_or(false);
+        if enabled {
+            self.sessions.write().insert((session_id, proto_id));
+        }
+    }
+
+    pub fn disconnected(&self, session_id: SessionId) {
+        self.sessions
+            .write()
+            .retain(|(session, _)| *session != session_id);
+    }
+
+    pub fn is_enabled(&self, session_id: SessionId, proto_id: ProtocolId) -> bool {
+        self.sessions.read().contains(&(session_id, proto_id))
+    }
+
+    /// Frames a message to send, it is unchanged if the session does not
+    /// compress
+    pub fn encode(&self, session_id: SessionId, proto_id: ProtocolId, data: Bytes) -> Bytes {
+        self.frame(proto_id, data).for_session(session_id)
+    }
+
+    /// Frames a message sent to several sessions, it is compressed and
+    /// named at most once whatever the number of sessions
+    pub fn frame(&self, proto_id: ProtocolId, data: Bytes) -> FramedMessage {
+        FramedMessage {
+            compression: self,
+            proto_id,
+            raw: data,
+            encoded: None,
+        }
+    }
+
+    fn record(&self, proto_id: ProtocolId, message_type: &'static str, raw: &[u8], encoded: &[u8]) {
+        let mut stats = self.stats.lock();
+        let stats = stats.entry((proto_id, message_type)).or_default();
+        stats.messages += 1;
+        if encoded[0] == FLAG_SNAPPY {
+            stats.compressed_messages += 1;
+        }
+        stats.raw_bytes += raw.len() as u64;
+        stats.sent_bytes += encoded.len() as u64;
+    }
+
+    /// Unframes a received message, it is unchanged if the session does not
+    /// compress
+    pub fn decode(
+        &self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        data: Bytes,
+    ) -> Result<Bytes, String> {
+        if self.is_enabled(session_id, proto_id) {
+            decode(&data)
+        } else {
+            Ok(data)
+        }
+    }
+
+    /// Statistics by protocol and message type
+    pub fn stats(&self) -> Vec<(ProtocolId, &'static str, CompressionStats)> {
+        self.stats
+            .lock()
+            .iter()
+            .map(|((proto_id, message_type), stats)| (*proto_id, *message_type, *stats))
+            .collect()
+    }
+}
+
+pub struct FramedMessage<'a> {
+    compression: &'a Compression,
+    proto_id: ProtocolId,
+    raw: Bytes,
+    // The message encoded and its type, once a session compresses it
+    encoded: Option<(Bytes, Option<&'static str>)>,
+}
+
+impl<'a> FramedMessage<'a> {
+    /// The message as sent to the session
+    pub fn for_session(&mut self, session_id: SessionId) -> Bytes {
+        let compression = self.compression;
+        if !compression.is_enabled(session_id, self.proto_id) {
+            return self.raw.clone();
+        }
+        let (proto_id, raw) = (self.proto_id, &self.raw);
+        let (encoded, message_type) = self.encoded.get_or_insert_with(|| {
+            let message_type = compression
+                .protocols
+                .read()
+                .get(&proto_id)
+                .map(|protocol| (protocol.message_type)(raw));
+            (encode(raw), message_type)
+        });
+        if let Some(message_type) = *message_type {
+            compression.record(proto_id, message_type, raw, encoded);
+        }
+        encoded.clone()
+    }
+}
+
+pub fn encode(data: &[u8]) -> Bytes {
+    if data.len() >= COMPRESSION_THRESHOLD {
+        if let Ok(compressed) = snap::Encoder::new().compress_vec(data) {
+            if compressed.len() < data.len() {
+                let mut encoded = Vec::with_capacity(compressed.len() + 1);
+                encoded.push(FLAG_SNAPPY);
+                encoded.extend_from_slice(&compressed);
+                return encoded.into();
+            }
+        }
+    }
+    let mut encoded = Vec::with_capacity(data.len() + 1);
+    encoded.push(FLAG_RAW);
+    encoded.extend_from_slice(data);
+    encoded.into()
+}
+
+pub fn decode(data: &[u8]) -> Result<Bytes, String> {
+    match data.split_first() {
+        Some((&FLAG_RAW, raw)) => Ok(Bytes::from(raw)),
+        Some((&FLAG_SNAPPY, compressed)) => {
+            let len = snap::decompress_len(compressed).map_err(|err| err.to_string())?;
+            if len > MAX_FRAME_LENGTH {
+                return Err(format!("decompressed length {} is too large", len));
+            }
+            snap::Decoder::new()
+                .decompress_vec(compressed)
+                .map(Into::into)
+                .map_err(|err| err.to_string())
+        }
+        Some((flag, _)) => Err(format!("unknown compression flag {}", flag)),
+        None => Err("empty message".to_string()),
+    }
+}
+
+// Versions are compared as numbers when both are
+fn version_at_least(version: &str, since_version: &str) -> bool {
+    match (version.parse::<u64>(), since_version.parse::<u64>()) {
+        (Ok(version), Ok(since_version)) => version >= since_version,
+        _ => version >= since_version,
+    }
+}
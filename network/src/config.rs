@@ -1,5 +1,7 @@
 use crate::errors::{ConfigError, Error};
+use crate::network_group::MultiaddrExt;
 use crate::PeerId;
+use faster_hex::hex_decode;
 use log::info;
 use p2p::{
     multiaddr::{Multiaddr, Protocol},
@@ -11,8 +13,14 @@ use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::io::Read;
 use std::io::Write;
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
 
+pub const DEFAULT_MAX_ANCHOR_PEERS: usize = 4;
+pub const DEFAULT_MAX_WS_PEERS: u32 = 32;
+pub const DEFAULT_OUTBOUND_ROTATION_COUNT: usize = 1;
+pub const DEFAULT_MAX_OUTBOUND_PEERS_PER_GROUP: u32 = 2;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NetworkConfig {
     pub reserved_only: bool,
@@ -29,6 +37,65 @@ pub struct NetworkConfig {
     pub public_addresses: Vec<Multiaddr>,
     pub bootnodes: Vec<Multiaddr>,
     pub reserved_peers: Vec<Multiaddr>,
+    /// Protected peers, never evicted or banned and reconnected first
+    #[serde(default)]
+    pub whitelist_peers: Vec<Multiaddr>,
+    /// Outbound peers saved on shutdown and reconnected first on startup
+    #[serde(default = "default_max_anchor_peers")]
+    pub max_anchor_peers: usize,
+    /// Map the listen port on the local gateway and advertise the external address
+    #[serde(default)]
+    pub port_mapping: bool,
+    /// Gateway to map the port with NAT-PMP, UPnP is used when it is not set
+    #[serde(default)]
+    pub nat_pmp_gateway: Option<Ipv4Addr>,
+    /// Upload limit of each peer in bytes per second, 0 is unlimited
+    #[serde(default)]
+    pub max_peer_upload_rate: u64,
+    /// Upload limit of all the peers in bytes per second, 0 is unlimited
+    #[serde(default)]
+    pub max_upload_rate: u64,
+    /// WebSocket listen address for the browser and WASM light clients, e.g.
+    /// `/ip4/0.0.0.0/tcp/8116/ws`, the node refuses to start with it until
+    /// tentacle has the WebSocket transport
+    #[serde(default)]
+    pub ws_listen_address: Option<Multiaddr>,
+    /// Inbound peers of the WebSocket listener, besides the `max_peers`
+    #[serde(default = "default_max_ws_peers")]
+    pub max_ws_peers: u32,
+    /// Only the peers of the public keys in `allowlist` may connect, for the
+    /// private networks
+    #[serde(default)]
+    pub allowlist_only: bool,
+    /// Hex encoded secp256k1 public keys
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    /// Seconds between the rotations of the outbound peers, 0 disables them
+    #[serde(default)]
+    pub outbound_rotation_interval_secs: u64,
+    /// Outbound peers replaced by each rotation
+    #[serde(default = "default_outbound_rotation_count")]
+    pub outbound_rotation_count: usize,
+    /// Outbound peers in a network group, the /16 of IPv4 or the /32 of IPv6
+    /// addresses. The local addresses are not limited.
+    #[serde(default = "default_max_outbound_peers_per_group")]
+    pub max_outbound_peers_per_group: u32,
+}
+
+fn default_max_anchor_peers() -> usize {
+    DEFAULT_MAX_ANCHOR_PEERS
+}
+
+fn default_max_ws_peers() -> u32 {
+    DEFAULT_MAX_WS_PEERS
+}
+
+fn default_outbound_rotation_count() -> usize {
+    DEFAULT_OUTBOUND_ROTATION_COUNT
+}
+
+fn default_max_outbound_peers_per_group() -> u32 {
+    DEFAULT_MAX_OUTBOUND_PEERS_PER_GROUP
 }
 
 fn generate_random_key() -> [u8; 32] {
@@ -69,6 +136,19 @@ impl NetworkConfig {
         self.max_outbound_peers
     }
 
+    /// The WebSocket listen address, `/ws` is appended when it is missing
+    pub fn ws_listen_address(&self) -> Option<Multiaddr> {
+        self.ws_listen_address.as_ref().map(|addr| {
+            if addr.is_ws() {
+                addr.to_owned()
+            } else {
+                let mut addr = addr.to_owned();
+                addr.push(Protocol::Ws);
+                addr
+            }
+        })
+    }
+
     fn read_secret_key(&self) -> Result<Option<secio::SecioKeyPair>, Error> {
         let path = self.secret_key_path();
         let mut file = match fs::File::open(path) {
@@ -120,6 +200,37 @@ impl NetworkConfig {
         Ok(peers)
     }
 
+    pub fn whitelist_peers(&self) -> Result<Vec<(PeerId, Multiaddr)>, Error> {
+        let mut peers = Vec::with_capacity(self.whitelist_peers.len());
+        for addr_str in &self.whitelist_peers {
+            let mut addr = addr_str.to_owned();
+            let peer_id = match addr.pop() {
+                Some(Protocol::P2p(key)) => {
+                    PeerId::from_bytes(key.into_bytes()).map_err(|_| ConfigError::BadAddress)?
+                }
+                _ => return Err(ConfigError::BadAddress.into()),
+            };
+            peers.push((peer_id, addr))
+        }
+        Ok(peers)
+    }
+
+    /// Peer ids of the public keys in the allowlist
+    pub fn allowlist(&self) -> Result<Vec<PeerId>, Error> {
+        let mut peers = Vec::with_capacity(self.allowlist.len());
+        for key_str in &self.allowlist {
+            let key_str = key_str.trim_start_matches("0x");
+            let mut bytes = vec![0u8; key_str.len() / 2];
+            hex_decode(key_str.as_bytes(), &mut bytes)
+                .map_err(|_| ConfigError::InvalidPublicKey(key_str.to_owned()))?;
+            let key = secp256k1::key::PublicKey::from_slice(&bytes)
+                .map_err(|_| ConfigError::InvalidPublicKey(key_str.to_owned()))?;
+            let public_key = secio::PublicKey::Secp256k1(key.serialize().to_vec());
+            peers.push(public_key.peer_id());
+        }
+        Ok(peers)
+    }
+
     pub fn bootnodes(&self) -> Result<Vec<(PeerId, Multiaddr)>, Error> {
         let mut peers = Vec::with_capacity(self.bootnodes.len());
         for addr_str in &self.bootnodes {
@@ -19,6 +19,7 @@ pub enum Error {
 pub enum ConfigError {
     BadAddress,
     InvalidKey,
+    InvalidPublicKey(String),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -30,6 +31,11 @@ pub enum PeerError {
     Banned,
     ReachMaxInboundLimit,
     ReachMaxOutboundLimit,
+    ReachMaxWsInboundLimit,
+    NotAllowed,
+    /// The message is dropped, too many bytes to the peer are held back by
+    /// the upload throttle
+    UploadQueueFull(SessionId),
 }
 
 #[derive(Debug)]
@@ -1,4 +1,6 @@
+mod bandwidth;
 mod behaviour;
+mod compression;
 mod config;
 pub mod errors;
 pub mod network;
@@ -13,13 +15,15 @@ mod services;
 mod tests;
 
 pub use crate::{
+    bandwidth::{PeerTraffic, Traffic},
     behaviour::Behaviour,
+    compression::{CompressionStats, MessageType},
     config::NetworkConfig,
     errors::Error,
     network::{NetworkController, NetworkService, NetworkState},
     peer::{Peer, PeerIdentifyInfo},
     peer_registry::PeerRegistry,
-    peer_store::Score,
+    peer_store::{BannedAddr, IpNetwork, Score},
     protocols::{CKBProtocol, CKBProtocolContext, CKBProtocolHandler, PeerIndex},
 };
 pub use p2p::{
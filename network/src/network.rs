@@ -1,18 +1,27 @@
-use crate::errors::Error;
+use crate::bandwidth::{Bandwidth, PeerTraffic, Traffic};
+use crate::compression::{Compression, CompressionStats};
+use crate::errors::{ConfigError, Error, PeerError};
+use crate::network_group::MultiaddrExt;
 use crate::peer_registry::{ConnectionStatus, PeerRegistry};
-use crate::peer_store::{sqlite::SqlitePeerStore, PeerStore, Status};
+use crate::peer_store::{
+    sqlite::SqlitePeerStore, AnchorPeer, BannedAddr, IpNetwork, PeerStore, Status,
+};
 use crate::protocols::feeler::Feeler;
 use crate::protocols::{
     discovery::{DiscoveryProtocol, DiscoveryService},
     identify::IdentifyCallback,
     ping::PingService,
 };
-use crate::services::{dns_seeding::DnsSeedingService, outbound_peer::OutboundPeerService};
+use crate::services::{
+    dns_seeding::DnsSeedingService, outbound_peer::OutboundPeerService,
+    port_mapping::PortMappingService, upload_throttle::UploadThrottleService,
+};
 use crate::Peer;
 use crate::{
     Behaviour, CKBProtocol, NetworkConfig, ProtocolId, ProtocolVersion, PublicKey, ServiceControl,
 };
 use ckb_util::{Mutex, RwLock};
+use faketime::unix_time;
 use fnv::{FnvHashMap, FnvHashSet};
 use futures::sync::mpsc::channel;
 use futures::sync::{mpsc, oneshot};
@@ -39,6 +48,7 @@ use p2p_identify::IdentifyProtocol;
 use p2p_ping::PingHandler;
 use std::boxed::Box;
 use std::cmp::max;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -75,7 +85,12 @@ pub struct NetworkState {
     disconnecting_sessions: RwLock<FnvHashSet<SessionId>>,
     local_private_key: secio::SecioKeyPair,
     local_peer_id: PeerId,
+    pub(crate) whitelist_peers: Vec<(PeerId, Multiaddr)>,
+    pub(crate) bandwidth: Bandwidth,
+    pub(crate) compression: Compression,
     pub(crate) config: NetworkConfig,
+    // Sessions rejected for the peers not in the allowlist
+    not_allowed_sessions: AtomicUsize,
 }
 
 impl NetworkState {
@@ -104,15 +119,31 @@ impl NetworkState {
             .iter()
             .map(|(peer_id, _)| peer_id.to_owned())
             .collect::<Vec<_>>();
+        let whitelist_peers = config.whitelist_peers()?;
         let peer_registry = PeerRegistry::new(
             config.max_inbound_peers(),
             config.max_outbound_peers(),
             config.reserved_only,
             reserved_peers,
-        );
+        )
+        .whitelist_peers(
+            whitelist_peers
+                .iter()
+                .map(|(peer_id, _)| peer_id.to_owned())
+                .collect(),
+        )
+        .max_ws_inbound(config.max_ws_peers);
+        let peer_registry = if config.allowlist_only {
+            peer_registry.allowlist(config.allowlist()?)
+        } else {
+            peer_registry
+        };
 
+        let bandwidth = Bandwidth::new(config.max_peer_upload_rate, config.max_upload_rate);
         Ok(NetworkState {
             peer_store,
+            bandwidth,
+            compression: Compression::default(),
             config,
             peer_registry: RwLock::new(peer_registry),
             failed_dials: RwLock::new(LruCache::new(FAILED_DIAL_CACHE_SIZE)),
@@ -122,6 +153,8 @@ impl NetworkState {
             local_private_key: local_private_key.clone(),
             local_peer_id: local_private_key.to_public_key().peer_id(),
             protocol_ids: RwLock::new(FnvHashSet::default()),
+            whitelist_peers,
+            not_allowed_sessions: AtomicUsize::new(0),
         })
     }
 
@@ -147,6 +180,9 @@ impl NetworkState {
         behaviour: Behaviour,
     ) {
         trace!(target: "network", "report {:?} because {:?}", peer_id, behaviour);
+        if self.is_whitelisted(peer_id) {
+            return;
+        }
         if self
             .peer_store
             .lock()
@@ -165,6 +201,38 @@ impl NetworkState {
         }
     }
 
+    /// Saves the longest connected outbound peers, they are dialed first on
+    /// the next startup
+    pub(crate) fn persist_anchor_peers(&self) {
+        let mut peers = self.with_peer_registry(|reg| {
+            reg.peers()
+                .values()
+                .filter(|peer| peer.is_outbound() && !reg.is_feeler(&peer.peer_id))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+        peers.sort_by_key(|peer| peer.connected_time);
+        let now = unix_time();
+        let mut peer_store = self.peer_store.lock();
+        let anchors = peers
+            .into_iter()
+            .filter(|peer| !peer_store.is_banned(&peer.peer_id))
+            .take(self.config.max_anchor_peers)
+            .map(|peer| AnchorPeer {
+                peer_id: peer.peer_id,
+                address: peer.address,
+                last_seen_at: now,
+                protocols: peer.protocols.into_iter().collect(),
+            })
+            .collect::<Vec<_>>();
+        debug!(target: "network", "persist {} anchor peers", anchors.len());
+        peer_store.update_anchor_peers(anchors);
+    }
+
+    pub(crate) fn is_whitelisted(&self, peer_id: &PeerId) -> bool {
+        self.with_peer_registry(|reg| reg.is_whitelisted(peer_id))
+    }
+
     pub(crate) fn ban_session(
         &self,
         p2p_control: &ServiceControl,
@@ -186,6 +254,10 @@ impl NetworkState {
         peer_id: &PeerId,
         timeout: Duration,
     ) {
+        if self.is_whitelisted(peer_id) {
+            info!(target: "network", "ignore ban of whitelisted peer {:?}", peer_id);
+            return;
+        }
         info!(target: "network", "ban peer {:?} with {:?}", peer_id, timeout);
         self.peer_store.lock().ban_peer(peer_id, timeout);
         self.with_peer_registry_mut(|reg| {
@@ -198,6 +270,40 @@ impl NetworkState {
         });
     }
 
+    /// Bans the IP address or subnet and disconnects its peers, except the
+    /// whitelisted ones
+    pub(crate) fn ban_network(
+        &self,
+        p2p_control: &ServiceControl,
+        address: IpNetwork,
+        timeout: Duration,
+        reason: String,
+    ) {
+        info!(target: "network", "ban {} with {:?}: {}", address, timeout, reason);
+        self.peer_store.lock().ban_network(address, timeout, reason);
+        self.with_peer_registry_mut(|reg| {
+            let sessions = reg
+                .peers()
+                .values()
+                .filter(|peer| {
+                    !peer.is_whitelisted
+                        && peer
+                            .address
+                            .extract_ip_addr()
+                            .map(|ip| address.contains(ip))
+                            .unwrap_or(false)
+                })
+                .map(|peer| peer.session_id)
+                .collect::<Vec<_>>();
+            for session_id in sessions {
+                reg.remove_peer(session_id);
+                if let Err(err) = p2p_control.disconnect(session_id) {
+                    error!(target: "network", "send message to p2p service error: {:?}", err);
+                }
+            }
+        });
+    }
+
     pub(crate) fn query_session_id(&self, peer_id: &PeerId) -> Option<SessionId> {
         let mut target_session_id = None;
         // Create a scope for avoid dead lock
@@ -234,8 +340,12 @@ impl NetworkState {
                 peer_store.as_mut(),
             )
         };
-        if accept_peer_result.is_ok() {
-            peer_store.update_status(&peer_id, Status::Connected);
+        match accept_peer_result {
+            Ok(_) => peer_store.update_status(&peer_id, Status::Connected),
+            Err(PeerError::NotAllowed) => {
+                self.not_allowed_sessions.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(_) => (),
         }
         accept_peer_result.map_err(Into::into)
     }
@@ -272,6 +382,10 @@ impl NetworkState {
         callback(self.peer_store.lock().as_mut())
     }
 
+    pub fn not_allowed_sessions(&self) -> usize {
+        self.not_allowed_sessions.load(Ordering::Relaxed)
+    }
+
     pub fn local_peer_id(&self) -> &PeerId {
         &self.local_peer_id
     }
@@ -293,6 +407,15 @@ impl NetworkState {
             .collect()
     }
 
+    // Advertised with the max score, as the public addresses
+    pub(crate) fn add_listened_address(&self, addr: Multiaddr) {
+        self.listened_addresses.write().insert(addr, std::u8::MAX);
+    }
+
+    pub(crate) fn remove_listened_address(&self, addr: &Multiaddr) {
+        self.listened_addresses.write().remove(addr);
+    }
+
     pub(crate) fn connection_status(&self) -> ConnectionStatus {
         self.peer_registry.read().connection_status()
     }
@@ -337,6 +460,76 @@ impl NetworkState {
             .collect::<Vec<_>>()
     }
 
+    /// Sends the message now, or queues it when the upload is throttled
+    pub(crate) fn send_message(
+        &self,
+        p2p_control: &ServiceControl,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        let data = self.compression.encode(session_id, proto_id, data);
+        self.send_framed(p2p_control, session_id, proto_id, data)
+    }
+
+    // Sends the message framed for the session
+    fn send_framed(
+        &self,
+        p2p_control: &ServiceControl,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        match self
+            .bandwidth
+            .send(session_id, proto_id, data, Instant::now())?
+        {
+            Some(data) => p2p_control
+                .send_message_to(session_id, proto_id, data)
+                .map_err(|err| Error::P2P(format!("{:?}", err))),
+            None => {
+                trace!(target: "network", "upload throttled, message to {} is queued", session_id);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn filter_broadcast(
+        &self,
+        p2p_control: &ServiceControl,
+        target: TargetSession,
+        proto_id: ProtocolId,
+        data: Bytes,
+    ) -> Result<(), Error> {
+        let session_ids = match target {
+            TargetSession::All => self.with_peer_registry(PeerRegistry::connected_peers),
+            TargetSession::Single(session_id) => vec![session_id],
+            TargetSession::Multi(session_ids) => session_ids,
+        };
+        if self.bandwidth.is_throttled() || self.compression.is_registered(proto_id) {
+            // Each peer has its own throttle and compression, a peer
+            // dropping the message does not stop the others
+            let mut result = Ok(());
+            let mut framed = self.compression.frame(proto_id, data);
+            for session_id in session_ids {
+                let data = framed.for_session(session_id);
+                if let Err(err) = self.send_framed(p2p_control, session_id, proto_id, data) {
+                    debug!(target: "network", "broadcast to {} failed: {:?}", session_id, err);
+                    result = Err(err);
+                }
+            }
+            result
+        } else {
+            for session_id in &session_ids {
+                self.bandwidth
+                    .record_sent(*session_id, proto_id, data.len());
+            }
+            p2p_control
+                .filter_broadcast(TargetSession::Multi(session_ids), proto_id, data)
+                .map_err(|err| Error::P2P(format!("{:?}", err)))
+        }
+    }
+
     pub fn dial(
         &self,
         p2p_control: &ServiceControl,
@@ -344,6 +537,14 @@ impl NetworkState {
         mut addr: Multiaddr,
         target: DialProtocol,
     ) {
+        if !self.with_peer_registry(|reg| reg.is_allowed(peer_id)) {
+            debug!(target: "network", "skip dialing peer {:?} not in the allowlist", peer_id);
+            return;
+        }
+        if !self.is_whitelisted(peer_id) && self.peer_store.lock().is_addr_banned(&addr) {
+            debug!(target: "network", "skip dialing banned address {}", addr);
+            return;
+        }
         if !self.listened_addresses.read().contains_key(&addr) {
             match Multihash::from_bytes(peer_id.as_bytes().to_vec()) {
                 Ok(peer_id_hash) => {
@@ -419,7 +620,9 @@ impl ServiceHandle for EventHandler {
                 error,
             } => {
                 warn!(target: "network", "ProtocolError({}, {}) {}", id, proto_id, error);
-                context.disconnect(id);
+                if let Err(err) = context.disconnect(id) {
+                    debug!(target: "network", "disconnect {} error: {:?}", id, err);
+                }
             }
             ServiceError::SessionTimeout { session_context } => {
                 warn!(
@@ -440,7 +643,9 @@ impl ServiceHandle for EventHandler {
                     session_context.address,
                     error,
                 );
-                context.disconnect(session_context.id)
+                if let Err(err) = context.disconnect(session_context.id) {
+                    debug!(target: "network", "disconnect {} error: {:?}", session_context.id, err);
+                }
             }
             _ => {
                 warn!(target: "network", "p2p service error: {:?}", error);
@@ -474,7 +679,11 @@ impl ServiceHandle for EventHandler {
                         session_context.address,
                     );
                 } else {
-                    match self.network_state.accept_peer(&session_context) {
+                    let accepted = self.network_state.accept_peer(&session_context);
+                    if accepted.is_ok() && self.network_state.is_whitelisted(&peer_id) {
+                        self.network_state.bandwidth.exempt_peer(session_context.id);
+                    }
+                    match accepted {
                         Ok(Some(evicted_peer)) => {
                             info!(
                                 target: "network",
@@ -482,7 +691,14 @@ impl ServiceHandle for EventHandler {
                                 evicted_peer.session_id,
                                 evicted_peer.address,
                             );
-                            context.disconnect(evicted_peer.session_id);
+                            if let Err(err) = context.disconnect(evicted_peer.session_id) {
+                                debug!(
+                                    target: "network",
+                                    "disconnect {} error: {:?}",
+                                    evicted_peer.session_id,
+                                    err
+                                );
+                            }
                         }
                         Ok(None) => info!(
                             target: "network",
@@ -490,6 +706,23 @@ impl ServiceHandle for EventHandler {
                             session_context.id,
                             session_context.address,
                         ),
+                        Err(Error::Peer(PeerError::NotAllowed)) => {
+                            warn!(
+                                target: "network",
+                                "reject peer {:?} not in the allowlist, {} => {}",
+                                peer_id,
+                                session_context.id,
+                                session_context.address,
+                            );
+                            if let Err(err) = context.disconnect(session_context.id) {
+                                debug!(
+                                    target: "network",
+                                    "disconnect {} error: {:?}",
+                                    session_context.id,
+                                    err
+                                );
+                            }
+                        }
                         Err(err) => {
                             warn!(
                                 target: "network",
@@ -498,7 +731,14 @@ impl ServiceHandle for EventHandler {
                                 session_context.id,
                                 session_context.address,
                             );
-                            context.disconnect(session_context.id);
+                            if let Err(err) = context.disconnect(session_context.id) {
+                                debug!(
+                                    target: "network",
+                                    "disconnect {} error: {:?}",
+                                    session_context.id,
+                                    err
+                                );
+                            }
                         }
                     }
                 }
@@ -514,6 +754,10 @@ impl ServiceHandle for EventHandler {
                     .disconnecting_sessions
                     .write()
                     .remove(&session_context.id);
+                self.network_state.bandwidth.remove_peer(session_context.id);
+                self.network_state
+                    .compression
+                    .disconnected(session_context.id);
                 let peer_exists = self
                     .network_state
                     .peer_registry
@@ -607,7 +851,9 @@ impl ServiceHandle for EventHandler {
                         .disconnecting_sessions
                         .write()
                         .insert(session_id);
-                    context.disconnect(session_id);
+                    if let Err(err) = context.disconnect(session_id) {
+                        debug!(target: "network", "disconnect {} error: {:?}", session_id, err);
+                    }
                 }
             }
             _ => {}
@@ -713,12 +959,26 @@ impl NetworkService {
             Arc::clone(&network_state),
             network_state.config.dns_seeds.clone(),
         );
-        let bg_services = vec![
+        let mut bg_services = vec![
             Box::new(ping_service.for_each(|_| Ok(()))) as Box<_>,
             Box::new(disc_service.for_each(|_| Ok(()))) as Box<_>,
             Box::new(outbound_peer_service.for_each(|_| Ok(()))) as Box<_>,
             Box::new(dns_seeding_service) as Box<_>,
         ];
+        if network_state.bandwidth.is_throttled() {
+            let upload_throttle_service = UploadThrottleService::new(
+                Arc::clone(&network_state),
+                p2p_service.control().to_owned(),
+            );
+            bg_services.push(Box::new(upload_throttle_service.for_each(|_| Ok(()))) as Box<_>);
+        }
+        if network_state.config.port_mapping {
+            let port_mapping_service = PortMappingService::new(
+                Arc::clone(&network_state),
+                network_state.config.nat_pmp_gateway,
+            );
+            bg_services.push(Box::new(port_mapping_service) as Box<_>);
+        }
 
         NetworkService {
             p2p_service,
@@ -758,6 +1018,17 @@ impl NetworkService {
             };
         }
 
+        // tentacle 0.2 only has the TCP transport, listening on the WebSocket
+        // address would serve the raw protocol on it, so it is refused
+        if let Some(addr) = config.ws_listen_address() {
+            error!(
+                target: "network",
+                "listen on WebSocket address {} failed, the WebSocket transport is not supported",
+                addr
+            );
+            return Err(ConfigError::BadAddress.into());
+        }
+
         // dial reserved_nodes
         for (peer_id, addr) in config.reserved_peers()? {
             debug!(target: "network", "dial reserved_peers {:?} {:?}", peer_id, addr);
@@ -765,9 +1036,33 @@ impl NetworkService {
                 .dial_all(self.p2p_service.control(), &peer_id, addr);
         }
 
-        let bootnodes = self.network_state.with_peer_store(|peer_store| {
-            peer_store.bootnodes(max((config.max_outbound_peers / 2) as u32, 1))
-        });
+        for (peer_id, addr) in &self.network_state.whitelist_peers {
+            debug!(target: "network", "dial whitelist_peers {:?} {:?}", peer_id, addr);
+            self.network_state
+                .dial_all(self.p2p_service.control(), peer_id, addr.to_owned());
+        }
+
+        // dial anchor peers of the last run, before the discovered ones
+        let anchor_peers = self
+            .network_state
+            .with_peer_store(|peer_store| peer_store.anchor_peers());
+        for anchor in anchor_peers.iter().take(config.max_anchor_peers) {
+            debug!(target: "network", "dial anchor peer {:?} {:?}", anchor.peer_id, anchor.address);
+            self.network_state.dial_all(
+                self.p2p_service.control(),
+                &anchor.peer_id,
+                anchor.address.to_owned(),
+            );
+        }
+
+        let bootnodes_count = max((config.max_outbound_peers / 2) as usize, 1)
+            .saturating_sub(anchor_peers.len().min(config.max_anchor_peers));
+        let bootnodes = if bootnodes_count > 0 {
+            self.network_state
+                .with_peer_store(|peer_store| peer_store.bootnodes(bootnodes_count as u32))
+        } else {
+            Vec::new()
+        };
         // dial half bootnodes
         for (peer_id, addr) in bootnodes {
             debug!(target: "network", "dial bootnode {:?} {:?}", peer_id, addr);
@@ -809,6 +1104,9 @@ impl NetworkService {
 
                 // Recevied stop signal, doing cleanup
                 let _ = receiver.recv();
+                self.network_state.persist_anchor_peers();
+                self.network_state
+                    .with_peer_store_mut(|peer_store| peer_store.persist_addrs());
                 for peer in self.network_state.peer_registry.read().peers().values() {
                     info!(target: "network", "disconnect peer {}", peer.address);
                     if let Err(err) = inner_p2p_control.disconnect(peer.session_id) {
@@ -851,11 +1149,52 @@ impl NetworkController {
         self.network_state.node_id()
     }
 
+    /// Addresses the node listens on
+    pub fn listen_addresses(&self) -> Vec<Multiaddr> {
+        self.network_state
+            .original_listened_addresses
+            .read()
+            .clone()
+    }
+
+    /// Addresses of the node observed by the outbound peers
+    pub fn observed_addresses(&self, max_addrs: u32) -> Vec<Multiaddr> {
+        let local_peer_id = self.network_state.local_peer_id();
+        self.network_state.with_peer_store(|peer_store| {
+            peer_store
+                .peer_addrs(local_peer_id, max_addrs)
+                .unwrap_or_default()
+        })
+    }
+
     pub fn add_node(&self, peer_id: &PeerId, address: Multiaddr) {
         self.network_state
             .add_node(&self.p2p_control, peer_id, address)
     }
 
+    /// Bans the IP address or subnet for `timeout`, its connected peers are
+    /// disconnected
+    pub fn ban(&self, address: IpNetwork, timeout: Duration, reason: String) {
+        self.network_state
+            .ban_network(&self.p2p_control, address, timeout, reason)
+    }
+
+    /// Returns false if the address is not banned
+    pub fn unban(&self, address: &IpNetwork) -> bool {
+        self.network_state
+            .with_peer_store_mut(|peer_store| peer_store.unban_network(address))
+    }
+
+    pub fn get_banned_addrs(&self) -> Vec<BannedAddr> {
+        self.network_state
+            .with_peer_store(|peer_store| peer_store.banned_addrs())
+    }
+
+    pub fn clear_banned_addrs(&self) {
+        self.network_state
+            .with_peer_store_mut(|peer_store| peer_store.clear_banned_addrs())
+    }
+
     pub fn connected_peers(&self) -> Vec<(PeerId, Peer, MultiaddrList)> {
         let peers = self
             .network_state
@@ -880,18 +1219,43 @@ impl NetworkController {
         })
     }
 
+    /// Traffic of the connected peer
+    pub fn peer_traffic(&self, session_id: SessionId) -> Option<PeerTraffic> {
+        self.network_state.bandwidth.peer_traffic(session_id)
+    }
+
+    /// Sessions rejected since startup for the peers not in the allowlist
+    pub fn not_allowed_sessions(&self) -> usize {
+        self.network_state.not_allowed_sessions()
+    }
+
+    /// Compression statistics by protocol and message type
+    pub fn compression_stats(&self) -> Vec<(ProtocolId, &'static str, CompressionStats)> {
+        self.network_state.compression.stats()
+    }
+
+    /// Traffic of all the peers since startup
+    pub fn total_traffic(&self) -> Traffic {
+        self.network_state.bandwidth.total_traffic()
+    }
+
     pub fn broadcast(&self, proto_id: ProtocolId, data: Bytes) {
         let session_ids = self.network_state.peer_registry.read().connected_peers();
-        if let Err(err) =
-            self.p2p_control
-                .filter_broadcast(TargetSession::Multi(session_ids), proto_id, data)
-        {
+        if let Err(err) = self.network_state.filter_broadcast(
+            &self.p2p_control,
+            TargetSession::Multi(session_ids),
+            proto_id,
+            data,
+        ) {
             warn!(target: "network", "broadcast message to {} failed: {:?}", proto_id, err);
         }
     }
 
     pub fn send_message_to(&self, session_id: SessionId, proto_id: ProtocolId, data: Bytes) {
-        if let Err(err) = self.p2p_control.send_message_to(session_id, proto_id, data) {
+        if let Err(err) =
+            self.network_state
+                .send_message(&self.p2p_control, session_id, proto_id, data)
+        {
             warn!(target: "network", "send message to {} {} failed: {:?}", session_id, proto_id, err);
         }
     }
@@ -1,7 +1,7 @@
 use p2p::multiaddr::{Multiaddr, Protocol};
 use std::net::IpAddr;
 
-#[derive(Hash, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
 pub enum Group {
     NoGroup,
     LocalNetwork,
@@ -9,6 +9,16 @@ pub enum Group {
     IP6([u8; 4]),
 }
 
+impl Group {
+    /// Local and ungrouped addresses are not limited by the diversity rules
+    pub fn is_diversity_limited(self) -> bool {
+        match self {
+            Group::IP4(_) | Group::IP6(_) => true,
+            Group::NoGroup | Group::LocalNetwork => false,
+        }
+    }
+}
+
 pub trait NetworkGroup {
     fn network_group(&self) -> Group;
 }
@@ -21,6 +31,8 @@ pub trait MultiaddrExt {
             IpAddr::V6(ipv6) => ipv6.octets().to_vec(),
         })
     }
+    /// Whether the address is of the WebSocket transport
+    fn is_ws(&self) -> bool;
 }
 
 impl MultiaddrExt for Multiaddr {
@@ -34,6 +46,13 @@ impl MultiaddrExt for Multiaddr {
         }
         None
     }
+
+    fn is_ws(&self) -> bool {
+        self.iter().any(|addr_component| match addr_component {
+            Protocol::Ws => true,
+            _ => false,
+        })
+    }
 }
 
 impl NetworkGroup for Multiaddr {
@@ -1,4 +1,4 @@
-use crate::network_group::{Group, NetworkGroup};
+use crate::network_group::{Group, MultiaddrExt, NetworkGroup};
 use crate::{multiaddr::Multiaddr, ProtocolId, ProtocolVersion, SessionType};
 use fnv::FnvHashMap;
 use p2p::{secio::PeerId, SessionId};
@@ -20,6 +20,8 @@ pub struct Peer {
     pub identify_info: Option<PeerIdentifyInfo>,
     pub last_ping_time: Option<Instant>,
     pub last_message_time: Option<Instant>,
+    /// Last time the peer delivered a block which became our tip
+    pub last_block_time: Option<Instant>,
     pub ping: Option<Duration>,
     pub is_feeler: bool,
     pub connected_time: Instant,
@@ -27,6 +29,7 @@ pub struct Peer {
     pub session_type: SessionType,
     pub protocols: FnvHashMap<ProtocolId, ProtocolVersion>,
     pub is_reserved: bool,
+    pub is_whitelisted: bool,
 }
 
 impl Peer {
@@ -43,6 +46,7 @@ impl Peer {
             ping: None,
             last_ping_time: None,
             last_message_time: None,
+            last_block_time: None,
             connected_time: Instant::now(),
             is_feeler: false,
             peer_id,
@@ -50,9 +54,14 @@ impl Peer {
             session_type,
             protocols: FnvHashMap::with_capacity_and_hasher(1, Default::default()),
             is_reserved,
+            is_whitelisted: false,
         }
     }
 
+    pub fn is_ws(&self) -> bool {
+        self.address.is_ws()
+    }
+
     pub fn is_outbound(&self) -> bool {
         self.session_type.is_outbound()
     }
@@ -1,3 +1,4 @@
+use crate::network_group::{Group, MultiaddrExt};
 use crate::peer_store::PeerStore;
 use crate::{errors::PeerError, Peer, PeerId, SessionType};
 use fnv::{FnvHashMap, FnvHashSet};
@@ -5,6 +6,7 @@ use log::debug;
 use p2p::{multiaddr::Multiaddr, SessionId};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use std::time::Duration;
 
 pub(crate) const EVICTION_PROTECT_PEERS: usize = 8;
 
@@ -14,10 +16,17 @@ pub struct PeerRegistry {
     max_inbound: u32,
     // max outbound limitation
     max_outbound: u32,
+    // max inbound limitation of the WebSocket listener, these peers do not
+    // take the inbound slots
+    max_ws_inbound: u32,
     // Only reserved peers or allow all peers.
     reserved_only: bool,
     reserved_peers: FnvHashSet<PeerId>,
+    // Exempt from the connection limits, eviction and bans
+    whitelist_peers: FnvHashSet<PeerId>,
     feeler_peers: FnvHashSet<PeerId>,
+    // Only these peers may connect when it is set
+    allowlist: Option<FnvHashSet<PeerId>>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -25,8 +34,10 @@ pub struct ConnectionStatus {
     pub total: u32,
     pub unreserved_inbound: u32,
     pub unreserved_outbound: u32,
+    pub ws_inbound: u32,
     pub max_inbound: u32,
     pub max_outbound: u32,
+    pub max_ws_inbound: u32,
 }
 
 fn sort_then_drop<T, F>(list: &mut Vec<T>, n: usize, compare: F)
@@ -54,13 +65,42 @@ impl PeerRegistry {
         PeerRegistry {
             peers: FnvHashMap::with_capacity_and_hasher(20, Default::default()),
             reserved_peers: reserved_peers_set,
+            whitelist_peers: FnvHashSet::default(),
             feeler_peers: FnvHashSet::default(),
             max_inbound,
             max_outbound,
+            max_ws_inbound: 0,
             reserved_only,
+            allowlist: None,
         }
     }
 
+    pub fn allowlist(mut self, allowlist: Vec<PeerId>) -> Self {
+        self.allowlist = Some(allowlist.into_iter().collect());
+        self
+    }
+
+    pub fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        self.allowlist
+            .as_ref()
+            .map(|allowlist| allowlist.contains(peer_id))
+            .unwrap_or(true)
+    }
+
+    pub fn max_ws_inbound(mut self, max_ws_inbound: u32) -> Self {
+        self.max_ws_inbound = max_ws_inbound;
+        self
+    }
+
+    pub fn whitelist_peers(mut self, whitelist_peers: Vec<PeerId>) -> Self {
+        self.whitelist_peers = whitelist_peers.into_iter().collect();
+        self
+    }
+
+    pub fn is_whitelisted(&self, peer_id: &PeerId) -> bool {
+        self.whitelist_peers.contains(peer_id)
+    }
+
     pub(crate) fn accept_peer(
         &mut self,
         peer_id: PeerId,
@@ -75,22 +115,31 @@ impl PeerRegistry {
         if self.get_key_by_peer_id(&peer_id).is_some() {
             return Err(PeerError::PeerIdExists(peer_id));
         }
+        // Even the reserved and whitelisted peers must be in the allowlist
+        if !self.is_allowed(&peer_id) {
+            return Err(PeerError::NotAllowed);
+        }
 
         let is_reserved = self.reserved_peers.contains(&peer_id);
+        let is_whitelisted = self.whitelist_peers.contains(&peer_id);
         let mut evicted_peer: Option<Peer> = None;
 
-        if !is_reserved {
-            if self.reserved_only {
-                return Err(PeerError::NonReserved);
-            }
+        if self.reserved_only && !is_reserved {
+            return Err(PeerError::NonReserved);
+        }
+        if !is_reserved && !is_whitelisted {
             // ban_list lock acquired
-            if peer_store.is_banned(&peer_id) {
+            if peer_store.is_banned(&peer_id) || peer_store.is_addr_banned(&remote_addr) {
                 return Err(PeerError::Banned);
             }
 
             let connection_status = self.connection_status();
             // check peers connection limitation
-            if session_type.is_inbound() {
+            if session_type.is_inbound() && remote_addr.is_ws() {
+                if connection_status.ws_inbound >= self.max_ws_inbound {
+                    return Err(PeerError::ReachMaxWsInboundLimit);
+                }
+            } else if session_type.is_inbound() {
                 if connection_status.unreserved_inbound >= self.max_inbound {
                     if let Some(evicted_session) = self.try_evict_inbound_peer(peer_store) {
                         evicted_peer = self.remove_peer(evicted_session);
@@ -103,7 +152,8 @@ impl PeerRegistry {
             }
         }
         peer_store.add_connected_peer(&peer_id, remote_addr.clone(), session_type);
-        let peer = Peer::new(session_id, session_type, peer_id, remote_addr, is_reserved);
+        let mut peer = Peer::new(session_id, session_type, peer_id, remote_addr, is_reserved);
+        peer.is_whitelisted = is_whitelisted;
         self.peers.insert(session_id, peer);
         Ok(evicted_peer)
     }
@@ -114,7 +164,9 @@ impl PeerRegistry {
         let mut candidate_peers = {
             self.peers
                 .values()
-                .filter(|peer| peer.is_inbound() && !peer.is_reserved)
+                .filter(|peer| {
+                    peer.is_inbound() && !peer.is_ws() && !peer.is_reserved && !peer.is_whitelisted
+                })
                 .collect::<Vec<_>>()
         };
         // Protect peers based on characteristics that an attacker hard to simulate or manipulate
@@ -243,13 +295,61 @@ impl PeerRegistry {
         self.peers.keys().cloned().collect()
     }
 
+    fn rotatable_outbound_peers(&self) -> impl Iterator<Item = &Peer> {
+        self.peers
+            .values()
+            .filter(|peer| peer.is_outbound() && !peer.is_reserved && !peer.is_whitelisted)
+    }
+
+    /// Unreserved outbound peers counted by network group
+    pub fn outbound_groups(&self) -> FnvHashMap<Group, u32> {
+        let mut groups = FnvHashMap::default();
+        for peer in self.rotatable_outbound_peers() {
+            *groups.entry(peer.network_group()).or_insert(0) += 1;
+        }
+        groups
+    }
+
+    /// Unreserved outbound peers to replace, connected for longer than
+    /// `min_age`. The peers of the most crowded groups go first, the others
+    /// are picked randomly.
+    pub fn peers_to_rotate(&self, count: usize, min_age: Duration) -> Vec<SessionId> {
+        let groups = self.outbound_groups();
+        let mut candidates = self
+            .rotatable_outbound_peers()
+            .filter(|peer| {
+                // A peer giving us the best chain is worth keeping
+                peer.connected_time.elapsed() >= min_age
+                    && peer
+                        .last_block_time
+                        .map(|time| time.elapsed() >= min_age)
+                        .unwrap_or(true)
+            })
+            .map(|peer| (groups[&peer.network_group()], peer.session_id))
+            .collect::<Vec<_>>();
+        candidates.shuffle(&mut thread_rng());
+        candidates.sort_by(|(count1, _), (count2, _)| count2.cmp(count1));
+        candidates
+            .into_iter()
+            .take(count)
+            .map(|(_, session_id)| session_id)
+            .collect()
+    }
+
     pub(crate) fn connection_status(&self) -> ConnectionStatus {
         let total = self.peers.len() as u32;
         let mut unreserved_inbound: u32 = 0;
         let mut unreserved_outbound: u32 = 0;
-        for peer in self.peers.values().filter(|peer| !peer.is_reserved) {
+        let mut ws_inbound: u32 = 0;
+        for peer in self
+            .peers
+            .values()
+            .filter(|peer| !peer.is_reserved && !peer.is_whitelisted)
+        {
             if peer.is_outbound() {
                 unreserved_outbound += 1;
+            } else if peer.is_ws() {
+                ws_inbound += 1;
             } else {
                 unreserved_inbound += 1;
             }
@@ -258,8 +358,10 @@ impl PeerRegistry {
             total,
             unreserved_inbound,
             unreserved_outbound,
+            ws_inbound,
             max_inbound: self.max_inbound,
             max_outbound: self.max_outbound,
+            max_ws_inbound: self.max_ws_inbound,
         }
     }
 }
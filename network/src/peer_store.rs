@@ -1,7 +1,14 @@
+pub mod addr_manager;
+pub mod ban_list;
 pub mod sqlite;
 
-pub use crate::{peer_store::sqlite::SqlitePeerStore, SessionType};
+pub use crate::{
+    peer_store::ban_list::{BannedAddr, IpNetwork},
+    peer_store::sqlite::SqlitePeerStore,
+    SessionType,
+};
 pub(crate) use crate::{Behaviour, PeerId};
+use crate::{ProtocolId, ProtocolVersion};
 use p2p::multiaddr::Multiaddr;
 use std::time::Duration;
 
@@ -25,6 +32,15 @@ impl Default for PeerScoreConfig {
     }
 }
 
+/// Good outbound peer saved on shutdown, reconnected first on startup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorPeer {
+    pub peer_id: PeerId,
+    pub address: Multiaddr,
+    pub last_seen_at: Duration,
+    pub protocols: Vec<(ProtocolId, ProtocolVersion)>,
+}
+
 /// PeerStore
 /// See [rfc0007](https://github.com/nervosnetwork/rfcs/blob/master/rfcs/0007-scoring-system-and-network-security/0007-scoring-system-and-network-security.md) for details.
 pub trait PeerStore: Send {
@@ -34,6 +50,16 @@ pub trait PeerStore: Send {
     /// Add discovered peer addresses
     /// this method will assume peer and addr is untrust since we have not connected to it.
     fn add_discovered_addr(&mut self, peer_id: &PeerId, address: Multiaddr) -> bool;
+    /// Add peer addresses told by the peer at `source`, the addresses from a source share a
+    /// limited number of buckets in the address manager.
+    fn add_discovered_addr_from(
+        &mut self,
+        peer_id: &PeerId,
+        address: Multiaddr,
+        source: &Multiaddr,
+    ) -> bool;
+    /// Record a dial attempt to the address
+    fn attempt_addr(&mut self, peer_id: &PeerId, address: &Multiaddr);
     /// Report peer behaviours
     fn report(&mut self, peer_id: &PeerId, behaviour: Behaviour) -> ReportResult;
     /// Update peer status
@@ -47,8 +73,9 @@ pub trait PeerStore: Send {
     /// Get addrs of a peer, note a peer may have multiple addrs
     fn peer_addrs(&self, peer_id: &PeerId, count: u32) -> Option<Vec<Multiaddr>>;
     /// Get peers for outbound connection, this method randomly return non-connected peer addrs
+    /// of distinct network groups, from both tried and new addresses.
     fn peers_to_attempt(&self, count: u32) -> Vec<(PeerId, Multiaddr)>;
-    /// Get peers for feeler connection, this method randomly return peer addrs that we never
+    /// Get peers for feeler connection, this method randomly return new peer addrs that we never
     /// connected to.
     fn peers_to_feeler(&self, count: u32) -> Vec<(PeerId, Multiaddr)>;
     /// Randomly get peers
@@ -57,8 +84,27 @@ pub trait PeerStore: Send {
     fn ban_peer(&mut self, peer_id: &PeerId, timeout: Duration);
     /// Check peer ban status
     fn is_banned(&self, peer_id: &PeerId) -> bool;
+    /// Ban an IP address or subnet, replacing the previous ban of it
+    fn ban_network(&mut self, address: IpNetwork, timeout: Duration, reason: String);
+    /// Lift the ban of an IP address or subnet, returns false if it is not banned
+    fn unban_network(&mut self, address: &IpNetwork) -> bool;
+    /// Check whether the IP of the address is banned
+    fn is_addr_banned(&self, addr: &Multiaddr) -> bool;
+    /// Unexpired bans
+    fn banned_addrs(&self) -> Vec<BannedAddr>;
+    /// Lift all bans
+    fn clear_banned_addrs(&mut self);
     /// peer score config
     fn peer_score_config(&self) -> PeerScoreConfig;
+    /// Replace the anchor peers
+    fn update_anchor_peers(&mut self, peers: Vec<AnchorPeer>);
+    /// Anchor peers saved by the last run, the most recently seen first
+    fn anchor_peers(&self) -> Vec<AnchorPeer>;
+    /// Save the address manager to disk
+    fn persist_addrs(&mut self);
+    /// Save the address manager to disk without waiting for the write, the
+    /// snapshot is skipped while the previous one is being written
+    fn persist_addrs_in_background(&mut self);
 }
 
 /// Peer Status
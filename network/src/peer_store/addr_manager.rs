@@ -0,0 +1,347 @@
+//! Address manager, in the style of the bitcoin addrman.
+//!
+//! Addresses are kept in two tables of fixed size buckets. The new table
+//! holds the addresses we have not connected to yet, the bucket of an address
+//! depends on its network group and on the network group of the peer telling
+//! us about it, so a single source can only fill a few buckets. The tried
+//! table holds the addresses we have connected to, the bucket depends on the
+//! network group of the address only. Positions are derived from a secret
+//! random key, an attacker can not predict the collisions of its addresses.
+use crate::network_group::{Group, NetworkGroup};
+use crate::peer_store::{Multiaddr, PeerId};
+use fnv::{FnvHashMap, FnvHashSet};
+use hash::new_blake2b;
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use std::time::Duration;
+
+pub const NEW_BUCKET_COUNT: usize = 1024;
+pub const TRIED_BUCKET_COUNT: usize = 256;
+pub const BUCKET_SIZE: usize = 64;
+// Buckets of the new table reachable by the addresses of one source group
+const NEW_BUCKETS_PER_SOURCE_GROUP: u64 = 64;
+// Buckets of the tried table reachable by the addresses of one group
+const TRIED_BUCKETS_PER_GROUP: u64 = 8;
+// Attempts after which a never connected address is terrible
+const MAX_RETRIES: u32 = 3;
+// Failures after which a connected address is terrible, when its last
+// success is older than MIN_FAIL_DURATION
+const MAX_FAILURES: u32 = 10;
+const MIN_FAIL_DURATION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+pub type AddrKey = (PeerId, Multiaddr);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddrInfo {
+    pub peer_id: PeerId,
+    pub addr: Multiaddr,
+    /// Network group of the peer telling us about the address
+    pub source_group: Vec<u8>,
+    pub tried: bool,
+    /// Attempts since the last success
+    pub attempts: u32,
+    pub last_attempt_at: Duration,
+    pub last_success_at: Duration,
+}
+
+impl AddrInfo {
+    pub fn new(peer_id: PeerId, addr: Multiaddr, source: &Multiaddr) -> Self {
+        AddrInfo {
+            peer_id,
+            addr,
+            source_group: group_to_bytes(&source.network_group()),
+            tried: false,
+            attempts: 0,
+            last_attempt_at: Duration::from_secs(0),
+            last_success_at: Duration::from_secs(0),
+        }
+    }
+
+    /// Terrible addresses are never selected and are the first to be replaced
+    pub fn is_terrible(&self, now: Duration) -> bool {
+        if self.last_success_at == Duration::from_secs(0) {
+            self.attempts >= MAX_RETRIES
+        } else {
+            self.attempts >= MAX_FAILURES && self.last_success_at + MIN_FAIL_DURATION < now
+        }
+    }
+
+    fn key(&self) -> AddrKey {
+        (self.peer_id.clone(), self.addr.clone())
+    }
+}
+
+pub struct AddrManager {
+    key: [u8; 32],
+    infos: FnvHashMap<AddrKey, AddrInfo>,
+    new_table: FnvHashMap<(usize, usize), AddrKey>,
+    tried_table: FnvHashMap<(usize, usize), AddrKey>,
+}
+
+impl AddrManager {
+    pub fn new(key: [u8; 32]) -> Self {
+        AddrManager {
+            key,
+            infos: FnvHashMap::default(),
+            new_table: FnvHashMap::default(),
+            tried_table: FnvHashMap::default(),
+        }
+    }
+
+    pub fn random_key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        thread_rng().fill(&mut key);
+        key
+    }
+
+    pub fn key(&self) -> &[u8; 32] {
+        &self.key
+    }
+
+    pub fn len(&self) -> usize {
+        self.infos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.infos.is_empty()
+    }
+
+    pub fn get(&self, peer_id: &PeerId, addr: &Multiaddr) -> Option<&AddrInfo> {
+        self.infos.get(&(peer_id.to_owned(), addr.to_owned()))
+    }
+
+    pub fn infos(&self) -> impl Iterator<Item = &AddrInfo> {
+        self.infos.values()
+    }
+
+    /// Addresses of the peer, the most recently connected first
+    pub fn peer_addrs(&self, peer_id: &PeerId) -> Vec<&AddrInfo> {
+        let mut infos = self
+            .infos
+            .values()
+            .filter(|info| &info.peer_id == peer_id)
+            .collect::<Vec<_>>();
+        infos.sort_by(|a, b| b.last_success_at.cmp(&a.last_success_at));
+        infos
+    }
+
+    /// Restores a persisted address, a tried address goes back to the new
+    /// table when its tried slot is taken. Returns false if no slot is free.
+    pub fn load(&mut self, mut info: AddrInfo) -> bool {
+        let key = info.key();
+        if self.infos.contains_key(&key) {
+            return false;
+        }
+        if info.tried {
+            let position = self.tried_position(&key);
+            if !self.tried_table.contains_key(&position) {
+                self.tried_table.insert(position, key.clone());
+                self.infos.insert(key, info);
+                return true;
+            }
+            info.tried = false;
+        }
+        let position = self.new_position(&key, &info.source_group);
+        if self.new_table.contains_key(&position) {
+            return false;
+        }
+        self.new_table.insert(position, key.clone());
+        self.infos.insert(key, info);
+        true
+    }
+
+    /// Adds an address told by the peer at `source` to the new table, a
+    /// taken slot is only replaced when its address is terrible. Returns
+    /// false if the address is known or rejected.
+    pub fn add(
+        &mut self,
+        peer_id: &PeerId,
+        addr: Multiaddr,
+        source: &Multiaddr,
+        now: Duration,
+    ) -> bool {
+        let info = AddrInfo::new(peer_id.to_owned(), addr, source);
+        let key = info.key();
+        if self.infos.contains_key(&key) {
+            return false;
+        }
+        let position = self.new_position(&key, &info.source_group);
+        if let Some(occupant) = self.new_table.get(&position) {
+            if !self.infos[occupant].is_terrible(now) {
+                return false;
+            }
+            let occupant = occupant.to_owned();
+            self.infos.remove(&occupant);
+        }
+        self.new_table.insert(position, key.clone());
+        self.infos.insert(key, info);
+        true
+    }
+
+    pub fn mark_attempt(&mut self, peer_id: &PeerId, addr: &Multiaddr, now: Duration) {
+        if let Some(info) = self.infos.get_mut(&(peer_id.to_owned(), addr.to_owned())) {
+            info.attempts = info.attempts.saturating_add(1);
+            info.last_attempt_at = now;
+        }
+    }
+
+    /// Moves the address to the tried table after a successful outbound
+    /// connection, the address in its tried slot goes back to the new table
+    pub fn mark_good(&mut self, peer_id: &PeerId, addr: &Multiaddr, now: Duration) {
+        let key = (peer_id.to_owned(), addr.to_owned());
+        let (tried, source_group) = {
+            // An address dialed without being known, e.g. a bootnode, goes
+            // to the tried table directly
+            let info = self
+                .infos
+                .entry(key.clone())
+                .or_insert_with(|| AddrInfo::new(peer_id.to_owned(), addr.to_owned(), addr));
+            info.attempts = 0;
+            info.last_success_at = now;
+            (info.tried, info.source_group.clone())
+        };
+        if tried {
+            return;
+        }
+
+        let new_position = self.new_position(&key, &source_group);
+        if self.new_table.get(&new_position) == Some(&key) {
+            self.new_table.remove(&new_position);
+        }
+        let tried_position = self.tried_position(&key);
+        if let Some(evicted) = self.tried_table.insert(tried_position, key.clone()) {
+            let source_group = {
+                let info = self.infos.get_mut(&evicted).expect("known address");
+                info.tried = false;
+                info.source_group.clone()
+            };
+            let position = self.new_position(&evicted, &source_group);
+            if let Some(occupant) = self.new_table.insert(position, evicted) {
+                self.infos.remove(&occupant);
+            }
+        }
+        self.infos.get_mut(&key).expect("known address").tried = true;
+    }
+
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        let keys = self
+            .infos
+            .keys()
+            .filter(|(id, _)| id == peer_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            let info = self.infos.remove(&key).expect("known address");
+            if info.tried {
+                let position = self.tried_position(&key);
+                self.tried_table.remove(&position);
+            } else {
+                let position = self.new_position(&key, &info.source_group);
+                self.new_table.remove(&position);
+            }
+        }
+    }
+
+    /// Randomly selects up to `count` addresses accepted by `filter`, each of
+    /// a distinct peer and network group. Tried and new addresses are picked
+    /// with the same chance, unless `only_new` is set.
+    pub fn select<F>(
+        &self,
+        count: usize,
+        only_new: bool,
+        now: Duration,
+        filter: F,
+    ) -> Vec<&AddrInfo>
+    where
+        F: Fn(&AddrInfo) -> bool,
+    {
+        let candidates = |table: &FnvHashMap<(usize, usize), AddrKey>| {
+            let mut infos = table
+                .values()
+                .map(|key| &self.infos[key])
+                .filter(|info| !info.is_terrible(now))
+                .collect::<Vec<_>>();
+            infos.shuffle(&mut thread_rng());
+            infos
+        };
+        let mut tried = if only_new {
+            Vec::new()
+        } else {
+            candidates(&self.tried_table)
+        };
+        let mut new = candidates(&self.new_table);
+
+        let mut rng = thread_rng();
+        let mut groups = FnvHashSet::default();
+        let mut peers = FnvHashSet::default();
+        let mut selected = Vec::with_capacity(count);
+        while selected.len() < count {
+            let info = match (tried.is_empty(), new.is_empty()) {
+                (true, true) => break,
+                (false, true) => tried.pop(),
+                (true, false) => new.pop(),
+                (false, false) => {
+                    if rng.gen() {
+                        tried.pop()
+                    } else {
+                        new.pop()
+                    }
+                }
+            }
+            .expect("not empty");
+            if peers.contains(&info.peer_id) || !filter(info) {
+                continue;
+            }
+            if !groups.insert(info.addr.network_group()) {
+                continue;
+            }
+            peers.insert(info.peer_id.clone());
+            selected.push(info);
+        }
+        selected
+    }
+
+    fn new_position(&self, key: &AddrKey, source_group: &[u8]) -> (usize, usize) {
+        let group = group_to_bytes(&key.1.network_group());
+        let index = self.hash(&[b"new", &group, source_group]) % NEW_BUCKETS_PER_SOURCE_GROUP;
+        let bucket =
+            self.hash(&[b"new", source_group, &index.to_le_bytes()]) % NEW_BUCKET_COUNT as u64;
+        (bucket as usize, self.slot(b"new", bucket, key))
+    }
+
+    fn tried_position(&self, key: &AddrKey) -> (usize, usize) {
+        let group = group_to_bytes(&key.1.network_group());
+        let index =
+            self.hash(&[b"tried", key.0.as_bytes(), key.1.as_ref()]) % TRIED_BUCKETS_PER_GROUP;
+        let bucket =
+            self.hash(&[b"tried", &group, &index.to_le_bytes()]) % TRIED_BUCKET_COUNT as u64;
+        (bucket as usize, self.slot(b"tried", bucket, key))
+    }
+
+    fn slot(&self, table: &[u8], bucket: u64, key: &AddrKey) -> usize {
+        let slot = self.hash(&[
+            table,
+            &bucket.to_le_bytes(),
+            key.0.as_bytes(),
+            key.1.as_ref(),
+        ]);
+        (slot % BUCKET_SIZE as u64) as usize
+    }
+
+    fn hash(&self, parts: &[&[u8]]) -> u64 {
+        let mut blake2b = new_blake2b();
+        blake2b.update(&self.key);
+        for part in parts {
+            blake2b.update(&(part.len() as u64).to_le_bytes());
+            blake2b.update(part);
+        }
+        let mut result = [0u8; 32];
+        blake2b.finalize(&mut result);
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&result[..8]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+fn group_to_bytes(group: &Group) -> Vec<u8> {
+    format!("{:?}", group).into_bytes()
+}
@@ -0,0 +1,181 @@
+use crate::network_group::MultiaddrExt;
+use crate::peer_store::Multiaddr;
+use fnv::FnvHashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// An IP address or subnet, written as `ip` or `ip/prefix`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    ip: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// The bits of `ip` beyond `prefix` are cleared, an IPv4 mapped IPv6
+    /// network is kept as the IPv4 network
+    pub fn new(ip: IpAddr, prefix: u8) -> Result<Self, String> {
+        let (ip, prefix) = match ip {
+            IpAddr::V6(ipv6) if prefix >= 96 && prefix <= 128 => match to_ipv4_mapped(ipv6) {
+                Some(ipv4) => (IpAddr::V4(ipv4), prefix - 96),
+                None => (ip, prefix),
+            },
+            _ => (ip, prefix),
+        };
+        let ip = match ip {
+            IpAddr::V4(ipv4) => {
+                if prefix > 32 {
+                    return Err(format!("invalid IPv4 prefix {}", prefix));
+                }
+                let mask = u32::max_value()
+                    .checked_shl(32 - u32::from(prefix))
+                    .unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(u32::from(ipv4) & mask))
+            }
+            IpAddr::V6(ipv6) => {
+                if prefix > 128 {
+                    return Err(format!("invalid IPv6 prefix {}", prefix));
+                }
+                let mask = u128::max_value()
+                    .checked_shl(128 - u32::from(prefix))
+                    .unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(ipv6) & mask))
+            }
+        };
+        Ok(IpNetwork { ip, prefix })
+    }
+
+    /// The network of the single address
+    pub fn from_ip(ip: IpAddr) -> Self {
+        let ip = canonical_ip(ip);
+        let prefix = if ip.is_ipv4() { 32 } else { 128 };
+        IpNetwork { ip, prefix }
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        self.ip
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // IPv4 mapped IPv6 addresses belong to the IPv4 networks
+        let ip = canonical_ip(ip);
+        if ip.is_ipv4() != self.ip.is_ipv4() {
+            return false;
+        }
+        IpNetwork::new(ip, self.prefix)
+            .map(|network| network.ip == self.ip)
+            .unwrap_or(false)
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let ip = parts
+            .next()
+            .unwrap_or_default()
+            .parse::<IpAddr>()
+            .map_err(|err| format!("invalid ip {}: {}", s, err))?;
+        match parts.next() {
+            Some(prefix) => {
+                let prefix = prefix
+                    .parse::<u8>()
+                    .map_err(|err| format!("invalid prefix {}: {}", s, err))?;
+                IpNetwork::new(ip, prefix)
+            }
+            None => Ok(IpNetwork::from_ip(ip)),
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix)
+    }
+}
+
+// The IPv4 address of an IPv4 mapped IPv6 address `::ffff:a.b.c.d`, unlike
+// `Ipv6Addr::to_ipv4` the IPv4 compatible ones such as `::1` are not
+fn to_ipv4_mapped(ipv6: Ipv6Addr) -> Option<Ipv4Addr> {
+    match ipv6.segments() {
+        [0, 0, 0, 0, 0, 0xffff, high, low] => {
+            Some(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)))
+        }
+        _ => None,
+    }
+}
+
+// The same address is banned whichever way it is written
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => to_ipv4_mapped(ipv6).map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BannedAddr {
+    pub address: IpNetwork,
+    pub ban_until: Duration,
+    pub ban_reason: String,
+    pub created_at: Duration,
+}
+
+/// Banned networks, an address is banned while its IP is in one of them
+#[derive(Default)]
+pub struct BanList {
+    inner: FnvHashMap<IpNetwork, BannedAddr>,
+}
+
+impl BanList {
+    pub fn insert(&mut self, banned_addr: BannedAddr) {
+        self.inner.insert(banned_addr.address, banned_addr);
+    }
+
+    pub fn remove(&mut self, address: &IpNetwork) -> Option<BannedAddr> {
+        self.inner.remove(address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn is_ip_banned(&self, ip: IpAddr, now: Duration) -> bool {
+        self.inner
+            .values()
+            .any(|banned_addr| banned_addr.ban_until > now && banned_addr.address.contains(ip))
+    }
+
+    pub fn is_addr_banned(&self, addr: &Multiaddr, now: Duration) -> bool {
+        match addr.extract_ip_addr() {
+            Some(ip) => self.is_ip_banned(ip, now),
+            None => false,
+        }
+    }
+
+    /// Unexpired bans
+    pub fn banned_addrs(&self, now: Duration) -> Vec<BannedAddr> {
+        self.inner
+            .values()
+            .filter(|banned_addr| banned_addr.ban_until > now)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_expires(&mut self, now: Duration) {
+        self.inner
+            .retain(|_, banned_addr| banned_addr.ban_until > now);
+    }
+}
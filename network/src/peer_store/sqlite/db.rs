@@ -1,12 +1,14 @@
 use crate::network_group::{Group, NetworkGroup};
+use crate::peer_store::addr_manager::AddrInfo;
+use crate::peer_store::ban_list::{BannedAddr, IpNetwork};
 use crate::peer_store::sqlite::DBError;
-use crate::peer_store::{Multiaddr, PeerId, Score, Status};
-use crate::SessionType;
-use rusqlite::types::ToSql;
+use crate::peer_store::{AnchorPeer, Multiaddr, PeerId, Score, Status};
+use crate::{ProtocolId, SessionType};
 use rusqlite::OptionalExtension;
 use rusqlite::{Connection, NO_PARAMS};
 use std::convert::TryFrom;
 use std::iter::FromIterator;
+use std::net::IpAddr;
 use std::time::Duration;
 
 type DBResult<T> = Result<T, DBError>;
@@ -27,20 +29,39 @@ pub fn create_tables(conn: &Connection) -> DBResult<()> {
     "#;
     conn.execute_batch(sql)?;
     let sql = r#"
-    CREATE TABLE IF NOT EXISTS peer_addr (
+    CREATE TABLE IF NOT EXISTS banned_addr (
     id INTEGER PRIMARY KEY NOT NULL,
-    peer_info_id INTEGER NOT NULL,
+    address TEXT UNIQUE NOT NULL,
+    ban_until INTEGER NOT NULL,
+    ban_reason TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+    );
+    "#;
+    conn.execute_batch(sql)?;
+    let sql = r#"
+    CREATE TABLE IF NOT EXISTS anchor_peer (
+    id INTEGER PRIMARY KEY NOT NULL,
+    peer_id BINARY UNIQUE NOT NULL,
     addr BINARY NOT NULL,
-    last_connected_at INTEGER NOT NULL
+    last_seen_at INTEGER NOT NULL,
+    protocols TEXT NOT NULL
     );
-    CREATE UNIQUE INDEX IF NOT EXISTS idx_peer_info_id_addr_on_peer_addr ON peer_addr (peer_info_id, addr);
     "#;
     conn.execute_batch(sql)?;
     let sql = r#"
-    CREATE TABLE IF NOT EXISTS ban_list (
+    CREATE TABLE IF NOT EXISTS addr_manager_key (
+    id INTEGER PRIMARY KEY NOT NULL,
+    key BINARY NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS addr_info (
     id INTEGER PRIMARY KEY NOT NULL,
-    ip BINARY UNIQUE NOT NULL,
-    ban_time INTEGER NOT NULL
+    peer_id BINARY NOT NULL,
+    addr BINARY NOT NULL,
+    source_group BINARY NOT NULL,
+    tried BOOL NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_attempt_at INTEGER NOT NULL,
+    last_success_at INTEGER NOT NULL
     );
     "#;
     conn.execute_batch(sql).map_err(Into::into)
@@ -185,179 +206,254 @@ impl PeerInfo {
     }
 }
 
-pub struct PeerAddr;
-
-impl PeerAddr {
-    pub fn insert(
-        conn: &Connection,
-        peer_info_id: u32,
-        addr: &Multiaddr,
-        last_connected_at: Duration,
-    ) -> DBResult<usize> {
-        let mut stmt = conn.prepare(
-            "INSERT OR IGNORE INTO peer_addr (peer_info_id, addr, last_connected_at)
-                     VALUES(:peer_info_id, :addr, :last_connected_at)",
-        )?;
-        stmt.execute_named(&[
-            (":peer_info_id", &peer_info_id),
-            (":addr", &addr.as_ref()),
-            (":last_connected_at", &duration_to_secs(last_connected_at)),
-        ])
-        .map_err(Into::into)
+/// Addresses kept in the `peer_addr` table before the address manager, with
+/// the time of their last outbound connection. Empty if the table is gone.
+pub fn get_legacy_peer_addrs(conn: &Connection) -> DBResult<Vec<(PeerId, Multiaddr, Duration)>> {
+    let exists = conn.query_row::<u32, _, _>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='peer_addr'",
+        NO_PARAMS,
+        |r| r.get(0),
+    )? > 0;
+    if !exists {
+        return Ok(Vec::new());
     }
+    let mut stmt = conn.prepare(
+        "SELECT peer_info.peer_id, peer_addr.addr, peer_addr.last_connected_at FROM peer_addr
+                     INNER JOIN peer_info ON peer_info.id = peer_addr.peer_info_id
+                     ORDER BY peer_addr.last_connected_at DESC",
+    )?;
+    let rows = stmt.query_map(NO_PARAMS, |row| {
+        Ok((
+            PeerId::from_bytes(row.get(0)?).expect("parse peer_id"),
+            Multiaddr::try_from(row.get::<_, Vec<u8>>(1)?).expect("parse multiaddr"),
+            secs_to_duration(row.get(2)?),
+        ))
+    })?;
+    Result::from_iter(rows).map_err(Into::into)
+}
 
-    pub fn update_connected_at(
-        conn: &Connection,
-        peer_info_id: u32,
-        addr: Multiaddr,
-        last_connected_at: Duration,
-    ) -> DBResult<usize> {
-        let mut stmt = conn.prepare(
-            "UPDATE peer_addr SET last_connected_at=:last_connected_at
-                    WHERE peer_info_id=:peer_info_id AND addr=:addr",
-        )?;
-        stmt.execute_named(&[
-            (":peer_info_id", &peer_info_id),
-            (":addr", &addr.as_ref()),
-            (":last_connected_at", &duration_to_secs(last_connected_at)),
-        ])
+pub fn drop_legacy_peer_addrs(conn: &Connection) -> DBResult<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS peer_addr")
         .map_err(Into::into)
-    }
-
-    pub fn get_addrs(conn: &Connection, id: u32, count: u32) -> DBResult<Vec<Multiaddr>> {
-        let mut stmt = conn.prepare(
-            "SELECT addr FROM peer_addr WHERE peer_info_id == :id 
-                         ORDER BY last_connected_at DESC LIMIT :count",
-        )?;
-        let rows = stmt.query_map_named(&[(":id", &id), (":count", &count)], |row| {
-            Ok(Multiaddr::try_from(row.get::<_, Vec<u8>>(0)?).expect("parse multiaddr"))
-        })?;
-        Result::from_iter(rows).map_err(Into::into)
-    }
-
-    pub fn delete_by_peer_id(conn: &Connection, id: u32) -> DBResult<usize> {
-        conn.execute("DELETE FROM peer_addr WHERE peer_info_id=?1", &[id])
-            .map_err(Into::into)
-    }
 }
 
-pub fn get_random_peers(
-    conn: &Connection,
-    count: u32,
-    expired_at: Duration,
-) -> DBResult<Vec<(PeerId, Multiaddr)>> {
-    // random select peers that we have connect to recently.
+pub fn insert_banned_addr(conn: &Connection, banned_addr: &BannedAddr) -> DBResult<usize> {
     let mut stmt = conn.prepare(
-        "SELECT id, peer_id FROM peer_info 
-                                WHERE ban_time < strftime('%s','now') 
-                                AND last_connected_at > :time 
-                                ORDER BY RANDOM() LIMIT :count",
-    )?;
-    let rows = stmt.query_map_named(
-        &[(":count", &count), (":time", &duration_to_secs(expired_at))],
-        |row| {
-            Ok((
-                row.get::<_, u32>(0)?,
-                PeerId::from_bytes(row.get(1)?).expect("parse peer_id"),
-            ))
-        },
+        "INSERT OR REPLACE INTO banned_addr (address, ban_until, ban_reason, created_at)
+                     VALUES(:address, :ban_until, :ban_reason, :created_at)",
     )?;
+    stmt.execute_named(&[
+        (":address", &banned_addr.address.to_string()),
+        (":ban_until", &duration_to_secs(banned_addr.ban_until)),
+        (":ban_reason", &banned_addr.ban_reason),
+        (":created_at", &duration_to_secs(banned_addr.created_at)),
+    ])
+    .map_err(Into::into)
+}
 
-    let mut peers = Vec::with_capacity(count as usize);
-    for row in rows {
-        let (id, peer_id) = row?;
-        let mut addrs = PeerAddr::get_addrs(conn, id, 1)?;
-        if let Some(addr) = addrs.pop() {
-            peers.push((peer_id, addr));
-        }
-    }
-    Ok(peers)
+pub fn delete_banned_addr(conn: &Connection, address: &IpNetwork) -> DBResult<usize> {
+    conn.execute(
+        "DELETE FROM banned_addr WHERE address=?1",
+        &[&address.to_string()],
+    )
+    .map_err(Into::into)
 }
 
-pub fn get_peers_to_attempt(conn: &Connection, count: u32) -> DBResult<Vec<(u32, PeerId)>> {
-    // random select peers
+pub fn get_banned_addrs(conn: &Connection, now: Duration) -> DBResult<Vec<BannedAddr>> {
     let mut stmt = conn.prepare(
-        "SELECT id, peer_id FROM peer_info 
-                                WHERE status != :connected_status 
-                                AND ban_time < strftime('%s','now') 
-                                ORDER BY RANDOM() LIMIT :count",
-    )?;
-    let rows = stmt.query_map_named(
-        &[
-            (
-                ":connected_status",
-                &status_to_u8(Status::Connected) as &ToSql,
-            ),
-            (":count", &count),
-        ],
-        |row| {
-            Ok((
-                row.get::<_, u32>(0)?,
-                PeerId::from_bytes(row.get(1)?).expect("parse peer_id"),
-            ))
-        },
+        "SELECT address, ban_until, ban_reason, created_at FROM banned_addr WHERE ban_until > :now",
     )?;
-
-    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    let rows = stmt.query_map_named(&[(":now", &duration_to_secs(now))], |row| {
+        Ok(BannedAddr {
+            address: row
+                .get::<_, String>(0)?
+                .parse()
+                .expect("parse banned address"),
+            ban_until: secs_to_duration(row.get(1)?),
+            ban_reason: row.get(2)?,
+            created_at: secs_to_duration(row.get(3)?),
+        })
+    })?;
+    Result::from_iter(rows).map_err(Into::into)
 }
 
-pub fn get_peers_to_feeler(
-    conn: &Connection,
-    count: u32,
-    expired_at: Duration,
-) -> DBResult<Vec<(u32, PeerId)>> {
-    // random select peers
-    let mut stmt = conn.prepare(
-        "SELECT id, peer_id FROM peer_info 
-                                WHERE status != :connected_status 
-                                AND last_connected_at < :time 
-                                AND ban_time < strftime('%s','now') 
-                                ORDER BY RANDOM() LIMIT :count",
-    )?;
-    let rows = stmt.query_map_named(
-        &[
-            (
-                ":connected_status",
-                &status_to_u8(Status::Connected) as &ToSql,
-            ),
-            (":time", &duration_to_secs(expired_at)),
-            (":count", &count),
-        ],
-        |row| {
-            Ok((
-                row.get::<_, u32>(0)?,
-                PeerId::from_bytes(row.get(1)?).expect("parse peer_id"),
-            ))
-        },
-    )?;
-    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+pub fn clear_expires_banned_addrs(conn: &Connection, now: Duration) -> DBResult<usize> {
+    let mut stmt = conn.prepare("DELETE FROM banned_addr WHERE ban_until < :now")?;
+    stmt.execute_named(&[(":now", &duration_to_secs(now))])
+        .map_err(Into::into)
 }
 
-pub fn insert_ban_record(conn: &Connection, ip: &[u8], ban_time: Duration) -> DBResult<usize> {
-    let mut stmt =
-        conn.prepare("INSERT OR REPLACE INTO ban_list (ip, ban_time) VALUES(:ip, :ban_time);")?;
-    stmt.execute_named(&[(":ip", &ip), (":ban_time", &duration_to_secs(ban_time))])
+pub fn clear_banned_addrs(conn: &Connection) -> DBResult<usize> {
+    conn.execute("DELETE FROM banned_addr", NO_PARAMS)
         .map_err(Into::into)
 }
 
-pub fn get_ban_records(conn: &Connection, now: Duration) -> DBResult<Vec<(Vec<u8>, Duration)>> {
+/// Unexpired bans kept in the `ban_list` table before the banned networks,
+/// the IP and the time the ban ends. Empty if the table is gone.
+pub fn get_legacy_ban_records(
+    conn: &Connection,
+    now: Duration,
+) -> DBResult<Vec<(IpAddr, Duration)>> {
+    let exists = conn.query_row::<u32, _, _>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='ban_list'",
+        NO_PARAMS,
+        |r| r.get(0),
+    )? > 0;
+    if !exists {
+        return Ok(Vec::new());
+    }
     let mut stmt = conn.prepare("SELECT ip, ban_time FROM ban_list WHERE ban_time > :now")?;
     let rows = stmt.query_map_named(&[(":now", &duration_to_secs(now))], |row| {
         Ok((row.get::<_, Vec<u8>>(0)?, secs_to_duration(row.get(1)?)))
     })?;
+    let records = Result::<Vec<_>, _>::from_iter(rows)?;
+    Ok(records
+        .into_iter()
+        .filter_map(|(ip, ban_time)| bytes_to_ip(&ip).map(|ip| (ip, ban_time)))
+        .collect())
+}
+
+pub fn drop_legacy_ban_list(conn: &Connection) -> DBResult<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS ban_list")
+        .map_err(Into::into)
+}
+
+/// Replaces the anchor peers
+pub fn replace_anchor_peers(conn: &mut Connection, peers: &[AnchorPeer]) -> DBResult<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM anchor_peer", NO_PARAMS)?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO anchor_peer (peer_id, addr, last_seen_at, protocols) VALUES(:peer_id, :addr, :last_seen_at, :protocols)",
+        )?;
+        for peer in peers {
+            stmt.execute_named(&[
+                (":peer_id", &peer.peer_id.as_bytes()),
+                (":addr", &peer.address.as_ref()),
+                (":last_seen_at", &duration_to_secs(peer.last_seen_at)),
+                (":protocols", &protocols_to_string(&peer.protocols)),
+            ])?;
+        }
+    }
+    tx.commit().map_err(Into::into)
+}
+
+/// Anchor peers, the most recently seen first
+pub fn get_anchor_peers(conn: &Connection) -> DBResult<Vec<AnchorPeer>> {
+    let mut stmt = conn.prepare(
+        "SELECT peer_id, addr, last_seen_at, protocols FROM anchor_peer ORDER BY last_seen_at DESC",
+    )?;
+    let rows = stmt.query_map(NO_PARAMS, |row| {
+        Ok(AnchorPeer {
+            peer_id: PeerId::from_bytes(row.get(0)?).expect("parse peer_id"),
+            address: Multiaddr::try_from(row.get::<_, Vec<u8>>(1)?).expect("parse multiaddr"),
+            last_seen_at: secs_to_duration(row.get(2)?),
+            protocols: string_to_protocols(&row.get::<_, String>(3)?),
+        })
+    })?;
     Result::from_iter(rows).map_err(Into::into)
 }
 
-pub fn clear_expires_banned_ip(conn: &Connection, now: Duration) -> DBResult<Vec<Vec<u8>>> {
-    let mut stmt = conn.prepare("SELECT ip FROM ban_list WHERE ban_time < :now")?;
-    let rows = stmt.query_map_named(&[(":now", &duration_to_secs(now))], |row| {
-        row.get::<_, Vec<u8>>(0)
+pub fn get_addr_manager_key(conn: &Connection) -> DBResult<Option<Vec<u8>>> {
+    conn.query_row(
+        "SELECT key FROM addr_manager_key WHERE id=0",
+        NO_PARAMS,
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(Into::into)
+}
+
+pub fn set_addr_manager_key(conn: &Connection, key: &[u8]) -> DBResult<usize> {
+    let mut stmt =
+        conn.prepare("INSERT OR REPLACE INTO addr_manager_key (id, key) VALUES(0, :key)")?;
+    stmt.execute_named(&[(":key", &key)]).map_err(Into::into)
+}
+
+/// Replaces the addresses of the address manager
+pub fn replace_addr_infos<'a, I: Iterator<Item = &'a AddrInfo>>(
+    conn: &mut Connection,
+    infos: I,
+) -> DBResult<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM addr_info", NO_PARAMS)?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO addr_info (peer_id, addr, source_group, tried, attempts, last_attempt_at, last_success_at)
+                     VALUES(:peer_id, :addr, :source_group, :tried, :attempts, :last_attempt_at, :last_success_at)",
+        )?;
+        for info in infos {
+            stmt.execute_named(&[
+                (":peer_id", &info.peer_id.as_bytes()),
+                (":addr", &info.addr.as_ref()),
+                (":source_group", &info.source_group),
+                (":tried", &info.tried),
+                (":attempts", &info.attempts),
+                (":last_attempt_at", &duration_to_secs(info.last_attempt_at)),
+                (":last_success_at", &duration_to_secs(info.last_success_at)),
+            ])?;
+        }
+    }
+    tx.commit().map_err(Into::into)
+}
+
+/// Addresses of the address manager, the tried ones first
+pub fn get_addr_infos(conn: &Connection) -> DBResult<Vec<AddrInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT peer_id, addr, source_group, tried, attempts, last_attempt_at, last_success_at FROM addr_info ORDER BY tried DESC",
+    )?;
+    let rows = stmt.query_map(NO_PARAMS, |row| {
+        Ok(AddrInfo {
+            peer_id: PeerId::from_bytes(row.get(0)?).expect("parse peer_id"),
+            addr: Multiaddr::try_from(row.get::<_, Vec<u8>>(1)?).expect("parse multiaddr"),
+            source_group: row.get(2)?,
+            tried: row.get(3)?,
+            attempts: row.get(4)?,
+            last_attempt_at: secs_to_duration(row.get(5)?),
+            last_success_at: secs_to_duration(row.get(6)?),
+        })
     })?;
-    let mut stmt = conn.prepare("DELETE FROM ban_list WHERE ban_time < :now")?;
-    stmt.execute_named(&[(":now", &duration_to_secs(now))])?;
     Result::from_iter(rows).map_err(Into::into)
 }
 
+// Protocols are kept as `id:version` pairs separated by commas
+fn protocols_to_string(protocols: &[(ProtocolId, String)]) -> String {
+    protocols
+        .iter()
+        .map(|(id, version)| format!("{}:{}", id.value(), version))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn string_to_protocols(protocols: &str) -> Vec<(ProtocolId, String)> {
+    protocols
+        .split(',')
+        .filter_map(|protocol| {
+            let mut parts = protocol.splitn(2, ':');
+            let id = parts.next()?.parse::<usize>().ok()?;
+            let version = parts.next()?;
+            Some((id.into(), version.to_owned()))
+        })
+        .collect()
+}
+
+// The octets of an IPv4 or IPv6 address
+fn bytes_to_ip(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => {
+            let mut octets = [0u8; 4];
+            octets.copy_from_slice(bytes);
+            Some(IpAddr::from(octets))
+        }
+        16 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(bytes);
+            Some(IpAddr::from(octets))
+        }
+        _ => None,
+    }
+}
+
 fn status_to_u8(status: Status) -> u8 {
     status as u8
 }
@@ -1,4 +1,6 @@
 use crate::network_group::MultiaddrExt;
+use crate::peer_store::addr_manager::{AddrInfo, AddrManager};
+use crate::peer_store::ban_list::{BanList, BannedAddr, IpNetwork};
 use crate::peer_store::sqlite::{db, DBError};
 /// SqlitePeerStore
 /// Principles:
@@ -12,12 +14,16 @@ use crate::peer_store::sqlite::{db, DBError};
 ///    score.
 /// 4. Good peers can get higher score than bad peers.
 use crate::peer_store::{
-    Behaviour, Multiaddr, PeerId, PeerScoreConfig, PeerStore, ReportResult, Score, Status,
+    AnchorPeer, Behaviour, Multiaddr, PeerId, PeerScoreConfig, PeerStore, ReportResult, Score,
+    Status,
 };
 use crate::SessionType;
+use crossbeam_channel::{bounded, Sender, TrySendError};
 use faketime::unix_time;
-use fnv::FnvHashMap;
-use rusqlite::Connection;
+use log::{debug, warn};
+use p2p::multiaddr::Protocol;
+use rusqlite::{Connection, NO_PARAMS};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
 /// After this limitation, peer store will try to eviction peers
@@ -26,20 +32,90 @@ pub(crate) const PEER_STORE_LIMIT: u32 = 8192;
 pub(crate) const LAST_CONNECTED_TIMEOUT_SECS: u64 = 14 * 24 * 3600;
 /// Clear banned list if the list reach this size
 const BAN_LIST_CLEAR_EXPIRES_SIZE: usize = 1024;
-const DEFAULT_ADDRS: u32 = 3;
+const PEER_BAN_REASON: &str = "misbehaviour";
+/// Time a connection waits for the lock held by the other one
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct SqlitePeerStore {
     bootnodes: Vec<(PeerId, Multiaddr)>,
     peer_score_config: PeerScoreConfig,
-    ban_list: FnvHashMap<Vec<u8>, Duration>,
+    ban_list: BanList,
+    addr_manager: AddrManager,
+    // None for the stores which can not be opened twice, they persist the
+    // address manager in place
+    addrs_writer: Option<AddrsWriter>,
     pub(crate) conn: Connection,
 }
 
+// Writes the snapshots of the address manager on its own connection, the
+// network reactor only pays for the snapshot
+struct AddrsWriter {
+    // The snapshot and the sender notified once it is written
+    sender: Option<Sender<(Vec<AddrInfo>, Option<Sender<()>>)>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AddrsWriter {
+    fn spawn(mut conn: Connection) -> Self {
+        // One snapshot waits while another one is written, newer ones are
+        // skipped until then
+        let (sender, receiver) = bounded::<(Vec<AddrInfo>, Option<Sender<()>>)>(1);
+        let thread = thread::Builder::new()
+            .name("PersistAddrs".to_string())
+            .spawn(move || {
+                for (infos, done) in receiver {
+                    if let Err(err) = db::replace_addr_infos(&mut conn, infos.iter()) {
+                        warn!(target: "network", "persist addr manager error: {:?}", err);
+                    }
+                    if let Some(done) = done {
+                        let _ = done.send(());
+                    }
+                }
+            })
+            .expect("spawn persist addrs thread");
+        AddrsWriter {
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    fn try_send(&self, infos: Vec<AddrInfo>) {
+        if let Some(ref sender) = self.sender {
+            if let Err(TrySendError::Full(_)) = sender.try_send((infos, None)) {
+                debug!(target: "network", "addr manager is still being persisted, skip");
+            }
+        }
+    }
+
+    // Waits until the snapshot is written, after the pending ones, so an
+    // older snapshot never overwrites it
+    fn send(&self, infos: Vec<AddrInfo>) {
+        if let Some(ref sender) = self.sender {
+            let (done_sender, done_receiver) = bounded(1);
+            if sender.send((infos, Some(done_sender))).is_ok() {
+                let _ = done_receiver.recv();
+            }
+        }
+    }
+}
+
+impl Drop for AddrsWriter {
+    fn drop(&mut self) {
+        // Finish the pending snapshot
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
 impl SqlitePeerStore {
     pub fn new(conn: Connection, peer_score_config: PeerScoreConfig) -> Self {
         let mut peer_store = SqlitePeerStore {
             bootnodes: Vec::new(),
             ban_list: Default::default(),
+            addr_manager: AddrManager::new([0u8; 32]),
+            addrs_writer: None,
             conn,
             peer_score_config,
         };
@@ -48,8 +124,13 @@ impl SqlitePeerStore {
     }
 
     pub fn file(path: String) -> Result<Self, DBError> {
-        let conn = Connection::open(path)?;
-        Ok(SqlitePeerStore::new(conn, PeerScoreConfig::default()))
+        let conn = open_file(&path)?;
+        let mut peer_store = SqlitePeerStore::new(conn, PeerScoreConfig::default());
+        // An empty path is a private temporary database
+        if !path.is_empty() {
+            peer_store.addrs_writer = Some(AddrsWriter::spawn(open_file(&path)?));
+        }
+        Ok(peer_store)
     }
 
     pub fn memory() -> Result<Self, DBError> {
@@ -65,7 +146,8 @@ impl SqlitePeerStore {
     fn prepare(&mut self) -> Result<(), DBError> {
         self.create_tables()?;
         self.reset_status()?;
-        self.load_banlist()
+        self.load_banlist()?;
+        self.load_addr_manager()
     }
 
     fn create_tables(&self) -> Result<(), DBError> {
@@ -77,48 +159,77 @@ impl SqlitePeerStore {
     }
 
     fn load_banlist(&mut self) -> Result<(), DBError> {
-        self.clear_expires_banned_ip()?;
-        let now = unix_time();
-        let ban_records = db::get_ban_records(&self.conn, now)?;
-        for (ip, ban_time) in ban_records {
-            self.ban_list.insert(ip, ban_time);
+        self.migrate_legacy_ban_list()?;
+        self.clear_expires_banned_addrs()?;
+        for banned_addr in db::get_banned_addrs(&self.conn, unix_time())? {
+            self.ban_list.insert(banned_addr);
         }
         Ok(())
     }
 
-    fn ban_ip(&mut self, addr: &Multiaddr, timeout: Duration) {
-        let ip = {
-            match addr.extract_ip_addr_binary() {
-                Some(binary) => binary,
-                None => return,
+    // Moves the unexpired bans of the `ban_list` table, which was replaced by
+    // the banned networks, then drops the table
+    fn migrate_legacy_ban_list(&mut self) -> Result<(), DBError> {
+        let now = unix_time();
+        let tx = self.conn.transaction()?;
+        for (ip, ban_until) in db::get_legacy_ban_records(&tx, now)? {
+            let banned_addr = BannedAddr {
+                address: IpNetwork::from_ip(ip),
+                ban_until,
+                ban_reason: PEER_BAN_REASON.to_owned(),
+                created_at: now,
+            };
+            db::insert_banned_addr(&tx, &banned_addr)?;
+        }
+        db::drop_legacy_ban_list(&tx)?;
+        tx.commit().map_err(Into::into)
+    }
+
+    fn load_addr_manager(&mut self) -> Result<(), DBError> {
+        let key = match db::get_addr_manager_key(&self.conn)? {
+            Some(ref key) if key.len() == 32 => {
+                let mut buf = [0u8; 32];
+                buf.copy_from_slice(key);
+                buf
+            }
+            _ => {
+                let key = AddrManager::random_key();
+                db::set_addr_manager_key(&self.conn, &key)?;
+                key
             }
         };
-        let ban_time = unix_time() + timeout;
-        db::insert_ban_record(&self.conn, &ip, ban_time).expect("ban ip");
-        self.ban_list.insert(ip, ban_time);
-        if self.ban_list.len() > BAN_LIST_CLEAR_EXPIRES_SIZE {
-            self.clear_expires_banned_ip().expect("clear ban list");
+        self.addr_manager = AddrManager::new(key);
+        for info in db::get_addr_infos(&self.conn)? {
+            self.addr_manager.load(info);
         }
+        self.migrate_legacy_peer_addrs()
     }
 
-    fn is_addr_banned(&self, addr: &Multiaddr) -> bool {
-        let ip = match addr.extract_ip_addr_binary() {
-            Some(ip) => ip,
-            None => return false,
-        };
+    // Moves the addresses of the `peer_addr` table, which was replaced by the
+    // address manager, then drops the table
+    fn migrate_legacy_peer_addrs(&mut self) -> Result<(), DBError> {
+        let legacy_addrs = db::get_legacy_peer_addrs(&self.conn)?;
+        if legacy_addrs.is_empty() {
+            return db::drop_legacy_peer_addrs(&self.conn);
+        }
         let now = unix_time();
-        match self.ban_list.get(&ip) {
-            Some(ban_time) => *ban_time > now,
-            None => false,
+        for (peer_id, addr, last_connected_at) in legacy_addrs {
+            let addr = without_peer_id(addr);
+            if last_connected_at > Duration::from_secs(0) {
+                self.addr_manager
+                    .mark_good(&peer_id, &addr, last_connected_at);
+            } else {
+                self.addr_manager.add(&peer_id, addr.clone(), &addr, now);
+            }
         }
+        db::replace_addr_infos(&mut self.conn, self.addr_manager.infos())?;
+        db::drop_legacy_peer_addrs(&self.conn)
     }
 
-    fn clear_expires_banned_ip(&mut self) -> Result<(), DBError> {
+    fn clear_expires_banned_addrs(&mut self) -> Result<(), DBError> {
         let now = unix_time();
-        let ips = db::clear_expires_banned_ip(&self.conn, now)?;
-        for ip in ips {
-            self.ban_list.remove(&ip);
-        }
+        db::clear_expires_banned_addrs(&self.conn, now)?;
+        self.ban_list.clear_expires(now);
         Ok(())
     }
 
@@ -145,11 +256,8 @@ impl SqlitePeerStore {
             return Err(());
         }
 
-        let tx = self.conn.transaction().expect("db tx");
-        db::PeerInfo::delete(&tx, candidate_peer.id).expect("delete peer error");
-        db::PeerAddr::delete_by_peer_id(&tx, candidate_peer.id)
-            .expect("delete peer by peer_id error");
-        tx.commit().expect("delete peer error");
+        db::PeerInfo::delete(&self.conn, candidate_peer.id).expect("delete peer error");
+        self.addr_manager.remove_peer(&candidate_peer.peer_id);
         Ok(())
     }
 
@@ -171,19 +279,18 @@ impl SqlitePeerStore {
         db::PeerInfo::get_by_peer_id(&self.conn, peer_id).expect("get peer info")
     }
 
-    fn find_addrs_for_peers(
-        &self,
-        conn: &rusqlite::Connection,
-        peers: Vec<(u32, PeerId)>,
-    ) -> Result<Vec<(PeerId, Multiaddr)>, DBError> {
-        let mut peer_addrs = Vec::with_capacity(peers.len());
-        for (id, peer_id) in peers {
-            let addrs = db::PeerAddr::get_addrs(conn, id, DEFAULT_ADDRS)?;
-            if let Some(addr) = addrs.into_iter().find(|addr| !self.is_addr_banned(&addr)) {
-                peer_addrs.push((peer_id, addr));
-            }
-        }
-        Ok(peer_addrs)
+    // Peer and address of the selected addresses, skipping banned ones
+    fn select_addrs<F>(&self, count: u32, only_new: bool, filter: F) -> Vec<(PeerId, Multiaddr)>
+    where
+        F: Fn(&AddrInfo) -> bool,
+    {
+        self.addr_manager
+            .select(count as usize, only_new, unix_time(), |info| {
+                !self.is_addr_banned(&info.addr) && filter(info)
+            })
+            .into_iter()
+            .map(|info| (info.peer_id.to_owned(), info.addr.to_owned()))
+            .collect()
     }
 }
 
@@ -214,24 +321,33 @@ impl PeerStore for SqlitePeerStore {
             .expect("update peer failed");
 
         if endpoint.is_outbound() {
-            let peer = db::PeerInfo::get_by_peer_id(&self.conn, peer_id)
-                .expect("get_by_peer_id failed")
-                .expect("must have");
-            db::PeerAddr::update_connected_at(&self.conn, peer.id, addr, now)
-                .expect("update connected at");
+            self.addr_manager
+                .mark_good(peer_id, &without_peer_id(addr), now);
         }
     }
 
     fn add_discovered_addr(&mut self, peer_id: &PeerId, addr: Multiaddr) -> bool {
+        let source = addr.clone();
+        self.add_discovered_addr_from(peer_id, addr, &source)
+    }
+
+    fn add_discovered_addr_from(
+        &mut self,
+        peer_id: &PeerId,
+        addr: Multiaddr,
+        source: &Multiaddr,
+    ) -> bool {
         // peer store is full
         if self.check_store_limit().is_err() {
             return false;
         }
-        let peer_info = self.fetch_peer_info(peer_id);
-        let inserted =
-            db::PeerAddr::insert(&self.conn, peer_info.id, &addr, Duration::from_secs(0))
-                .expect("insert addr");
-        inserted > 0
+        self.fetch_peer_info(peer_id);
+        self.addr_manager.add(peer_id, addr, source, unix_time())
+    }
+
+    fn attempt_addr(&mut self, peer_id: &PeerId, addr: &Multiaddr) {
+        self.addr_manager
+            .mark_attempt(peer_id, &without_peer_id(addr.to_owned()), unix_time());
     }
 
     fn report(&mut self, peer_id: &PeerId, behaviour: Behaviour) -> ReportResult {
@@ -281,40 +397,49 @@ impl PeerStore for SqlitePeerStore {
         peers
     }
     fn peer_addrs<'a>(&'a self, peer_id: &'a PeerId, count: u32) -> Option<Vec<Multiaddr>> {
-        self.get_peer_info(peer_id).map(|peer| {
-            db::PeerAddr::get_addrs(&self.conn, peer.id, count).expect("get peer addrs")
+        self.get_peer_info(peer_id).map(|_peer| {
+            self.addr_manager
+                .peer_addrs(peer_id)
+                .into_iter()
+                .take(count as usize)
+                .map(|info| info.addr.to_owned())
+                .collect()
         })
     }
 
     fn peers_to_attempt(&self, count: u32) -> Vec<(PeerId, Multiaddr)> {
-        let peers = db::get_peers_to_attempt(&self.conn, count).expect("get peers to attempt");
-        self.find_addrs_for_peers(&self.conn, peers)
-            .expect("find_addrs_for_peers failed")
+        self.select_addrs(count, false, |info| {
+            self.peer_status(&info.peer_id) != Status::Connected
+        })
     }
 
     fn peers_to_feeler(&self, count: u32) -> Vec<(PeerId, Multiaddr)> {
-        let peers = db::get_peers_to_feeler(
-            &self.conn,
-            count,
-            unix_time() - Duration::from_secs(LAST_CONNECTED_TIMEOUT_SECS),
-        )
-        .expect("get peers to feeler");
-        self.find_addrs_for_peers(&self.conn, peers)
-            .expect("find_addrs_for_peers failed")
+        let not_seen_timeout = unix_time() - Duration::from_secs(LAST_CONNECTED_TIMEOUT_SECS);
+        self.select_addrs(count, true, |info| {
+            self.get_peer_info(&info.peer_id)
+                .map(|peer| {
+                    peer.status != Status::Connected && peer.last_connected_at < not_seen_timeout
+                })
+                .unwrap_or(true)
+        })
     }
 
     fn random_peers(&self, count: u32) -> Vec<(PeerId, Multiaddr)> {
-        db::get_random_peers(
-            &self.conn,
-            count,
-            unix_time() - Duration::from_secs(LAST_CONNECTED_TIMEOUT_SECS),
-        )
-        .expect("get random peers")
+        // peers that we have connected to recently
+        let not_seen_timeout = unix_time() - Duration::from_secs(LAST_CONNECTED_TIMEOUT_SECS);
+        self.select_addrs(count, false, |info| {
+            self.get_peer_info(&info.peer_id)
+                .map(|peer| peer.last_connected_at > not_seen_timeout)
+                .unwrap_or(false)
+        })
     }
 
     fn ban_peer(&mut self, peer_id: &PeerId, timeout: Duration) {
-        if let Some(peer) = self.get_peer_info(peer_id) {
-            self.ban_ip(&peer.connected_addr, timeout);
+        if let Some(ip) = self
+            .get_peer_info(peer_id)
+            .and_then(|peer| peer.connected_addr.extract_ip_addr())
+        {
+            self.ban_network(IpNetwork::from_ip(ip), timeout, PEER_BAN_REASON.to_owned());
         }
     }
 
@@ -324,7 +449,90 @@ impl PeerStore for SqlitePeerStore {
         }
         false
     }
+
+    fn ban_network(&mut self, address: IpNetwork, timeout: Duration, reason: String) {
+        let now = unix_time();
+        let banned_addr = BannedAddr {
+            address,
+            ban_until: now + timeout,
+            ban_reason: reason,
+            created_at: now,
+        };
+        db::insert_banned_addr(&self.conn, &banned_addr).expect("ban address");
+        self.ban_list.insert(banned_addr);
+        if self.ban_list.len() > BAN_LIST_CLEAR_EXPIRES_SIZE {
+            self.clear_expires_banned_addrs().expect("clear ban list");
+        }
+    }
+
+    fn unban_network(&mut self, address: &IpNetwork) -> bool {
+        db::delete_banned_addr(&self.conn, address).expect("unban address");
+        self.ban_list.remove(address).is_some()
+    }
+
+    fn is_addr_banned(&self, addr: &Multiaddr) -> bool {
+        self.ban_list.is_addr_banned(addr, unix_time())
+    }
+
+    fn banned_addrs(&self) -> Vec<BannedAddr> {
+        self.ban_list.banned_addrs(unix_time())
+    }
+
+    fn clear_banned_addrs(&mut self) {
+        db::clear_banned_addrs(&self.conn).expect("clear ban list");
+        self.ban_list = BanList::default();
+    }
+
     fn peer_score_config(&self) -> PeerScoreConfig {
         self.peer_score_config
     }
+
+    fn update_anchor_peers(&mut self, peers: Vec<AnchorPeer>) {
+        db::replace_anchor_peers(&mut self.conn, &peers).expect("update anchor peers");
+    }
+
+    fn anchor_peers(&self) -> Vec<AnchorPeer> {
+        db::get_anchor_peers(&self.conn)
+            .expect("get anchor peers")
+            .into_iter()
+            .filter(|peer| !self.is_addr_banned(&peer.address))
+            .collect()
+    }
+
+    fn persist_addrs(&mut self) {
+        match self.addrs_writer {
+            Some(ref writer) => writer.send(self.addr_manager.infos().cloned().collect()),
+            None => db::replace_addr_infos(&mut self.conn, self.addr_manager.infos())
+                .expect("persist addr manager"),
+        }
+    }
+
+    fn persist_addrs_in_background(&mut self) {
+        match self.addrs_writer {
+            Some(ref writer) => writer.try_send(self.addr_manager.infos().cloned().collect()),
+            None => self.persist_addrs(),
+        }
+    }
+}
+
+// Both connections of a file store write to it, they wait for each other
+// instead of failing with a busy error
+fn open_file(path: &str) -> Result<Connection, DBError> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // Readers do not wait for the writer in the write-ahead log mode
+    conn.query_row("PRAGMA journal_mode=WAL", NO_PARAMS, |row| {
+        row.get::<_, String>(0)
+    })?;
+    Ok(conn)
+}
+
+// Addresses are kept without the peer id component
+fn without_peer_id(addr: Multiaddr) -> Multiaddr {
+    addr.into_iter()
+        .filter(|proto| match proto {
+            Protocol::P2p(_) => false,
+            _ => true,
+        })
+        .collect()
 }
@@ -60,7 +60,9 @@ impl ServiceProtocol for DiscoveryProtocol {
                     })
             })
             .expect("Discovery init only once");
-        context.future_task(discovery_task);
+        if let Err(err) = context.future_task(discovery_task) {
+            warn!(target: "network", "start discovery future_task error: {:?}", err);
+        }
     }
 
     fn connected(&mut self, context: ProtocolContextMutRef, _: &str) {
@@ -181,8 +183,14 @@ impl Stream for DiscoveryService {
                 self.sessions.remove(&session_id);
             }
             Some(DiscoveryEvent::AddNewAddrs { session_id, addrs }) => {
-                if let Some(_peer_id) = self.sessions.get(&session_id) {
-                    // TODO: wait for peer store update
+                // Addresses are bucketed by the network group of the session telling them
+                let network_state = &self.network_state;
+                let source = self.sessions.get(&session_id).and_then(|_peer_id| {
+                    network_state.with_peer_registry(|reg| {
+                        reg.get_peer(session_id).map(|peer| peer.address.clone())
+                    })
+                });
+                if let Some(source) = source {
                     for addr in addrs.into_iter() {
                         trace!(target: "network", "Add discovered address:{:?}", addr);
                         if let Some(peer_id) = extract_peer_id(&addr) {
@@ -195,7 +203,7 @@ impl Stream for DiscoveryService {
                                 .collect::<Multiaddr>();
 
                             self.network_state.with_peer_store_mut(|peer_store| {
-                                if !peer_store.add_discovered_addr(&peer_id, addr) {
+                                if !peer_store.add_discovered_addr_from(&peer_id, addr, &source) {
                                     trace!(target: "network", "add_discovered_addr failed {:?}", peer_id);
                                 }
                             });
@@ -1,5 +1,5 @@
 use crate::NetworkState;
-use log::info;
+use log::{debug, info};
 use p2p::{
     context::{ProtocolContext, ProtocolContextMutRef},
     secio::PublicKey,
@@ -36,7 +36,9 @@ impl ServiceProtocol for Feeler {
             peer_store.add_connected_peer(&peer_id, session.address.clone(), session.ty);
         });
         info!(target: "feeler", "peer={} FeelerProtocol.connected", session.address);
-        context.disconnect(session.id);
+        if let Err(err) = context.disconnect(session.id) {
+            debug!(target: "feeler", "disconnect {} error: {:?}", session.id, err);
+        }
     }
 
     fn disconnected(&mut self, context: ProtocolContextMutRef) {
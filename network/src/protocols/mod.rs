@@ -3,7 +3,7 @@ pub(crate) mod feeler;
 pub(crate) mod identify;
 pub(crate) mod ping;
 
-use log::{error, trace};
+use log::{debug, error, trace};
 use p2p::{
     builder::MetaBuilder,
     bytes::Bytes,
@@ -13,12 +13,14 @@ use p2p::{
     ProtocolId, SessionId,
 };
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::codec::length_delimited;
 
 pub type PeerIndex = SessionId;
 
-use crate::{Behaviour, NetworkState, Peer, PeerRegistry, ProtocolVersion, MAX_FRAME_LENGTH};
+use crate::{
+    Behaviour, MessageType, NetworkState, Peer, PeerRegistry, ProtocolVersion, MAX_FRAME_LENGTH,
+};
 
 pub trait CKBProtocolContext: Send {
     // Interact with underlying p2p service
@@ -33,6 +35,8 @@ pub trait CKBProtocolContext: Send {
     fn connected_peers(&self) -> Vec<PeerIndex>;
     fn report_peer(&self, peer_index: PeerIndex, behaviour: Behaviour);
     fn ban_peer(&self, peer_index: PeerIndex, timeout: Duration);
+    /// The peer delivered a block which became the tip
+    fn record_best_block(&self, peer_index: PeerIndex);
     // Other methods
     fn protocol_id(&self) -> ProtocolId;
 }
@@ -71,6 +75,8 @@ pub struct CKBProtocol {
     supported_versions: Vec<ProtocolVersion>,
     handler: Box<Fn() -> Box<dyn CKBProtocolHandler + Send + 'static> + Send + 'static>,
     network_state: Arc<NetworkState>,
+    // compress the messages from this version
+    compression: Option<(ProtocolVersion, MessageType)>,
 }
 
 impl CKBProtocol {
@@ -91,9 +97,17 @@ impl CKBProtocol {
                 versions.sort_by(|a, b| b.cmp(a));
                 versions.to_vec()
             },
+            compression: None,
         }
     }
 
+    /// Compresses the large messages of the sessions negotiating `version` or
+    /// a later one, `message_type` names the messages in the statistics
+    pub fn compress_since(mut self, version: ProtocolVersion, message_type: MessageType) -> Self {
+        self.compression = Some((version, message_type));
+        self
+    }
+
     pub fn id(&self) -> ProtocolId {
         self.id
     }
@@ -107,6 +121,11 @@ impl CKBProtocol {
     }
 
     pub fn build(self) -> ProtocolMeta {
+        if let Some((version, message_type)) = self.compression.clone() {
+            self.network_state
+                .compression
+                .register(self.id, version, message_type);
+        }
         let protocol_name = self.protocol_name();
         let supported_versions = self
             .supported_versions
@@ -160,6 +179,9 @@ impl ServiceProtocol for CKBHandler {
             p2p_control: context.control().to_owned(),
         };
         let peer_index = context.session.id;
+        self.network_state
+            .compression
+            .connected(peer_index, self.proto_id, version);
         self.handler.connected(Box::new(nc), peer_index, version);
     }
 
@@ -181,6 +203,21 @@ impl ServiceProtocol for CKBHandler {
             p2p_control: context.control().to_owned(),
         };
         let peer_index = context.session.id;
+        self.network_state
+            .bandwidth
+            .record_received(peer_index, self.proto_id, data.len());
+        let data = match self
+            .network_state
+            .compression
+            .decode(peer_index, self.proto_id, data)
+        {
+            Ok(data) => data,
+            Err(err) => {
+                debug!(target: "network", "decode message from {} error: {}", peer_index, err);
+                nc.disconnect(peer_index);
+                return;
+            }
+        };
         self.handler.received(Box::new(nc), peer_index, data);
     }
 
@@ -224,23 +261,26 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
     }
     fn send_message(&self, proto_id: ProtocolId, peer_index: PeerIndex, data: Bytes) {
         trace!(target: "network", "[send message]: {}, to={}, length={}", proto_id, peer_index, data.len());
-        if let Err(err) = self.p2p_control.send_message_to(peer_index, proto_id, data) {
+        if let Err(err) =
+            self.network_state
+                .send_message(&self.p2p_control, peer_index, proto_id, data)
+        {
             error!(target: "network", "send message to p2p service error: {:?}", err);
         }
     }
     fn send_message_to(&self, peer_index: PeerIndex, data: Bytes) {
         trace!(target: "network", "[send message to]: {}, to={}, length={}", self.proto_id, peer_index, data.len());
-        if let Err(err) = self
-            .p2p_control
-            .send_message_to(peer_index, self.proto_id, data)
+        if let Err(err) =
+            self.network_state
+                .send_message(&self.p2p_control, peer_index, self.proto_id, data)
         {
             error!(target: "network", "send message to p2p service error: {:?}", err);
         }
     }
     fn filter_broadcast(&self, target: TargetSession, data: Bytes) {
-        if let Err(err) = self
-            .p2p_control
-            .filter_broadcast(target, self.proto_id, data)
+        if let Err(err) =
+            self.network_state
+                .filter_broadcast(&self.p2p_control, target, self.proto_id, data)
         {
             error!(target: "network", "send message to p2p service error: {:?}", err);
         }
@@ -267,6 +307,13 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
         self.network_state
             .ban_session(&self.p2p_control, peer_index, timeout);
     }
+    fn record_best_block(&self, peer_index: PeerIndex) {
+        self.network_state.with_peer_registry_mut(|reg| {
+            if let Some(peer) = reg.get_peer_mut(peer_index) {
+                peer.last_block_time = Some(Instant::now());
+            }
+        });
+    }
 
     fn protocol_id(&self) -> ProtocolId {
         self.proto_id
@@ -1,11 +1,16 @@
 use std::error::Error;
 use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
 use faster_hex::hex_decode;
+use futures::sync::oneshot;
 use futures::{Async, Future, Poll, Stream};
 use log::{debug, error, info, trace, warn};
-use p2p::{multiaddr::Protocol, secio::PeerId};
+use p2p::{
+    multiaddr::{Multiaddr, Protocol},
+    secio::PeerId,
+};
 use resolve::record::Txt;
 use resolve::{DnsConfig, DnsResolver};
 use secp256k1::key::PublicKey;
@@ -19,12 +24,27 @@ use seed_record::SeedRecord;
 // FIXME: should replace this later
 const TXT_VERIFY_PUBKEY: &str = "33afa0d4309e4720ba60b29e63c4f378fef860bcfe14732fd2790107c4237ca92244ec8c76e013ba7d88499288ef94ff412b5c8bf239fbb70488d5f6fbbc75a2";
 
+// Seeds are queried again at this interval while the address book is starved
+const SEEDING_INTERVAL: Duration = Duration::from_secs(60);
+// The address book is starved with fewer addresses to attempt than this
+const STARVED_ADDRESSES: u32 = 16;
+const MIN_OUTBOUND_PEERS: usize = 2;
+
+/// Queries the DNS seeds for peer addresses when the address book is starved.
+///
+/// Each round walks the seeds from where the last round stopped, until one
+/// of them returns some addresses, so the seeds are used in rotation and a
+/// broken seed does not stall the bootstrap. The blocking DNS queries of a
+/// round run on their own thread, off the network reactor.
 pub(crate) struct DnsSeedingService {
     network_state: Arc<NetworkState>,
     wait_until: Instant,
     // Because tokio timer is not reliable
     check_interval: Interval,
     seeds: Vec<String>,
+    next_seed: usize,
+    // Addresses resolved by the running round, and the seed to start the next one from
+    pending: Option<oneshot::Receiver<(Vec<Multiaddr>, usize)>>,
 }
 
 impl DnsSeedingService {
@@ -42,19 +62,32 @@ impl DnsSeedingService {
             wait_until,
             check_interval,
             seeds,
+            next_seed: 0,
+            pending: None,
         }
     }
 
-    fn seeding(&self) -> Result<(), Box<dyn Error>> {
+    fn is_starved(&self) -> bool {
         let enough_outbound = self.network_state.with_peer_registry(|reg| {
             reg.peers()
                 .values()
                 .filter(|peer| peer.is_outbound())
                 .count()
-                >= 2
+                >= MIN_OUTBOUND_PEERS
         });
         if enough_outbound {
             debug!(target: "network", "Enough outbound peers");
+            return false;
+        }
+        let addresses = self.network_state.with_peer_store(|peer_store| {
+            peer_store.peers_to_attempt(STARVED_ADDRESSES).len() as u32
+        });
+        trace!(target: "network", "{} addresses to attempt in peer store", addresses);
+        addresses < STARVED_ADDRESSES
+    }
+
+    fn seeding(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.is_starved() {
             return Ok(());
         }
 
@@ -64,42 +97,32 @@ impl DnsSeedingService {
         let pubkey = PublicKey::from_slice(&pubkey_bytes)
             .map_err(|err| format!("create PublicKey failed: {:?}", err))?;
 
-        let resolver = DnsConfig::load_default()
-            .map_err(|err| format!("Failed to load system configuration: {}", err))
-            .and_then(|config| {
-                DnsResolver::new(config)
-                    .map_err(|err| format!("Failed to create DNS resolver: {}", err))
+        let (sender, receiver) = oneshot::channel();
+        let seeds = self.seeds.clone();
+        let next_seed = self.next_seed;
+        thread::Builder::new()
+            .name("DnsSeeding".to_string())
+            .spawn(move || {
+                let _ = sender.send(resolve_seeds(&seeds, next_seed, &pubkey));
             })?;
+        self.pending = Some(receiver);
+        Ok(())
+    }
 
-        let mut addrs = Vec::new();
-        for seed in &self.seeds {
-            debug!(target: "network", "query txt records from: {}", seed);
-            match resolver.resolve_record::<Txt>(seed) {
-                Ok(records) => {
-                    for record in records {
-                        match std::str::from_utf8(&record.data) {
-                            Ok(record) => match SeedRecord::decode_with_pubkey(&record, &pubkey) {
-                                Ok(seed_record) => {
-                                    let address = seed_record.address();
-                                    trace!(target: "network", "got dns txt address: {}", address);
-                                    addrs.push(address);
-                                }
-                                Err(err) => {
-                                    debug!(target: "network", "decode dns txt record failed: {:?}, {:?}", err, record);
-                                }
-                            },
-                            Err(err) => {
-                                debug!(target: "network", "get dns txt record error: {:?}", err);
-                            }
-                        }
-                    }
-                }
-                Err(_) => {
-                    warn!(target: "network", "Invalid domain name: {}", seed);
-                }
+    // Adds the addresses of the finished round to the peer store
+    fn poll_pending(&mut self) {
+        let result = match self.pending.as_mut().map(Future::poll) {
+            Some(Ok(Async::Ready(result))) => result,
+            Some(Ok(Async::NotReady)) | None => return,
+            Some(Err(_)) => {
+                warn!(target: "network", "DNS seeding round is canceled");
+                self.pending = None;
+                return;
             }
-        }
-
+        };
+        self.pending = None;
+        let (addrs, next_seed) = result;
+        self.next_seed = next_seed;
         debug!(target: "network", "DNS seeding got {} address", addrs.len());
         self.network_state.with_peer_store_mut(|peer_store| {
             for mut addr in addrs {
@@ -115,8 +138,71 @@ impl DnsSeedingService {
                 }
             }
         });
-        Ok(())
+        debug!(target: "network", "DNS seeding round finished");
+    }
+}
+
+// Walks the seeds from `next_seed` until one returns some addresses, returns
+// them along with the seed to start the next round from
+fn resolve_seeds(
+    seeds: &[String],
+    mut next_seed: usize,
+    pubkey: &PublicKey,
+) -> (Vec<Multiaddr>, usize) {
+    let resolver = DnsConfig::load_default()
+        .map_err(|err| format!("Failed to load system configuration: {}", err))
+        .and_then(|config| {
+            DnsResolver::new(config)
+                .map_err(|err| format!("Failed to create DNS resolver: {}", err))
+        });
+    let resolver = match resolver {
+        Ok(resolver) => resolver,
+        Err(err) => {
+            error!(target: "network", "seeding error: {}", err);
+            return (Vec::new(), next_seed);
+        }
+    };
+
+    for _ in 0..seeds.len() {
+        let seed = &seeds[next_seed % seeds.len()];
+        next_seed = (next_seed + 1) % seeds.len();
+        let addrs = resolve_seed(&resolver, seed, pubkey);
+        if !addrs.is_empty() {
+            return (addrs, next_seed);
+        }
     }
+    (Vec::new(), next_seed)
+}
+
+// Addresses in the signed TXT records of `seed`
+fn resolve_seed(resolver: &DnsResolver, seed: &str, pubkey: &PublicKey) -> Vec<Multiaddr> {
+    let mut addrs = Vec::new();
+    debug!(target: "network", "query txt records from: {}", seed);
+    match resolver.resolve_record::<Txt>(seed) {
+        Ok(records) => {
+            for record in records {
+                match std::str::from_utf8(&record.data) {
+                    Ok(record) => match SeedRecord::decode_with_pubkey(&record, pubkey) {
+                        Ok(seed_record) => {
+                            let address = seed_record.address();
+                            trace!(target: "network", "got dns txt address: {}", address);
+                            addrs.push(address);
+                        }
+                        Err(err) => {
+                            debug!(target: "network", "decode dns txt record failed: {:?}, {:?}", err, record);
+                        }
+                    },
+                    Err(err) => {
+                        debug!(target: "network", "get dns txt record error: {:?}", err);
+                    }
+                }
+            }
+        }
+        Err(_) => {
+            warn!(target: "network", "Invalid domain name: {}", seed);
+        }
+    }
+    addrs
 }
 
 impl Future for DnsSeedingService {
@@ -127,12 +213,15 @@ impl Future for DnsSeedingService {
         loop {
             match self.check_interval.poll() {
                 Ok(Async::Ready(Some(_))) => {
-                    if self.wait_until < Instant::now() {
+                    if self.seeds.is_empty() {
+                        debug!(target: "network", "No DNS seed configured");
+                        return Ok(Async::Ready(()));
+                    }
+                    if self.pending.is_none() && self.wait_until < Instant::now() {
                         if let Err(err) = self.seeding() {
                             error!(target: "network", "seeding error: {:?}", err);
                         }
-                        debug!(target: "network", "DNS seeding finished");
-                        return Ok(Async::Ready(()));
+                        self.wait_until = Instant::now() + SEEDING_INTERVAL;
                     } else {
                         trace!(target: "network", "DNS check interval");
                     }
@@ -148,6 +237,7 @@ impl Future for DnsSeedingService {
                 }
             }
         }
+        self.poll_pending();
         Ok(Async::NotReady)
     }
 }
@@ -1,2 +1,4 @@
 pub(crate) mod dns_seeding;
 pub(crate) mod outbound_peer;
+pub(crate) mod port_mapping;
+pub(crate) mod upload_throttle;
@@ -1,6 +1,7 @@
-use crate::NetworkState;
+use crate::network_group::NetworkGroup;
+use crate::{NetworkState, PeerRegistry};
 use futures::{try_ready, Async, Stream};
-use log::{debug, trace, warn};
+use log::{debug, info, trace, warn};
 use p2p::service::ServiceControl;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -8,11 +9,16 @@ use std::usize;
 use tokio::timer::Interval;
 
 const FEELER_CONNECTION_COUNT: u32 = 5;
+const PERSIST_ADDRS_INTERVAL: Duration = Duration::from_secs(15 * 60);
 
 pub struct OutboundPeerService {
     pub stream_interval: Interval,
     pub network_state: Arc<NetworkState>,
     pub p2p_control: ServiceControl,
+    last_persisted: Instant,
+    // None if the rotation is disabled
+    rotation_interval: Option<Duration>,
+    last_rotated: Instant,
 }
 
 impl OutboundPeerService {
@@ -21,14 +27,25 @@ impl OutboundPeerService {
         p2p_control: ServiceControl,
         try_connect_interval: Duration,
     ) -> Self {
+        let rotation_interval = match network_state.config.outbound_rotation_interval_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        };
         OutboundPeerService {
             network_state,
             p2p_control,
             stream_interval: Interval::new_interval(try_connect_interval),
+            last_persisted: Instant::now(),
+            rotation_interval,
+            last_rotated: Instant::now(),
         }
     }
 
     fn attempt_dial_peers(&mut self, count: u32) {
+        let max_peers_per_group = self.network_state.config.max_outbound_peers_per_group;
+        let mut groups = self
+            .network_state
+            .with_peer_registry(PeerRegistry::outbound_groups);
         let attempt_peers = self
             .network_state
             .with_peer_store(|peer_store| peer_store.peers_to_attempt(count + 5));
@@ -52,13 +69,43 @@ impl OutboundPeerService {
                         })
                         .unwrap_or(true)
             })
+            .filter(|(_peer_id, addr)| {
+                // Keep the outbound peers in diverse network groups
+                let group = addr.network_group();
+                if !group.is_diversity_limited() {
+                    return true;
+                }
+                let group_count = groups.entry(group).or_insert(0);
+                if *group_count >= max_peers_per_group {
+                    return false;
+                }
+                *group_count += 1;
+                true
+            })
             .take(count as usize)
         {
             debug!(target: "network", "dial attempt peer: {:?}", addr);
+            self.network_state
+                .with_peer_store_mut(|peer_store| peer_store.attempt_addr(&peer_id, &addr));
             self.network_state.dial_all(&p2p_control, &peer_id, addr);
         }
     }
 
+    // Whitelisted peers are reconnected before any other outbound peer
+    fn dial_whitelist_peers(&mut self) {
+        let p2p_control = self.p2p_control.clone();
+        for (peer_id, addr) in &self.network_state.whitelist_peers {
+            let connected = self
+                .network_state
+                .with_peer_registry(|reg| reg.get_key_by_peer_id(peer_id).is_some());
+            if !connected {
+                debug!(target: "network", "dial whitelist peer: {:?}", addr);
+                self.network_state
+                    .dial_all(&p2p_control, peer_id, addr.to_owned());
+            }
+        }
+    }
+
     fn feeler_peers(&mut self, count: u32) {
         let peers = self
             .network_state
@@ -72,9 +119,43 @@ impl OutboundPeerService {
                 reg.add_feeler(peer_id.clone());
             });
             debug!(target: "network", "dial feeler peer: {:?}", addr);
+            self.network_state
+                .with_peer_store_mut(|peer_store| peer_store.attempt_addr(&peer_id, &addr));
             self.network_state.dial_feeler(&p2p_control, &peer_id, addr);
         }
     }
+
+    // Replaces some outbound peers once in a while, so an attacker can not
+    // keep the slots it has taken
+    fn rotate_peers(&mut self) {
+        let interval = match self.rotation_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        if self.last_rotated.elapsed() < interval {
+            return;
+        }
+        self.last_rotated = Instant::now();
+        let count = self.network_state.config.outbound_rotation_count;
+        let sessions = self
+            .network_state
+            .with_peer_registry(|reg| reg.peers_to_rotate(count, interval));
+        for session_id in sessions {
+            info!(target: "network", "rotate outbound peer, disconnect {}", session_id);
+            if let Err(err) = self.p2p_control.disconnect(session_id) {
+                debug!(target: "network", "disconnect {} error: {:?}", session_id, err);
+            }
+        }
+    }
+
+    fn persist_addrs(&mut self) {
+        if self.last_persisted.elapsed() > PERSIST_ADDRS_INTERVAL {
+            trace!(target: "network", "persist address manager");
+            self.network_state
+                .with_peer_store_mut(|peer_store| peer_store.persist_addrs_in_background());
+            self.last_persisted = Instant::now();
+        }
+    }
 }
 
 impl Stream for OutboundPeerService {
@@ -84,6 +165,7 @@ impl Stream for OutboundPeerService {
     fn poll(&mut self) -> Result<Async<Option<Self::Item>>, Self::Error> {
         match try_ready!(self.stream_interval.poll().map_err(|_| ())) {
             Some(_tick) => {
+                self.dial_whitelist_peers();
                 let status = self.network_state.connection_status();
                 let new_outbound = status.max_outbound - status.unreserved_outbound;
                 if new_outbound > 0 {
@@ -92,7 +174,9 @@ impl Stream for OutboundPeerService {
                 } else {
                     // feeler peers
                     self.feeler_peers(FEELER_CONNECTION_COUNT);
+                    self.rotate_peers();
                 }
+                self.persist_addrs();
             }
             None => {
                 warn!(target: "network", "ckb outbound peer service stopped");
@@ -0,0 +1,316 @@
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{self, Sender};
+use futures::sync::oneshot;
+use futures::{Async, Future, Poll, Stream};
+use igd::{Gateway, PortMappingProtocol, SearchOptions};
+use log::{debug, info, trace, warn};
+use p2p::{
+    multiaddr::{Multiaddr, Protocol},
+    utils::multiaddr_to_socketaddr,
+};
+use tokio::timer::Interval;
+
+mod nat_pmp;
+
+use crate::NetworkState;
+
+// Lifetime asked for the mapping, it is renewed at half of the granted one
+const LEASE_DURATION: u32 = 60 * 60;
+const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+const DESCRIPTION: &str = "ckb";
+
+#[derive(Clone)]
+enum MappingGateway {
+    Upnp(Gateway),
+    NatPmp(Ipv4Addr),
+}
+
+#[derive(Clone)]
+struct Mapping {
+    gateway: MappingGateway,
+    external_port: u16,
+    external_address: Multiaddr,
+}
+
+// The mapping and its granted lifetime
+type MapResult = Result<(Mapping, u32), String>;
+
+// Requests to the thread talking to the gateway
+enum Command {
+    // Maps the local port, renewing `previous` if any
+    Map {
+        local_port: u16,
+        previous: Option<Mapping>,
+        responder: oneshot::Sender<MapResult>,
+    },
+    Remove {
+        local_port: Option<u16>,
+        mapping: Mapping,
+    },
+}
+
+/// Maps the listen port on the local gateway with UPnP, or NAT-PMP when the
+/// gateway address is configured, renews the mapping before it expires and
+/// advertises the external address to peers.
+///
+/// The gateway requests block, they are sent by a dedicated thread.
+pub(crate) struct PortMappingService {
+    network_state: Arc<NetworkState>,
+    wait_until: Instant,
+    // Because tokio timer is not reliable
+    check_interval: Interval,
+    mapping: Option<Mapping>,
+    sender: Sender<Command>,
+    pending: Option<oneshot::Receiver<MapResult>>,
+}
+
+impl PortMappingService {
+    pub(crate) fn new(
+        network_state: Arc<NetworkState>,
+        nat_pmp_gateway: Option<Ipv4Addr>,
+    ) -> PortMappingService {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        // The thread exits once the service is dropped and its last command is done
+        thread::Builder::new()
+            .name("PortMapping".to_string())
+            .spawn(move || {
+                for command in receiver {
+                    match command {
+                        Command::Map {
+                            local_port,
+                            previous,
+                            responder,
+                        } => {
+                            let result = map_port(nat_pmp_gateway, previous.as_ref(), local_port);
+                            let _ = responder.send(result);
+                        }
+                        Command::Remove {
+                            local_port,
+                            mapping,
+                        } => remove_port(mapping, local_port),
+                    }
+                }
+            })
+            .expect("Start PortMapping thread failed");
+        PortMappingService {
+            network_state,
+            wait_until: Instant::now(),
+            check_interval: Interval::new_interval(Duration::from_secs(1)),
+            mapping: None,
+            sender,
+            pending: None,
+        }
+    }
+
+    // The first IPv4 TCP listen address
+    fn listen_port(&self) -> Option<u16> {
+        self.network_state
+            .original_listened_addresses
+            .read()
+            .iter()
+            .filter_map(multiaddr_to_socketaddr)
+            .find(SocketAddr::is_ipv4)
+            .map(|addr| addr.port())
+    }
+
+    fn renew(&mut self, local_port: u16) {
+        let (responder, receiver) = oneshot::channel();
+        let command = Command::Map {
+            local_port,
+            previous: self.mapping.clone(),
+            responder,
+        };
+        if self.sender.send(command).is_ok() {
+            self.pending = Some(receiver);
+        } else {
+            warn!(target: "network", "PortMapping thread is gone");
+            self.wait_until = Instant::now() + RETRY_INTERVAL;
+        }
+    }
+
+    // Applies the result of the running renewal
+    fn poll_pending(&mut self) {
+        let result = match self.pending.as_mut().map(Future::poll) {
+            Some(Ok(Async::Ready(result))) => result,
+            Some(Ok(Async::NotReady)) | None => return,
+            Some(Err(_)) => Err("PortMapping thread is gone".to_string()),
+        };
+        self.pending = None;
+        match result {
+            Ok((mapping, lifetime)) => {
+                let previous = self.mapping.take();
+                if previous.as_ref().map(|m| &m.external_address) != Some(&mapping.external_address)
+                {
+                    if let Some(previous) = previous {
+                        self.network_state
+                            .remove_listened_address(&previous.external_address);
+                    }
+                    info!(target: "network", "Port mapping external address: {}", mapping.external_address);
+                    self.network_state
+                        .add_listened_address(mapping.external_address.clone());
+                } else {
+                    trace!(target: "network", "renew port mapping {}", mapping.external_address);
+                }
+                self.mapping = Some(mapping);
+                self.wait_until = Instant::now() + Duration::from_secs(u64::from(lifetime / 2));
+            }
+            Err(err) => {
+                warn!(target: "network", "port mapping failed: {}", err);
+                if let Some(previous) = self.mapping.take() {
+                    self.network_state
+                        .remove_listened_address(&previous.external_address);
+                }
+                self.wait_until = Instant::now() + RETRY_INTERVAL;
+            }
+        }
+    }
+}
+
+impl Drop for PortMappingService {
+    fn drop(&mut self) {
+        if let Some(mapping) = self.mapping.take() {
+            let command = Command::Remove {
+                local_port: self.listen_port(),
+                mapping,
+            };
+            let _ = self.sender.send(command);
+        }
+    }
+}
+
+// Returns the mapping and the granted lifetime
+fn map_port(
+    nat_pmp_gateway: Option<Ipv4Addr>,
+    previous: Option<&Mapping>,
+    local_port: u16,
+) -> MapResult {
+    let gateway = match (previous, nat_pmp_gateway) {
+        (Some(mapping), _) => mapping.gateway.clone(),
+        (None, Some(gateway)) => MappingGateway::NatPmp(gateway),
+        (None, None) => {
+            let options = SearchOptions {
+                timeout: Some(SEARCH_TIMEOUT),
+                ..Default::default()
+            };
+            MappingGateway::Upnp(
+                igd::search_gateway(options)
+                    .map_err(|err| format!("search UPnP gateway error: {}", err))?,
+            )
+        }
+    };
+    // Keep the external port of the mapping to renew
+    let wanted_port = previous
+        .map(|mapping| mapping.external_port)
+        .unwrap_or(local_port);
+
+    let (ip, external_port, lifetime) = match &gateway {
+        MappingGateway::Upnp(igd_gateway) => {
+            let local_ip = local_ip_to(igd_gateway.addr)
+                .map_err(|err| format!("get local address error: {}", err))?;
+            igd_gateway
+                .add_port(
+                    PortMappingProtocol::TCP,
+                    wanted_port,
+                    SocketAddrV4::new(local_ip, local_port),
+                    LEASE_DURATION,
+                    DESCRIPTION,
+                )
+                .map_err(|err| format!("add UPnP port mapping error: {}", err))?;
+            let ip = igd_gateway
+                .get_external_ip()
+                .map_err(|err| format!("get UPnP external address error: {}", err))?;
+            (ip, wanted_port, LEASE_DURATION)
+        }
+        MappingGateway::NatPmp(gateway_ip) => {
+            let (external_port, lifetime) =
+                nat_pmp::map_tcp_port(*gateway_ip, local_port, wanted_port, LEASE_DURATION)
+                    .map_err(|err| format!("add NAT-PMP port mapping error: {}", err))?;
+            let ip = nat_pmp::external_address(*gateway_ip)
+                .map_err(|err| format!("get NAT-PMP external address error: {}", err))?;
+            (ip, external_port, lifetime)
+        }
+    };
+
+    let external_address = [Protocol::Ip4(ip), Protocol::Tcp(external_port)]
+        .iter()
+        .cloned()
+        .collect();
+    Ok((
+        Mapping {
+            gateway,
+            external_port,
+            external_address,
+        },
+        lifetime,
+    ))
+}
+
+fn remove_port(mapping: Mapping, local_port: Option<u16>) {
+    debug!(target: "network", "remove port mapping {}", mapping.external_address);
+    let result = match mapping.gateway {
+        MappingGateway::Upnp(gateway) => gateway
+            .remove_port(PortMappingProtocol::TCP, mapping.external_port)
+            .map_err(|err| err.to_string()),
+        MappingGateway::NatPmp(gateway) => local_port
+            .ok_or_else(|| "no listen port".to_string())
+            .and_then(|port| {
+                nat_pmp::map_tcp_port(gateway, port, 0, 0).map_err(|err| err.to_string())
+            })
+            .map(|_| ()),
+    };
+    if let Err(err) = result {
+        debug!(target: "network", "remove port mapping error: {}", err);
+    }
+}
+
+// Local address of the interface routing to `gateway`
+fn local_ip_to(gateway: SocketAddrV4) -> std::io::Result<Ipv4Addr> {
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.connect(gateway)?;
+    match socket.local_addr()? {
+        SocketAddr::V4(addr) => Ok(*addr.ip()),
+        SocketAddr::V6(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "IPv6 local address",
+        )),
+    }
+}
+
+impl Future for PortMappingService {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            match self.check_interval.poll() {
+                Ok(Async::Ready(Some(_))) => {
+                    if self.pending.is_none() && self.wait_until < Instant::now() {
+                        match self.listen_port() {
+                            Some(port) => self.renew(port),
+                            None => {
+                                debug!(target: "network", "No IPv4 listen address to map");
+                                return Ok(Async::Ready(()));
+                            }
+                        }
+                    }
+                }
+                Ok(Async::Ready(None)) => {
+                    warn!(target: "network", "Poll PortMappingService interval return None");
+                    return Err(());
+                }
+                Ok(Async::NotReady) => break,
+                Err(err) => {
+                    warn!(target: "network", "Poll PortMappingService interval error: {:?}", err);
+                    return Err(());
+                }
+            }
+        }
+        self.poll_pending();
+        Ok(Async::NotReady)
+    }
+}
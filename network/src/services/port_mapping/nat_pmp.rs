@@ -0,0 +1,146 @@
+//! NAT Port Mapping Protocol client, as specified by RFC 6886.
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::Duration;
+
+pub const NAT_PMP_PORT: u16 = 5351;
+
+const VERSION: u8 = 0;
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+const OP_MAP_TCP: u8 = 2;
+const OP_RESPONSE: u8 = 128;
+// The RFC retries from 250ms, doubling the timeout each time
+const INITIAL_TIMEOUT_MS: u64 = 250;
+const MAX_ATTEMPTS: u32 = 4;
+
+pub fn encode_external_address_request() -> [u8; 2] {
+    [VERSION, OP_EXTERNAL_ADDRESS]
+}
+
+pub fn decode_external_address_response(data: &[u8]) -> io::Result<Ipv4Addr> {
+    check_response(data, OP_EXTERNAL_ADDRESS, 12)?;
+    Ok(Ipv4Addr::new(data[8], data[9], data[10], data[11]))
+}
+
+pub fn encode_map_tcp_request(internal_port: u16, external_port: u16, lifetime: u32) -> [u8; 12] {
+    let mut request = [0u8; 12];
+    request[0] = VERSION;
+    request[1] = OP_MAP_TCP;
+    request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    request[6..8].copy_from_slice(&external_port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+    request
+}
+
+/// Returns the mapped external port and the lifetime of the mapping
+pub fn decode_map_tcp_response(data: &[u8], internal_port: u16) -> io::Result<(u16, u32)> {
+    check_response(data, OP_MAP_TCP, 16)?;
+    if u16::from_be_bytes([data[8], data[9]]) != internal_port {
+        return Err(invalid_data("unmatched internal port"));
+    }
+    let external_port = u16::from_be_bytes([data[10], data[11]]);
+    let lifetime = u32::from_be_bytes([data[12], data[13], data[14], data[15]]);
+    Ok((external_port, lifetime))
+}
+
+fn check_response(data: &[u8], op: u8, len: usize) -> io::Result<()> {
+    if data.len() < len || data[0] != VERSION || data[1] != OP_RESPONSE + op {
+        return Err(invalid_data("malformed response"));
+    }
+    match u16::from_be_bytes([data[2], data[3]]) {
+        0 => Ok(()),
+        code => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("gateway returns result code {}", code),
+        )),
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn request(gateway: Ipv4Addr, payload: &[u8], response: &mut [u8]) -> io::Result<usize> {
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+    let gateway = SocketAddr::V4(SocketAddrV4::new(gateway, NAT_PMP_PORT));
+    socket.connect(gateway)?;
+    let mut timeout = INITIAL_TIMEOUT_MS;
+    for _ in 0..MAX_ATTEMPTS {
+        socket.set_read_timeout(Some(Duration::from_millis(timeout)))?;
+        socket.send(payload)?;
+        match socket.recv(response) {
+            Ok(len) => return Ok(len),
+            Err(ref err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::TimedOut =>
+            {
+                timeout *= 2;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "no response from gateway",
+    ))
+}
+
+pub fn external_address(gateway: Ipv4Addr) -> io::Result<Ipv4Addr> {
+    let mut response = [0u8; 16];
+    let len = request(gateway, &encode_external_address_request(), &mut response)?;
+    decode_external_address_response(&response[..len])
+}
+
+/// Maps `internal_port` for `lifetime` seconds, a lifetime of 0 deletes the
+/// mapping
+pub fn map_tcp_port(
+    gateway: Ipv4Addr,
+    internal_port: u16,
+    external_port: u16,
+    lifetime: u32,
+) -> io::Result<(u16, u32)> {
+    let mut response = [0u8; 16];
+    let len = request(
+        gateway,
+        &encode_map_tcp_request(internal_port, external_port, lifetime),
+        &mut response,
+    )?;
+    decode_map_tcp_response(&response[..len], internal_port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_tcp_codec() {
+        assert_eq!(
+            encode_map_tcp_request(8115, 8115, 3600),
+            [0, 2, 0, 0, 0x1f, 0xb3, 0x1f, 0xb3, 0, 0, 0x0e, 0x10]
+        );
+        let response = [
+            0, 130, 0, 0, 0, 0, 0, 1, 0x1f, 0xb3, 0x1f, 0xb4, 0, 0, 0x07, 0x08,
+        ];
+        assert_eq!(
+            decode_map_tcp_response(&response, 8115).unwrap(),
+            (8116, 1800)
+        );
+        assert!(decode_map_tcp_response(&response, 8116).is_err());
+        assert!(decode_map_tcp_response(&response[..12], 8115).is_err());
+
+        let mut failure = response;
+        failure[3] = 3;
+        assert!(decode_map_tcp_response(&failure, 8115).is_err());
+    }
+
+    #[test]
+    fn test_external_address_codec() {
+        assert_eq!(encode_external_address_request(), [0, 0]);
+        let response = [0, 128, 0, 0, 0, 0, 0, 1, 203, 0, 113, 7];
+        assert_eq!(
+            decode_external_address_response(&response).unwrap(),
+            Ipv4Addr::new(203, 0, 113, 7)
+        );
+        assert!(decode_external_address_response(&response[..8]).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::NetworkState;
+use futures::{try_ready, Async, Stream};
+use log::{error, warn};
+use p2p::service::ServiceControl;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sends the messages held back by the upload throttle as the rates allow
+pub(crate) struct UploadThrottleService {
+    network_state: Arc<NetworkState>,
+    p2p_control: ServiceControl,
+    flush_interval: Interval,
+}
+
+impl UploadThrottleService {
+    pub(crate) fn new(network_state: Arc<NetworkState>, p2p_control: ServiceControl) -> Self {
+        UploadThrottleService {
+            network_state,
+            p2p_control,
+            flush_interval: Interval::new_interval(FLUSH_INTERVAL),
+        }
+    }
+}
+
+impl Stream for UploadThrottleService {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Result<Async<Option<Self::Item>>, Self::Error> {
+        match try_ready!(self.flush_interval.poll().map_err(|_| ())) {
+            Some(_tick) => {
+                for (session_id, proto_id, data) in
+                    self.network_state.bandwidth.flush(Instant::now())
+                {
+                    if let Err(err) = self.p2p_control.send_message_to(session_id, proto_id, data) {
+                        error!(target: "network", "send message to p2p service error: {:?}", err);
+                    }
+                }
+            }
+            None => {
+                warn!(target: "network", "ckb upload throttle service stopped");
+                return Ok(Async::Ready(None));
+            }
+        }
+        Ok(Async::Ready(Some(())))
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    multiaddr::Multiaddr,
+    peer_store::addr_manager::{AddrManager, BUCKET_SIZE},
+    PeerId,
+};
+use std::time::Duration;
+
+fn new_addr_manager() -> AddrManager {
+    AddrManager::new(AddrManager::random_key())
+}
+
+fn addr(ip: &str) -> Multiaddr {
+    format!("/ip4/{}/tcp/8115", ip).parse().unwrap()
+}
+
+#[test]
+fn test_add_and_select() {
+    let mut addr_manager = new_addr_manager();
+    let now = Duration::from_secs(100);
+    assert!(addr_manager.select(1, false, now, |_| true).is_empty());
+
+    let peer_id = PeerId::random();
+    let source = addr("1.1.1.1");
+    assert!(addr_manager.add(&peer_id, addr("2.2.2.2"), &source, now));
+    // known address
+    assert!(!addr_manager.add(&peer_id, addr("2.2.2.2"), &source, now));
+    assert_eq!(addr_manager.len(), 1);
+
+    let selected = addr_manager.select(2, false, now, |_| true);
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].addr, addr("2.2.2.2"));
+    assert!(addr_manager.select(2, false, now, |_| false).is_empty());
+}
+
+#[test]
+fn test_source_fills_limited_slots() {
+    let mut addr_manager = new_addr_manager();
+    let now = Duration::from_secs(100);
+    let source = addr("1.1.1.1");
+    // addresses of one group from one source share a single bucket
+    for i in 0..(BUCKET_SIZE * 4) {
+        addr_manager.add(
+            &PeerId::random(),
+            addr(&format!("2.2.{}.{}", i / 256, i % 256)),
+            &source,
+            now,
+        );
+    }
+    assert!(addr_manager.len() <= BUCKET_SIZE);
+}
+
+#[test]
+fn test_select_distinct_groups() {
+    let mut addr_manager = new_addr_manager();
+    let now = Duration::from_secs(100);
+    for ip in &["2.2.0.1", "2.2.0.2", "2.2.0.3", "3.3.0.1"] {
+        let addr = addr(ip);
+        addr_manager.add(&PeerId::random(), addr.clone(), &addr, now);
+    }
+    assert_eq!(addr_manager.select(4, false, now, |_| true).len(), 2);
+}
+
+#[test]
+fn test_mark_good() {
+    let mut addr_manager = new_addr_manager();
+    let now = Duration::from_secs(100);
+    let peer_id = PeerId::random();
+    let addr = addr("2.2.2.2");
+    addr_manager.add(&peer_id, addr.clone(), &addr, now);
+    addr_manager.mark_attempt(&peer_id, &addr, now);
+    assert_eq!(addr_manager.get(&peer_id, &addr).unwrap().attempts, 1);
+
+    addr_manager.mark_good(&peer_id, &addr, now);
+    let info = addr_manager.get(&peer_id, &addr).unwrap();
+    assert!(info.tried);
+    assert_eq!(info.attempts, 0);
+    assert_eq!(info.last_success_at, now);
+    // tried addresses are not feeler candidates
+    assert!(addr_manager.select(1, true, now, |_| true).is_empty());
+    assert_eq!(addr_manager.select(1, false, now, |_| true).len(), 1);
+
+    addr_manager.remove_peer(&peer_id);
+    assert!(addr_manager.is_empty());
+}
+
+#[test]
+fn test_terrible_addr() {
+    let mut addr_manager = new_addr_manager();
+    let now = Duration::from_secs(100);
+    let peer_id = PeerId::random();
+    let addr = addr("2.2.2.2");
+    addr_manager.add(&peer_id, addr.clone(), &addr, now);
+    for _ in 0..3 {
+        addr_manager.mark_attempt(&peer_id, &addr, now);
+    }
+    assert!(addr_manager.get(&peer_id, &addr).unwrap().is_terrible(now));
+    assert!(addr_manager.select(1, false, now, |_| true).is_empty());
+}
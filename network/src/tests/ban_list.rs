@@ -0,0 +1,83 @@
+use crate::peer_store::ban_list::{BanList, BannedAddr, IpNetwork};
+use std::net::IpAddr;
+use std::time::Duration;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_parse_ip_network() {
+    let network = "192.168.1.7".parse::<IpNetwork>().unwrap();
+    assert_eq!(network.prefix(), 32);
+    assert_eq!(network.to_string(), "192.168.1.7/32");
+
+    // host bits are cleared
+    let network = "192.168.1.7/16".parse::<IpNetwork>().unwrap();
+    assert_eq!(network.ip(), ip("192.168.0.0"));
+    assert_eq!(network.to_string(), "192.168.0.0/16");
+
+    let network = "2001:db8::1/32".parse::<IpNetwork>().unwrap();
+    assert_eq!(network.to_string(), "2001:db8::/32");
+
+    assert!("192.168.1.7/33".parse::<IpNetwork>().is_err());
+    assert!("192.168.1/24".parse::<IpNetwork>().is_err());
+    assert!("192.168.1.7/a".parse::<IpNetwork>().is_err());
+}
+
+#[test]
+fn test_ip_network_contains() {
+    let network = "192.168.0.0/16".parse::<IpNetwork>().unwrap();
+    assert!(network.contains(ip("192.168.1.7")));
+    assert!(network.contains(ip("::ffff:192.168.1.7")));
+    assert!(!network.contains(ip("192.169.1.7")));
+    assert!(!network.contains(ip("2001:db8::1")));
+
+    let network = "2001:db8::/32".parse::<IpNetwork>().unwrap();
+    assert!(network.contains(ip("2001:db8:1::1")));
+    assert!(!network.contains(ip("2001:db9::1")));
+    assert!(!network.contains(ip("192.168.1.7")));
+
+    let all = "0.0.0.0/0".parse::<IpNetwork>().unwrap();
+    assert!(all.contains(ip("1.2.3.4")));
+    // IPv4 compatible addresses are not IPv4 ones
+    assert!(!all.contains(ip("::1")));
+}
+
+#[test]
+fn test_ipv4_mapped_network() {
+    let network = "::ffff:192.168.1.7".parse::<IpNetwork>().unwrap();
+    assert_eq!(network, "192.168.1.7".parse::<IpNetwork>().unwrap());
+    assert_eq!(network.to_string(), "192.168.1.7/32");
+    assert_eq!(
+        IpNetwork::from_ip(ip("::ffff:192.168.1.7")),
+        IpNetwork::from_ip(ip("192.168.1.7"))
+    );
+
+    let network = "::ffff:192.168.0.0/112".parse::<IpNetwork>().unwrap();
+    assert_eq!(network.to_string(), "192.168.0.0/16");
+    assert!(network.contains(ip("192.168.1.7")));
+    assert!(network.contains(ip("::ffff:192.168.1.7")));
+
+    let network = "::1".parse::<IpNetwork>().unwrap();
+    assert_eq!(network.to_string(), "::1/128");
+}
+
+#[test]
+fn test_ban_list_expires() {
+    let mut ban_list = BanList::default();
+    let address = "10.0.0.0/8".parse::<IpNetwork>().unwrap();
+    ban_list.insert(BannedAddr {
+        address,
+        ban_until: Duration::from_secs(100),
+        ban_reason: "test".to_owned(),
+        created_at: Duration::from_secs(0),
+    });
+    let addr = "/ip4/10.1.2.3/tcp/8115".parse().unwrap();
+    assert!(ban_list.is_addr_banned(&addr, Duration::from_secs(50)));
+    assert!(!ban_list.is_addr_banned(&addr, Duration::from_secs(150)));
+    assert_eq!(ban_list.banned_addrs(Duration::from_secs(50)).len(), 1);
+
+    ban_list.clear_expires(Duration::from_secs(150));
+    assert!(ban_list.is_empty());
+}
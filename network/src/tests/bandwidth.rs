@@ -0,0 +1,167 @@
+use crate::bandwidth::{Bandwidth, Traffic, MAX_QUEUED_BYTES};
+use crate::errors::PeerError;
+use crate::ProtocolId;
+use p2p::bytes::Bytes;
+use std::time::{Duration, Instant};
+
+fn message(len: usize) -> Bytes {
+    Bytes::from(vec![0u8; len])
+}
+
+#[test]
+fn test_traffic_accounting() {
+    let bandwidth = Bandwidth::new(0, 0);
+    assert!(!bandwidth.is_throttled());
+    let now = Instant::now();
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(10), now)
+        .unwrap()
+        .is_some());
+    bandwidth.record_sent(2.into(), 100.into(), 5);
+    bandwidth.record_received(1.into(), 101.into(), 7);
+
+    let traffic = bandwidth.peer_traffic(1.into()).unwrap();
+    assert_eq!(
+        traffic.total,
+        Traffic {
+            bytes_sent: 10,
+            bytes_received: 7,
+        }
+    );
+    assert_eq!(traffic.protocols[&ProtocolId::from(100)].bytes_sent, 10);
+    assert_eq!(traffic.protocols[&ProtocolId::from(101)].bytes_received, 7);
+    assert_eq!(
+        bandwidth.total_traffic(),
+        Traffic {
+            bytes_sent: 15,
+            bytes_received: 7,
+        }
+    );
+
+    bandwidth.remove_peer(1.into());
+    assert!(bandwidth.peer_traffic(1.into()).is_none());
+    assert_eq!(bandwidth.total_traffic().bytes_sent, 15);
+}
+
+#[test]
+fn test_peer_upload_throttle() {
+    let bandwidth = Bandwidth::new(100, 0);
+    assert!(bandwidth.is_throttled());
+    let now = Instant::now();
+    // a message larger than the rate still goes when the bucket is full
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(150), now)
+        .unwrap()
+        .is_some());
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(10), now)
+        .unwrap()
+        .is_none());
+    // other peers have their own throttle
+    assert!(bandwidth
+        .send(2.into(), 100.into(), message(10), now)
+        .unwrap()
+        .is_some());
+    assert!(bandwidth.flush(now).is_empty());
+
+    // the bucket is back above zero after 0.5s
+    let released = bandwidth.flush(now + Duration::from_millis(600));
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].0, 1.into());
+    assert_eq!(released[0].2.len(), 10);
+    assert_eq!(
+        bandwidth.peer_traffic(1.into()).unwrap().total.bytes_sent,
+        160
+    );
+}
+
+#[test]
+fn test_global_upload_throttle() {
+    let bandwidth = Bandwidth::new(0, 100);
+    let now = Instant::now();
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(100), now)
+        .unwrap()
+        .is_some());
+    assert!(bandwidth
+        .send(2.into(), 100.into(), message(10), now)
+        .unwrap()
+        .is_none());
+    assert_eq!(bandwidth.flush(now + Duration::from_secs(1)).len(), 1);
+}
+
+#[test]
+fn test_upload_queue_limit() {
+    let bandwidth = Bandwidth::new(1, 0);
+    let now = Instant::now();
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(1), now)
+        .unwrap()
+        .is_some());
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(MAX_QUEUED_BYTES), now)
+        .unwrap()
+        .is_none());
+    // dropped, the queue is full
+    assert_eq!(
+        bandwidth.send(1.into(), 100.into(), message(1), now),
+        Err(PeerError::UploadQueueFull(1.into()))
+    );
+    let released = bandwidth.flush(now + Duration::from_secs(2));
+    assert_eq!(released.len(), 1);
+    assert!(bandwidth.flush(now + Duration::from_secs(3600)).is_empty());
+}
+
+#[test]
+fn test_flush_round_robin() {
+    let bandwidth = Bandwidth::new(0, 100);
+    let now = Instant::now();
+    assert!(bandwidth
+        .send(1.into(), 100.into(), message(100), now)
+        .unwrap()
+        .is_some());
+    for _ in 0..3 {
+        for session_id in 1..=2 {
+            assert!(bandwidth
+                .send(session_id.into(), 100.into(), message(40), now)
+                .unwrap()
+                .is_none());
+        }
+    }
+    // the peers share the global rate, 1s releases 3 messages of 40 bytes
+    let released = bandwidth
+        .flush(now + Duration::from_secs(1))
+        .into_iter()
+        .map(|(session_id, _, _)| session_id)
+        .collect::<Vec<_>>();
+    assert_eq!(released, vec![1.into(), 2.into(), 1.into()]);
+    // peer 2 goes first in the next flush, 80 bytes for 2 messages
+    let released = bandwidth
+        .flush(now + Duration::from_secs(2))
+        .into_iter()
+        .map(|(session_id, _, _)| session_id)
+        .collect::<Vec<_>>();
+    assert_eq!(released, vec![2.into(), 1.into()]);
+}
+
+#[test]
+fn test_exempt_peer() {
+    let bandwidth = Bandwidth::new(1, 1);
+    bandwidth.exempt_peer(1.into());
+    let now = Instant::now();
+    for _ in 0..3 {
+        assert!(bandwidth
+            .send(1.into(), 100.into(), message(MAX_QUEUED_BYTES), now)
+            .unwrap()
+            .is_some());
+    }
+    // the global rate is left to the others
+    assert!(bandwidth
+        .send(2.into(), 100.into(), message(1), now)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        bandwidth.total_traffic().bytes_sent,
+        3 * MAX_QUEUED_BYTES as u64 + 1
+    );
+}
@@ -0,0 +1,114 @@
+use crate::compression::{decode, encode, Compression, COMPRESSION_THRESHOLD};
+use crate::ProtocolId;
+use p2p::bytes::Bytes;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn message_type(data: &[u8]) -> &'static str {
+    if data.len() >= COMPRESSION_THRESHOLD {
+        "Large"
+    } else {
+        "Small"
+    }
+}
+
+#[test]
+fn test_encode_decode() {
+    let small = vec![7u8; 10];
+    let encoded = encode(&small);
+    assert_eq!(encoded.len(), small.len() + 1);
+    assert_eq!(decode(&encoded).unwrap(), Bytes::from(small));
+
+    let large = vec![7u8; COMPRESSION_THRESHOLD * 4];
+    let encoded = encode(&large);
+    assert!(encoded.len() < large.len());
+    assert_eq!(decode(&encoded).unwrap(), Bytes::from(large));
+
+    assert!(decode(&[]).is_err());
+    assert!(decode(&[2, 0]).is_err());
+    assert!(decode(&[1, 0xff, 0xff, 0xff]).is_err());
+}
+
+#[test]
+fn test_negotiated_compression() {
+    let compression = Compression::default();
+    let proto_id = ProtocolId::from(100);
+    compression.register(proto_id, "2".to_string(), message_type);
+    assert!(compression.is_registered(proto_id));
+    assert!(!compression.is_registered(ProtocolId::from(101)));
+
+    compression.connected(1.into(), proto_id, "1");
+    compression.connected(2.into(), proto_id, "2");
+    compression.connected(3.into(), proto_id, "10");
+    compression.connected(2.into(), ProtocolId::from(101), "2");
+    assert!(!compression.is_enabled(1.into(), proto_id));
+    assert!(compression.is_enabled(2.into(), proto_id));
+    assert!(compression.is_enabled(3.into(), proto_id));
+    assert!(!compression.is_enabled(2.into(), ProtocolId::from(101)));
+
+    // Sessions of the older versions are unchanged
+    let large = Bytes::from(vec![7u8; COMPRESSION_THRESHOLD * 4]);
+    assert_eq!(compression.encode(1.into(), proto_id, large.clone()), large);
+    assert_eq!(
+        compression
+            .decode(1.into(), proto_id, large.clone())
+            .unwrap(),
+        large
+    );
+
+    let encoded = compression.encode(2.into(), proto_id, large.clone());
+    assert!(encoded.len() < large.len());
+    assert_eq!(
+        compression
+            .decode(2.into(), proto_id, encoded.clone())
+            .unwrap(),
+        large
+    );
+    compression.encode(2.into(), proto_id, Bytes::from(vec![7u8; 10]));
+
+    let mut stats = compression.stats();
+    stats.sort_by_key(|(_, message_type, _)| *message_type);
+    assert_eq!(stats.len(), 2);
+    let (_, message_type, large_stats) = stats[0];
+    assert_eq!(message_type, "Large");
+    assert_eq!(large_stats.messages, 1);
+    assert_eq!(large_stats.compressed_messages, 1);
+    assert_eq!(large_stats.raw_bytes, large.len() as u64);
+    assert_eq!(large_stats.sent_bytes, encoded.len() as u64);
+    let (_, message_type, small_stats) = stats[1];
+    assert_eq!(message_type, "Small");
+    assert_eq!(small_stats.compressed_messages, 0);
+    assert_eq!(small_stats.sent_bytes, 11);
+
+    compression.disconnected(2.into());
+    assert!(!compression.is_enabled(2.into(), proto_id));
+    assert!(compression.is_enabled(3.into(), proto_id));
+}
+
+#[test]
+fn test_frame_once_for_all_sessions() {
+    static NAMED: AtomicUsize = AtomicUsize::new(0);
+    fn counted_message_type(_data: &[u8]) -> &'static str {
+        NAMED.fetch_add(1, Ordering::SeqCst);
+        "Counted"
+    }
+
+    let compression = Compression::default();
+    let proto_id = ProtocolId::from(100);
+    compression.register(proto_id, "2".to_string(), counted_message_type);
+    compression.connected(1.into(), proto_id, "1");
+    compression.connected(2.into(), proto_id, "2");
+    compression.connected(3.into(), proto_id, "2");
+
+    let large = Bytes::from(vec![7u8; COMPRESSION_THRESHOLD * 4]);
+    let mut framed = compression.frame(proto_id, large.clone());
+    assert_eq!(framed.for_session(1.into()), large);
+    let encoded = framed.for_session(2.into());
+    assert!(encoded.len() < large.len());
+    assert_eq!(framed.for_session(3.into()), encoded);
+
+    // named once, counted for each compressing session
+    assert_eq!(NAMED.load(Ordering::SeqCst), 1);
+    let stats = compression.stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].2.messages, 2);
+}
@@ -1,3 +1,11 @@
+#[cfg(test)]
+mod addr_manager;
+#[cfg(test)]
+mod ban_list;
+#[cfg(test)]
+mod bandwidth;
+#[cfg(test)]
+mod compression;
 mod peer_registry;
 #[cfg(test)]
 mod sqlite_peer_store;
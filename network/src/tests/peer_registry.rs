@@ -5,6 +5,7 @@ use crate::{
     peer_store::{PeerStore, SqlitePeerStore},
     Behaviour, PeerId, SessionType,
 };
+use std::thread;
 use std::time::{Duration, Instant};
 
 fn new_peer_store() -> Box<dyn PeerStore> {
@@ -286,3 +287,189 @@ fn test_accept_inbound_peer_eviction() {
         .expect("accept");
     assert!(peers_registry.get_key_by_peer_id(&evict_target).is_none());
 }
+
+#[test]
+fn test_accept_whitelisted_peer() {
+    let mut peer_store = new_peer_store();
+    let whitelisted_peer = PeerId::random();
+    let addr = "/ip4/127.0.0.1".parse::<Multiaddr>().unwrap();
+    let mut peers =
+        PeerRegistry::new(1, 1, false, vec![]).whitelist_peers(vec![whitelisted_peer.clone()]);
+    peers
+        .accept_peer(
+            PeerId::random(),
+            addr.clone(),
+            1.into(),
+            SessionType::Inbound,
+            peer_store.as_mut(),
+        )
+        .expect("accept");
+
+    // whitelisted peers are accepted even banned or over the limits
+    peer_store.ban_peer(&whitelisted_peer, Duration::from_secs(10));
+    assert!(peers
+        .accept_peer(
+            whitelisted_peer.clone(),
+            addr.clone(),
+            2.into(),
+            SessionType::Inbound,
+            peer_store.as_mut(),
+        )
+        .expect("accept")
+        .is_none());
+    assert!(peers.get_peer(2.into()).unwrap().is_whitelisted);
+    assert_eq!(peers.connection_status().unreserved_inbound, 1);
+
+    // and never evicted for new inbound peers
+    let evicted = peers
+        .accept_peer(
+            PeerId::random(),
+            addr.clone(),
+            3.into(),
+            SessionType::Inbound,
+            peer_store.as_mut(),
+        )
+        .expect("accept")
+        .expect("evict a peer");
+    assert_ne!(evicted.peer_id, whitelisted_peer);
+}
+
+#[test]
+fn test_accept_ws_inbound_peer_until_full() {
+    let mut peer_store = new_peer_store();
+    let addr = "/ip4/127.0.0.1/tcp/8115".parse::<Multiaddr>().unwrap();
+    let ws_addr = "/ip4/127.0.0.1/tcp/8116/ws".parse::<Multiaddr>().unwrap();
+    let mut peers = PeerRegistry::new(1, 1, false, vec![]).max_ws_inbound(2);
+    for session_id in 1..=2 {
+        peers
+            .accept_peer(
+                PeerId::random(),
+                ws_addr.clone(),
+                session_id.into(),
+                SessionType::Inbound,
+                peer_store.as_mut(),
+            )
+            .expect("accept");
+    }
+    assert_eq!(
+        peers
+            .accept_peer(
+                PeerId::random(),
+                ws_addr.clone(),
+                3.into(),
+                SessionType::Inbound,
+                peer_store.as_mut(),
+            )
+            .unwrap_err(),
+        PeerError::ReachMaxWsInboundLimit,
+    );
+
+    // WebSocket peers do not take the inbound slots
+    peers
+        .accept_peer(
+            PeerId::random(),
+            addr.clone(),
+            4.into(),
+            SessionType::Inbound,
+            peer_store.as_mut(),
+        )
+        .expect("accept");
+    let status = peers.connection_status();
+    assert_eq!(status.ws_inbound, 2);
+    assert_eq!(status.unreserved_inbound, 1);
+}
+
+#[test]
+fn test_accept_allowlisted_peer_only() {
+    let mut peer_store = new_peer_store();
+    let allowed_peer = PeerId::random();
+    let reserved_peer = PeerId::random();
+    let addr = "/ip4/127.0.0.1".parse::<Multiaddr>().unwrap();
+    let mut peers = PeerRegistry::new(3, 3, false, vec![reserved_peer.clone()])
+        .allowlist(vec![allowed_peer.clone()]);
+    assert!(peers.is_allowed(&allowed_peer));
+    assert!(!peers.is_allowed(&reserved_peer));
+
+    for (session_id, peer_id) in vec![(1, reserved_peer), (2, PeerId::random())] {
+        assert_eq!(
+            peers
+                .accept_peer(
+                    peer_id,
+                    addr.clone(),
+                    session_id.into(),
+                    SessionType::Outbound,
+                    peer_store.as_mut(),
+                )
+                .unwrap_err(),
+            PeerError::NotAllowed,
+        );
+    }
+    peers
+        .accept_peer(
+            allowed_peer,
+            addr.clone(),
+            3.into(),
+            SessionType::Inbound,
+            peer_store.as_mut(),
+        )
+        .expect("accept");
+}
+
+#[test]
+fn test_peers_to_rotate() {
+    let mut peer_store = new_peer_store();
+    let reserved_peer = PeerId::random();
+    let mut peers = PeerRegistry::new(3, 5, false, vec![reserved_peer.clone()]);
+    let outbound = vec![
+        (1, PeerId::random(), "/ip4/1.1.1.1/tcp/8115"),
+        (2, PeerId::random(), "/ip4/1.1.2.2/tcp/8115"),
+        (3, PeerId::random(), "/ip4/2.2.1.1/tcp/8115"),
+        (4, reserved_peer, "/ip4/1.1.3.3/tcp/8115"),
+    ];
+    for (session_id, peer_id, addr) in outbound {
+        peers
+            .accept_peer(
+                peer_id,
+                addr.parse().unwrap(),
+                session_id.into(),
+                SessionType::Outbound,
+                peer_store.as_mut(),
+            )
+            .expect("accept");
+    }
+    peers
+        .accept_peer(
+            PeerId::random(),
+            "/ip4/1.1.4.4/tcp/8115".parse().unwrap(),
+            5.into(),
+            SessionType::Inbound,
+            peer_store.as_mut(),
+        )
+        .expect("accept");
+
+    // Reserved and inbound peers are not counted
+    let groups = peers.outbound_groups();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups.values().sum::<u32>(), 3);
+    assert_eq!(groups.values().max(), Some(&2));
+
+    // The peers of the crowded group go first
+    let rotated = peers.peers_to_rotate(2, Duration::from_secs(0));
+    assert_eq!(rotated.len(), 2);
+    assert!(rotated.contains(&1.into()));
+    assert!(rotated.contains(&2.into()));
+    assert_eq!(peers.peers_to_rotate(5, Duration::from_secs(0)).len(), 3);
+
+    // Newly connected peers are kept
+    assert!(peers
+        .peers_to_rotate(2, Duration::from_secs(3600))
+        .is_empty());
+
+    // So are the peers recently delivering the best chain
+    let min_age = Duration::from_millis(50);
+    thread::sleep(min_age);
+    peers.get_peer_mut(3.into()).unwrap().last_block_time = Some(Instant::now());
+    let rotated = peers.peers_to_rotate(5, min_age);
+    assert_eq!(rotated.len(), 2);
+    assert!(!rotated.contains(&3.into()));
+}
@@ -3,7 +3,7 @@ use crate::{
     peer_store::{
         sqlite::db,
         sqlite::peer_store::{LAST_CONNECTED_TIMEOUT_SECS, PEER_STORE_LIMIT},
-        PeerStore, SqlitePeerStore, Status,
+        AnchorPeer, IpNetwork, PeerStore, SqlitePeerStore, Status,
     },
     Behaviour, PeerId, SessionType,
 };
@@ -18,12 +18,16 @@ fn test_add_connected_peer() {
     let mut peer_store: Box<dyn PeerStore> = Box::new(new_peer_store());
     let peer_id = PeerId::random();
     let addr = "/ip4/127.0.0.1".parse().unwrap();
-    peer_store.add_connected_peer(&peer_id, addr, SessionType::Outbound);
+    peer_store.add_connected_peer(&peer_id, addr, SessionType::Inbound);
     assert_eq!(
         peer_store.peer_score(&peer_id),
         Some(peer_store.peer_score_config().default_score)
     );
     assert_eq!(peer_store.peer_addrs(&peer_id, 1).unwrap().len(), 0);
+    // the outbound address is known afterward
+    let addr = "/ip4/127.0.0.1/tcp/8115".parse().unwrap();
+    peer_store.add_connected_peer(&peer_id, addr, SessionType::Outbound);
+    assert_eq!(peer_store.peer_addrs(&peer_id, 2).unwrap().len(), 1);
 }
 
 #[test]
@@ -194,3 +198,230 @@ fn test_delete_peer_info() {
     // evict_target is evicted in previous step
     assert_eq!(peer_store.peer_score(&evict_target), None);
 }
+
+#[test]
+fn test_anchor_peers() {
+    let mut peer_store: Box<dyn PeerStore> = Box::new(new_peer_store());
+    assert!(peer_store.anchor_peers().is_empty());
+    let anchor = AnchorPeer {
+        peer_id: PeerId::random(),
+        address: "/ip4/127.0.0.1/tcp/8115".parse().unwrap(),
+        last_seen_at: Duration::from_secs(42),
+        protocols: vec![(100.into(), "1".to_owned()), (101.into(), "1".to_owned())],
+    };
+    peer_store.update_anchor_peers(vec![anchor.clone()]);
+    assert_eq!(peer_store.anchor_peers(), vec![anchor.clone()]);
+
+    // replaced on each update
+    let other = AnchorPeer {
+        peer_id: PeerId::random(),
+        address: "/ip4/127.0.0.2/tcp/8115".parse().unwrap(),
+        last_seen_at: Duration::from_secs(43),
+        protocols: vec![],
+    };
+    peer_store.update_anchor_peers(vec![other.clone()]);
+    assert_eq!(peer_store.anchor_peers(), vec![other]);
+}
+
+#[test]
+fn test_persist_addrs() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir
+        .path()
+        .join("peer_store.db")
+        .to_string_lossy()
+        .to_string();
+    let peer_id = PeerId::random();
+    let addr = "/ip4/1.1.1.1/tcp/8115".parse::<Multiaddr>().unwrap();
+    {
+        let mut peer_store = SqlitePeerStore::file(path.clone()).expect("file");
+        peer_store.add_discovered_addr(&peer_id, addr.clone());
+        peer_store.add_connected_peer(&peer_id, addr.clone(), SessionType::Outbound);
+        peer_store.persist_addrs();
+    }
+    let mut peer_store = SqlitePeerStore::file(path).expect("file");
+    assert_eq!(
+        peer_store.peers_to_attempt(2),
+        vec![(peer_id.clone(), addr)]
+    );
+    // connected outbound, it is not a feeler candidate anymore
+    peer_store.update_status(&peer_id, Status::Unknown);
+    assert!(peer_store.peers_to_feeler(2).is_empty());
+}
+
+#[test]
+fn test_peers_to_attempt_distinct_groups() {
+    let mut peer_store: Box<dyn PeerStore> = Box::new(new_peer_store());
+    for ip in &["1.1.0.1", "1.1.0.2", "2.2.0.1"] {
+        let addr = format!("/ip4/{}/tcp/8115", ip).parse().unwrap();
+        peer_store.add_discovered_addr(&PeerId::random(), addr);
+    }
+    assert_eq!(peer_store.peers_to_attempt(3).len(), 2);
+}
+
+#[test]
+fn test_ban_network() {
+    let mut peer_store: Box<dyn PeerStore> = Box::new(new_peer_store());
+    let peer_id = PeerId::random();
+    let addr = "/ip4/10.1.2.3/tcp/8115".parse::<Multiaddr>().unwrap();
+    peer_store.add_connected_peer(&peer_id, addr.clone(), SessionType::Inbound);
+    peer_store.add_discovered_addr(&peer_id, addr.clone());
+
+    let subnet = "10.1.0.0/16".parse::<IpNetwork>().unwrap();
+    peer_store.ban_network(subnet, Duration::from_secs(10), "test".to_owned());
+    assert!(peer_store.is_addr_banned(&addr));
+    assert!(peer_store.is_banned(&peer_id));
+    assert!(peer_store.peers_to_attempt(1).is_empty());
+    let banned_addrs = peer_store.banned_addrs();
+    assert_eq!(banned_addrs.len(), 1);
+    assert_eq!(banned_addrs[0].address, subnet);
+    assert_eq!(banned_addrs[0].ban_reason, "test");
+
+    assert!(peer_store.unban_network(&subnet));
+    assert!(!peer_store.unban_network(&subnet));
+    assert!(!peer_store.is_addr_banned(&addr));
+    assert_eq!(peer_store.peers_to_attempt(1).len(), 1);
+
+    peer_store.ban_network(subnet, Duration::from_secs(10), "test".to_owned());
+    peer_store.clear_banned_addrs();
+    assert!(peer_store.banned_addrs().is_empty());
+}
+
+#[test]
+fn test_persist_banned_addrs() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir
+        .path()
+        .join("peer_store.db")
+        .to_string_lossy()
+        .to_string();
+    let subnet = "10.1.0.0/16".parse::<IpNetwork>().unwrap();
+    {
+        let mut peer_store = SqlitePeerStore::file(path.clone()).expect("file");
+        peer_store.ban_network(subnet, Duration::from_secs(100), "test".to_owned());
+    }
+    let peer_store = SqlitePeerStore::file(path).expect("file");
+    let addr = "/ip4/10.1.2.3/tcp/8115".parse::<Multiaddr>().unwrap();
+    assert!(peer_store.is_addr_banned(&addr));
+    assert_eq!(peer_store.banned_addrs()[0].address, subnet);
+}
+
+#[test]
+fn test_migrate_legacy_peer_addrs() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir
+        .path()
+        .join("peer_store.db")
+        .to_string_lossy()
+        .to_string();
+    let connected = PeerId::random();
+    let discovered = PeerId::random();
+    let connected_addr = "/ip4/1.1.1.1/tcp/8115".parse::<Multiaddr>().unwrap();
+    let discovered_addr = "/ip4/2.2.2.2/tcp/8115".parse::<Multiaddr>().unwrap();
+    {
+        // the tables of a peer store saving addresses in `peer_addr`
+        let conn = rusqlite::Connection::open(&path).expect("open");
+        db::create_tables(&conn).expect("create tables");
+        conn.execute_batch(
+            "CREATE TABLE peer_addr (
+            id INTEGER PRIMARY KEY NOT NULL,
+            peer_info_id INTEGER NOT NULL,
+            addr BINARY NOT NULL,
+            last_connected_at INTEGER NOT NULL
+            );",
+        )
+        .expect("create peer_addr");
+        for (id, (peer_id, addr, last_connected_at)) in [
+            (&connected, &connected_addr, 42u32),
+            (&discovered, &discovered_addr, 0u32),
+        ]
+        .iter()
+        .enumerate()
+        {
+            db::PeerInfo::insert(
+                &conn,
+                peer_id,
+                addr,
+                SessionType::Outbound,
+                100,
+                Duration::from_secs(0),
+            )
+            .expect("insert peer info");
+            let addr: &[u8] = addr.as_ref();
+            conn.execute_named(
+                "INSERT INTO peer_addr (peer_info_id, addr, last_connected_at)
+                VALUES (:peer_info_id, :addr, :last_connected_at)",
+                &[
+                    (":peer_info_id", &(id as u32 + 1)),
+                    (":addr", &addr),
+                    (":last_connected_at", last_connected_at),
+                ],
+            )
+            .expect("insert peer addr");
+        }
+    }
+    let peer_store = SqlitePeerStore::file(path).expect("file");
+    assert_eq!(
+        peer_store.peer_addrs(&connected, 2),
+        Some(vec![connected_addr])
+    );
+    assert_eq!(
+        peer_store.peer_addrs(&discovered, 2),
+        Some(vec![discovered_addr])
+    );
+    assert!(db::get_legacy_peer_addrs(&peer_store.conn)
+        .expect("legacy addrs")
+        .is_empty());
+    // the migrated addresses are persisted
+    assert!(!db::get_addr_infos(&peer_store.conn)
+        .expect("addr infos")
+        .is_empty());
+}
+
+#[test]
+fn test_migrate_legacy_ban_list() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir
+        .path()
+        .join("peer_store.db")
+        .to_string_lossy()
+        .to_string();
+    let now = faketime::unix_time().as_secs() as u32;
+    {
+        // the ban records of a peer store banning IPs in `ban_list`
+        let conn = rusqlite::Connection::open(&path).expect("open");
+        conn.execute_batch(
+            "CREATE TABLE ban_list (
+            id INTEGER PRIMARY KEY NOT NULL,
+            ip BINARY UNIQUE NOT NULL,
+            ban_time INTEGER NOT NULL
+            );",
+        )
+        .expect("create ban_list");
+        for (ip, ban_time) in [([10u8, 1, 2, 3], now + 100), ([10u8, 1, 2, 4], now - 100)].iter() {
+            let ip: &[u8] = ip;
+            conn.execute_named(
+                "INSERT INTO ban_list (ip, ban_time) VALUES (:ip, :ban_time)",
+                &[(":ip", &ip), (":ban_time", ban_time)],
+            )
+            .expect("insert ban record");
+        }
+    }
+    let peer_store = SqlitePeerStore::file(path.clone()).expect("file");
+    assert!(peer_store.is_addr_banned(&"/ip4/10.1.2.3/tcp/8115".parse().unwrap()));
+    // expired bans are not migrated
+    assert!(!peer_store.is_addr_banned(&"/ip4/10.1.2.4/tcp/8115".parse().unwrap()));
+    drop(peer_store);
+
+    // kept after the table is dropped
+    let peer_store = SqlitePeerStore::file(path).expect("file");
+    assert!(
+        db::get_legacy_ban_records(&peer_store.conn, faketime::unix_time())
+            .expect("legacy bans")
+            .is_empty()
+    );
+    assert_eq!(
+        peer_store.banned_addrs()[0].address,
+        "10.1.2.3".parse::<IpNetwork>().unwrap()
+    );
+}
@@ -2,6 +2,7 @@
 
 use ckb_core::block::Block;
 use ckb_core::service::Request;
+use ckb_core::transaction::Transaction;
 use crossbeam_channel::{select, Receiver, Sender};
 use fnv::FnvHashMap;
 use log::{debug, trace, warn};
@@ -37,8 +38,12 @@ pub const NOTIFY_CHANNEL_SIZE: usize = 128;
 //     }
 // }
 
-pub type MsgNewTransaction = ();
-// pub type MsgNewTip = Arc<Block>;
+pub type MsgNewTransaction = Arc<Transaction>;
+/// A transaction rejected by the pool, with the reason
+pub type MsgRejectedTransaction = Arc<(Transaction, String)>;
+/// The transactions evicted from the pool at once for not being committed in time
+pub type MsgExpiredTransaction = Arc<Vec<Transaction>>;
+pub type MsgNewTip = Arc<Block>;
 pub type MsgNewUncle = Arc<Block>;
 // pub type MsgSwitchFork = Arc<ForkBlocks>;
 pub type NotifyRegister<M> = Sender<Request<(String, usize), Receiver<M>>>;
@@ -49,12 +54,16 @@ pub struct NotifyService {}
 #[derive(Clone)]
 pub struct NotifyController {
     stop: StopHandler<()>,
-    // new_transaction_register: NotifyRegister<MsgNewTransaction>,
-    // new_tip_register: NotifyRegister<MsgNewTip>,
+    new_transaction_register: NotifyRegister<MsgNewTransaction>,
+    rejected_transaction_register: NotifyRegister<MsgRejectedTransaction>,
+    expired_transaction_register: NotifyRegister<MsgExpiredTransaction>,
+    new_tip_register: NotifyRegister<MsgNewTip>,
     new_uncle_register: NotifyRegister<MsgNewUncle>,
     // switch_fork_register: NotifyRegister<MsgSwitchFork>,
-    // new_transaction_notifier: Sender<MsgNewTransaction>,
-    // new_tip_notifier: Sender<MsgNewTip>,
+    new_transaction_notifier: Sender<MsgNewTransaction>,
+    rejected_transaction_notifier: Sender<MsgRejectedTransaction>,
+    expired_transaction_notifier: Sender<MsgExpiredTransaction>,
+    new_tip_notifier: Sender<MsgNewTip>,
     new_uncle_notifier: Sender<MsgNewUncle>,
     // switch_fork_notifier: Sender<MsgSwitchFork>,
 }
@@ -69,26 +78,36 @@ impl NotifyService {
     pub fn start<S: ToString>(self, thread_name: Option<S>) -> NotifyController {
         let (signal_sender, signal_receiver) =
             crossbeam_channel::bounded::<()>(SIGNAL_CHANNEL_SIZE);
-        // let (new_transaction_register, new_transaction_register_receiver) =
-        //     crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
-        // let (new_tip_register, new_tip_register_receiver) =
-        //     crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
+        let (new_transaction_register, new_transaction_register_receiver) =
+            crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
+        let (rejected_transaction_register, rejected_transaction_register_receiver) =
+            crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
+        let (expired_transaction_register, expired_transaction_register_receiver) =
+            crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
+        let (new_tip_register, new_tip_register_receiver) =
+            crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
         let (new_uncle_register, new_uncle_register_receiver) =
             crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
         // let (switch_fork_register, switch_fork_register_receiver) =
         //     crossbeam_channel::bounded(REGISTER_CHANNEL_SIZE);
 
-        // let (new_transaction_sender, new_transaction_receiver) =
-        //     crossbeam_channel::bounded::<MsgNewTransaction>(NOTIFY_CHANNEL_SIZE);
-        // let (new_tip_sender, new_tip_receiver) =
-        //     crossbeam_channel::bounded::<MsgNewTip>(NOTIFY_CHANNEL_SIZE);
+        let (new_transaction_sender, new_transaction_receiver) =
+            crossbeam_channel::bounded::<MsgNewTransaction>(NOTIFY_CHANNEL_SIZE);
+        let (rejected_transaction_sender, rejected_transaction_receiver) =
+            crossbeam_channel::bounded::<MsgRejectedTransaction>(NOTIFY_CHANNEL_SIZE);
+        let (expired_transaction_sender, expired_transaction_receiver) =
+            crossbeam_channel::bounded::<MsgExpiredTransaction>(NOTIFY_CHANNEL_SIZE);
+        let (new_tip_sender, new_tip_receiver) =
+            crossbeam_channel::bounded::<MsgNewTip>(NOTIFY_CHANNEL_SIZE);
         let (new_uncle_sender, new_uncle_receiver) =
             crossbeam_channel::bounded::<MsgNewUncle>(NOTIFY_CHANNEL_SIZE);
         // let (switch_fork_sender, switch_fork_receiver) =
         //     crossbeam_channel::bounded::<MsgSwitchFork>(NOTIFY_CHANNEL_SIZE);
 
-        // let mut new_transaction_subscribers = FnvHashMap::default();
-        // let mut new_tip_subscribers = FnvHashMap::default();
+        let mut new_transaction_subscribers = FnvHashMap::default();
+        let mut rejected_transaction_subscribers = FnvHashMap::default();
+        let mut expired_transaction_subscribers = FnvHashMap::default();
+        let mut new_tip_subscribers = FnvHashMap::default();
         let mut new_uncle_subscribers = FnvHashMap::default();
         // let mut switch_fork_subscribers = FnvHashMap::default();
 
@@ -104,12 +123,18 @@ impl NotifyService {
                         break;
                     }
 
-                    // recv(new_transaction_register_receiver) -> msg => Self::handle_register_new_transaction(
-                    //     &mut new_transaction_subscribers, msg
-                    // ),
-                    // recv(new_tip_register_receiver) -> msg => Self::handle_register_new_tip(
-                    //     &mut new_tip_subscribers, msg
-                    // ),
+                    recv(new_transaction_register_receiver) -> msg => Self::handle_register_new_transaction(
+                        &mut new_transaction_subscribers, msg
+                    ),
+                    recv(rejected_transaction_register_receiver) -> msg => Self::handle_register_rejected_transaction(
+                        &mut rejected_transaction_subscribers, msg
+                    ),
+                    recv(expired_transaction_register_receiver) -> msg => Self::handle_register_expired_transaction(
+                        &mut expired_transaction_subscribers, msg
+                    ),
+                    recv(new_tip_register_receiver) -> msg => Self::handle_register_new_tip(
+                        &mut new_tip_subscribers, msg
+                    ),
                     recv(new_uncle_register_receiver) -> msg => Self::handle_register_new_uncle(
                         &mut new_uncle_subscribers, msg
                     ),
@@ -117,12 +142,18 @@ impl NotifyService {
                     //     &mut switch_fork_subscribers, msg
                     // ),
 
-                    // recv(new_transaction_receiver) -> msg => Self::handle_notify_new_transaction(
-                    //     &new_transaction_subscribers, msg
-                    // ),
-                    // recv(new_tip_receiver) -> msg => Self::handle_notify_new_tip(
-                    //     &new_tip_subscribers, msg
-                    // ),
+                    recv(new_transaction_receiver) -> msg => Self::handle_notify_new_transaction(
+                        &new_transaction_subscribers, msg
+                    ),
+                    recv(rejected_transaction_receiver) -> msg => Self::handle_notify_rejected_transaction(
+                        &rejected_transaction_subscribers, msg
+                    ),
+                    recv(expired_transaction_receiver) -> msg => Self::handle_notify_expired_transaction(
+                        &expired_transaction_subscribers, msg
+                    ),
+                    recv(new_tip_receiver) -> msg => Self::handle_notify_new_tip(
+                        &new_tip_subscribers, msg
+                    ),
                     recv(new_uncle_receiver) -> msg => Self::handle_notify_new_uncle(
                         &new_uncle_subscribers, msg
                     ),
@@ -134,56 +165,104 @@ impl NotifyService {
             .expect("Start notify service failed");
 
         NotifyController {
-            // new_transaction_register,
-            // new_tip_register,
+            new_transaction_register,
+            rejected_transaction_register,
+            expired_transaction_register,
+            new_tip_register,
             new_uncle_register,
             // switch_fork_register,
-            // new_transaction_notifier: new_transaction_sender,
-            // new_tip_notifier: new_tip_sender,
+            new_transaction_notifier: new_transaction_sender,
+            rejected_transaction_notifier: rejected_transaction_sender,
+            expired_transaction_notifier: expired_transaction_sender,
+            new_tip_notifier: new_tip_sender,
             new_uncle_notifier: new_uncle_sender,
             // switch_fork_notifier: switch_fork_sender,
             stop: StopHandler::new(SignalSender::Crossbeam(signal_sender), join_handle),
         }
     }
 
-    // fn handle_register_new_transaction(
-    //     subscribers: &mut FnvHashMap<String, Sender<MsgNewTransaction>>,
-    //     msg: Result<
-    //         Request<(String, usize), Receiver<MsgNewTransaction>>,
-    //         crossbeam_channel::RecvError,
-    //     >,
-    // ) {
-    //     match msg {
-    //         Ok(Request {
-    //             responder,
-    //             arguments: (name, capacity),
-    //         }) => {
-    //             debug!(target: "notify", "Register new_transaction {:?}", name);
-    //             let (sender, receiver) = crossbeam_channel::bounded::<MsgNewTransaction>(capacity);
-    //             subscribers.insert(name, sender);
-    //             let _ = responder.send(receiver);
-    //         }
-    //         _ => warn!(target: "notify", "Register new_transaction channel is closed"),
-    //     }
-    // }
+    fn handle_register_new_transaction(
+        subscribers: &mut FnvHashMap<String, Sender<MsgNewTransaction>>,
+        msg: Result<
+            Request<(String, usize), Receiver<MsgNewTransaction>>,
+            crossbeam_channel::RecvError,
+        >,
+    ) {
+        match msg {
+            Ok(Request {
+                responder,
+                arguments: (name, capacity),
+            }) => {
+                debug!(target: "notify", "Register new_transaction {:?}", name);
+                let (sender, receiver) = crossbeam_channel::bounded::<MsgNewTransaction>(capacity);
+                subscribers.insert(name, sender);
+                let _ = responder.send(receiver);
+            }
+            _ => warn!(target: "notify", "Register new_transaction channel is closed"),
+        }
+    }
 
-    // fn handle_register_new_tip(
-    //     subscribers: &mut FnvHashMap<String, Sender<MsgNewTip>>,
-    //     msg: Result<Request<(String, usize), Receiver<MsgNewTip>>, crossbeam_channel::RecvError>,
-    // ) {
-    //     match msg {
-    //         Ok(Request {
-    //             responder,
-    //             arguments: (name, capacity),
-    //         }) => {
-    //             debug!(target: "notify", "Register new_tip {:?}", name);
-    //             let (sender, receiver) = crossbeam_channel::bounded::<MsgNewTip>(capacity);
-    //             subscribers.insert(name, sender);
-    //             let _ = responder.send(receiver);
-    //         }
-    //         _ => warn!(target: "notify", "Register new_tip channel is closed"),
-    //     }
-    // }
+    fn handle_register_rejected_transaction(
+        subscribers: &mut FnvHashMap<String, Sender<MsgRejectedTransaction>>,
+        msg: Result<
+            Request<(String, usize), Receiver<MsgRejectedTransaction>>,
+            crossbeam_channel::RecvError,
+        >,
+    ) {
+        match msg {
+            Ok(Request {
+                responder,
+                arguments: (name, capacity),
+            }) => {
+                debug!(target: "notify", "Register rejected_transaction {:?}", name);
+                let (sender, receiver) =
+                    crossbeam_channel::bounded::<MsgRejectedTransaction>(capacity);
+                subscribers.insert(name, sender);
+                let _ = responder.send(receiver);
+            }
+            _ => warn!(target: "notify", "Register rejected_transaction channel is closed"),
+        }
+    }
+
+    fn handle_register_expired_transaction(
+        subscribers: &mut FnvHashMap<String, Sender<MsgExpiredTransaction>>,
+        msg: Result<
+            Request<(String, usize), Receiver<MsgExpiredTransaction>>,
+            crossbeam_channel::RecvError,
+        >,
+    ) {
+        match msg {
+            Ok(Request {
+                responder,
+                arguments: (name, capacity),
+            }) => {
+                debug!(target: "notify", "Register expired_transaction {:?}", name);
+                let (sender, receiver) =
+                    crossbeam_channel::bounded::<MsgExpiredTransaction>(capacity);
+                subscribers.insert(name, sender);
+                let _ = responder.send(receiver);
+            }
+            _ => warn!(target: "notify", "Register expired_transaction channel is closed"),
+        }
+    }
+
+    fn handle_register_new_tip(
+        subscribers: &mut FnvHashMap<String, Sender<MsgNewTip>>,
+        msg: Result<Request<(String, usize), Receiver<MsgNewTip>>, crossbeam_channel::RecvError>,
+    ) {
+        match msg {
+            Ok(Request {
+                responder,
+                arguments: (name, capacity),
+            }) => {
+                debug!(target: "notify", "Register new_tip {:?}", name);
+                let (sender, receiver) = crossbeam_channel::bounded::<MsgNewTip>(capacity);
+                subscribers.insert(name, sender);
+                let _ = responder.send(receiver);
+            }
+            _ => warn!(target: "notify", "Register new_tip channel is closed"),
+        }
+    }
 
     fn handle_register_new_uncle(
         subscribers: &mut FnvHashMap<String, Sender<MsgNewUncle>>,
@@ -224,35 +303,65 @@ impl NotifyService {
     //     }
     // }
 
-    // fn handle_notify_new_transaction(
-    //     subscribers: &FnvHashMap<String, Sender<MsgNewTransaction>>,
-    //     msg: Result<MsgNewTransaction, crossbeam_channel::RecvError>,
-    // ) {
-    //     match msg {
-    //         Ok(()) => {
-    //             trace!(target: "notify", "event new transaction {:?}", msg);
-    //             for subscriber in subscribers.values() {
-    //                 let _ = subscriber.send(());
-    //             }
-    //         }
-    //         _ => warn!(target: "notify", "new transaction channel is closed"),
-    //     }
-    // }
+    fn handle_notify_new_transaction(
+        subscribers: &FnvHashMap<String, Sender<MsgNewTransaction>>,
+        msg: Result<MsgNewTransaction, crossbeam_channel::RecvError>,
+    ) {
+        match msg {
+            Ok(msg) => {
+                trace!(target: "notify", "event new transaction {:?}", msg);
+                for subscriber in subscribers.values() {
+                    let _ = subscriber.send(Arc::clone(&msg));
+                }
+            }
+            _ => warn!(target: "notify", "new transaction channel is closed"),
+        }
+    }
 
-    // fn handle_notify_new_tip(
-    //     subscribers: &FnvHashMap<String, Sender<MsgNewTip>>,
-    //     msg: Result<MsgNewTip, crossbeam_channel::RecvError>,
-    // ) {
-    //     match msg {
-    //         Ok(msg) => {
-    //             trace!(target: "notify", "event new tip {:?}", msg);
-    //             for subscriber in subscribers.values() {
-    //                 let _ = subscriber.send(Arc::clone(&msg));
-    //             }
-    //         }
-    //         _ => warn!(target: "notify", "new tip channel is closed"),
-    //     }
-    // }
+    fn handle_notify_rejected_transaction(
+        subscribers: &FnvHashMap<String, Sender<MsgRejectedTransaction>>,
+        msg: Result<MsgRejectedTransaction, crossbeam_channel::RecvError>,
+    ) {
+        match msg {
+            Ok(msg) => {
+                trace!(target: "notify", "event rejected transaction {:?}", msg);
+                for subscriber in subscribers.values() {
+                    let _ = subscriber.send(Arc::clone(&msg));
+                }
+            }
+            _ => warn!(target: "notify", "rejected transaction channel is closed"),
+        }
+    }
+
+    fn handle_notify_expired_transaction(
+        subscribers: &FnvHashMap<String, Sender<MsgExpiredTransaction>>,
+        msg: Result<MsgExpiredTransaction, crossbeam_channel::RecvError>,
+    ) {
+        match msg {
+            Ok(msg) => {
+                trace!(target: "notify", "event expired transaction {:?}", msg);
+                for subscriber in subscribers.values() {
+                    let _ = subscriber.send(Arc::clone(&msg));
+                }
+            }
+            _ => warn!(target: "notify", "expired transaction channel is closed"),
+        }
+    }
+
+    fn handle_notify_new_tip(
+        subscribers: &FnvHashMap<String, Sender<MsgNewTip>>,
+        msg: Result<MsgNewTip, crossbeam_channel::RecvError>,
+    ) {
+        match msg {
+            Ok(msg) => {
+                trace!(target: "notify", "event new tip {:?}", msg);
+                for subscriber in subscribers.values() {
+                    let _ = subscriber.send(Arc::clone(&msg));
+                }
+            }
+            _ => warn!(target: "notify", "new tip channel is closed"),
+        }
+    }
 
     fn handle_notify_new_uncle(
         subscribers: &FnvHashMap<String, Sender<MsgNewUncle>>,
@@ -286,14 +395,28 @@ impl NotifyService {
 }
 
 impl NotifyController {
-    // pub fn subscribe_new_transaction<S: ToString>(&self, name: S) -> Receiver<MsgNewTransaction> {
-    //     Request::call(&self.new_transaction_register, (name.to_string(), 128))
-    //         .expect("Subscribe new transaction failed")
-    // }
-    // pub fn subscribe_new_tip<S: ToString>(&self, name: S) -> Receiver<MsgNewTip> {
-    //     Request::call(&self.new_tip_register, (name.to_string(), 128))
-    //         .expect("Subscribe new tip failed")
-    // }
+    pub fn subscribe_new_transaction<S: ToString>(&self, name: S) -> Receiver<MsgNewTransaction> {
+        Request::call(&self.new_transaction_register, (name.to_string(), 128))
+            .expect("Subscribe new transaction failed")
+    }
+    pub fn subscribe_rejected_transaction<S: ToString>(
+        &self,
+        name: S,
+    ) -> Receiver<MsgRejectedTransaction> {
+        Request::call(&self.rejected_transaction_register, (name.to_string(), 128))
+            .expect("Subscribe rejected transaction failed")
+    }
+    pub fn subscribe_expired_transaction<S: ToString>(
+        &self,
+        name: S,
+    ) -> Receiver<MsgExpiredTransaction> {
+        Request::call(&self.expired_transaction_register, (name.to_string(), 128))
+            .expect("Subscribe expired transaction failed")
+    }
+    pub fn subscribe_new_tip<S: ToString>(&self, name: S) -> Receiver<MsgNewTip> {
+        Request::call(&self.new_tip_register, (name.to_string(), 128))
+            .expect("Subscribe new tip failed")
+    }
     pub fn subscribe_new_uncle<S: ToString>(&self, name: S) -> Receiver<MsgNewUncle> {
         Request::call(&self.new_uncle_register, (name.to_string(), 128))
             .expect("Subscribe new uncle failed")
@@ -303,12 +426,18 @@ impl NotifyController {
     //         .expect("Subscribe switch fork failed")
     // }
 
-    // pub fn notify_new_transaction(&self) {
-    //     let _ = self.new_transaction_notifier.send(());
-    // }
-    // pub fn notify_new_tip(&self, block: MsgNewTip) {
-    //     let _ = self.new_tip_notifier.send(block);
-    // }
+    pub fn notify_new_transaction(&self, tx: MsgNewTransaction) {
+        let _ = self.new_transaction_notifier.send(tx);
+    }
+    pub fn notify_rejected_transaction(&self, tx: MsgRejectedTransaction) {
+        let _ = self.rejected_transaction_notifier.send(tx);
+    }
+    pub fn notify_expired_transaction(&self, txs: MsgExpiredTransaction) {
+        let _ = self.expired_transaction_notifier.send(txs);
+    }
+    pub fn notify_new_tip(&self, block: MsgNewTip) {
+        let _ = self.new_tip_notifier.send(block);
+    }
     pub fn notify_new_uncle(&self, block: MsgNewUncle) {
         let _ = self.new_uncle_notifier.send(block);
     }
@@ -319,28 +448,51 @@ impl NotifyController {
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use ckb_core::transaction::TransactionBuilder;
+
+    #[test]
+    fn test_new_transaction() {
+        let tx = Arc::new(TransactionBuilder::default().build());
+        let notify = NotifyService::default().start::<&str>(None);
+        let receiver1 = notify.subscribe_new_transaction("miner1");
+        let receiver2 = notify.subscribe_new_transaction("miner2");
+        notify.notify_new_transaction(Arc::clone(&tx));
+        assert_eq!(receiver1.recv(), Ok(Arc::clone(&tx)));
+        assert_eq!(receiver2.recv(), Ok(tx));
+    }
 
-    // #[test]
-    // fn test_new_transaction() {
-    //     let notify = NotifyService::default().start::<&str>(None);
-    //     let receiver1 = notify.subscribe_new_transaction("miner1");
-    //     let receiver2 = notify.subscribe_new_transaction("miner2");
-    //     notify.notify_new_transaction();
-    //     assert_eq!(receiver1.recv(), Ok(()));
-    //     assert_eq!(receiver2.recv(), Ok(()));
-    // }
+    #[test]
+    fn test_rejected_transaction() {
+        let rejected = Arc::new((
+            TransactionBuilder::default().build(),
+            "InvalidTx".to_string(),
+        ));
+        let notify = NotifyService::default().start::<&str>(None);
+        let receiver = notify.subscribe_rejected_transaction("rpc");
+        notify.notify_rejected_transaction(Arc::clone(&rejected));
+        assert_eq!(receiver.recv(), Ok(rejected));
+    }
 
-    // #[test]
-    // fn test_new_tip() {
-    //     let tip = Arc::new(Block::default());
-    //     let notify = NotifyService::default().start::<&str>(None);
-    //     let receiver1 = notify.subscribe_new_tip("miner1");
-    //     let receiver2 = notify.subscribe_new_tip("miner2");
-    //     notify.notify_new_tip(Arc::clone(&tip));
-    //     assert_eq!(receiver1.recv(), Ok(Arc::clone(&tip)));
-    //     assert_eq!(receiver2.recv(), Ok(tip));
-    // }
+    #[test]
+    fn test_expired_transaction() {
+        let txs = Arc::new(vec![TransactionBuilder::default().build()]);
+        let notify = NotifyService::default().start::<&str>(None);
+        let receiver = notify.subscribe_expired_transaction("rpc");
+        notify.notify_expired_transaction(Arc::clone(&txs));
+        assert_eq!(receiver.recv(), Ok(txs));
+    }
+
+    #[test]
+    fn test_new_tip() {
+        let tip = Arc::new(Block::default());
+        let notify = NotifyService::default().start::<&str>(None);
+        let receiver1 = notify.subscribe_new_tip("miner1");
+        let receiver2 = notify.subscribe_new_tip("miner2");
+        notify.notify_new_tip(Arc::clone(&tip));
+        assert_eq!(receiver1.recv(), Ok(Arc::clone(&tip)));
+        assert_eq!(receiver2.recv(), Ok(tip));
+    }
 
     // #[test]
     // fn test_switch_fork() {
@@ -1,19 +1,25 @@
 use crate::protocol_generated::ckb::protocol::{
-    Block as FbsBlock, BlockBuilder, BlockProposalBuilder, BlockTransactionsBuilder,
-    Bytes as FbsBytes, BytesBuilder, CellInput as FbsCellInput, CellInputBuilder,
-    CellOutput as FbsCellOutput, CellOutputBuilder, CompactBlock, CompactBlockBuilder,
-    FilteredBlock, FilteredBlockBuilder, GetBlockProposalBuilder, GetBlockTransactionsBuilder,
-    GetBlocks as FbsGetBlocks, GetBlocksBuilder, GetHeaders as FbsGetHeaders, GetHeadersBuilder,
-    GetRelayTransaction as FbsGetRelayTransaction, GetRelayTransactionBuilder, Header as FbsHeader,
-    HeaderBuilder, Headers as FbsHeaders, HeadersBuilder, IndexTransactionBuilder,
-    MerkleProofBuilder, OutPoint as FbsOutPoint, OutPointBuilder,
+    Block as FbsBlock, BlockBuilder, BlockFilterHeadersBuilder, BlockFiltersBuilder,
+    BlockProposalBuilder, BlockTransactionsBuilder, Bytes as FbsBytes, BytesBuilder,
+    CellInput as FbsCellInput, CellInputBuilder, CellOutput as FbsCellOutput, CellOutputBuilder,
+    CellStatusProofBuilder, CellsStatusBuilder, CompactBlock, CompactBlockBuilder, FilterMessage,
+    FilterMessageBuilder, FilterPayload, FilteredBlock, FilteredBlockBuilder,
+    GetBlockFilterHeadersBuilder, GetBlockFiltersBuilder, GetBlockProposalBuilder,
+    GetBlockTransactionsBuilder, GetBlocks as FbsGetBlocks, GetBlocksBuilder,
+    GetCellsStatusBuilder, GetHeaders as FbsGetHeaders, GetHeadersBuilder, GetLightHeadersBuilder,
+    GetRelayTransaction as FbsGetRelayTransaction, GetRelayTransactionBuilder,
+    GetRelayTransactionsBuilder, GetTransactionProofsBuilder, Header as FbsHeader, HeaderBuilder,
+    Headers as FbsHeaders, HeadersBuilder, IndexTransactionBuilder, LightClientMessage,
+    LightClientMessageBuilder, LightClientPayload, LightHeadersBuilder,
+    MerkleProof as FbsMerkleProof, MerkleProofBuilder, OutPoint as FbsOutPoint, OutPointBuilder,
     ProposalShortId as FbsProposalShortId, RelayMessage, RelayMessageBuilder, RelayPayload,
     RelayTransaction as FbsRelayTransaction, RelayTransactionBuilder,
     RelayTransactionHash as FbsRelayTransactionHash, RelayTransactionHashBuilder,
-    Script as FbsScript, ScriptBuilder, SyncMessage, SyncMessageBuilder, SyncPayload,
-    Time as FbsTime, TimeBuilder, TimeMessage, TimeMessageBuilder, Transaction as FbsTransaction,
-    TransactionBuilder, UncleBlock as FbsUncleBlock, UncleBlockBuilder, Witness as FbsWitness,
-    WitnessBuilder, H256 as FbsH256,
+    RelayTransactionHashesBuilder, RelayTransactionsBuilder, Script as FbsScript, ScriptBuilder,
+    SyncMessage, SyncMessageBuilder, SyncPayload, Time as FbsTime, TimeBuilder, TimeMessage,
+    TimeMessageBuilder, Transaction as FbsTransaction, TransactionBuilder, TransactionProofBuilder,
+    TransactionProofsBuilder, UncleBlock as FbsUncleBlock, UncleBlockBuilder,
+    Witness as FbsWitness, WitnessBuilder, H256 as FbsH256,
 };
 use crate::{short_transaction_id, short_transaction_id_keys};
 use ckb_core::block::Block;
@@ -22,7 +28,7 @@ use ckb_core::script::Script;
 use ckb_core::transaction::{CellInput, CellOutput, OutPoint, ProposalShortId, Transaction};
 use ckb_core::uncle::UncleBlock;
 use ckb_core::Cycle;
-use ckb_merkle_tree::build_merkle_proof;
+use ckb_merkle_tree::{build_merkle_proof, MerkleProof};
 use flatbuffers::{FlatBufferBuilder, WIPOffset};
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
@@ -389,6 +395,26 @@ impl<'a> FbsTime<'a> {
     }
 }
 
+impl<'a> FbsMerkleProof<'a> {
+    pub fn build<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        proof: &MerkleProof,
+    ) -> WIPOffset<FbsMerkleProof<'b>> {
+        let lemmas = fbb.create_vector(
+            &proof
+                .lemmas()
+                .iter()
+                .map(Into::into)
+                .collect::<Vec<FbsH256>>(),
+        );
+        let indices = fbb.create_vector(proof.indices());
+        let mut builder = MerkleProofBuilder::new(fbb);
+        builder.add_lemmas(lemmas);
+        builder.add_indices(indices);
+        builder.finish()
+    }
+}
+
 impl<'a> SyncMessage<'a> {
     pub fn build_get_headers<'b>(
         fbb: &mut FlatBufferBuilder<'b>,
@@ -434,6 +460,22 @@ impl<'a> SyncMessage<'a> {
         builder.finish()
     }
 
+    /// The handshake of the version 2 synchronizers, announcing the capability bits
+    pub fn build_capabilities<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        capabilities: u64,
+    ) -> WIPOffset<SyncMessage<'b>> {
+        let fbs_capabilities = {
+            let mut builder = CapabilitiesBuilder::new(fbb);
+            builder.add_capabilities(capabilities);
+            builder.finish()
+        };
+        let mut builder = SyncMessageBuilder::new(fbb);
+        builder.add_payload_type(SyncPayload::Capabilities);
+        builder.add_payload(fbs_capabilities.as_union_value());
+        builder.finish()
+    }
+
     pub fn build_filtered_block<'b>(
         fbb: &mut FlatBufferBuilder<'b>,
         block: &Block,
@@ -476,15 +518,7 @@ impl<'a> FilteredBlock<'a> {
                 transactions_index,
             );
 
-            let proof = proof.map(|p| {
-                let lemmas =
-                    fbb.create_vector(&p.lemmas().iter().map(Into::into).collect::<Vec<FbsH256>>());
-                let indices = fbb.create_vector(p.indices());
-                let mut builder = MerkleProofBuilder::new(fbb);
-                builder.add_lemmas(lemmas);
-                builder.add_indices(indices);
-                builder.finish()
-            });
+            let proof = proof.map(|p| FbsMerkleProof::build(fbb, &p));
 
             let header = FbsHeader::build(fbb, &block.header());
             let fbs_transactions = fbb.create_vector(&transactions);
@@ -609,6 +643,63 @@ impl<'a> RelayMessage<'a> {
         builder.finish()
     }
 
+    pub fn build_transaction_hashes<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        tx_hashes: &[H256],
+    ) -> WIPOffset<RelayMessage<'b>> {
+        let relay_tx_hashes = {
+            let vec = tx_hashes.iter().map(Into::into).collect::<Vec<FbsH256>>();
+            let tx_hashes = fbb.create_vector(&vec);
+            let mut builder = RelayTransactionHashesBuilder::new(fbb);
+            builder.add_tx_hashes(tx_hashes);
+            builder.finish()
+        };
+
+        let mut builder = RelayMessageBuilder::new(fbb);
+        builder.add_payload_type(RelayPayload::RelayTransactionHashes);
+        builder.add_payload(relay_tx_hashes.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_get_transactions<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        tx_hashes: &[H256],
+    ) -> WIPOffset<RelayMessage<'b>> {
+        let get_txs = {
+            let vec = tx_hashes.iter().map(Into::into).collect::<Vec<FbsH256>>();
+            let tx_hashes = fbb.create_vector(&vec);
+            let mut builder = GetRelayTransactionsBuilder::new(fbb);
+            builder.add_tx_hashes(tx_hashes);
+            builder.finish()
+        };
+
+        let mut builder = RelayMessageBuilder::new(fbb);
+        builder.add_payload_type(RelayPayload::GetRelayTransactions);
+        builder.add_payload(get_txs.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_transactions<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        transactions: &[(Transaction, Cycle)],
+    ) -> WIPOffset<RelayMessage<'b>> {
+        let relay_txs = {
+            let vec = transactions
+                .iter()
+                .map(|(transaction, cycles)| FbsRelayTransaction::build(fbb, transaction, *cycles))
+                .collect::<Vec<_>>();
+            let transactions = fbb.create_vector(&vec);
+            let mut builder = RelayTransactionsBuilder::new(fbb);
+            builder.add_transactions(transactions);
+            builder.finish()
+        };
+
+        let mut builder = RelayMessageBuilder::new(fbb);
+        builder.add_payload_type(RelayPayload::RelayTransactions);
+        builder.add_payload(relay_txs.as_union_value());
+        builder.finish()
+    }
+
     pub fn build_get_block_transactions<'b>(
         fbb: &mut FlatBufferBuilder<'b>,
         block_hash: &H256,
@@ -712,6 +803,256 @@ impl<'a> TimeMessage<'a> {
     }
 }
 
+impl<'a> FilterMessage<'a> {
+    pub fn build_get_block_filters<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        start_number: BlockNumber,
+        stop_hash: &H256,
+    ) -> WIPOffset<FilterMessage<'b>> {
+        let get_block_filters = {
+            let fbs_stop_hash = stop_hash.into();
+            let mut builder = GetBlockFiltersBuilder::new(fbb);
+            builder.add_start_number(start_number);
+            builder.add_stop_hash(&fbs_stop_hash);
+            builder.finish()
+        };
+
+        let mut builder = FilterMessageBuilder::new(fbb);
+        builder.add_payload_type(FilterPayload::GetBlockFilters);
+        builder.add_payload(get_block_filters.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_block_filters<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        stop_hash: &H256,
+        filters: &[(H256, Vec<u8>)],
+    ) -> WIPOffset<FilterMessage<'b>> {
+        let block_filters = {
+            let fbs_stop_hash = stop_hash.into();
+            let vec = filters
+                .iter()
+                .map(|(block_hash, _)| block_hash.into())
+                .collect::<Vec<FbsH256>>();
+            let block_hashes = fbb.create_vector(&vec);
+            let vec = filters
+                .iter()
+                .map(|(_, filter)| FbsBytes::build(fbb, filter))
+                .collect::<Vec<_>>();
+            let filters = fbb.create_vector(&vec);
+
+            let mut builder = BlockFiltersBuilder::new(fbb);
+            builder.add_stop_hash(&fbs_stop_hash);
+            builder.add_block_hashes(block_hashes);
+            builder.add_filters(filters);
+            builder.finish()
+        };
+
+        let mut builder = FilterMessageBuilder::new(fbb);
+        builder.add_payload_type(FilterPayload::BlockFilters);
+        builder.add_payload(block_filters.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_get_block_filter_headers<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        start_number: BlockNumber,
+        stop_hash: &H256,
+    ) -> WIPOffset<FilterMessage<'b>> {
+        let get_block_filter_headers = {
+            let fbs_stop_hash = stop_hash.into();
+            let mut builder = GetBlockFilterHeadersBuilder::new(fbb);
+            builder.add_start_number(start_number);
+            builder.add_stop_hash(&fbs_stop_hash);
+            builder.finish()
+        };
+
+        let mut builder = FilterMessageBuilder::new(fbb);
+        builder.add_payload_type(FilterPayload::GetBlockFilterHeaders);
+        builder.add_payload(get_block_filter_headers.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_block_filter_headers<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        stop_hash: &H256,
+        previous_filter_header: &H256,
+        filter_hashes: &[H256],
+    ) -> WIPOffset<FilterMessage<'b>> {
+        let block_filter_headers = {
+            let fbs_stop_hash = stop_hash.into();
+            let fbs_previous_filter_header = previous_filter_header.into();
+            let vec = filter_hashes
+                .iter()
+                .map(Into::into)
+                .collect::<Vec<FbsH256>>();
+            let filter_hashes = fbb.create_vector(&vec);
+
+            let mut builder = BlockFilterHeadersBuilder::new(fbb);
+            builder.add_stop_hash(&fbs_stop_hash);
+            builder.add_previous_filter_header(&fbs_previous_filter_header);
+            builder.add_filter_hashes(filter_hashes);
+            builder.finish()
+        };
+
+        let mut builder = FilterMessageBuilder::new(fbb);
+        builder.add_payload_type(FilterPayload::BlockFilterHeaders);
+        builder.add_payload(block_filter_headers.as_union_value());
+        builder.finish()
+    }
+}
+
+impl<'a> LightClientMessage<'a> {
+    pub fn build_get_light_headers<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        start_number: BlockNumber,
+        count: u64,
+    ) -> WIPOffset<LightClientMessage<'b>> {
+        let get_light_headers = {
+            let mut builder = GetLightHeadersBuilder::new(fbb);
+            builder.add_start_number(start_number);
+            builder.add_count(count);
+            builder.finish()
+        };
+
+        let mut builder = LightClientMessageBuilder::new(fbb);
+        builder.add_payload_type(LightClientPayload::GetLightHeaders);
+        builder.add_payload(get_light_headers.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_light_headers<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        headers: &[Header],
+    ) -> WIPOffset<LightClientMessage<'b>> {
+        let light_headers = {
+            let vec = headers
+                .iter()
+                .map(|header| FbsHeader::build(fbb, header))
+                .collect::<Vec<_>>();
+            let headers = fbb.create_vector(&vec);
+            let mut builder = LightHeadersBuilder::new(fbb);
+            builder.add_headers(headers);
+            builder.finish()
+        };
+
+        let mut builder = LightClientMessageBuilder::new(fbb);
+        builder.add_payload_type(LightClientPayload::LightHeaders);
+        builder.add_payload(light_headers.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_get_transaction_proofs<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        tx_hashes: &[H256],
+    ) -> WIPOffset<LightClientMessage<'b>> {
+        let get_transaction_proofs = {
+            let vec = tx_hashes.iter().map(Into::into).collect::<Vec<FbsH256>>();
+            let tx_hashes = fbb.create_vector(&vec);
+            let mut builder = GetTransactionProofsBuilder::new(fbb);
+            builder.add_tx_hashes(tx_hashes);
+            builder.finish()
+        };
+
+        let mut builder = LightClientMessageBuilder::new(fbb);
+        builder.add_payload_type(LightClientPayload::GetTransactionProofs);
+        builder.add_payload(get_transaction_proofs.as_union_value());
+        builder.finish()
+    }
+
+    /// `proofs` are the block hash, the proven transaction hashes and their
+    /// inclusion proof of each block
+    pub fn build_transaction_proofs<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        proofs: &[(H256, Vec<H256>, MerkleProof)],
+    ) -> WIPOffset<LightClientMessage<'b>> {
+        let transaction_proofs = {
+            let vec = proofs
+                .iter()
+                .map(|(block_hash, tx_hashes, proof)| {
+                    let fbs_block_hash = block_hash.into();
+                    let vec = tx_hashes.iter().map(Into::into).collect::<Vec<FbsH256>>();
+                    let tx_hashes = fbb.create_vector(&vec);
+                    let proof = FbsMerkleProof::build(fbb, proof);
+                    let mut builder = TransactionProofBuilder::new(fbb);
+                    builder.add_block_hash(&fbs_block_hash);
+                    builder.add_tx_hashes(tx_hashes);
+                    builder.add_proof(proof);
+                    builder.finish()
+                })
+                .collect::<Vec<_>>();
+            let proofs = fbb.create_vector(&vec);
+            let mut builder = TransactionProofsBuilder::new(fbb);
+            builder.add_proofs(proofs);
+            builder.finish()
+        };
+
+        let mut builder = LightClientMessageBuilder::new(fbb);
+        builder.add_payload_type(LightClientPayload::TransactionProofs);
+        builder.add_payload(transaction_proofs.as_union_value());
+        builder.finish()
+    }
+
+    pub fn build_get_cells_status<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        out_points: &[OutPoint],
+    ) -> WIPOffset<LightClientMessage<'b>> {
+        let get_cells_status = {
+            let vec = out_points
+                .iter()
+                .map(|out_point| FbsOutPoint::build(fbb, out_point))
+                .collect::<Vec<_>>();
+            let out_points = fbb.create_vector(&vec);
+            let mut builder = GetCellsStatusBuilder::new(fbb);
+            builder.add_out_points(out_points);
+            builder.finish()
+        };
+
+        let mut builder = LightClientMessageBuilder::new(fbb);
+        builder.add_payload_type(LightClientPayload::GetCellsStatus);
+        builder.add_payload(get_cells_status.as_union_value());
+        builder.finish()
+    }
+
+    /// `cells` are the status of each requested cell, along with the hash of
+    /// the block committing its transaction and the inclusion proof of the
+    /// transaction when it is known
+    pub fn build_cells_status<'b>(
+        fbb: &mut FlatBufferBuilder<'b>,
+        cells: &[(u8, Option<(H256, MerkleProof)>)],
+    ) -> WIPOffset<LightClientMessage<'b>> {
+        let cells_status = {
+            let vec = cells
+                .iter()
+                .map(|(status, proof)| {
+                    let block_hash = proof.as_ref().map(|(block_hash, _)| block_hash.into());
+                    let proof = proof
+                        .as_ref()
+                        .map(|(_, proof)| FbsMerkleProof::build(fbb, proof));
+                    let mut builder = CellStatusProofBuilder::new(fbb);
+                    builder.add_status(*status);
+                    if let Some(ref block_hash) = block_hash {
+                        builder.add_block_hash(block_hash);
+                    }
+                    if let Some(proof) = proof {
+                        builder.add_proof(proof);
+                    }
+                    builder.finish()
+                })
+                .collect::<Vec<_>>();
+            let cells = fbb.create_vector(&vec);
+            let mut builder = CellsStatusBuilder::new(fbb);
+            builder.add_cells(cells);
+            builder.finish()
+        };
+
+        let mut builder = LightClientMessageBuilder::new(fbb);
+        builder.add_payload_type(LightClientPayload::CellsStatus);
+        builder.add_payload(cells_status.as_union_value());
+        builder.finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
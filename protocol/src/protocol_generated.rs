@@ -33,11 +33,12 @@ pub enum SyncPayload {
   AddFilter = 6,
   ClearFilter = 7,
   FilteredBlock = 8,
+  Capabilities = 9,
 
 }
 
 const ENUM_MIN_SYNC_PAYLOAD: u8 = 0;
-const ENUM_MAX_SYNC_PAYLOAD: u8 = 8;
+const ENUM_MAX_SYNC_PAYLOAD: u8 = 9;
 
 impl<'a> flatbuffers::Follow<'a> for SyncPayload {
   type Inner = Self;
@@ -71,7 +72,7 @@ impl flatbuffers::Push for SyncPayload {
 }
 
 #[allow(non_camel_case_types)]
-const ENUM_VALUES_SYNC_PAYLOAD:[SyncPayload; 9] = [
+const ENUM_VALUES_SYNC_PAYLOAD:[SyncPayload; 10] = [
   SyncPayload::NONE,
   SyncPayload::GetHeaders,
   SyncPayload::Headers,
@@ -80,11 +81,12 @@ const ENUM_VALUES_SYNC_PAYLOAD:[SyncPayload; 9] = [
   SyncPayload::SetFilter,
   SyncPayload::AddFilter,
   SyncPayload::ClearFilter,
-  SyncPayload::FilteredBlock
+  SyncPayload::FilteredBlock,
+  SyncPayload::Capabilities
 ];
 
 #[allow(non_camel_case_types)]
-const ENUM_NAMES_SYNC_PAYLOAD:[&'static str; 9] = [
+const ENUM_NAMES_SYNC_PAYLOAD:[&'static str; 10] = [
     "NONE",
     "GetHeaders",
     "Headers",
@@ -93,7 +95,8 @@ const ENUM_NAMES_SYNC_PAYLOAD:[&'static str; 9] = [
     "SetFilter",
     "AddFilter",
     "ClearFilter",
-    "FilteredBlock"
+    "FilteredBlock",
+    "Capabilities"
 ];
 
 pub fn enum_name_sync_payload(e: SyncPayload) -> &'static str {
@@ -115,11 +118,14 @@ pub enum RelayPayload {
   BlockTransactions = 6,
   GetBlockProposal = 7,
   BlockProposal = 8,
+  RelayTransactionHashes = 9,
+  GetRelayTransactions = 10,
+  RelayTransactions = 11,
 
 }
 
 const ENUM_MIN_RELAY_PAYLOAD: u8 = 0;
-const ENUM_MAX_RELAY_PAYLOAD: u8 = 8;
+const ENUM_MAX_RELAY_PAYLOAD: u8 = 11;
 
 impl<'a> flatbuffers::Follow<'a> for RelayPayload {
   type Inner = Self;
@@ -153,7 +159,7 @@ impl flatbuffers::Push for RelayPayload {
 }
 
 #[allow(non_camel_case_types)]
-const ENUM_VALUES_RELAY_PAYLOAD:[RelayPayload; 9] = [
+const ENUM_VALUES_RELAY_PAYLOAD:[RelayPayload; 12] = [
   RelayPayload::NONE,
   RelayPayload::CompactBlock,
   RelayPayload::RelayTransaction,
@@ -162,11 +168,14 @@ const ENUM_VALUES_RELAY_PAYLOAD:[RelayPayload; 9] = [
   RelayPayload::GetBlockTransactions,
   RelayPayload::BlockTransactions,
   RelayPayload::GetBlockProposal,
-  RelayPayload::BlockProposal
+  RelayPayload::BlockProposal,
+  RelayPayload::RelayTransactionHashes,
+  RelayPayload::GetRelayTransactions,
+  RelayPayload::RelayTransactions
 ];
 
 #[allow(non_camel_case_types)]
-const ENUM_NAMES_RELAY_PAYLOAD:[&'static str; 9] = [
+const ENUM_NAMES_RELAY_PAYLOAD:[&'static str; 12] = [
     "NONE",
     "CompactBlock",
     "RelayTransaction",
@@ -175,7 +184,10 @@ const ENUM_NAMES_RELAY_PAYLOAD:[&'static str; 9] = [
     "GetBlockTransactions",
     "BlockTransactions",
     "GetBlockProposal",
-    "BlockProposal"
+    "BlockProposal",
+    "RelayTransactionHashes",
+    "GetRelayTransactions",
+    "RelayTransactions"
 ];
 
 pub fn enum_name_relay_payload(e: RelayPayload) -> &'static str {
@@ -184,6 +196,152 @@ pub fn enum_name_relay_payload(e: RelayPayload) -> &'static str {
 }
 
 pub struct RelayPayloadUnionTableOffset {}
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FilterPayload {
+  NONE = 0,
+  GetBlockFilters = 1,
+  BlockFilters = 2,
+  GetBlockFilterHeaders = 3,
+  BlockFilterHeaders = 4,
+
+}
+
+const ENUM_MIN_FILTER_PAYLOAD: u8 = 0;
+const ENUM_MAX_FILTER_PAYLOAD: u8 = 4;
+
+impl<'a> flatbuffers::Follow<'a> for FilterPayload {
+  type Inner = Self;
+  #[inline]
+  fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::read_scalar_at::<Self>(buf, loc)
+  }
+}
+
+impl flatbuffers::EndianScalar for FilterPayload {
+  #[inline]
+  fn to_little_endian(self) -> Self {
+    let n = u8::to_le(self as u8);
+    let p = &n as *const u8 as *const FilterPayload;
+    unsafe { *p }
+  }
+  #[inline]
+  fn from_little_endian(self) -> Self {
+    let n = u8::from_le(self as u8);
+    let p = &n as *const u8 as *const FilterPayload;
+    unsafe { *p }
+  }
+}
+
+impl flatbuffers::Push for FilterPayload {
+    type Output = FilterPayload;
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        flatbuffers::emplace_scalar::<FilterPayload>(dst, *self);
+    }
+}
+
+#[allow(non_camel_case_types)]
+const ENUM_VALUES_FILTER_PAYLOAD:[FilterPayload; 5] = [
+  FilterPayload::NONE,
+  FilterPayload::GetBlockFilters,
+  FilterPayload::BlockFilters,
+  FilterPayload::GetBlockFilterHeaders,
+  FilterPayload::BlockFilterHeaders
+];
+
+#[allow(non_camel_case_types)]
+const ENUM_NAMES_FILTER_PAYLOAD:[&'static str; 5] = [
+    "NONE",
+    "GetBlockFilters",
+    "BlockFilters",
+    "GetBlockFilterHeaders",
+    "BlockFilterHeaders"
+];
+
+pub fn enum_name_filter_payload(e: FilterPayload) -> &'static str {
+  let index: usize = e as usize;
+  ENUM_NAMES_FILTER_PAYLOAD[index]
+}
+
+pub struct FilterPayloadUnionTableOffset {}
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LightClientPayload {
+  NONE = 0,
+  GetLightHeaders = 1,
+  LightHeaders = 2,
+  GetTransactionProofs = 3,
+  TransactionProofs = 4,
+  GetCellsStatus = 5,
+  CellsStatus = 6,
+
+}
+
+const ENUM_MIN_LIGHT_CLIENT_PAYLOAD: u8 = 0;
+const ENUM_MAX_LIGHT_CLIENT_PAYLOAD: u8 = 6;
+
+impl<'a> flatbuffers::Follow<'a> for LightClientPayload {
+  type Inner = Self;
+  #[inline]
+  fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::read_scalar_at::<Self>(buf, loc)
+  }
+}
+
+impl flatbuffers::EndianScalar for LightClientPayload {
+  #[inline]
+  fn to_little_endian(self) -> Self {
+    let n = u8::to_le(self as u8);
+    let p = &n as *const u8 as *const LightClientPayload;
+    unsafe { *p }
+  }
+  #[inline]
+  fn from_little_endian(self) -> Self {
+    let n = u8::from_le(self as u8);
+    let p = &n as *const u8 as *const LightClientPayload;
+    unsafe { *p }
+  }
+}
+
+impl flatbuffers::Push for LightClientPayload {
+    type Output = LightClientPayload;
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        flatbuffers::emplace_scalar::<LightClientPayload>(dst, *self);
+    }
+}
+
+#[allow(non_camel_case_types)]
+const ENUM_VALUES_LIGHT_CLIENT_PAYLOAD:[LightClientPayload; 7] = [
+  LightClientPayload::NONE,
+  LightClientPayload::GetLightHeaders,
+  LightClientPayload::LightHeaders,
+  LightClientPayload::GetTransactionProofs,
+  LightClientPayload::TransactionProofs,
+  LightClientPayload::GetCellsStatus,
+  LightClientPayload::CellsStatus
+];
+
+#[allow(non_camel_case_types)]
+const ENUM_NAMES_LIGHT_CLIENT_PAYLOAD:[&'static str; 7] = [
+    "NONE",
+    "GetLightHeaders",
+    "LightHeaders",
+    "GetTransactionProofs",
+    "TransactionProofs",
+    "GetCellsStatus",
+    "CellsStatus"
+];
+
+pub fn enum_name_light_client_payload(e: LightClientPayload) -> &'static str {
+  let index: usize = e as usize;
+  ENUM_NAMES_LIGHT_CLIENT_PAYLOAD[index]
+}
+
+pub struct LightClientPayloadUnionTableOffset {}
 // struct ProposalShortId, aligned to 1
 #[repr(C, align(1))]
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -621,6 +779,16 @@ impl<'a> SyncMessage<'a> {
     }
   }
 
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_capabilities(&'a self) -> Option<Capabilities> {
+    if self.payload_type() == SyncPayload::Capabilities {
+      self.payload().map(|u| Capabilities::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
 }
 
 pub struct SyncMessageArgs {
@@ -992,6 +1160,82 @@ impl<'a: 'b, 'b> HeadersBuilder<'a, 'b> {
   }
 }
 
+pub enum CapabilitiesOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct Capabilities<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for Capabilities<'a> {
+    type Inner = Capabilities<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> Capabilities<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        Capabilities {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args CapabilitiesArgs<'args>) -> flatbuffers::WIPOffset<Capabilities<'bldr>> {
+      let mut builder = CapabilitiesBuilder::new(_fbb);
+      builder.add_capabilities(args.capabilities);
+      builder.finish()
+    }
+
+    pub const VT_CAPABILITIES: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn capabilities(&self) -> u64 {
+    self._tab.get::<u64>(Capabilities::VT_CAPABILITIES, Some(0)).unwrap()
+  }
+}
+
+pub struct CapabilitiesArgs<'a> {
+    pub capabilities: u64,
+}
+impl<'a> Default for CapabilitiesArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        CapabilitiesArgs {
+            capabilities: 0,
+        }
+    }
+}
+pub struct CapabilitiesBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> CapabilitiesBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_capabilities(&mut self, capabilities: u64) {
+    self.fbb_.push_slot::<u64>(Capabilities::VT_CAPABILITIES, capabilities, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> CapabilitiesBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    CapabilitiesBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<Capabilities<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
 pub enum HeaderOffset {}
 #[derive(Copy, Clone, Debug, PartialEq)]
 
@@ -2161,6 +2405,36 @@ impl<'a> RelayMessage<'a> {
     }
   }
 
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_relay_transaction_hashes(&'a self) -> Option<RelayTransactionHashes> {
+    if self.payload_type() == RelayPayload::RelayTransactionHashes {
+      self.payload().map(|u| RelayTransactionHashes::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_get_relay_transactions(&'a self) -> Option<GetRelayTransactions> {
+    if self.payload_type() == RelayPayload::GetRelayTransactions {
+      self.payload().map(|u| GetRelayTransactions::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_relay_transactions(&'a self) -> Option<RelayTransactions> {
+    if self.payload_type() == RelayPayload::RelayTransactions {
+      self.payload().map(|u| RelayTransactions::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
 }
 
 pub struct RelayMessageArgs {
@@ -2668,15 +2942,15 @@ impl<'a: 'b, 'b> RelayTransactionBuilder<'a, 'b> {
   }
 }
 
-pub enum GetBlockTransactionsOffset {}
+pub enum RelayTransactionHashesOffset {}
 #[derive(Copy, Clone, Debug, PartialEq)]
 
-pub struct GetBlockTransactions<'a> {
+pub struct RelayTransactionHashes<'a> {
   pub _tab: flatbuffers::Table<'a>,
 }
 
-impl<'a> flatbuffers::Follow<'a> for GetBlockTransactions<'a> {
-    type Inner = GetBlockTransactions<'a>;
+impl<'a> flatbuffers::Follow<'a> for RelayTransactionHashes<'a> {
+    type Inner = RelayTransactionHashes<'a>;
     #[inline]
     fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
         Self {
@@ -2685,86 +2959,74 @@ impl<'a> flatbuffers::Follow<'a> for GetBlockTransactions<'a> {
     }
 }
 
-impl<'a> GetBlockTransactions<'a> {
+impl<'a> RelayTransactionHashes<'a> {
     #[inline]
     pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
-        GetBlockTransactions {
+        RelayTransactionHashes {
             _tab: table,
         }
     }
     #[allow(unused_mut)]
     pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
         _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
-        args: &'args GetBlockTransactionsArgs<'args>) -> flatbuffers::WIPOffset<GetBlockTransactions<'bldr>> {
-      let mut builder = GetBlockTransactionsBuilder::new(_fbb);
-      if let Some(x) = args.indexes { builder.add_indexes(x); }
-      if let Some(x) = args.block_hash { builder.add_block_hash(x); }
+        args: &'args RelayTransactionHashesArgs<'args>) -> flatbuffers::WIPOffset<RelayTransactionHashes<'bldr>> {
+      let mut builder = RelayTransactionHashesBuilder::new(_fbb);
+      if let Some(x) = args.tx_hashes { builder.add_tx_hashes(x); }
       builder.finish()
     }
 
-    pub const VT_BLOCK_HASH: flatbuffers::VOffsetT = 4;
-    pub const VT_INDEXES: flatbuffers::VOffsetT = 6;
+    pub const VT_TX_HASHES: flatbuffers::VOffsetT = 4;
 
   #[inline]
-  pub fn block_hash(&self) -> Option<&'a H256> {
-    self._tab.get::<H256>(GetBlockTransactions::VT_BLOCK_HASH, None)
-  }
-  #[inline]
-  pub fn indexes(&self) -> Option<flatbuffers::Vector<'a, u32>> {
-    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(GetBlockTransactions::VT_INDEXES, None)
+  pub fn tx_hashes(&self) -> Option<&'a [H256]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<H256>>>(RelayTransactionHashes::VT_TX_HASHES, None).map(|v| v.safe_slice() )
   }
 }
 
-pub struct GetBlockTransactionsArgs<'a> {
-    pub block_hash: Option<&'a  H256>,
-    pub indexes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a ,  u32>>>,
+pub struct RelayTransactionHashesArgs<'a> {
+    pub tx_hashes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , H256>>>,
 }
-impl<'a> Default for GetBlockTransactionsArgs<'a> {
+impl<'a> Default for RelayTransactionHashesArgs<'a> {
     #[inline]
     fn default() -> Self {
-        GetBlockTransactionsArgs {
-            block_hash: None,
-            indexes: None,
+        RelayTransactionHashesArgs {
+            tx_hashes: None,
         }
     }
 }
-pub struct GetBlockTransactionsBuilder<'a: 'b, 'b> {
+pub struct RelayTransactionHashesBuilder<'a: 'b, 'b> {
   fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
   start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
 }
-impl<'a: 'b, 'b> GetBlockTransactionsBuilder<'a, 'b> {
-  #[inline]
-  pub fn add_block_hash(&mut self, block_hash: &'b  H256) {
-    self.fbb_.push_slot_always::<&H256>(GetBlockTransactions::VT_BLOCK_HASH, block_hash);
-  }
+impl<'a: 'b, 'b> RelayTransactionHashesBuilder<'a, 'b> {
   #[inline]
-  pub fn add_indexes(&mut self, indexes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
-    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GetBlockTransactions::VT_INDEXES, indexes);
+  pub fn add_tx_hashes(&mut self, tx_hashes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , H256>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(RelayTransactionHashes::VT_TX_HASHES, tx_hashes);
   }
   #[inline]
-  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetBlockTransactionsBuilder<'a, 'b> {
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> RelayTransactionHashesBuilder<'a, 'b> {
     let start = _fbb.start_table();
-    GetBlockTransactionsBuilder {
+    RelayTransactionHashesBuilder {
       fbb_: _fbb,
       start_: start,
     }
   }
   #[inline]
-  pub fn finish(self) -> flatbuffers::WIPOffset<GetBlockTransactions<'a>> {
+  pub fn finish(self) -> flatbuffers::WIPOffset<RelayTransactionHashes<'a>> {
     let o = self.fbb_.end_table(self.start_);
     flatbuffers::WIPOffset::new(o.value())
   }
 }
 
-pub enum BlockTransactionsOffset {}
+pub enum GetRelayTransactionsOffset {}
 #[derive(Copy, Clone, Debug, PartialEq)]
 
-pub struct BlockTransactions<'a> {
+pub struct GetRelayTransactions<'a> {
   pub _tab: flatbuffers::Table<'a>,
 }
 
-impl<'a> flatbuffers::Follow<'a> for BlockTransactions<'a> {
-    type Inner = BlockTransactions<'a>;
+impl<'a> flatbuffers::Follow<'a> for GetRelayTransactions<'a> {
+    type Inner = GetRelayTransactions<'a>;
     #[inline]
     fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
         Self {
@@ -2773,86 +3035,74 @@ impl<'a> flatbuffers::Follow<'a> for BlockTransactions<'a> {
     }
 }
 
-impl<'a> BlockTransactions<'a> {
+impl<'a> GetRelayTransactions<'a> {
     #[inline]
     pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
-        BlockTransactions {
+        GetRelayTransactions {
             _tab: table,
         }
     }
     #[allow(unused_mut)]
     pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
         _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
-        args: &'args BlockTransactionsArgs<'args>) -> flatbuffers::WIPOffset<BlockTransactions<'bldr>> {
-      let mut builder = BlockTransactionsBuilder::new(_fbb);
-      if let Some(x) = args.transactions { builder.add_transactions(x); }
-      if let Some(x) = args.block_hash { builder.add_block_hash(x); }
+        args: &'args GetRelayTransactionsArgs<'args>) -> flatbuffers::WIPOffset<GetRelayTransactions<'bldr>> {
+      let mut builder = GetRelayTransactionsBuilder::new(_fbb);
+      if let Some(x) = args.tx_hashes { builder.add_tx_hashes(x); }
       builder.finish()
     }
 
-    pub const VT_BLOCK_HASH: flatbuffers::VOffsetT = 4;
-    pub const VT_TRANSACTIONS: flatbuffers::VOffsetT = 6;
+    pub const VT_TX_HASHES: flatbuffers::VOffsetT = 4;
 
   #[inline]
-  pub fn block_hash(&self) -> Option<&'a H256> {
-    self._tab.get::<H256>(BlockTransactions::VT_BLOCK_HASH, None)
-  }
-  #[inline]
-  pub fn transactions(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Transaction<'a>>>> {
-    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Transaction<'a>>>>>(BlockTransactions::VT_TRANSACTIONS, None)
+  pub fn tx_hashes(&self) -> Option<&'a [H256]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<H256>>>(GetRelayTransactions::VT_TX_HASHES, None).map(|v| v.safe_slice() )
   }
 }
 
-pub struct BlockTransactionsArgs<'a> {
-    pub block_hash: Option<&'a  H256>,
-    pub transactions: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<Transaction<'a >>>>>,
+pub struct GetRelayTransactionsArgs<'a> {
+    pub tx_hashes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , H256>>>,
 }
-impl<'a> Default for BlockTransactionsArgs<'a> {
+impl<'a> Default for GetRelayTransactionsArgs<'a> {
     #[inline]
     fn default() -> Self {
-        BlockTransactionsArgs {
-            block_hash: None,
-            transactions: None,
+        GetRelayTransactionsArgs {
+            tx_hashes: None,
         }
     }
 }
-pub struct BlockTransactionsBuilder<'a: 'b, 'b> {
+pub struct GetRelayTransactionsBuilder<'a: 'b, 'b> {
   fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
   start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
 }
-impl<'a: 'b, 'b> BlockTransactionsBuilder<'a, 'b> {
-  #[inline]
-  pub fn add_block_hash(&mut self, block_hash: &'b  H256) {
-    self.fbb_.push_slot_always::<&H256>(BlockTransactions::VT_BLOCK_HASH, block_hash);
-  }
+impl<'a: 'b, 'b> GetRelayTransactionsBuilder<'a, 'b> {
   #[inline]
-  pub fn add_transactions(&mut self, transactions: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<Transaction<'b >>>>) {
-    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BlockTransactions::VT_TRANSACTIONS, transactions);
+  pub fn add_tx_hashes(&mut self, tx_hashes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , H256>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GetRelayTransactions::VT_TX_HASHES, tx_hashes);
   }
   #[inline]
-  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> BlockTransactionsBuilder<'a, 'b> {
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetRelayTransactionsBuilder<'a, 'b> {
     let start = _fbb.start_table();
-    BlockTransactionsBuilder {
+    GetRelayTransactionsBuilder {
       fbb_: _fbb,
       start_: start,
     }
   }
   #[inline]
-  pub fn finish(self) -> flatbuffers::WIPOffset<BlockTransactions<'a>> {
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetRelayTransactions<'a>> {
     let o = self.fbb_.end_table(self.start_);
     flatbuffers::WIPOffset::new(o.value())
   }
 }
 
-pub enum GetBlockProposalOffset {}
+pub enum RelayTransactionsOffset {}
 #[derive(Copy, Clone, Debug, PartialEq)]
 
-pub struct GetBlockProposal<'a> {
+pub struct RelayTransactions<'a> {
   pub _tab: flatbuffers::Table<'a>,
 }
 
-impl<'a> flatbuffers::Follow<'a> for GetBlockProposal<'a> {
-    type Inner = GetBlockProposal<'a>;
+impl<'a> flatbuffers::Follow<'a> for RelayTransactions<'a> {
+    type Inner = RelayTransactions<'a>;
     #[inline]
     fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
         Self {
@@ -2861,33 +3111,285 @@ impl<'a> flatbuffers::Follow<'a> for GetBlockProposal<'a> {
     }
 }
 
-impl<'a> GetBlockProposal<'a> {
+impl<'a> RelayTransactions<'a> {
     #[inline]
     pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
-        GetBlockProposal {
+        RelayTransactions {
             _tab: table,
         }
     }
     #[allow(unused_mut)]
     pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
         _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
-        args: &'args GetBlockProposalArgs<'args>) -> flatbuffers::WIPOffset<GetBlockProposal<'bldr>> {
-      let mut builder = GetBlockProposalBuilder::new(_fbb);
-      builder.add_block_number(args.block_number);
-      if let Some(x) = args.proposals { builder.add_proposals(x); }
+        args: &'args RelayTransactionsArgs<'args>) -> flatbuffers::WIPOffset<RelayTransactions<'bldr>> {
+      let mut builder = RelayTransactionsBuilder::new(_fbb);
+      if let Some(x) = args.transactions { builder.add_transactions(x); }
       builder.finish()
     }
 
-    pub const VT_BLOCK_NUMBER: flatbuffers::VOffsetT = 4;
-    pub const VT_PROPOSALS: flatbuffers::VOffsetT = 6;
+    pub const VT_TRANSACTIONS: flatbuffers::VOffsetT = 4;
 
   #[inline]
-  pub fn block_number(&self) -> u64 {
-    self._tab.get::<u64>(GetBlockProposal::VT_BLOCK_NUMBER, Some(0)).unwrap()
-  }
-  #[inline]
-  pub fn proposals(&self) -> Option<&'a [ProposalShortId]> {
-    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<ProposalShortId>>>(GetBlockProposal::VT_PROPOSALS, None).map(|v| v.safe_slice() )
+  pub fn transactions(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<RelayTransaction<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<RelayTransaction<'a>>>>>(RelayTransactions::VT_TRANSACTIONS, None)
+  }
+}
+
+pub struct RelayTransactionsArgs<'a> {
+    pub transactions: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<RelayTransaction<'a >>>>>,
+}
+impl<'a> Default for RelayTransactionsArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        RelayTransactionsArgs {
+            transactions: None,
+        }
+    }
+}
+pub struct RelayTransactionsBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> RelayTransactionsBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_transactions(&mut self, transactions: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<RelayTransaction<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(RelayTransactions::VT_TRANSACTIONS, transactions);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> RelayTransactionsBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    RelayTransactionsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<RelayTransactions<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetBlockTransactionsOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetBlockTransactions<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetBlockTransactions<'a> {
+    type Inner = GetBlockTransactions<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetBlockTransactions<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetBlockTransactions {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetBlockTransactionsArgs<'args>) -> flatbuffers::WIPOffset<GetBlockTransactions<'bldr>> {
+      let mut builder = GetBlockTransactionsBuilder::new(_fbb);
+      if let Some(x) = args.indexes { builder.add_indexes(x); }
+      if let Some(x) = args.block_hash { builder.add_block_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_BLOCK_HASH: flatbuffers::VOffsetT = 4;
+    pub const VT_INDEXES: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn block_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(GetBlockTransactions::VT_BLOCK_HASH, None)
+  }
+  #[inline]
+  pub fn indexes(&self) -> Option<flatbuffers::Vector<'a, u32>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u32>>>(GetBlockTransactions::VT_INDEXES, None)
+  }
+}
+
+pub struct GetBlockTransactionsArgs<'a> {
+    pub block_hash: Option<&'a  H256>,
+    pub indexes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a ,  u32>>>,
+}
+impl<'a> Default for GetBlockTransactionsArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        GetBlockTransactionsArgs {
+            block_hash: None,
+            indexes: None,
+        }
+    }
+}
+pub struct GetBlockTransactionsBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> GetBlockTransactionsBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_block_hash(&mut self, block_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(GetBlockTransactions::VT_BLOCK_HASH, block_hash);
+  }
+  #[inline]
+  pub fn add_indexes(&mut self, indexes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u32>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GetBlockTransactions::VT_INDEXES, indexes);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetBlockTransactionsBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    GetBlockTransactionsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetBlockTransactions<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum BlockTransactionsOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct BlockTransactions<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for BlockTransactions<'a> {
+    type Inner = BlockTransactions<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> BlockTransactions<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        BlockTransactions {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args BlockTransactionsArgs<'args>) -> flatbuffers::WIPOffset<BlockTransactions<'bldr>> {
+      let mut builder = BlockTransactionsBuilder::new(_fbb);
+      if let Some(x) = args.transactions { builder.add_transactions(x); }
+      if let Some(x) = args.block_hash { builder.add_block_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_BLOCK_HASH: flatbuffers::VOffsetT = 4;
+    pub const VT_TRANSACTIONS: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn block_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(BlockTransactions::VT_BLOCK_HASH, None)
+  }
+  #[inline]
+  pub fn transactions(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Transaction<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Transaction<'a>>>>>(BlockTransactions::VT_TRANSACTIONS, None)
+  }
+}
+
+pub struct BlockTransactionsArgs<'a> {
+    pub block_hash: Option<&'a  H256>,
+    pub transactions: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<Transaction<'a >>>>>,
+}
+impl<'a> Default for BlockTransactionsArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        BlockTransactionsArgs {
+            block_hash: None,
+            transactions: None,
+        }
+    }
+}
+pub struct BlockTransactionsBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> BlockTransactionsBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_block_hash(&mut self, block_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(BlockTransactions::VT_BLOCK_HASH, block_hash);
+  }
+  #[inline]
+  pub fn add_transactions(&mut self, transactions: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<Transaction<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BlockTransactions::VT_TRANSACTIONS, transactions);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> BlockTransactionsBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    BlockTransactionsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<BlockTransactions<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetBlockProposalOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetBlockProposal<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetBlockProposal<'a> {
+    type Inner = GetBlockProposal<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetBlockProposal<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetBlockProposal {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetBlockProposalArgs<'args>) -> flatbuffers::WIPOffset<GetBlockProposal<'bldr>> {
+      let mut builder = GetBlockProposalBuilder::new(_fbb);
+      builder.add_block_number(args.block_number);
+      if let Some(x) = args.proposals { builder.add_proposals(x); }
+      builder.finish()
+    }
+
+    pub const VT_BLOCK_NUMBER: flatbuffers::VOffsetT = 4;
+    pub const VT_PROPOSALS: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn block_number(&self) -> u64 {
+    self._tab.get::<u64>(GetBlockProposal::VT_BLOCK_NUMBER, Some(0)).unwrap()
+  }
+  #[inline]
+  pub fn proposals(&self) -> Option<&'a [ProposalShortId]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<ProposalShortId>>>(GetBlockProposal::VT_PROPOSALS, None).map(|v| v.safe_slice() )
   }
 }
 
@@ -3587,6 +4089,1326 @@ impl<'a: 'b, 'b> TimeBuilder<'a, 'b> {
   }
 }
 
+pub enum FilterMessageOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct FilterMessage<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for FilterMessage<'a> {
+    type Inner = FilterMessage<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> FilterMessage<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        FilterMessage {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args FilterMessageArgs) -> flatbuffers::WIPOffset<FilterMessage<'bldr>> {
+      let mut builder = FilterMessageBuilder::new(_fbb);
+      if let Some(x) = args.payload { builder.add_payload(x); }
+      builder.add_payload_type(args.payload_type);
+      builder.finish()
+    }
+
+    pub const VT_PAYLOAD_TYPE: flatbuffers::VOffsetT = 4;
+    pub const VT_PAYLOAD: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn payload_type(&self) -> FilterPayload {
+    self._tab.get::<FilterPayload>(FilterMessage::VT_PAYLOAD_TYPE, Some(FilterPayload::NONE)).unwrap()
+  }
+  #[inline]
+  pub fn payload(&self) -> Option<flatbuffers::Table<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Table<'a>>>(FilterMessage::VT_PAYLOAD, None)
+  }
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_get_block_filters(&'a self) -> Option<GetBlockFilters> {
+    if self.payload_type() == FilterPayload::GetBlockFilters {
+      self.payload().map(|u| GetBlockFilters::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_block_filters(&'a self) -> Option<BlockFilters> {
+    if self.payload_type() == FilterPayload::BlockFilters {
+      self.payload().map(|u| BlockFilters::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_get_block_filter_headers(&'a self) -> Option<GetBlockFilterHeaders> {
+    if self.payload_type() == FilterPayload::GetBlockFilterHeaders {
+      self.payload().map(|u| GetBlockFilterHeaders::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_block_filter_headers(&'a self) -> Option<BlockFilterHeaders> {
+    if self.payload_type() == FilterPayload::BlockFilterHeaders {
+      self.payload().map(|u| BlockFilterHeaders::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+}
+
+pub struct FilterMessageArgs {
+    pub payload_type: FilterPayload,
+    pub payload: Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>,
+}
+impl<'a> Default for FilterMessageArgs {
+    #[inline]
+    fn default() -> Self {
+        FilterMessageArgs {
+            payload_type: FilterPayload::NONE,
+            payload: None,
+        }
+    }
+}
+pub struct FilterMessageBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> FilterMessageBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_payload_type(&mut self, payload_type: FilterPayload) {
+    self.fbb_.push_slot::<FilterPayload>(FilterMessage::VT_PAYLOAD_TYPE, payload_type, FilterPayload::NONE);
+  }
+  #[inline]
+  pub fn add_payload(&mut self, payload: flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(FilterMessage::VT_PAYLOAD, payload);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> FilterMessageBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    FilterMessageBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<FilterMessage<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetBlockFiltersOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetBlockFilters<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetBlockFilters<'a> {
+    type Inner = GetBlockFilters<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetBlockFilters<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetBlockFilters {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetBlockFiltersArgs<'args>) -> flatbuffers::WIPOffset<GetBlockFilters<'bldr>> {
+      let mut builder = GetBlockFiltersBuilder::new(_fbb);
+      builder.add_start_number(args.start_number);
+      if let Some(x) = args.stop_hash { builder.add_stop_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_START_NUMBER: flatbuffers::VOffsetT = 4;
+    pub const VT_STOP_HASH: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn start_number(&self) -> u64 {
+    self._tab.get::<u64>(GetBlockFilters::VT_START_NUMBER, Some(0)).unwrap()
+  }
+  #[inline]
+  pub fn stop_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(GetBlockFilters::VT_STOP_HASH, None)
+  }
+}
+
+pub struct GetBlockFiltersArgs<'a> {
+    pub start_number: u64,
+    pub stop_hash: Option<&'a  H256>,
+}
+impl<'a> Default for GetBlockFiltersArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        GetBlockFiltersArgs {
+            start_number: 0,
+            stop_hash: None,
+        }
+    }
+}
+pub struct GetBlockFiltersBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> GetBlockFiltersBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_start_number(&mut self, start_number: u64) {
+    self.fbb_.push_slot::<u64>(GetBlockFilters::VT_START_NUMBER, start_number, 0);
+  }
+  #[inline]
+  pub fn add_stop_hash(&mut self, stop_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(GetBlockFilters::VT_STOP_HASH, stop_hash);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetBlockFiltersBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    GetBlockFiltersBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetBlockFilters<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum BlockFiltersOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct BlockFilters<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for BlockFilters<'a> {
+    type Inner = BlockFilters<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> BlockFilters<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        BlockFilters {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args BlockFiltersArgs<'args>) -> flatbuffers::WIPOffset<BlockFilters<'bldr>> {
+      let mut builder = BlockFiltersBuilder::new(_fbb);
+      if let Some(x) = args.filters { builder.add_filters(x); }
+      if let Some(x) = args.block_hashes { builder.add_block_hashes(x); }
+      if let Some(x) = args.stop_hash { builder.add_stop_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_STOP_HASH: flatbuffers::VOffsetT = 4;
+    pub const VT_BLOCK_HASHES: flatbuffers::VOffsetT = 6;
+    pub const VT_FILTERS: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn stop_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(BlockFilters::VT_STOP_HASH, None)
+  }
+  #[inline]
+  pub fn block_hashes(&self) -> Option<&'a [H256]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<H256>>>(BlockFilters::VT_BLOCK_HASHES, None).map(|v| v.safe_slice() )
+  }
+  #[inline]
+  pub fn filters(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Bytes<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Bytes<'a>>>>>(BlockFilters::VT_FILTERS, None)
+  }
+}
+
+pub struct BlockFiltersArgs<'a> {
+    pub stop_hash: Option<&'a  H256>,
+    pub block_hashes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , H256>>>,
+    pub filters: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<Bytes<'a >>>>>,
+}
+impl<'a> Default for BlockFiltersArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        BlockFiltersArgs {
+            stop_hash: None,
+            block_hashes: None,
+            filters: None,
+        }
+    }
+}
+pub struct BlockFiltersBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> BlockFiltersBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_stop_hash(&mut self, stop_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(BlockFilters::VT_STOP_HASH, stop_hash);
+  }
+  #[inline]
+  pub fn add_block_hashes(&mut self, block_hashes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , H256>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BlockFilters::VT_BLOCK_HASHES, block_hashes);
+  }
+  #[inline]
+  pub fn add_filters(&mut self, filters: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<Bytes<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BlockFilters::VT_FILTERS, filters);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> BlockFiltersBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    BlockFiltersBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<BlockFilters<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetBlockFilterHeadersOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetBlockFilterHeaders<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetBlockFilterHeaders<'a> {
+    type Inner = GetBlockFilterHeaders<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetBlockFilterHeaders<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetBlockFilterHeaders {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetBlockFilterHeadersArgs<'args>) -> flatbuffers::WIPOffset<GetBlockFilterHeaders<'bldr>> {
+      let mut builder = GetBlockFilterHeadersBuilder::new(_fbb);
+      builder.add_start_number(args.start_number);
+      if let Some(x) = args.stop_hash { builder.add_stop_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_START_NUMBER: flatbuffers::VOffsetT = 4;
+    pub const VT_STOP_HASH: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn start_number(&self) -> u64 {
+    self._tab.get::<u64>(GetBlockFilterHeaders::VT_START_NUMBER, Some(0)).unwrap()
+  }
+  #[inline]
+  pub fn stop_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(GetBlockFilterHeaders::VT_STOP_HASH, None)
+  }
+}
+
+pub struct GetBlockFilterHeadersArgs<'a> {
+    pub start_number: u64,
+    pub stop_hash: Option<&'a  H256>,
+}
+impl<'a> Default for GetBlockFilterHeadersArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        GetBlockFilterHeadersArgs {
+            start_number: 0,
+            stop_hash: None,
+        }
+    }
+}
+pub struct GetBlockFilterHeadersBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> GetBlockFilterHeadersBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_start_number(&mut self, start_number: u64) {
+    self.fbb_.push_slot::<u64>(GetBlockFilterHeaders::VT_START_NUMBER, start_number, 0);
+  }
+  #[inline]
+  pub fn add_stop_hash(&mut self, stop_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(GetBlockFilterHeaders::VT_STOP_HASH, stop_hash);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetBlockFilterHeadersBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    GetBlockFilterHeadersBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetBlockFilterHeaders<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum BlockFilterHeadersOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct BlockFilterHeaders<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for BlockFilterHeaders<'a> {
+    type Inner = BlockFilterHeaders<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> BlockFilterHeaders<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        BlockFilterHeaders {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args BlockFilterHeadersArgs<'args>) -> flatbuffers::WIPOffset<BlockFilterHeaders<'bldr>> {
+      let mut builder = BlockFilterHeadersBuilder::new(_fbb);
+      if let Some(x) = args.filter_hashes { builder.add_filter_hashes(x); }
+      if let Some(x) = args.previous_filter_header { builder.add_previous_filter_header(x); }
+      if let Some(x) = args.stop_hash { builder.add_stop_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_STOP_HASH: flatbuffers::VOffsetT = 4;
+    pub const VT_PREVIOUS_FILTER_HEADER: flatbuffers::VOffsetT = 6;
+    pub const VT_FILTER_HASHES: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn stop_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(BlockFilterHeaders::VT_STOP_HASH, None)
+  }
+  #[inline]
+  pub fn previous_filter_header(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(BlockFilterHeaders::VT_PREVIOUS_FILTER_HEADER, None)
+  }
+  #[inline]
+  pub fn filter_hashes(&self) -> Option<&'a [H256]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<H256>>>(BlockFilterHeaders::VT_FILTER_HASHES, None).map(|v| v.safe_slice() )
+  }
+}
+
+pub struct BlockFilterHeadersArgs<'a> {
+    pub stop_hash: Option<&'a  H256>,
+    pub previous_filter_header: Option<&'a  H256>,
+    pub filter_hashes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , H256>>>,
+}
+impl<'a> Default for BlockFilterHeadersArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        BlockFilterHeadersArgs {
+            stop_hash: None,
+            previous_filter_header: None,
+            filter_hashes: None,
+        }
+    }
+}
+pub struct BlockFilterHeadersBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> BlockFilterHeadersBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_stop_hash(&mut self, stop_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(BlockFilterHeaders::VT_STOP_HASH, stop_hash);
+  }
+  #[inline]
+  pub fn add_previous_filter_header(&mut self, previous_filter_header: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(BlockFilterHeaders::VT_PREVIOUS_FILTER_HEADER, previous_filter_header);
+  }
+  #[inline]
+  pub fn add_filter_hashes(&mut self, filter_hashes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , H256>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BlockFilterHeaders::VT_FILTER_HASHES, filter_hashes);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> BlockFilterHeadersBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    BlockFilterHeadersBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<BlockFilterHeaders<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum LightClientMessageOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct LightClientMessage<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for LightClientMessage<'a> {
+    type Inner = LightClientMessage<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> LightClientMessage<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        LightClientMessage {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args LightClientMessageArgs) -> flatbuffers::WIPOffset<LightClientMessage<'bldr>> {
+      let mut builder = LightClientMessageBuilder::new(_fbb);
+      if let Some(x) = args.payload { builder.add_payload(x); }
+      builder.add_payload_type(args.payload_type);
+      builder.finish()
+    }
+
+    pub const VT_PAYLOAD_TYPE: flatbuffers::VOffsetT = 4;
+    pub const VT_PAYLOAD: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn payload_type(&self) -> LightClientPayload {
+    self._tab.get::<LightClientPayload>(LightClientMessage::VT_PAYLOAD_TYPE, Some(LightClientPayload::NONE)).unwrap()
+  }
+  #[inline]
+  pub fn payload(&self) -> Option<flatbuffers::Table<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Table<'a>>>(LightClientMessage::VT_PAYLOAD, None)
+  }
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_get_light_headers(&'a self) -> Option<GetLightHeaders> {
+    if self.payload_type() == LightClientPayload::GetLightHeaders {
+      self.payload().map(|u| GetLightHeaders::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_light_headers(&'a self) -> Option<LightHeaders> {
+    if self.payload_type() == LightClientPayload::LightHeaders {
+      self.payload().map(|u| LightHeaders::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_get_transaction_proofs(&'a self) -> Option<GetTransactionProofs> {
+    if self.payload_type() == LightClientPayload::GetTransactionProofs {
+      self.payload().map(|u| GetTransactionProofs::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_transaction_proofs(&'a self) -> Option<TransactionProofs> {
+    if self.payload_type() == LightClientPayload::TransactionProofs {
+      self.payload().map(|u| TransactionProofs::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_get_cells_status(&'a self) -> Option<GetCellsStatus> {
+    if self.payload_type() == LightClientPayload::GetCellsStatus {
+      self.payload().map(|u| GetCellsStatus::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn payload_as_cells_status(&'a self) -> Option<CellsStatus> {
+    if self.payload_type() == LightClientPayload::CellsStatus {
+      self.payload().map(|u| CellsStatus::init_from_table(u))
+    } else {
+      None
+    }
+  }
+
+}
+
+pub struct LightClientMessageArgs {
+    pub payload_type: LightClientPayload,
+    pub payload: Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>,
+}
+impl<'a> Default for LightClientMessageArgs {
+    #[inline]
+    fn default() -> Self {
+        LightClientMessageArgs {
+            payload_type: LightClientPayload::NONE,
+            payload: None,
+        }
+    }
+}
+pub struct LightClientMessageBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> LightClientMessageBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_payload_type(&mut self, payload_type: LightClientPayload) {
+    self.fbb_.push_slot::<LightClientPayload>(LightClientMessage::VT_PAYLOAD_TYPE, payload_type, LightClientPayload::NONE);
+  }
+  #[inline]
+  pub fn add_payload(&mut self, payload: flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(LightClientMessage::VT_PAYLOAD, payload);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> LightClientMessageBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    LightClientMessageBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<LightClientMessage<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetLightHeadersOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetLightHeaders<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetLightHeaders<'a> {
+    type Inner = GetLightHeaders<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetLightHeaders<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetLightHeaders {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetLightHeadersArgs<'args>) -> flatbuffers::WIPOffset<GetLightHeaders<'bldr>> {
+      let mut builder = GetLightHeadersBuilder::new(_fbb);
+      builder.add_count(args.count);
+      builder.add_start_number(args.start_number);
+      builder.finish()
+    }
+
+    pub const VT_START_NUMBER: flatbuffers::VOffsetT = 4;
+    pub const VT_COUNT: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn start_number(&self) -> u64 {
+    self._tab.get::<u64>(GetLightHeaders::VT_START_NUMBER, Some(0)).unwrap()
+  }
+  #[inline]
+  pub fn count(&self) -> u64 {
+    self._tab.get::<u64>(GetLightHeaders::VT_COUNT, Some(0)).unwrap()
+  }
+}
+
+pub struct GetLightHeadersArgs<'a> {
+    pub start_number: u64,
+    pub count: u64,
+}
+impl<'a> Default for GetLightHeadersArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        GetLightHeadersArgs {
+            start_number: 0,
+            count: 0,
+        }
+    }
+}
+pub struct GetLightHeadersBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> GetLightHeadersBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_start_number(&mut self, start_number: u64) {
+    self.fbb_.push_slot::<u64>(GetLightHeaders::VT_START_NUMBER, start_number, 0);
+  }
+  #[inline]
+  pub fn add_count(&mut self, count: u64) {
+    self.fbb_.push_slot::<u64>(GetLightHeaders::VT_COUNT, count, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetLightHeadersBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    GetLightHeadersBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetLightHeaders<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum LightHeadersOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct LightHeaders<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for LightHeaders<'a> {
+    type Inner = LightHeaders<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> LightHeaders<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        LightHeaders {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args LightHeadersArgs<'args>) -> flatbuffers::WIPOffset<LightHeaders<'bldr>> {
+      let mut builder = LightHeadersBuilder::new(_fbb);
+      if let Some(x) = args.headers { builder.add_headers(x); }
+      builder.finish()
+    }
+
+    pub const VT_HEADERS: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn headers(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Header<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<Header<'a>>>>>(LightHeaders::VT_HEADERS, None)
+  }
+}
+
+pub struct LightHeadersArgs<'a> {
+    pub headers: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<Header<'a >>>>>,
+}
+impl<'a> Default for LightHeadersArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        LightHeadersArgs {
+            headers: None,
+        }
+    }
+}
+pub struct LightHeadersBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> LightHeadersBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_headers(&mut self, headers: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<Header<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(LightHeaders::VT_HEADERS, headers);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> LightHeadersBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    LightHeadersBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<LightHeaders<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetTransactionProofsOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetTransactionProofs<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetTransactionProofs<'a> {
+    type Inner = GetTransactionProofs<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetTransactionProofs<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetTransactionProofs {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetTransactionProofsArgs<'args>) -> flatbuffers::WIPOffset<GetTransactionProofs<'bldr>> {
+      let mut builder = GetTransactionProofsBuilder::new(_fbb);
+      if let Some(x) = args.tx_hashes { builder.add_tx_hashes(x); }
+      builder.finish()
+    }
+
+    pub const VT_TX_HASHES: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn tx_hashes(&self) -> Option<&'a [H256]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<H256>>>(GetTransactionProofs::VT_TX_HASHES, None).map(|v| v.safe_slice() )
+  }
+}
+
+pub struct GetTransactionProofsArgs<'a> {
+    pub tx_hashes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , H256>>>,
+}
+impl<'a> Default for GetTransactionProofsArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        GetTransactionProofsArgs {
+            tx_hashes: None,
+        }
+    }
+}
+pub struct GetTransactionProofsBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> GetTransactionProofsBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_tx_hashes(&mut self, tx_hashes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , H256>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GetTransactionProofs::VT_TX_HASHES, tx_hashes);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetTransactionProofsBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    GetTransactionProofsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetTransactionProofs<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum TransactionProofsOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct TransactionProofs<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for TransactionProofs<'a> {
+    type Inner = TransactionProofs<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> TransactionProofs<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        TransactionProofs {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args TransactionProofsArgs<'args>) -> flatbuffers::WIPOffset<TransactionProofs<'bldr>> {
+      let mut builder = TransactionProofsBuilder::new(_fbb);
+      if let Some(x) = args.proofs { builder.add_proofs(x); }
+      builder.finish()
+    }
+
+    pub const VT_PROOFS: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn proofs(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<TransactionProof<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<TransactionProof<'a>>>>>(TransactionProofs::VT_PROOFS, None)
+  }
+}
+
+pub struct TransactionProofsArgs<'a> {
+    pub proofs: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<TransactionProof<'a >>>>>,
+}
+impl<'a> Default for TransactionProofsArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        TransactionProofsArgs {
+            proofs: None,
+        }
+    }
+}
+pub struct TransactionProofsBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> TransactionProofsBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_proofs(&mut self, proofs: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<TransactionProof<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(TransactionProofs::VT_PROOFS, proofs);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> TransactionProofsBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    TransactionProofsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<TransactionProofs<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum TransactionProofOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct TransactionProof<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for TransactionProof<'a> {
+    type Inner = TransactionProof<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> TransactionProof<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        TransactionProof {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args TransactionProofArgs<'args>) -> flatbuffers::WIPOffset<TransactionProof<'bldr>> {
+      let mut builder = TransactionProofBuilder::new(_fbb);
+      if let Some(x) = args.proof { builder.add_proof(x); }
+      if let Some(x) = args.tx_hashes { builder.add_tx_hashes(x); }
+      if let Some(x) = args.block_hash { builder.add_block_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_BLOCK_HASH: flatbuffers::VOffsetT = 4;
+    pub const VT_TX_HASHES: flatbuffers::VOffsetT = 6;
+    pub const VT_PROOF: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn block_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(TransactionProof::VT_BLOCK_HASH, None)
+  }
+  #[inline]
+  pub fn tx_hashes(&self) -> Option<&'a [H256]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<H256>>>(TransactionProof::VT_TX_HASHES, None).map(|v| v.safe_slice() )
+  }
+  #[inline]
+  pub fn proof(&self) -> Option<MerkleProof<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<MerkleProof<'a>>>(TransactionProof::VT_PROOF, None)
+  }
+}
+
+pub struct TransactionProofArgs<'a> {
+    pub block_hash: Option<&'a  H256>,
+    pub tx_hashes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , H256>>>,
+    pub proof: Option<flatbuffers::WIPOffset<MerkleProof<'a >>>,
+}
+impl<'a> Default for TransactionProofArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        TransactionProofArgs {
+            block_hash: None,
+            tx_hashes: None,
+            proof: None,
+        }
+    }
+}
+pub struct TransactionProofBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> TransactionProofBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_block_hash(&mut self, block_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(TransactionProof::VT_BLOCK_HASH, block_hash);
+  }
+  #[inline]
+  pub fn add_tx_hashes(&mut self, tx_hashes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , H256>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(TransactionProof::VT_TX_HASHES, tx_hashes);
+  }
+  #[inline]
+  pub fn add_proof(&mut self, proof: flatbuffers::WIPOffset<MerkleProof<'b >>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<MerkleProof>>(TransactionProof::VT_PROOF, proof);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> TransactionProofBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    TransactionProofBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<TransactionProof<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum GetCellsStatusOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct GetCellsStatus<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for GetCellsStatus<'a> {
+    type Inner = GetCellsStatus<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> GetCellsStatus<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        GetCellsStatus {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args GetCellsStatusArgs<'args>) -> flatbuffers::WIPOffset<GetCellsStatus<'bldr>> {
+      let mut builder = GetCellsStatusBuilder::new(_fbb);
+      if let Some(x) = args.out_points { builder.add_out_points(x); }
+      builder.finish()
+    }
+
+    pub const VT_OUT_POINTS: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn out_points(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<OutPoint<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<OutPoint<'a>>>>>(GetCellsStatus::VT_OUT_POINTS, None)
+  }
+}
+
+pub struct GetCellsStatusArgs<'a> {
+    pub out_points: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<OutPoint<'a >>>>>,
+}
+impl<'a> Default for GetCellsStatusArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        GetCellsStatusArgs {
+            out_points: None,
+        }
+    }
+}
+pub struct GetCellsStatusBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> GetCellsStatusBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_out_points(&mut self, out_points: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<OutPoint<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(GetCellsStatus::VT_OUT_POINTS, out_points);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> GetCellsStatusBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    GetCellsStatusBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<GetCellsStatus<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum CellsStatusOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct CellsStatus<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for CellsStatus<'a> {
+    type Inner = CellsStatus<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> CellsStatus<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        CellsStatus {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args CellsStatusArgs<'args>) -> flatbuffers::WIPOffset<CellsStatus<'bldr>> {
+      let mut builder = CellsStatusBuilder::new(_fbb);
+      if let Some(x) = args.cells { builder.add_cells(x); }
+      builder.finish()
+    }
+
+    pub const VT_CELLS: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn cells(&self) -> Option<flatbuffers::Vector<flatbuffers::ForwardsUOffset<CellStatusProof<'a>>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<flatbuffers::ForwardsUOffset<CellStatusProof<'a>>>>>(CellsStatus::VT_CELLS, None)
+  }
+}
+
+pub struct CellsStatusArgs<'a> {
+    pub cells: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a , flatbuffers::ForwardsUOffset<CellStatusProof<'a >>>>>,
+}
+impl<'a> Default for CellsStatusArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        CellsStatusArgs {
+            cells: None,
+        }
+    }
+}
+pub struct CellsStatusBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> CellsStatusBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_cells(&mut self, cells: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<CellStatusProof<'b >>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(CellsStatus::VT_CELLS, cells);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> CellsStatusBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    CellsStatusBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<CellsStatus<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+pub enum CellStatusProofOffset {}
+#[derive(Copy, Clone, Debug, PartialEq)]
+
+pub struct CellStatusProof<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for CellStatusProof<'a> {
+    type Inner = CellStatusProof<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: flatbuffers::Table { buf: buf, loc: loc },
+        }
+    }
+}
+
+impl<'a> CellStatusProof<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        CellStatusProof {
+            _tab: table,
+        }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args CellStatusProofArgs<'args>) -> flatbuffers::WIPOffset<CellStatusProof<'bldr>> {
+      let mut builder = CellStatusProofBuilder::new(_fbb);
+      if let Some(x) = args.proof { builder.add_proof(x); }
+      if let Some(x) = args.block_hash { builder.add_block_hash(x); }
+      builder.add_status(args.status);
+      builder.finish()
+    }
+
+    pub const VT_STATUS: flatbuffers::VOffsetT = 4;
+    pub const VT_BLOCK_HASH: flatbuffers::VOffsetT = 6;
+    pub const VT_PROOF: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn status(&self) -> u8 {
+    self._tab.get::<u8>(CellStatusProof::VT_STATUS, Some(0)).unwrap()
+  }
+  #[inline]
+  pub fn block_hash(&self) -> Option<&'a H256> {
+    self._tab.get::<H256>(CellStatusProof::VT_BLOCK_HASH, None)
+  }
+  #[inline]
+  pub fn proof(&self) -> Option<MerkleProof<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<MerkleProof<'a>>>(CellStatusProof::VT_PROOF, None)
+  }
+}
+
+pub struct CellStatusProofArgs<'a> {
+    pub status: u8,
+    pub block_hash: Option<&'a  H256>,
+    pub proof: Option<flatbuffers::WIPOffset<MerkleProof<'a >>>,
+}
+impl<'a> Default for CellStatusProofArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        CellStatusProofArgs {
+            status: 0,
+            block_hash: None,
+            proof: None,
+        }
+    }
+}
+pub struct CellStatusProofBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> CellStatusProofBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_status(&mut self, status: u8) {
+    self.fbb_.push_slot::<u8>(CellStatusProof::VT_STATUS, status, 0);
+  }
+  #[inline]
+  pub fn add_block_hash(&mut self, block_hash: &'b  H256) {
+    self.fbb_.push_slot_always::<&H256>(CellStatusProof::VT_BLOCK_HASH, block_hash);
+  }
+  #[inline]
+  pub fn add_proof(&mut self, proof: flatbuffers::WIPOffset<MerkleProof<'b >>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<MerkleProof>>(CellStatusProof::VT_PROOF, proof);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> CellStatusProofBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    CellStatusProofBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<CellStatusProof<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
 #[inline]
 pub fn get_root_as_sync_message<'a>(buf: &'a [u8]) -> SyncMessage<'a> {
   flatbuffers::get_root::<SyncMessage<'a>>(buf)
@@ -239,7 +239,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::BlockProposal<'a> {
+        impl<'a> Verify for reader::BlockFilterHeaders<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -302,21 +302,38 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_STOP_HASH as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
+                    let voffset = vtab.get(Self::VT_STOP_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PREVIOUS_FILTER_HEADER as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PREVIOUS_FILTER_HEADER) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_FILTER_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_FILTER_HASHES) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let transactions_verifier = VectorVerifier::follow(
+                        let filter_hashes_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        transactions_verifier
-                            .verify_reference_elements::<reader::Transaction>()?;
+                        filter_hashes_verifier.verify_scalar_elements(32)?;
                     }
                 }
 
@@ -324,7 +341,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::BlockTransactions<'a> {
+        impl<'a> Verify for reader::BlockFilters<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -387,30 +404,47 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_BLOCK_HASH as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_STOP_HASH as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_BLOCK_HASH) as usize;
+                    let voffset = vtab.get(Self::VT_STOP_HASH) as usize;
                     if voffset > 0 && object_inline_num_bytes - voffset < 32 {
                         return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_BLOCK_HASHES as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
+                    let voffset = vtab.get(Self::VT_BLOCK_HASHES) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let transactions_verifier = VectorVerifier::follow(
+                        let block_hashes_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        transactions_verifier
-                            .verify_reference_elements::<reader::Transaction>()?;
+                        block_hashes_verifier.verify_scalar_elements(32)?;
+                    }
+                }
+
+                if Self::VT_FILTERS as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_FILTERS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let filters_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        filters_verifier
+                            .verify_reference_elements::<reader::Bytes>()?;
                     }
                 }
 
@@ -418,7 +452,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::Bytes<'a> {
+        impl<'a> Verify for reader::BlockProposal<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -481,20 +515,21 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_SEQ as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_SEQ) as usize;
+                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let seq_verifier = VectorVerifier::follow(
+                        let transactions_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        seq_verifier.verify_scalar_elements(1)?;
+                        transactions_verifier
+                            .verify_reference_elements::<reader::Transaction>()?;
                     }
                 }
 
@@ -502,7 +537,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::CellInput<'a> {
+        impl<'a> Verify for reader::BlockTransactions<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -574,48 +609,21 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_TX_HASH as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_TX_HASH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_INDEX as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_INDEX) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_SINCE as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_SINCE) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_ARGS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_ARGS) as usize;
+                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let args_verifier = VectorVerifier::follow(
+                        let transactions_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        args_verifier
-                            .verify_reference_elements::<reader::Bytes>()?;
+                        transactions_verifier
+                            .verify_reference_elements::<reader::Transaction>()?;
                     }
                 }
 
@@ -623,7 +631,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::CellOutput<'a> {
+        impl<'a> Verify for reader::Bytes<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -686,57 +694,20 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_CAPACITY as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_CAPACITY) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_DATA as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_DATA) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        if let Some(f) = self.data() {
-                            f.verify()?;
-                        }
-                    }
-                }
-
-                if Self::VT_LOCK as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_LOCK) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        if let Some(f) = self.lock() {
-                            f.verify()?;
-                        }
-                    }
-                }
-
-                if Self::VT_TYPE_ as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_SEQ as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TYPE_) as usize;
+                    let voffset = vtab.get(Self::VT_SEQ) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        if let Some(f) = self.type_() {
-                            f.verify()?;
-                        }
+                        let seq_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        seq_verifier.verify_scalar_elements(1)?;
                     }
                 }
 
@@ -744,7 +715,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::ClearFilter<'a> {
+        impl<'a> Verify for reader::Capabilities<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -807,11 +778,20 @@ pub mod ckb {
                     }
                 }
 
+                if Self::VT_CAPABILITIES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_CAPABILITIES) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
                 Ok(())
             }
         }
 
-        impl<'a> Verify for reader::CompactBlock<'a> {
+        impl<'a> Verify for reader::CellInput<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -874,98 +854,57 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_HEADER as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_HEADER) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        if let Some(f) = self.header() {
-                            f.verify()?;
-                        }
-                    }
-                }
-
-                if Self::VT_NONCE as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_BLOCK_HASH as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_NONCE) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                    let voffset = vtab.get(Self::VT_BLOCK_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
                         return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_SHORT_IDS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TX_HASH as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_SHORT_IDS) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        let short_ids_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        short_ids_verifier
-                            .verify_reference_elements::<reader::Bytes>()?;
+                    let voffset = vtab.get(Self::VT_TX_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_PREFILLED_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_INDEX as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_PREFILLED_TRANSACTIONS) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        let prefilled_transactions_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        prefilled_transactions_verifier
-                            .verify_reference_elements::<reader::IndexTransaction>()?;
+                    let voffset = vtab.get(Self::VT_INDEX) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
+                        return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_UNCLES as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_SINCE as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_UNCLES) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        let uncles_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        uncles_verifier
-                            .verify_reference_elements::<reader::UncleBlock>()?;
+                    let voffset = vtab.get(Self::VT_SINCE) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_PROPOSALS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_ARGS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_PROPOSALS) as usize;
+                    let voffset = vtab.get(Self::VT_ARGS) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let proposals_verifier = VectorVerifier::follow(
+                        let args_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        proposals_verifier.verify_scalar_elements(10)?;
+                        args_verifier
+                            .verify_reference_elements::<reader::Bytes>()?;
                     }
                 }
 
@@ -973,7 +912,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::FilteredBlock<'a> {
+        impl<'a> Verify for reader::CellOutput<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1036,49 +975,55 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_HEADER as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_CAPACITY as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_HEADER) as usize;
+                    let voffset = vtab.get(Self::VT_CAPACITY) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_DATA as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_DATA) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        if let Some(f) = self.header() {
+                        if let Some(f) = self.data() {
                             f.verify()?;
                         }
                     }
                 }
 
-                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_LOCK as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
+                    let voffset = vtab.get(Self::VT_LOCK) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let transactions_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        transactions_verifier
-                            .verify_reference_elements::<reader::Transaction>()?;
+                        if let Some(f) = self.lock() {
+                            f.verify()?;
+                        }
                     }
                 }
 
-                if Self::VT_PROOF as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TYPE_ as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_PROOF) as usize;
+                    let voffset = vtab.get(Self::VT_TYPE_) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        if let Some(f) = self.proof() {
+                        if let Some(f) = self.type_() {
                             f.verify()?;
                         }
                     }
@@ -1088,7 +1033,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::GetBlockProposal<'a> {
+        impl<'a> Verify for reader::CellStatusProof<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1151,29 +1096,36 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_BLOCK_NUMBER as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_STATUS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_BLOCK_NUMBER) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                    let voffset = vtab.get(Self::VT_STATUS) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 1 {
                         return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_PROPOSALS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_BLOCK_HASH as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_PROPOSALS) as usize;
+                    let voffset = vtab.get(Self::VT_BLOCK_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PROOF as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PROOF) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let proposals_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        proposals_verifier.verify_scalar_elements(10)?;
+                        if let Some(f) = self.proof() {
+                            f.verify()?;
+                        }
                     }
                 }
 
@@ -1181,7 +1133,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::GetBlockTransactions<'a> {
+        impl<'a> Verify for reader::CellsStatus<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1244,29 +1196,21 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_BLOCK_HASH as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_BLOCK_HASH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_INDEXES as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_CELLS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_INDEXES) as usize;
+                    let voffset = vtab.get(Self::VT_CELLS) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let indexes_verifier = VectorVerifier::follow(
+                        let cells_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        indexes_verifier.verify_scalar_elements(4)?;
+                        cells_verifier
+                            .verify_reference_elements::<reader::CellStatusProof>()?;
                     }
                 }
 
@@ -1274,7 +1218,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::GetBlocks<'a> {
+        impl<'a> Verify for reader::ClearFilter<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1337,28 +1281,11 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_BLOCK_HASHES as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_BLOCK_HASHES) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        let block_hashes_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        block_hashes_verifier.verify_scalar_elements(32)?;
-                    }
-                }
-
                 Ok(())
             }
         }
 
-        impl<'a> Verify for reader::GetHeaders<'a> {
+        impl<'a> Verify for reader::CompactBlock<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1421,38 +1348,98 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_VERSION as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_HEADER as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_VERSION) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
+                    let voffset = vtab.get(Self::VT_HEADER) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        if let Some(f) = self.header() {
+                            f.verify()?;
+                        }
+                    }
+                }
+
+                if Self::VT_NONCE as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_NONCE) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
                         return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_BLOCK_LOCATOR_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_SHORT_IDS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_BLOCK_LOCATOR_HASHES) as usize;
+                    let voffset = vtab.get(Self::VT_SHORT_IDS) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let block_locator_hashes_verifier = VectorVerifier::follow(
+                        let short_ids_verifier = VectorVerifier::follow(
                             buf,
                             try_follow_uoffset(buf, tab.loc + voffset)?,
                         );
-                        block_locator_hashes_verifier.verify_scalar_elements(32)?;
+                        short_ids_verifier
+                            .verify_reference_elements::<reader::Bytes>()?;
                     }
                 }
 
-                if Self::VT_HASH_STOP as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_PREFILLED_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_HASH_STOP) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
+                    let voffset = vtab.get(Self::VT_PREFILLED_TRANSACTIONS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let prefilled_transactions_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        prefilled_transactions_verifier
+                            .verify_reference_elements::<reader::IndexTransaction>()?;
+                    }
+                }
+
+                if Self::VT_UNCLES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_UNCLES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let uncles_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        uncles_verifier
+                            .verify_reference_elements::<reader::UncleBlock>()?;
+                    }
+                }
+
+                if Self::VT_PROPOSALS as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PROPOSALS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let proposals_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        proposals_verifier.verify_scalar_elements(10)?;
                     }
                 }
 
@@ -1460,7 +1447,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::GetRelayTransaction<'a> {
+        impl<'a> Verify for reader::FilterMessage<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1523,20 +1510,52 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_TX_HASH as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_PAYLOAD_TYPE as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TX_HASH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                    let voffset = vtab.get(Self::VT_PAYLOAD_TYPE) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 1 {
                         return Err(Error::OutOfBounds);
                     }
                 }
 
+                if Self::VT_PAYLOAD as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PAYLOAD) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        match self.payload_type() {
+                            reader::FilterPayload::GetBlockFilters => self
+                                .payload_as_get_block_filters()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::FilterPayload::BlockFilters => self
+                                .payload_as_block_filters()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::FilterPayload::GetBlockFilterHeaders => self
+                                .payload_as_get_block_filter_headers()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::FilterPayload::BlockFilterHeaders => self
+                                .payload_as_block_filter_headers()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::FilterPayload::NONE => return Err(Error::UnmatchedUnion),
+                        }
+                    }
+                }
+
                 Ok(())
             }
         }
 
-        impl<'a> Verify for reader::Header<'a> {
+
+        impl<'a> Verify for reader::FilteredBlock<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1599,53 +1618,1124 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_VERSION as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_HEADER as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_VERSION) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
+                    let voffset = vtab.get(Self::VT_HEADER) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
 
-                if Self::VT_PARENT_HASH as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_PARENT_HASH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
+                        if let Some(f) = self.header() {
+                            f.verify()?;
+                        }
                     }
                 }
 
-                if Self::VT_TIMESTAMP as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TIMESTAMP) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
+                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
 
-                if Self::VT_NUMBER as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_NUMBER) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
-                        return Err(Error::OutOfBounds);
+                        let transactions_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        transactions_verifier
+                            .verify_reference_elements::<reader::Transaction>()?;
                     }
                 }
 
-                if Self::VT_TRANSACTIONS_ROOT as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_PROOF as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TRANSACTIONS_ROOT) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
+                    let voffset = vtab.get(Self::VT_PROOF) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        if let Some(f) = self.proof() {
+                            f.verify()?;
+                        }
                     }
                 }
 
-                if Self::VT_PROPOSALS_ROOT as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetBlockFilterHeaders<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_START_NUMBER as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_START_NUMBER) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_STOP_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_STOP_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetBlockFilters<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_START_NUMBER as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_START_NUMBER) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_STOP_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_STOP_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetBlockProposal<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_BLOCK_NUMBER as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_BLOCK_NUMBER) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PROPOSALS as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PROPOSALS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let proposals_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        proposals_verifier.verify_scalar_elements(10)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetBlockTransactions<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_BLOCK_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_BLOCK_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_INDEXES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_INDEXES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let indexes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        indexes_verifier.verify_scalar_elements(4)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetBlocks<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_BLOCK_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_BLOCK_HASHES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let block_hashes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        block_hashes_verifier.verify_scalar_elements(32)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetCellsStatus<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_OUT_POINTS as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_OUT_POINTS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let out_points_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        out_points_verifier
+                            .verify_reference_elements::<reader::OutPoint>()?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetHeaders<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_VERSION as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_VERSION) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_BLOCK_LOCATOR_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_BLOCK_LOCATOR_HASHES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let block_locator_hashes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        block_locator_hashes_verifier.verify_scalar_elements(32)?;
+                    }
+                }
+
+                if Self::VT_HASH_STOP as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_HASH_STOP) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetLightHeaders<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_START_NUMBER as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_START_NUMBER) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_COUNT as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_COUNT) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetRelayTransaction<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TX_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TX_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetRelayTransactions<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TX_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TX_HASHES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let tx_hashes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        tx_hashes_verifier.verify_scalar_elements(32)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::GetTransactionProofs<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TX_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TX_HASHES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let tx_hashes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        tx_hashes_verifier.verify_scalar_elements(32)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::Header<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_VERSION as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_VERSION) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PARENT_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PARENT_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TIMESTAMP as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TIMESTAMP) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_NUMBER as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_NUMBER) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TRANSACTIONS_ROOT as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TRANSACTIONS_ROOT) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PROPOSALS_ROOT as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
                 {
                     let voffset = vtab.get(Self::VT_PROPOSALS_ROOT) as usize;
                     if voffset > 0 && object_inline_num_bytes - voffset < 32 {
@@ -1656,83 +2746,259 @@ pub mod ckb {
                 if Self::VT_WITNESSES_ROOT as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_WITNESSES_ROOT) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
-                    }
+                    let voffset = vtab.get(Self::VT_WITNESSES_ROOT) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_DIFFICULTY as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_DIFFICULTY) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        if let Some(f) = self.difficulty() {
+                            f.verify()?;
+                        }
+                    }
+                }
+
+                if Self::VT_NONCE as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_NONCE) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PROOF as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PROOF) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        if let Some(f) = self.proof() {
+                            f.verify()?;
+                        }
+                    }
+                }
+
+                if Self::VT_UNCLES_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_UNCLES_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_UNCLES_COUNT as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_UNCLES_COUNT) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_EPOCH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_EPOCH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::Headers<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_HEADERS as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_HEADERS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let headers_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        headers_verifier
+                            .verify_reference_elements::<reader::Header>()?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::IndexTransaction<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
                 }
 
-                if Self::VT_DIFFICULTY as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_DIFFICULTY) as usize;
-                    if voffset > 0 {
-                        if voffset + 4 > object_inline_num_bytes {
-                            return Err(Error::OutOfBounds);
-                        }
-
-                        if let Some(f) = self.difficulty() {
-                            f.verify()?;
-                        }
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_NONCE as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_INDEX as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_NONCE) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                    let voffset = vtab.get(Self::VT_INDEX) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
                         return Err(Error::OutOfBounds);
                     }
                 }
 
-                if Self::VT_PROOF as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TRANSACTION as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_PROOF) as usize;
+                    let voffset = vtab.get(Self::VT_TRANSACTION) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        if let Some(f) = self.proof() {
+                        if let Some(f) = self.transaction() {
                             f.verify()?;
                         }
                     }
                 }
 
-                if Self::VT_UNCLES_HASH as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_UNCLES_HASH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_UNCLES_COUNT as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_UNCLES_COUNT) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_EPOCH as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_EPOCH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
                 Ok(())
             }
         }
 
-        impl<'a> Verify for reader::Headers<'a> {
+        impl<'a> Verify for reader::LightClientMessage<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1795,21 +3061,51 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_HEADERS as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_PAYLOAD_TYPE as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_HEADERS) as usize;
+                    let voffset = vtab.get(Self::VT_PAYLOAD_TYPE) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 1 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PAYLOAD as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PAYLOAD) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        let headers_verifier = VectorVerifier::follow(
-                            buf,
-                            try_follow_uoffset(buf, tab.loc + voffset)?,
-                        );
-                        headers_verifier
-                            .verify_reference_elements::<reader::Header>()?;
+                        match self.payload_type() {
+                            reader::LightClientPayload::GetLightHeaders => self
+                                .payload_as_get_light_headers()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::LightClientPayload::LightHeaders => self
+                                .payload_as_light_headers()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::LightClientPayload::GetTransactionProofs => self
+                                .payload_as_get_transaction_proofs()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::LightClientPayload::TransactionProofs => self
+                                .payload_as_transaction_proofs()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::LightClientPayload::GetCellsStatus => self
+                                .payload_as_get_cells_status()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::LightClientPayload::CellsStatus => self
+                                .payload_as_cells_status()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::LightClientPayload::NONE => return Err(Error::UnmatchedUnion),
+                        }
                     }
                 }
 
@@ -1817,7 +3113,8 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::IndexTransaction<'a> {
+
+        impl<'a> Verify for reader::LightHeaders<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -1880,27 +3177,21 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_INDEX as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_INDEX) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 4 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_TRANSACTION as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_HEADERS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TRANSACTION) as usize;
+                    let voffset = vtab.get(Self::VT_HEADERS) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        if let Some(f) = self.transaction() {
-                            f.verify()?;
-                        }
+                        let headers_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        headers_verifier
+                            .verify_reference_elements::<reader::Header>()?;
                     }
                 }
 
@@ -2217,6 +3508,18 @@ pub mod ckb {
                                 .payload_as_block_proposal()
                                 .ok_or(Error::UnmatchedUnion)?
                                 .verify()?,
+                            reader::RelayPayload::RelayTransactionHashes => self
+                                .payload_as_relay_transaction_hashes()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::RelayPayload::GetRelayTransactions => self
+                                .payload_as_get_relay_transactions()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
+                            reader::RelayPayload::RelayTransactions => self
+                                .payload_as_relay_transactions()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
                             reader::RelayPayload::NONE => return Err(Error::UnmatchedUnion),
                         }
                     }
@@ -2226,7 +3529,174 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::RelayTransaction<'a> {
+        impl<'a> Verify for reader::RelayTransaction<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_CYCLES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_CYCLES) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TRANSACTION as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TRANSACTION) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        if let Some(f) = self.transaction() {
+                            f.verify()?;
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::RelayTransactionHash<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TX_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TX_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::RelayTransactionHashes<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -2289,27 +3759,20 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_CYCLES as usize + flatbuffers::SIZE_VOFFSET
-                    <= vtab_num_bytes
-                {
-                    let voffset = vtab.get(Self::VT_CYCLES) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 8 {
-                        return Err(Error::OutOfBounds);
-                    }
-                }
-
-                if Self::VT_TRANSACTION as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TX_HASHES as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TRANSACTION) as usize;
+                    let voffset = vtab.get(Self::VT_TX_HASHES) as usize;
                     if voffset > 0 {
                         if voffset + 4 > object_inline_num_bytes {
                             return Err(Error::OutOfBounds);
                         }
 
-                        if let Some(f) = self.transaction() {
-                            f.verify()?;
-                        }
+                        let tx_hashes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        tx_hashes_verifier.verify_scalar_elements(32)?;
                     }
                 }
 
@@ -2317,7 +3780,7 @@ pub mod ckb {
             }
         }
 
-        impl<'a> Verify for reader::RelayTransactionHash<'a> {
+        impl<'a> Verify for reader::RelayTransactions<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
                 let buf = tab.buf;
@@ -2380,12 +3843,21 @@ pub mod ckb {
                     }
                 }
 
-                if Self::VT_TX_HASH as usize + flatbuffers::SIZE_VOFFSET
+                if Self::VT_TRANSACTIONS as usize + flatbuffers::SIZE_VOFFSET
                     <= vtab_num_bytes
                 {
-                    let voffset = vtab.get(Self::VT_TX_HASH) as usize;
-                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
-                        return Err(Error::OutOfBounds);
+                    let voffset = vtab.get(Self::VT_TRANSACTIONS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let transactions_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        transactions_verifier
+                            .verify_reference_elements::<reader::RelayTransaction>()?;
                     }
                 }
 
@@ -2703,6 +4175,10 @@ pub mod ckb {
                                 .payload_as_filtered_block()
                                 .ok_or(Error::UnmatchedUnion)?
                                 .verify()?,
+                            reader::SyncPayload::Capabilities => self
+                                .payload_as_capabilities()
+                                .ok_or(Error::UnmatchedUnion)?
+                                .verify()?,
                             reader::SyncPayload::NONE => return Err(Error::UnmatchedUnion),
                         }
                     }
@@ -3018,6 +4494,199 @@ pub mod ckb {
             }
         }
 
+        impl<'a> Verify for reader::TransactionProof<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_BLOCK_HASH as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_BLOCK_HASH) as usize;
+                    if voffset > 0 && object_inline_num_bytes - voffset < 32 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_TX_HASHES as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_TX_HASHES) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let tx_hashes_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        tx_hashes_verifier.verify_scalar_elements(32)?;
+                    }
+                }
+
+                if Self::VT_PROOF as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PROOF) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        if let Some(f) = self.proof() {
+                            f.verify()?;
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl<'a> Verify for reader::TransactionProofs<'a> {
+            fn verify(&self) -> Result {
+                let tab = self._tab;
+                let buf = tab.buf;
+                let buf_len = buf.len();
+
+                if tab.loc > MAX_OFFSET_LOC || tab.loc + flatbuffers::SIZE_SOFFSET > buf_len {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab_loc = {
+                    let soffset_slice = &buf[tab.loc..];
+                    let soffset = flatbuffers::read_scalar::<flatbuffers::SOffsetT>(soffset_slice);
+                    if soffset >= 0 {
+                        tab.loc.checked_sub(soffset as usize)
+                    } else {
+                        soffset
+                            .checked_neg()
+                            .and_then(|foffset| tab.loc.checked_add(foffset as usize))
+                    }
+                }
+                .ok_or(Error::OutOfBounds)?;
+                if vtab_loc
+                    .checked_add(flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                let vtab = tab.vtable();
+                let vtab_num_bytes = vtab.num_bytes();
+                let object_inline_num_bytes = vtab.object_inline_num_bytes();
+                if vtab_num_bytes < flatbuffers::SIZE_VOFFSET + flatbuffers::SIZE_VOFFSET
+                    || object_inline_num_bytes < flatbuffers::SIZE_SOFFSET
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if vtab_loc
+                    .checked_add(vtab_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+                if tab
+                    .loc
+                    .checked_add(object_inline_num_bytes)
+                    .filter(|loc| *loc <= buf_len)
+                    .is_none()
+                {
+                    return Err(Error::OutOfBounds);
+                }
+
+                for i in 0..vtab.num_fields() {
+                    let voffset = vtab.get_field(i) as usize;
+                    if (voffset > 0 && voffset < flatbuffers::SIZE_SOFFSET)
+                        || voffset >= object_inline_num_bytes
+                    {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+
+                if Self::VT_PROOFS as usize + flatbuffers::SIZE_VOFFSET
+                    <= vtab_num_bytes
+                {
+                    let voffset = vtab.get(Self::VT_PROOFS) as usize;
+                    if voffset > 0 {
+                        if voffset + 4 > object_inline_num_bytes {
+                            return Err(Error::OutOfBounds);
+                        }
+
+                        let proofs_verifier = VectorVerifier::follow(
+                            buf,
+                            try_follow_uoffset(buf, tab.loc + voffset)?,
+                        );
+                        proofs_verifier
+                            .verify_reference_elements::<reader::TransactionProof>()?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
         impl<'a> Verify for reader::UncleBlock<'a> {
             fn verify(&self) -> Result {
                 let tab = self._tab;
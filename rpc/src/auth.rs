@@ -0,0 +1,122 @@
+use crate::config::Auth;
+use jsonrpc_http_server::hyper::header::{HeaderValue, AUTHORIZATION};
+use jsonrpc_http_server::hyper::{Body, Method, Request, StatusCode};
+use jsonrpc_http_server::{RequestMiddleware, RequestMiddlewareAction, Response};
+
+/// Rejects the HTTP requests without the configured credentials, lets all of them through
+/// when there are none
+pub(crate) struct AuthMiddleware {
+    // The expected value of the `Authorization` header
+    authorization: Option<Vec<u8>>,
+}
+
+impl AuthMiddleware {
+    pub fn new(auth: Option<&Auth>) -> Self {
+        let authorization = auth.map(|auth| {
+            let authorization = match auth {
+                Auth::Token(token) => format!("Bearer {}", token),
+                Auth::Basic { username, password } => format!(
+                    "Basic {}",
+                    base64::encode(format!("{}:{}", username, password).as_bytes())
+                ),
+            };
+            authorization.into_bytes()
+        });
+        AuthMiddleware { authorization }
+    }
+
+    pub fn is_authorized(&self, request: &Request<Body>) -> bool {
+        let authorization = match self.authorization {
+            Some(ref authorization) => authorization,
+            None => return true,
+        };
+        // CORS preflight requests never carry the credentials
+        if request.method() == Method::OPTIONS {
+            return true;
+        }
+        request.headers().get(AUTHORIZATION).map_or(false, |value| {
+            constant_time_eq(value.as_bytes(), authorization)
+        })
+    }
+}
+
+impl RequestMiddleware for AuthMiddleware {
+    fn on_request(&self, request: Request<Body>) -> RequestMiddlewareAction {
+        if self.is_authorized(&request) {
+            request.into()
+        } else {
+            Response {
+                code: StatusCode::UNAUTHORIZED,
+                content_type: HeaderValue::from_static("text/plain; charset=utf-8"),
+                content: "Unauthorized\n".to_owned(),
+            }
+            .into()
+        }
+    }
+}
+
+// Compares without leaking the length of the matching prefix through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, authorization: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder();
+        builder.method(method);
+        if let Some(authorization) = authorization {
+            builder.header(AUTHORIZATION, authorization);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret1"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn test_open_without_auth() {
+        let middleware = AuthMiddleware::new(None);
+        assert!(middleware.is_authorized(&request(Method::POST, None)));
+        assert!(middleware.is_authorized(&request(Method::POST, Some("Bearer any"))));
+    }
+
+    #[test]
+    fn test_token_auth() {
+        let auth = Auth::Token("secret".to_owned());
+        let middleware = AuthMiddleware::new(Some(&auth));
+        assert!(middleware.is_authorized(&request(Method::POST, Some("Bearer secret"))));
+        assert!(!middleware.is_authorized(&request(Method::POST, Some("Bearer other"))));
+        assert!(!middleware.is_authorized(&request(Method::POST, Some("secret"))));
+        assert!(!middleware.is_authorized(&request(Method::POST, None)));
+        // CORS preflight
+        assert!(middleware.is_authorized(&request(Method::OPTIONS, None)));
+    }
+
+    #[test]
+    fn test_basic_auth() {
+        let auth = Auth::Basic {
+            username: "ckb".to_owned(),
+            password: "secret".to_owned(),
+        };
+        let middleware = AuthMiddleware::new(Some(&auth));
+        let authorization = format!("Basic {}", base64::encode(b"ckb:secret"));
+        assert!(middleware.is_authorized(&request(Method::POST, Some(&authorization))));
+        let wrong = format!("Basic {}", base64::encode(b"ckb:wrong"));
+        assert!(!middleware.is_authorized(&request(Method::POST, Some(&wrong))));
+        assert!(!middleware.is_authorized(&request(Method::POST, Some("Bearer secret"))));
+
+        match middleware.on_request(request(Method::POST, None)) {
+            RequestMiddlewareAction::Respond { .. } => (),
+            RequestMiddlewareAction::Proceed { .. } => panic!("unauthorized request proceeds"),
+        }
+    }
+}
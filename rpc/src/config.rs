@@ -1,24 +1,100 @@
 use serde_derive::{Deserialize, Serialize};
+use std::net::SocketAddr;
 
 #[derive(Clone, Debug, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Module {
     Net,
     Chain,
+    Experiment,
     Miner,
     Pool,
     Trace,
+    Stats,
+    Subscription,
+    Indexer,
+    Admin,
     IntegrationTest,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Config {
+    /// HTTP endpoint, served unless `http_enable` is false
     pub listen_address: String,
+    #[serde(default = "default_http_enable")]
+    pub http_enable: bool,
+    /// Raw TCP endpoint of newline delimited requests, serving the subscriptions too
+    pub tcp_listen_address: Option<String>,
+    /// WebSocket endpoint, serving the subscriptions too
+    pub ws_listen_address: Option<String>,
     pub max_request_body_size: usize,
     pub threads: Option<usize>,
+    /// Threads verifying the transactions of `submit_transaction`, the number
+    /// of CPUs when absent
+    #[serde(default)]
+    pub tx_verify_workers: Option<usize>,
     pub modules: Vec<Module>,
+    /// Credentials required by the HTTP endpoint, open when absent
+    #[serde(default)]
+    pub auth: Option<Auth>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Auth {
+    /// Sent as `Authorization: Bearer <token>`
+    Token(String),
+    /// Sent as `Authorization: Basic <base64 of username:password>`
+    Basic { username: String, password: String },
+}
+
+fn default_http_enable() -> bool {
+    true
+}
+
+impl Module {
+    pub fn all() -> Vec<Module> {
+        vec![
+            Module::Net,
+            Module::Chain,
+            Module::Experiment,
+            Module::Miner,
+            Module::Pool,
+            Module::Trace,
+            Module::Stats,
+            Module::Subscription,
+            Module::Indexer,
+            Module::Admin,
+            Module::IntegrationTest,
+        ]
+    }
 }
 
 impl Config {
+    /// The TCP and WebSocket endpoints take no credentials, they must listen
+    /// on a loopback address when the HTTP endpoint requires them
+    pub fn check_auth(&self) -> Result<(), String> {
+        if self.auth.is_none() {
+            return Ok(());
+        }
+        for address in self
+            .tcp_listen_address
+            .iter()
+            .chain(self.ws_listen_address.iter())
+        {
+            let is_loopback = address
+                .parse::<SocketAddr>()
+                .map(|address| address.ip().is_loopback())
+                .unwrap_or(false);
+            if !is_loopback {
+                return Err(format!(
+                    "rpc.auth is not enforced on {}, listen on a loopback address instead",
+                    address
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn net_enable(&self) -> bool {
         self.modules.contains(&Module::Net)
     }
@@ -27,6 +103,10 @@ impl Config {
         self.modules.contains(&Module::Chain)
     }
 
+    pub fn experiment_enable(&self) -> bool {
+        self.modules.contains(&Module::Experiment)
+    }
+
     pub(crate) fn miner_enable(&self) -> bool {
         self.modules.contains(&Module::Miner)
     }
@@ -39,7 +119,53 @@ impl Config {
         self.modules.contains(&Module::Trace)
     }
 
+    pub(crate) fn stats_enable(&self) -> bool {
+        self.modules.contains(&Module::Stats)
+    }
+
+    pub(crate) fn subscription_enable(&self) -> bool {
+        self.modules.contains(&Module::Subscription)
+    }
+
+    pub fn indexer_enable(&self) -> bool {
+        self.modules.contains(&Module::Indexer)
+    }
+
+    pub(crate) fn admin_enable(&self) -> bool {
+        self.modules.contains(&Module::Admin)
+    }
+
     pub(crate) fn integration_test_enable(&self) -> bool {
         self.modules.contains(&Module::IntegrationTest)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_auth() {
+        let mut config = Config {
+            listen_address: "0.0.0.0:8114".to_owned(),
+            http_enable: true,
+            tcp_listen_address: Some("0.0.0.0:18113".to_owned()),
+            ws_listen_address: Some("127.0.0.1:18114".to_owned()),
+            max_request_body_size: 10_485_760,
+            threads: None,
+            tx_verify_workers: None,
+            modules: Module::all(),
+            auth: None,
+        };
+        assert_eq!(config.check_auth(), Ok(()));
+
+        config.auth = Some(Auth::Token("secret".to_owned()));
+        assert!(config.check_auth().is_err());
+        config.tcp_listen_address = Some("[::1]:18113".to_owned());
+        assert_eq!(config.check_auth(), Ok(()));
+        config.ws_listen_address = Some("localhost:18114".to_owned());
+        assert!(config.check_auth().is_err());
+        config.ws_listen_address = None;
+        assert_eq!(config.check_auth(), Ok(()));
+    }
+}
@@ -1,4 +1,6 @@
+use ckb_verification::TransactionError;
 use jsonrpc_core::{Error, ErrorCode};
+use serde_json::json;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RPCError {
@@ -13,4 +15,17 @@ impl RPCError {
             data: None,
         }
     }
+
+    /// Error of a transaction rejected by verification, `data` carries the
+    /// stable `TransactionError::code` and the structured error.
+    pub fn transaction_rejected(err: TransactionError) -> Error {
+        Error {
+            code: ErrorCode::ServerError(RPCError::Invalid as i64),
+            message: err.to_string(),
+            data: Some(json!({
+                "code": err.code(),
+                "error": err,
+            })),
+        }
+    }
 }
@@ -1,7 +1,11 @@
+mod auth;
 mod config;
 mod error;
 mod module;
+mod schema;
 mod server;
+mod tcp;
 
-pub use crate::config::Config;
+pub use crate::config::{Auth, Config, Module};
+pub use crate::schema::openrpc_document;
 pub use crate::server::RpcServer;
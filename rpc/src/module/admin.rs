@@ -0,0 +1,51 @@
+use crate::error::RPCError;
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use numext_fixed_hash::H256;
+use std::path::Path;
+
+#[rpc]
+pub trait AdminRpc {
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"backup_store","params": ["/path/to/backup"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "backup_store")]
+    fn backup_store(&self, target: String) -> Result<()>;
+
+    // curl -d '{"params": [], "method": "clear_tx_pool", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
+    #[rpc(name = "clear_tx_pool")]
+    fn clear_tx_pool(&self) -> Result<()>;
+
+    // curl -d '{"params": ["0x..."], "method": "remove_transaction", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
+    #[rpc(name = "remove_transaction")]
+    fn remove_transaction(&self, _hash: H256) -> Result<Vec<H256>>;
+}
+
+pub(crate) struct AdminRpcImpl<CS> {
+    pub shared: Shared<CS>,
+}
+
+impl<CS: ChainStore + 'static> AdminRpc for AdminRpcImpl<CS> {
+    fn backup_store(&self, target: String) -> Result<()> {
+        self.shared
+            .store()
+            .backup(Path::new(&target))
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))
+    }
+
+    fn clear_tx_pool(&self) -> Result<()> {
+        self.shared.chain_state().lock().clear_tx_pool();
+        Ok(())
+    }
+
+    fn remove_transaction(&self, hash: H256) -> Result<Vec<H256>> {
+        let removed = self.shared.chain_state().lock().remove_tx_from_pool(&hash);
+        if removed.is_empty() {
+            return Err(RPCError::custom(
+                RPCError::Invalid,
+                "transaction is not in the pool".to_owned(),
+            ));
+        }
+        Ok(removed.iter().map(|tx| tx.hash().to_owned()).collect())
+    }
+}
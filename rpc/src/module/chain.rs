@@ -1,5 +1,9 @@
 use crate::error::RPCError;
+use bincode::serialize;
+use ckb_core::block::Block;
 use ckb_core::cell::{CellProvider, CellStatus};
+use ckb_core::header::Header;
+use ckb_core::transaction::{Capacity, Transaction};
 use ckb_core::{transaction::ProposalShortId, BlockNumber};
 use ckb_shared::shared::Shared;
 use ckb_store::ChainStore;
@@ -7,24 +11,63 @@ use ckb_traits::ChainProvider;
 use jsonrpc_core::{Error, Result};
 use jsonrpc_derive::rpc;
 use jsonrpc_types::{
-    BlockView, CellOutPoint, CellOutputWithOutPoint, CellWithStatus, EpochExt, HeaderView,
-    OutPoint, TransactionWithStatus,
+    BlockView, BlockWithExtView, CellOutPoint, CellOutputWithOutPoint, CellWithStatus,
+    CellbaseOutputCapacityDetails, EpochExt, HeaderView, JsonBytes, OutPoint, ResponseFormat,
+    TransactionView, TransactionWithStatus, TxStatus, VERBOSITY_JSON, VERBOSITY_JSON_WITH_EXT,
+    VERBOSITY_SERIALIZED,
 };
 use numext_fixed_hash::H256;
 use std::convert::TryInto;
 
 pub const PAGE_SIZE: u64 = 100;
 
+// The current consensus does not reward the proposals, the cellbase claims
+// the primary reward and the fees of the committed transactions
+fn cellbase_output_capacity_details(
+    block: &Block,
+    primary: Capacity,
+    txs_fees: &[Capacity],
+) -> Result<CellbaseOutputCapacityDetails> {
+    // Fees are only recorded once the transactions are verified
+    if txs_fees.len() + 1 != block.transactions().len() {
+        return Err(RPCError::custom(
+            RPCError::Invalid,
+            "transactions of the block are not verified".to_owned(),
+        ));
+    }
+    let tx_fee = txs_fees
+        .iter()
+        .try_fold(Capacity::zero(), |acc, fee| acc.safe_add(*fee))
+        .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))?;
+    Ok(CellbaseOutputCapacityDetails {
+        primary: primary.to_string(),
+        proposal_reward: Capacity::zero().to_string(),
+        tx_fee: tx_fee.to_string(),
+    })
+}
+
 #[rpc]
 pub trait ChainRpc {
     #[rpc(name = "get_block")]
-    fn get_block(&self, _hash: H256) -> Result<Option<BlockView>>;
+    fn get_block(
+        &self,
+        _hash: H256,
+        _verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<BlockWithExtView>>>;
 
     #[rpc(name = "get_block_by_number")]
-    fn get_block_by_number(&self, _number: String) -> Result<Option<BlockView>>;
+    fn get_block_by_number(
+        &self,
+        _number: String,
+        _verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<BlockWithExtView>>>;
 
     #[rpc(name = "get_transaction")]
-    fn get_transaction(&self, _hash: H256) -> Result<Option<TransactionWithStatus>>;
+    fn get_transaction(
+        &self,
+        _hash: H256,
+        _verbosity: Option<u32>,
+    ) -> Result<Option<TransactionWithStatus>>;
 
     #[rpc(name = "get_block_hash")]
     fn get_block_hash(&self, _number: String) -> Result<Option<H256>>;
@@ -32,6 +75,20 @@ pub trait ChainRpc {
     #[rpc(name = "get_tip_header")]
     fn get_tip_header(&self) -> Result<HeaderView>;
 
+    #[rpc(name = "get_header")]
+    fn get_header(
+        &self,
+        _hash: H256,
+        _verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<HeaderView>>>;
+
+    #[rpc(name = "get_header_by_number")]
+    fn get_header_by_number(
+        &self,
+        _number: String,
+        _verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<HeaderView>>>;
+
     #[rpc(name = "get_cells_by_lock_hash")]
     fn get_cells_by_lock_hash(
         &self,
@@ -48,6 +105,12 @@ pub trait ChainRpc {
 
     #[rpc(name = "get_current_epoch")]
     fn get_current_epoch(&self) -> Result<EpochExt>;
+
+    #[rpc(name = "get_cellbase_output_capacity_details")]
+    fn get_cellbase_output_capacity_details(
+        &self,
+        _hash: H256,
+    ) -> Result<Option<CellbaseOutputCapacityDetails>>;
 }
 
 pub(crate) struct ChainRpcImpl<CS> {
@@ -55,22 +118,45 @@ pub(crate) struct ChainRpcImpl<CS> {
 }
 
 impl<CS: ChainStore + 'static> ChainRpc for ChainRpcImpl<CS> {
-    fn get_block(&self, hash: H256) -> Result<Option<BlockView>> {
-        Ok(self.shared.block(&hash).as_ref().map(Into::into))
+    fn get_block(
+        &self,
+        hash: H256,
+        verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<BlockWithExtView>>> {
+        let verbosity = parse_verbosity(verbosity, VERBOSITY_JSON_WITH_EXT)?;
+        self.check_unpruned(&hash)?;
+        Ok(self
+            .shared
+            .block(&hash)
+            .map(|block| self.block_response(&block, verbosity)))
     }
 
-    fn get_block_by_number(&self, number: String) -> Result<Option<BlockView>> {
+    fn get_block_by_number(
+        &self,
+        number: String,
+        verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<BlockWithExtView>>> {
+        let number = number
+            .parse::<BlockNumber>()
+            .map_err(|_| Error::parse_error())?;
+        let verbosity = parse_verbosity(verbosity, VERBOSITY_JSON_WITH_EXT)?;
+        let hash = match self.shared.block_hash(number) {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        self.check_unpruned(&hash)?;
         Ok(self
             .shared
-            .block_hash(
-                number
-                    .parse::<BlockNumber>()
-                    .map_err(|_| Error::parse_error())?,
-            )
-            .and_then(|hash| self.shared.block(&hash).as_ref().map(Into::into)))
+            .block(&hash)
+            .map(|block| self.block_response(&block, verbosity)))
     }
 
-    fn get_transaction(&self, hash: H256) -> Result<Option<TransactionWithStatus>> {
+    fn get_transaction(
+        &self,
+        hash: H256,
+        verbosity: Option<u32>,
+    ) -> Result<Option<TransactionWithStatus>> {
+        let verbosity = parse_verbosity(verbosity, VERBOSITY_JSON)?;
         let id = ProposalShortId::from_tx_hash(&hash);
 
         let tx = {
@@ -79,19 +165,34 @@ impl<CS: ChainStore + 'static> ChainRpc for ChainRpcImpl<CS> {
             let tx_pool = chan_state.tx_pool();
             tx_pool
                 .get_tx_from_staging(&id)
-                .map(TransactionWithStatus::with_proposed)
+                .map(|tx| (tx, TxStatus::proposed()))
                 .or_else(|| {
                     tx_pool
                         .get_tx_without_conflict(&id)
-                        .map(TransactionWithStatus::with_pending)
+                        .map(|tx| (tx, TxStatus::pending()))
                 })
         };
 
-        Ok(tx.or_else(|| {
+        let tx = tx.or_else(|| {
             self.shared
                 .get_transaction(&hash)
-                .map(|(tx, block_hash)| TransactionWithStatus::with_committed(tx, block_hash))
-        }))
+                .map(|(tx, block_hash)| (tx, TxStatus::committed(block_hash)))
+        });
+        if let Some((tx, tx_status)) = tx {
+            return Ok(Some(TransactionWithStatus {
+                transaction: Some(transaction_response(&tx, verbosity)),
+                tx_status,
+            }));
+        }
+        if let Some(address) = self.shared.store().get_transaction_address(&hash) {
+            self.check_unpruned(&address.block_hash)?;
+        }
+
+        let chain_state = self.shared.chain_state().lock();
+        Ok(chain_state
+            .tx_pool()
+            .get_rejection(&hash)
+            .map(|rejection| TransactionWithStatus::with_rejected(rejection.reason.to_string())))
     }
 
     fn get_block_hash(&self, number: String) -> Result<Option<H256>> {
@@ -112,6 +213,34 @@ impl<CS: ChainStore + 'static> ChainRpc for ChainRpcImpl<CS> {
             .expect("tip header exists"))
     }
 
+    fn get_header(
+        &self,
+        hash: H256,
+        verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<HeaderView>>> {
+        let verbosity = parse_verbosity(verbosity, VERBOSITY_JSON)?;
+        Ok(self
+            .shared
+            .block_header(&hash)
+            .map(|header| header_response(&header, verbosity)))
+    }
+
+    fn get_header_by_number(
+        &self,
+        number: String,
+        verbosity: Option<u32>,
+    ) -> Result<Option<ResponseFormat<HeaderView>>> {
+        let number = number
+            .parse::<BlockNumber>()
+            .map_err(|_| Error::parse_error())?;
+        let verbosity = parse_verbosity(verbosity, VERBOSITY_JSON)?;
+        Ok(self
+            .shared
+            .block_hash(number)
+            .and_then(|hash| self.shared.block_header(&hash))
+            .map(|header| header_response(&header, verbosity)))
+    }
+
     fn get_current_epoch(&self) -> Result<EpochExt> {
         Ok(self
             .shared
@@ -121,6 +250,25 @@ impl<CS: ChainStore + 'static> ChainRpc for ChainRpcImpl<CS> {
             .expect("current_epoch exists"))
     }
 
+    fn get_cellbase_output_capacity_details(
+        &self,
+        hash: H256,
+    ) -> Result<Option<CellbaseOutputCapacityDetails>> {
+        self.check_unpruned(&hash)?;
+        let block = match self.shared.block(&hash) {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let primary = self
+            .shared
+            .get_epoch_ext(&hash)
+            .expect("block epoch stored")
+            .block_reward(block.header().number())
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))?;
+        let ext = self.shared.block_ext(&hash).expect("block ext stored");
+        cellbase_output_capacity_details(&block, primary, &ext.txs_fees).map(Some)
+    }
+
     // TODO: we need to build a proper index instead of scanning every time
     fn get_cells_by_lock_hash(
         &self,
@@ -208,3 +356,111 @@ impl<CS: ChainStore + 'static> ChainRpc for ChainRpcImpl<CS> {
         self.get_tip_header().map(|h| h.inner.number)
     }
 }
+
+impl<CS: ChainStore> ChainRpcImpl<CS> {
+    // The pruned blocks are known but their bodies can no longer be served
+    fn check_unpruned(&self, hash: &H256) -> Result<()> {
+        if self.shared.store().is_block_pruned(hash) {
+            Err(RPCError::custom(
+                RPCError::Invalid,
+                format!("the body of block {:#x} is pruned", hash),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn block_response(&self, block: &Block, verbosity: u32) -> ResponseFormat<BlockWithExtView> {
+        if verbosity == VERBOSITY_SERIALIZED {
+            return ResponseFormat::Serialized(JsonBytes::from_vec(
+                serialize(block).expect("serialize block should be ok"),
+            ));
+        }
+
+        let mut view = BlockWithExtView::from(BlockView::from(block));
+        if verbosity == VERBOSITY_JSON_WITH_EXT {
+            if let Some(ext) = self.shared.block_ext(block.header().hash()) {
+                // Fees are only recorded once the transactions are verified
+                if ext.txs_fees.len() + 1 == block.transactions().len() {
+                    view.txs_fees = Some(ext.txs_fees.iter().map(ToString::to_string).collect());
+                }
+                view.txs_cycles = ext
+                    .txs_cycles
+                    .map(|cycles| cycles.iter().map(ToString::to_string).collect());
+            }
+        }
+        ResponseFormat::Json(view)
+    }
+}
+
+// The verbosity defaults to the JSON view
+fn parse_verbosity(verbosity: Option<u32>, max: u32) -> Result<u32> {
+    let verbosity = verbosity.unwrap_or(VERBOSITY_JSON);
+    if verbosity > max {
+        return Err(Error::invalid_params(format!(
+            "verbosity should be at most {}",
+            max
+        )));
+    }
+    Ok(verbosity)
+}
+
+fn header_response(header: &Header, verbosity: u32) -> ResponseFormat<HeaderView> {
+    if verbosity == VERBOSITY_SERIALIZED {
+        ResponseFormat::Serialized(JsonBytes::from_vec(
+            serialize(header).expect("serialize header should be ok"),
+        ))
+    } else {
+        ResponseFormat::Json(header.into())
+    }
+}
+
+fn transaction_response(tx: &Transaction, verbosity: u32) -> ResponseFormat<TransactionView> {
+    if verbosity == VERBOSITY_SERIALIZED {
+        ResponseFormat::Serialized(JsonBytes::from_vec(
+            serialize(tx).expect("serialize transaction should be ok"),
+        ))
+    } else {
+        ResponseFormat::Json(tx.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_core::block::BlockBuilder;
+    use ckb_core::transaction::{CellInput, TransactionBuilder};
+
+    #[test]
+    fn test_cellbase_output_capacity_details() {
+        let cellbase = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(1))
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(Default::default(), 0, vec![]))
+            .build();
+        let block = BlockBuilder::default()
+            .transaction(cellbase)
+            .transactions(vec![tx.clone(), tx])
+            .build();
+        let primary = Capacity::shannons(5000);
+
+        let details = cellbase_output_capacity_details(
+            &block,
+            primary,
+            &[Capacity::shannons(100), Capacity::shannons(20)],
+        )
+        .unwrap();
+        assert_eq!(
+            details,
+            CellbaseOutputCapacityDetails {
+                primary: "5000".to_owned(),
+                proposal_reward: "0".to_owned(),
+                tx_fee: "120".to_owned(),
+            }
+        );
+
+        // The transactions are not verified yet
+        assert!(cellbase_output_capacity_details(&block, primary, &[]).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::RPCError;
+use ckb_core::transaction::Transaction as CoreTransaction;
+use ckb_shared::fee_estimator::{FeeEstimatorService, FEE_HISTORY_BLOCKS};
+use ckb_shared::shared::Shared;
+use ckb_shared::tx_pool::PoolError;
+use ckb_store::ChainStore;
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_types::{DryRunResult, FeeRate, Transaction};
+use std::convert::TryInto;
+
+#[rpc]
+pub trait ExperimentRpc {
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"dry_run_transaction","params": [{"version":2, "deps":[], "inputs":[], "outputs":[]}]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "dry_run_transaction")]
+    fn dry_run_transaction(&self, _tx: Transaction) -> Result<DryRunResult>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"estimate_fee_rate","params": ["10"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "estimate_fee_rate")]
+    fn estimate_fee_rate(&self, _target_blocks: String) -> Result<FeeRate>;
+}
+
+pub(crate) struct ExperimentRpcImpl<CS> {
+    pub shared: Shared<CS>,
+    pub fee_estimator: FeeEstimatorService<CS>,
+}
+
+impl<CS: ChainStore + 'static> ExperimentRpc for ExperimentRpcImpl<CS> {
+    fn dry_run_transaction(&self, tx: Transaction) -> Result<DryRunResult> {
+        let tx: CoreTransaction = tx.try_into().map_err(|_| Error::parse_error())?;
+        let result = {
+            let resolved = self.shared.chain_state().lock().resolve_tx_for_dry_run(&tx);
+            resolved.and_then(|(rtx, context)| context.verify(&rtx))
+        };
+        match result {
+            Ok(cycles) => Ok(DryRunResult {
+                cycles: cycles.to_string(),
+            }),
+            Err(PoolError::InvalidTx(err)) => Err(RPCError::transaction_rejected(err)),
+            Err(e) => Err(RPCError::custom(RPCError::Invalid, e.to_string())),
+        }
+    }
+
+    fn estimate_fee_rate(&self, target_blocks: String) -> Result<FeeRate> {
+        let target_blocks = target_blocks
+            .parse::<u64>()
+            .map_err(|_| Error::parse_error())?;
+        if target_blocks == 0 || target_blocks > FEE_HISTORY_BLOCKS {
+            return Err(Error::invalid_params(format!(
+                "target_blocks should be between 1 and {}",
+                FEE_HISTORY_BLOCKS
+            )));
+        }
+        match self.fee_estimator.estimate_fee_rate(target_blocks) {
+            Some(fee_rate) => Ok(FeeRate {
+                fee_rate: fee_rate.to_string(),
+            }),
+            None => Err(RPCError::custom(
+                RPCError::Invalid,
+                "no fee rate sampled from the recent blocks".to_owned(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::error::RPCError;
+use ckb_core::BlockNumber;
+use ckb_indexer::{
+    CellTransaction as CoreCellTransaction, IndexerStore, LiveCell as CoreLiveCell,
+    LockHashIndexState as CoreLockHashIndexState, TransactionPoint as CoreTransactionPoint,
+};
+use ckb_store::ChainStore;
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_types::{CellTransaction, LiveCell, LockHashIndexState, TransactionPoint};
+use numext_fixed_hash::H256;
+
+const MAX_PAGE_SIZE: usize = 50;
+
+#[rpc]
+pub trait IndexerRpc {
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"index_lock_hash","params": ["0x...", "0"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "index_lock_hash")]
+    fn index_lock_hash(
+        &self,
+        _lock_hash: H256,
+        _index_from: Option<String>,
+    ) -> Result<LockHashIndexState>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"deindex_lock_hash","params": ["0x..."]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "deindex_lock_hash")]
+    fn deindex_lock_hash(&self, _lock_hash: H256) -> Result<()>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_lock_hash_index_states","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_lock_hash_index_states")]
+    fn get_lock_hash_index_states(&self) -> Result<Vec<LockHashIndexState>>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_live_cells_by_lock_hash","params": ["0x...", "0", "50"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_live_cells_by_lock_hash")]
+    fn get_live_cells_by_lock_hash(
+        &self,
+        _lock_hash: H256,
+        _page: String,
+        _per_page: String,
+        _reverse_order: Option<bool>,
+    ) -> Result<Vec<LiveCell>>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_transactions_by_lock_hash","params": ["0x...", "0", "50"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_transactions_by_lock_hash")]
+    fn get_transactions_by_lock_hash(
+        &self,
+        _lock_hash: H256,
+        _page: String,
+        _per_page: String,
+        _reverse_order: Option<bool>,
+    ) -> Result<Vec<CellTransaction>>;
+}
+
+pub(crate) struct IndexerRpcImpl<CS> {
+    pub store: IndexerStore<CS>,
+}
+
+impl<CS: ChainStore + 'static> IndexerRpc for IndexerRpcImpl<CS> {
+    fn index_lock_hash(
+        &self,
+        lock_hash: H256,
+        index_from: Option<String>,
+    ) -> Result<LockHashIndexState> {
+        let index_from = match index_from {
+            Some(number) => Some(
+                number
+                    .parse::<BlockNumber>()
+                    .map_err(|_| Error::parse_error())?,
+            ),
+            None => None,
+        };
+        if let (Some(number), Some(pruned_number)) = (index_from, self.store.pruned_number()) {
+            if number <= pruned_number {
+                return Err(RPCError::custom(
+                    RPCError::Invalid,
+                    format!(
+                        "the bodies of the blocks up to {} are pruned, index from a later block",
+                        pruned_number
+                    ),
+                ));
+            }
+        }
+        let state = self.store.insert_lock_hash(&lock_hash, index_from);
+        Ok(to_json_state(lock_hash, state))
+    }
+
+    fn deindex_lock_hash(&self, lock_hash: H256) -> Result<()> {
+        if self.store.remove_lock_hash(&lock_hash) {
+            Ok(())
+        } else {
+            Err(RPCError::custom(
+                RPCError::Invalid,
+                "lock hash is not indexed".to_owned(),
+            ))
+        }
+    }
+
+    fn get_lock_hash_index_states(&self) -> Result<Vec<LockHashIndexState>> {
+        Ok(self
+            .store
+            .get_lock_hash_index_states()
+            .into_iter()
+            .map(|(lock_hash, state)| to_json_state(lock_hash, state))
+            .collect())
+    }
+
+    fn get_live_cells_by_lock_hash(
+        &self,
+        lock_hash: H256,
+        page: String,
+        per_page: String,
+        reverse_order: Option<bool>,
+    ) -> Result<Vec<LiveCell>> {
+        let (skip, take) = pagination(&page, &per_page)?;
+        Ok(self
+            .store
+            .get_live_cells(&lock_hash, skip, take, reverse_order.unwrap_or(false))
+            .into_iter()
+            .map(
+                |CoreLiveCell {
+                     created_by,
+                     cell_output,
+                 }| LiveCell {
+                    created_by: to_json_point(created_by),
+                    cell_output: cell_output.into(),
+                },
+            )
+            .collect())
+    }
+
+    fn get_transactions_by_lock_hash(
+        &self,
+        lock_hash: H256,
+        page: String,
+        per_page: String,
+        reverse_order: Option<bool>,
+    ) -> Result<Vec<CellTransaction>> {
+        let (skip, take) = pagination(&page, &per_page)?;
+        Ok(self
+            .store
+            .get_transactions(&lock_hash, skip, take, reverse_order.unwrap_or(false))
+            .into_iter()
+            .map(
+                |CoreCellTransaction {
+                     created_by,
+                     consumed_by,
+                 }| CellTransaction {
+                    created_by: to_json_point(created_by),
+                    consumed_by: consumed_by.map(to_json_point),
+                },
+            )
+            .collect())
+    }
+}
+
+// Pages are numbered from 0
+fn pagination(page: &str, per_page: &str) -> Result<(usize, usize)> {
+    let page = page.parse::<usize>().map_err(|_| Error::parse_error())?;
+    let per_page = per_page
+        .parse::<usize>()
+        .map_err(|_| Error::parse_error())?;
+    if per_page > MAX_PAGE_SIZE {
+        return Err(RPCError::custom(
+            RPCError::Invalid,
+            "too large page size".to_owned(),
+        ));
+    }
+    Ok((page.saturating_mul(per_page), per_page))
+}
+
+fn to_json_point(point: CoreTransactionPoint) -> TransactionPoint {
+    TransactionPoint {
+        block_number: point.block_number.to_string(),
+        tx_hash: point.tx_hash,
+        index: point.index,
+    }
+}
+
+fn to_json_state(lock_hash: H256, state: CoreLockHashIndexState) -> LockHashIndexState {
+    LockHashIndexState {
+        lock_hash,
+        block_number: state.block_number.to_string(),
+        block_hash: state.block_hash,
+    }
+}
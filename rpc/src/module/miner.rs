@@ -1,5 +1,6 @@
 use ckb_chain::chain::ChainController;
 use ckb_core::block::Block as CoreBlock;
+use ckb_core::script::Script as CoreScript;
 use ckb_miner::BlockAssemblerController;
 use ckb_network::NetworkController;
 use ckb_protocol::RelayMessage;
@@ -10,29 +11,50 @@ use ckb_traits::ChainProvider;
 use ckb_verification::{HeaderResolverWrapper, HeaderVerifier, Verifier};
 use faketime::unix_time_as_millis;
 use flatbuffers::FlatBufferBuilder;
-use jsonrpc_core::{Error, Result};
+use jsonrpc_core::futures::future::{self, Future};
+use jsonrpc_core::{BoxFuture, Error, Result};
 use jsonrpc_derive::rpc;
-use jsonrpc_types::{Block, BlockTemplate};
+use jsonrpc_types::{Block, BlockTemplate, BlockTemplateDelta, MiningInfo, Script};
 use log::{debug, error};
 use numext_fixed_hash::H256;
 use std::collections::HashSet;
 use std::convert::TryInto;
 use std::sync::Arc;
+use std::time::Duration;
+
+// Upper bound of a long-polling get_block_template request, in milliseconds
+const MAX_BLOCK_TEMPLATE_WAIT: u64 = 30_000;
 
 #[rpc]
 pub trait MinerRpc {
     // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template","params": ["0x1b1c832d02fdb4339f9868c8a8636c3d9dd10bd53ac7ce99595825bd6beeffb3", 1000, 1000]}' -H 'content-type:application/json' 'http://localhost:8114'
+    // Pass the work_id of the current template to wait until it changes, at most `wait_timeout` milliseconds:
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template","params": [null, null, null, "12", "10000"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    // Pass a lock to pay the cellbase to it instead of the lock of the block_assembler config:
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template","params": [null, null, null, null, null, {"code_hash": "0x0000000000000000000000000000000000000000000000000000000000000001", "args": []}]}' -H 'content-type:application/json' 'http://localhost:8114'
     #[rpc(name = "get_block_template")]
     fn get_block_template(
         &self,
         bytes_limit: Option<String>,
         proposals_limit: Option<String>,
         max_version: Option<u32>,
-    ) -> Result<BlockTemplate>;
+        work_id: Option<String>,
+        wait_timeout: Option<String>,
+        lock: Option<Script>,
+    ) -> BoxFuture<BlockTemplate>;
+
+    // Returns the changes of the current template relative to the one of `work_id`, null if unknown:
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template_delta","params": ["12"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_block_template_delta")]
+    fn get_block_template_delta(&self, work_id: String) -> BoxFuture<Option<BlockTemplateDelta>>;
 
     // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"submit_block","params": [{"header":{}, "uncles":[], "transactions":[], "proposals":[]}]}' -H 'content-type:application/json' 'http://localhost:8114'
     #[rpc(name = "submit_block")]
     fn submit_block(&self, _work_id: String, _data: Block) -> Result<Option<H256>>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_mining_info","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_mining_info")]
+    fn get_mining_info(&self) -> Result<MiningInfo>;
 }
 
 pub(crate) struct MinerRpcImpl<CS> {
@@ -48,20 +70,45 @@ impl<CS: ChainStore + 'static> MinerRpc for MinerRpcImpl<CS> {
         bytes_limit: Option<String>,
         proposals_limit: Option<String>,
         max_version: Option<u32>,
-    ) -> Result<BlockTemplate> {
-        let bytes_limit = match bytes_limit {
-            Some(b) => Some(b.parse::<u64>().map_err(|_| Error::parse_error())?),
-            None => None,
+        work_id: Option<String>,
+        wait_timeout: Option<String>,
+        lock: Option<Script>,
+    ) -> BoxFuture<BlockTemplate> {
+        let params = parse_block_template_params(bytes_limit, proposals_limit, wait_timeout, lock);
+        let (bytes_limit, proposals_limit, wait_timeout, lock) = match params {
+            Ok(params) => params,
+            Err(err) => return Box::new(future::err(err)),
         };
 
-        let proposals_limit = match proposals_limit {
-            Some(b) => Some(b.parse::<u64>().map_err(|_| Error::parse_error())?),
-            None => None,
-        };
+        match work_id {
+            // Long polling resolves once the template changes, without holding
+            // a serving thread meanwhile
+            Some(work_id) => Box::new(
+                self.block_assembler
+                    .poll_block_template(
+                        bytes_limit,
+                        proposals_limit,
+                        max_version,
+                        lock,
+                        work_id,
+                        Duration::from_millis(wait_timeout.min(MAX_BLOCK_TEMPLATE_WAIT)),
+                    )
+                    .map_err(|_| Error::internal_error()),
+            ),
+            None => Box::new(
+                self.block_assembler
+                    .get_block_template(bytes_limit, proposals_limit, max_version, lock)
+                    .map_err(|_| Error::internal_error()),
+            ),
+        }
+    }
 
-        self.block_assembler
-            .get_block_template(bytes_limit, proposals_limit, max_version)
-            .map_err(|_| Error::internal_error())
+    fn get_block_template_delta(&self, work_id: String) -> BoxFuture<Option<BlockTemplateDelta>> {
+        Box::new(
+            self.block_assembler
+                .get_block_template_delta(work_id)
+                .map_err(|_| Error::internal_error()),
+        )
     }
 
     fn submit_block(&self, work_id: String, data: Block) -> Result<Option<H256>> {
@@ -74,17 +121,25 @@ impl<CS: ChainStore + 'static> MinerRpc for MinerRpcImpl<CS> {
         debug!(target: "rpc", "[{}] submit block", work_id);
         let block: Arc<CoreBlock> = Arc::new(data.try_into().map_err(|_| Error::parse_error())?);
         let resolver = HeaderResolverWrapper::new(block.header(), self.shared.clone());
-        let header_verify_ret = {
+        let (header_verify_ret, stale) = {
             let chain_state = self.shared.chain_state().lock();
             let header_verifier = HeaderVerifier::new(
                 &*chain_state,
                 Arc::clone(&self.shared.consensus().pow_engine()),
             );
-            header_verifier.verify(&resolver)
+            // The tip moved on since the template was issued
+            let stale = block.header().parent_hash() != chain_state.tip_hash();
+            (header_verifier.verify(&resolver), stale)
         };
+        let stats = self.block_assembler.mining_stats();
+        stats.block_submitted();
+        if stale {
+            stats.block_stale();
+        }
         if header_verify_ret.is_ok() {
             let ret = self.chain.process_block(Arc::clone(&block));
             if ret.is_ok() {
+                stats.block_accepted();
                 debug!(target: "rpc", "[block_relay] announce new block {} {}", block.header().hash(), unix_time_as_millis());
                 // announce new block
 
@@ -109,4 +164,37 @@ impl<CS: ChainStore + 'static> MinerRpc for MinerRpcImpl<CS> {
             Ok(None)
         }
     }
+
+    fn get_mining_info(&self) -> Result<MiningInfo> {
+        Ok(self.block_assembler.mining_stats().info())
+    }
+}
+
+fn parse_block_template_params(
+    bytes_limit: Option<String>,
+    proposals_limit: Option<String>,
+    wait_timeout: Option<String>,
+    lock: Option<Script>,
+) -> Result<(Option<u64>, Option<u64>, u64, Option<CoreScript>)> {
+    let bytes_limit = match bytes_limit {
+        Some(b) => Some(b.parse::<u64>().map_err(|_| Error::parse_error())?),
+        None => None,
+    };
+
+    let proposals_limit = match proposals_limit {
+        Some(b) => Some(b.parse::<u64>().map_err(|_| Error::parse_error())?),
+        None => None,
+    };
+
+    let wait_timeout = match wait_timeout {
+        Some(t) => t.parse::<u64>().map_err(|_| Error::parse_error())?,
+        None => MAX_BLOCK_TEMPLATE_WAIT,
+    };
+
+    let lock = match lock {
+        Some(lock) => Some(lock.try_into().map_err(|_| Error::parse_error())?),
+        None => None,
+    };
+
+    Ok((bytes_limit, proposals_limit, wait_timeout, lock))
 }
@@ -1,13 +1,23 @@
+mod admin;
 mod chain;
+mod experiment;
+mod indexer;
 mod miner;
 mod net;
 mod pool;
+mod stats;
+mod subscription;
 mod test;
 mod trace;
 
+pub(crate) use self::admin::{AdminRpc, AdminRpcImpl};
 pub(crate) use self::chain::{ChainRpc, ChainRpcImpl};
+pub(crate) use self::experiment::{ExperimentRpc, ExperimentRpcImpl};
+pub(crate) use self::indexer::{IndexerRpc, IndexerRpcImpl};
 pub(crate) use self::miner::{MinerRpc, MinerRpcImpl};
 pub(crate) use self::net::{NetworkRpc, NetworkRpcImpl};
-pub(crate) use self::pool::{PoolRpc, PoolRpcImpl};
+pub(crate) use self::pool::{PoolRpc, PoolRpcImpl, TxSubmitter};
+pub(crate) use self::stats::{StatsRpc, StatsRpcImpl};
+pub(crate) use self::subscription::{SubscriptionRpc, SubscriptionRpcImpl, Topic};
 pub(crate) use self::test::{IntegrationTestRpc, IntegrationTestRpcImpl};
 pub(crate) use self::trace::{TraceRpc, TraceRpcImpl};
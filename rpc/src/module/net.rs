@@ -1,10 +1,18 @@
 use build_info::{get_version, Version};
-use ckb_network::NetworkController;
-use jsonrpc_core::Result;
+use ckb_network::{IpNetwork, NetworkController};
+use ckb_sync::Peers;
+use jsonrpc_core::{Error, Result};
 use jsonrpc_derive::rpc;
-use jsonrpc_types::{Node, NodeAddress};
+use jsonrpc_types::{
+    BannedAddress, Node, NodeAddress, NodeProtocol, PeerSyncState, PeerTraffic, RemoteNode,
+};
+use std::sync::Arc;
+use std::time::Duration;
 
 const MAX_ADDRS: usize = 50;
+// 24 hours
+const DEFAULT_BAN_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_BAN_REASON: &str = "banned by the rpc";
 
 #[rpc]
 pub trait NetworkRpc {
@@ -14,11 +22,26 @@ pub trait NetworkRpc {
 
     // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_peers","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
     #[rpc(name = "get_peers")]
-    fn get_peers(&self) -> Result<Vec<Node>>;
+    fn get_peers(&self) -> Result<Vec<RemoteNode>>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_banned_addresses","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_banned_addresses")]
+    fn get_banned_addresses(&self) -> Result<Vec<BannedAddress>>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"set_ban","params": ["192.168.0.2", "insert", "86400000", "spam"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "set_ban")]
+    fn set_ban(
+        &self,
+        address: String,
+        command: String,
+        ban_time: Option<String>,
+        reason: Option<String>,
+    ) -> Result<()>;
 }
 
 pub(crate) struct NetworkRpcImpl {
     pub network_controller: NetworkController,
+    pub sync_peers: Arc<Peers>,
 }
 
 impl NetworkRpc for NetworkRpcImpl {
@@ -33,29 +56,132 @@ impl NetworkRpc for NetworkRpcImpl {
                 .into_iter()
                 .map(|(address, score)| NodeAddress { address, score })
                 .collect(),
+            listen_addresses: self
+                .network_controller
+                .listen_addresses()
+                .into_iter()
+                .map(|address| address.to_string())
+                .collect(),
+            observed_addresses: self
+                .network_controller
+                .observed_addresses(MAX_ADDRS as u32)
+                .into_iter()
+                .map(|address| address.to_string())
+                .collect(),
         })
     }
 
-    fn get_peers(&self) -> Result<Vec<Node>> {
+    fn get_peers(&self) -> Result<Vec<RemoteNode>> {
         let peers = self.network_controller.connected_peers();
         Ok(peers
             .into_iter()
-            .map(|(peer_id, peer, addresses)| Node {
-                is_outbound: Some(peer.is_outbound()),
-                version: peer
-                    .identify_info
-                    .map(|info| info.client_version)
-                    .unwrap_or_else(|| "unknown".to_string()),
-                node_id: peer_id.to_base58(),
-                // TODO how to get correct port and score?
-                addresses: addresses
-                    .into_iter()
-                    .map(|(address, score)| NodeAddress {
-                        address: address.to_string(),
-                        score,
+            .map(|(peer_id, peer, addresses)| {
+                let sync_info = self.sync_peers.sync_info(peer.session_id);
+                let mut protocols = peer
+                    .protocols
+                    .iter()
+                    .map(|(id, version)| NodeProtocol {
+                        id: id.value().to_string(),
+                        version: version.to_owned(),
                     })
-                    .collect(),
+                    .collect::<Vec<_>>();
+                protocols.sort_by(|a, b| a.id.cmp(&b.id));
+                RemoteNode {
+                    is_outbound: peer.is_outbound(),
+                    version: peer
+                        .identify_info
+                        .map(|info| info.client_version)
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    node_id: peer_id.to_base58(),
+                    // TODO how to get correct port and score?
+                    addresses: addresses
+                        .into_iter()
+                        .map(|(address, score)| NodeAddress {
+                            address: address.to_string(),
+                            score,
+                        })
+                        .collect(),
+                    connected_address: peer.address.to_string(),
+                    protocols,
+                    connected_duration: peer.connected_time.elapsed().as_millis().to_string(),
+                    last_ping_duration: peer.ping.map(|ping| ping.as_millis().to_string()),
+                    sync_state: PeerSyncState {
+                        best_known_header_number: sync_info
+                            .best_known_header
+                            .as_ref()
+                            .map(|(number, _)| number.to_string()),
+                        best_known_header_hash: sync_info.best_known_header.map(|(_, hash)| hash),
+                        last_common_header_number: sync_info
+                            .last_common_header
+                            .as_ref()
+                            .map(|(number, _)| number.to_string()),
+                        last_common_header_hash: sync_info.last_common_header.map(|(_, hash)| hash),
+                        inflight_blocks: sync_info.inflight_blocks.to_string(),
+                        sync_started: sync_info.sync_started,
+                    },
+                    traffic: self
+                        .network_controller
+                        .peer_traffic(peer.session_id)
+                        .map(|traffic| PeerTraffic {
+                            bytes_sent: traffic.total.bytes_sent.to_string(),
+                            bytes_received: traffic.total.bytes_received.to_string(),
+                        }),
+                }
             })
             .collect())
     }
+
+    fn get_banned_addresses(&self) -> Result<Vec<BannedAddress>> {
+        Ok(self
+            .network_controller
+            .get_banned_addrs()
+            .into_iter()
+            .map(|banned| BannedAddress {
+                address: banned.address.to_string(),
+                ban_until: banned.ban_until.as_millis().to_string(),
+                ban_reason: banned.ban_reason,
+                created_at: banned.created_at.as_millis().to_string(),
+            })
+            .collect())
+    }
+
+    fn set_ban(
+        &self,
+        address: String,
+        command: String,
+        ban_time: Option<String>,
+        reason: Option<String>,
+    ) -> Result<()> {
+        let address = address
+            .parse::<IpNetwork>()
+            .map_err(|err| Error::invalid_params(format!("invalid address: {}", err)))?;
+        match command.as_str() {
+            "insert" => {
+                let ban_time =
+                    match ban_time {
+                        Some(ban_time) => ban_time
+                            .parse::<u64>()
+                            .map(Duration::from_millis)
+                            .map_err(|err| {
+                                Error::invalid_params(format!("invalid ban_time: {}", err))
+                            })?,
+                        None => DEFAULT_BAN_TIME,
+                    };
+                let reason = reason.unwrap_or_else(|| DEFAULT_BAN_REASON.to_owned());
+                self.network_controller.ban(address, ban_time, reason);
+                Ok(())
+            }
+            "delete" => {
+                if self.network_controller.unban(&address) {
+                    Ok(())
+                } else {
+                    Err(Error::invalid_params(format!("{} is not banned", address)))
+                }
+            }
+            _ => Err(Error::invalid_params(format!(
+                "invalid command {}, expected insert or delete",
+                command
+            ))),
+        }
+    }
 }
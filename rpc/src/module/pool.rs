@@ -1,16 +1,33 @@
 use crate::error::RPCError;
-use ckb_core::transaction::Transaction as CoreTransaction;
+use ckb_core::transaction::{ProposalShortId, Transaction as CoreTransaction};
+use ckb_core::Cycle;
 use ckb_network::NetworkController;
+use ckb_notify::NotifyController;
 use ckb_protocol::RelayMessage;
 use ckb_shared::shared::Shared;
+use ckb_shared::tx_pool::{PoolEntry, PoolError};
 use ckb_store::ChainStore;
 use ckb_sync::NetworkProtocol;
+use ckb_util::Mutex;
+use crossbeam_channel::Sender;
 use flatbuffers::FlatBufferBuilder;
 use jsonrpc_core::{Error, Result};
 use jsonrpc_derive::rpc;
-use jsonrpc_types::{Transaction, TxPoolInfo};
+use jsonrpc_types::{
+    RawTxPool, Transaction, TxPoolEntries, TxPoolEntry, TxPoolIds, TxPoolInfo, TxRejection,
+    TxStatus,
+};
+use log::debug;
 use numext_fixed_hash::H256;
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::sync::Arc;
+use std::thread;
+
+// Submissions waiting for a verification worker beyond it are refused
+const SUBMIT_QUEUE_SIZE: usize = 1024;
+// Max verbose entries of get_raw_tx_pool, also the default page size
+const MAX_PAGE_SIZE: usize = 100;
 
 #[rpc]
 pub trait PoolRpc {
@@ -18,37 +35,203 @@ pub trait PoolRpc {
     #[rpc(name = "send_transaction")]
     fn send_transaction(&self, _tx: Transaction) -> Result<H256>;
 
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"submit_transaction","params": [{"version":2, "deps":[], "inputs":[], "outputs":[]}]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "submit_transaction")]
+    fn submit_transaction(&self, _tx: Transaction) -> Result<H256>;
+
+    // curl -d '{"params": ["0x..."], "method": "get_transaction_status", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
+    #[rpc(name = "get_transaction_status")]
+    fn get_transaction_status(&self, _hash: H256) -> Result<Option<TxStatus>>;
+
     // curl -d '{"params": [], "method": "tx_pool_info", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
     #[rpc(name = "tx_pool_info")]
     fn tx_pool_info(&self) -> Result<TxPoolInfo>;
+
+    // curl -d '{"params": [true, "0", "100"], "method": "get_raw_tx_pool", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
+    #[rpc(name = "get_raw_tx_pool")]
+    fn get_raw_tx_pool(
+        &self,
+        _verbose: Option<bool>,
+        _page: Option<String>,
+        _per_page: Option<String>,
+    ) -> Result<RawTxPool>;
+
+    // curl -d '{"params": ["0x..."], "method": "get_transaction_rejection", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
+    #[rpc(name = "get_transaction_rejection")]
+    fn get_transaction_rejection(&self, _hash: H256) -> Result<Option<TxRejection>>;
 }
 
 pub(crate) struct PoolRpcImpl<CS> {
     pub network_controller: NetworkController,
+    pub notify_controller: NotifyController,
     pub shared: Shared<CS>,
+    pub tx_submitter: TxSubmitter,
+}
+
+/// Verifies the transactions of `submit_transaction` on the worker threads,
+/// the hashes are kept until the verification ends. The chain state is only
+/// locked to resolve a transaction and to add it to the pool, its scripts run
+/// in between.
+#[derive(Clone)]
+pub(crate) struct TxSubmitter {
+    sender: Sender<CoreTransaction>,
+    verifying: Arc<Mutex<HashSet<H256>>>,
+}
+
+impl TxSubmitter {
+    pub fn new<CS: ChainStore + 'static>(
+        workers: usize,
+        shared: Shared<CS>,
+        network_controller: NetworkController,
+        notify_controller: NotifyController,
+    ) -> TxSubmitter {
+        let (sender, receiver) = crossbeam_channel::bounded::<CoreTransaction>(SUBMIT_QUEUE_SIZE);
+        let verifying = Arc::new(Mutex::new(HashSet::new()));
+        for i in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            let verifying = Arc::clone(&verifying);
+            let shared = shared.clone();
+            let network_controller = network_controller.clone();
+            let notify_controller = notify_controller.clone();
+            thread::Builder::new()
+                .name(format!("tx-verify-{}", i))
+                .spawn(move || {
+                    // Stops once the rpc server and its senders are dropped
+                    while let Ok(tx) = receiver.recv() {
+                        let tx_hash = tx.hash().to_owned();
+                        // The transactions resolved only once the replaced ones are
+                        // removed, and the orphans, are verified under the lock
+                        let verified = {
+                            let resolved = shared.chain_state().lock().resolve_tx_for_pool(&tx);
+                            resolved.ok().map(|(rtx, context)| context.verify(&rtx))
+                        };
+                        let ret = add_tx_to_pool(
+                            &shared,
+                            &network_controller,
+                            &notify_controller,
+                            tx,
+                            verified,
+                        );
+                        if let Err(err) = ret {
+                            debug!(target: "rpc", "submitted tx {:#x} rejected {:?}", tx_hash, err);
+                        }
+                        verifying.lock().remove(&tx_hash);
+                    }
+                })
+                .expect("Start tx verify worker failed");
+        }
+        TxSubmitter { sender, verifying }
+    }
+
+    /// Queues the transaction for the verification, returns false when the
+    /// queue is full
+    pub fn submit(&self, tx: CoreTransaction) -> bool {
+        let tx_hash = tx.hash().to_owned();
+        if !self.verifying.lock().insert(tx_hash.clone()) {
+            return true;
+        }
+        if self.sender.try_send(tx).is_err() {
+            self.verifying.lock().remove(&tx_hash);
+            return false;
+        }
+        true
+    }
+
+    pub fn is_verifying(&self, hash: &H256) -> bool {
+        self.verifying.lock().contains(hash)
+    }
+}
+
+// Adds the transaction to the pool, announcing its hash to the peers once it
+// is accepted, and publishes it to the subscribers either way. The result of
+// its verification outside the chain state is passed if any.
+fn add_tx_to_pool<CS: ChainStore>(
+    shared: &Shared<CS>,
+    network_controller: &NetworkController,
+    notify_controller: &NotifyController,
+    tx: CoreTransaction,
+    verified: Option<std::result::Result<Cycle, PoolError>>,
+) -> std::result::Result<H256, PoolError> {
+    let result = {
+        let chain_state = shared.chain_state().lock();
+        match verified {
+            Some(verified) => chain_state.add_verified_tx_to_pool(tx.clone(), verified),
+            None => chain_state.add_tx_to_pool(tx.clone()),
+        }
+    };
+
+    match result {
+        Ok(_cycles) => {
+            // Peers fetch the transaction after the hash announcement
+            let fbb = &mut FlatBufferBuilder::new();
+            let message = RelayMessage::build_transaction_hashes(fbb, &[tx.hash().to_owned()]);
+            fbb.finish(message, None);
+            let data = fbb.finished_data().into();
+            network_controller.broadcast(NetworkProtocol::RELAY.into(), data);
+            let tx_hash = tx.hash().to_owned();
+            notify_controller.notify_new_transaction(Arc::new(tx));
+            Ok(tx_hash)
+        }
+        Err(e) => {
+            notify_controller.notify_rejected_transaction(Arc::new((tx, e.to_string())));
+            Err(e)
+        }
+    }
 }
 
 impl<CS: ChainStore + 'static> PoolRpc for PoolRpcImpl<CS> {
     fn send_transaction(&self, tx: Transaction) -> Result<H256> {
         let tx: CoreTransaction = tx.try_into().map_err(|_| Error::parse_error())?;
 
-        let result = {
-            let chain_state = self.shared.chain_state().lock();
-            chain_state.add_tx_to_pool(tx.clone())
-        };
+        add_tx_to_pool(
+            &self.shared,
+            &self.network_controller,
+            &self.notify_controller,
+            tx,
+            None,
+        )
+        .map_err(|e| match e {
+            PoolError::InvalidTx(err) => RPCError::transaction_rejected(err),
+            e => RPCError::custom(RPCError::Invalid, e.to_string()),
+        })
+    }
+
+    fn submit_transaction(&self, tx: Transaction) -> Result<H256> {
+        let tx: CoreTransaction = tx.try_into().map_err(|_| Error::parse_error())?;
+        let tx_hash = tx.hash().to_owned();
+        if self.tx_submitter.submit(tx) {
+            Ok(tx_hash)
+        } else {
+            Err(RPCError::custom(
+                RPCError::Invalid,
+                "too many transactions waiting for the verification".to_owned(),
+            ))
+        }
+    }
 
-        match result {
-            Ok(cycles) => {
-                let fbb = &mut FlatBufferBuilder::new();
-                let message = RelayMessage::build_transaction(fbb, &tx, cycles);
-                fbb.finish(message, None);
-                let data = fbb.finished_data().into();
-                self.network_controller
-                    .broadcast(NetworkProtocol::RELAY.into(), data);
-                Ok(tx.hash().to_owned())
-            }
-            Err(e) => Err(RPCError::custom(RPCError::Invalid, e.to_string())),
+    fn get_transaction_status(&self, hash: H256) -> Result<Option<TxStatus>> {
+        if self.tx_submitter.is_verifying(&hash) {
+            return Ok(Some(TxStatus::pending_verification()));
         }
+        let id = ProposalShortId::from_tx_hash(&hash);
+        let chain_state = self.shared.chain_state().lock();
+        let tx_pool = chain_state.tx_pool();
+        let is_pool_tx = |tx: CoreTransaction| tx.hash() == &hash;
+        let status = if tx_pool.get_tx_from_staging(&id).map_or(false, is_pool_tx) {
+            Some(TxStatus::proposed())
+        } else if tx_pool
+            .get_tx_without_conflict(&id)
+            .map_or(false, is_pool_tx)
+        {
+            Some(TxStatus::pending())
+        } else if let Some(address) = self.shared.store().get_transaction_address(&hash) {
+            Some(TxStatus::committed(address.block_hash))
+        } else {
+            tx_pool
+                .get_rejection(&hash)
+                .map(|rejection| TxStatus::rejected(rejection.reason.to_string()))
+        };
+        Ok(status)
     }
 
     fn tx_pool_info(&self) -> Result<TxPoolInfo> {
@@ -59,6 +242,76 @@ impl<CS: ChainStore + 'static> PoolRpc for PoolRpcImpl<CS> {
             staging: tx_pool.staging_size(),
             orphan: tx_pool.orphan_size(),
             last_txs_updated_at: chain_state.get_last_txs_updated_at().to_string(),
+            total_tx_size: tx_pool.backlog_bytes().to_string(),
+            total_tx_cycles: tx_pool.total_tx_cycles().to_string(),
+            min_fee_rate: tx_pool.min_fee_rate().to_string(),
         })
     }
+
+    fn get_raw_tx_pool(
+        &self,
+        verbose: Option<bool>,
+        page: Option<String>,
+        per_page: Option<String>,
+    ) -> Result<RawTxPool> {
+        let page = match page {
+            Some(page) => page.parse::<usize>().map_err(|_| Error::parse_error())?,
+            None => 0,
+        };
+        let per_page = match per_page {
+            Some(per_page) => per_page
+                .parse::<usize>()
+                .map_err(|_| Error::parse_error())?,
+            None => MAX_PAGE_SIZE,
+        };
+        if per_page > MAX_PAGE_SIZE {
+            return Err(RPCError::custom(
+                RPCError::Invalid,
+                "too large page size".to_owned(),
+            ));
+        }
+
+        let chain_state = self.shared.chain_state().lock();
+        let tx_pool = chain_state.tx_pool();
+        if verbose.unwrap_or(false) {
+            let to_entry = |entry: &PoolEntry| TxPoolEntry {
+                transaction: (&entry.transaction).into(),
+                cycles: entry.cycles.map(|cycles| cycles.to_string()),
+                fee: entry.fee.map(|fee| fee.to_string()),
+                size: entry.size.to_string(),
+                timestamp: entry.timestamp.to_string(),
+            };
+            // The pages run through the pending transactions, then the proposed ones
+            let skip = page.saturating_mul(per_page);
+            let pending: Vec<_> = tx_pool
+                .pending_txs_iter()
+                .skip(skip)
+                .take(per_page)
+                .map(to_entry)
+                .collect();
+            let proposed = tx_pool
+                .staging_txs_iter()
+                .skip(skip.saturating_sub(tx_pool.pending_size() as usize))
+                .take(per_page - pending.len())
+                .map(to_entry)
+                .collect();
+            Ok(RawTxPool::Verbose(TxPoolEntries { pending, proposed }))
+        } else {
+            let to_hash = |entry: &PoolEntry| entry.transaction.hash().to_owned();
+            Ok(RawTxPool::Ids(TxPoolIds {
+                pending: tx_pool.pending_txs_iter().map(to_hash).collect(),
+                proposed: tx_pool.staging_txs_iter().map(to_hash).collect(),
+            }))
+        }
+    }
+
+    fn get_transaction_rejection(&self, hash: H256) -> Result<Option<TxRejection>> {
+        let chain_state = self.shared.chain_state().lock();
+        let tx_pool = chain_state.tx_pool();
+        Ok(tx_pool.get_rejection(&hash).map(|rejection| TxRejection {
+            hash: rejection.hash.clone(),
+            reason: rejection.reason.to_string(),
+            timestamp: rejection.timestamp.to_string(),
+        }))
+    }
 }
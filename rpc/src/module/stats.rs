@@ -0,0 +1,79 @@
+use ckb_db::CacheStats;
+use ckb_store::ChainStore;
+use ckb_sync::SyncSharedState;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use jsonrpc_types::{CacheStats as CacheStatsView, ChainInfo, StoreCacheStats, SyncState};
+use std::sync::Arc;
+
+#[rpc]
+pub trait StatsRpc {
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_blockchain_info","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_blockchain_info")]
+    fn get_blockchain_info(&self) -> Result<ChainInfo>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"sync_state","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "sync_state")]
+    fn sync_state(&self) -> Result<SyncState>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_store_cache_stats","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_store_cache_stats")]
+    fn get_store_cache_stats(&self) -> Result<StoreCacheStats>;
+}
+
+pub(crate) struct StatsRpcImpl<CS> {
+    pub sync_shared_state: Arc<SyncSharedState<CS>>,
+}
+
+impl<CS: ChainStore + 'static> StatsRpc for StatsRpcImpl<CS> {
+    fn get_blockchain_info(&self) -> Result<ChainInfo> {
+        let (tip_number, epoch, difficulty) = {
+            let chain_state = self.sync_shared_state.chain_state().lock();
+            let tip_header = chain_state.tip_header();
+            (
+                tip_header.number(),
+                chain_state.current_epoch_ext().number(),
+                tip_header.difficulty().to_owned(),
+            )
+        };
+        Ok(ChainInfo {
+            chain: self.sync_shared_state.consensus().id.clone(),
+            tip_number: tip_number.to_string(),
+            epoch: epoch.to_string(),
+            difficulty,
+            is_initial_block_download: self.sync_shared_state.is_initial_block_download(),
+        })
+    }
+
+    fn sync_state(&self) -> Result<SyncState> {
+        let state = self.sync_shared_state.sync_state();
+        Ok(SyncState {
+            best_known_header_number: state.best_known_header_number.to_string(),
+            best_known_header_hash: state.best_known_header_hash,
+            tip_number: state.tip_number.to_string(),
+            tip_hash: state.tip_hash,
+            inflight_blocks: state.inflight_blocks.to_string(),
+            orphan_blocks: state.orphan_blocks.to_string(),
+            estimated_remaining_time: state.estimated_remaining_time.map(|time| time.to_string()),
+            updated_at: state.updated_at.to_string(),
+        })
+    }
+
+    fn get_store_cache_stats(&self) -> Result<StoreCacheStats> {
+        let stats = self.sync_shared_state.shared().store().get_cache_stats();
+        Ok(StoreCacheStats {
+            header: stats.header.map(cache_stats_view),
+            block: stats.block.map(cache_stats_view),
+            cell_meta: stats.cell_meta.map(cache_stats_view),
+        })
+    }
+}
+
+fn cache_stats_view(stats: CacheStats) -> CacheStatsView {
+    CacheStatsView {
+        capacity: stats.capacity.to_string(),
+        size: stats.size.to_string(),
+        hits: stats.hits.to_string(),
+        misses: stats.misses.to_string(),
+    }
+}
@@ -0,0 +1,204 @@
+use ckb_notify::NotifyController;
+use ckb_util::RwLock;
+use crossbeam_channel::select;
+use futures::{future, AsyncSink, Future, Sink as _};
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{
+    typed::{Sink, Subscriber},
+    Session, SubscriptionId,
+};
+use jsonrpc_types::{BlockView, HeaderView, RejectedTransaction, TransactionView};
+use log::{debug, error};
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const SUBSCRIBER_NAME: &str = "rpc-subscription";
+
+#[rpc]
+pub trait SubscriptionRpc {
+    type Metadata;
+
+    // {"id": 2, "jsonrpc": "2.0", "method": "subscribe", "params": ["new_tip_header"]}
+    #[pubsub(subscription = "subscribe", subscribe, name = "subscribe")]
+    fn subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<String>, topic: Topic);
+
+    // {"id": 2, "jsonrpc": "2.0", "method": "unsubscribe", "params": ["0x0"]}
+    #[pubsub(subscription = "subscribe", unsubscribe, name = "unsubscribe")]
+    fn unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Hash, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    NewTipHeader,
+    NewTipBlock,
+    NewTransaction,
+    RejectedTransaction,
+    ExpiredTransaction,
+}
+
+type Subscribers = HashMap<Topic, HashMap<SubscriptionId, Sink<String>>>;
+
+#[derive(Clone)]
+pub(crate) struct SubscriptionRpcImpl {
+    id_generator: Arc<AtomicUsize>,
+    subscribers: Arc<RwLock<Subscribers>>,
+}
+
+impl SubscriptionRpcImpl {
+    /// Publishes the events of `notify_controller` to the subscribers, from a
+    /// dedicated thread
+    pub fn new(notify_controller: &NotifyController) -> Self {
+        let new_tip_receiver = notify_controller.subscribe_new_tip(SUBSCRIBER_NAME);
+        let new_transaction_receiver = notify_controller.subscribe_new_transaction(SUBSCRIBER_NAME);
+        let rejected_transaction_receiver =
+            notify_controller.subscribe_rejected_transaction(SUBSCRIBER_NAME);
+        let expired_transaction_receiver =
+            notify_controller.subscribe_expired_transaction(SUBSCRIBER_NAME);
+
+        let subscription = SubscriptionRpcImpl {
+            id_generator: Arc::new(AtomicUsize::new(0)),
+            subscribers: Arc::new(RwLock::new(HashMap::default())),
+        };
+        let subscribers = Arc::clone(&subscription.subscribers);
+        thread::Builder::new()
+            .name("subscription".to_string())
+            .spawn(move || loop {
+                select! {
+                    recv(new_tip_receiver) -> msg => match msg {
+                        Ok(block) => {
+                            let mut subscribers = subscribers.write();
+                            publish(&mut subscribers, Topic::NewTipHeader, || {
+                                HeaderView::from(block.header())
+                            });
+                            publish(&mut subscribers, Topic::NewTipBlock, || {
+                                BlockView::from(&*block)
+                            });
+                        }
+                        Err(_) => {
+                            error!(target: "rpc", "new tip channel is closed");
+                            break;
+                        }
+                    },
+                    recv(new_transaction_receiver) -> msg => match msg {
+                        Ok(tx) => {
+                            publish(&mut subscribers.write(), Topic::NewTransaction, || {
+                                TransactionView::from(&*tx)
+                            });
+                        }
+                        Err(_) => {
+                            error!(target: "rpc", "new transaction channel is closed");
+                            break;
+                        }
+                    },
+                    recv(rejected_transaction_receiver) -> msg => match msg {
+                        Ok(rejected) => {
+                            let (tx, reason) = &*rejected;
+                            publish(&mut subscribers.write(), Topic::RejectedTransaction, || {
+                                RejectedTransaction {
+                                    transaction: tx.into(),
+                                    reason: reason.to_owned(),
+                                }
+                            });
+                        }
+                        Err(_) => {
+                            error!(target: "rpc", "rejected transaction channel is closed");
+                            break;
+                        }
+                    },
+                    recv(expired_transaction_receiver) -> msg => match msg {
+                        Ok(txs) => {
+                            let mut subscribers = subscribers.write();
+                            for tx in txs.iter() {
+                                publish(&mut subscribers, Topic::ExpiredTransaction, || {
+                                    TransactionView::from(tx)
+                                });
+                            }
+                        }
+                        Err(_) => {
+                            error!(target: "rpc", "expired transaction channel is closed");
+                            break;
+                        }
+                    },
+                }
+            })
+            .expect("Start subscription service failed");
+
+        subscription
+    }
+}
+
+// The message is only built when the topic has subscribers. The sends never
+// wait, a sink which is closed or whose buffer is full is dropped, so a slow
+// client can not stall the events of the others.
+fn publish<T: Serialize, F: FnOnce() -> T>(
+    subscribers: &mut Subscribers,
+    topic: Topic,
+    message: F,
+) {
+    if let Some(sinks) = subscribers.get_mut(&topic) {
+        if sinks.is_empty() {
+            return;
+        }
+        let json = serde_json::to_string(&message()).expect("serialize json");
+        sinks.retain(|id, sink| {
+            // start_send needs a task to park the sender when the buffer fills
+            match future::lazy(|| sink.start_send(Ok(json.clone()))).wait() {
+                Ok(AsyncSink::Ready) => true,
+                Ok(AsyncSink::NotReady(_)) => {
+                    debug!(target: "rpc", "subscriber {:?} is too slow, drop it", id);
+                    false
+                }
+                Err(err) => {
+                    debug!(target: "rpc", "subscriber {:?} is closed: {:?}", id, err);
+                    false
+                }
+            }
+        });
+    }
+}
+
+fn remove_subscriber(subscribers: &RwLock<Subscribers>, id: &SubscriptionId) -> bool {
+    subscribers
+        .write()
+        .values_mut()
+        .any(|sinks| sinks.remove(id).is_some())
+}
+
+impl SubscriptionRpc for SubscriptionRpcImpl {
+    type Metadata = Option<Arc<Session>>;
+
+    fn subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<String>, topic: Topic) {
+        let id = SubscriptionId::String(format!(
+            "{:#x}",
+            self.id_generator.fetch_add(1, Ordering::SeqCst)
+        ));
+        if let Ok(sink) = subscriber.assign_id(id.clone()) {
+            self.subscribers
+                .write()
+                .entry(topic)
+                .or_default()
+                .insert(id.clone(), sink);
+            // The subscriptions end with the connection
+            if let Some(session) = meta {
+                let subscribers = Arc::clone(&self.subscribers);
+                session.on_drop(move || {
+                    remove_subscriber(&subscribers, &id);
+                });
+            }
+        }
+    }
+
+    fn unsubscribe(&self, _meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+        if remove_subscriber(&self.subscribers, &id) {
+            Ok(true)
+        } else {
+            Err(Error::invalid_params("invalid subscription id"))
+        }
+    }
+}
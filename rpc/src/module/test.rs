@@ -1,12 +1,16 @@
+use crate::error::RPCError;
+use ckb_chain::chain::ChainController;
 use ckb_core::transaction::Transaction as CoreTransaction;
 use ckb_network::NetworkController;
 use ckb_shared::shared::Shared;
 use ckb_store::ChainStore;
+use ckb_sync::SyncSharedState;
 use jsonrpc_core::{Error, Result};
 use jsonrpc_derive::rpc;
 use jsonrpc_types::Transaction;
 use numext_fixed_hash::H256;
 use std::convert::TryInto;
+use std::sync::Arc;
 
 #[rpc]
 pub trait IntegrationTestRpc {
@@ -16,11 +20,17 @@ pub trait IntegrationTestRpc {
 
     #[rpc(name = "enqueue_test_transaction")]
     fn enqueue_test_transaction(&self, _tx: Transaction) -> Result<H256>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"truncate","params": ["0x..."]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "truncate")]
+    fn truncate(&self, _target_tip_hash: H256) -> Result<()>;
 }
 
 pub(crate) struct IntegrationTestRpcImpl<CS> {
     pub network_controller: NetworkController,
     pub shared: Shared<CS>,
+    pub chain: ChainController,
+    pub sync_shared_state: Arc<SyncSharedState<CS>>,
 }
 
 impl<CS: ChainStore + 'static> IntegrationTestRpc for IntegrationTestRpcImpl<CS> {
@@ -36,7 +46,15 @@ impl<CS: ChainStore + 'static> IntegrationTestRpc for IntegrationTestRpcImpl<CS>
         let tx: CoreTransaction = tx.try_into().map_err(|_| Error::parse_error())?;
         let mut chain_state = self.shared.chain_state().lock();
         let tx_hash = tx.hash().to_owned();
-        chain_state.mut_tx_pool().enqueue_tx(None, tx);
+        chain_state.mut_tx_pool().enqueue_tx(None, None, tx);
         Ok(tx_hash)
     }
+
+    fn truncate(&self, target_tip_hash: H256) -> Result<()> {
+        self.chain
+            .truncate(target_tip_hash)
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))?;
+        self.sync_shared_state.reset_best_known_header();
+        Ok(())
+    }
 }
@@ -0,0 +1,258 @@
+use crate::auth::AuthMiddleware;
+use crate::config::Module;
+use crate::module::Topic;
+use jsonrpc_http_server::hyper::header::HeaderValue;
+use jsonrpc_http_server::hyper::{Body, Method, Request, StatusCode};
+use jsonrpc_http_server::{RequestMiddleware, RequestMiddlewareAction, Response};
+use jsonrpc_types::{
+    BannedAddress, Block, BlockTemplate, BlockTemplateDelta, BlockWithExtView,
+    CellOutputWithOutPoint, CellTransaction, CellWithStatus, CellbaseOutputCapacityDetails,
+    ChainInfo, DryRunResult, EpochExt, FeeRate, HeaderView, JsonSchema, LiveCell,
+    LockHashIndexState, MiningInfo, Node, OutPoint, RawTxPool, RemoteNode, ResponseFormat, Script,
+    StoreCacheStats, SyncState, Transaction, TransactionWithStatus, TxPoolInfo, TxRejection,
+    TxStatus, TxTrace,
+};
+use numext_fixed_hash::H256;
+use serde_json::{json, Value};
+
+/// The HTTP path serving the OpenRPC document
+pub(crate) const SCHEMA_PATH: &str = "/schema";
+
+const OPENRPC_VERSION: &str = "1.0.0-rc1";
+
+// Describes a method by its name, its positional params and its result
+macro_rules! method {
+    ($name:literal ($($param:literal : $ty:ty),*) -> $result:ty) => {
+        json!({
+            "name": $name,
+            "params": [$(param::<$ty>($param)),*],
+            "result": {
+                "name": "result",
+                "schema": <$result as JsonSchema>::json_schema(),
+            },
+        })
+    };
+}
+
+fn param<T: JsonSchema>(name: &str) -> Value {
+    json!({
+        "name": name,
+        "required": !T::is_optional(),
+        "schema": T::json_schema(),
+    })
+}
+
+impl JsonSchema for Topic {
+    fn json_schema() -> Value {
+        json!({
+            "type": "string",
+            "enum": [
+                "new_tip_header",
+                "new_tip_block",
+                "new_transaction",
+                "rejected_transaction",
+                "expired_transaction",
+            ],
+        })
+    }
+}
+
+fn module_methods(module: Module) -> Vec<Value> {
+    match module {
+        Module::Chain => vec![
+            method!("get_block"("hash": H256, "verbosity": Option<u32>)
+                -> Option<ResponseFormat<BlockWithExtView>>),
+            method!("get_block_by_number"("number": String, "verbosity": Option<u32>)
+                -> Option<ResponseFormat<BlockWithExtView>>),
+            method!("get_transaction"("hash": H256, "verbosity": Option<u32>)
+                -> Option<TransactionWithStatus>),
+            method!("get_block_hash"("number": String) -> Option<H256>),
+            method!("get_tip_header"() -> HeaderView),
+            method!("get_header"("hash": H256, "verbosity": Option<u32>)
+                -> Option<ResponseFormat<HeaderView>>),
+            method!("get_header_by_number"("number": String, "verbosity": Option<u32>)
+                -> Option<ResponseFormat<HeaderView>>),
+            method!("get_cells_by_lock_hash"("lock_hash": H256, "from": String, "to": String)
+                -> Vec<CellOutputWithOutPoint>),
+            method!("get_live_cell"("out_point": OutPoint) -> CellWithStatus),
+            method!("get_tip_block_number"() -> String),
+            method!("get_current_epoch"() -> EpochExt),
+            method!("get_cellbase_output_capacity_details"("hash": H256)
+                -> Option<CellbaseOutputCapacityDetails>),
+        ],
+        Module::Experiment => vec![
+            method!("dry_run_transaction"("tx": Transaction) -> DryRunResult),
+            method!("estimate_fee_rate"("target_blocks": String) -> FeeRate),
+        ],
+        Module::Indexer => vec![
+            method!("index_lock_hash"("lock_hash": H256, "index_from": Option<String>)
+                -> LockHashIndexState),
+            method!("deindex_lock_hash"("lock_hash": H256) -> ()),
+            method!("get_lock_hash_index_states"() -> Vec<LockHashIndexState>),
+            method!("get_live_cells_by_lock_hash"(
+                "lock_hash": H256,
+                "page": String,
+                "per_page": String,
+                "reverse_order": Option<bool>
+            ) -> Vec<LiveCell>),
+            method!("get_transactions_by_lock_hash"(
+                "lock_hash": H256,
+                "page": String,
+                "per_page": String,
+                "reverse_order": Option<bool>
+            ) -> Vec<CellTransaction>),
+        ],
+        Module::Miner => vec![
+            method!("get_block_template"(
+                "bytes_limit": Option<String>,
+                "proposals_limit": Option<String>,
+                "max_version": Option<u32>,
+                "work_id": Option<String>,
+                "wait_timeout": Option<String>,
+                "lock": Option<Script>
+            ) -> BlockTemplate),
+            method!("get_block_template_delta"("work_id": String)
+                -> Option<BlockTemplateDelta>),
+            method!("submit_block"("work_id": String, "data": Block) -> Option<H256>),
+            method!("get_mining_info"() -> MiningInfo),
+        ],
+        Module::Net => vec![
+            method!("local_node_info"() -> Node),
+            method!("get_peers"() -> Vec<RemoteNode>),
+            method!("get_banned_addresses"() -> Vec<BannedAddress>),
+            method!("set_ban"(
+                "address": String,
+                "command": String,
+                "ban_time": Option<String>,
+                "reason": Option<String>
+            ) -> ()),
+        ],
+        Module::Pool => vec![
+            method!("send_transaction"("tx": Transaction) -> H256),
+            method!("submit_transaction"("tx": Transaction) -> H256),
+            method!("get_transaction_status"("hash": H256) -> Option<TxStatus>),
+            method!("tx_pool_info"() -> TxPoolInfo),
+            method!("get_raw_tx_pool"(
+                "verbose": Option<bool>,
+                "page": Option<String>,
+                "per_page": Option<String>
+            ) -> RawTxPool),
+            method!("get_transaction_rejection"("hash": H256) -> Option<TxRejection>),
+        ],
+        Module::Admin => vec![
+            method!("backup_store"("target": String) -> ()),
+            method!("clear_tx_pool"() -> ()),
+            method!("remove_transaction"("hash": H256) -> Vec<H256>),
+        ],
+        Module::Stats => vec![
+            method!("get_blockchain_info"() -> ChainInfo),
+            method!("sync_state"() -> SyncState),
+            method!("get_store_cache_stats"() -> StoreCacheStats),
+        ],
+        Module::Subscription => vec![
+            method!("subscribe"("topic": Topic) -> String),
+            method!("unsubscribe"("id": String) -> bool),
+        ],
+        Module::IntegrationTest => vec![
+            method!("add_node"("peer_id": String, "address": String) -> ()),
+            method!("enqueue_test_transaction"("tx": Transaction) -> H256),
+            method!("truncate"("target_tip_hash": H256) -> ()),
+        ],
+        Module::Trace => vec![
+            method!("trace_transaction"("tx": Transaction) -> H256),
+            method!("get_transaction_trace"("hash": H256) -> Option<Vec<TxTrace>>),
+        ],
+    }
+}
+
+/// Builds the OpenRPC document describing the methods of the given modules
+pub fn openrpc_document(modules: &[Module]) -> Value {
+    let methods = modules
+        .iter()
+        .flat_map(|module| module_methods(*module))
+        .collect::<Vec<_>>();
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "CKB JSON-RPC",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+    })
+}
+
+/// Serves the OpenRPC document on `GET /schema`, behind the same credentials as the methods
+pub(crate) struct SchemaMiddleware {
+    document: String,
+    auth: AuthMiddleware,
+}
+
+impl SchemaMiddleware {
+    pub fn new(document: &Value, auth: AuthMiddleware) -> Self {
+        SchemaMiddleware {
+            document: document.to_string(),
+            auth,
+        }
+    }
+}
+
+impl RequestMiddleware for SchemaMiddleware {
+    fn on_request(&self, request: Request<Body>) -> RequestMiddlewareAction {
+        let is_schema = request.method() == Method::GET && request.uri().path() == SCHEMA_PATH;
+        if is_schema && self.auth.is_authorized(&request) {
+            Response {
+                code: StatusCode::OK,
+                content_type: HeaderValue::from_static("application/json; charset=utf-8"),
+                content: self.document.clone(),
+            }
+            .into()
+        } else {
+            self.auth.on_request(request)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    // The names of the methods a module registers, from its `#[rpc(name = ..)]` attributes
+    fn registered_methods(module: Module) -> BTreeSet<String> {
+        let source = match module {
+            Module::Net => include_str!("module/net.rs"),
+            Module::Chain => include_str!("module/chain.rs"),
+            Module::Experiment => include_str!("module/experiment.rs"),
+            Module::Miner => include_str!("module/miner.rs"),
+            Module::Pool => include_str!("module/pool.rs"),
+            Module::Trace => include_str!("module/trace.rs"),
+            Module::Stats => include_str!("module/stats.rs"),
+            Module::Subscription => include_str!("module/subscription.rs"),
+            Module::Indexer => include_str!("module/indexer.rs"),
+            Module::Admin => include_str!("module/admin.rs"),
+            Module::IntegrationTest => include_str!("module/test.rs"),
+        };
+        source
+            .split(" name = \"")
+            .skip(1)
+            .chain(source.split("(name = \"").skip(1))
+            .map(|rest| rest[..rest.find('"').unwrap()].to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_documented_methods() {
+        for module in Module::all() {
+            let documented = module_methods(module)
+                .iter()
+                .map(|method| method["name"].as_str().unwrap().to_owned())
+                .collect::<BTreeSet<_>>();
+            assert_eq!(
+                documented,
+                registered_methods(module),
+                "methods of {:?}",
+                module
+            );
+        }
+    }
+}
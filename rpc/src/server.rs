@@ -1,20 +1,34 @@
+use crate::auth::AuthMiddleware;
 use crate::config::Config;
 use crate::module::{
-    ChainRpc, ChainRpcImpl, IntegrationTestRpc, IntegrationTestRpcImpl, MinerRpc, MinerRpcImpl,
-    NetworkRpc, NetworkRpcImpl, PoolRpc, PoolRpcImpl, TraceRpc, TraceRpcImpl,
+    AdminRpc, AdminRpcImpl, ChainRpc, ChainRpcImpl, ExperimentRpc, ExperimentRpcImpl, IndexerRpc,
+    IndexerRpcImpl, IntegrationTestRpc, IntegrationTestRpcImpl, MinerRpc, MinerRpcImpl, NetworkRpc,
+    NetworkRpcImpl, PoolRpc, PoolRpcImpl, StatsRpc, StatsRpcImpl, SubscriptionRpc,
+    SubscriptionRpcImpl, TraceRpc, TraceRpcImpl, TxSubmitter,
 };
+use crate::schema::{openrpc_document, SchemaMiddleware};
+use crate::tcp::TcpServer;
 use ckb_chain::chain::ChainController;
+use ckb_indexer::IndexerStore;
 use ckb_miner::BlockAssemblerController;
 use ckb_network::NetworkController;
+use ckb_notify::NotifyController;
+use ckb_shared::fee_estimator::FeeEstimatorService;
 use ckb_shared::shared::Shared;
 use ckb_store::ChainStore;
-use jsonrpc_core::IoHandler;
+use ckb_sync::{Peers, SyncSharedState};
+use jsonrpc_core::MetaIoHandler;
 use jsonrpc_http_server::{Server, ServerBuilder};
+use jsonrpc_pubsub::Session;
 use jsonrpc_server_utils::cors::AccessControlAllowOrigin;
 use jsonrpc_server_utils::hosts::DomainsValidation;
+use jsonrpc_ws_server::RequestContext;
+use std::sync::Arc;
 
 pub struct RpcServer {
-    server: Server,
+    server: Option<Server>,
+    tcp_server: Option<TcpServer>,
+    ws_server: Option<jsonrpc_ws_server::Server>,
 }
 
 impl RpcServer {
@@ -24,11 +38,17 @@ impl RpcServer {
         shared: Shared<CS>,
         chain: ChainController,
         block_assembler: BlockAssemblerController,
+        sync_shared_state: Arc<SyncSharedState<CS>>,
+        sync_peers: Arc<Peers>,
+        notify_controller: NotifyController,
+        indexer_store: Option<IndexerStore<CS>>,
+        fee_estimator: Option<FeeEstimatorService<CS>>,
     ) -> RpcServer
     where
         CS: ChainStore,
     {
-        let mut io = IoHandler::new();
+        // Only the TCP and WebSocket sessions carry the metadata of the subscriptions
+        let mut io = MetaIoHandler::<Option<Arc<Session>>>::default();
 
         if config.chain_enable() {
             io.extend_with(
@@ -43,7 +63,24 @@ impl RpcServer {
             io.extend_with(
                 PoolRpcImpl {
                     network_controller: network_controller.clone(),
+                    notify_controller: notify_controller.clone(),
                     shared: shared.clone(),
+                    tx_submitter: TxSubmitter::new(
+                        config.tx_verify_workers.unwrap_or_else(num_cpus::get),
+                        shared.clone(),
+                        network_controller.clone(),
+                        notify_controller.clone(),
+                    ),
+                }
+                .to_delegate(),
+            );
+        }
+
+        if let Some(fee_estimator) = fee_estimator {
+            io.extend_with(
+                ExperimentRpcImpl {
+                    shared: shared.clone(),
+                    fee_estimator,
                 }
                 .to_delegate(),
             );
@@ -54,7 +91,7 @@ impl RpcServer {
                 MinerRpcImpl {
                     shared: shared.clone(),
                     block_assembler,
-                    chain,
+                    chain: chain.clone(),
                     network_controller: network_controller.clone(),
                 }
                 .to_delegate(),
@@ -65,6 +102,7 @@ impl RpcServer {
             io.extend_with(
                 NetworkRpcImpl {
                     network_controller: network_controller.clone(),
+                    sync_peers,
                 }
                 .to_delegate(),
             );
@@ -80,35 +118,114 @@ impl RpcServer {
             );
         }
 
+        if config.stats_enable() {
+            io.extend_with(
+                StatsRpcImpl {
+                    sync_shared_state: Arc::clone(&sync_shared_state),
+                }
+                .to_delegate(),
+            );
+        }
+
+        if config.subscription_enable() {
+            io.extend_with(SubscriptionRpcImpl::new(&notify_controller).to_delegate());
+        }
+
+        if let Some(store) = indexer_store {
+            io.extend_with(IndexerRpcImpl { store }.to_delegate());
+        }
+
+        if config.admin_enable() {
+            io.extend_with(
+                AdminRpcImpl {
+                    shared: shared.clone(),
+                }
+                .to_delegate(),
+            );
+        }
+
         if config.integration_test_enable() {
             io.extend_with(
                 IntegrationTestRpcImpl {
                     network_controller,
                     shared,
+                    chain,
+                    sync_shared_state,
                 }
                 .to_delegate(),
             );
         }
 
-        let server = ServerBuilder::new(io)
-            .cors(DomainsValidation::AllowOnly(vec![
-                AccessControlAllowOrigin::Null,
-                AccessControlAllowOrigin::Any,
-            ]))
-            .threads(config.threads.unwrap_or_else(num_cpus::get))
-            .max_request_body_size(config.max_request_body_size)
-            .start_http(
-                &config
-                    .listen_address
+        let server = if config.http_enable {
+            let middleware = SchemaMiddleware::new(
+                &openrpc_document(&config.modules),
+                AuthMiddleware::new(config.auth.as_ref()),
+            );
+            let server_builder = ServerBuilder::new(io.clone())
+                .cors(DomainsValidation::AllowOnly(vec![
+                    AccessControlAllowOrigin::Null,
+                    AccessControlAllowOrigin::Any,
+                ]))
+                .threads(config.threads.unwrap_or_else(num_cpus::get))
+                .max_request_body_size(config.max_request_body_size)
+                .request_middleware(middleware);
+            Some(
+                server_builder
+                    .start_http(
+                        &config
+                            .listen_address
+                            .parse()
+                            .expect("config listen_address parsed"),
+                    )
+                    .expect("Jsonrpc initialize"),
+            )
+        } else {
+            None
+        };
+
+        let tcp_server = config
+            .tcp_listen_address
+            .as_ref()
+            .map(|tcp_listen_address| {
+                TcpServer::start(
+                    io.clone(),
+                    &tcp_listen_address
+                        .parse()
+                        .expect("config tcp_listen_address parsed"),
+                    config.max_request_body_size,
+                )
+                .expect("Jsonrpc TCP initialize")
+            });
+
+        let ws_server = config.ws_listen_address.as_ref().map(|ws_listen_address| {
+            jsonrpc_ws_server::ServerBuilder::with_meta_extractor(io, |context: &RequestContext| {
+                Some(Arc::new(Session::new(context.sender())))
+            })
+            .max_payload(config.max_request_body_size)
+            .start(
+                &ws_listen_address
                     .parse()
-                    .expect("config listen_address parsed"),
+                    .expect("config ws_listen_address parsed"),
             )
-            .expect("Jsonrpc initialize");
+            .expect("Jsonrpc WebSocket initialize")
+        });
 
-        RpcServer { server }
+        RpcServer {
+            server,
+            tcp_server,
+            ws_server,
+        }
     }
 
     pub fn close(self) {
-        self.server.close()
+        if let Some(server) = self.server {
+            server.close();
+        }
+        if let Some(tcp_server) = self.tcp_server {
+            tcp_server.close();
+        }
+        if let Some(ws_server) = self.ws_server {
+            ws_server.close();
+        }
     }
 }
@@ -0,0 +1,132 @@
+use bytes::BytesMut;
+use futures::sync::mpsc;
+use futures::{Future, Sink, Stream};
+use jsonrpc_core::MetaIoHandler;
+use jsonrpc_pubsub::Session;
+use jsonrpc_server_utils::codecs::{Separator, StreamCodec};
+use jsonrpc_server_utils::tokio;
+use jsonrpc_server_utils::tokio::net::TcpListener;
+use jsonrpc_server_utils::tokio::runtime::Runtime;
+use jsonrpc_server_utils::tokio_codec::{Decoder, Encoder};
+use jsonrpc_server_utils::SuspendableStream;
+use log::debug;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+// Notifications of the subscriptions waiting to be written to a connection
+const SESSION_QUEUE_SIZE: usize = 1024;
+
+/// Raw TCP endpoint of newline delimited requests. A connection sending a
+/// request larger than the limit is closed, its unterminated request is
+/// never buffered beyond it.
+pub(crate) struct TcpServer {
+    runtime: Runtime,
+}
+
+impl TcpServer {
+    pub fn start(
+        io: MetaIoHandler<Option<Arc<Session>>>,
+        address: &SocketAddr,
+        max_request_size: usize,
+    ) -> io::Result<TcpServer> {
+        let listener = TcpListener::bind(address)?;
+        let server = SuspendableStream::new(listener.incoming()).for_each(move |socket| {
+            let peer_addr = socket.peer_addr();
+            let (sender, receiver) = mpsc::channel(SESSION_QUEUE_SIZE);
+            let session = Some(Arc::new(Session::new(sender)));
+            let io = io.clone();
+            let (writer, reader) = LimitedCodec::new(max_request_size).framed(socket).split();
+            let responses = reader
+                .and_then(move |request| {
+                    io.handle_request(&request, session.clone())
+                        .map_err(|_| io::Error::from(io::ErrorKind::Other))
+                })
+                .filter_map(|response| response);
+            let notifications = receiver.map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe));
+            tokio::spawn(
+                writer
+                    .send_all(responses.select(notifications))
+                    .then(move |ret| {
+                        if let Err(err) = ret {
+                            debug!(target: "rpc", "tcp connection {:?} closed: {}", peer_addr, err);
+                        }
+                        Ok(())
+                    }),
+            );
+            Ok(())
+        });
+        let runtime = Runtime::new()?;
+        runtime.executor().spawn(server);
+        Ok(TcpServer { runtime })
+    }
+
+    pub fn close(self) {
+        let _ = self.runtime.shutdown_now().wait();
+    }
+}
+
+/// Newline delimited codec refusing the requests larger than the limit
+struct LimitedCodec {
+    inner: StreamCodec,
+    max_request_size: usize,
+}
+
+impl LimitedCodec {
+    fn new(max_request_size: usize) -> Self {
+        LimitedCodec {
+            inner: StreamCodec::new(Separator::Byte(b'\n'), Separator::Byte(b'\n')),
+            max_request_size,
+        }
+    }
+
+    fn too_large(&self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("request exceeds {} bytes", self.max_request_size),
+        )
+    }
+}
+
+impl Decoder for LimitedCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
+        match self.inner.decode(buf)? {
+            Some(ref request) if request.len() > self.max_request_size => Err(self.too_large()),
+            None if buf.len() > self.max_request_size => Err(self.too_large()),
+            request => Ok(request),
+        }
+    }
+}
+
+impl Encoder for LimitedCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn encode(&mut self, response: String, buf: &mut BytesMut) -> io::Result<()> {
+        self.inner.encode(response, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limited_codec() {
+        let mut codec = LimitedCodec::new(8);
+        let mut buf = BytesMut::from(&b"12345678\n123"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some("12345678".to_owned()));
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+        // The unterminated request grows beyond the limit
+        buf.extend_from_slice(b"456789");
+        assert!(codec.decode(&mut buf).is_err());
+
+        // The request arrives at once
+        let mut buf = BytesMut::from(&b"123456789\n"[..]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use ckb_core::transaction::CellOutPoint;
+use ckb_core::Bytes;
+use ckb_util::RwLock;
+use fnv::FnvHashMap;
+use numext_fixed_hash::H256;
+
+/// Dep cell data shared by the script verification of many transactions,
+/// e.g. all the transactions in a block, so that the same dep cells are
+/// loaded and hashed only once.
+///
+/// Both maps are safe to share: an out point always refers to the same cell,
+/// and data is keyed by its own hash.
+#[derive(Default)]
+pub struct ScriptDataCache {
+    data_hashes: RwLock<FnvHashMap<CellOutPoint, H256>>,
+    data: RwLock<FnvHashMap<H256, Bytes>>,
+}
+
+impl ScriptDataCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn data_hash(&self, out_point: &CellOutPoint) -> Option<H256> {
+        self.data_hashes.read().get(out_point).cloned()
+    }
+
+    pub fn data(&self, data_hash: &H256) -> Option<Bytes> {
+        self.data.read().get(data_hash).cloned()
+    }
+
+    pub fn insert(&self, out_point: CellOutPoint, data_hash: H256, data: Bytes) {
+        self.data_hashes
+            .write()
+            .insert(out_point, data_hash.clone());
+        self.data.write().insert(data_hash, data);
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.read().is_empty()
+    }
+}
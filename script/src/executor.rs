@@ -0,0 +1,206 @@
+use crate::mock::{MockStore, MockTransaction, ResolvedMockTransaction};
+use crate::{ScriptConfig, ScriptError, ScriptLocation, TransactionScriptsVerifier};
+use ckb_core::Cycle;
+use failure::Error as FailureError;
+use std::cell::RefCell;
+use std::convert::TryInto;
+use std::net::TcpListener;
+use std::sync::Arc;
+
+/// The outcome of executing a single script, debug output is collected even
+/// when the script fails.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScriptExecution {
+    pub result: Result<Cycle, ScriptError>,
+    pub debug_output: Vec<String>,
+}
+
+/// Executes scripts of a mock transaction in isolation, without a chain store.
+pub struct ScriptExecutor {
+    mock_tx: ResolvedMockTransaction,
+    config: ScriptConfig,
+}
+
+impl ScriptExecutor {
+    pub fn new(mock_tx: MockTransaction, config: ScriptConfig) -> Result<Self, FailureError> {
+        Ok(ScriptExecutor {
+            mock_tx: mock_tx.try_into()?,
+            config,
+        })
+    }
+
+    pub fn execute(&self, location: ScriptLocation, max_cycles: Cycle) -> ScriptExecution {
+        let debug_output = RefCell::new(Vec::new());
+        let rtx = self.mock_tx.resolved_transaction();
+        let result = {
+            let mut verifier =
+                TransactionScriptsVerifier::new(&rtx, Arc::new(MockStore), &self.config);
+            verifier.set_debug_printer(|_prefix, message| {
+                debug_output.borrow_mut().push(message.to_owned());
+            });
+            verifier.verify_location(location, max_cycles)
+        };
+
+        ScriptExecution {
+            result,
+            debug_output: debug_output.into_inner(),
+        }
+    }
+
+    /// Executes the script under a GDB remote stub, see
+    /// `TransactionScriptsVerifier::debug_script`.
+    pub fn debug(
+        &self,
+        location: ScriptLocation,
+        listener: &TcpListener,
+        max_cycles: Cycle,
+    ) -> ScriptExecution {
+        let debug_output = RefCell::new(Vec::new());
+        let rtx = self.mock_tx.resolved_transaction();
+        let result = {
+            let mut verifier =
+                TransactionScriptsVerifier::new(&rtx, Arc::new(MockStore), &self.config);
+            verifier.set_debug_printer(|_prefix, message| {
+                debug_output.borrow_mut().push(message.to_owned());
+            });
+            verifier.debug_script(location, listener, max_cycles)
+        };
+
+        ScriptExecution {
+            result,
+            debug_output: debug_output.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockCell;
+    use crate::Runner;
+    use ckb_core::script::Script;
+    use ckb_core::transaction::{CellInput, CellOutput, OutPoint, TransactionBuilder};
+    use ckb_core::{capacity_bytes, Bytes, Capacity};
+    use crypto::secp::Generator;
+    use faster_hex::hex_encode;
+    use hash::{blake2b_256, sha3_256};
+    use numext_fixed_hash::{h256, H256};
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::path::Path;
+
+    fn load_verify_binary() -> Vec<u8> {
+        let mut buffer = Vec::new();
+        File::open(Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/verify"))
+            .unwrap()
+            .read_to_end(&mut buffer)
+            .unwrap();
+        buffer
+    }
+
+    // Builds a mock transaction whose only input is locked by the verify
+    // script, signing the script args unless `valid_signature` is false.
+    fn build_mock_transaction(valid_signature: bool) -> MockTransaction {
+        let binary = load_verify_binary();
+        let privkey = Generator::new().random_privkey();
+        let args = vec![Bytes::from(b"foo".to_vec()), Bytes::from(b"bar".to_vec())];
+
+        let mut bytes = vec![];
+        for argument in &args {
+            bytes.write_all(argument).unwrap();
+        }
+        if !valid_signature {
+            bytes.write_all(b"baz").unwrap();
+        }
+        let message = sha3_256(sha3_256(&bytes));
+        let signature_der = privkey
+            .sign_recoverable(&message.into())
+            .unwrap()
+            .serialize_der();
+        let mut hex_signature = vec![0; signature_der.len() * 2];
+        hex_encode(&signature_der, &mut hex_signature).expect("hex signature");
+        let pubkey = privkey.pubkey().unwrap().serialize();
+        let mut hex_pubkey = vec![0; pubkey.len() * 2];
+        hex_encode(&pubkey, &mut hex_pubkey).expect("hex pubkey");
+
+        let code_hash: H256 = (&blake2b_256(&binary)).into();
+        let dep_output = CellOutput::new(
+            Capacity::bytes(binary.len()).unwrap(),
+            Bytes::from(binary),
+            Script::default(),
+            None,
+        );
+        let input_output = CellOutput::new(
+            capacity_bytes!(100),
+            Bytes::default(),
+            Script::new(args, code_hash),
+            None,
+        );
+        let transaction = TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(h256!("0x1"), 0),
+                0,
+                vec![],
+            ))
+            .dep(OutPoint::new_cell(h256!("0x2"), 0))
+            .witness(vec![hex_pubkey, hex_signature])
+            .build();
+
+        MockTransaction {
+            transaction: (&transaction).into(),
+            inputs: vec![MockCell {
+                output: Some(input_output.into()),
+                header: None,
+            }],
+            deps: vec![MockCell {
+                output: Some(dep_output.into()),
+                header: None,
+            }],
+        }
+    }
+
+    fn rust_config() -> ScriptConfig {
+        ScriptConfig {
+            runner: Runner::Rust,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_execute_input_lock() {
+        let executor = ScriptExecutor::new(build_mock_transaction(true), rust_config()).unwrap();
+        let execution = executor.execute(ScriptLocation::Input(0), 100_000_000);
+
+        assert!(execution.result.unwrap() > 0);
+        assert!(execution.debug_output.is_empty());
+    }
+
+    #[test]
+    fn test_execute_invalid_signature() {
+        let executor = ScriptExecutor::new(build_mock_transaction(false), rust_config()).unwrap();
+        let execution = executor.execute(ScriptLocation::Input(0), 100_000_000);
+
+        assert!(match execution.result {
+            Err(ScriptError::ValidationFailure(_)) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn test_execute_missing_script() {
+        let executor = ScriptExecutor::new(build_mock_transaction(true), rust_config()).unwrap();
+
+        assert_eq!(
+            executor
+                .execute(ScriptLocation::Input(1), 100_000_000)
+                .result,
+            Err(ScriptError::NoScript)
+        );
+        assert_eq!(
+            executor
+                .execute(ScriptLocation::Output(0), 100_000_000)
+                .result,
+            Err(ScriptError::NoScript)
+        );
+    }
+}
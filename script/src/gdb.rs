@@ -0,0 +1,399 @@
+//! A minimal GDB remote serial protocol stub, allowing a single script to be
+//! debugged by connecting `riscv64-unknown-elf-gdb` with `target remote`.
+//!
+//! Execution is synchronous: `continue` runs until a breakpoint is hit or the
+//! script exits, interrupt requests are ignored.
+
+use ckb_vm::decoder::{build_imc_decoder, Decoder};
+use ckb_vm::{
+    CoreMachine, DefaultMachine, Error as VMError, Memory, Register, SupportMachine,
+    RISCV_MAX_MEMORY,
+};
+use log::{debug, info};
+use std::cmp;
+use std::collections::HashSet;
+use std::io::{BufReader, Bytes, Read, Write};
+use std::net::TcpStream;
+
+// General purpose registers x0..x31 are followed by pc
+const PC_REGISTER_NUMBER: usize = 32;
+const REGISTER_BYTES: usize = 8;
+const SIGTRAP: u8 = 5;
+const SIGILL: u8 = 4;
+const PACKET_SIZE: usize = 4096;
+
+enum Action {
+    Reply(String),
+    Detach(Option<String>),
+}
+
+pub struct GdbStub<'a, 'b, Inner> {
+    machine: &'b mut DefaultMachine<'a, Inner>,
+    decoder: Decoder,
+    breakpoints: HashSet<u64>,
+}
+
+impl<'a, 'b, Inner: SupportMachine> GdbStub<'a, 'b, Inner> {
+    /// The program must already be loaded into `machine`.
+    pub fn new(machine: &'b mut DefaultMachine<'a, Inner>) -> Self {
+        GdbStub {
+            machine,
+            decoder: build_imc_decoder::<Inner::REG>(),
+            breakpoints: HashSet::default(),
+        }
+    }
+
+    /// Serves a single debugger session on `stream`. Once the debugger
+    /// detaches or the connection is closed, the script runs to completion,
+    /// so detaching never changes the result. Returns the exit code.
+    pub fn serve(mut self, stream: TcpStream) -> Result<u8, VMError> {
+        info!(target: "script", "gdb connected from {:?}", stream.peer_addr());
+        self.machine.set_running(true);
+        let mut connection = Connection::new(stream)?;
+        while let Some(packet) = connection.read_packet()? {
+            debug!(target: "script", "gdb packet: {}", String::from_utf8_lossy(&packet));
+            match self.handle(&packet) {
+                Ok(Action::Reply(reply)) => connection.write_packet(&reply)?,
+                Ok(Action::Detach(reply)) => {
+                    if let Some(reply) = reply {
+                        connection.write_packet(&reply)?;
+                    }
+                    break;
+                }
+                Err(err) => {
+                    connection.write_packet(&format!("X{:02x}", SIGILL))?;
+                    return Err(err);
+                }
+            }
+        }
+        info!(target: "script", "gdb detached");
+
+        while self.machine.running() {
+            self.machine.step(&self.decoder)?;
+        }
+        Ok(self.machine.exit_code())
+    }
+
+    fn handle(&mut self, packet: &[u8]) -> Result<Action, VMError> {
+        let (command, body) = match packet.split_first() {
+            Some((command, body)) => (*command, String::from_utf8_lossy(body).into_owned()),
+            None => return Ok(Action::Reply(String::new())),
+        };
+        let reply = match command {
+            b'?' => self.stop_reply(),
+            b'g' => self.read_registers(),
+            b'G' => self.write_registers(&body),
+            b'p' => self.read_register(&body),
+            b'P' => self.write_register(&body),
+            b'm' => self.read_memory(&body),
+            b'M' => self.write_memory(&body),
+            b'c' => {
+                self.set_pc_if_present(&body);
+                self.resume(false)?
+            }
+            b's' => {
+                self.set_pc_if_present(&body);
+                self.resume(true)?
+            }
+            b'Z' => self.set_breakpoint(&body, true),
+            b'z' => self.set_breakpoint(&body, false),
+            b'H' => "OK".to_string(),
+            b'q' if body.starts_with("Supported") => format!("PacketSize={:x}", PACKET_SIZE),
+            b'q' if body == "Attached" => "1".to_string(),
+            b'D' => return Ok(Action::Detach(Some("OK".to_string()))),
+            b'k' => return Ok(Action::Detach(None)),
+            // Empty reply tells gdb the packet is not supported
+            _ => String::new(),
+        };
+        Ok(Action::Reply(reply))
+    }
+
+    fn stop_reply(&self) -> String {
+        if self.machine.running() {
+            format!("S{:02x}", SIGTRAP)
+        } else {
+            format!("W{:02x}", self.machine.exit_code())
+        }
+    }
+
+    fn resume(&mut self, single_step: bool) -> Result<String, VMError> {
+        while self.machine.running() {
+            self.machine.step(&self.decoder)?;
+            if single_step || self.breakpoints.contains(&self.machine.pc().to_u64()) {
+                break;
+            }
+        }
+        Ok(self.stop_reply())
+    }
+
+    fn set_pc_if_present(&mut self, body: &str) {
+        if let Ok(pc) = u64::from_str_radix(body, 16) {
+            self.machine.set_pc(Inner::REG::from_u64(pc));
+        }
+    }
+
+    fn register(&self, number: usize) -> Option<u64> {
+        if number < PC_REGISTER_NUMBER {
+            self.machine.registers().get(number).map(Register::to_u64)
+        } else if number == PC_REGISTER_NUMBER {
+            Some(self.machine.pc().to_u64())
+        } else {
+            None
+        }
+    }
+
+    fn set_register(&mut self, number: usize, value: u64) -> bool {
+        if number < PC_REGISTER_NUMBER {
+            self.machine
+                .set_register(number, Inner::REG::from_u64(value));
+            true
+        } else if number == PC_REGISTER_NUMBER {
+            self.machine.set_pc(Inner::REG::from_u64(value));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn read_registers(&self) -> String {
+        (0..=PC_REGISTER_NUMBER)
+            .filter_map(|number| self.register(number))
+            .map(encode_register)
+            .collect()
+    }
+
+    fn write_registers(&mut self, body: &str) -> String {
+        let width = REGISTER_BYTES * 2;
+        if body.len() < width * (PC_REGISTER_NUMBER + 1) {
+            return error_reply(1);
+        }
+        for number in 0..=PC_REGISTER_NUMBER {
+            match body
+                .get(number * width..(number + 1) * width)
+                .and_then(decode_register)
+            {
+                Some(value) => {
+                    self.set_register(number, value);
+                }
+                None => return error_reply(1),
+            }
+        }
+        "OK".to_string()
+    }
+
+    fn read_register(&self, body: &str) -> String {
+        usize::from_str_radix(body, 16)
+            .ok()
+            .and_then(|number| self.register(number))
+            .map(encode_register)
+            .unwrap_or_else(|| error_reply(1))
+    }
+
+    fn write_register(&mut self, body: &str) -> String {
+        let mut parts = body.splitn(2, '=');
+        let number = parts.next().and_then(|n| usize::from_str_radix(n, 16).ok());
+        let value = parts.next().and_then(decode_register);
+        match (number, value) {
+            (Some(number), Some(value)) if self.set_register(number, value) => "OK".to_string(),
+            _ => error_reply(1),
+        }
+    }
+
+    fn read_memory(&mut self, body: &str) -> String {
+        let (addr, length) = match parse_address_length(body) {
+            Some(parsed) => parsed,
+            None => return error_reply(1),
+        };
+        // The client may ask for any length, no more than the memory of the
+        // VM can be read, the protocol allows a shorter reply
+        let length = cmp::min(length, RISCV_MAX_MEMORY);
+        let mut reply = String::with_capacity(length * 2);
+        for offset in 0..length as u64 {
+            let addr = Inner::REG::from_u64(addr.wrapping_add(offset));
+            match self.machine.memory_mut().load8(&addr) {
+                Ok(byte) => reply.push_str(&format!("{:02x}", byte.to_u8())),
+                Err(_) => return error_reply(14),
+            }
+        }
+        reply
+    }
+
+    fn write_memory(&mut self, body: &str) -> String {
+        let mut parts = body.splitn(2, ':');
+        let (addr, length) = match parts.next().and_then(parse_address_length) {
+            Some(parsed) => parsed,
+            None => return error_reply(1),
+        };
+        let data = match parts.next().and_then(decode_hex) {
+            Some(data) => data,
+            None => return error_reply(1),
+        };
+        if data.len() != length {
+            return error_reply(1);
+        }
+        for (offset, byte) in data.into_iter().enumerate() {
+            let addr = Inner::REG::from_u64(addr.wrapping_add(offset as u64));
+            let value = Inner::REG::from_u8(byte);
+            if self.machine.memory_mut().store8(&addr, &value).is_err() {
+                return error_reply(14);
+            }
+        }
+        "OK".to_string()
+    }
+
+    // Software and hardware breakpoints are handled identically by checking
+    // pc after every step, watchpoints are not supported.
+    fn set_breakpoint(&mut self, body: &str, insert: bool) -> String {
+        let mut parts = body.split(',');
+        let kind = parts.next();
+        let addr = parts.next().and_then(|a| u64::from_str_radix(a, 16).ok());
+        match (kind, addr) {
+            (Some("0"), Some(addr)) | (Some("1"), Some(addr)) => {
+                if insert {
+                    self.breakpoints.insert(addr);
+                } else {
+                    self.breakpoints.remove(&addr);
+                }
+                "OK".to_string()
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+struct Connection {
+    reader: Bytes<BufReader<TcpStream>>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self, VMError> {
+        let writer = stream.try_clone()?;
+        Ok(Connection {
+            reader: BufReader::new(stream).bytes(),
+            writer,
+        })
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>, VMError> {
+        self.reader.next().transpose().map_err(Into::into)
+    }
+
+    /// Reads the next packet, acknowledging it. Returns `None` when the
+    /// connection is closed.
+    fn read_packet(&mut self) -> Result<Option<Vec<u8>>, VMError> {
+        loop {
+            match self.read_byte()? {
+                Some(b'$') => {
+                    let mut data = Vec::new();
+                    loop {
+                        match self.read_byte()? {
+                            Some(b'#') => break,
+                            Some(byte) => data.push(byte),
+                            None => return Ok(None),
+                        }
+                    }
+                    let mut expected = [0u8; 2];
+                    for byte in expected.iter_mut() {
+                        match self.read_byte()? {
+                            Some(b) => *byte = b,
+                            None => return Ok(None),
+                        }
+                    }
+                    let expected = std::str::from_utf8(&expected)
+                        .ok()
+                        .and_then(|e| u8::from_str_radix(e, 16).ok());
+                    if expected == Some(checksum(&data)) {
+                        self.writer.write_all(b"+")?;
+                        return Ok(Some(data));
+                    } else {
+                        self.writer.write_all(b"-")?;
+                    }
+                }
+                // Acknowledgements and interrupt requests are ignored
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn write_packet(&mut self, data: &str) -> Result<(), VMError> {
+        self.writer.write_all(&encode_packet(data))?;
+        self.writer.flush().map_err(Into::into)
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn encode_packet(data: &str) -> Vec<u8> {
+    format!("${}#{:02x}", data, checksum(data.as_bytes())).into_bytes()
+}
+
+fn error_reply(code: u8) -> String {
+    format!("E{:02x}", code)
+}
+
+// Registers are transferred in target byte order, which is little endian
+fn encode_register(value: u64) -> String {
+    value
+        .to_le_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn decode_register(hex: &str) -> Option<u64> {
+    let bytes = decode_hex(hex)?;
+    if bytes.len() != REGISTER_BYTES {
+        return None;
+    }
+    let mut buffer = [0u8; REGISTER_BYTES];
+    buffer.copy_from_slice(&bytes);
+    Some(u64::from_le_bytes(buffer))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn parse_address_length(body: &str) -> Option<(u64, usize)> {
+    let mut parts = body.splitn(2, ',');
+    let addr = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let length = usize::from_str_radix(parts.next()?, 16).ok()?;
+    Some((addr, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_packet() {
+        assert_eq!(encode_packet("OK"), b"$OK#9a".to_vec());
+        assert_eq!(encode_packet(""), b"$#00".to_vec());
+        assert_eq!(encode_packet("S05"), b"$S05#b8".to_vec());
+    }
+
+    #[test]
+    fn test_register_encoding() {
+        assert_eq!(encode_register(0x1122), "2211000000000000");
+        assert_eq!(decode_register("2211000000000000"), Some(0x1122));
+        assert_eq!(decode_register("2211"), None);
+        assert_eq!(decode_register("zz11000000000000"), None);
+    }
+
+    #[test]
+    fn test_parse_address_length() {
+        assert_eq!(parse_address_length("10078,4"), Some((0x10078, 4)));
+        assert_eq!(parse_address_length("10078"), None);
+        assert_eq!(parse_address_length("xyz,4"), None);
+    }
+}
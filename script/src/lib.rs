@@ -1,11 +1,20 @@
 mod common;
 mod cost_model;
+mod data_cache;
+mod executor;
+mod gdb;
+mod mock;
 mod syscalls;
 mod verify;
 
 use ckb_vm::Error as VMInternalError;
+use serde::Serializer;
 use serde_derive::{Deserialize, Serialize};
 
+pub use crate::data_cache::ScriptDataCache;
+pub use crate::executor::{ScriptExecution, ScriptExecutor};
+pub use crate::mock::{MockCell, MockStore, MockTransaction, ResolvedMockTransaction};
+pub use crate::syscalls::generate_c_header;
 pub use crate::verify::TransactionScriptsVerifier;
 
 #[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Hash, Debug)]
@@ -20,17 +29,46 @@ impl Default for Runner {
     }
 }
 
+impl Runner {
+    /// The assembly machine requires the `asm` feature and is only available
+    /// on x86_64, `Runner::Assembly` falls back to the Rust interpreter elsewhere.
+    pub fn is_assembly_supported() -> bool {
+        cfg!(all(feature = "asm", target_arch = "x86_64"))
+    }
+}
+
+/// Locates a single script in a transaction: the lock script of an input or
+/// the type script of an output.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum ScriptLocation {
+    Input(usize),
+    Output(usize),
+}
+
 #[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Hash, Debug, Default)]
 pub struct ScriptConfig {
     pub runner: Runner,
+    /// Number of threads verifying the transactions of a block concurrently,
+    /// 0 means one thread per CPU.
+    #[serde(default)]
+    pub verification_workers: usize,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Serialize)]
 pub enum ScriptError {
     NoScript,
     InvalidReferenceIndex,
     ArgumentError,
     ValidationFailure(u8),
+    #[serde(serialize_with = "serialize_vm_error")]
     VMError(VMInternalError),
     ExceededMaximumCycles,
 }
+
+// VM errors have no serde support, they are reported by name
+fn serialize_vm_error<S: Serializer>(
+    error: &VMInternalError,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{:?}", error))
+}
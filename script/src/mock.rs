@@ -0,0 +1,203 @@
+use crate::common::LazyLoadCellOutput;
+use ckb_core::cell::{CellMeta, ResolvedOutPoint, ResolvedTransaction};
+use ckb_core::header::Header as CoreHeader;
+use ckb_core::transaction::{
+    CellOutput as CoreCellOutput, OutPoint, Transaction as CoreTransaction,
+};
+use failure::{format_err, Error as FailureError};
+use jsonrpc_types::{CellOutput, Header, Transaction};
+use serde_derive::{Deserialize, Serialize};
+use std::convert::{TryFrom, TryInto};
+
+/// A cell referenced by a mock transaction, either a resolved input or a
+/// resolved dep. `output` is absent for out points referencing only a header.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct MockCell {
+    pub output: Option<CellOutput>,
+    pub header: Option<Header>,
+}
+
+/// A transaction together with everything its inputs and deps resolve to,
+/// allowing scripts to be executed against real syscall data without a chain
+/// store. `inputs` and `deps` are matched by position with the transaction's
+/// inputs and deps.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct MockTransaction {
+    pub transaction: Transaction,
+    pub inputs: Vec<MockCell>,
+    pub deps: Vec<MockCell>,
+}
+
+pub struct ResolvedMockTransaction {
+    transaction: CoreTransaction,
+    resolved_inputs: Vec<ResolvedOutPoint>,
+    resolved_deps: Vec<ResolvedOutPoint>,
+}
+
+impl ResolvedMockTransaction {
+    pub fn transaction(&self) -> &CoreTransaction {
+        &self.transaction
+    }
+
+    pub fn resolved_transaction(&self) -> ResolvedTransaction {
+        ResolvedTransaction {
+            transaction: &self.transaction,
+            resolved_inputs: self.resolved_inputs.clone(),
+            resolved_deps: self.resolved_deps.clone(),
+            resolved_dep_groups: Vec::new(),
+        }
+    }
+}
+
+impl TryFrom<MockTransaction> for ResolvedMockTransaction {
+    type Error = FailureError;
+
+    fn try_from(json: MockTransaction) -> Result<Self, Self::Error> {
+        let MockTransaction {
+            transaction,
+            inputs,
+            deps,
+        } = json;
+        let transaction: CoreTransaction = transaction.try_into()?;
+
+        if inputs.len() != transaction.inputs().len() {
+            return Err(format_err!(
+                "expect {} mock inputs, got {}",
+                transaction.inputs().len(),
+                inputs.len()
+            ));
+        }
+        if deps.len() != transaction.deps().len() {
+            return Err(format_err!(
+                "expect {} mock deps, got {}",
+                transaction.deps().len(),
+                deps.len()
+            ));
+        }
+
+        let resolved_inputs = transaction
+            .inputs()
+            .iter()
+            .zip(inputs.into_iter())
+            .map(|(input, cell)| resolve_mock_cell(&input.previous_output, cell))
+            .collect::<Result<_, _>>()?;
+        let resolved_deps = transaction
+            .deps()
+            .iter()
+            .zip(deps.into_iter())
+            .map(|(dep, cell)| resolve_mock_cell(dep, cell))
+            .collect::<Result<_, _>>()?;
+
+        Ok(ResolvedMockTransaction {
+            transaction,
+            resolved_inputs,
+            resolved_deps,
+        })
+    }
+}
+
+fn resolve_mock_cell(
+    out_point: &OutPoint,
+    cell: MockCell,
+) -> Result<ResolvedOutPoint, FailureError> {
+    let cell_meta = match cell.output {
+        Some(output) => {
+            let output: CoreCellOutput = output.try_into()?;
+            Some(CellMeta {
+                capacity: output.capacity,
+                out_point: out_point.cell.clone().unwrap_or_default(),
+                cell_output: Some(output),
+                ..Default::default()
+            })
+        }
+        None => None,
+    };
+    let header: Option<CoreHeader> = cell.header.map(TryInto::try_into).transpose()?;
+
+    Ok(ResolvedOutPoint {
+        cell: cell_meta,
+        header: header.map(Box::new),
+    })
+}
+
+/// Every mock cell carries its own output, so nothing is ever loaded from a
+/// chain store.
+pub struct MockStore;
+
+impl LazyLoadCellOutput for MockStore {
+    fn lazy_load_cell_output(&self, cell_meta: &CellMeta) -> CoreCellOutput {
+        cell_meta
+            .cell_output
+            .clone()
+            .expect("mock cell carries its output")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_core::script::Script;
+    use ckb_core::transaction::{CellInput, TransactionBuilder};
+    use ckb_core::{capacity_bytes, Bytes, Capacity};
+    use numext_fixed_hash::h256;
+
+    fn mock_output() -> CoreCellOutput {
+        CoreCellOutput::new(
+            capacity_bytes!(100),
+            Bytes::from(vec![1, 2, 3]),
+            Script::always_success(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_resolve_mock_transaction() {
+        let input_out_point = OutPoint::new_cell(h256!("0x1"), 0);
+        let dep_out_point = OutPoint::new_cell(h256!("0x2"), 1);
+        let transaction = TransactionBuilder::default()
+            .input(CellInput::new(input_out_point.clone(), 0, vec![]))
+            .dep(dep_out_point.clone())
+            .output(mock_output())
+            .build();
+
+        let mock = MockTransaction {
+            transaction: (&transaction).into(),
+            inputs: vec![MockCell {
+                output: Some(mock_output().into()),
+                header: None,
+            }],
+            deps: vec![MockCell {
+                output: Some(mock_output().into()),
+                header: None,
+            }],
+        };
+        let resolved: ResolvedMockTransaction = mock.try_into().unwrap();
+        let rtx = resolved.resolved_transaction();
+
+        assert_eq!(rtx.transaction.hash(), transaction.hash());
+        let input_cell = rtx.resolved_inputs[0].cell().unwrap();
+        assert_eq!(Some(&input_cell.out_point), input_out_point.cell.as_ref());
+        assert_eq!(MockStore.lazy_load_cell_output(input_cell), mock_output());
+        let dep_cell = rtx.resolved_deps[0].cell().unwrap();
+        assert_eq!(Some(&dep_cell.out_point), dep_out_point.cell.as_ref());
+    }
+
+    #[test]
+    fn test_resolve_mock_transaction_with_missing_cells() {
+        let transaction = TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(h256!("0x1"), 0),
+                0,
+                vec![],
+            ))
+            .build();
+        let mock = MockTransaction {
+            transaction: (&transaction).into(),
+            inputs: vec![],
+            deps: vec![],
+        };
+        let resolved: Result<ResolvedMockTransaction, _> = mock.try_into();
+
+        assert!(resolved.is_err());
+    }
+}
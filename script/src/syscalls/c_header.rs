@@ -0,0 +1,107 @@
+use super::{
+    CellField, InputField, Source, DEBUG_PRINT_SYSCALL_NUMBER, ITEM_MISSING,
+    LOAD_CELL_BY_FIELD_SYSCALL_NUMBER, LOAD_CELL_SYSCALL_NUMBER, LOAD_HEADER_SYSCALL_NUMBER,
+    LOAD_INPUT_BY_FIELD_SYSCALL_NUMBER, LOAD_SCRIPT_HASH_SYSCALL_NUMBER,
+    LOAD_TX_HASH_SYSCALL_NUMBER, LOAD_TX_SYSCALL_NUMBER, SUCCESS,
+};
+use std::fmt::Write;
+
+const RETURN_CODES: &[(&str, u8)] = &[("CKB_SUCCESS", SUCCESS), ("CKB_ITEM_MISSING", ITEM_MISSING)];
+
+const SYSCALL_NUMBERS: &[(&str, u64)] = &[
+    ("SYS_ckb_load_tx", LOAD_TX_SYSCALL_NUMBER),
+    ("SYS_ckb_load_cell", LOAD_CELL_SYSCALL_NUMBER),
+    (
+        "SYS_ckb_load_cell_by_field",
+        LOAD_CELL_BY_FIELD_SYSCALL_NUMBER,
+    ),
+    (
+        "SYS_ckb_load_input_by_field",
+        LOAD_INPUT_BY_FIELD_SYSCALL_NUMBER,
+    ),
+    ("SYS_ckb_load_header", LOAD_HEADER_SYSCALL_NUMBER),
+    ("SYS_ckb_load_tx_hash", LOAD_TX_HASH_SYSCALL_NUMBER),
+    ("SYS_ckb_load_script_hash", LOAD_SCRIPT_HASH_SYSCALL_NUMBER),
+    ("SYS_ckb_debug", DEBUG_PRINT_SYSCALL_NUMBER),
+];
+
+const SOURCES: &[(&str, Source)] = &[
+    ("CKB_SOURCE_INPUT", Source::Input),
+    ("CKB_SOURCE_OUTPUT", Source::Output),
+    ("CKB_SOURCE_DEP", Source::Dep),
+    ("CKB_SOURCE_DEP_GROUP", Source::DepGroup),
+];
+
+const CELL_FIELDS: &[(&str, CellField)] = &[
+    ("CKB_CELL_FIELD_CAPACITY", CellField::Capacity),
+    ("CKB_CELL_FIELD_DATA", CellField::Data),
+    ("CKB_CELL_FIELD_DATA_HASH", CellField::DataHash),
+    ("CKB_CELL_FIELD_LOCK_HASH", CellField::LockHash),
+    ("CKB_CELL_FIELD_TYPE", CellField::Type),
+    ("CKB_CELL_FIELD_TYPE_HASH", CellField::TypeHash),
+    ("CKB_CELL_FIELD_LOCK", CellField::Lock),
+    (
+        "CKB_CELL_FIELD_OCCUPIED_CAPACITY",
+        CellField::OccupiedCapacity,
+    ),
+];
+
+const INPUT_FIELDS: &[(&str, InputField)] = &[
+    ("CKB_INPUT_FIELD_ARGS", InputField::Args),
+    ("CKB_INPUT_FIELD_OUT_POINT", InputField::OutPoint),
+];
+
+fn write_defines<T: Copy + Into<u64>>(header: &mut String, defines: &[(&str, T)]) {
+    for (name, value) in defines {
+        writeln!(header, "#define {} {}", name, (*value).into()).expect("write to string");
+    }
+    header.push('\n');
+}
+
+/// Generates a C header defining the syscall numbers, return codes, sources
+/// and fields understood by CKB VM, so contracts never duplicate them by hand.
+pub fn generate_c_header() -> String {
+    let mut header = String::new();
+    header.push_str("/* Generated by `ckb cli script-constants`, do not edit. */\n");
+    header.push_str("#ifndef CKB_CONSTS_H_\n#define CKB_CONSTS_H_\n\n");
+    write_defines(&mut header, RETURN_CODES);
+    write_defines(&mut header, SYSCALL_NUMBERS);
+    write_defines(&mut header, SOURCES);
+    write_defines(&mut header, CELL_FIELDS);
+    write_defines(&mut header, INPUT_FIELDS);
+    header.push_str("#endif /* CKB_CONSTS_H_ */\n");
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every value written to the header must parse back to the same
+    // variant, otherwise contracts would request a different field.
+    #[test]
+    fn test_header_values_match_parsers() {
+        for (_, source) in SOURCES {
+            assert_eq!(Source::parse_from_u64((*source).into()), Ok(*source));
+        }
+        for (_, field) in CELL_FIELDS {
+            assert_eq!(CellField::parse_from_u64((*field).into()), Ok(*field));
+        }
+        for (_, field) in INPUT_FIELDS {
+            assert_eq!(InputField::parse_from_u64((*field).into()), Ok(*field));
+        }
+    }
+
+    #[test]
+    fn test_generate_c_header() {
+        let header = generate_c_header();
+
+        assert!(header.starts_with("/* Generated by"));
+        assert!(header.contains("#define CKB_ITEM_MISSING 2\n"));
+        assert!(header.contains("#define SYS_ckb_load_tx 2049\n"));
+        assert!(header.contains("#define SYS_ckb_debug 2177\n"));
+        assert!(header.contains("#define CKB_SOURCE_DEP 3\n"));
+        assert!(header.contains("#define CKB_CELL_FIELD_OCCUPIED_CAPACITY 7\n"));
+        assert!(header.ends_with("#endif /* CKB_CONSTS_H_ */\n"));
+    }
+}
@@ -3,15 +3,15 @@ use ckb_vm::{
     registers::{A0, A7},
     Error as VMError, Memory, Register, SupportMachine, Syscalls,
 };
-use log::debug;
 
 pub struct Debugger<'a> {
     prefix: &'a str,
+    printer: &'a dyn Fn(&str, &str),
 }
 
 impl<'a> Debugger<'a> {
-    pub fn new(prefix: &'a str) -> Debugger<'a> {
-        Debugger { prefix }
+    pub fn new(prefix: &'a str, printer: &'a dyn Fn(&str, &str)) -> Debugger<'a> {
+        Debugger { prefix, printer }
     }
 }
 
@@ -43,7 +43,7 @@ impl<'a, Mac: SupportMachine> Syscalls<Mac> for Debugger<'a> {
 
         machine.add_cycles((buffer.len() as u64 + 1) * 10)?;
         let s = String::from_utf8(buffer).map_err(|_| VMError::ParseError)?;
-        debug!(target: "script", "{} DEBUG OUTPUT: {}", self.prefix, s);
+        (self.printer)(self.prefix, &s);
         Ok(true)
     }
 }
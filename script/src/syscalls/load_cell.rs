@@ -15,6 +15,7 @@ pub struct LoadCell<'a, CS> {
     outputs: &'a [CellMeta],
     resolved_inputs: &'a [&'a ResolvedOutPoint],
     resolved_deps: &'a [&'a ResolvedOutPoint],
+    resolved_dep_groups: &'a [&'a ResolvedOutPoint],
 }
 
 impl<'a, CS: LazyLoadCellOutput + 'a> LoadCell<'a, CS> {
@@ -23,12 +24,14 @@ impl<'a, CS: LazyLoadCellOutput + 'a> LoadCell<'a, CS> {
         outputs: &'a [CellMeta],
         resolved_inputs: &'a [&'a ResolvedOutPoint],
         resolved_deps: &'a [&'a ResolvedOutPoint],
+        resolved_dep_groups: &'a [&'a ResolvedOutPoint],
     ) -> LoadCell<'a, CS> {
         LoadCell {
             store,
             outputs,
             resolved_inputs,
             resolved_deps,
+            resolved_dep_groups,
         }
     }
 
@@ -37,6 +40,7 @@ impl<'a, CS: LazyLoadCellOutput + 'a> LoadCell<'a, CS> {
             Source::Input => self.resolved_inputs.get(index).and_then(|r| r.cell()),
             Source::Output => self.outputs.get(index),
             Source::Dep => self.resolved_deps.get(index).and_then(|r| r.cell()),
+            Source::DepGroup => self.resolved_dep_groups.get(index).and_then(|r| r.cell()),
         }
     }
 }
@@ -10,6 +10,7 @@ use ckb_vm::{
     Error as VMError, Register, SupportMachine, Syscalls,
 };
 use flatbuffers::FlatBufferBuilder;
+use occupied_capacity::OccupiedCapacity;
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -18,6 +19,7 @@ pub struct LoadCellByField<'a, CS> {
     outputs: &'a [CellMeta],
     resolved_inputs: &'a [&'a ResolvedOutPoint],
     resolved_deps: &'a [&'a ResolvedOutPoint],
+    resolved_dep_groups: &'a [&'a ResolvedOutPoint],
 }
 
 impl<'a, CS: LazyLoadCellOutput> LoadCellByField<'a, CS> {
@@ -26,12 +28,14 @@ impl<'a, CS: LazyLoadCellOutput> LoadCellByField<'a, CS> {
         outputs: &'a [CellMeta],
         resolved_inputs: &'a [&'a ResolvedOutPoint],
         resolved_deps: &'a [&'a ResolvedOutPoint],
+        resolved_dep_groups: &'a [&'a ResolvedOutPoint],
     ) -> LoadCellByField<'a, CS> {
         LoadCellByField {
             store,
             outputs,
             resolved_inputs,
             resolved_deps,
+            resolved_dep_groups,
         }
     }
 
@@ -40,6 +44,7 @@ impl<'a, CS: LazyLoadCellOutput> LoadCellByField<'a, CS> {
             Source::Input => self.resolved_inputs.get(index).and_then(|r| r.cell()),
             Source::Output => self.outputs.get(index),
             Source::Dep => self.resolved_deps.get(index).and_then(|r| r.cell()),
+            Source::DepGroup => self.resolved_dep_groups.get(index).and_then(|r| r.cell()),
         }
     }
 }
@@ -74,6 +79,16 @@ impl<'a, Mac: SupportMachine, CS: LazyLoadCellOutput> Syscalls<Mac> for LoadCell
                 store_data(machine, &buffer)?;
                 (SUCCESS, buffer.len())
             }
+            CellField::OccupiedCapacity => {
+                let output = self.store.lazy_load_cell_output(cell);
+                let occupied_capacity = output
+                    .occupied_capacity()
+                    .map_err(|_| VMError::Unexpected)?;
+                let mut buffer = vec![];
+                buffer.write_u64::<LittleEndian>(occupied_capacity.as_u64())?;
+                store_data(machine, &buffer)?;
+                (SUCCESS, buffer.len())
+            }
             CellField::Data => {
                 let output = self.store.lazy_load_cell_output(cell);
                 store_data(machine, &output.data)?;
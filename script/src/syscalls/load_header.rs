@@ -13,16 +13,19 @@ use std::cmp;
 pub struct LoadHeader<'a> {
     resolved_inputs: &'a [&'a ResolvedOutPoint],
     resolved_deps: &'a [&'a ResolvedOutPoint],
+    resolved_dep_groups: &'a [&'a ResolvedOutPoint],
 }
 
 impl<'a> LoadHeader<'a> {
     pub fn new(
         resolved_inputs: &'a [&'a ResolvedOutPoint],
         resolved_deps: &'a [&'a ResolvedOutPoint],
+        resolved_dep_groups: &'a [&'a ResolvedOutPoint],
     ) -> LoadHeader<'a> {
         LoadHeader {
             resolved_inputs,
             resolved_deps,
+            resolved_dep_groups,
         }
     }
 
@@ -31,6 +34,7 @@ impl<'a> LoadHeader<'a> {
             Source::Input => self.resolved_inputs.get(index).and_then(|r| r.header()),
             Source::Output => None,
             Source::Dep => self.resolved_deps.get(index).and_then(|r| r.header()),
+            Source::DepGroup => self.resolved_dep_groups.get(index).and_then(|r| r.header()),
         }
     }
 }
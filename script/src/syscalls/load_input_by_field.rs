@@ -24,7 +24,7 @@ impl<'a> LoadInputByField<'a> {
         match source {
             Source::Input => self.inputs.get(index).cloned(),
             Source::Output => None,
-            Source::Dep => None,
+            Source::Dep | Source::DepGroup => None,
         }
     }
 }
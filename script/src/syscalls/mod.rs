@@ -1,4 +1,5 @@
 mod builder;
+mod c_header;
 mod debugger;
 mod load_cell;
 mod load_cell_by_field;
@@ -10,6 +11,7 @@ mod load_tx_hash;
 mod utils;
 
 pub use self::builder::build_tx;
+pub use self::c_header::generate_c_header;
 pub use self::debugger::Debugger;
 pub use self::load_cell::LoadCell;
 pub use self::load_cell_by_field::LoadCellByField;
@@ -42,6 +44,13 @@ enum CellField {
     LockHash = 3,
     Type = 4,
     TypeHash = 5,
+    OccupiedCapacity = 7,
+}
+
+impl From<CellField> for u64 {
+    fn from(value: CellField) -> u64 {
+        value as u64
+    }
 }
 
 impl CellField {
@@ -54,6 +63,7 @@ impl CellField {
             4 => Ok(CellField::Type),
             5 => Ok(CellField::TypeHash),
             6 => Ok(CellField::Lock),
+            7 => Ok(CellField::OccupiedCapacity),
             _ => Err(Error::ParseError),
         }
     }
@@ -65,6 +75,12 @@ enum InputField {
     OutPoint = 1,
 }
 
+impl From<InputField> for u64 {
+    fn from(value: InputField) -> u64 {
+        value as u64
+    }
+}
+
 impl InputField {
     fn parse_from_u64(i: u64) -> Result<InputField, Error> {
         match i {
@@ -75,11 +91,21 @@ impl InputField {
     }
 }
 
+// The dep groups are expanded in place, a script finds their members at the
+// same `Dep` indices as if they were listed one by one. The group cells
+// themselves are loaded from `DepGroup`.
 #[derive(Debug, PartialEq, Clone, Copy, Eq)]
 enum Source {
     Input = 1,
     Output = 2,
     Dep = 3,
+    DepGroup = 4,
+}
+
+impl From<Source> for u64 {
+    fn from(value: Source) -> u64 {
+        value as u64
+    }
 }
 
 impl Source {
@@ -88,6 +114,7 @@ impl Source {
             1 => Ok(Source::Input),
             2 => Ok(Source::Output),
             3 => Ok(Source::Dep),
+            4 => Ok(Source::DepGroup),
             _ => Err(Error::ParseError),
         }
     }
@@ -115,6 +142,7 @@ mod tests {
     use flatbuffers::FlatBufferBuilder;
     use hash::blake2b_256;
     use numext_fixed_hash::H256;
+    use occupied_capacity::OccupiedCapacity;
     use proptest::{collection::size_range, prelude::*};
     use std::sync::Arc;
 
@@ -126,6 +154,7 @@ mod tests {
         CellMeta {
             capacity: output.capacity,
             data_hash: None,
+            dep_group: false,
             out_point: CellOutPoint {
                 tx_hash: Default::default(),
                 index: 0,
@@ -285,7 +314,7 @@ mod tests {
         let outputs = vec![output];
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![];
-        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         prop_assert!(load_cell.ecall(&mut machine).is_ok());
         prop_assert_eq!(machine.registers()[A0], u64::from(ITEM_MISSING));
@@ -327,7 +356,7 @@ mod tests {
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         let mut builder = FlatBufferBuilder::new();
         let fbs_offset = FbsCellOutput::build(
@@ -427,7 +456,7 @@ mod tests {
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         let mut builder = FlatBufferBuilder::new();
         let fbs_offset = FbsCellOutput::build(
@@ -485,7 +514,7 @@ mod tests {
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell = LoadCell::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         let mut builder = FlatBufferBuilder::new();
         let fbs_offset = FbsCellOutput::build(
@@ -542,7 +571,8 @@ mod tests {
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell =
+            LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         prop_assert!(machine.memory_mut().store64(&size_addr, &16).is_ok());
 
@@ -567,6 +597,59 @@ mod tests {
         }
     }
 
+    fn _test_load_cell_occupied_capacity(data: &[u8]) -> Result<(), TestCaseError> {
+        let mut machine = DefaultCoreMachine::<u64, SparseMemory<u64>>::default();
+        let size_addr: u64 = 0;
+        let addr: u64 = 100;
+
+        machine.set_register(A0, addr); // addr
+        machine.set_register(A1, size_addr); // size_addr
+        machine.set_register(A2, 0); // offset
+        machine.set_register(A3, 0); //index
+        machine.set_register(A4, Source::Input as u64); //source: 1 input
+        machine.set_register(A5, CellField::OccupiedCapacity as u64); //field: 7 occupied capacity
+        machine.set_register(A7, LOAD_CELL_BY_FIELD_SYSCALL_NUMBER); // syscall number
+
+        let output = CellOutput::new(
+            capacity_bytes!(100),
+            Bytes::from(data),
+            Script::default(),
+            None,
+        );
+        let occupied_capacity = output.occupied_capacity().unwrap();
+        let input_cell = build_resolved_outpoint(output);
+        let outputs = vec![];
+        let resolved_inputs = vec![&input_cell];
+        let resolved_deps = vec![];
+        let store = Arc::new(new_memory_store());
+        let mut load_cell =
+            LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
+
+        prop_assert!(machine.memory_mut().store64(&size_addr, &16).is_ok());
+
+        prop_assert!(load_cell.ecall(&mut machine).is_ok());
+        prop_assert_eq!(machine.registers()[A0], u64::from(SUCCESS));
+
+        prop_assert_eq!(machine.memory_mut().load64(&size_addr), Ok(8));
+
+        let mut buffer = vec![];
+        buffer
+            .write_u64::<LittleEndian>(occupied_capacity.as_u64())
+            .unwrap();
+
+        for (i, addr) in (addr..addr + buffer.len() as u64).enumerate() {
+            prop_assert_eq!(machine.memory_mut().load8(&addr), Ok(u64::from(buffer[i])));
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_load_cell_occupied_capacity(ref data in any_with::<Vec<u8>>(size_range(1000).lift())) {
+            _test_load_cell_occupied_capacity(data)?;
+        }
+    }
+
     #[test]
     fn test_load_missing_contract() {
         let mut machine = DefaultCoreMachine::<u64, SparseMemory<u64>>::default();
@@ -591,7 +674,8 @@ mod tests {
         let resolved_inputs = vec![];
         let resolved_deps = vec![];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell =
+            LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         assert!(machine.memory_mut().store64(&size_addr, &100).is_ok());
 
@@ -751,7 +835,8 @@ mod tests {
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![&dep_cell];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell =
+            LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         prop_assert!(machine
             .memory_mut()
@@ -779,6 +864,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_dep_group_cell_data() {
+        let mut machine = DefaultCoreMachine::<u64, SparseMemory<u64>>::default();
+        let size_addr: u64 = 0;
+        let addr: u64 = 100;
+
+        let member_cell = build_resolved_outpoint(CellOutput::new(
+            capacity_bytes!(1000),
+            Bytes::from(vec![1, 2, 3]),
+            Script::default(),
+            None,
+        ));
+        let group_output = CellOutput::new_dep_group(
+            capacity_bytes!(1000),
+            &[OutPoint::new_cell(Default::default(), 0)],
+            Script::default(),
+        );
+        let group_data = group_output.data.clone();
+        let group_cell = build_resolved_outpoint(group_output);
+        let outputs = vec![];
+        let resolved_inputs = vec![];
+        let resolved_deps = vec![&member_cell];
+        let resolved_dep_groups = vec![&group_cell];
+        let store = Arc::new(new_memory_store());
+        let mut load_cell = LoadCellByField::new(
+            store,
+            &outputs,
+            &resolved_inputs,
+            &resolved_deps,
+            &resolved_dep_groups,
+        );
+
+        // the member keeps the index of the group among the deps, the group
+        // cell is loaded from its own source
+        for (source, data) in &[
+            (Source::Dep, Bytes::from(vec![1, 2, 3])),
+            (Source::DepGroup, group_data),
+        ] {
+            machine.set_register(A0, addr); // addr
+            machine.set_register(A1, size_addr); // size_addr
+            machine.set_register(A2, 0); // offset
+            machine.set_register(A3, 0); //index
+            machine.set_register(A4, *source as u64); //source
+            machine.set_register(A5, CellField::Data as u64); //field: 1 data
+            machine.set_register(A7, LOAD_CELL_BY_FIELD_SYSCALL_NUMBER); // syscall number
+            assert!(machine
+                .memory_mut()
+                .store64(&size_addr, &(data.len() as u64))
+                .is_ok());
+
+            assert!(load_cell.ecall(&mut machine).is_ok());
+            assert_eq!(machine.registers()[A0], u64::from(SUCCESS));
+            for (i, addr) in (addr..addr + data.len() as u64).enumerate() {
+                assert_eq!(machine.memory_mut().load8(&addr), Ok(u64::from(data[i])));
+            }
+        }
+    }
+
     fn _test_load_dep_cell_data_hash(data: &[u8]) -> Result<(), TestCaseError> {
         let mut machine = DefaultCoreMachine::<u64, SparseMemory<u64>>::default();
         let size_addr: u64 = 0;
@@ -808,7 +951,8 @@ mod tests {
         let resolved_inputs = vec![&input_cell];
         let resolved_deps = vec![&dep_cell];
         let store = Arc::new(new_memory_store());
-        let mut load_cell = LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps);
+        let mut load_cell =
+            LoadCellByField::new(store, &outputs, &resolved_inputs, &resolved_deps, &[]);
 
         let data_hash = blake2b_256(&data);
 
@@ -866,7 +1010,7 @@ mod tests {
         let dep_cell = ResolvedOutPoint::header_only(header);
         let resolved_inputs = vec![];
         let resolved_deps = vec![&dep_cell];
-        let mut load_cell = LoadHeader::new(&resolved_inputs, &resolved_deps);
+        let mut load_cell = LoadHeader::new(&resolved_inputs, &resolved_deps, &[]);
 
         prop_assert!(machine
             .memory_mut()
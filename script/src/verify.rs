@@ -1,24 +1,28 @@
 use crate::{
     common::LazyLoadCellOutput,
     cost_model::instruction_cycles,
+    data_cache::ScriptDataCache,
+    gdb::GdbStub,
     syscalls::{
         build_tx, Debugger, LoadCell, LoadCellByField, LoadHeader, LoadInputByField,
         LoadScriptHash, LoadTx, LoadTxHash,
     },
-    Runner, ScriptConfig, ScriptError,
+    Runner, ScriptConfig, ScriptError, ScriptLocation,
 };
 use ckb_core::cell::{CellMeta, ResolvedOutPoint, ResolvedTransaction};
-use ckb_core::script::{Script, ALWAYS_SUCCESS_HASH};
+use ckb_core::script::{Script, ALWAYS_SUCCESS_HASH, DEP_GROUP_HASH, TYPE_ID_CODE_HASH};
 use ckb_core::transaction::{CellInput, CellOutPoint};
 use ckb_core::{Bytes, Cycle};
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+use ckb_vm::machine::asm::{AsmCoreMachine, AsmMachine};
 use ckb_vm::{
-    machine::asm::{AsmCoreMachine, AsmMachine},
     DefaultCoreMachine, DefaultMachineBuilder, SparseMemory, SupportMachine, TraceMachine,
 };
 use flatbuffers::FlatBufferBuilder;
 use fnv::FnvHashMap;
-use log::info;
+use log::{debug, info};
 use numext_fixed_hash::H256;
+use std::net::TcpListener;
 use std::sync::Arc;
 
 // This struct leverages CKB VM to verify transaction inputs.
@@ -32,9 +36,12 @@ pub struct TransactionScriptsVerifier<'a, CS> {
     tx_builder: FlatBufferBuilder<'a>,
     resolved_inputs: Vec<&'a ResolvedOutPoint>,
     resolved_deps: Vec<&'a ResolvedOutPoint>,
+    resolved_dep_groups: Vec<&'a ResolvedOutPoint>,
     witnesses: FnvHashMap<u32, &'a [Vec<u8>]>,
     hash: H256,
     config: &'a ScriptConfig,
+    data_cache: Option<&'a ScriptDataCache>,
+    debug_printer: Box<dyn Fn(&str, &str) + 'a>,
 }
 
 impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
@@ -42,9 +49,30 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
         rtx: &'a ResolvedTransaction,
         store: Arc<CS>,
         config: &'a ScriptConfig,
+    ) -> TransactionScriptsVerifier<'a, CS> {
+        Self::build(rtx, store, config, None)
+    }
+
+    /// Loads dep cell data through `data_cache`, which can be shared by the
+    /// verifiers of all the transactions in a block.
+    pub fn with_data_cache(
+        rtx: &'a ResolvedTransaction,
+        store: Arc<CS>,
+        config: &'a ScriptConfig,
+        data_cache: &'a ScriptDataCache,
+    ) -> TransactionScriptsVerifier<'a, CS> {
+        Self::build(rtx, store, config, Some(data_cache))
+    }
+
+    fn build(
+        rtx: &'a ResolvedTransaction,
+        store: Arc<CS>,
+        config: &'a ScriptConfig,
+        data_cache: Option<&'a ScriptDataCache>,
     ) -> TransactionScriptsVerifier<'a, CS> {
         let tx_hash = rtx.transaction.hash();
         let resolved_deps: Vec<&'a ResolvedOutPoint> = rtx.resolved_deps.iter().collect();
+        let resolved_dep_groups = rtx.resolved_dep_groups.iter().collect();
         let resolved_inputs = rtx.resolved_inputs.iter().collect();
         let inputs = rtx.transaction.inputs().iter().collect();
         let outputs = rtx
@@ -63,6 +91,7 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
                     cellbase: false,
                     capacity: output.capacity,
                     data_hash: None,
+                    dep_group: output.is_dep_group(),
                 }
             })
             .collect();
@@ -79,11 +108,22 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
             .enumerate()
             .map(|(i, dep_cell)| {
                 if let Some(cell_meta) = &dep_cell.cell {
-                    let hash = match cell_meta.data_hash() {
-                        Some(hash) => hash.to_owned(),
-                        None => {
+                    let cached_hash =
+                        data_cache.and_then(|cache| cache.data_hash(&cell_meta.out_point));
+                    let hash = match (cell_meta.data_hash(), cached_hash) {
+                        (Some(hash), _) => hash.to_owned(),
+                        (None, Some(hash)) => hash,
+                        (None, None) => {
                             let output = store.lazy_load_cell_output(cell_meta);
-                            output.data_hash()
+                            let hash = output.data_hash();
+                            if let Some(cache) = data_cache {
+                                cache.insert(
+                                    cell_meta.out_point.clone(),
+                                    hash.clone(),
+                                    output.data,
+                                );
+                            }
+                            hash
                         }
                     };
                     Some((hash, i))
@@ -106,12 +146,23 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
             outputs,
             resolved_inputs,
             resolved_deps,
+            resolved_dep_groups,
             witnesses,
             config,
+            data_cache,
             hash: tx_hash.to_owned(),
+            debug_printer: Box::new(|prefix, message| {
+                debug!(target: "script", "{} DEBUG OUTPUT: {}", prefix, message);
+            }),
         }
     }
 
+    /// Replaces the handler of the debug syscall, which logs the output by
+    /// default. The handler receives the script prefix and the message.
+    pub fn set_debug_printer<F: Fn(&str, &str) + 'a>(&mut self, printer: F) {
+        self.debug_printer = Box::new(printer);
+    }
+
     fn build_load_tx_hash(&self) -> LoadTxHash {
         LoadTxHash::new(&self.hash.as_bytes())
     }
@@ -126,6 +177,7 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
             &self.outputs,
             &self.resolved_inputs,
             &self.resolved_deps,
+            &self.resolved_dep_groups,
         )
     }
 
@@ -135,6 +187,7 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
             &self.outputs,
             &self.resolved_inputs,
             &self.resolved_deps,
+            &self.resolved_dep_groups,
         )
     }
 
@@ -147,20 +200,79 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
     }
 
     fn build_load_header(&'a self) -> LoadHeader<'a> {
-        LoadHeader::new(&self.resolved_inputs, &self.resolved_deps)
+        LoadHeader::new(
+            &self.resolved_inputs,
+            &self.resolved_deps,
+            &self.resolved_dep_groups,
+        )
     }
 
     // Extracts actual script binary either in dep cells.
     fn extract_script(&self, script: &'a Script) -> Result<Bytes, ScriptError> {
-        match self.binary_index.get(&script.code_hash).and_then(|index| {
-            self.resolved_deps[*index]
-                .cell
-                .as_ref()
-                .map(|cell_meta| self.store.lazy_load_cell_output(&cell_meta))
-        }) {
-            Some(cell_output) => Ok(cell_output.data),
-            None => Err(ScriptError::InvalidReferenceIndex),
+        let cell_meta = self
+            .binary_index
+            .get(&script.code_hash)
+            .and_then(|index| self.resolved_deps[*index].cell.as_ref())
+            .ok_or(ScriptError::InvalidReferenceIndex)?;
+        if let Some(data) = self
+            .data_cache
+            .and_then(|cache| cache.data(&script.code_hash))
+        {
+            return Ok(data);
+        }
+        let data = self.store.lazy_load_cell_output(&cell_meta).data;
+        if let Some(cache) = self.data_cache {
+            cache.insert(
+                cell_meta.out_point.clone(),
+                script.code_hash.clone(),
+                data.clone(),
+            );
         }
+        Ok(data)
+    }
+
+    // Extracts the script binary and builds the VM arguments, returning them
+    // along with the current script hash.
+    fn prepare_script(
+        &self,
+        script: &Script,
+        witness: Option<&&'a [Vec<u8>]>,
+        current_input: Option<&'a CellInput>,
+    ) -> Result<(Bytes, Vec<Vec<u8>>, H256), ScriptError> {
+        let mut args = vec![b"verify".to_vec()];
+        let script_binary = self.extract_script(script)?;
+        args.extend_from_slice(
+            &script
+                .args
+                .iter()
+                .map(|b| b[..].to_vec())
+                .collect::<Vec<Vec<u8>>>(),
+        );
+
+        let mut appended_arguments = vec![];
+        // TODO: change CKB VM to use Bytes in its API, then we can simplify the
+        // code here with less copying.
+        if let Some(ref input) = current_input {
+            appended_arguments.extend_from_slice(&input.args);
+        }
+        if let Some(witness) = witness {
+            appended_arguments.extend_from_slice(
+                &witness
+                    .iter()
+                    .map(|w| w.to_vec().into())
+                    .collect::<Vec<Bytes>>(),
+            );
+        }
+
+        let current_script_hash = script.hash_with_appended_arguments(&appended_arguments);
+        args.extend_from_slice(
+            &appended_arguments
+                .iter()
+                .map(|a| a[..].to_vec())
+                .collect::<Vec<Vec<u8>>>(),
+        );
+
+        Ok((script_binary, args, current_script_hash))
     }
 
     pub fn verify_script(
@@ -171,50 +283,121 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
         current_input: Option<&'a CellInput>,
         max_cycles: Cycle,
     ) -> Result<Cycle, ScriptError> {
-        if script.code_hash == ALWAYS_SUCCESS_HASH {
+        // Dep group and type id cells are validated natively by the
+        // transaction verifier
+        if script.code_hash == ALWAYS_SUCCESS_HASH
+            || script.code_hash == DEP_GROUP_HASH
+            || script.code_hash == TYPE_ID_CODE_HASH
+        {
             return Ok(0);
         }
-        let mut args = vec![b"verify".to_vec()];
-        self.extract_script(script).and_then(|script_binary| {
-            args.extend_from_slice(
-                &script
-                    .args
-                    .iter()
-                    .map(|b| b[..].to_vec())
-                    .collect::<Vec<Vec<u8>>>(),
-            );
+        let (script_binary, args, current_script_hash) =
+            self.prepare_script(script, witness, current_input)?;
+        self.run(
+            &script_binary,
+            &args,
+            prefix,
+            max_cycles,
+            &current_script_hash.as_bytes(),
+        )
+    }
 
-            let mut appended_arguments = vec![];
-            // TODO: change CKB VM to use Bytes in its API, then we can simplify the
-            // code here with less copying.
-            if let Some(ref input) = current_input {
-                appended_arguments.extend_from_slice(&input.args);
+    // Finds the script at `location` together with the witness and input
+    // passed to it.
+    fn locate_script(
+        &self,
+        location: ScriptLocation,
+    ) -> Result<
+        (
+            Script,
+            String,
+            Option<&&'a [Vec<u8>]>,
+            Option<&'a CellInput>,
+        ),
+        ScriptError,
+    > {
+        match location {
+            ScriptLocation::Input(i) => {
+                let input_cell = self
+                    .resolved_inputs
+                    .get(i)
+                    .and_then(|resolved| resolved.cell.as_ref())
+                    .ok_or(ScriptError::NoScript)?;
+                let output = self.store.lazy_load_cell_output(input_cell);
+                let prefix = format!("Transaction {}, input {}", self.hash, i);
+                Ok((
+                    output.lock,
+                    prefix,
+                    self.witnesses.get(&(i as u32)),
+                    Some(self.inputs[i]),
+                ))
             }
-            if let Some(witness) = witness {
-                appended_arguments.extend_from_slice(
-                    &witness
-                        .iter()
-                        .map(|w| w.to_vec().into())
-                        .collect::<Vec<Bytes>>(),
-                );
+            ScriptLocation::Output(i) => {
+                let type_ = self
+                    .outputs
+                    .get(i)
+                    .and_then(|cell_meta| cell_meta.cell_output.as_ref())
+                    .and_then(|output| output.type_.clone())
+                    .ok_or(ScriptError::NoScript)?;
+                let prefix = format!("Transaction {}, output {}", self.hash, i);
+                Ok((type_, prefix, None, None))
             }
+        }
+    }
 
-            let current_script_hash = script.hash_with_appended_arguments(&appended_arguments);
-            args.extend_from_slice(
-                &appended_arguments
-                    .iter()
-                    .map(|a| a[..].to_vec())
-                    .collect::<Vec<Vec<u8>>>(),
-            );
+    /// Verifies only the script at `location`.
+    pub fn verify_location(
+        &self,
+        location: ScriptLocation,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, ScriptError> {
+        let (script, prefix, witness, current_input) = self.locate_script(location)?;
+        self.verify_script(&script, &prefix, witness, current_input, max_cycles)
+    }
 
-            self.run(
-                &script_binary,
-                &args,
-                prefix,
-                max_cycles,
-                &current_script_hash.as_bytes(),
-            )
-        })
+    /// Runs the script at `location` on the Rust interpreter under a GDB
+    /// remote stub, blocking until a debugger connects to `listener`.
+    pub fn debug_script(
+        &self,
+        location: ScriptLocation,
+        listener: &TcpListener,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, ScriptError> {
+        let (script, prefix, witness, current_input) = self.locate_script(location)?;
+        let (program, args, current_script_hash) =
+            self.prepare_script(&script, witness, current_input)?;
+        let current_script_hash = current_script_hash.as_bytes();
+
+        let core_machine =
+            DefaultCoreMachine::<u64, SparseMemory<u64>>::new_with_max_cycles(max_cycles);
+        let mut machine =
+            DefaultMachineBuilder::<DefaultCoreMachine<u64, SparseMemory<u64>>>::new(core_machine)
+                .instruction_cycle_func(Box::new(instruction_cycles))
+                .syscall(Box::new(self.build_load_script_hash(&current_script_hash)))
+                .syscall(Box::new(self.build_load_tx_hash()))
+                .syscall(Box::new(self.build_load_tx()))
+                .syscall(Box::new(self.build_load_cell()))
+                .syscall(Box::new(self.build_load_cell_by_field()))
+                .syscall(Box::new(self.build_load_input_by_field()))
+                .syscall(Box::new(self.build_load_header()))
+                .syscall(Box::new(Debugger::new(&prefix, &*self.debug_printer)))
+                .build();
+        machine
+            .load_program(&program, &args)
+            .map_err(ScriptError::VMError)?;
+
+        info!(target: "script", "waiting for gdb on {:?}", listener.local_addr());
+        let (stream, _) = listener
+            .accept()
+            .map_err(|err| ScriptError::VMError(err.into()))?;
+        let code = GdbStub::new(&mut machine)
+            .serve(stream)
+            .map_err(ScriptError::VMError)?;
+        if code == 0 {
+            Ok(machine.cycles())
+        } else {
+            Err(ScriptError::ValidationFailure(code))
+        }
     }
 
     pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, ScriptError> {
@@ -274,50 +457,14 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
         max_cycles: Cycle,
         current_script_hash: &[u8],
     ) -> Result<Cycle, ScriptError> {
+        // Both machines share the same instruction cycle function and
+        // syscalls, so the cycles consumed never depend on the runner.
         let (code, cycles) = match self.config.runner {
             Runner::Assembly => {
-                let core_machine = AsmCoreMachine::new_with_max_cycles(max_cycles);
-                let machine = DefaultMachineBuilder::<Box<AsmCoreMachine>>::new(core_machine)
-                    .instruction_cycle_func(Box::new(instruction_cycles))
-                    .syscall(Box::new(self.build_load_script_hash(current_script_hash)))
-                    .syscall(Box::new(self.build_load_tx_hash()))
-                    .syscall(Box::new(self.build_load_tx()))
-                    .syscall(Box::new(self.build_load_cell()))
-                    .syscall(Box::new(self.build_load_cell_by_field()))
-                    .syscall(Box::new(self.build_load_input_by_field()))
-                    .syscall(Box::new(self.build_load_header()))
-                    .syscall(Box::new(Debugger::new(prefix)))
-                    .build();
-                let mut machine = AsmMachine::new(machine);
-                machine
-                    .load_program(&program, &args)
-                    .map_err(ScriptError::VMError)?;
-                let code = machine.run().map_err(ScriptError::VMError)?;
-                (code, machine.machine.cycles())
+                self.run_assembly(program, args, prefix, max_cycles, current_script_hash)?
             }
             Runner::Rust => {
-                let core_machine =
-                    DefaultCoreMachine::<u64, SparseMemory<u64>>::new_with_max_cycles(max_cycles);
-                let machine =
-                    DefaultMachineBuilder::<DefaultCoreMachine<u64, SparseMemory<u64>>>::new(
-                        core_machine,
-                    )
-                    .instruction_cycle_func(Box::new(instruction_cycles))
-                    .syscall(Box::new(self.build_load_script_hash(current_script_hash)))
-                    .syscall(Box::new(self.build_load_tx_hash()))
-                    .syscall(Box::new(self.build_load_tx()))
-                    .syscall(Box::new(self.build_load_cell()))
-                    .syscall(Box::new(self.build_load_cell_by_field()))
-                    .syscall(Box::new(self.build_load_input_by_field()))
-                    .syscall(Box::new(self.build_load_header()))
-                    .syscall(Box::new(Debugger::new(prefix)))
-                    .build();
-                let mut machine = TraceMachine::new(machine);
-                machine
-                    .load_program(&program, &args)
-                    .map_err(ScriptError::VMError)?;
-                let code = machine.run().map_err(ScriptError::VMError)?;
-                (code, machine.machine.cycles())
+                self.run_rust(program, args, prefix, max_cycles, current_script_hash)?
             }
         };
         if code == 0 {
@@ -326,6 +473,79 @@ impl<'a, CS: LazyLoadCellOutput> TransactionScriptsVerifier<'a, CS> {
             Err(ScriptError::ValidationFailure(code))
         }
     }
+
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    fn run_assembly(
+        &self,
+        program: &[u8],
+        args: &[Vec<u8>],
+        prefix: &str,
+        max_cycles: Cycle,
+        current_script_hash: &[u8],
+    ) -> Result<(u8, Cycle), ScriptError> {
+        let core_machine = AsmCoreMachine::new_with_max_cycles(max_cycles);
+        let machine = DefaultMachineBuilder::<Box<AsmCoreMachine>>::new(core_machine)
+            .instruction_cycle_func(Box::new(instruction_cycles))
+            .syscall(Box::new(self.build_load_script_hash(current_script_hash)))
+            .syscall(Box::new(self.build_load_tx_hash()))
+            .syscall(Box::new(self.build_load_tx()))
+            .syscall(Box::new(self.build_load_cell()))
+            .syscall(Box::new(self.build_load_cell_by_field()))
+            .syscall(Box::new(self.build_load_input_by_field()))
+            .syscall(Box::new(self.build_load_header()))
+            .syscall(Box::new(Debugger::new(prefix, &*self.debug_printer)))
+            .build();
+        let mut machine = AsmMachine::new(machine);
+        machine
+            .load_program(&program, &args)
+            .map_err(ScriptError::VMError)?;
+        let code = machine.run().map_err(ScriptError::VMError)?;
+        Ok((code, machine.machine.cycles()))
+    }
+
+    // Assembly machine is unavailable in this build, fall back to the Rust
+    // interpreter which produces identical results.
+    #[cfg(not(all(feature = "asm", target_arch = "x86_64")))]
+    fn run_assembly(
+        &self,
+        program: &[u8],
+        args: &[Vec<u8>],
+        prefix: &str,
+        max_cycles: Cycle,
+        current_script_hash: &[u8],
+    ) -> Result<(u8, Cycle), ScriptError> {
+        self.run_rust(program, args, prefix, max_cycles, current_script_hash)
+    }
+
+    fn run_rust(
+        &self,
+        program: &[u8],
+        args: &[Vec<u8>],
+        prefix: &str,
+        max_cycles: Cycle,
+        current_script_hash: &[u8],
+    ) -> Result<(u8, Cycle), ScriptError> {
+        let core_machine =
+            DefaultCoreMachine::<u64, SparseMemory<u64>>::new_with_max_cycles(max_cycles);
+        let machine =
+            DefaultMachineBuilder::<DefaultCoreMachine<u64, SparseMemory<u64>>>::new(core_machine)
+                .instruction_cycle_func(Box::new(instruction_cycles))
+                .syscall(Box::new(self.build_load_script_hash(current_script_hash)))
+                .syscall(Box::new(self.build_load_tx_hash()))
+                .syscall(Box::new(self.build_load_tx()))
+                .syscall(Box::new(self.build_load_cell()))
+                .syscall(Box::new(self.build_load_cell_by_field()))
+                .syscall(Box::new(self.build_load_input_by_field()))
+                .syscall(Box::new(self.build_load_header()))
+                .syscall(Box::new(Debugger::new(prefix, &*self.debug_printer)))
+                .build();
+        let mut machine = TraceMachine::new(machine);
+        machine
+            .load_program(&program, &args)
+            .map_err(ScriptError::VMError)?;
+        let code = machine.run().map_err(ScriptError::VMError)?;
+        Ok((code, machine.machine.cycles()))
+    }
 }
 
 #[cfg(test)]
@@ -377,6 +597,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
 
         let store = Arc::new(new_memory_store());
@@ -386,6 +607,7 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
@@ -434,6 +656,7 @@ mod tests {
             cellbase: false,
             capacity: output.capacity,
             data_hash: Some(code_hash.clone()),
+            dep_group: false,
             out_point: dep_out_point.cell.as_ref().unwrap().clone(),
             cell_output: Some(output),
         });
@@ -459,6 +682,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![dep_cell],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
         let store = Arc::new(new_memory_store());
 
@@ -467,12 +691,109 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
         assert!(verifier.verify(100_000_000).is_ok());
     }
 
+    #[test]
+    fn check_signature_with_data_cache() {
+        let mut file = open_cell_verify();
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).unwrap();
+
+        let gen = Generator::new();
+        let privkey = gen.random_privkey();
+        let args = vec![Bytes::from(b"foo".to_vec()), Bytes::from(b"bar".to_vec())];
+        let mut witness_data = vec![];
+
+        let mut bytes = vec![];
+        for argument in &args {
+            bytes.write_all(argument).unwrap();
+        }
+        let hash1 = sha3_256(&bytes);
+        let hash2 = sha3_256(hash1);
+        let signature = privkey.sign_recoverable(&hash2.into()).unwrap();
+
+        let signature_der = signature.serialize_der();
+        let mut hex_signature = vec![0; signature_der.len() * 2];
+        hex_encode(&signature_der, &mut hex_signature).expect("hex signature");
+        witness_data.insert(0, hex_signature);
+
+        let pubkey = privkey.pubkey().unwrap().serialize();
+        let mut hex_pubkey = vec![0; pubkey.len() * 2];
+        hex_encode(&pubkey, &mut hex_pubkey).expect("hex pubkey");
+        witness_data.insert(0, hex_pubkey);
+
+        let code_hash: H256 = (&blake2b_256(&buffer)).into();
+        let dep_out_point = OutPoint::new_cell(H256::from_trimmed_hex_str("123").unwrap(), 8);
+        let output = CellOutput::new(
+            Capacity::bytes(buffer.len()).unwrap(),
+            Bytes::from(buffer),
+            Script::default(),
+            None,
+        );
+        let dep_cell = ResolvedOutPoint::cell_only(CellMeta {
+            block_number: Some(1),
+            cellbase: false,
+            capacity: output.capacity,
+            data_hash: None,
+            dep_group: false,
+            out_point: dep_out_point.cell.as_ref().unwrap().clone(),
+            cell_output: Some(output),
+        });
+
+        let script = Script::new(args, code_hash.clone());
+        let input = CellInput::new(OutPoint::null(), 0, vec![]);
+
+        let transaction = TransactionBuilder::default()
+            .input(input.clone())
+            .dep(dep_out_point.clone())
+            .witness(witness_data)
+            .build();
+
+        let output = CellOutput::new(capacity_bytes!(100), Bytes::default(), script, None);
+        let dummy_cell = ResolvedOutPoint::cell_only(CellMeta {
+            cell_output: Some(output.clone()),
+            block_number: Some(1),
+            capacity: output.capacity,
+            ..Default::default()
+        });
+
+        let rtx = ResolvedTransaction {
+            transaction: &transaction,
+            resolved_deps: vec![dep_cell],
+            resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
+        };
+        let store = Arc::new(new_memory_store());
+        let config = ScriptConfig {
+            runner: Runner::Assembly,
+            ..Default::default()
+        };
+        let data_cache = ScriptDataCache::new();
+
+        let verifier = TransactionScriptsVerifier::with_data_cache(
+            &rtx,
+            Arc::clone(&store),
+            &config,
+            &data_cache,
+        );
+        let cycles = verifier.verify(100_000_000).unwrap();
+        assert_eq!(data_cache.len(), 1);
+        assert_eq!(
+            data_cache.data_hash(dep_out_point.cell.as_ref().unwrap()),
+            Some(code_hash.clone())
+        );
+
+        // the second run reads the dep data from the cache
+        let verifier =
+            TransactionScriptsVerifier::with_data_cache(&rtx, store, &config, &data_cache);
+        assert_eq!(verifier.verify(100_000_000), Ok(cycles));
+    }
+
     #[test]
     fn check_signature_rust() {
         let mut file = open_cell_verify();
@@ -515,6 +836,7 @@ mod tests {
             cellbase: false,
             capacity: output.capacity,
             data_hash: Some(code_hash.clone()),
+            dep_group: false,
             out_point: dep_out_point.cell.clone().unwrap(),
             cell_output: Some(output),
         });
@@ -540,6 +862,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![dep_cell],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
         let store = Arc::new(new_memory_store());
 
@@ -548,12 +871,105 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Rust,
+                ..Default::default()
             },
         );
 
         assert!(verifier.verify(100_000_000).is_ok());
     }
 
+    #[cfg(feature = "asm")]
+    #[test]
+    fn check_signature_cycles_match_between_runners() {
+        let mut file = open_cell_verify();
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).unwrap();
+
+        let gen = Generator::new();
+        let privkey = gen.random_privkey();
+        let args = vec![Bytes::from(b"foo".to_vec()), Bytes::from(b"bar".to_vec())];
+        let mut witness_data = vec![];
+
+        let mut bytes = vec![];
+        for argument in &args {
+            bytes.write_all(argument).unwrap();
+        }
+        let hash1 = sha3_256(&bytes);
+        let hash2 = sha3_256(hash1);
+        let signature = privkey.sign_recoverable(&hash2.into()).unwrap();
+
+        let signature_der = signature.serialize_der();
+        let mut hex_signature = vec![0; signature_der.len() * 2];
+        hex_encode(&signature_der, &mut hex_signature).expect("hex signature");
+        witness_data.insert(0, hex_signature);
+
+        let pubkey = privkey.pubkey().unwrap().serialize();
+        let mut hex_pubkey = vec![0; pubkey.len() * 2];
+        hex_encode(&pubkey, &mut hex_pubkey).expect("hex pubkey");
+        witness_data.insert(0, hex_pubkey);
+
+        let code_hash: H256 = (&blake2b_256(&buffer)).into();
+        let dep_out_point = OutPoint::new_cell(H256::from_trimmed_hex_str("123").unwrap(), 8);
+        let output = CellOutput::new(
+            Capacity::bytes(buffer.len()).unwrap(),
+            Bytes::from(buffer),
+            Script::default(),
+            None,
+        );
+        let dep_cell = ResolvedOutPoint::cell_only(CellMeta {
+            block_number: Some(1),
+            cellbase: false,
+            capacity: output.capacity,
+            data_hash: Some(code_hash.clone()),
+            dep_group: false,
+            out_point: dep_out_point.cell.clone().unwrap(),
+            cell_output: Some(output),
+        });
+
+        let script = Script::new(args, code_hash);
+        let input = CellInput::new(OutPoint::null(), 0, vec![]);
+
+        let transaction = TransactionBuilder::default()
+            .input(input.clone())
+            .dep(dep_out_point)
+            .witness(witness_data)
+            .build();
+
+        let output = CellOutput::new(capacity_bytes!(100), Bytes::default(), script, None);
+        let dummy_cell = ResolvedOutPoint::cell_only(CellMeta {
+            cell_output: Some(output.clone()),
+            block_number: Some(1),
+            capacity: output.capacity,
+            ..Default::default()
+        });
+
+        let rtx = ResolvedTransaction {
+            transaction: &transaction,
+            resolved_deps: vec![dep_cell],
+            resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
+        };
+        let store = Arc::new(new_memory_store());
+
+        let assembly_config = ScriptConfig {
+            runner: Runner::Assembly,
+            ..Default::default()
+        };
+        let rust_config = ScriptConfig {
+            runner: Runner::Rust,
+            ..Default::default()
+        };
+        let assembly_cycles =
+            TransactionScriptsVerifier::new(&rtx, Arc::clone(&store), &assembly_config)
+                .verify(100_000_000)
+                .unwrap();
+        let rust_cycles = TransactionScriptsVerifier::new(&rtx, store, &rust_config)
+            .verify(100_000_000)
+            .unwrap();
+
+        assert_eq!(assembly_cycles, rust_cycles);
+    }
+
     #[test]
     fn check_signature_with_not_enough_cycles() {
         let mut file = open_cell_verify();
@@ -596,6 +1012,7 @@ mod tests {
             block_number: Some(1),
             cellbase: false,
             data_hash: Some(code_hash.clone()),
+            dep_group: false,
             capacity: output.capacity,
             out_point: dep_out_point.cell.as_ref().unwrap().clone(),
         });
@@ -621,6 +1038,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![dep_cell],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
 
         let store = Arc::new(new_memory_store());
@@ -630,6 +1048,7 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
@@ -704,6 +1123,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![dep_cell],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
 
         let store = Arc::new(new_memory_store());
@@ -712,6 +1132,7 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
@@ -770,6 +1191,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
 
         let store = Arc::new(new_memory_store());
@@ -778,6 +1200,7 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
@@ -848,6 +1271,7 @@ mod tests {
                 cellbase: false,
                 capacity: output.capacity,
                 data_hash: None,
+                dep_group: false,
                 out_point: dep_out_point.cell.as_ref().unwrap().clone(),
             })
         };
@@ -862,6 +1286,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![dep_cell],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
 
         let store = Arc::new(new_memory_store());
@@ -870,6 +1295,7 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
@@ -954,6 +1380,7 @@ mod tests {
             transaction: &transaction,
             resolved_deps: vec![dep_cell],
             resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
         };
 
         let store = Arc::new(new_memory_store());
@@ -963,6 +1390,7 @@ mod tests {
             store,
             &ScriptConfig {
                 runner: Runner::Assembly,
+                ..Default::default()
             },
         );
 
@@ -67,6 +67,50 @@ impl CellSet {
         }
     }
 
+    /// Restores the set persisted in the store
+    pub fn restore(cells: Vec<(H256, TransactionMeta)>) -> Self {
+        CellSet {
+            inner: cells.into_iter().collect(),
+        }
+    }
+
+    /// The transactions changed by `update(diff)` with their new metas, `None`
+    /// for the ones leaving the set, to be persisted along with the blocks
+    pub fn changes(&self, diff: &CellSetDiff) -> Vec<(H256, Option<TransactionMeta>)> {
+        let inputs = diff
+            .old_inputs
+            .iter()
+            .chain(diff.new_inputs.iter())
+            .filter_map(|o| o.cell.as_ref().map(|cell| cell.tx_hash.to_owned()));
+        let touched: FnvHashSet<H256> = diff
+            .old_outputs
+            .iter()
+            .cloned()
+            .chain(diff.new_outputs.keys().cloned())
+            .chain(inputs)
+            .collect();
+
+        // applies the diff to a copy of the touched transactions only
+        let mut changed = CellSet {
+            inner: touched
+                .iter()
+                .filter_map(|hash| {
+                    self.inner
+                        .get(hash)
+                        .map(|meta| (hash.clone(), meta.clone()))
+                })
+                .collect(),
+        };
+        changed.update(diff.clone());
+        touched
+            .into_iter()
+            .map(|hash| {
+                let meta = changed.remove(&hash);
+                (hash, meta)
+            })
+            .collect()
+    }
+
     pub fn new_overlay<'a>(&'a self, diff: &CellSetDiff) -> CellSetOverlay<'a> {
         let mut new = FnvHashMap::default();
         let mut removed = FnvHashSet::default();
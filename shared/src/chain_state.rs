@@ -1,7 +1,7 @@
 use crate::cell_set::{CellSet, CellSetDiff, CellSetOverlay};
 use crate::error::SharedError;
 use crate::tx_pool::types::PoolEntry;
-use crate::tx_pool::{PoolError, TxPool, TxPoolConfig};
+use crate::tx_pool::{fee_rate, PoolError, TxPool, TxPoolConfig};
 use crate::tx_proposal_table::TxProposalTable;
 use ckb_chain_spec::consensus::{Consensus, ProposalWindow};
 use ckb_core::block::Block;
@@ -12,12 +12,15 @@ use ckb_core::cell::{
 use ckb_core::extras::EpochExt;
 use ckb_core::header::{BlockNumber, Header};
 use ckb_core::transaction::CellOutput;
-use ckb_core::transaction::{OutPoint, ProposalShortId, Transaction};
-use ckb_core::Cycle;
+use ckb_core::transaction::{Capacity, OutPoint, ProposalShortId, Transaction};
+use ckb_core::{Cycle, EpochNumber};
 use ckb_script::ScriptConfig;
 use ckb_store::ChainStore;
 use ckb_traits::BlockMedianTimeContext;
-use ckb_verification::{PoolTransactionVerifier, TransactionVerifier};
+use ckb_verification::{
+    NonContextualTransactionVerifier, PoolTransactionVerifier, TransactionVerifier,
+};
+use faketime::unix_time_as_millis;
 use fnv::{FnvHashMap, FnvHashSet};
 use log::{error, trace};
 use numext_fixed_hash::H256;
@@ -25,6 +28,9 @@ use numext_fixed_uint::U256;
 use std::cell::{Ref, RefCell};
 use std::sync::Arc;
 
+// Transactions replaced at once at most, along with their descendants
+const MAX_REPLACED_TXS: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct ChainState<CS> {
     store: Arc<CS>,
@@ -88,7 +94,7 @@ impl<CS: ChainStore> ChainState<CS> {
         let proposal_window = consensus.tx_proposal_window();
         let proposal_ids = Self::init_proposal_ids(&store, proposal_window, tip_number);
 
-        let cell_set = Self::init_cell_set(&store, tip_number);
+        let cell_set = CellSet::restore(store.get_cell_set());
 
         let total_difficulty = store
             .get_block_ext(&tip_header.hash())
@@ -134,26 +140,6 @@ impl<CS: ChainStore> ChainState<CS> {
         proposal_ids
     }
 
-    fn init_cell_set(store: &CS, number: u64) -> CellSet {
-        let mut cell_set = CellSet::new();
-
-        for n in 0..=number {
-            let hash = store.get_block_hash(n).unwrap();
-            for tx in store.get_block_body(&hash).unwrap() {
-                let inputs = tx.input_pts();
-                let output_len = tx.outputs().len();
-
-                for o in inputs {
-                    cell_set.mark_dead(&o);
-                }
-
-                cell_set.insert(tx.hash().to_owned(), n, tx.is_cellbase(), output_len);
-            }
-        }
-
-        cell_set
-    }
-
     pub fn tip_number(&self) -> BlockNumber {
         self.tip_header.number()
     }
@@ -226,24 +212,296 @@ impl<CS: ChainStore> ChainState<CS> {
     }
 
     pub fn add_tx_to_pool(&self, tx: Transaction) -> Result<Cycle, PoolError> {
+        self.add_tx_to_pool_with(tx, Ok(None))
+    }
+
+    /// Resolves the transaction against the pool, its scripts are then run on
+    /// the returned context without holding the chain state
+    pub fn resolve_tx_for_pool<'a>(
+        &self,
+        tx: &'a Transaction,
+    ) -> Result<(ResolvedTransaction<'a>, TxVerifyContext<CS>), UnresolvableError> {
+        let rtx = self.resolve_tx_from_pending_and_staging(tx, &self.tx_pool.borrow())?;
+        Ok((rtx, self.verify_context()))
+    }
+
+    /// Adds the transaction verified on the context of `resolve_tx_for_pool`,
+    /// the pool and the tip may have changed meanwhile so it is resolved again
+    /// and only its maturity and since are checked once more
+    pub fn add_verified_tx_to_pool(
+        &self,
+        tx: Transaction,
+        verified: Result<Cycle, PoolError>,
+    ) -> Result<Cycle, PoolError> {
+        self.add_tx_to_pool_with(tx, verified.map(Some))
+    }
+
+    fn add_tx_to_pool_with(
+        &self,
+        tx: Transaction,
+        cycles: Result<Option<Cycle>, PoolError>,
+    ) -> Result<Cycle, PoolError> {
         let mut tx_pool = self.tx_pool.borrow_mut();
+        let ret = cycles.and_then(|cycles| self.admit_tx(&mut tx_pool, tx.clone(), cycles));
+        let short_id = tx.proposal_short_id();
+        // The orphans stay in the pool until their parents arrive, the
+        // evicted ones are logged by the pool
+        if tx_pool.contains_proposal_id(&short_id) {
+            tx_pool.rejections.remove(tx.hash());
+            if ret.is_ok() {
+                tx_pool.add_arrival(short_id, self.tip_number());
+            }
+        } else if let Err(err) = &ret {
+            tx_pool.add_rejection(tx.hash(), err.clone());
+        }
+        ret
+    }
+
+    fn admit_tx(
+        &self,
+        tx_pool: &mut TxPool,
+        tx: Transaction,
+        cycles: Option<Cycle>,
+    ) -> Result<Cycle, PoolError> {
+        // Reports duplicate deps and inputs by index instead of as unresolvable cells
+        NonContextualTransactionVerifier::new(&tx, self.consensus().max_tx_bytes())
+            .verify()
+            .map_err(PoolError::InvalidTx)?;
         let short_id = tx.proposal_short_id();
-        match self.resolve_tx_from_pending_and_staging(&tx, &tx_pool) {
+        let conflicts = tx_pool.conflicts(&tx);
+        let cycles = if conflicts.is_empty() {
+            self.accept_tx(tx_pool, tx, cycles)?
+        } else {
+            self.replace_txs(tx_pool, tx, &conflicts, cycles)?
+        };
+        if tx_pool.is_over_budget() {
+            self.evict_low_fee_rate_txs(tx_pool);
+            if !tx_pool.contains_proposal_id(&short_id) {
+                return Err(PoolError::OverCapacity);
+            }
+        }
+        Ok(cycles)
+    }
+
+    // The replacement is resolved and verified as if the replaced transactions
+    // and their descendants were removed, they are removed only once it is
+    // accepted. Its fee rate must exceed the ones of all the conflicts by the
+    // `replace_fee_rate_increment` of the pool, and its fee the sum of the
+    // replaced ones by the same rate of its own size.
+    fn replace_txs(
+        &self,
+        tx_pool: &mut TxPool,
+        tx: Transaction,
+        conflicts: &FnvHashSet<ProposalShortId>,
+        cycles: Option<Cycle>,
+    ) -> Result<Cycle, PoolError> {
+        let increment = tx_pool.config.replace_fee_rate_increment;
+        let (replaced, replaced_fees) = {
+            let entries = tx_pool
+                .tx_and_descendants(conflicts.iter(), MAX_REPLACED_TXS)
+                .ok_or(PoolError::TooManyReplacements {
+                    max: MAX_REPLACED_TXS,
+                })?;
+            let fees = entries
+                .iter()
+                .filter_map(|entry| entry.fee)
+                .map(Capacity::as_u64)
+                .fold(0, u64::saturating_add);
+            let ids: FnvHashSet<_> = entries
+                .iter()
+                .map(|entry| entry.transaction.proposal_short_id())
+                .collect();
+            (ids, fees)
+        };
+        let highest = conflicts
+            .iter()
+            .filter_map(|id| tx_pool.get_entry(id))
+            .map(|entry| {
+                let rate = entry
+                    .fee
+                    .map(|fee| fee_rate(fee, entry.size))
+                    .unwrap_or_else(u64::max_value);
+                (entry.transaction.hash().to_owned(), rate)
+            })
+            .max_by_key(|(_, rate)| *rate);
+
+        let (fee, cycles) = {
+            let cell_provider = ReplacementCellProvider {
+                chain_state: self,
+                tx_pool,
+                replaced: &replaced,
+            };
+            let mut seen_inputs = FnvHashSet::default();
+            let rtx = resolve_transaction(&tx, &mut seen_inputs, &cell_provider, self)
+                .map_err(PoolError::UnresolvableTransaction)?;
+            let fee = rtx.fee().unwrap_or_else(|_| Capacity::zero());
+            let size = tx.serialized_size();
+            if let Some((conflict, rate)) = highest {
+                let min = rate.saturating_add(increment);
+                let actual = fee_rate(fee, size);
+                if actual < min {
+                    Err(PoolError::LowReplaceFeeRate {
+                        conflict,
+                        min,
+                        actual,
+                    })?;
+                }
+            }
+            let min = Capacity::shannons(
+                replaced_fees.saturating_add(increment.saturating_mul(size as u64) / 1000),
+            );
+            if fee < min {
+                Err(PoolError::LowReplaceFee { min, actual: fee })?;
+            }
+            let min_fee_rate = tx_pool.min_fee_rate();
+            let actual = fee_rate(fee, size);
+            if actual < min_fee_rate {
+                Err(PoolError::LowFeeRate {
+                    min: min_fee_rate,
+                    actual,
+                })?;
+            }
+            (fee, self.verify_rtx(&rtx, cycles)?)
+        };
+
+        for id in conflicts {
+            for entry in tx_pool.remove_tx_and_descendants(id) {
+                tx_pool.add_rejection(
+                    entry.transaction.hash(),
+                    PoolError::Replaced {
+                        by: tx.hash().to_owned(),
+                    },
+                );
+            }
+        }
+        self.add_verified_tx(tx_pool, cycles, Some(fee), tx);
+        Ok(cycles)
+    }
+
+    // Evicts the pending transactions whose packages with their ancestors pay
+    // the lowest fee rates, along with their descendants, until the pool fits
+    // in its budget. The proposed ones are kept since they are about to be
+    // committed.
+    fn evict_low_fee_rate_txs(&self, tx_pool: &mut TxPool) {
+        let mut fee_rates: Vec<_> = tx_pool
+            .pending_txs_iter()
+            .map(|entry| {
+                (
+                    tx_pool.ancestors_fee_rate(entry),
+                    entry.transaction.proposal_short_id(),
+                )
+            })
+            .collect();
+        fee_rates.sort_by_key(|(rate, _)| *rate);
+        for (rate, id) in fee_rates {
+            if !tx_pool.is_over_budget() {
+                break;
+            }
+            for entry in tx_pool.evict(&id, rate) {
+                trace!(
+                    target: "tx_pool",
+                    "evict {:#x}, fee rate {}",
+                    entry.transaction.hash(),
+                    rate
+                );
+            }
+        }
+    }
+
+    fn accept_tx(
+        &self,
+        tx_pool: &mut TxPool,
+        tx: Transaction,
+        cycles: Option<Cycle>,
+    ) -> Result<Cycle, PoolError> {
+        match self.resolve_tx_from_pending_and_staging(&tx, tx_pool) {
             Ok(rtx) => {
-                self.verify_rtx(&rtx, None).map(|cycles| {
-                    if self.contains_proposal_id(&short_id) {
-                        // if tx is proposed, we resolve from staging, verify again
-                        self.staging_tx_and_descendants(&mut tx_pool, Some(cycles), tx);
-                    } else {
-                        tx_pool.enqueue_tx(Some(cycles), tx);
+                let fee = rtx.fee().ok();
+                let min_fee_rate = tx_pool.min_fee_rate();
+                if min_fee_rate > 0 {
+                    let fee = rtx.fee().map_err(|err| PoolError::InvalidTx(err.into()))?;
+                    let actual = fee_rate(fee, tx.serialized_size());
+                    if actual < min_fee_rate {
+                        return Err(PoolError::LowFeeRate {
+                            min: min_fee_rate,
+                            actual,
+                        });
                     }
+                }
+                self.verify_rtx(&rtx, cycles).map(|cycles| {
+                    self.add_verified_tx(tx_pool, cycles, fee, tx.clone());
                     cycles
                 })
             }
-            Err(err) => Err(PoolError::UnresolvableTransaction(err)),
+            Err(err) => {
+                // Kept until the transactions of the unknown cells arrive
+                if let UnresolvableError::Unknown(out_points) = &err {
+                    tx_pool.add_orphan(cycles, tx.clone(), out_points.clone());
+                }
+                Err(PoolError::UnresolvableTransaction(err))
+            }
+        }
+    }
+
+    fn add_verified_tx(
+        &self,
+        tx_pool: &mut TxPool,
+        cycles: Cycle,
+        fee: Option<Capacity>,
+        tx: Transaction,
+    ) {
+        if self.contains_proposal_id(&tx.proposal_short_id()) {
+            // if tx is proposed, we resolve from staging, verify again
+            self.staging_tx_and_descendants(tx_pool, Some(cycles), tx);
+        } else {
+            tx_pool.enqueue_tx(Some(cycles), fee, tx.clone());
+            self.try_staging_orphan_by_ancestor(tx_pool, &tx);
         }
     }
 
+    /// Resolves the transaction against the tip like `add_tx_to_pool`, without
+    /// resolving it from or adding it to the pool. Its scripts are then run on
+    /// the returned context without holding the chain state, returning the
+    /// consumed cycles.
+    pub fn resolve_tx_for_dry_run<'a>(
+        &self,
+        tx: &'a Transaction,
+    ) -> Result<(ResolvedTransaction<'a>, TxVerifyContext<CS>), PoolError> {
+        NonContextualTransactionVerifier::new(tx, self.consensus().max_tx_bytes())
+            .verify()
+            .map_err(PoolError::InvalidTx)?;
+        let mut seen_inputs = FnvHashSet::default();
+        let rtx = resolve_transaction(tx, &mut seen_inputs, self, self)
+            .map_err(PoolError::UnresolvableTransaction)?;
+        Ok((rtx, self.verify_context()))
+    }
+
+    /// Fee of a transaction of the pool, its inputs are looked up in the pool
+    /// and the store whether they are spent or not
+    pub fn pool_tx_fee(&self, tx_pool: &TxPool, tx: &Transaction) -> Option<Capacity> {
+        let mut inputs_capacity = Capacity::zero();
+        for input in tx.inputs() {
+            let cell_out_point = input.previous_output.cell.as_ref()?;
+            let capacity = tx_pool
+                .get_tx(&ProposalShortId::from_tx_hash(&cell_out_point.tx_hash))
+                .filter(|prev_tx| prev_tx.hash() == &cell_out_point.tx_hash)
+                .map(|prev_tx| {
+                    prev_tx
+                        .outputs()
+                        .get(cell_out_point.index as usize)
+                        .map(|output| output.capacity)
+                })
+                .unwrap_or_else(|| {
+                    self.store
+                        .get_cell_output(&cell_out_point.tx_hash, cell_out_point.index)
+                        .map(|output| output.capacity)
+                })?;
+            inputs_capacity = inputs_capacity.safe_add(capacity).ok()?;
+        }
+        let outputs_capacity = tx.outputs_capacity().ok()?;
+        inputs_capacity.safe_sub(outputs_capacity).ok()
+    }
+
     pub fn resolve_tx_from_pending_and_staging<'a>(
         &self,
         tx: &'a Transaction,
@@ -283,35 +541,32 @@ impl<CS: ChainStore> ChainState<CS> {
                 .map_err(PoolError::InvalidTx)?;
                 Ok(cycles)
             }
-            None => {
-                let max_cycles = self.consensus.max_block_cycles();
-                let cycles = TransactionVerifier::new(
-                    &rtx,
-                    Arc::clone(&self.store),
-                    &self,
-                    self.tip_number(),
-                    self.consensus().cellbase_maturity,
-                    &self.script_config,
-                )
-                .verify(max_cycles)
-                .map_err(PoolError::InvalidTx)?;
-                Ok(cycles)
-            }
+            None => self.verify_context().verify(rtx),
+        }
+    }
+
+    fn verify_context(&self) -> TxVerifyContext<CS> {
+        TxVerifyContext {
+            store: Arc::clone(&self.store),
+            consensus: Arc::clone(&self.consensus),
+            script_config: self.script_config.clone(),
+            tip_number: self.tip_number(),
         }
     }
 
-    // remove resolved tx from orphan pool
+    // Moves the orphans whose unknown cells are all created by the transaction
+    // and its orphan descendants back into the pool, verifying them again
     pub(crate) fn try_staging_orphan_by_ancestor(&self, tx_pool: &mut TxPool, tx: &Transaction) {
         let entries = tx_pool.orphan.remove_by_ancestor(tx);
         for entry in entries {
-            if self.contains_proposal_id(&tx.proposal_short_id()) {
-                let tx_hash = entry.transaction.hash().to_owned();
-                let ret = self.staging_tx(tx_pool, entry.cycles, entry.transaction);
-                if ret.is_err() {
-                    trace!(target: "tx_pool", "staging tx {:x} failed {:?}", tx_hash, ret);
-                }
+            let tx_hash = entry.transaction.hash().to_owned();
+            let ret = if self.contains_proposal_id(&entry.transaction.proposal_short_id()) {
+                self.staging_tx(tx_pool, entry.cycles, entry.transaction)
             } else {
-                tx_pool.enqueue_tx(entry.cycles, entry.transaction);
+                self.accept_tx(tx_pool, entry.transaction, entry.cycles)
+            };
+            if ret.is_err() {
+                trace!(target: "tx_pool", "reprocessing orphan tx {:x} failed {:?}", tx_hash, ret);
             }
         }
     }
@@ -328,7 +583,8 @@ impl<CS: ChainStore> ChainState<CS> {
         match self.resolve_tx_from_staging(&tx, tx_pool) {
             Ok(rtx) => match self.verify_rtx(&rtx, cycles) {
                 Ok(cycles) => {
-                    tx_pool.add_staging(cycles, tx);
+                    let fee = rtx.fee().unwrap_or_else(|_| Capacity::zero());
+                    tx_pool.add_staging(cycles, fee, tx);
                     Ok(cycles)
                 }
                 Err(e) => {
@@ -341,17 +597,19 @@ impl<CS: ChainStore> ChainState<CS> {
                     UnresolvableError::Dead(_) => {
                         tx_pool
                             .conflict
-                            .insert(short_id, PoolEntry::new(tx, 0, cycles));
+                            .insert(short_id, PoolEntry::new(tx, 0, cycles, None));
                     }
                     UnresolvableError::Unknown(out_points) => {
                         tx_pool.add_orphan(cycles, tx, out_points.clone());
                     }
-                    // The remaining errors are Empty, UnspecifiedInputCell and
-                    // InvalidHeader. They all represent invalid transactions
-                    // that should just be discarded.
+                    // The remaining errors are Empty, UnspecifiedInputCell,
+                    // InvalidHeader, InvalidDepGroup and DuplicateDep. They all represent
+                    // invalid transactions that should just be discarded.
                     UnresolvableError::Empty => (),
                     UnresolvableError::UnspecifiedInputCell(_) => (),
                     UnresolvableError::InvalidHeader(_) => (),
+                    UnresolvableError::InvalidDepGroup(_) => (),
+                    UnresolvableError::DuplicateDep(_) => (),
                 }
                 Err(PoolError::UnresolvableTransaction(err))
             }
@@ -382,28 +640,48 @@ impl<CS: ChainStore> ChainState<CS> {
     ) {
         let mut tx_pool = self.tx_pool.borrow_mut();
 
-        let mut detached = FnvHashSet::default();
+        let mut detached = Vec::new();
         let mut attached = FnvHashSet::default();
+        let mut changes = ReorgChanges::default();
 
         for blk in detached_blocks {
-            detached.extend(blk.transactions().iter().skip(1).cloned())
+            let number = blk.header().number();
+            changes
+                .detached_blocks
+                .insert(blk.header().hash().to_owned());
+            changes
+                .detached_txs
+                .extend(blk.transactions().iter().map(|tx| tx.hash().to_owned()));
+            detached.extend(
+                blk.transactions()
+                    .iter()
+                    .skip(1)
+                    .map(|tx| (number, tx.clone())),
+            )
         }
+        // The parents are resurrected before their children
+        detached.sort_by_key(|(number, _)| *number);
 
         for blk in attached_blocks {
             attached.extend(blk.transactions().iter().skip(1).cloned())
         }
 
-        let retain: Vec<Transaction> = detached.difference(&attached).cloned().collect();
-
         tx_pool.remove_expired(detached_proposal_id);
         tx_pool.remove_committed_txs_from_staging(attached.iter());
 
-        for tx in retain {
-            if self.contains_proposal_id(&tx.proposal_short_id()) {
-                self.staging_tx_and_descendants(&mut tx_pool, None, tx);
-            } else {
-                tx_pool.enqueue_tx(None, tx);
+        if !changes.detached_blocks.is_empty() {
+            changes.attached_inputs = attached.iter().flat_map(Transaction::input_pts).collect();
+            // The resurrected transactions were verified in their blocks, they
+            // are only resolved again along with the pool
+            for (_, tx) in detached {
+                if attached.contains(&tx) || tx_pool.contains_proposal_id(&tx.proposal_short_id()) {
+                    continue;
+                }
+                let fee = self.pool_tx_fee(&tx_pool, &tx);
+                tx_pool.enqueue_tx(None, fee, tx.clone());
+                self.try_staging_orphan_by_ancestor(&mut tx_pool, &tx);
             }
+            self.revalidate_tx_pool(&mut tx_pool, &changes);
         }
 
         for tx in &attached {
@@ -417,10 +695,123 @@ impl<CS: ChainStore> ChainState<CS> {
         }
     }
 
+    // Checks the pool transactions touching the cells and the headers changed
+    // by the reorg against the new tip. The staged ones no longer spending
+    // live cells go back to the pending queue, the pending ones spending dead
+    // cells or failing the verification are dropped with their descendants,
+    // and the ones spending unknown cells wait as orphans for the resurrected
+    // transactions.
+    fn revalidate_tx_pool(&self, tx_pool: &mut TxPool, changes: &ReorgChanges) {
+        let stale_staged: Vec<_> = tx_pool
+            .staging_txs_iter()
+            .filter(|entry| changes.touches(&entry.transaction))
+            .filter(|entry| {
+                let tx = &entry.transaction;
+                tx.input_pts()
+                    .iter()
+                    .chain(tx.dep_pts().iter())
+                    .filter(|out_point| out_point.cell.is_some())
+                    .any(|out_point| {
+                        tx_pool.staging.edges.get_inner(out_point).is_none()
+                            && !CellProvider::cell(self, out_point).is_live()
+                    })
+            })
+            .map(|entry| entry.transaction.proposal_short_id())
+            .collect();
+        for id in stale_staged {
+            for entry in tx_pool.staging.remove(&id).unwrap_or_default() {
+                tx_pool.enqueue_tx(entry.cycles, entry.fee, entry.transaction);
+            }
+        }
+
+        // Orphaning a transaction makes its pending children stale in the
+        // next round, the dropped ones are removed along with their descendants
+        let mut queue: Vec<_> = tx_pool
+            .pending_txs_iter()
+            .filter(|entry| changes.touches(&entry.transaction))
+            .map(|entry| entry.transaction.proposal_short_id())
+            .collect();
+        while !queue.is_empty() {
+            let stale: Vec<_> = queue
+                .drain(..)
+                .filter_map(|id| {
+                    let entry = tx_pool.pending.get(&id)?;
+                    self.resolve_tx_from_pending_and_staging(&entry.transaction, tx_pool)
+                        .map_err(PoolError::UnresolvableTransaction)
+                        .and_then(|rtx| match entry.cycles {
+                            Some(cycles) => self.verify_rtx(&rtx, Some(cycles)).map(|_| ()),
+                            None => Ok(()),
+                        })
+                        .err()
+                        .map(|err| (id, err))
+                })
+                .collect();
+            for (id, err) in stale {
+                match err {
+                    PoolError::UnresolvableTransaction(UnresolvableError::Unknown(out_points)) => {
+                        if let Some(entry) = tx_pool.pending.remove(&id) {
+                            queue.extend(tx_pool.pending.children(&entry.transaction.output_pts()));
+                            tx_pool.add_orphan(entry.cycles, entry.transaction, out_points);
+                        }
+                    }
+                    err => {
+                        for entry in tx_pool.remove_tx_and_descendants(&id) {
+                            trace!(
+                                target: "tx_pool",
+                                "drop stale tx {:#x}, {:?}",
+                                entry.transaction.hash(),
+                                err
+                            );
+                            tx_pool.add_rejection(entry.transaction.hash(), err.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Evicts the transactions not committed before the expiry of the pool,
+    /// returns the evicted transactions
+    pub fn expire_txs(&self) -> Vec<Transaction> {
+        let mut tx_pool = self.tx_pool.borrow_mut();
+        tx_pool
+            .expire_txs(unix_time_as_millis(), self.tip_number())
+            .into_iter()
+            .map(|entry| entry.transaction)
+            .collect()
+    }
+
+    /// Drops all the transactions of the pool
+    pub fn clear_tx_pool(&self) {
+        self.tx_pool.borrow_mut().clear();
+    }
+
+    /// Removes the transaction and its descendants from the pool, returns the
+    /// removed transactions, empty when the transaction is not in the pool
+    pub fn remove_tx_from_pool(&self, hash: &H256) -> Vec<Transaction> {
+        let mut tx_pool = self.tx_pool.borrow_mut();
+        let id = ProposalShortId::from_tx_hash(hash);
+        match tx_pool.get_tx(&id) {
+            Some(ref tx) if tx.hash() == hash => tx_pool
+                .remove_tx_and_descendants(&id)
+                .into_iter()
+                .map(|entry| entry.transaction)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn get_last_txs_updated_at(&self) -> u64 {
         self.tx_pool.borrow().last_txs_updated_at
     }
 
+    /// Returns the number and the total fees of txs staged since the pool was
+    /// created, both only ever grow.
+    pub fn get_staged_txs_stats(&self) -> (u64, Capacity) {
+        let tx_pool = self.tx_pool.borrow();
+        (tx_pool.staged_txs, tx_pool.staged_fees)
+    }
+
     pub fn get_proposals(&self, proposals_limit: usize) -> Vec<ProposalShortId> {
         let tx_pool = self.tx_pool.borrow();
         tx_pool.pending.fetch(proposals_limit)
@@ -435,7 +826,7 @@ impl<CS: ChainStore> ChainState<CS> {
             .txs_iter()
             .take_while(|tx| {
                 cycles += tx.cycles.expect("staging tx have cycles");
-                size += tx.transaction.serialized_size();
+                size += tx.size;
                 (size < txs_size_limit) && (cycles < cycles_limit)
             })
             .cloned()
@@ -494,6 +885,78 @@ impl<CS: ChainStore> CellProvider for ChainState<CS> {
             CellStatus::Unspecified
         }
     }
+
+    fn load_cell_output(&self, cell_meta: &CellMeta) -> Option<CellOutput> {
+        cell_meta.cell_output.clone().or_else(|| {
+            self.store
+                .get_cell_output(&cell_meta.out_point.tx_hash, cell_meta.out_point.index)
+        })
+    }
+}
+
+// The cells and the headers gone or spent with a reorg
+#[derive(Default)]
+struct ReorgChanges {
+    detached_blocks: FnvHashSet<H256>,
+    detached_txs: FnvHashSet<H256>,
+    attached_inputs: FnvHashSet<OutPoint>,
+}
+
+impl ReorgChanges {
+    fn touches(&self, tx: &Transaction) -> bool {
+        tx.input_pts()
+            .iter()
+            .chain(tx.dep_pts().iter())
+            .any(|out_point| {
+                self.attached_inputs.contains(out_point)
+                    || out_point.cell.as_ref().map_or(false, |cell_out_point| {
+                        self.detached_txs.contains(&cell_out_point.tx_hash)
+                    })
+                    || out_point.block_hash.as_ref().map_or(false, |block_hash| {
+                        self.detached_blocks.contains(block_hash)
+                    })
+            })
+    }
+}
+
+// The cells of the pool and the chain as if the replaced transactions were
+// removed, the cells they spend are live again and the ones they create dead
+struct ReplacementCellProvider<'a, CS> {
+    chain_state: &'a ChainState<CS>,
+    tx_pool: &'a TxPool,
+    replaced: &'a FnvHashSet<ProposalShortId>,
+}
+
+impl<'a, CS: ChainStore> CellProvider for ReplacementCellProvider<'a, CS> {
+    fn cell(&self, out_point: &OutPoint) -> CellStatus {
+        let cell_out_point = match &out_point.cell {
+            Some(cell_out_point) => cell_out_point,
+            None => return CellStatus::Unspecified,
+        };
+        if self
+            .replaced
+            .contains(&ProposalShortId::from_tx_hash(&cell_out_point.tx_hash))
+        {
+            return CellStatus::Dead;
+        }
+        let staging = &self.tx_pool.staging;
+        let spent_by_replaced = |spender: Option<&Option<ProposalShortId>>| match spender {
+            Some(Some(id)) => self.replaced.contains(id),
+            _ => false,
+        };
+        if spent_by_replaced(staging.edges.get_inner(out_point)) {
+            return staging.live_cell(out_point);
+        }
+        if spent_by_replaced(staging.edges.get_outer(out_point)) {
+            return self.chain_state.cell(out_point);
+        }
+        let staging_provider = OverlayCellProvider::new(staging, self.chain_state);
+        OverlayCellProvider::new(&self.tx_pool.pending, &staging_provider).cell(out_point)
+    }
+
+    fn load_cell_output(&self, cell_meta: &CellMeta) -> Option<CellOutput> {
+        self.chain_state.load_cell_output(cell_meta)
+    }
 }
 
 impl<CS: ChainStore> HeaderProvider for ChainState<CS> {
@@ -543,6 +1006,7 @@ impl<'a, CS: ChainStore> CellProvider for ChainCellSetOverlay<'a, CS> {
                                     cellbase: tx_meta.is_cellbase(),
                                     capacity: output.capacity,
                                     data_hash: None,
+                                    dep_group: output.is_dep_group(),
                                 }
                             })
                             .or_else(|| {
@@ -560,6 +1024,13 @@ impl<'a, CS: ChainStore> CellProvider for ChainCellSetOverlay<'a, CS> {
             CellStatus::Unspecified
         }
     }
+
+    fn load_cell_output(&self, cell_meta: &CellMeta) -> Option<CellOutput> {
+        cell_meta.cell_output.clone().or_else(|| {
+            self.store
+                .get_cell_output(&cell_meta.out_point.tx_hash, cell_meta.out_point.index)
+        })
+    }
 }
 
 impl<CS: ChainStore> BlockMedianTimeContext for &ChainState<CS> {
@@ -574,4 +1045,57 @@ impl<CS: ChainStore> BlockMedianTimeContext for &ChainState<CS> {
                 .map(|header| header.timestamp())
         })
     }
+
+    fn epoch_number(&self, number: BlockNumber) -> Option<EpochNumber> {
+        self.store
+            .get_block_hash(number)
+            .and_then(|hash| self.store.get_header(&hash))
+            .map(|header| header.epoch())
+    }
+}
+
+/// What the scripts of a pool transaction are verified on, taken from the
+/// chain state so that they run without holding it
+pub struct TxVerifyContext<CS> {
+    store: Arc<CS>,
+    consensus: Arc<Consensus>,
+    script_config: ScriptConfig,
+    tip_number: BlockNumber,
+}
+
+impl<CS: ChainStore> TxVerifyContext<CS> {
+    pub fn verify(&self, rtx: &ResolvedTransaction) -> Result<Cycle, PoolError> {
+        TransactionVerifier::new(
+            rtx,
+            Arc::clone(&self.store),
+            self,
+            self.tip_number,
+            self.consensus.cellbase_maturity,
+            self.consensus.max_tx_bytes(),
+            &self.script_config,
+        )
+        .verify(self.consensus.max_block_cycles())
+        .map_err(PoolError::InvalidTx)
+    }
+}
+
+impl<CS: ChainStore> BlockMedianTimeContext for TxVerifyContext<CS> {
+    fn median_block_count(&self) -> u64 {
+        self.consensus.median_time_block_count() as u64
+    }
+
+    fn timestamp(&self, number: BlockNumber) -> Option<u64> {
+        self.store.get_block_hash(number).and_then(|hash| {
+            self.store
+                .get_header(&hash)
+                .map(|header| header.timestamp())
+        })
+    }
+
+    fn epoch_number(&self, number: BlockNumber) -> Option<EpochNumber> {
+        self.store
+            .get_block_hash(number)
+            .and_then(|hash| self.store.get_header(&hash))
+            .map(|header| header.epoch())
+    }
 }
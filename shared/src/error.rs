@@ -1,6 +1,7 @@
 use ckb_core::cell::UnresolvableError;
 use ckb_db::Error as DBError;
 use failure::Fail;
+use numext_fixed_hash::H256;
 
 #[derive(Debug, PartialEq, Clone, Eq, Fail)]
 pub enum SharedError {
@@ -14,4 +15,6 @@ pub enum SharedError {
     InvalidData(String),
     #[fail(display = "DB error: {}", _0)]
     DB(DBError),
+    #[fail(display = "BlockPruned: {:#x}", _0)]
+    BlockPruned(H256),
 }
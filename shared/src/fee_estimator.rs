@@ -0,0 +1,211 @@
+//! Fee rate estimation from the transactions of the recent blocks and the
+//! backlog of the transaction pool.
+//!
+//! Fee rates are in shannons per KB of the serialized transaction.
+
+use crate::shared::Shared;
+use crate::tx_pool::fee_rate;
+use ckb_core::block::Block;
+use ckb_core::header::BlockNumber;
+use ckb_core::transaction::{Capacity, Transaction};
+use ckb_notify::NotifyController;
+use ckb_store::ChainStore;
+use ckb_traits::ChainProvider;
+use ckb_util::RwLock;
+use log::error;
+use numext_fixed_hash::H256;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::thread;
+
+/// Number of the recent blocks sampled, also the max estimation target
+pub const FEE_HISTORY_BLOCKS: u64 = 100;
+const SUBSCRIBER_NAME: &str = "fee-estimator";
+
+/// Fee rates of the committed transactions of the recent blocks
+#[derive(Debug, Default)]
+pub struct FeeEstimator {
+    blocks: VecDeque<(BlockNumber, H256, Vec<u64>)>,
+}
+
+impl FeeEstimator {
+    /// Records the fee rates of a block attached to the main chain, the
+    /// recorded blocks not lower than it are dropped
+    pub fn attach_block(&mut self, number: BlockNumber, hash: H256, fee_rates: Vec<u64>) {
+        while self
+            .blocks
+            .back()
+            .map_or(false, |(last, _, _)| *last >= number)
+        {
+            self.blocks.pop_back();
+        }
+        self.blocks.push_back((number, hash, fee_rates));
+        while self.blocks.len() as u64 > FEE_HISTORY_BLOCKS {
+            self.blocks.pop_front();
+        }
+    }
+
+    /// Drops the highest recorded block, detached from the main chain
+    pub fn detach_block(&mut self) {
+        self.blocks.pop_back();
+    }
+
+    /// Number and hash of the highest recorded block
+    pub fn tip(&self) -> Option<(BlockNumber, &H256)> {
+        self.blocks.back().map(|(number, hash, _)| (*number, hash))
+    }
+
+    /// Recommends a fee rate for the transaction to be committed in
+    /// `target_blocks` blocks, `None` when no fee rate is sampled.
+    ///
+    /// The fee rate is picked from the sampled ones, from the median when the
+    /// pool is idle up to the 95th percentile when the backlog fills the blocks
+    /// of the target.
+    pub fn estimate(
+        &self,
+        target_blocks: u64,
+        backlog_bytes: u64,
+        max_block_bytes: u64,
+    ) -> Option<u64> {
+        let mut fee_rates = self
+            .blocks
+            .iter()
+            .flat_map(|(_, _, fee_rates)| fee_rates.iter().cloned())
+            .collect::<Vec<_>>();
+        if fee_rates.is_empty() {
+            return None;
+        }
+        fee_rates.sort_unstable();
+
+        let capacity = target_blocks.saturating_mul(max_block_bytes).max(1);
+        let percentile = 50 + 45 * backlog_bytes.min(capacity) / capacity;
+        let index = (fee_rates.len() - 1) as u64 * percentile / 100;
+        Some(fee_rates[index as usize])
+    }
+}
+
+/// Fee rates of the transactions of the block, except the cellbase and the
+/// ones whose inputs are not found in the store
+pub fn block_fee_rates<CS: ChainStore>(store: &CS, block: &Block) -> Vec<u64> {
+    block
+        .transactions()
+        .iter()
+        .skip(1)
+        .filter_map(|tx| transaction_fee(store, tx).map(|fee| fee_rate(fee, tx.serialized_size())))
+        .collect()
+}
+
+fn transaction_fee<CS: ChainStore>(store: &CS, tx: &Transaction) -> Option<Capacity> {
+    let mut inputs_capacity = Capacity::zero();
+    for input in tx.inputs() {
+        let cell_out_point = input.previous_output.cell.as_ref()?;
+        let capacity = store
+            .get_cell_output(&cell_out_point.tx_hash, cell_out_point.index)?
+            .capacity;
+        inputs_capacity = inputs_capacity.safe_add(capacity).ok()?;
+    }
+    let outputs_capacity = tx.outputs_capacity().ok()?;
+    inputs_capacity.safe_sub(outputs_capacity).ok()
+}
+
+/// Maintains a `FeeEstimator` along the main chain, and estimates the fee
+/// rates with the backlog of the pool
+pub struct FeeEstimatorService<CS> {
+    shared: Shared<CS>,
+    estimator: Arc<RwLock<FeeEstimator>>,
+}
+
+impl<CS: ChainStore> Clone for FeeEstimatorService<CS> {
+    fn clone(&self) -> Self {
+        FeeEstimatorService {
+            shared: self.shared.clone(),
+            estimator: Arc::clone(&self.estimator),
+        }
+    }
+}
+
+impl<CS: ChainStore + 'static> FeeEstimatorService<CS> {
+    pub fn new(shared: Shared<CS>) -> Self {
+        FeeEstimatorService {
+            shared,
+            estimator: Arc::new(RwLock::new(FeeEstimator::default())),
+        }
+    }
+
+    /// Samples the recent blocks of the main chain, then follows the blocks
+    /// attached and detached on every new tip
+    pub fn start<S: ToString>(&self, notify_controller: &NotifyController, thread_name: Option<S>) {
+        let new_tip_receiver = notify_controller.subscribe_new_tip(SUBSCRIBER_NAME);
+        let service = self.clone();
+
+        let mut thread_builder = thread::Builder::new();
+        if let Some(name) = thread_name {
+            thread_builder = thread_builder.name(name.to_string());
+        }
+        thread_builder
+            .spawn(move || {
+                service.sync_main_chain();
+                loop {
+                    match new_tip_receiver.recv() {
+                        Ok(_) => service.sync_main_chain(),
+                        Err(_) => {
+                            error!(target: "fee_estimator", "new tip channel is closed");
+                            break;
+                        }
+                    }
+                }
+            })
+            .expect("Start fee estimator failed");
+    }
+
+    /// Detaches the recorded blocks left off the main chain, then attaches
+    /// the blocks of the main chain up to the tip, so that a reorg replaces
+    /// every block of the old fork
+    pub(crate) fn sync_main_chain(&self) {
+        let tip_number = self.shared.chain_state().lock().tip_number();
+        let mut estimator = self.estimator.write();
+        while let Some((number, hash)) = estimator.tip() {
+            if self.shared.block_hash(number).as_ref() == Some(hash) {
+                break;
+            }
+            estimator.detach_block();
+        }
+
+        let oldest = tip_number.saturating_sub(FEE_HISTORY_BLOCKS - 1).max(1);
+        let from = estimator
+            .tip()
+            .map_or(oldest, |(number, _)| oldest.max(number + 1));
+        for number in from..=tip_number {
+            let block = match self
+                .shared
+                .block_hash(number)
+                .and_then(|hash| self.shared.block(&hash))
+            {
+                Some(block) => block,
+                // The tip moves under the round, the next new tip goes on
+                None => break,
+            };
+            let fee_rates = block_fee_rates(&**self.shared.store(), &block);
+            estimator.attach_block(number, block.header().hash().to_owned(), fee_rates);
+        }
+    }
+
+    /// Recommended fee rate for the transaction to be committed in
+    /// `target_blocks` blocks, never lower than the `min_fee_rate` of the
+    /// pool, `None` when no fee rate is sampled yet
+    pub fn estimate_fee_rate(&self, target_blocks: u64) -> Option<u64> {
+        let (backlog_bytes, max_block_bytes, min_fee_rate) = {
+            let chain_state = self.shared.chain_state().lock();
+            let tx_pool = chain_state.tx_pool();
+            (
+                tx_pool.backlog_bytes(),
+                chain_state.consensus().max_block_bytes(),
+                tx_pool.min_fee_rate(),
+            )
+        };
+        self.estimator
+            .read()
+            .estimate(target_blocks, backlog_bytes, max_block_bytes)
+            .map(|fee_rate| fee_rate.max(min_fee_rate))
+    }
+}
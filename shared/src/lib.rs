@@ -10,6 +10,7 @@
 pub mod cell_set;
 pub mod chain_state;
 pub mod error;
+pub mod fee_estimator;
 pub mod shared;
 pub mod tx_pool;
 mod tx_proposal_table;
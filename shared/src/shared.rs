@@ -2,17 +2,19 @@ use crate::chain_state::ChainState;
 use crate::error::SharedError;
 use crate::tx_pool::TxPoolConfig;
 use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::versionbits::{self, ThresholdState};
 use ckb_core::block::Block;
 use ckb_core::extras::{BlockExt, EpochExt};
 use ckb_core::header::{BlockNumber, Header};
 use ckb_core::transaction::{ProposalShortId, Transaction};
 use ckb_core::uncle::UncleBlock;
-use ckb_db::{CacheDB, DBConfig, KeyValueDB, MemoryKeyValueDB, RocksDB};
+use ckb_db::{BackendDB, CacheDB, DBConfig, IterableKeyValueDB, KeyValueDB, MemoryKeyValueDB};
 use ckb_script::ScriptConfig;
-use ckb_store::{ChainKVStore, ChainStore, COLUMNS, COLUMN_BLOCK_HEADER};
+use ckb_store::{ChainKVStore, ChainStore, Freezer, StoreConfig, COLUMNS};
 use ckb_traits::ChainProvider;
 use ckb_util::Mutex;
 use numext_fixed_hash::H256;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -163,6 +165,26 @@ impl<CS: ChainStore> ChainProvider for Shared<CS> {
         )
     }
 
+    fn versionbits_states(&self, epoch: &EpochExt) -> Vec<ThresholdState> {
+        self.store
+            .get_versionbits_states(epoch.last_block_hash_in_previous_epoch())
+            .unwrap_or_else(|| vec![ThresholdState::Defined; self.consensus.deployments().len()])
+    }
+
+    fn next_versionbits_states(
+        &self,
+        last_epoch: &EpochExt,
+        header: &Header,
+    ) -> Vec<ThresholdState> {
+        versionbits::next_states(
+            self.consensus.deployments(),
+            &self.versionbits_states(last_epoch),
+            last_epoch,
+            header,
+            |hash| self.block_header(hash),
+        )
+    }
+
     fn consensus(&self) -> &Consensus {
         &*self.consensus
     }
@@ -173,6 +195,7 @@ pub struct SharedBuilder<DB: KeyValueDB> {
     consensus: Option<Consensus>,
     tx_pool_config: Option<TxPoolConfig>,
     script_config: Option<ScriptConfig>,
+    freezer_path: Option<PathBuf>,
 }
 
 impl<DB: KeyValueDB> Default for SharedBuilder<DB> {
@@ -182,6 +205,7 @@ impl<DB: KeyValueDB> Default for SharedBuilder<DB> {
             consensus: None,
             tx_pool_config: None,
             script_config: None,
+            freezer_path: None,
         }
     }
 }
@@ -193,19 +217,20 @@ impl SharedBuilder<MemoryKeyValueDB> {
             consensus: None,
             tx_pool_config: None,
             script_config: None,
+            freezer_path: None,
         }
     }
 }
 
-impl SharedBuilder<CacheDB<RocksDB>> {
+impl SharedBuilder<CacheDB<BackendDB>> {
     pub fn new() -> Self {
         Default::default()
     }
 
-    pub fn db(mut self, config: &DBConfig) -> Self {
+    pub fn db(mut self, config: &DBConfig, store_config: &StoreConfig) -> Self {
         self.db = Some(CacheDB::new(
-            RocksDB::open(config, COLUMNS),
-            &[(COLUMN_BLOCK_HEADER, 4096)],
+            BackendDB::open(config, COLUMNS),
+            &store_config.cache_cols(),
         ));
         self
     }
@@ -213,7 +238,7 @@ impl SharedBuilder<CacheDB<RocksDB>> {
 
 pub const MIN_TXS_VERIFY_CACHE_SIZE: Option<usize> = Some(100);
 
-impl<DB: KeyValueDB> SharedBuilder<DB> {
+impl<DB: IterableKeyValueDB> SharedBuilder<DB> {
     pub fn consensus(mut self, value: Consensus) -> Self {
         self.consensus = Some(value);
         self
@@ -229,8 +254,17 @@ impl<DB: KeyValueDB> SharedBuilder<DB> {
         self
     }
 
+    /// Serves the ancient blocks from the freezer in the directory
+    pub fn freezer_path(mut self, path: Option<PathBuf>) -> Self {
+        self.freezer_path = path;
+        self
+    }
+
     pub fn build(self) -> Result<Shared<ChainKVStore<DB>>, SharedError> {
-        let store = ChainKVStore::new(self.db.unwrap());
+        let mut store = ChainKVStore::new(self.db.unwrap());
+        if let Some(path) = self.freezer_path {
+            store = store.with_freezer(Freezer::open(&path).map_err(SharedError::DB)?);
+        }
         let consensus = self.consensus.unwrap_or_else(Consensus::default);
         let tx_pool_config = self.tx_pool_config.unwrap_or_else(Default::default);
         let script_config = self.script_config.unwrap_or_else(Default::default);
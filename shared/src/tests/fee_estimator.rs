@@ -0,0 +1,91 @@
+use crate::fee_estimator::{block_fee_rates, FeeEstimator, FEE_HISTORY_BLOCKS};
+use ckb_core::block::{Block, BlockBuilder};
+use ckb_core::transaction::{CellInput, CellOutput, OutPoint, Transaction, TransactionBuilder};
+use ckb_core::{capacity_bytes, Bytes, Capacity};
+use ckb_db::MemoryKeyValueDB;
+use ckb_store::{ChainKVStore, ChainStore, StoreBatch};
+use numext_fixed_hash::{h256, H256};
+use std::sync::Arc;
+
+fn output(capacity: Capacity) -> CellOutput {
+    CellOutput::new(capacity, Bytes::default(), Default::default(), None)
+}
+
+fn block_with(txs: Vec<Transaction>) -> Block {
+    let cellbase = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(1))
+        .output(output(capacity_bytes!(100)))
+        .build();
+    BlockBuilder::default()
+        .transaction(cellbase)
+        .transactions(txs)
+        .build()
+}
+
+#[test]
+fn test_estimate() {
+    let mut estimator = FeeEstimator::default();
+    assert_eq!(estimator.estimate(1, 0, 1000), None);
+
+    estimator.attach_block(1, h256!("0x1"), (1..=101).collect());
+    // Idle pool picks the median, full blocks the 95th percentile
+    assert_eq!(estimator.estimate(1, 0, 1000), Some(51));
+    assert_eq!(estimator.estimate(1, 1000, 1000), Some(96));
+    assert_eq!(estimator.estimate(1, 5000, 1000), Some(96));
+    // The backlog fills half of the blocks of a longer target
+    assert_eq!(estimator.estimate(2, 1000, 1000), Some(73));
+
+    // Reorg replaces the detached blocks
+    estimator.attach_block(2, h256!("0x2"), vec![1000; 101]);
+    estimator.attach_block(3, h256!("0x3"), vec![1000; 101]);
+    estimator.detach_block();
+    estimator.detach_block();
+    assert_eq!(estimator.tip(), Some((1, &h256!("0x1"))));
+    estimator.attach_block(2, h256!("0x22"), vec![]);
+    assert_eq!(estimator.estimate(1, 0, 1000), Some(51));
+
+    for number in 3..FEE_HISTORY_BLOCKS + 3 {
+        estimator.attach_block(number, H256::zero(), vec![7]);
+    }
+    assert_eq!(estimator.estimate(1, 1000, 1000), Some(7));
+}
+
+#[test]
+fn test_block_fee_rates() {
+    let store = Arc::new(ChainKVStore::new(MemoryKeyValueDB::open(
+        ckb_store::COLUMNS as usize,
+    )));
+    let funding = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::null(), 0, vec![]))
+        .output(output(capacity_bytes!(1000)))
+        .build();
+    let funding_block = block_with(vec![funding.clone()]);
+    let mut batch = store.new_batch().unwrap();
+    batch.insert_block(&funding_block).unwrap();
+    batch.attach_block(&funding_block).unwrap();
+    batch.commit().unwrap();
+
+    let spending = TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(funding.hash().to_owned(), 0),
+            0,
+            vec![],
+        ))
+        .output(output(capacity_bytes!(900)))
+        .build();
+    let unknown = TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(Default::default(), 0),
+            0,
+            vec![],
+        ))
+        .output(output(capacity_bytes!(900)))
+        .build();
+    let block = block_with(vec![spending.clone(), unknown]);
+
+    let fee = capacity_bytes!(100).as_u64();
+    assert_eq!(
+        block_fee_rates(&*store, &block),
+        vec![fee * 1000 / spending.serialized_size() as u64]
+    );
+}
@@ -1 +1,3 @@
+mod fee_estimator;
 mod shared;
+mod tx_pool;
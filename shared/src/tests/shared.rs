@@ -1,8 +1,16 @@
 use crate::shared::{Shared, SharedBuilder};
-use ckb_core::{block::BlockBuilder, header::HeaderBuilder};
-use ckb_db::{KeyValueDB, MemoryKeyValueDB};
+use crate::tx_pool::{fee_rate, PoolError, TxPoolConfig};
+use ckb_chain_spec::consensus::Consensus;
+use ckb_core::cell::UnresolvableError;
+use ckb_core::script::Script;
+use ckb_core::transaction::{CellInput, CellOutput, OutPoint, Transaction, TransactionBuilder};
+use ckb_core::{block::BlockBuilder, capacity_bytes, header::HeaderBuilder, Bytes, Capacity};
+use ckb_db::{IterableKeyValueDB, MemoryKeyValueDB};
 use ckb_store::{ChainKVStore, ChainStore, StoreBatch};
 use ckb_traits::BlockMedianTimeContext;
+use ckb_verification::TransactionError;
+use numext_fixed_hash::H256;
+use std::iter;
 
 fn new_shared() -> Shared<ChainKVStore<MemoryKeyValueDB>> {
     SharedBuilder::<MemoryKeyValueDB>::new().build().unwrap()
@@ -10,7 +18,7 @@ fn new_shared() -> Shared<ChainKVStore<MemoryKeyValueDB>> {
 
 fn insert_block_timestamps<T>(store: &ChainKVStore<T>, timestamps: &[u64])
 where
-    T: KeyValueDB,
+    T: IterableKeyValueDB,
 {
     let mut blocks = Vec::with_capacity(timestamps.len());
     let tip_header = store.get_tip_header().expect("tip");
@@ -54,3 +62,371 @@ fn test_block_median_time() {
         17
     );
 }
+
+#[test]
+fn test_resolve_tx_for_dry_run() {
+    let shared = new_shared();
+    let chain_state = shared.chain_state().lock();
+
+    let empty = TransactionBuilder::default().build();
+    assert_eq!(
+        chain_state.resolve_tx_for_dry_run(&empty).err(),
+        Some(PoolError::InvalidTx(TransactionError::Empty))
+    );
+
+    let out_point = OutPoint::new_cell(Default::default(), 0);
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(out_point.clone(), 0, vec![]))
+        .output(CellOutput::default())
+        .build();
+    assert_eq!(
+        chain_state.resolve_tx_for_dry_run(&tx).err(),
+        Some(PoolError::UnresolvableTransaction(
+            UnresolvableError::Unknown(vec![out_point])
+        ))
+    );
+    assert_eq!(chain_state.tx_pool().pending_size(), 0);
+}
+
+#[test]
+fn test_pool_tx_fee() {
+    let shared = new_shared();
+    let mut chain_state = shared.chain_state().lock();
+
+    let output = |capacity| CellOutput::new(capacity, Bytes::default(), Default::default(), None);
+    let parent = TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(Default::default(), 0),
+            0,
+            vec![],
+        ))
+        .output(output(capacity_bytes!(1000)))
+        .build();
+    let child = TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(parent.hash().to_owned(), 0),
+            0,
+            vec![],
+        ))
+        .output(output(capacity_bytes!(900)))
+        .build();
+    chain_state
+        .mut_tx_pool()
+        .enqueue_tx(None, None, parent.clone());
+    chain_state
+        .mut_tx_pool()
+        .enqueue_tx(None, None, child.clone());
+
+    let tx_pool = chain_state.tx_pool();
+    assert_eq!(chain_state.pool_tx_fee(&tx_pool, &parent), None);
+    assert_eq!(
+        chain_state.pool_tx_fee(&tx_pool, &child),
+        Some(capacity_bytes!(100))
+    );
+}
+
+fn output(capacity: Capacity) -> CellOutput {
+    CellOutput::new(capacity, Bytes::default(), Script::always_success(), None)
+}
+
+fn spend(tx_hash: &H256, index: u32, capacity: Capacity) -> Transaction {
+    TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(tx_hash.to_owned(), index),
+            0,
+            vec![],
+        ))
+        .output(output(capacity))
+        .build()
+}
+
+// The genesis block holds 3 cells of 1000 CKB anyone can spend
+fn new_shared_with_genesis_cells(
+    tx_pool_config: TxPoolConfig,
+) -> (Shared<ChainKVStore<MemoryKeyValueDB>>, Transaction) {
+    let genesis_tx = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::null(), 0, vec![]))
+        .outputs(vec![output(capacity_bytes!(1000)); 3])
+        .build();
+    let genesis_block = BlockBuilder::default()
+        .transaction(genesis_tx.clone())
+        .build();
+    let consensus = Consensus::default()
+        .set_genesis_block(genesis_block)
+        .set_cellbase_maturity(0);
+    let shared = SharedBuilder::<MemoryKeyValueDB>::new()
+        .consensus(consensus)
+        .tx_pool_config(tx_pool_config)
+        .build()
+        .unwrap();
+    (shared, genesis_tx)
+}
+
+#[test]
+fn test_fee_stored_at_admission() {
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(TxPoolConfig::default());
+    let chain_state = shared.chain_state().lock();
+
+    let parent = spend(genesis_tx.hash(), 0, capacity_bytes!(900));
+    let child = spend(parent.hash(), 0, capacity_bytes!(850));
+    for tx in &[&parent, &child] {
+        chain_state.add_tx_to_pool((*tx).clone()).unwrap();
+    }
+    let tx_pool = chain_state.tx_pool();
+    let fee = |tx: &Transaction| {
+        tx_pool
+            .get_entry(&tx.proposal_short_id())
+            .and_then(|entry| entry.fee)
+    };
+    assert_eq!(fee(&parent), Some(capacity_bytes!(100)));
+    assert_eq!(fee(&child), Some(capacity_bytes!(50)));
+}
+
+#[test]
+fn test_add_verified_tx_to_pool() {
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(TxPoolConfig::default());
+
+    let tx = spend(genesis_tx.hash(), 0, capacity_bytes!(900));
+    let verified = {
+        let (rtx, context) = shared
+            .chain_state()
+            .lock()
+            .resolve_tx_for_pool(&tx)
+            .unwrap();
+        context.verify(&rtx)
+    };
+    let chain_state = shared.chain_state().lock();
+    assert_eq!(
+        chain_state.add_verified_tx_to_pool(tx.clone(), verified.clone()),
+        verified
+    );
+    assert!(chain_state
+        .tx_pool()
+        .get_tx(&tx.proposal_short_id())
+        .is_some());
+
+    // the failed verification is logged as the rejection of the transaction
+    let invalid = spend(genesis_tx.hash(), 1, capacity_bytes!(900));
+    let err = PoolError::InvalidTx(TransactionError::Empty);
+    assert_eq!(
+        chain_state.add_verified_tx_to_pool(invalid.clone(), Err(err.clone())),
+        Err(err.clone())
+    );
+    let tx_pool = chain_state.tx_pool();
+    assert!(tx_pool.get_tx(&invalid.proposal_short_id()).is_none());
+    assert_eq!(
+        tx_pool
+            .get_rejection(invalid.hash())
+            .map(|rejection| rejection.reason.clone()),
+        Some(err)
+    );
+}
+
+#[test]
+fn test_replace_by_fee() {
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(TxPoolConfig::default());
+    let chain_state = shared.chain_state().lock();
+
+    let original = spend(genesis_tx.hash(), 0, capacity_bytes!(900));
+    let child = spend(original.hash(), 0, capacity_bytes!(850));
+    chain_state.add_tx_to_pool(original.clone()).unwrap();
+    chain_state.add_tx_to_pool(child.clone()).unwrap();
+
+    // paying a lower fee does not replace the original
+    let low_fee = spend(genesis_tx.hash(), 0, capacity_bytes!(950));
+    match chain_state.add_tx_to_pool(low_fee) {
+        Err(PoolError::LowReplaceFeeRate {
+            conflict,
+            min,
+            actual,
+        }) => {
+            assert_eq!(&conflict, original.hash());
+            assert!(actual < min);
+        }
+        ret => panic!("unexpected {:?}", ret),
+    }
+    assert_eq!(chain_state.tx_pool().pending_size(), 2);
+
+    // a higher fee rate not paying for the descendants does not replace them
+    let low_total_fee = spend(genesis_tx.hash(), 0, capacity_bytes!(880));
+    match chain_state.add_tx_to_pool(low_total_fee) {
+        Err(PoolError::LowReplaceFee { min, actual }) => {
+            assert!(min > capacity_bytes!(150));
+            assert_eq!(actual, capacity_bytes!(120));
+        }
+        ret => panic!("unexpected {:?}", ret),
+    }
+    assert_eq!(chain_state.tx_pool().pending_size(), 2);
+
+    // the replacement evicts the original and its descendants
+    let replacement = spend(genesis_tx.hash(), 0, capacity_bytes!(800));
+    chain_state.add_tx_to_pool(replacement.clone()).unwrap();
+    let tx_pool = chain_state.tx_pool();
+    assert_eq!(tx_pool.pending_size(), 1);
+    assert!(tx_pool.get_tx(&replacement.proposal_short_id()).is_some());
+    assert!(tx_pool.get_tx(&original.proposal_short_id()).is_none());
+    assert!(tx_pool.get_tx(&child.proposal_short_id()).is_none());
+    for replaced in &[&original, &child] {
+        assert_eq!(
+            tx_pool
+                .get_rejection(replaced.hash())
+                .map(|rejection| rejection.reason.clone()),
+            Some(PoolError::Replaced {
+                by: replacement.hash().to_owned()
+            })
+        );
+    }
+}
+
+#[test]
+fn test_replace_too_many_txs() {
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(TxPoolConfig::default());
+    let chain_state = shared.chain_state().lock();
+
+    let mut parent = genesis_tx.clone();
+    for i in 1..=101 {
+        let child = spend(parent.hash(), 0, Capacity::bytes(1000 - i).unwrap());
+        chain_state.add_tx_to_pool(child.clone()).unwrap();
+        parent = child;
+    }
+    let replacement = spend(genesis_tx.hash(), 0, capacity_bytes!(500));
+    assert_eq!(
+        chain_state.add_tx_to_pool(replacement),
+        Err(PoolError::TooManyReplacements { max: 100 })
+    );
+    assert_eq!(chain_state.tx_pool().pending_size(), 101);
+}
+
+#[test]
+fn test_orphan_reprocessed_on_parent_arrival() {
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(TxPoolConfig::default());
+    let chain_state = shared.chain_state().lock();
+
+    let parent = spend(genesis_tx.hash(), 0, capacity_bytes!(900));
+    let child = spend(parent.hash(), 0, capacity_bytes!(800));
+    let grandchild = spend(child.hash(), 0, capacity_bytes!(700));
+    for tx in &[&grandchild, &child] {
+        assert_eq!(
+            chain_state.add_tx_to_pool((*tx).clone()),
+            Err(PoolError::UnresolvableTransaction(
+                UnresolvableError::Unknown(tx.input_pts())
+            ))
+        );
+    }
+    assert_eq!(chain_state.tx_pool().orphan_size(), 2);
+
+    chain_state.add_tx_to_pool(parent).unwrap();
+    let tx_pool = chain_state.tx_pool();
+    assert_eq!(tx_pool.orphan_size(), 0);
+    assert_eq!(tx_pool.pending_size(), 3);
+    assert!(tx_pool
+        .get_entry(&grandchild.proposal_short_id())
+        .and_then(|entry| entry.cycles)
+        .is_some());
+}
+
+#[test]
+fn test_resurrect_detached_txs() {
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(TxPoolConfig::default());
+    let chain_state = shared.chain_state().lock();
+
+    let parent = spend(genesis_tx.hash(), 0, capacity_bytes!(900));
+    let child = spend(parent.hash(), 0, capacity_bytes!(800));
+    let unrelated = spend(genesis_tx.hash(), 1, capacity_bytes!(900));
+    chain_state.add_tx_to_pool(unrelated.clone()).unwrap();
+    // the child waits as an orphan for its parent, resurrected below
+    chain_state.add_tx_to_pool(child.clone()).unwrap_err();
+    assert_eq!(chain_state.tx_pool().orphan_size(), 1);
+
+    let detached = BlockBuilder::default()
+        .transaction(TransactionBuilder::default().build())
+        .transaction(parent.clone())
+        .build();
+    chain_state.update_tx_pool_for_reorg(iter::once(&detached), iter::empty(), iter::empty());
+    let tx_pool = chain_state.tx_pool();
+    assert_eq!(tx_pool.orphan_size(), 0);
+    assert_eq!(tx_pool.pending_size(), 3);
+    let cycles = |tx: &Transaction| {
+        tx_pool
+            .get_entry(&tx.proposal_short_id())
+            .and_then(|entry| entry.cycles)
+    };
+    // the resurrected tx is not verified again
+    assert_eq!(cycles(&parent), None);
+    assert!(cycles(&child).is_some());
+    assert!(cycles(&unrelated).is_some());
+}
+
+#[test]
+fn test_evict_low_fee_rate_txs() {
+    let tx_size = spend(&H256::zero(), 0, Capacity::zero()).serialized_size() as u64;
+    let config = TxPoolConfig {
+        max_pool_bytes: 2 * tx_size,
+        ..Default::default()
+    };
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(config);
+    let chain_state = shared.chain_state().lock();
+
+    let high = spend(genesis_tx.hash(), 0, capacity_bytes!(900));
+    let low = spend(genesis_tx.hash(), 1, capacity_bytes!(950));
+    let higher = spend(genesis_tx.hash(), 2, capacity_bytes!(800));
+    for tx in &[&high, &low, &higher] {
+        chain_state.add_tx_to_pool((*tx).clone()).unwrap();
+    }
+    let low_fee_rate = fee_rate(capacity_bytes!(50), low.serialized_size());
+    {
+        let tx_pool = chain_state.tx_pool();
+        assert_eq!(tx_pool.pending_size(), 2);
+        assert!(tx_pool.get_tx(&low.proposal_short_id()).is_none());
+        assert_eq!(tx_pool.min_fee_rate(), low_fee_rate + 1);
+    }
+
+    // paying less than the evicted ones is rejected
+    let lower = spend(genesis_tx.hash(), 1, capacity_bytes!(960));
+    match chain_state.add_tx_to_pool(lower) {
+        Err(PoolError::LowFeeRate { min, .. }) => assert_eq!(min, low_fee_rate + 1),
+        ret => panic!("unexpected {:?}", ret),
+    }
+    // paying more but still the least is evicted at once
+    let lowest_kept = spend(genesis_tx.hash(), 1, capacity_bytes!(940));
+    assert_eq!(
+        chain_state.add_tx_to_pool(lowest_kept),
+        Err(PoolError::OverCapacity)
+    );
+    assert_eq!(chain_state.tx_pool().pending_size(), 2);
+}
+
+#[test]
+fn test_rejection_log() {
+    let config = TxPoolConfig {
+        min_fee_rate: 1000,
+        max_rejections: 1,
+        ..Default::default()
+    };
+    let (shared, genesis_tx) = new_shared_with_genesis_cells(config);
+    let chain_state = shared.chain_state().lock();
+
+    let free = spend(genesis_tx.hash(), 0, capacity_bytes!(1000));
+    let err = chain_state.add_tx_to_pool(free.clone()).unwrap_err();
+    {
+        let tx_pool = chain_state.tx_pool();
+        let rejection = tx_pool
+            .get_rejection(free.hash())
+            .expect("rejection logged");
+        assert_eq!(&rejection.hash, free.hash());
+        assert_eq!(rejection.reason, err);
+    }
+
+    // the log is bounded, the oldest rejection is dropped first
+    let another_free = spend(genesis_tx.hash(), 1, capacity_bytes!(1000));
+    chain_state
+        .add_tx_to_pool(another_free.clone())
+        .unwrap_err();
+    // the orphan waits in the pool for its parent, it is not rejected
+    let orphan = spend(&H256::zero(), 0, capacity_bytes!(900));
+    chain_state.add_tx_to_pool(orphan.clone()).unwrap_err();
+    let tx_pool = chain_state.tx_pool();
+    assert!(tx_pool.get_rejection(free.hash()).is_none());
+    assert!(tx_pool.get_rejection(another_free.hash()).is_some());
+    assert!(tx_pool.get_rejection(orphan.hash()).is_none());
+}
@@ -0,0 +1,163 @@
+use crate::tx_pool::{fee_rate, PoolError, TxPool, TxPoolConfig};
+use ckb_core::transaction::{CellInput, CellOutput, OutPoint, Transaction, TransactionBuilder};
+use ckb_core::{capacity_bytes, Bytes, Capacity};
+use numext_fixed_hash::H256;
+
+// The data tells apart the transactions spending the same cell
+fn spend(tx_hash: &H256, index: u32, data: u8) -> Transaction {
+    TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(tx_hash.to_owned(), index),
+            0,
+            vec![],
+        ))
+        .output(CellOutput::new(
+            capacity_bytes!(100),
+            Bytes::from(vec![data]),
+            Default::default(),
+            None,
+        ))
+        .build()
+}
+
+#[test]
+fn test_fee_rate() {
+    assert_eq!(fee_rate(Capacity::shannons(500), 250), 2000);
+    assert_eq!(fee_rate(Capacity::shannons(1), 3), 333);
+    assert_eq!(fee_rate(Capacity::zero(), 0), 0);
+}
+
+#[test]
+fn test_pool_totals() {
+    let mut tx_pool = TxPool::new(TxPoolConfig::default());
+    assert_eq!(tx_pool.backlog_bytes(), 0);
+    assert_eq!(tx_pool.total_tx_cycles(), 0);
+    assert_eq!(tx_pool.min_fee_rate(), 0);
+
+    let txs = (0..2u8)
+        .map(|data| spend(&H256::zero(), 0, data))
+        .collect::<Vec<_>>();
+    tx_pool.enqueue_tx(Some(100), None, txs[0].clone());
+    tx_pool.enqueue_tx(None, None, txs[1].clone());
+    assert_eq!(
+        tx_pool.backlog_bytes(),
+        txs.iter()
+            .map(|tx| tx.serialized_size() as u64)
+            .sum::<u64>()
+    );
+    assert_eq!(tx_pool.total_tx_cycles(), 100);
+
+    tx_pool.remove_tx_and_descendants(&txs[0].proposal_short_id());
+    assert_eq!(tx_pool.backlog_bytes(), txs[1].serialized_size() as u64);
+}
+
+#[test]
+fn test_remove_tx_and_descendants() {
+    let mut tx_pool = TxPool::new(TxPoolConfig::default());
+    let parent = spend(&H256::zero(), 0, 0);
+    let child = spend(parent.hash(), 0, 1);
+    let grandchild = spend(child.hash(), 0, 2);
+    let unrelated = spend(&H256::zero(), 0, 3);
+    for tx in &[&parent, &child, &grandchild, &unrelated] {
+        tx_pool.enqueue_tx(None, None, (*tx).clone());
+    }
+
+    let mut removed = tx_pool
+        .remove_tx_and_descendants(&child.proposal_short_id())
+        .into_iter()
+        .map(|entry| entry.transaction.hash().to_owned())
+        .collect::<Vec<_>>();
+    removed.sort();
+    let mut expected = vec![child.hash().to_owned(), grandchild.hash().to_owned()];
+    expected.sort();
+    assert_eq!(removed, expected);
+    assert_eq!(tx_pool.pending_size(), 2);
+
+    assert!(tx_pool
+        .remove_tx_and_descendants(&child.proposal_short_id())
+        .is_empty());
+
+    // the removed orphan leaves no edge behind
+    let orphan = spend(&H256::zero(), 1, 4);
+    tx_pool.add_orphan(None, orphan.clone(), orphan.input_pts());
+    assert_eq!(
+        tx_pool
+            .remove_tx_and_descendants(&orphan.proposal_short_id())
+            .len(),
+        1
+    );
+    assert!(tx_pool.orphan.edges.is_empty());
+
+    tx_pool.clear();
+    assert_eq!(tx_pool.pending_size(), 0);
+    assert_eq!(tx_pool.backlog_bytes(), 0);
+}
+
+#[test]
+fn test_expire_txs() {
+    let mut tx_pool = TxPool::new(TxPoolConfig {
+        tx_expiry: 60,
+        tx_expiry_blocks: 10,
+        ..Default::default()
+    });
+    let (old, distant, fresh) = (
+        spend(&H256::zero(), 0, 0),
+        spend(&H256::zero(), 0, 1),
+        spend(&H256::zero(), 0, 2),
+    );
+    for tx in &[&old, &distant, &fresh] {
+        tx_pool.enqueue_tx(None, None, (*tx).clone());
+    }
+    tx_pool.add_arrival(old.proposal_short_id(), 100);
+    tx_pool.add_arrival(distant.proposal_short_id(), 90);
+    tx_pool.add_arrival(fresh.proposal_short_id(), 100);
+    tx_pool
+        .arrivals
+        .get_mut(&old.proposal_short_id())
+        .unwrap()
+        .0 -= 60_000;
+
+    let now = tx_pool.arrivals[&fresh.proposal_short_id()].0;
+    let mut expired = tx_pool
+        .expire_txs(now, 100)
+        .into_iter()
+        .map(|entry| entry.transaction.hash().to_owned())
+        .collect::<Vec<_>>();
+    expired.sort();
+    let mut expected = vec![old.hash().to_owned(), distant.hash().to_owned()];
+    expected.sort();
+    assert_eq!(expired, expected);
+    assert_eq!(tx_pool.pending_size(), 1);
+    assert_eq!(
+        tx_pool.get_rejection(old.hash()).map(|r| r.reason.clone()),
+        Some(PoolError::Expired)
+    );
+    assert!(tx_pool.expire_txs(now, 109).is_empty());
+    assert_eq!(tx_pool.expire_txs(now, 110).len(), 1);
+}
+
+#[test]
+fn test_conflicts_by_spent_index() {
+    let mut tx_pool = TxPool::new(TxPoolConfig::default());
+    let pending = spend(&H256::zero(), 0, 0);
+    tx_pool.enqueue_tx(None, None, pending.clone());
+
+    let double_spend = spend(&H256::zero(), 0, 1);
+    let conflicts = tx_pool.conflicts(&double_spend);
+    assert_eq!(conflicts.len(), 1);
+    assert!(conflicts.contains(&pending.proposal_short_id()));
+    assert!(tx_pool.conflicts(&pending).is_empty());
+    assert!(tx_pool.conflicts(&spend(&H256::zero(), 1, 0)).is_empty());
+
+    // both the double spends enqueued are indexed
+    tx_pool.enqueue_tx(None, None, double_spend.clone());
+    let conflicts = tx_pool.conflicts(&spend(&H256::zero(), 0, 2));
+    assert_eq!(conflicts.len(), 2);
+    tx_pool.remove_tx_and_descendants(&pending.proposal_short_id());
+    assert!(tx_pool
+        .conflicts(&pending)
+        .contains(&double_spend.proposal_short_id()));
+
+    tx_pool.remove_tx_and_descendants(&double_spend.proposal_short_id());
+    assert!(tx_pool.conflicts(&pending).is_empty());
+}
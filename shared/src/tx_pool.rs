@@ -7,4 +7,4 @@ mod pending;
 mod staging;
 
 pub use self::pool::TxPool;
-pub use self::types::{PoolEntry, PoolError, TxPoolConfig};
+pub use self::types::{fee_rate, PoolEntry, PoolError, Rejection, TxPoolConfig};
@@ -48,7 +48,7 @@ impl OrphanPool {
         unknown: impl ExactSizeIterator<Item = OutPoint>,
     ) {
         let short_id = tx.proposal_short_id();
-        let entry = PoolEntry::new(tx, unknown.len(), cycles);
+        let entry = PoolEntry::new(tx, unknown.len(), cycles, None);
         for out_point in unknown {
             let edge = self.edges.entry(out_point).or_insert_with(Vec::new);
             edge.push(short_id);
@@ -56,6 +56,38 @@ impl OrphanPool {
         self.vertices.insert(short_id, entry);
     }
 
+    /// Removes the orphan alone, its edges included
+    pub(crate) fn remove(&mut self, id: &ProposalShortId) -> Option<PoolEntry> {
+        let entry = self.vertices.remove(id)?;
+        let tx = &entry.transaction;
+        for out_point in tx.input_pts().iter().chain(tx.dep_pts().iter()) {
+            if let hash_map::Entry::Occupied(mut edge) = self.edges.entry(out_point.clone()) {
+                edge.get_mut().retain(|cid| cid != id);
+                if edge.get().is_empty() {
+                    edge.remove();
+                }
+            }
+        }
+        Some(entry)
+    }
+
+    /// Removes the orphans added before `expired_before`, in milliseconds, then
+    /// the oldest ones until at most `limit` are left, returns the removed ones
+    pub(crate) fn evict(&mut self, expired_before: u64, limit: usize) -> Vec<PoolEntry> {
+        let mut ids: Vec<_> = self
+            .vertices
+            .iter()
+            .map(|(id, entry)| (entry.timestamp, *id))
+            .collect();
+        ids.sort_by_key(|(timestamp, _)| *timestamp);
+        let excess = ids.len().saturating_sub(limit);
+        ids.into_iter()
+            .enumerate()
+            .take_while(|(index, (timestamp, _))| *index < excess || *timestamp < expired_before)
+            .filter_map(|(_, (_, id))| self.remove(&id))
+            .collect()
+    }
+
     pub(crate) fn recursion_remove(&mut self, id: &ProposalShortId) {
         let mut queue: VecDeque<ProposalShortId> = VecDeque::new();
         queue.push_back(id.clone());
@@ -218,6 +250,49 @@ mod tests {
         assert_eq!(txs, vec![tx3, tx4]);
     }
 
+    #[test]
+    fn test_orphan_pool_evict() {
+        let mut pool = OrphanPool::new();
+
+        let tx1 = build_tx(vec![(&H256::zero(), 0)], 1);
+        let txs: Vec<_> = (1..4)
+            .map(|index| build_tx(vec![(tx1.hash(), 0), (&H256::zero(), index)], 1))
+            .collect();
+        for (timestamp, tx) in txs.iter().enumerate() {
+            pool.add_tx(None, tx.clone(), tx1.output_pts().into_iter());
+            pool.vertices
+                .get_mut(&tx.proposal_short_id())
+                .unwrap()
+                .timestamp = timestamp as u64;
+        }
+
+        // the expired one goes first, then the oldest ones over the limit
+        let evicted: Vec<_> = pool
+            .evict(1, 3)
+            .into_iter()
+            .map(|e| e.transaction)
+            .collect();
+        assert_eq!(evicted, vec![txs[0].clone()]);
+        let evicted: Vec<_> = pool
+            .evict(0, 1)
+            .into_iter()
+            .map(|e| e.transaction)
+            .collect();
+        assert_eq!(evicted, vec![txs[1].clone()]);
+        assert_eq!(
+            pool.edges[&tx1.output_pts()[0]],
+            vec![txs[2].proposal_short_id()]
+        );
+
+        let reprocessed: Vec<_> = pool
+            .remove_by_ancestor(&tx1)
+            .into_iter()
+            .map(|e| e.transaction)
+            .collect();
+        assert_eq!(reprocessed, vec![txs[2].clone()]);
+        assert!(pool.edges.is_empty());
+    }
+
     #[test]
     fn test_orphan_pool_recursion_remove() {
         let mut pool = OrphanPool::new();
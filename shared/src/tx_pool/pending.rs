@@ -2,19 +2,29 @@
 
 use crate::tx_pool::types::PoolEntry;
 use ckb_core::cell::{CellMeta, CellProvider, CellStatus};
-use ckb_core::transaction::{OutPoint, ProposalShortId, Transaction};
+use ckb_core::transaction::{Capacity, OutPoint, ProposalShortId, Transaction};
 use ckb_core::Cycle;
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 
 #[derive(Default, Debug, Clone)]
 pub(crate) struct PendingQueue {
     pub(crate) inner: FnvHashMap<ProposalShortId, PoolEntry>,
+    /// The pending txs spending each input, several of them when a tx double
+    /// spending another one is enqueued without replacing it
+    pub(crate) spent: FnvHashMap<OutPoint, FnvHashSet<ProposalShortId>>,
+    /// The pending txs depending on each cell
+    pub(crate) deps: FnvHashMap<OutPoint, FnvHashSet<ProposalShortId>>,
+    /// Serialized size of the pending txs
+    pub(crate) bytes: u64,
 }
 
 impl PendingQueue {
     pub fn new() -> Self {
         PendingQueue {
             inner: FnvHashMap::default(),
+            spent: FnvHashMap::default(),
+            deps: FnvHashMap::default(),
+            bytes: 0,
         }
     }
 
@@ -22,9 +32,26 @@ impl PendingQueue {
         self.inner.len()
     }
 
-    pub(crate) fn add_tx(&mut self, cycles: Option<Cycle>, tx: Transaction) -> Option<PoolEntry> {
+    pub(crate) fn add_tx(
+        &mut self,
+        cycles: Option<Cycle>,
+        fee: Option<Capacity>,
+        tx: Transaction,
+    ) -> Option<PoolEntry> {
         let short_id = tx.proposal_short_id();
-        self.inner.insert(short_id, PoolEntry::new(tx, 0, cycles))
+        for input in tx.input_pts() {
+            self.spent.entry(input).or_default().insert(short_id);
+        }
+        for dep in tx.dep_pts() {
+            self.deps.entry(dep).or_default().insert(short_id);
+        }
+        let entry = PoolEntry::new(tx, 0, cycles, fee);
+        self.bytes += entry.size as u64;
+        let replaced = self.inner.insert(short_id, entry);
+        if let Some(replaced) = &replaced {
+            self.bytes -= replaced.size as u64;
+        }
+        replaced
     }
 
     pub(crate) fn contains_key(&self, id: &ProposalShortId) -> bool {
@@ -40,7 +67,46 @@ impl PendingQueue {
     }
 
     pub(crate) fn remove(&mut self, id: &ProposalShortId) -> Option<PoolEntry> {
-        self.inner.remove(id)
+        self.inner.remove(id).map(|entry| {
+            self.bytes -= entry.size as u64;
+            for input in entry.transaction.input_pts() {
+                if let Some(ids) = self.spent.get_mut(&input) {
+                    ids.remove(id);
+                    if ids.is_empty() {
+                        self.spent.remove(&input);
+                    }
+                }
+            }
+            for dep in entry.transaction.dep_pts() {
+                if let Some(ids) = self.deps.get_mut(&dep) {
+                    ids.remove(id);
+                    if ids.is_empty() {
+                        self.deps.remove(&dep);
+                    }
+                }
+            }
+            entry
+        })
+    }
+
+    /// The pending txs spending the out point
+    pub(crate) fn spent_by<'a>(
+        &'a self,
+        out_point: &OutPoint,
+    ) -> impl Iterator<Item = &'a ProposalShortId> {
+        self.spent.get(out_point).into_iter().flatten()
+    }
+
+    /// The pending txs spending or depending on the cells
+    pub(crate) fn children<'a>(
+        &'a self,
+        out_points: &'a [OutPoint],
+    ) -> impl Iterator<Item = ProposalShortId> + 'a {
+        out_points.iter().flat_map(move |out_point| {
+            self.spent_by(out_point)
+                .chain(self.deps.get(out_point).into_iter().flatten())
+                .cloned()
+        })
     }
 
     pub(crate) fn fetch(&self, n: usize) -> Vec<ProposalShortId> {
@@ -63,6 +129,7 @@ impl CellProvider for PendingQueue {
                         out_point: cell_out_point.to_owned(),
                         capacity: output.capacity,
                         data_hash: None,
+                        dep_group: output.is_dep_group(),
                     }),
                     None => CellStatus::Unknown,
                 }
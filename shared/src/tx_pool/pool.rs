@@ -1,16 +1,19 @@
 //! Top-level Pool type, methods, and tests
 use super::trace::TxTraceMap;
-use super::types::{PoolEntry, TxPoolConfig};
+use super::types::{fee_rate, PoolEntry, PoolError, Rejection, TxPoolConfig};
 use crate::tx_pool::orphan::OrphanPool;
 use crate::tx_pool::pending::PendingQueue;
 use crate::tx_pool::staging::StagingPool;
-use ckb_core::transaction::{OutPoint, ProposalShortId, Transaction};
+use ckb_core::header::BlockNumber;
+use ckb_core::transaction::{Capacity, OutPoint, ProposalShortId, Transaction};
 use ckb_core::Cycle;
 use faketime::unix_time_as_millis;
+use fnv::{FnvHashMap, FnvHashSet};
 use jsonrpc_types::TxTrace;
 use log::trace;
 use lru_cache::LruCache;
 use numext_fixed_hash::H256;
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone)]
 pub struct TxPool {
@@ -25,14 +28,26 @@ pub struct TxPool {
     pub(crate) conflict: LruCache<ProposalShortId, PoolEntry>,
     /// trace record map
     pub(crate) trace: TxTraceMap,
+    /// recently rejected txs by hash
+    pub(crate) rejections: LruCache<H256, Rejection>,
+    /// timestamp and tip number the pending and the staging txs entered the
+    /// pool, kept while they move between the queues
+    pub(crate) arrivals: FnvHashMap<ProposalShortId, (u64, BlockNumber)>,
     /// last txs updated timestamp
     pub(crate) last_txs_updated_at: u64,
+    /// number of txs staged since the pool was created
+    pub(crate) staged_txs: u64,
+    /// total fees of txs staged since the pool was created
+    pub(crate) staged_fees: Capacity,
+    /// fee rate the new txs must pay since the pool overflowed, 0 when none
+    pub(crate) fee_rate_floor: u64,
 }
 
 impl TxPool {
     pub fn new(config: TxPoolConfig) -> TxPool {
         let cache_size = config.max_cache_size;
         let trace_size = config.trace.unwrap_or(0);
+        let rejections_size = config.max_rejections;
         let last_txs_updated_at = 0u64;
 
         TxPool {
@@ -42,7 +57,12 @@ impl TxPool {
             orphan: OrphanPool::new(),
             conflict: LruCache::new(cache_size),
             last_txs_updated_at,
+            staged_txs: 0,
+            staged_fees: Capacity::zero(),
+            fee_rate_floor: 0,
             trace: TxTraceMap::new(trace_size),
+            rejections: LruCache::new(rejections_size),
+            arrivals: FnvHashMap::default(),
         }
     }
 
@@ -57,8 +77,13 @@ impl TxPool {
     }
 
     // enqueue_tx inserts a new transaction into the non-verifiable transaction queue.
-    pub fn enqueue_tx(&mut self, cycles: Option<Cycle>, tx: Transaction) -> bool {
-        self.pending.add_tx(cycles, tx).is_none()
+    pub fn enqueue_tx(
+        &mut self,
+        cycles: Option<Cycle>,
+        fee: Option<Capacity>,
+        tx: Transaction,
+    ) -> bool {
+        self.pending.add_tx(cycles, fee, tx).is_none()
     }
 
     // trace_tx basically same as enqueue_tx, but additional register a trace.
@@ -67,19 +92,102 @@ impl TxPool {
             self.trace
                 .add_pending(&tx.hash(), "unknown tx, insert to pending queue");
         }
-        self.pending.add_tx(None, tx).is_none()
+        self.pending.add_tx(None, None, tx).is_none()
     }
 
     pub fn get_tx_traces(&self, hash: &H256) -> Option<&Vec<TxTrace>> {
         self.trace.get(hash)
     }
 
+    /// The reason the transaction was recently rejected, if it still is not in
+    /// the pool
+    pub fn get_rejection(&self, hash: &H256) -> Option<&Rejection> {
+        self.rejections.get(hash)
+    }
+
+    pub(crate) fn add_rejection(&mut self, hash: &H256, reason: PoolError) {
+        if self.config.max_rejections == 0 {
+            return;
+        }
+        trace!(target: "tx_pool", "reject {:#x}, {}", hash, reason);
+        self.rejections
+            .insert(hash.to_owned(), Rejection::new(hash.to_owned(), reason));
+    }
+
+    pub(crate) fn add_arrival(&mut self, id: ProposalShortId, tip_number: BlockNumber) {
+        self.arrivals
+            .entry(id)
+            .or_insert((unix_time_as_millis(), tip_number));
+    }
+
+    /// Evicts the pending and the staging transactions older than the
+    /// `tx_expiry` or the `tx_expiry_blocks`, along with their descendants
+    pub(crate) fn expire_txs(&mut self, now: u64, tip_number: BlockNumber) -> Vec<PoolEntry> {
+        let ids: FnvHashSet<_> = self
+            .pending_txs_iter()
+            .chain(self.staging_txs_iter())
+            .map(|entry| entry.transaction.proposal_short_id())
+            .collect();
+        // The txs resurrected on reorg are counted from now
+        self.arrivals.retain(|id, _| ids.contains(id));
+        for id in ids {
+            self.arrivals.entry(id).or_insert((now, tip_number));
+        }
+
+        let max_age = self.config.tx_expiry.saturating_mul(1000);
+        let max_blocks = self.config.tx_expiry_blocks;
+        let expired: Vec<_> = self
+            .arrivals
+            .iter()
+            .filter(|(_, (timestamp, number))| {
+                (max_age > 0 && now.saturating_sub(*timestamp) >= max_age)
+                    || (max_blocks > 0 && tip_number.saturating_sub(*number) >= max_blocks)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        let mut removed = Vec::new();
+        for id in expired {
+            for entry in self.remove_tx_and_descendants(&id) {
+                self.arrivals.remove(&entry.transaction.proposal_short_id());
+                if self.config.trace_enable() {
+                    self.trace
+                        .removed(&entry.transaction.hash(), "tx expired".to_string());
+                }
+                self.add_rejection(entry.transaction.hash(), PoolError::Expired);
+                removed.push(entry);
+            }
+        }
+        removed
+    }
+
     pub(crate) fn add_orphan(
         &mut self,
         cycles: Option<Cycle>,
         tx: Transaction,
         unknowns: Vec<OutPoint>,
     ) {
+        if self.orphan.contains(&tx) || self.config.max_orphan_size == 0 {
+            return;
+        }
+        // Makes room for the orphan, the ones whose parents did not arrive in
+        // time are dropped first
+        let expired_before =
+            unix_time_as_millis().saturating_sub(self.config.orphan_expiry.saturating_mul(1000));
+        for entry in self
+            .orphan
+            .evict(expired_before, self.config.max_orphan_size - 1)
+        {
+            if self.config.trace_enable() {
+                self.trace
+                    .removed(&entry.transaction.hash(), "orphan tx evicted".to_string());
+            }
+            let reason = if entry.timestamp < expired_before {
+                PoolError::Expired
+            } else {
+                PoolError::OverCapacity
+            };
+            self.add_rejection(entry.transaction.hash(), reason);
+        }
         trace!(target: "tx_pool", "add_orphan {:#x}", &tx.hash());
         if self.config.trace_enable() {
             self.trace.add_orphan(
@@ -90,13 +198,15 @@ impl TxPool {
         self.orphan.add_tx(cycles, tx, unknowns.into_iter());
     }
 
-    pub(crate) fn add_staging(&mut self, cycles: Cycle, tx: Transaction) {
+    pub(crate) fn add_staging(&mut self, cycles: Cycle, fee: Capacity, tx: Transaction) {
         trace!(target: "tx_pool", "add_staging {:#x}", tx.hash());
         if self.config.trace_enable() {
             self.trace.staged(&tx.hash(), "tx staged".to_string());
         }
         self.touch_last_txs_updated_at();
-        self.staging.add_tx(cycles, tx);
+        self.staged_txs += 1;
+        self.staged_fees = self.staged_fees.safe_add(fee).unwrap_or(self.staged_fees);
+        self.staging.add_tx(cycles, fee, tx);
     }
 
     pub(crate) fn remove_pending_and_conflict(
@@ -123,6 +233,82 @@ impl TxPool {
         self.last_txs_updated_at = unix_time_as_millis();
     }
 
+    /// Serialized size of the pending and the staging transactions
+    pub fn backlog_bytes(&self) -> u64 {
+        self.pending.bytes + self.staging.bytes
+    }
+
+    /// Verification cycles of the pending and the staging transactions
+    pub fn total_tx_cycles(&self) -> Cycle {
+        self.pending
+            .inner
+            .values()
+            .chain(self.staging.txs_iter())
+            .filter_map(|entry| entry.cycles)
+            .fold(0, Cycle::saturating_add)
+    }
+
+    /// The fee rate the new transactions must pay, the `min_fee_rate` of the
+    /// config raised above the ones evicted while the pool is full
+    pub fn min_fee_rate(&self) -> u64 {
+        self.config.min_fee_rate.max(self.fee_rate_floor)
+    }
+
+    /// Whether the pending and the staging transactions exceed the size or
+    /// the count limits
+    pub(crate) fn is_over_budget(&self) -> bool {
+        self.backlog_bytes() > self.config.max_pool_bytes
+            || self.pending.size() + self.staging.capacity() > self.config.max_pool_size
+    }
+
+    /// Fee rate of the transaction along with its ancestors in the pool, which
+    /// are committed before it
+    pub(crate) fn ancestors_fee_rate(&self, entry: &PoolEntry) -> u64 {
+        let mut fee = 0u64;
+        let mut size = 0;
+        let mut visited = FnvHashSet::default();
+        visited.insert(entry.transaction.proposal_short_id());
+        let mut stack = vec![entry];
+        while let Some(entry) = stack.pop() {
+            fee = fee.saturating_add(entry.fee.map_or(0, Capacity::as_u64));
+            size += entry.size;
+            for input in entry.transaction.inputs() {
+                if let Some(cell_out_point) = &input.previous_output.cell {
+                    let id = ProposalShortId::from_tx_hash(&cell_out_point.tx_hash);
+                    if visited.insert(id) {
+                        stack.extend(self.pending.get(&id).or_else(|| self.staging.get(&id)));
+                    }
+                }
+            }
+        }
+        fee_rate(Capacity::shannons(fee), size)
+    }
+
+    /// Removes the pending transaction and its descendants for room, the new
+    /// transactions must pay more than `fee_rate` until the pool drains
+    pub(crate) fn evict(&mut self, id: &ProposalShortId, fee_rate: u64) -> Vec<PoolEntry> {
+        let evicted = self.remove_tx_and_descendants(id);
+        if !evicted.is_empty() {
+            self.fee_rate_floor = self.fee_rate_floor.max(fee_rate.saturating_add(1));
+        }
+        if self.config.trace_enable() {
+            for entry in &evicted {
+                self.trace.removed(
+                    &entry.transaction.hash(),
+                    "tx evicted, pool full".to_string(),
+                );
+            }
+        }
+        for entry in &evicted {
+            self.add_rejection(entry.transaction.hash(), PoolError::OverCapacity);
+        }
+        evicted
+    }
+
+    pub fn pending_txs_iter(&self) -> impl Iterator<Item = &PoolEntry> {
+        self.pending.inner.values()
+    }
+
     pub fn staging_txs_iter(&self) -> impl Iterator<Item = &PoolEntry> {
         self.staging.txs_iter()
     }
@@ -163,6 +349,30 @@ impl TxPool {
         self.staging.get_tx(id).cloned()
     }
 
+    /// The pending and the staging transactions spending any input of the
+    /// transaction, which it would replace
+    pub(crate) fn conflicts(&self, tx: &Transaction) -> FnvHashSet<ProposalShortId> {
+        let id = tx.proposal_short_id();
+        let inputs = tx.input_pts();
+        inputs
+            .iter()
+            .filter_map(|input| {
+                self.staging
+                    .edges
+                    .get_outer(input)
+                    .or_else(|| self.staging.edges.get_inner(input))
+                    .cloned()
+                    .unwrap_or(None)
+            })
+            .chain(
+                inputs
+                    .iter()
+                    .flat_map(|input| self.pending.spent_by(input).cloned()),
+            )
+            .filter(|conflict| *conflict != id)
+            .collect()
+    }
+
     //FIXME: use memsize
     pub fn is_full(&self) -> bool {
         self.capacity() > self.config.max_pool_size
@@ -180,17 +390,107 @@ impl TxPool {
             }
             self.staging.remove_committed_tx(tx);
         }
+        if self.fee_rate_floor > 0 && self.backlog_bytes() <= self.config.max_pool_bytes / 2 {
+            self.fee_rate_floor = 0;
+        }
+    }
+
+    /// The transactions and their descendants in the pool, each once, None
+    /// when they are more than `limit`
+    pub(crate) fn tx_and_descendants<'a>(
+        &self,
+        ids: impl Iterator<Item = &'a ProposalShortId>,
+        limit: usize,
+    ) -> Option<Vec<&PoolEntry>> {
+        let mut entries = Vec::new();
+        let mut visited = FnvHashSet::default();
+        let mut queue: VecDeque<_> = ids.cloned().collect();
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let entry = match self.get_entry(&id) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            if entries.len() == limit {
+                return None;
+            }
+            let out_points = entry.transaction.output_pts();
+            queue.extend(self.pending.children(&out_points));
+            for out_point in &out_points {
+                if let Some(Some(child_id)) = self.staging.edges.get_inner(out_point) {
+                    queue.push_back(*child_id);
+                }
+                if let Some(ids) = self.staging.edges.get_deps(out_point) {
+                    queue.extend(ids.iter().cloned());
+                }
+                if let Some(ids) = self.orphan.edges.get(out_point) {
+                    queue.extend(ids.iter().cloned());
+                }
+            }
+            entries.push(entry);
+        }
+        Some(entries)
+    }
+
+    /// Removes the transaction and its descendants, returns the removed entries
+    pub fn remove_tx_and_descendants(&mut self, id: &ProposalShortId) -> Vec<PoolEntry> {
+        let mut removed = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(*id);
+        while let Some(id) = queue.pop_front() {
+            // The staged descendants are removed along with their ancestor
+            let entries = match self.pending.remove(&id) {
+                Some(entry) => vec![entry],
+                None => self
+                    .staging
+                    .remove(&id)
+                    .or_else(|| self.orphan.remove(&id).map(|entry| vec![entry]))
+                    .or_else(|| self.conflict.remove(&id).map(|entry| vec![entry]))
+                    .unwrap_or_default(),
+            };
+            for entry in entries {
+                let out_points = entry.transaction.output_pts();
+                queue.extend(self.pending.children(&out_points));
+                for out_point in &out_points {
+                    if let Some(ids) = self.orphan.edges.remove(out_point) {
+                        queue.extend(ids);
+                    }
+                }
+                if self.config.trace_enable() {
+                    self.trace
+                        .removed(&entry.transaction.hash(), "tx removed".to_string());
+                }
+                removed.push(entry);
+            }
+        }
+        if !removed.is_empty() {
+            self.touch_last_txs_updated_at();
+        }
+        removed
+    }
+
+    /// Drops all the transactions, the traces and the stats are kept
+    pub fn clear(&mut self) {
+        self.pending = PendingQueue::new();
+        self.staging = StagingPool::new();
+        self.orphan = OrphanPool::new();
+        self.conflict = LruCache::new(self.config.max_cache_size);
+        self.arrivals.clear();
+        self.fee_rate_floor = 0;
+        self.touch_last_txs_updated_at();
     }
 
     pub fn remove_expired<'a>(&mut self, ids: impl Iterator<Item = &'a ProposalShortId>) {
         for id in ids {
             if let Some(entries) = self.staging.remove(id) {
-                if self.config.trace_enable() {
-                    for entry in entries {
+                for entry in entries {
+                    if self.config.trace_enable() {
                         self.trace
                             .expired(&entry.transaction.hash(), "tx proposal expired".to_string());
-                        self.enqueue_tx(entry.cycles, entry.transaction);
                     }
+                    self.enqueue_tx(entry.cycles, entry.fee, entry.transaction);
                 }
             }
         }
@@ -2,7 +2,7 @@
 
 use crate::tx_pool::types::PoolEntry;
 use ckb_core::cell::{CellMeta, CellProvider, CellStatus};
-use ckb_core::transaction::{CellOutput, OutPoint, ProposalShortId, Transaction};
+use ckb_core::transaction::{Capacity, CellOutput, OutPoint, ProposalShortId, Transaction};
 use ckb_core::Cycle;
 use fnv::{FnvHashMap, FnvHashSet};
 use linked_hash_map::LinkedHashMap;
@@ -95,6 +95,8 @@ impl<K: Hash + Eq, V: Copy + Eq + Hash> Edges<K, V> {
 pub struct StagingPool {
     pub(crate) vertices: LinkedHashMap<ProposalShortId, PoolEntry>,
     pub(crate) edges: Edges<OutPoint, ProposalShortId>,
+    /// Serialized size of the staging txs
+    pub(crate) bytes: u64,
 }
 
 impl CellProvider for StagingPool {
@@ -106,15 +108,7 @@ impl CellProvider for StagingPool {
             if x.is_some() {
                 CellStatus::Dead
             } else {
-                let output = self.get_output(o).expect("output");
-                CellStatus::live_cell(CellMeta {
-                    cell_output: Some(output.clone()),
-                    out_point: o.cell.as_ref().unwrap().to_owned(),
-                    block_number: None,
-                    cellbase: false,
-                    capacity: output.capacity,
-                    data_hash: None,
-                })
+                self.live_cell(o)
             }
         } else if self.edges.get_outer(o).is_some() {
             CellStatus::Dead
@@ -153,8 +147,23 @@ impl StagingPool {
         })
     }
 
+    /// The output of a staging tx as a live cell, whether it is spent or not
+    pub(crate) fn live_cell(&self, o: &OutPoint) -> CellStatus {
+        let output = self.get_output(o).expect("output");
+        CellStatus::live_cell(CellMeta {
+            cell_output: Some(output.clone()),
+            out_point: o.cell.as_ref().unwrap().to_owned(),
+            block_number: None,
+            cellbase: false,
+            capacity: output.capacity,
+            data_hash: None,
+            dep_group: output.is_dep_group(),
+        })
+    }
+
     pub fn remove_vertex(&mut self, id: &ProposalShortId, rtxs: &mut Vec<PoolEntry>) {
         if let Some(x) = self.vertices.remove(id) {
+            self.bytes -= x.size as u64;
             let tx = &x.transaction;
             let inputs = tx.input_pts();
             let outputs = tx.output_pts();
@@ -198,7 +207,7 @@ impl StagingPool {
         }
     }
 
-    pub fn add_tx(&mut self, cycles: Cycle, tx: Transaction) {
+    pub fn add_tx(&mut self, cycles: Cycle, fee: Capacity, tx: Transaction) {
         let inputs = tx.input_pts();
         let outputs = tx.output_pts();
         let deps = tx.dep_pts();
@@ -231,8 +240,11 @@ impl StagingPool {
             self.edges.mark_inpool(o);
         }
 
-        self.vertices
-            .insert(id, PoolEntry::new(tx, count, Some(cycles)));
+        let entry = PoolEntry::new(tx, count, Some(cycles), Some(fee));
+        self.bytes += entry.size as u64;
+        if let Some(replaced) = self.vertices.insert(id, entry) {
+            self.bytes -= replaced.size as u64;
+        }
     }
 
     pub fn remove_committed_tx(&mut self, tx: &Transaction) {
@@ -241,7 +253,8 @@ impl StagingPool {
         let deps = tx.dep_pts();
         let id = tx.proposal_short_id();
 
-        if self.vertices.remove(&id).is_some() {
+        if let Some(entry) = self.vertices.remove(&id) {
+            self.bytes -= entry.size as u64;
             for o in outputs {
                 if let Some(cid) = self.edges.remove_inner(&o) {
                     self.dec_ref(&cid);
@@ -358,8 +371,8 @@ mod tests {
         let id1 = tx1.proposal_short_id();
         let id2 = tx2.proposal_short_id();
 
-        pool.add_tx(MOCK_CYCLES, tx1.clone());
-        pool.add_tx(MOCK_CYCLES, tx2.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx1.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx2.clone());
 
         assert_eq!(pool.vertices.len(), 2);
         assert_eq!(pool.edges.inner_len(), 2);
@@ -385,8 +398,8 @@ mod tests {
         let id1 = tx1.proposal_short_id();
         let id2 = tx2.proposal_short_id();
 
-        pool.add_tx(MOCK_CYCLES, tx1.clone());
-        pool.add_tx(MOCK_CYCLES, tx2.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx1.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx2.clone());
 
         assert_eq!(pool.get(&id1).unwrap().refs_count, 0);
         assert_eq!(pool.get(&id2).unwrap().refs_count, 0);
@@ -434,11 +447,11 @@ mod tests {
 
         let mut pool = StagingPool::new();
 
-        pool.add_tx(MOCK_CYCLES, tx1.clone());
-        pool.add_tx(MOCK_CYCLES, tx2.clone());
-        pool.add_tx(MOCK_CYCLES, tx3.clone());
-        pool.add_tx(MOCK_CYCLES, tx4.clone());
-        pool.add_tx(MOCK_CYCLES, tx5.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx1.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx2.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx3.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx4.clone());
+        pool.add_tx(MOCK_CYCLES, Capacity::zero(), tx5.clone());
 
         assert_eq!(pool.get(&id1).unwrap().refs_count, 0);
         assert_eq!(pool.get(&id3).unwrap().refs_count, 1);
@@ -49,6 +49,7 @@ impl TxTraceMap {
     define_method!(add_orphan, Action::AddOrphan);
     define_method!(expired, Action::Expired);
     define_method!(committed, Action::Committed);
+    define_method!(removed, Action::Removed);
 }
 
 #[cfg(test)]
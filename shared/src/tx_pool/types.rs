@@ -2,10 +2,12 @@
 //! and its top-level members.
 
 use ckb_core::cell::UnresolvableError;
-use ckb_core::transaction::Transaction;
+use ckb_core::transaction::{Capacity, Transaction};
 use ckb_core::Cycle;
 use ckb_verification::TransactionError;
 use failure::Fail;
+use faketime::unix_time_as_millis;
+use numext_fixed_hash::H256;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -15,22 +17,86 @@ use std::hash::{Hash, Hasher};
 pub struct TxPoolConfig {
     /// Maximum capacity of the pool in number of transactions
     pub max_pool_size: usize,
+    /// Maximum serialized size in bytes of the pending and the staging
+    /// transactions, the lowest fee rate pending ones are evicted beyond it
+    #[serde(default = "default_max_pool_bytes")]
+    pub max_pool_bytes: u64,
+    /// Maximum number of the transactions waiting for their unknown inputs
     pub max_orphan_size: usize,
+    /// Seconds an orphan transaction waits for its unknown inputs
+    #[serde(default = "default_orphan_expiry")]
+    pub orphan_expiry: u64,
     pub max_proposal_size: usize,
     pub max_cache_size: usize,
     pub max_pending_size: usize,
     pub trace: Option<usize>,
+    /// Transactions paying a lower fee rate, in shannons per KB, are rejected
+    #[serde(default)]
+    pub min_fee_rate: u64,
+    /// A transaction spending the inputs of pool transactions replaces them
+    /// when its fee rate exceeds theirs by this many shannons per KB, and its
+    /// fee exceeds the sum of the replaced ones by the same rate of its size
+    #[serde(default = "default_replace_fee_rate_increment")]
+    pub replace_fee_rate_increment: u64,
+    /// Number of the recently rejected transactions kept with their reasons
+    #[serde(default = "default_max_rejections")]
+    pub max_rejections: usize,
+    /// Seconds after which an unconfirmed transaction is evicted, 0 for never
+    #[serde(default = "default_tx_expiry")]
+    pub tx_expiry: u64,
+    /// Blocks after which an unconfirmed transaction is evicted, counted from
+    /// the tip it entered the pool, 0 for never
+    #[serde(default = "default_tx_expiry_blocks")]
+    pub tx_expiry_blocks: u64,
+}
+
+const DEFAULT_REPLACE_FEE_RATE_INCREMENT: u64 = 1000;
+const DEFAULT_ORPHAN_EXPIRY: u64 = 600;
+const DEFAULT_MAX_POOL_BYTES: u64 = 20_000_000;
+const DEFAULT_MAX_REJECTIONS: usize = 1000;
+const DEFAULT_TX_EXPIRY: u64 = 24 * 60 * 60;
+const DEFAULT_TX_EXPIRY_BLOCKS: u64 = 10_000;
+
+fn default_max_pool_bytes() -> u64 {
+    DEFAULT_MAX_POOL_BYTES
+}
+
+fn default_tx_expiry() -> u64 {
+    DEFAULT_TX_EXPIRY
+}
+
+fn default_tx_expiry_blocks() -> u64 {
+    DEFAULT_TX_EXPIRY_BLOCKS
+}
+
+fn default_max_rejections() -> usize {
+    DEFAULT_MAX_REJECTIONS
+}
+
+fn default_orphan_expiry() -> u64 {
+    DEFAULT_ORPHAN_EXPIRY
+}
+
+fn default_replace_fee_rate_increment() -> u64 {
+    DEFAULT_REPLACE_FEE_RATE_INCREMENT
 }
 
 impl Default for TxPoolConfig {
     fn default() -> Self {
         TxPoolConfig {
             max_pool_size: 10000,
+            max_pool_bytes: DEFAULT_MAX_POOL_BYTES,
             max_orphan_size: 10000,
+            orphan_expiry: DEFAULT_ORPHAN_EXPIRY,
             max_proposal_size: 10000,
             max_cache_size: 1000,
             max_pending_size: 10000,
             trace: Some(100),
+            min_fee_rate: 0,
+            replace_fee_rate_increment: DEFAULT_REPLACE_FEE_RATE_INCREMENT,
+            max_rejections: DEFAULT_MAX_REJECTIONS,
+            tx_expiry: DEFAULT_TX_EXPIRY,
+            tx_expiry_blocks: DEFAULT_TX_EXPIRY_BLOCKS,
         }
     }
 }
@@ -57,6 +123,28 @@ pub enum PoolError {
     InvalidBlockNumber,
     /// Duplicate tx
     Duplicate,
+    /// The fee rate is lower than the `min_fee_rate` of the pool
+    LowFeeRate { min: u64, actual: u64 },
+    /// The transaction double spends the inputs of the pool transaction
+    /// `conflict`, paying a fee rate too low to replace it
+    LowReplaceFeeRate {
+        conflict: H256,
+        min: u64,
+        actual: u64,
+    },
+    /// The transaction double spends the inputs of pool transactions, paying
+    /// a fee lower than the ones of all the transactions it would replace
+    /// plus the `replace_fee_rate_increment` for its own size
+    LowReplaceFee { min: Capacity, actual: Capacity },
+    /// The transaction double spends the inputs of pool transactions whose
+    /// descendants, along with them, are more than `max`
+    TooManyReplacements { max: usize },
+    /// The transaction is not committed, or its unknown inputs do not
+    /// arrive, before the expiry of the pool
+    Expired,
+    /// The transaction is replaced by the transaction `by` double spending
+    /// its inputs, or the ones of its ancestors, at a higher fee
+    Replaced { by: H256 },
 }
 
 impl PoolError {
@@ -76,6 +164,32 @@ impl fmt::Display for PoolError {
     }
 }
 
+/// Fee rate in shannons per KB of the serialized transaction
+pub fn fee_rate(fee: Capacity, tx_size: usize) -> u64 {
+    fee.as_u64().saturating_mul(1000) / tx_size.max(1) as u64
+}
+
+/// A transaction refused by or evicted from the pool
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    /// Hash of the transaction
+    pub hash: H256,
+    /// Why the transaction is not in the pool
+    pub reason: PoolError,
+    /// Timestamp in milliseconds the transaction is rejected
+    pub timestamp: u64,
+}
+
+impl Rejection {
+    pub fn new(hash: H256, reason: PoolError) -> Rejection {
+        Rejection {
+            hash,
+            reason,
+            timestamp: unix_time_as_millis(),
+        }
+    }
+}
+
 /// An entry in the transaction pool.
 #[derive(Debug, Clone)]
 pub struct PoolEntry {
@@ -85,15 +199,30 @@ pub struct PoolEntry {
     pub refs_count: usize,
     /// Cycles
     pub cycles: Option<Cycle>,
+    /// Serialized size of the transaction
+    pub size: usize,
+    /// Fee resolved when the transaction is admitted, absent when its inputs
+    /// are not resolved yet
+    pub fee: Option<Capacity>,
+    /// Timestamp in milliseconds the entry is added to its queue
+    pub timestamp: u64,
 }
 
 impl PoolEntry {
     /// Create new transaction pool entry
-    pub fn new(tx: Transaction, count: usize, cycles: Option<Cycle>) -> PoolEntry {
+    pub fn new(
+        tx: Transaction,
+        count: usize,
+        cycles: Option<Cycle>,
+        fee: Option<Capacity>,
+    ) -> PoolEntry {
         PoolEntry {
+            size: tx.serialized_size(),
+            fee,
             transaction: tx,
             refs_count: count,
             cycles,
+            timestamp: unix_time_as_millis(),
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::versionbits::Deployment;
 use ckb_core::block::{Block, BlockBuilder};
 use ckb_core::extras::EpochExt;
 use ckb_core::header::Header;
@@ -7,6 +8,7 @@ use ckb_pow::{Pow, PowEngine};
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
 use std::cmp;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 pub(crate) const MAX_UNCLE_NUM: usize = 2;
@@ -26,6 +28,8 @@ pub(crate) const MAX_EPOCH_LENGTH: u64 = EPOCH_DURATION_TARGET / MIN_BLOCK_INTER
 pub(crate) const MIN_EPOCH_LENGTH: u64 = EPOCH_DURATION_TARGET / MAX_BLOCK_INTERVAL; // 240
 pub(crate) const GENESIS_EPOCH_LENGTH: u64 = 1_000;
 pub(crate) const MAX_BLOCK_BYTES: u64 = 2_000_000; // 2mb
+pub(crate) const MAX_TX_BYTES: u64 = 512_000; // 512kb
+pub(crate) const MAX_CELLBASE_MESSAGE_BYTES: u64 = 1_000;
 pub(crate) const MAX_BLOCK_PROPOSALS_LIMIT: u64 = 6_000;
 pub(crate) const BLOCK_VERSION: u32 = 0;
 
@@ -64,11 +68,21 @@ pub struct Consensus {
     pub max_block_cycles: Cycle,
     // Maximum number of bytes to use for the entire block
     pub max_block_bytes: u64,
+    // Maximum number of bytes of a single transaction
+    pub max_tx_bytes: u64,
+    // Maximum number of bytes of the message carried in the cellbase witness
+    pub max_cellbase_message_bytes: u64,
     // block version number supported
     pub block_version: Version,
     // block version number supported
     pub max_block_proposals_limit: u64,
     pub genesis_epoch_ext: EpochExt,
+    // Soft forks signaled with header version bits
+    pub deployments: Vec<Deployment>,
+    // Known block hashes by number, headers conflicting with them are rejected during sync
+    pub checkpoints: BTreeMap<BlockNumber, H256>,
+    // Total difficulty a peer's best header must reach before blocks are downloaded from it
+    pub min_chain_work: U256,
 }
 
 // genesis difficulty should not be zero
@@ -103,9 +117,14 @@ impl Default for Consensus {
             median_time_block_count: MEDIAN_TIME_BLOCK_COUNT,
             max_block_cycles: 20_000_000_000,
             max_block_bytes: MAX_BLOCK_BYTES,
+            max_tx_bytes: MAX_TX_BYTES,
+            max_cellbase_message_bytes: MAX_CELLBASE_MESSAGE_BYTES,
             genesis_epoch_ext,
             block_version: BLOCK_VERSION,
             max_block_proposals_limit: MAX_BLOCK_PROPOSALS_LIMIT,
+            deployments: Vec::new(),
+            checkpoints: BTreeMap::new(),
+            min_chain_work: U256::zero(),
         }
     }
 }
@@ -147,6 +166,42 @@ impl Consensus {
         self
     }
 
+    #[must_use]
+    pub fn set_median_time_block_count(mut self, median_time_block_count: usize) -> Self {
+        self.median_time_block_count = median_time_block_count;
+        self
+    }
+
+    #[must_use]
+    pub fn set_max_tx_bytes(mut self, max_tx_bytes: u64) -> Self {
+        self.max_tx_bytes = max_tx_bytes;
+        self
+    }
+
+    #[must_use]
+    pub fn set_max_cellbase_message_bytes(mut self, max_cellbase_message_bytes: u64) -> Self {
+        self.max_cellbase_message_bytes = max_cellbase_message_bytes;
+        self
+    }
+
+    #[must_use]
+    pub fn set_deployments(mut self, deployments: Vec<Deployment>) -> Self {
+        self.deployments = deployments;
+        self
+    }
+
+    #[must_use]
+    pub fn set_checkpoints(mut self, checkpoints: BTreeMap<BlockNumber, H256>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    #[must_use]
+    pub fn set_min_chain_work(mut self, min_chain_work: U256) -> Self {
+        self.min_chain_work = min_chain_work;
+        self
+    }
+
     pub fn set_pow(mut self, pow: Pow) -> Self {
         self.pow = pow;
         self
@@ -216,6 +271,14 @@ impl Consensus {
         self.max_block_bytes
     }
 
+    pub fn max_tx_bytes(&self) -> u64 {
+        self.max_tx_bytes
+    }
+
+    pub fn max_cellbase_message_bytes(&self) -> u64 {
+        self.max_cellbase_message_bytes
+    }
+
     pub fn max_block_proposals_limit(&self) -> u64 {
         self.max_block_proposals_limit
     }
@@ -224,6 +287,18 @@ impl Consensus {
         self.block_version
     }
 
+    pub fn deployments(&self) -> &[Deployment] {
+        &self.deployments
+    }
+
+    pub fn checkpoints(&self) -> &BTreeMap<BlockNumber, H256> {
+        &self.checkpoints
+    }
+
+    pub fn min_chain_work(&self) -> &U256 {
+        &self.min_chain_work
+    }
+
     pub fn tx_proposal_window(&self) -> ProposalWindow {
         self.tx_proposal_window
     }
@@ -9,7 +9,11 @@
 //! we must put nested config struct in the tail to make it serializable,
 //! details https://docs.rs/toml/0.5.0/toml/ser/index.html
 
-use crate::consensus::{Consensus, GENESIS_EPOCH_LENGTH};
+use crate::consensus::{
+    Consensus, GENESIS_EPOCH_LENGTH, MAX_CELLBASE_MESSAGE_BYTES, MAX_TX_BYTES,
+    MEDIAN_TIME_BLOCK_COUNT,
+};
+use crate::versionbits::{validate_deployments, Deployment};
 use ckb_core::block::Block;
 use ckb_core::block::BlockBuilder;
 use ckb_core::extras::EpochExt;
@@ -28,11 +32,15 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 pub mod consensus;
+pub mod versionbits;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ChainSpec {
     pub resource: Resource,
     pub name: String,
+    /// DNS seeds of the chain, queried for peer addresses in addition to the
+    /// ones of the network config
+    pub dns_seeds: Vec<String>,
     pub genesis: Genesis,
     pub params: Params,
     pub system_cells: Vec<Resource>,
@@ -43,6 +51,8 @@ pub struct ChainSpec {
 #[derive(Serialize, Deserialize)]
 pub struct ChainSpecConfig {
     pub name: String,
+    #[serde(default)]
+    pub dns_seeds: Vec<String>,
     pub genesis: Genesis,
     pub params: Params,
     pub system_cells: Vec<SystemCell>,
@@ -54,6 +64,43 @@ pub struct Params {
     pub epoch_reward: Capacity,
     pub max_block_cycles: Cycle,
     pub cellbase_maturity: BlockNumber,
+    /// Count of ancestors whose median timestamp a new header must exceed
+    #[serde(default = "default_median_time_block_count")]
+    pub median_time_block_count: usize,
+    /// Maximum serialized size of a single transaction
+    #[serde(default = "default_max_tx_bytes")]
+    pub max_tx_bytes: u64,
+    /// Maximum size of the message in the cellbase witness
+    #[serde(default = "default_max_cellbase_message_bytes")]
+    pub max_cellbase_message_bytes: u64,
+    /// Soft forks signaled with header version bits, see `versionbits`
+    #[serde(default)]
+    pub deployments: Vec<Deployment>,
+    /// Known block hashes the synchronizer checks downloaded headers against
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+    /// Total difficulty a peer's best header must reach before blocks are
+    /// downloaded from it
+    #[serde(default)]
+    pub min_chain_work: U256,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub number: BlockNumber,
+    pub hash: H256,
+}
+
+fn default_median_time_block_count() -> usize {
+    MEDIAN_TIME_BLOCK_COUNT
+}
+
+fn default_max_tx_bytes() -> u64 {
+    MAX_TX_BYTES
+}
+
+fn default_max_cellbase_message_bytes() -> u64 {
+    MAX_CELLBASE_MESSAGE_BYTES
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -151,6 +198,7 @@ impl ChainSpec {
             resource,
             system_cells: system_cells_result?,
             name: spec_config.name,
+            dns_seeds: spec_config.dns_seeds,
             genesis: spec_config.genesis,
             params: spec_config.params,
             pow: spec_config.pow,
@@ -215,6 +263,7 @@ impl ChainSpec {
             .build();
 
         self.verify_genesis_hash(&genesis_block)?;
+        validate_deployments(&self.params.deployments)?;
 
         let block_reward =
             Capacity::shannons(self.params.epoch_reward.as_u64() / GENESIS_EPOCH_LENGTH);
@@ -236,8 +285,20 @@ impl ChainSpec {
             .set_genesis_epoch_ext(genesis_epoch_ext)
             .set_genesis_block(genesis_block)
             .set_cellbase_maturity(self.params.cellbase_maturity)
+            .set_median_time_block_count(self.params.median_time_block_count)
             .set_epoch_reward(self.params.epoch_reward)
             .set_max_block_cycles(self.params.max_block_cycles)
+            .set_max_tx_bytes(self.params.max_tx_bytes)
+            .set_max_cellbase_message_bytes(self.params.max_cellbase_message_bytes)
+            .set_deployments(self.params.deployments.clone())
+            .set_checkpoints(
+                self.params
+                    .checkpoints
+                    .iter()
+                    .map(|checkpoint| (checkpoint.number, checkpoint.hash.clone()))
+                    .collect(),
+            )
+            .set_min_chain_work(self.params.min_chain_work.clone())
             .set_pow(self.pow.clone());
 
         Ok(consensus)
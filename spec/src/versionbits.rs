@@ -0,0 +1,357 @@
+//! Soft fork signaling with header version bits, similar to BIP 9.
+//!
+//! A header signals readiness for a deployment when its version has the
+//! `VERSIONBITS_TOP_BITS` prefix and the deployment `bit` set. The state of
+//! every deployment changes only at epoch boundaries, depending on the share
+//! of signaling blocks in the last epoch. The chain tallies the signals and
+//! persists the states when it stores the first block of an epoch, headers
+//! are only checked for a well formed version.
+
+use ckb_core::extras::EpochExt;
+use ckb_core::header::Header;
+use ckb_core::{EpochNumber, Version};
+use numext_fixed_hash::H256;
+use serde_derive::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+
+pub const VERSIONBITS_TOP_BITS: Version = 0x2000_0000;
+pub const VERSIONBITS_TOP_MASK: Version = 0xE000_0000;
+pub const VERSIONBITS_NUM_BITS: u8 = 29;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ThresholdState {
+    /// Before the start epoch
+    Defined,
+    /// Signaling is counted
+    Started,
+    /// Threshold reached, the deployment activates in the next epoch
+    LockedIn,
+    /// The new rules are enforced
+    Active,
+    /// Timeout reached before lock in
+    Failed,
+}
+
+impl Default for ThresholdState {
+    fn default() -> Self {
+        ThresholdState::Defined
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Deployment {
+    pub name: String,
+    pub bit: u8,
+    /// First epoch in which signaling is counted
+    pub start: EpochNumber,
+    /// The deployment fails if it is not locked in before this epoch
+    pub timeout: EpochNumber,
+    /// Percentage of the blocks of an epoch which must signal to lock in
+    pub threshold: u8,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DeploymentError {
+    /// The bit is not one of the `VERSIONBITS_NUM_BITS` signaling bits
+    InvalidBit { name: String, bit: u8 },
+    /// The deployment times out before or when it starts
+    InvalidPeriod {
+        name: String,
+        start: EpochNumber,
+        timeout: EpochNumber,
+    },
+    /// The threshold is not a percentage in 1..=100
+    InvalidThreshold { name: String, threshold: u8 },
+    /// Two deployments share a name
+    DuplicateName { name: String },
+    /// Two deployments signal with the same bit in overlapping periods
+    BitOverlap {
+        name: String,
+        other: String,
+        bit: u8,
+    },
+}
+
+impl Error for DeploymentError {}
+
+impl fmt::Display for DeploymentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DeploymentError: {:?}", self)
+    }
+}
+
+/// Checks the deployments declared in a chain spec, so an inconsistent spec
+/// is refused when loaded instead of silently never activating.
+pub fn validate_deployments(deployments: &[Deployment]) -> Result<(), DeploymentError> {
+    for (index, deployment) in deployments.iter().enumerate() {
+        let name = deployment.name.to_owned();
+        if deployment.bit >= VERSIONBITS_NUM_BITS {
+            return Err(DeploymentError::InvalidBit {
+                name,
+                bit: deployment.bit,
+            });
+        }
+        if deployment.timeout <= deployment.start {
+            return Err(DeploymentError::InvalidPeriod {
+                name,
+                start: deployment.start,
+                timeout: deployment.timeout,
+            });
+        }
+        if deployment.threshold == 0 || deployment.threshold > 100 {
+            return Err(DeploymentError::InvalidThreshold {
+                name,
+                threshold: deployment.threshold,
+            });
+        }
+        for other in &deployments[..index] {
+            if other.name == deployment.name {
+                return Err(DeploymentError::DuplicateName { name });
+            }
+            if other.bit == deployment.bit
+                && other.start < deployment.timeout
+                && deployment.start < other.timeout
+            {
+                return Err(DeploymentError::BitOverlap {
+                    name,
+                    other: other.name.to_owned(),
+                    bit: deployment.bit,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if `version` signals `bit`
+pub fn signals(version: Version, bit: u8) -> bool {
+    bit < VERSIONBITS_NUM_BITS
+        && (version & VERSIONBITS_TOP_MASK) == VERSIONBITS_TOP_BITS
+        && (version & (1 << bit)) != 0
+}
+
+impl Deployment {
+    /// State in epoch `next_epoch`, given the state in the previous epoch and
+    /// its count of signaling blocks.
+    pub fn next_state(
+        &self,
+        state: ThresholdState,
+        next_epoch: EpochNumber,
+        signal_count: u64,
+        last_epoch_length: u64,
+    ) -> ThresholdState {
+        match state {
+            ThresholdState::Defined => {
+                if next_epoch >= self.timeout {
+                    ThresholdState::Failed
+                } else if next_epoch >= self.start {
+                    ThresholdState::Started
+                } else {
+                    ThresholdState::Defined
+                }
+            }
+            ThresholdState::Started => {
+                if next_epoch >= self.timeout {
+                    ThresholdState::Failed
+                } else if signal_count * 100 >= u64::from(self.threshold) * last_epoch_length {
+                    ThresholdState::LockedIn
+                } else {
+                    ThresholdState::Started
+                }
+            }
+            ThresholdState::LockedIn => ThresholdState::Active,
+            ThresholdState::Active | ThresholdState::Failed => state,
+        }
+    }
+}
+
+/// Computes the states of `deployments` in the epoch following `last_epoch`,
+/// `header` must be the last block of `last_epoch` and `last_states` the
+/// states in `last_epoch`, missing states are `Defined`.
+pub fn next_states<F>(
+    deployments: &[Deployment],
+    last_states: &[ThresholdState],
+    last_epoch: &EpochExt,
+    header: &Header,
+    get_header: F,
+) -> Vec<ThresholdState>
+where
+    F: Fn(&H256) -> Option<Header>,
+{
+    // Nothing to tally, spares the walk over the headers of the epoch
+    if deployments.is_empty() {
+        return Vec::new();
+    }
+
+    // Counts the signaling blocks of the last epoch for every deployment
+    let mut signal_counts = vec![0u64; deployments.len()];
+    let mut current = Some(header.to_owned());
+    while let Some(header) = current {
+        if header.number() < last_epoch.start_number() {
+            break;
+        }
+        for (count, deployment) in signal_counts.iter_mut().zip(deployments) {
+            if signals(header.version(), deployment.bit) {
+                *count += 1;
+            }
+        }
+        current = if header.number() == 0 {
+            None
+        } else {
+            get_header(header.parent_hash())
+        };
+    }
+
+    deployments
+        .iter()
+        .enumerate()
+        .map(|(index, deployment)| {
+            let state = last_states.get(index).cloned().unwrap_or_default();
+            deployment.next_state(
+                state,
+                last_epoch.number() + 1,
+                signal_counts[index],
+                last_epoch.length(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_core::header::HeaderBuilder;
+    use ckb_core::Capacity;
+    use numext_fixed_uint::U256;
+
+    fn deployment() -> Deployment {
+        Deployment {
+            name: "test".to_owned(),
+            bit: 1,
+            start: 2,
+            timeout: 10,
+            threshold: 75,
+        }
+    }
+
+    #[test]
+    fn test_signals() {
+        assert!(signals(VERSIONBITS_TOP_BITS | 0b10, 1));
+        assert!(!signals(VERSIONBITS_TOP_BITS | 0b10, 0));
+        assert!(!signals(0b10, 1));
+        assert!(!signals(VERSIONBITS_TOP_BITS, VERSIONBITS_NUM_BITS));
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let deployment = deployment();
+        let mut state = ThresholdState::Defined;
+        state = deployment.next_state(state, 1, 100, 100);
+        assert_eq!(state, ThresholdState::Defined);
+        state = deployment.next_state(state, 2, 0, 100);
+        assert_eq!(state, ThresholdState::Started);
+        state = deployment.next_state(state, 3, 74, 100);
+        assert_eq!(state, ThresholdState::Started);
+        state = deployment.next_state(state, 4, 75, 100);
+        assert_eq!(state, ThresholdState::LockedIn);
+        state = deployment.next_state(state, 5, 0, 100);
+        assert_eq!(state, ThresholdState::Active);
+        assert_eq!(
+            deployment.next_state(state, 10, 0, 100),
+            ThresholdState::Active
+        );
+    }
+
+    #[test]
+    fn test_validate_deployments() {
+        assert_eq!(validate_deployments(&[deployment()]), Ok(()));
+
+        let mut invalid = deployment();
+        invalid.bit = VERSIONBITS_NUM_BITS;
+        assert_eq!(
+            validate_deployments(&[invalid]),
+            Err(DeploymentError::InvalidBit {
+                name: "test".to_owned(),
+                bit: VERSIONBITS_NUM_BITS,
+            })
+        );
+
+        let mut invalid = deployment();
+        invalid.timeout = invalid.start;
+        assert_eq!(
+            validate_deployments(&[invalid]),
+            Err(DeploymentError::InvalidPeriod {
+                name: "test".to_owned(),
+                start: 2,
+                timeout: 2,
+            })
+        );
+
+        let mut invalid = deployment();
+        invalid.threshold = 101;
+        assert_eq!(
+            validate_deployments(&[invalid]),
+            Err(DeploymentError::InvalidThreshold {
+                name: "test".to_owned(),
+                threshold: 101,
+            })
+        );
+
+        let mut other = deployment();
+        other.name = "other".to_owned();
+        other.start = 9;
+        other.timeout = 20;
+        assert_eq!(
+            validate_deployments(&[deployment(), other.clone()]),
+            Err(DeploymentError::BitOverlap {
+                name: "other".to_owned(),
+                other: "test".to_owned(),
+                bit: 1,
+            })
+        );
+        // the bit is reused once the first deployment timed out
+        other.start = 10;
+        assert_eq!(validate_deployments(&[deployment(), other]), Ok(()));
+
+        let mut other = deployment();
+        other.bit = 2;
+        assert_eq!(
+            validate_deployments(&[deployment(), other]),
+            Err(DeploymentError::DuplicateName {
+                name: "test".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let deployment = deployment();
+        assert_eq!(
+            deployment.next_state(ThresholdState::Started, 10, 100, 100),
+            ThresholdState::Failed
+        );
+        assert_eq!(
+            deployment.next_state(ThresholdState::Defined, 10, 0, 100),
+            ThresholdState::Failed
+        );
+    }
+
+    #[test]
+    fn test_next_states_without_deployments() {
+        let epoch = EpochExt::new(
+            0,
+            Capacity::zero(),
+            Capacity::zero(),
+            H256::zero(),
+            0,
+            10,
+            U256::zero(),
+        );
+        let header = HeaderBuilder::default().number(9).build();
+        let states = next_states(&[], &[], &epoch, &header, |_| {
+            panic!("the headers of the epoch are not walked")
+        });
+        assert!(states.is_empty());
+    }
+}
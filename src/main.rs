@@ -16,6 +16,11 @@ fn run_app() -> Result<(), ExitCode> {
                 (cli::CMD_HASHES, Some(sub_matches)) => {
                     subcommand::cli::hashes(Setup::locator_from_matches(&matches)?, sub_matches)
                 }
+                (cli::CMD_DEBUG_SCRIPT, Some(sub_matches)) => {
+                    subcommand::cli::debug_script(sub_matches)
+                }
+                (cli::CMD_SCRIPT_CONSTANTS, _) => subcommand::cli::script_constants(),
+                (cli::CMD_RPC_SCHEMA, _) => subcommand::cli::rpc_schema(),
                 _ => unreachable!(),
             };
         }
@@ -28,11 +33,27 @@ fn run_app() -> Result<(), ExitCode> {
     let _guard = setup.setup_app();
 
     match app_matches.subcommand() {
-        (cli::CMD_RUN, _) => subcommand::run(setup.run()?),
+        (cli::CMD_RUN, Some(matches)) => subcommand::run(setup.run(&matches)?),
         (cli::CMD_MINER, _) => subcommand::miner(setup.miner()?),
         (cli::CMD_PROF, Some(matches)) => subcommand::profile(setup.prof(&matches)?),
         (cli::CMD_EXPORT, Some(matches)) => subcommand::export(setup.export(&matches)?),
         (cli::CMD_IMPORT, Some(matches)) => subcommand::import(setup.import(&matches)?),
+        (cli::CMD_DB, Some(matches)) => match matches.subcommand() {
+            (cli::CMD_BACKUP, Some(sub_matches)) => {
+                subcommand::db::backup(setup.backup(&sub_matches)?)
+            }
+            (cli::CMD_RESTORE, Some(sub_matches)) => {
+                subcommand::db::restore(setup.restore(&sub_matches)?)
+            }
+            (cli::CMD_CONVERT, Some(sub_matches)) => {
+                subcommand::db::convert(setup.convert(&sub_matches)?)
+            }
+            (cli::CMD_COMPACT, Some(sub_matches)) => {
+                subcommand::db::compact(setup.compact(&sub_matches)?)
+            }
+            (cli::CMD_STATS, _) => subcommand::db::stats(setup.stats()?),
+            _ => unreachable!(),
+        },
         _ => unreachable!(),
     }
 }
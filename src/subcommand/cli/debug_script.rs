@@ -0,0 +1,49 @@
+use ckb_app_config::{cli, ExitCode};
+use ckb_core::Cycle;
+use ckb_script::{MockTransaction, Runner, ScriptConfig, ScriptExecutor, ScriptLocation};
+use clap::{value_t, ArgMatches};
+use std::fs;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+pub fn debug_script<'m>(matches: &ArgMatches<'m>) -> Result<(), ExitCode> {
+    let tx_file = value_t!(matches.value_of(cli::ARG_TX_FILE), PathBuf)?;
+    let location = if matches.is_present(cli::ARG_INPUT) {
+        ScriptLocation::Input(value_t!(matches.value_of(cli::ARG_INPUT), usize)?)
+    } else {
+        ScriptLocation::Output(value_t!(matches.value_of(cli::ARG_OUTPUT), usize)?)
+    };
+    let listen = value_t!(matches.value_of(cli::ARG_LISTEN), SocketAddr)?;
+    let max_cycles = value_t!(matches.value_of(cli::ARG_MAX_CYCLES), Cycle)?;
+
+    let mock_tx: MockTransaction = serde_json::from_slice(&fs::read(tx_file)?).map_err(|err| {
+        eprintln!("Invalid mock transaction: {}", err);
+        ExitCode::Config
+    })?;
+    // Stepping is only supported by the Rust interpreter
+    let config = ScriptConfig {
+        runner: Runner::Rust,
+        ..Default::default()
+    };
+    let executor = ScriptExecutor::new(mock_tx, config).map_err(|err| {
+        eprintln!("Invalid mock transaction: {}", err);
+        ExitCode::Config
+    })?;
+
+    let listener = TcpListener::bind(listen)?;
+    println!("Waiting for gdb to connect on {}", listen);
+    let execution = executor.debug(location, &listener, max_cycles);
+    for message in &execution.debug_output {
+        println!("DEBUG OUTPUT: {}", message);
+    }
+    match execution.result {
+        Ok(cycles) => {
+            println!("Script succeeded, cycles: {}", cycles);
+            Ok(())
+        }
+        Err(err) => {
+            eprintln!("Script failed: {:?}", err);
+            Err(ExitCode::Failure)
+        }
+    }
+}
@@ -1,5 +1,11 @@
+mod debug_script;
 mod hashes;
 mod keygen;
+mod rpc_schema;
+mod script_constants;
 
+pub use debug_script::debug_script;
 pub use hashes::hashes;
 pub use keygen::keygen;
+pub use rpc_schema::rpc_schema;
+pub use script_constants::script_constants;
@@ -0,0 +1,11 @@
+use ckb_app_config::ExitCode;
+use ckb_rpc::{openrpc_document, Module};
+
+pub fn rpc_schema() -> Result<(), ExitCode> {
+    let document = openrpc_document(&Module::all());
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&document).map_err(|_| ExitCode::Failure)?
+    );
+    Ok(())
+}
@@ -0,0 +1,7 @@
+use ckb_app_config::ExitCode;
+use ckb_script::generate_c_header;
+
+pub fn script_constants() -> Result<(), ExitCode> {
+    print!("{}", generate_c_header());
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use ckb_app_config::{BackupArgs, ExitCode};
+use ckb_db::BackendDB;
+use ckb_store::{ChainKVStore, ChainStore, Freezer, COLUMNS};
+
+// The db is locked by a running node, which takes its backups through the
+// `backup_store` RPC instead
+pub fn backup(args: BackupArgs) -> Result<(), ExitCode> {
+    let db = BackendDB::open_with_check(&args.config.db, COLUMNS).map_err(|err| {
+        eprintln!(
+            "Backup error: {}, stop the node first or call the backup_store RPC",
+            err
+        );
+        ExitCode::Failure
+    })?;
+    let mut store = ChainKVStore::new(db);
+    // nothing was frozen yet without the directory
+    if let Some(path) = args.config.freezer_path().filter(|path| path.exists()) {
+        let freezer = Freezer::open_read_only(&path).map_err(|err| {
+            eprintln!("Backup error: {:?}", err);
+            ExitCode::Failure
+        })?;
+        store = store.with_freezer(freezer);
+    }
+    store.backup(&args.target).map_err(|err| {
+        eprintln!("Backup error: {:?}", err);
+        ExitCode::Failure
+    })?;
+    println!("Backup saved into {}", args.target.display());
+    Ok(())
+}
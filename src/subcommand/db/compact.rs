@@ -0,0 +1,27 @@
+use ckb_app_config::{CompactArgs, ExitCode};
+use ckb_db::{BackendDB, MaintenanceKeyValueDB};
+use ckb_store::COLUMNS;
+
+pub fn compact(args: CompactArgs) -> Result<(), ExitCode> {
+    let columns = match args.column {
+        Some(col) if col >= COLUMNS => {
+            eprintln!("Compact error: the columns are numbered below {}", COLUMNS);
+            return Err(ExitCode::Cli);
+        }
+        Some(col) => col..col + 1,
+        None => 0..COLUMNS,
+    };
+    let db = BackendDB::open_with_check(&args.config.db, COLUMNS).map_err(|err| {
+        eprintln!("Compact error: {}, stop the node first", err);
+        ExitCode::Failure
+    })?;
+    for col in columns {
+        println!("Compacting column {} ...", col);
+        db.compact(col).map_err(|err| {
+            eprintln!("Compact error: {:?}", err);
+            ExitCode::Failure
+        })?;
+    }
+    println!("Database compacted");
+    Ok(())
+}
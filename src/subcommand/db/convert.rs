@@ -0,0 +1,58 @@
+use ckb_app_config::{ConvertArgs, ExitCode};
+use ckb_db::{
+    BackendDB, DBBackend, DBConfig, DbBatch, Direction, Error, IterableKeyValueDB, KeyValueDB,
+};
+use ckb_store::COLUMNS;
+use std::fs;
+
+// Entries written in one batch, keeps the memory used by the copy bounded
+const BATCH_SIZE: usize = 10_000;
+
+pub fn convert(args: ConvertArgs) -> Result<(), ExitCode> {
+    if args.target.exists() && fs::read_dir(&args.target)?.next().is_some() {
+        eprintln!("Convert error: {} is not empty", args.target.display());
+        return Err(ExitCode::Cli);
+    }
+    let source = BackendDB::open_with_check(&args.config.db, COLUMNS).map_err(convert_error)?;
+    let target_config = DBConfig {
+        path: args.target.clone(),
+        backend: args.backend,
+        options: args.config.db.options.clone(),
+    };
+    let target = BackendDB::open_with_check(&target_config, COLUMNS).map_err(convert_error)?;
+
+    for col in 0..COLUMNS {
+        let mut batch = target.batch().map_err(convert_error)?;
+        let mut size = 0;
+        for (key, value) in source
+            .iter(col, &[], Direction::Forward)
+            .map_err(convert_error)?
+        {
+            batch.insert(col, &key, &value).map_err(convert_error)?;
+            size += 1;
+            if size == BATCH_SIZE {
+                batch.commit().map_err(convert_error)?;
+                batch = target.batch().map_err(convert_error)?;
+                size = 0;
+            }
+        }
+        batch.commit().map_err(convert_error)?;
+    }
+
+    println!(
+        "Database converted into {}. To use it, stop the node, replace {} with it and set \
+         `backend = \"{}\"` in the [db] section of ckb.toml",
+        args.target.display(),
+        args.config.db.path.display(),
+        match args.backend {
+            DBBackend::RocksDB => "rocksdb",
+            DBBackend::Sled => "sled",
+        }
+    );
+    Ok(())
+}
+
+fn convert_error(err: Error) -> ExitCode {
+    eprintln!("Convert error: {:?}", err);
+    ExitCode::Failure
+}
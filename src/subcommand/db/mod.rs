@@ -0,0 +1,11 @@
+mod backup;
+mod compact;
+mod convert;
+mod restore;
+mod stats;
+
+pub use backup::backup;
+pub use compact::compact;
+pub use convert::convert;
+pub use restore::restore;
+pub use stats::stats;
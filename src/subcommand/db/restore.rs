@@ -0,0 +1,54 @@
+use ckb_app_config::{ExitCode, RestoreArgs};
+use ckb_db::DBBackend;
+use ckb_store::{BACKUP_DB_DIR, BACKUP_FREEZER_DIR};
+use std::fs;
+use std::path::Path;
+
+pub fn restore(args: RestoreArgs) -> Result<(), ExitCode> {
+    let db_source = args.source.join(BACKUP_DB_DIR);
+    let freezer_source = args.source.join(BACKUP_FREEZER_DIR);
+    let freezer_path = args.config.freezer_dir();
+    if !db_source.is_dir() {
+        eprintln!("Restore error: {} is not a backup", args.source.display());
+        return Err(ExitCode::Cli);
+    }
+    // The backups are RocksDB checkpoints
+    if args.config.db.backend != DBBackend::RocksDB {
+        eprintln!("Restore error: the backups can only be restored into the rocksdb backend");
+        return Err(ExitCode::Config);
+    }
+    // The restored db must not be mixed with the blocks frozen by the replaced one
+    for path in &[&args.config.db.path, &freezer_path] {
+        if !is_empty_dir(path)? {
+            eprintln!(
+                "Restore error: {} is not empty, stop the node and remove it first",
+                path.display()
+            );
+            return Err(ExitCode::Failure);
+        }
+    }
+
+    copy_dir(&db_source, &args.config.db.path)?;
+    if freezer_source.is_dir() {
+        copy_dir(&freezer_source, &freezer_path)?;
+    }
+    println!("Backup {} restored", args.source.display());
+    Ok(())
+}
+
+fn is_empty_dir(path: &Path) -> Result<bool, ExitCode> {
+    if !path.exists() {
+        return Ok(true);
+    }
+    Ok(fs::read_dir(path)?.next().is_none())
+}
+
+// The db checkpoints and the freezers are flat directories
+fn copy_dir(from: &Path, to: &Path) -> Result<(), ExitCode> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        fs::copy(entry.path(), to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use ckb_app_config::{ExitCode, StatsArgs};
+use ckb_db::{BackendDB, MaintenanceKeyValueDB};
+use ckb_store::COLUMNS;
+
+pub fn stats(args: StatsArgs) -> Result<(), ExitCode> {
+    let db = BackendDB::open_with_check(&args.config.db, COLUMNS).map_err(|err| {
+        eprintln!("Stats error: {}, stop the node first", err);
+        ExitCode::Failure
+    })?;
+    let (mut total_keys, mut total_size) = (0, 0);
+    for col in 0..COLUMNS {
+        let stats = db.column_stats(col).map_err(|err| {
+            eprintln!("Stats error: {:?}", err);
+            ExitCode::Failure
+        })?;
+        println!("column {}: {} keys, {} bytes", col, stats.keys, stats.size);
+        for (name, value) in &stats.properties {
+            println!("    {}: {}", name, value);
+        }
+        total_keys += stats.keys;
+        total_size += stats.size;
+    }
+    println!("total: {} keys, {} bytes", total_keys, total_size);
+    Ok(())
+}
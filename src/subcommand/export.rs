@@ -1,12 +1,13 @@
 use ckb_app_config::{ExitCode, ExportArgs};
-use ckb_db::{CacheDB, RocksDB};
+use ckb_db::{BackendDB, CacheDB};
 use ckb_instrument::Export;
 use ckb_shared::shared::SharedBuilder;
 
 pub fn export(args: ExportArgs) -> Result<(), ExitCode> {
-    let shared = SharedBuilder::<CacheDB<RocksDB>>::default()
+    let shared = SharedBuilder::<CacheDB<BackendDB>>::default()
         .consensus(args.consensus)
-        .db(&args.config.db)
+        .db(&args.config.db, &args.config.store)
+        .freezer_path(args.config.freezer_path())
         .build()
         .map_err(|err| {
             eprintln!("Export error: {:?}", err);
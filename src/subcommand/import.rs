@@ -1,14 +1,16 @@
 use ckb_app_config::{ExitCode, ImportArgs};
 use ckb_chain::chain::ChainBuilder;
-use ckb_db::{CacheDB, RocksDB};
+use ckb_db::{BackendDB, CacheDB};
 use ckb_instrument::Import;
 use ckb_notify::NotifyService;
 use ckb_shared::shared::SharedBuilder;
+use ckb_traits::ChainProvider;
 
 pub fn import(args: ImportArgs) -> Result<(), ExitCode> {
-    let shared = SharedBuilder::<CacheDB<RocksDB>>::default()
+    let shared = SharedBuilder::<CacheDB<BackendDB>>::default()
         .consensus(args.consensus)
-        .db(&args.config.db)
+        .db(&args.config.db, &args.config.store)
+        .freezer_path(args.config.freezer_path())
         .build()
         .map_err(|err| {
             eprintln!("Import error: {:?}", err);
@@ -19,10 +21,17 @@ pub fn import(args: ImportArgs) -> Result<(), ExitCode> {
     let chain_service = ChainBuilder::new(shared.clone(), notify).build();
     let chain_controller = chain_service.start::<&str>(Some("ImportChainService"));
 
-    Import::new(chain_controller, args.format, args.source)
-        .execute()
-        .map_err(|err| {
-            eprintln!("Import error: {:?}", err);
-            ExitCode::Failure
-        })
+    Import::new(
+        chain_controller,
+        args.format,
+        args.source,
+        shared.genesis_hash().to_owned(),
+        shared.consensus().max_block_bytes(),
+    )
+    .fast(args.fast)
+    .execute()
+    .map_err(|err| {
+        eprintln!("Import error: {:?}", err);
+        ExitCode::Failure
+    })
 }
@@ -12,7 +12,10 @@ pub fn miner(args: MinerArgs) -> Result<(), ExitCode> {
 
     let client = Client::new(Arc::clone(&work), new_work_tx, args.config);
 
-    let miner = Miner::new(work, args.pow_engine, new_work_rx, client.clone());
+    let miner = Miner::new(work, args.pow_engine, new_work_rx, client.clone()).map_err(|err| {
+        eprintln!("Start miner workers error: {}", err);
+        ExitCode::Failure
+    })?;
 
     thread::Builder::new()
         .name("client".to_string())
@@ -1,4 +1,5 @@
 pub mod cli;
+pub mod db;
 mod export;
 mod import;
 mod init;
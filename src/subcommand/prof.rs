@@ -1,6 +1,6 @@
 use ckb_app_config::{ExitCode, ProfArgs};
 use ckb_chain::chain::ChainBuilder;
-use ckb_db::{CacheDB, DBConfig, RocksDB};
+use ckb_db::{BackendDB, CacheDB, DBConfig};
 use ckb_notify::NotifyService;
 use ckb_shared::shared::{Shared, SharedBuilder};
 use ckb_store::ChainStore;
@@ -8,9 +8,9 @@ use log::info;
 use std::sync::Arc;
 
 pub fn profile(args: ProfArgs) -> Result<(), ExitCode> {
-    let shared = SharedBuilder::<CacheDB<RocksDB>>::default()
+    let shared = SharedBuilder::<CacheDB<BackendDB>>::default()
         .consensus(args.consensus.clone())
-        .db(&args.config.db)
+        .db(&args.config.db, &args.config.store)
         .tx_pool_config(args.config.tx_pool.clone())
         .build()
         .map_err(|err| {
@@ -19,12 +19,15 @@ pub fn profile(args: ProfArgs) -> Result<(), ExitCode> {
         })?;
 
     let tmp_dir = tempfile::Builder::new().tempdir().unwrap();
-    let tmp_shared = SharedBuilder::<CacheDB<RocksDB>>::default()
+    let tmp_shared = SharedBuilder::<CacheDB<BackendDB>>::default()
         .consensus(args.consensus)
-        .db(&DBConfig {
-            path: tmp_dir.as_ref().to_path_buf(),
-            options: None,
-        })
+        .db(
+            &DBConfig {
+                path: tmp_dir.as_ref().to_path_buf(),
+                ..Default::default()
+            },
+            &args.config.store,
+        )
         .tx_pool_config(args.config.tx_pool)
         .build()
         .map_err(|err| {
@@ -1,27 +1,66 @@
 use crate::helper::{deadlock_detection, wait_for_exit};
 use ckb_app_config::{ExitCode, RunArgs};
-use ckb_chain::chain::{ChainBuilder, ChainController};
-use ckb_db::{CacheDB, RocksDB};
-use ckb_miner::BlockAssembler;
+use ckb_chain::chain::{ChainBuilder, ChainController, MIN_FREEZE_EPOCHS, MIN_PRUNE_DEPTH};
+use ckb_chain_spec::Genesis;
+use ckb_db::{BackendDB, CacheDB};
+use ckb_indexer::IndexerStore;
+use ckb_miner::{BlockAssembler, Stratum};
 use ckb_network::{CKBProtocol, NetworkService, NetworkState};
 use ckb_notify::{NotifyController, NotifyService};
+use ckb_protocol::RelayMessage;
 use ckb_rpc::RpcServer;
+use ckb_script::Runner;
+use ckb_shared::fee_estimator::FeeEstimatorService;
 use ckb_shared::shared::{Shared, SharedBuilder};
 use ckb_store::ChainStore;
-use ckb_sync::{NetTimeProtocol, NetworkProtocol, Relayer, SyncSharedState, Synchronizer};
+use ckb_sync::{
+    relay_message_type, supported_protocol_versions, sync_message_type, BlockFilterProtocol,
+    LightClientProtocol, NetTimeProtocol, NetworkProtocol, Relayer, SyncSharedState, Synchronizer,
+    COMPRESSION_PROTOCOL_VERSION,
+};
 use ckb_traits::chain_provider::ChainProvider;
-use ckb_verification::{BlockVerifier, Verifier};
-use log::info;
+use ckb_verification::{GenesisVerifier, Verifier};
+use flatbuffers::FlatBufferBuilder;
+use log::{info, warn};
+use std::collections::HashSet;
 use std::sync::Arc;
 
 pub fn run(args: RunArgs) -> Result<(), ExitCode> {
     deadlock_detection();
 
-    let shared = SharedBuilder::<CacheDB<RocksDB>>::new()
+    if args.config.script.runner == Runner::Assembly && !Runner::is_assembly_supported() {
+        warn!(target: "main", "Assembly script runner is not supported by this build, falling back to the Rust runner");
+    }
+
+    if let Some(prune_depth) = args.config.chain.prune_depth {
+        if prune_depth < MIN_PRUNE_DEPTH {
+            eprintln!(
+                "Config error: chain.prune_depth must be at least {}",
+                MIN_PRUNE_DEPTH
+            );
+            return Err(ExitCode::Config);
+        }
+    }
+    if let Err(err) = args.config.rpc.check_auth() {
+        eprintln!("Config error: {}", err);
+        return Err(ExitCode::Config);
+    }
+    if let Some(freeze_epochs) = args.config.chain.freeze_epochs {
+        if freeze_epochs < MIN_FREEZE_EPOCHS {
+            eprintln!(
+                "Config error: chain.freeze_epochs must be at least {}",
+                MIN_FREEZE_EPOCHS
+            );
+            return Err(ExitCode::Config);
+        }
+    }
+
+    let shared = SharedBuilder::<CacheDB<BackendDB>>::new()
         .consensus(args.consensus)
-        .db(&args.config.db)
+        .db(&args.config.db, &args.config.store)
         .tx_pool_config(args.config.tx_pool)
         .script_config(args.config.script)
+        .freezer_path(args.config.freezer_path())
         .build()
         .map_err(|err| {
             eprintln!("Run error: {:?}", err);
@@ -29,11 +68,16 @@ pub fn run(args: RunArgs) -> Result<(), ExitCode> {
         })?;
 
     // Verify genesis every time starting node
-    verify_genesis(&shared)?;
+    verify_genesis(&shared, &args.genesis)?;
 
     let notify = NotifyService::default().start(Some("notify"));
 
-    let chain_controller = setup_chain(shared.clone(), notify.clone());
+    let chain_controller = setup_chain(
+        shared.clone(),
+        notify.clone(),
+        args.config.chain.prune_depth,
+        args.config.chain.freeze_epochs,
+    );
     info!(target: "main", "chain genesis hash: {:#x}", shared.genesis_hash());
 
     let block_assembler = BlockAssembler::new(shared.clone(), args.config.block_assembler);
@@ -42,35 +86,48 @@ pub fn run(args: RunArgs) -> Result<(), ExitCode> {
     let network_state = Arc::new(
         NetworkState::from_config(args.config.network).expect("Init network state failed"),
     );
+    if let Some(target) = &args.assume_valid_target {
+        info!(target: "main", "skip running scripts of the ancestors of {:#x}", target);
+    }
     let sync_shared_state = Arc::new(SyncSharedState::new(shared.clone()));
     let synchronizer = Synchronizer::new(
         chain_controller.clone(),
         Arc::clone(&sync_shared_state),
         args.config.sync,
-    );
+    )
+    .assume_valid_target(args.assume_valid_target);
 
+    let sync_peers = synchronizer.peers();
     let relayer = Relayer::new(
         chain_controller.clone(),
-        sync_shared_state,
+        Arc::clone(&sync_shared_state),
         synchronizer.peers(),
-    );
-    let net_timer = NetTimeProtocol::default();
+    )
+    .transactions_rate_limit(synchronizer.config.transactions_rate_limit)
+    .get_transactions_rate_limit(synchronizer.config.get_transactions_rate_limit)
+    .notify_controller(notify.clone());
+    let net_timer = NetTimeProtocol::with_tolerant_offset(synchronizer.config.tolerant_time_offset);
+    let block_filter = BlockFilterProtocol::new(shared.clone());
+    let light_client = LightClientProtocol::new(shared.clone())
+        .proofs_rate_limit(synchronizer.config.light_client_proofs_rate_limit);
 
     let protocols = vec![
         CKBProtocol::new(
             "syn".to_string(),
             NetworkProtocol::SYNC.into(),
-            &["1".to_string()][..],
+            &supported_protocol_versions()[..],
             move || Box::new(synchronizer.clone()),
             Arc::clone(&network_state),
-        ),
+        )
+        .compress_since(COMPRESSION_PROTOCOL_VERSION.to_string(), sync_message_type),
         CKBProtocol::new(
             "rel".to_string(),
             NetworkProtocol::RELAY.into(),
-            &["1".to_string()][..],
+            &supported_protocol_versions()[..],
             move || Box::new(relayer.clone()),
             Arc::clone(&network_state),
-        ),
+        )
+        .compress_since(COMPRESSION_PROTOCOL_VERSION.to_string(), relay_message_type),
         CKBProtocol::new(
             "tim".to_string(),
             NetworkProtocol::TIME.into(),
@@ -78,17 +135,74 @@ pub fn run(args: RunArgs) -> Result<(), ExitCode> {
             move || Box::new(net_timer.clone()),
             Arc::clone(&network_state),
         ),
+        CKBProtocol::new(
+            "flt".to_string(),
+            NetworkProtocol::FILTER.into(),
+            &["1".to_string()][..],
+            move || Box::new(block_filter.clone()),
+            Arc::clone(&network_state),
+        ),
+        CKBProtocol::new(
+            "lig".to_string(),
+            NetworkProtocol::LIGHT_CLIENT.into(),
+            &["1".to_string()][..],
+            move || Box::new(light_client.clone()),
+            Arc::clone(&network_state),
+        ),
     ];
     let network_controller = NetworkService::new(Arc::clone(&network_state), protocols)
         .start(Some("NetworkService"))
         .expect("Start network service failed");
 
+    if let Some(stratum_config) = args.config.stratum {
+        let network_controller = network_controller.clone();
+        Stratum::new(
+            stratum_config,
+            block_assembler_controller.clone(),
+            chain_controller.clone(),
+            shared.consensus().pow_engine(),
+        )
+        .on_block(move |block| {
+            // announce new block
+            let fbb = &mut FlatBufferBuilder::new();
+            let message = RelayMessage::build_compact_block(fbb, block, &HashSet::new());
+            fbb.finish(message, None);
+            network_controller.broadcast(NetworkProtocol::RELAY.into(), fbb.finished_data().into());
+        })
+        .start()
+        .map_err(|err| {
+            eprintln!("Start stratum server error: {}", err);
+            ExitCode::Failure
+        })?;
+    }
+
+    let indexer_store = if args.config.rpc.indexer_enable() {
+        let indexer_store = IndexerStore::new(&args.config.indexer_db, shared.clone());
+        indexer_store.start(&notify, Some("Indexer"));
+        Some(indexer_store)
+    } else {
+        None
+    };
+
+    let fee_estimator = if args.config.rpc.experiment_enable() {
+        let fee_estimator = FeeEstimatorService::new(shared.clone());
+        fee_estimator.start(&notify, Some("FeeEstimator"));
+        Some(fee_estimator)
+    } else {
+        None
+    };
+
     let rpc_server = RpcServer::new(
         args.config.rpc,
         network_controller,
         shared,
         chain_controller,
         block_assembler_controller,
+        sync_shared_state,
+        sync_peers,
+        notify,
+        indexer_store,
+        fee_estimator,
     );
 
     wait_for_exit();
@@ -103,15 +217,33 @@ pub fn run(args: RunArgs) -> Result<(), ExitCode> {
 fn setup_chain<CS: ChainStore + 'static>(
     shared: Shared<CS>,
     notify: NotifyController,
+    prune_depth: Option<u64>,
+    freeze_epochs: Option<u64>,
 ) -> ChainController {
-    let chain_service = ChainBuilder::new(shared, notify).build();
+    let chain_service = ChainBuilder::new(shared, notify)
+        .prune_depth(prune_depth)
+        .freeze_epochs(freeze_epochs)
+        .build();
     chain_service.start(Some("ChainService"))
 }
 
-fn verify_genesis<CS: ChainStore + 'static>(shared: &Shared<CS>) -> Result<(), ExitCode> {
-    let genesis = shared.consensus().genesis_block();
-    BlockVerifier::new(shared.clone())
-        .verify(genesis)
+fn verify_genesis<CS: ChainStore + 'static>(
+    shared: &Shared<CS>,
+    genesis: &Genesis,
+) -> Result<(), ExitCode> {
+    // the stored genesis is the one the node runs on, the consensus one is
+    // derived from the same spec and would pass trivially
+    let block = shared
+        .store()
+        .get_block_hash(0)
+        .and_then(|hash| shared.store().get_block(&hash))
+        .ok_or_else(|| {
+            eprintln!("genesis error: the genesis block was not found in the database");
+            ExitCode::Failure
+        })?;
+    GenesisVerifier::new(shared.consensus())
+        .spec(genesis)
+        .verify(&block)
         .map_err(|err| {
             eprintln!("genesis error: {}", err);
             ExitCode::Config
@@ -0,0 +1,213 @@
+//! Golomb-coded sets, as specified by BIP158.
+//!
+//! A filter commits to a set of 32 bytes items, the items are hashed to
+//! `[0, n * M)` with siphash keyed by the block hash, sorted, and the
+//! differences between them are Golomb-Rice coded with parameter `P`.
+use numext_fixed_hash::H256;
+use siphasher::sip::SipHasher24;
+use std::hash::Hasher;
+
+/// Bits of the remainder in the Golomb-Rice coding
+pub const FILTER_P: u8 = 19;
+/// Inverse of the false positive rate
+pub const FILTER_M: u64 = 784_931;
+
+fn sip_keys(block_hash: &H256) -> (u64, u64) {
+    let bytes = block_hash.as_bytes();
+    let mut k0 = [0u8; 8];
+    let mut k1 = [0u8; 8];
+    k0.copy_from_slice(&bytes[0..8]);
+    k1.copy_from_slice(&bytes[8..16]);
+    (u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+}
+
+fn hash_to_range(keys: (u64, u64), item: &H256, range: u64) -> u64 {
+    let mut hasher = SipHasher24::new_with_keys(keys.0, keys.1);
+    hasher.write(item.as_bytes());
+    ((u128::from(hasher.finish()) * u128::from(range)) >> 64) as u64
+}
+
+fn hashed_set(block_hash: &H256, items: &[H256], n: u64) -> Vec<u64> {
+    let keys = sip_keys(block_hash);
+    let range = n * FILTER_M;
+    let mut values: Vec<u64> = items
+        .iter()
+        .map(|item| hash_to_range(keys, item, range))
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// Builds the filter of `items` for the block `block_hash`, it is prefixed with
+/// the number of items as a little endian u64.
+pub fn build_filter(block_hash: &H256, items: &[H256]) -> Vec<u8> {
+    let mut items = items.to_vec();
+    items.sort();
+    items.dedup();
+    let n = items.len() as u64;
+
+    let mut writer = BitWriter::new(n.to_le_bytes().to_vec());
+    let mut last = 0;
+    for value in hashed_set(block_hash, &items, n) {
+        let delta = value - last;
+        last = value;
+        // Unary coded quotient, then the remainder in FILTER_P bits
+        for _ in 0..(delta >> FILTER_P) {
+            writer.write_bit(true);
+        }
+        writer.write_bit(false);
+        writer.write_bits(delta, FILTER_P);
+    }
+    writer.finish()
+}
+
+/// Returns true if any of `items` may be in the filter, false positives
+/// happen at a rate of `1 / FILTER_M` per item.
+pub fn match_any(block_hash: &H256, filter: &[u8], items: &[H256]) -> bool {
+    if filter.len() < 8 || items.is_empty() {
+        return false;
+    }
+    let mut len = [0u8; 8];
+    len.copy_from_slice(&filter[..8]);
+    let n = u64::from_le_bytes(len);
+    if n == 0 {
+        return false;
+    }
+
+    let queries = hashed_set(block_hash, items, n);
+    let mut reader = BitReader::new(&filter[8..]);
+    let mut value = 0;
+    let mut queries = queries.iter().peekable();
+    // The padding of the last byte is shorter than any code, decoding stops there
+    loop {
+        let mut quotient = 0;
+        loop {
+            match reader.read_bit() {
+                Some(true) => quotient += 1,
+                Some(false) => break,
+                None => return false,
+            }
+        }
+        let remainder = match reader.read_bits(FILTER_P) {
+            Some(remainder) => remainder,
+            None => return false,
+        };
+        value += (quotient << FILTER_P) + remainder;
+
+        while let Some(query) = queries.peek() {
+            if **query < value {
+                queries.next();
+            } else if **query == value {
+                return true;
+            } else {
+                break;
+            }
+        }
+        if queries.peek().is_none() {
+            return false;
+        }
+    }
+}
+
+struct BitWriter {
+    buffer: Vec<u8>,
+    current: u8,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn new(buffer: Vec<u8>) -> Self {
+        BitWriter {
+            buffer,
+            current: 0,
+            bits: 0,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        self.current = (self.current << 1) | u8::from(bit);
+        self.bits += 1;
+        if self.bits == 8 {
+            self.buffer.push(self.current);
+            self.current = 0;
+            self.bits = 0;
+        }
+    }
+
+    // Writes the `count` lowest bits of `value`, most significant first
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for i in (0..count).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.buffer.push(self.current << (8 - self.bits));
+        }
+        self.buffer
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.data.get(self.position / 8)?;
+        let bit = (byte >> (7 - self.position % 8)) & 1 == 1;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | u64::from(self.read_bit()?);
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(i: u64) -> H256 {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&i.to_le_bytes());
+        H256::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_match_filter() {
+        let block_hash = item(42);
+        let items: Vec<H256> = (0..100).map(item).collect();
+        let filter = build_filter(&block_hash, &items);
+
+        for i in 0..100 {
+            assert!(match_any(&block_hash, &filter, &[item(i)]));
+        }
+        assert!(match_any(&block_hash, &filter, &[item(1000), item(7)]));
+        let false_positives = (1000..2000)
+            .filter(|i| match_any(&block_hash, &filter, &[item(*i)]))
+            .count();
+        assert!(false_positives < 2);
+    }
+
+    #[test]
+    fn test_empty_filter() {
+        let block_hash = item(42);
+        let filter = build_filter(&block_hash, &[]);
+        assert_eq!(filter, vec![0u8; 8]);
+        assert!(!match_any(&block_hash, &filter, &[item(1)]));
+        assert!(!match_any(&block_hash, &[], &[item(1)]));
+    }
+}
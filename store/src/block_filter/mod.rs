@@ -0,0 +1,275 @@
+//! Compact block filters for light clients.
+//!
+//! The filter of every stored block is built when the block is inserted by
+//! the chain, along with the filter header chaining it to the filters of its
+//! ancestors.
+mod gcs;
+
+pub use self::gcs::{build_filter, match_any};
+
+use crate::store::{ChainStore, StoreBatch};
+use ckb_core::block::Block;
+use ckb_core::transaction::CellOutput;
+use ckb_db::Error;
+use hash::new_blake2b;
+use numext_fixed_hash::H256;
+use std::collections::HashMap;
+
+// Ancestors missing a filter built along with a block at most
+const MAX_FILTER_BACKFILL: usize = 1000;
+
+/// Items committed by the filter of `block`: the lock and type script hashes of
+/// the created cells and the lock script hashes of the spent cells
+pub fn block_filter_items<CS: ChainStore>(store: &CS, block: &Block) -> Vec<H256> {
+    // Cells created in the block may be spent by its later transactions
+    let created: HashMap<(&H256, u32), &CellOutput> = block
+        .transactions()
+        .iter()
+        .flat_map(|tx| {
+            tx.outputs()
+                .iter()
+                .enumerate()
+                .map(move |(index, output)| ((tx.hash(), index as u32), output))
+        })
+        .collect();
+    let mut items = Vec::new();
+    for tx in block.transactions() {
+        for output in tx.outputs() {
+            items.push(output.lock.hash());
+            if let Some(type_) = &output.type_ {
+                items.push(type_.hash());
+            }
+        }
+        if tx.is_cellbase() {
+            continue;
+        }
+        for input in tx.inputs() {
+            if let Some(cell) = &input.previous_output.cell {
+                let lock_hash = match created.get(&(&cell.tx_hash, cell.index)) {
+                    Some(output) => Some(output.lock.hash()),
+                    None => store
+                        .get_cell_output(&cell.tx_hash, cell.index)
+                        .map(|output| output.lock.hash()),
+                };
+                if let Some(lock_hash) = lock_hash {
+                    items.push(lock_hash);
+                }
+            }
+        }
+    }
+    items
+}
+
+pub fn filter_hash(filter: &[u8]) -> H256 {
+    let mut hash = [0u8; 32];
+    let mut blake2b = new_blake2b();
+    blake2b.update(filter);
+    blake2b.finalize(&mut hash);
+    hash.into()
+}
+
+/// The filter header commits to the filter of the block and to the filter
+/// header of its parent, so a chain of filters can be checked from a single header.
+pub fn filter_header(filter_hash: &H256, previous_filter_header: &H256) -> H256 {
+    let mut hash = [0u8; 32];
+    let mut blake2b = new_blake2b();
+    blake2b.update(filter_hash.as_bytes());
+    blake2b.update(previous_filter_header.as_bytes());
+    blake2b.finalize(&mut hash);
+    hash.into()
+}
+
+/// Inserts the filter and the filter header of `block` into `batch`.
+///
+/// The filters of the ancestors missing one, e.g. in a store created before
+/// the filters, are built first, `MAX_FILTER_BACKFILL` of them at most. When
+/// an ancestor cannot be filtered, being pruned or deeper, the block is
+/// recorded as unfiltered instead, so its descendants are recorded as well
+/// without walking back again.
+pub fn insert_block_filter<CS: ChainStore>(
+    store: &CS,
+    batch: &mut StoreBatch,
+    block: &Block,
+) -> Result<(), Error> {
+    let block_hash = block.header().hash();
+    let mut pending = Vec::new();
+    let mut parent_hash = block.header().parent_hash().to_owned();
+    let mut previous_filter_header = if block.header().number() == 0 {
+        H256::zero()
+    } else {
+        loop {
+            if let Some(filter_header) = store.get_block_filter_header(&parent_hash) {
+                break filter_header;
+            }
+            if pending.len() == MAX_FILTER_BACKFILL || store.is_block_unfiltered(&parent_hash) {
+                return batch.insert_unfiltered_block(&block_hash);
+            }
+            let parent = match store.get_header(&parent_hash) {
+                Some(parent) => parent,
+                None => return batch.insert_unfiltered_block(&block_hash),
+            };
+            pending.push(parent_hash);
+            parent_hash = parent.parent_hash().to_owned();
+            if parent.number() == 0 {
+                break H256::zero();
+            }
+        }
+    };
+
+    // The ancestors are loaded one at a time, the pruned ones are the deepest
+    for hash in pending.iter().rev() {
+        let ancestor = match store.get_block(hash) {
+            Some(ancestor) => ancestor,
+            None => return batch.insert_unfiltered_block(&block_hash),
+        };
+        previous_filter_header = insert_filter(store, batch, &ancestor, &previous_filter_header)?;
+    }
+    insert_filter(store, batch, block, &previous_filter_header)?;
+    Ok(())
+}
+
+fn insert_filter<CS: ChainStore>(
+    store: &CS,
+    batch: &mut StoreBatch,
+    block: &Block,
+    previous_filter_header: &H256,
+) -> Result<H256, Error> {
+    let hash = block.header().hash();
+    let filter = build_filter(&hash, &block_filter_items(store, block));
+    let header = filter_header(&filter_hash(&filter), previous_filter_header);
+    batch.insert_block_filter(&hash, &filter, &header)?;
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChainKVStore, COLUMNS};
+    use ckb_chain_spec::consensus::Consensus;
+    use ckb_core::block::BlockBuilder;
+    use ckb_core::header::HeaderBuilder;
+    use ckb_core::script::Script;
+    use ckb_core::transaction::{CellInput, OutPoint, TransactionBuilder};
+    use ckb_core::{capacity_bytes, Bytes, Capacity};
+    use ckb_db::memorydb::MemoryKeyValueDB;
+
+    #[test]
+    fn test_insert_block_filter() {
+        let genesis_lock = Script::always_success();
+        let genesis_tx = TransactionBuilder::default()
+            .output(CellOutput::new(
+                capacity_bytes!(100),
+                Bytes::default(),
+                genesis_lock.clone(),
+                None,
+            ))
+            .build();
+        let genesis = BlockBuilder::default()
+            .transaction(genesis_tx.clone())
+            .build();
+        let consensus = Consensus::default().set_genesis_block(genesis.clone());
+        let store = ChainKVStore::new(MemoryKeyValueDB::open(COLUMNS as usize));
+        store.init(&consensus).unwrap();
+
+        // the genesis filter is built along with the store
+        let genesis_hash = genesis.header().hash();
+        let genesis_filter = store.get_block_filter(&genesis_hash).unwrap();
+        let genesis_filter_header = store.get_block_filter_header(&genesis_hash).unwrap();
+        assert_eq!(
+            block_filter_items(&store, &genesis),
+            vec![genesis_lock.hash()]
+        );
+        assert_eq!(
+            genesis_filter,
+            build_filter(&genesis_hash, &[genesis_lock.hash()])
+        );
+        assert_eq!(
+            genesis_filter_header,
+            filter_header(&filter_hash(&genesis_filter), &H256::zero())
+        );
+        assert!(match_any(
+            &genesis_hash,
+            &genesis_filter,
+            &[genesis_lock.hash()]
+        ));
+
+        // the block spends the genesis cell and a cell created by itself
+        let lock = Script::new(vec![Bytes::from(vec![1])], H256::zero());
+        let tx1 = TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(genesis_tx.hash().to_owned(), 0),
+                0,
+                vec![],
+            ))
+            .output(CellOutput::new(
+                capacity_bytes!(100),
+                Bytes::default(),
+                lock.clone(),
+                None,
+            ))
+            .build();
+        let tx2 = TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(tx1.hash().to_owned(), 0),
+                0,
+                vec![],
+            ))
+            .output(CellOutput::new(
+                capacity_bytes!(100),
+                Bytes::default(),
+                genesis_lock.clone(),
+                None,
+            ))
+            .build();
+        let block = BlockBuilder::default()
+            .header_builder(
+                HeaderBuilder::default()
+                    .number(1)
+                    .parent_hash(genesis_hash.to_owned()),
+            )
+            .transaction(TransactionBuilder::default().build())
+            .transaction(tx1)
+            .transaction(tx2)
+            .build();
+        assert_eq!(
+            block_filter_items(&store, &block),
+            vec![
+                lock.hash(),
+                genesis_lock.hash(),
+                genesis_lock.hash(),
+                lock.hash()
+            ]
+        );
+
+        let mut batch = store.new_batch().unwrap();
+        insert_block_filter(&store, &mut batch, &block).unwrap();
+        batch.commit().unwrap();
+        let block_hash = block.header().hash();
+        let filter = store.get_block_filter(&block_hash).unwrap();
+        assert_eq!(
+            store.get_block_filter_header(&block_hash),
+            Some(filter_header(&filter_hash(&filter), &genesis_filter_header))
+        );
+        assert!(match_any(&block_hash, &filter, &[lock.hash()]));
+
+        // the block missing an ancestor is recorded as unfiltered, and so is its child
+        let orphan = BlockBuilder::default()
+            .header_builder(HeaderBuilder::default().number(2).parent_hash(H256::zero()))
+            .build();
+        let child = BlockBuilder::default()
+            .header_builder(
+                HeaderBuilder::default()
+                    .number(3)
+                    .parent_hash(orphan.header().hash().to_owned()),
+            )
+            .build();
+        for block in &[&orphan, &child] {
+            let mut batch = store.new_batch().unwrap();
+            insert_block_filter(&store, &mut batch, block).unwrap();
+            batch.commit().unwrap();
+            let block_hash = block.header().hash();
+            assert!(store.is_block_unfiltered(&block_hash));
+            assert_eq!(store.get_block_filter(&block_hash), None);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{COLUMN_BLOCK_BODY, COLUMN_BLOCK_HEADER, COLUMN_CELL_META};
+use ckb_db::cachedb::CacheCols;
+use serde_derive::{Deserialize, Serialize};
+
+/// Numbers of the entries of the LRU caches of the store, 0 disables a cache
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct StoreConfig {
+    pub header_cache_size: usize,
+    /// The cached block bodies also serve their transactions
+    pub block_cache_size: usize,
+    /// The metas of the cells, without their outputs, read to resolve the transaction inputs
+    pub cell_meta_cache_size: usize,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig {
+            header_cache_size: 4096,
+            block_cache_size: 128,
+            cell_meta_cache_size: 65536,
+        }
+    }
+}
+
+impl StoreConfig {
+    /// The capacities of the caches of the columns
+    pub fn cache_cols(&self) -> Vec<CacheCols> {
+        vec![
+            (COLUMN_BLOCK_HEADER, self.header_cache_size),
+            (COLUMN_BLOCK_BODY, self.block_cache_size),
+            (COLUMN_CELL_META, self.cell_meta_cache_size),
+        ]
+    }
+}
@@ -0,0 +1,360 @@
+use ckb_core::header::BlockNumber;
+use ckb_db::Error;
+use ckb_util::RwLock;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+// Each index entry is the end offset of the data of a block in the data file
+const INDEX_ENTRY_SIZE: u64 = 8;
+
+/// The data of a frozen block, each kind is kept in its own table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreezerTable {
+    Hash,
+    Body,
+    Addresses,
+    Uncles,
+    Proposals,
+}
+
+const TABLES: [FreezerTable; 5] = [
+    FreezerTable::Hash,
+    FreezerTable::Body,
+    FreezerTable::Addresses,
+    FreezerTable::Uncles,
+    FreezerTable::Proposals,
+];
+
+impl FreezerTable {
+    fn name(self) -> &'static str {
+        match self {
+            FreezerTable::Hash => "hashes",
+            FreezerTable::Body => "bodies",
+            FreezerTable::Addresses => "addresses",
+            FreezerTable::Uncles => "uncles",
+            FreezerTable::Proposals => "proposals",
+        }
+    }
+
+    fn position(self) -> usize {
+        TABLES
+            .iter()
+            .position(|table| *table == self)
+            .expect("known table")
+    }
+}
+
+/// Append-only flat files holding the data of the ancient main chain blocks,
+/// the block numbered `n` is the `n`th entry of each table.
+///
+/// The entries are read at their offset, the reads only wait for the appends.
+pub struct Freezer {
+    path: PathBuf,
+    files: RwLock<FreezerFiles>,
+    read_only: bool,
+}
+
+struct FreezerFiles {
+    tables: Vec<TableFiles>,
+    // number of the frozen blocks
+    number: BlockNumber,
+}
+
+struct TableFiles {
+    data: File,
+    index: File,
+    data_size: u64,
+}
+
+impl Freezer {
+    /// Opens the freezer in the directory, discarding the entries left
+    /// incomplete by an interrupted append
+    pub fn open(path: &Path) -> Result<Freezer, Error> {
+        fs::create_dir_all(path).map_err(io_error)?;
+        Self::open_with(path, false)
+    }
+
+    /// Opens the freezer without ever writing to its files, the entries left
+    /// incomplete are ignored rather than discarded
+    pub fn open_read_only(path: &Path) -> Result<Freezer, Error> {
+        Self::open_with(path, true)
+    }
+
+    fn open_with(path: &Path, read_only: bool) -> Result<Freezer, Error> {
+        let open = |name: String| {
+            OpenOptions::new()
+                .read(true)
+                .write(!read_only)
+                .create(!read_only)
+                .open(path.join(name))
+                .map_err(io_error)
+        };
+
+        let mut tables = Vec::with_capacity(TABLES.len());
+        let mut number = BlockNumber::max_value();
+        for table in TABLES.iter() {
+            let data = open(format!("{}.dat", table.name()))?;
+            let index = open(format!("{}.idx", table.name()))?;
+            let data_len = data.metadata().map_err(io_error)?.len();
+            let mut complete = index.metadata().map_err(io_error)?.len() / INDEX_ENTRY_SIZE;
+            while complete > 0 && read_index_entry(&index, complete - 1)? > data_len {
+                complete -= 1;
+            }
+            number = number.min(complete);
+            tables.push(TableFiles {
+                data,
+                index,
+                data_size: 0,
+            });
+        }
+
+        // a block is frozen once all its entries are written
+        for table in tables.iter_mut() {
+            table.data_size = if number == 0 {
+                0
+            } else {
+                read_index_entry(&table.index, number - 1)?
+            };
+            if !read_only {
+                table
+                    .index
+                    .set_len(number * INDEX_ENTRY_SIZE)
+                    .map_err(io_error)?;
+                table.data.set_len(table.data_size).map_err(io_error)?;
+            }
+        }
+
+        Ok(Freezer {
+            path: path.to_path_buf(),
+            files: RwLock::new(FreezerFiles { tables, number }),
+            read_only,
+        })
+    }
+
+    /// The number of the frozen blocks, which is also the number of the next block to freeze
+    pub fn number(&self) -> BlockNumber {
+        self.files.read().number
+    }
+
+    /// Appends the entries of the block numbered `number()`, in the order of the tables
+    pub fn append(&self, entries: &[&[u8]; 5]) -> Result<(), Error> {
+        if self.read_only {
+            Err(Error::DBError("freezer opened read-only".to_owned()))?;
+        }
+        let mut files = self.files.write();
+        for (table, entry) in files.tables.iter_mut().zip(entries.iter()) {
+            let end = table.data_size + entry.len() as u64;
+            table.data.seek(SeekFrom::End(0)).map_err(io_error)?;
+            table.data.write_all(entry).map_err(io_error)?;
+            table.index.seek(SeekFrom::End(0)).map_err(io_error)?;
+            table
+                .index
+                .write_all(&end.to_le_bytes())
+                .map_err(io_error)?;
+            table.data_size = end;
+        }
+        files.number += 1;
+        Ok(())
+    }
+
+    /// Flushes the appended blocks to the disk
+    pub fn sync(&self) -> Result<(), Error> {
+        let files = self.files.read();
+        for table in &files.tables {
+            table.data.sync_all().map_err(io_error)?;
+            table.index.sync_all().map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    /// Copies the files into the directory, blocking the appends meanwhile
+    pub fn copy_to(&self, path: &Path) -> Result<(), Error> {
+        let _files = self.files.read();
+        fs::create_dir_all(path).map_err(io_error)?;
+        for table in TABLES.iter() {
+            for name in &[
+                format!("{}.dat", table.name()),
+                format!("{}.idx", table.name()),
+            ] {
+                fs::copy(self.path.join(name), path.join(name)).map_err(io_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The entry of the frozen block in the table
+    pub fn retrieve(
+        &self,
+        table: FreezerTable,
+        number: BlockNumber,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.retrieve_with(table, number, |len| Some(0..len))
+    }
+
+    /// The `range` of the entry of the frozen block in the table, None when
+    /// the entry is shorter
+    pub fn partial_retrieve(
+        &self,
+        table: FreezerTable,
+        number: BlockNumber,
+        range: &Range<usize>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.retrieve_with(table, number, |len| {
+            if range.start <= range.end && range.end <= len {
+                Some(range.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn retrieve_with<F>(
+        &self,
+        table: FreezerTable,
+        number: BlockNumber,
+        range: F,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        F: FnOnce(usize) -> Option<Range<usize>>,
+    {
+        let files = self.files.read();
+        if number >= files.number {
+            return Ok(None);
+        }
+        let table = &files.tables[table.position()];
+        let start = if number == 0 {
+            0
+        } else {
+            read_index_entry(&table.index, number - 1)?
+        };
+        let end = read_index_entry(&table.index, number)?;
+        let range = match range((end - start) as usize) {
+            Some(range) => range,
+            None => return Ok(None),
+        };
+        let mut data = vec![0; range.end - range.start];
+        read_exact_at(&table.data, &mut data, start + range.start as u64)?;
+        Ok(Some(data))
+    }
+}
+
+fn read_index_entry(index: &File, number: BlockNumber) -> Result<u64, Error> {
+    let mut entry = [0u8; INDEX_ENTRY_SIZE as usize];
+    read_exact_at(index, &mut entry, number * INDEX_ENTRY_SIZE)?;
+    Ok(u64::from_le_bytes(entry))
+}
+
+// Reads at the offset without moving the cursor shared by the readers
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> Result<(), Error> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset).map_err(io_error)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> Result<(), Error> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(io_error(io::ErrorKind::UnexpectedEof.into())),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(io_error(err)),
+        }
+    }
+    Ok(())
+}
+
+fn io_error(err: io::Error) -> Error {
+    Error::DBError(format!("freezer {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(freezer: &Freezer, entry: &[u8]) {
+        freezer
+            .append(&[entry, entry, entry, entry, entry])
+            .unwrap();
+    }
+
+    #[test]
+    fn append_and_retrieve() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("freezer_append_and_retrieve")
+            .tempdir()
+            .unwrap();
+        let freezer = Freezer::open(tmp_dir.path()).unwrap();
+        assert_eq!(freezer.number(), 0);
+        append(&freezer, &[1, 2, 3]);
+        append(&freezer, &[]);
+        freezer.append(&[&[4], &[5], &[6, 7], &[], &[8]]).unwrap();
+        freezer.sync().unwrap();
+        assert_eq!(freezer.number(), 3);
+        assert_eq!(
+            freezer.retrieve(FreezerTable::Hash, 0).unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            freezer.retrieve(FreezerTable::Body, 1).unwrap(),
+            Some(vec![])
+        );
+        assert_eq!(
+            freezer.retrieve(FreezerTable::Addresses, 2).unwrap(),
+            Some(vec![6, 7])
+        );
+        assert_eq!(
+            freezer.retrieve(FreezerTable::Uncles, 2).unwrap(),
+            Some(vec![])
+        );
+        assert_eq!(freezer.retrieve(FreezerTable::Body, 3).unwrap(), None);
+        assert_eq!(
+            freezer
+                .partial_retrieve(FreezerTable::Body, 0, &(1..3))
+                .unwrap(),
+            Some(vec![2, 3])
+        );
+        assert_eq!(
+            freezer
+                .partial_retrieve(FreezerTable::Body, 0, &(2..4))
+                .unwrap(),
+            None
+        );
+        drop(freezer);
+
+        // the block whose entries were not all written is discarded
+        let data = OpenOptions::new()
+            .write(true)
+            .open(tmp_dir.path().join("proposals.dat"))
+            .unwrap();
+        data.set_len(3).unwrap();
+        let freezer = Freezer::open_read_only(tmp_dir.path()).unwrap();
+        assert_eq!(freezer.number(), 2);
+        assert!(freezer.append(&[&[], &[], &[], &[], &[]]).is_err());
+        drop(freezer);
+        assert_eq!(
+            fs::metadata(tmp_dir.path().join("proposals.idx"))
+                .unwrap()
+                .len(),
+            3 * INDEX_ENTRY_SIZE
+        );
+
+        let freezer = Freezer::open(tmp_dir.path()).unwrap();
+        assert_eq!(freezer.number(), 2);
+        assert_eq!(
+            freezer.retrieve(FreezerTable::Body, 1).unwrap(),
+            Some(vec![])
+        );
+        append(&freezer, &[9]);
+        assert_eq!(
+            freezer.retrieve(FreezerTable::Proposals, 2).unwrap(),
+            Some(vec![9])
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use crate::store::ChainStore;
+use ckb_core::block::Block;
+use ckb_core::header::{BlockNumber, Header};
+
+/// Iterates the main chain blocks in ascending number, up to the tip or the
+/// first block whose body is pruned
+pub struct BlockIter<'a, S: ?Sized> {
+    store: &'a S,
+    number: BlockNumber,
+}
+
+impl<'a, S: ?Sized> BlockIter<'a, S> {
+    pub(crate) fn new(store: &'a S, from: BlockNumber) -> Self {
+        BlockIter {
+            store,
+            number: from,
+        }
+    }
+}
+
+impl<'a, S: ChainStore + ?Sized> Iterator for BlockIter<'a, S> {
+    type Item = Block;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = self
+            .store
+            .get_block_hash(self.number)
+            .and_then(|hash| self.store.get_block(&hash))?;
+        self.number += 1;
+        Some(block)
+    }
+}
+
+/// Iterates the main chain headers in ascending number, up to the tip
+pub struct HeaderIter<'a, S: ?Sized> {
+    store: &'a S,
+    number: BlockNumber,
+}
+
+impl<'a, S: ?Sized> HeaderIter<'a, S> {
+    pub(crate) fn new(store: &'a S, from: BlockNumber) -> Self {
+        HeaderIter {
+            store,
+            number: from,
+        }
+    }
+}
+
+impl<'a, S: ChainStore + ?Sized> Iterator for HeaderIter<'a, S> {
+    type Item = Header;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = self
+            .store
+            .get_block_hash(self.number)
+            .and_then(|hash| self.store.get_header(&hash))?;
+        self.number += 1;
+        Some(header)
+    }
+}
@@ -1,11 +1,25 @@
+mod block_filter;
+mod config;
 mod flat_serializer;
+mod freezer;
+mod iter;
+mod mmr;
 mod store;
 
-pub use store::{ChainKVStore, ChainStore, StoreBatch};
+pub use block_filter::{
+    block_filter_items, build_filter, filter_hash, filter_header, insert_block_filter, match_any,
+};
+pub use config::StoreConfig;
+pub use freezer::{Freezer, FreezerTable};
+pub use iter::{BlockIter, HeaderIter};
+pub use mmr::HeaderProof;
+pub use store::{
+    ChainKVStore, ChainStore, StoreBatch, StoreCacheStats, BACKUP_DB_DIR, BACKUP_FREEZER_DIR,
+};
 
 use ckb_db::Col;
 
-pub const COLUMNS: u32 = 12;
+pub const COLUMNS: u32 = 19;
 pub const COLUMN_INDEX: Col = 0;
 pub const COLUMN_BLOCK_HEADER: Col = 1;
 pub const COLUMN_BLOCK_BODY: Col = 2;
@@ -18,3 +32,10 @@ pub const COLUMN_BLOCK_PROPOSAL_IDS: Col = 8;
 pub const COLUMN_CELL_META: Col = 9;
 pub const COLUMN_BLOCK_EPOCH: Col = 10;
 pub const COLUMN_EPOCH: Col = 11;
+pub const COLUMN_VERSIONBITS: Col = 12;
+pub const COLUMN_BLOCK_FILTER: Col = 13;
+pub const COLUMN_BLOCK_FILTER_HEADER: Col = 14;
+pub const COLUMN_PRUNED_CELL_OUTPUT: Col = 15;
+pub const COLUMN_CELL_SET: Col = 16;
+pub const COLUMN_HEADER_MMR: Col = 17;
+pub const COLUMN_UNFILTERED_BLOCK: Col = 18;
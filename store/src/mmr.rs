@@ -0,0 +1,239 @@
+//! Merkle Mountain Range over the headers of the main chain, the leaf numbered
+//! `n` is the hash of the block numbered `n`.
+//!
+//! The nodes are numbered in the order they are appended, a parent being
+//! appended right after its right child:
+//!
+//! ```text
+//!        6
+//!      /   \
+//!     2     5     9
+//!    / \   / \   / \
+//!   0   1 3   4 7   8 10
+//! ```
+use ckb_core::header::BlockNumber;
+use ckb_merkle_tree::MergeH256;
+use merkle_cbt::merkle_tree::Merge;
+use numext_fixed_hash::H256;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Proof that a header is the leaf numbered `number` of the MMR of `leaves`
+/// headers, so a client holding only the root of the MMR can check the
+/// ancestry of the header.
+#[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct HeaderProof {
+    number: BlockNumber,
+    leaves: u64,
+    /// Siblings on the path from the leaf to its peak, bottom up
+    path: Vec<H256>,
+    /// The peaks other than the one of the leaf, left to right
+    peaks: Vec<H256>,
+}
+
+impl HeaderProof {
+    pub fn new(number: BlockNumber, leaves: u64, path: Vec<H256>, peaks: Vec<H256>) -> Self {
+        HeaderProof {
+            number,
+            leaves,
+            path,
+            peaks,
+        }
+    }
+
+    pub fn number(&self) -> BlockNumber {
+        self.number
+    }
+
+    pub fn leaves(&self) -> u64 {
+        self.leaves
+    }
+
+    pub fn path(&self) -> &[H256] {
+        &self.path
+    }
+
+    pub fn peaks(&self) -> &[H256] {
+        &self.peaks
+    }
+
+    /// Returns true if `header_hash` is the leaf numbered `self.number()` of
+    /// the MMR whose root is `root`.
+    pub fn verify(&self, root: &H256, header_hash: &H256) -> bool {
+        if self.number >= self.leaves {
+            return false;
+        }
+        let peak_positions = peak_positions(self.leaves);
+        let mut pos = leaf_position(self.number);
+        let mut hash = header_hash.to_owned();
+        for sibling in &self.path {
+            if peak_positions.contains(&pos) {
+                return false;
+            }
+            let height = pos_height(pos);
+            if pos_height(pos + 1) > height {
+                hash = MergeH256::merge(sibling, &hash);
+                pos += 1;
+            } else {
+                hash = MergeH256::merge(&hash, sibling);
+                pos += 2 << height;
+            }
+        }
+        match peak_positions.iter().position(|peak| *peak == pos) {
+            Some(index) if self.peaks.len() + 1 == peak_positions.len() => {
+                let mut peaks = self.peaks.clone();
+                peaks.insert(index, hash);
+                bag_peaks(&peaks) == *root
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The position of the leaf numbered `number`, which is also the number of
+/// the nodes of the MMR of `number` leaves
+pub(crate) fn leaf_position(number: u64) -> u64 {
+    2 * number - u64::from(number.count_ones())
+}
+
+/// The positions of the peaks of the MMR of `leaves` leaves, left to right
+pub(crate) fn peak_positions(leaves: u64) -> Vec<u64> {
+    let mut peaks = Vec::new();
+    let mut offset = 0;
+    for height in (0..64).rev() {
+        if leaves & (1 << height) != 0 {
+            // a perfect tree of 2^height leaves has 2^(height+1)-1 nodes
+            let size = (2u64 << height) - 1;
+            peaks.push(offset + size - 1);
+            offset += size;
+        }
+    }
+    peaks
+}
+
+/// Walks the path from the leaf numbered `number` up to its peak in the MMR of
+/// `leaves` leaves, returning the positions of the siblings and of the peak
+pub(crate) fn proof_positions(number: u64, leaves: u64) -> (Vec<u64>, u64) {
+    let peak_positions = peak_positions(leaves);
+    let mut pos = leaf_position(number);
+    let mut path = Vec::new();
+    while !peak_positions.contains(&pos) {
+        let height = pos_height(pos);
+        if pos_height(pos + 1) > height {
+            path.push(pos - ((2 << height) - 1));
+            pos += 1;
+        } else {
+            path.push(pos + ((2 << height) - 1));
+            pos += 2 << height;
+        }
+    }
+    (path, pos)
+}
+
+/// Appends the leaves numbered from `number`, returning the added nodes. The
+/// nodes of the MMR of `number` leaves are read by `get_node`.
+pub(crate) fn append_leaves<F>(number: u64, leaves: &[H256], get_node: F) -> Vec<(u64, H256)>
+where
+    F: Fn(u64) -> Option<H256>,
+{
+    let mut added = HashMap::new();
+    let mut nodes = Vec::new();
+    for (offset, leaf) in leaves.iter().enumerate() {
+        let mut pos = leaf_position(number + offset as u64);
+        let mut hash = leaf.to_owned();
+        let mut height = 0;
+        added.insert(pos, hash.clone());
+        nodes.push((pos, hash.clone()));
+        while pos_height(pos + 1) > height {
+            let left_pos = pos + 1 - (2 << height);
+            let left = added
+                .get(&left_pos)
+                .cloned()
+                .or_else(|| get_node(left_pos))
+                .expect("mmr node stored");
+            hash = MergeH256::merge(&left, &hash);
+            pos += 1;
+            height += 1;
+            added.insert(pos, hash.clone());
+            nodes.push((pos, hash.clone()));
+        }
+    }
+    nodes
+}
+
+/// Folds the peaks, left to right, into the root
+pub(crate) fn bag_peaks(peaks: &[H256]) -> H256 {
+    let mut iter = peaks.iter().rev();
+    let last = iter.next().cloned().unwrap_or_else(H256::zero);
+    iter.fold(last, |root, peak| MergeH256::merge(peak, &root))
+}
+
+fn pos_height(pos: u64) -> u32 {
+    let mut pos = pos + 1;
+    // the positions, counted from 1, of the nodes on the left edge of a
+    // perfect tree are all ones
+    while !all_ones(pos) {
+        pos -= (1 << (63 - pos.leading_zeros())) - 1;
+    }
+    63 - pos.leading_zeros()
+}
+
+fn all_ones(n: u64) -> bool {
+    n != 0 && n.count_zeros() == n.leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let heights: Vec<_> = (0..11).map(pos_height).collect();
+        assert_eq!(heights, vec![0, 0, 1, 0, 0, 1, 2, 0, 0, 1, 0]);
+        let leaves: Vec<_> = (0..7).map(leaf_position).collect();
+        assert_eq!(leaves, vec![0, 1, 3, 4, 7, 8, 10]);
+        assert_eq!(peak_positions(7), vec![6, 9, 10]);
+        assert_eq!(proof_positions(2, 7), (vec![4, 2], 6));
+        assert_eq!(proof_positions(6, 7), (vec![], 10));
+    }
+
+    #[test]
+    fn append_and_verify() {
+        let leaves: Vec<H256> = (1..=7u64)
+            .map(|n| H256::from_trimmed_hex_str(&format!("{:x}", n)).unwrap())
+            .collect();
+        let mut nodes = HashMap::new();
+        for (number, leaf) in leaves.iter().enumerate() {
+            let added = append_leaves(number as u64, &[leaf.clone()], |pos| {
+                nodes.get(&pos).cloned()
+            });
+            nodes.extend(added);
+        }
+        // appending at once adds the same nodes
+        let at_once: HashMap<_, _> = append_leaves(0, &leaves, |_| None).into_iter().collect();
+        assert_eq!(at_once, nodes);
+        assert_eq!(nodes.len(), 11);
+
+        let root = bag_peaks(
+            &peak_positions(7)
+                .iter()
+                .map(|pos| nodes[pos].clone())
+                .collect::<Vec<_>>(),
+        );
+        for number in 0..7 {
+            let (path, peak) = proof_positions(number, 7);
+            let proof = HeaderProof::new(
+                number,
+                7,
+                path.iter().map(|pos| nodes[pos].clone()).collect(),
+                peak_positions(7)
+                    .into_iter()
+                    .filter(|pos| *pos != peak)
+                    .map(|pos| nodes[&pos].clone())
+                    .collect(),
+            );
+            assert!(proof.verify(&root, &leaves[number as usize]));
+            assert!(!proof.verify(&root, &leaves[(number as usize + 1) % 7]));
+        }
+    }
+}
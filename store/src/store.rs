@@ -1,11 +1,18 @@
+use crate::block_filter::insert_block_filter;
 use crate::flat_serializer::{serialize as flat_serialize, serialized_addresses, Address};
+use crate::freezer::{Freezer, FreezerTable};
+use crate::iter::{BlockIter, HeaderIter};
+use crate::mmr::{self, HeaderProof};
 use crate::{
-    COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS,
-    COLUMN_BLOCK_TRANSACTION_ADDRESSES, COLUMN_BLOCK_UNCLE, COLUMN_CELL_META, COLUMN_EPOCH,
-    COLUMN_EXT, COLUMN_INDEX, COLUMN_META, COLUMN_TRANSACTION_ADDR,
+    COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_FILTER, COLUMN_BLOCK_FILTER_HEADER,
+    COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS, COLUMN_BLOCK_TRANSACTION_ADDRESSES,
+    COLUMN_BLOCK_UNCLE, COLUMN_CELL_META, COLUMN_CELL_SET, COLUMN_EPOCH, COLUMN_EXT,
+    COLUMN_HEADER_MMR, COLUMN_INDEX, COLUMN_META, COLUMN_PRUNED_CELL_OUTPUT,
+    COLUMN_TRANSACTION_ADDR, COLUMN_UNFILTERED_BLOCK, COLUMN_VERSIONBITS,
 };
 use bincode::{deserialize, serialize};
 use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::versionbits::ThresholdState;
 use ckb_core::block::{Block, BlockBuilder};
 use ckb_core::cell::CellMeta;
 use ckb_core::extras::{BlockExt, EpochExt, TransactionAddress};
@@ -13,14 +20,23 @@ use ckb_core::header::{BlockNumber, Header};
 use ckb_core::transaction::{
     CellOutPoint, CellOutput, ProposalShortId, Transaction, TransactionBuilder,
 };
+use ckb_core::transaction_meta::TransactionMeta;
 use ckb_core::uncle::UncleBlock;
-use ckb_db::{Col, DbBatch, Error, KeyValueDB};
+use ckb_db::{CacheStats, Col, DbBatch, Direction, Error, IterableKeyValueDB};
 use numext_fixed_hash::H256;
 use serde::Serialize;
+use std::fs;
 use std::ops::Range;
+use std::path::Path;
 
 const META_TIP_HEADER_KEY: &[u8] = b"TIP_HEADER";
 const META_CURRENT_EPOCH_KEY: &[u8] = b"CURRENT_EPOCH";
+const META_PRUNED_NUMBER_KEY: &[u8] = b"PRUNED_NUMBER";
+
+/// The directory of a backup holding the copy of the db
+pub const BACKUP_DB_DIR: &str = "db";
+/// The directory of a backup holding the copy of the freezer
+pub const BACKUP_FREEZER_DIR: &str = "freezer";
 
 fn cell_store_key(tx_hash: &H256, index: u32) -> Vec<u8> {
     let mut key: [u8; 36] = [0; 36];
@@ -31,11 +47,53 @@ fn cell_store_key(tx_hash: &H256, index: u32) -> Vec<u8> {
 
 pub struct ChainKVStore<T> {
     db: T,
+    freezer: Option<Freezer>,
 }
 
-impl<T: KeyValueDB> ChainKVStore<T> {
+impl<T: IterableKeyValueDB> ChainKVStore<T> {
     pub fn new(db: T) -> Self {
-        ChainKVStore { db }
+        ChainKVStore { db, freezer: None }
+    }
+
+    /// Moves the data of the ancient blocks into the freezer on `freeze`
+    pub fn with_freezer(mut self, freezer: Freezer) -> Self {
+        self.freezer = Some(freezer);
+        self
+    }
+
+    // Reads the block data stored in the column, falling back to the freezer
+    fn get_block_data(&self, col: Col, hash: &H256) -> Option<Vec<u8>> {
+        self.get(col, hash.as_bytes()).or_else(|| {
+            let table = match col {
+                COLUMN_BLOCK_BODY => FreezerTable::Body,
+                COLUMN_BLOCK_TRANSACTION_ADDRESSES => FreezerTable::Addresses,
+                COLUMN_BLOCK_UNCLE => FreezerTable::Uncles,
+                COLUMN_BLOCK_PROPOSAL_IDS => FreezerTable::Proposals,
+                _ => return None,
+            };
+            let (freezer, number) = self.frozen_block_number(hash)?;
+            freezer
+                .retrieve(table, number)
+                .expect("freezer operation should be ok")
+                // the data of a block pruned before being frozen is empty
+                .filter(|data| !data.is_empty())
+        })
+    }
+
+    // The number of the block in the freezer, when it is a frozen main chain block
+    fn frozen_block_number(&self, hash: &H256) -> Option<(&Freezer, BlockNumber)> {
+        let freezer = self.freezer.as_ref()?;
+        let number = self.get_block_number(hash)?;
+        freezer
+            .retrieve(FreezerTable::Hash, number)
+            .expect("freezer operation should be ok")
+            .filter(|frozen_hash| frozen_hash.as_slice() == hash.as_bytes())
+            .map(|_| (freezer, number))
+    }
+
+    fn get_header_mmr_node(&self, pos: u64) -> Option<H256> {
+        self.get(COLUMN_HEADER_MMR, &pos.to_le_bytes())
+            .map(|raw| H256::from_slice(&raw[..]).expect("db safe access"))
     }
 
     pub fn get(&self, col: Col, key: &[u8]) -> Option<Vec<u8>> {
@@ -55,6 +113,17 @@ pub trait ChainStore: Sync + Send {
     type Batch: StoreBatch;
     /// New a store batch handle
     fn new_batch(&self) -> Result<Self::Batch, Error>;
+    /// Build a batch by `f` and commit it, nothing is written if `f` fails
+    fn write<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Self::Batch) -> Result<T, E>,
+        E: From<Error>,
+    {
+        let mut batch = self.new_batch()?;
+        let ret = f(&mut batch)?;
+        batch.commit()?;
+        Ok(ret)
+    }
 
     /// Get block by block header hash
     fn get_block(&self, block_hash: &H256) -> Option<Block>;
@@ -78,14 +147,73 @@ pub trait ChainStore: Sync + Send {
     fn get_tip_header(&self) -> Option<Header>;
     /// Get commit transaction and block hash by it's hash
     fn get_transaction(&self, h: &H256) -> Option<(Transaction, H256)>;
-    /// Get commit transaction address by it's hash
+    /// Get the location of the main chain transaction, which outlives the pruning of its block
     fn get_transaction_address(&self, hash: &H256) -> Option<TransactionAddress>;
     fn get_cell_meta(&self, tx_hash: &H256, index: u32) -> Option<CellMeta>;
     fn get_cell_output(&self, tx_hash: &H256, index: u32) -> Option<CellOutput>;
     fn get_current_epoch_ext(&self) -> Option<EpochExt>;
     fn get_epoch_ext(&self, hash: &H256) -> Option<EpochExt>;
+    /// Deployment states of the epoch stored under `epoch_hash`, in the order
+    /// of `Consensus::deployments`
+    fn get_versionbits_states(&self, epoch_hash: &H256) -> Option<Vec<ThresholdState>>;
+    /// Get the compact filter of the block
+    fn get_block_filter(&self, block_hash: &H256) -> Option<Vec<u8>>;
+    /// Get the filter header of the block, which commits to the filters of all its ancestors
+    fn get_block_filter_header(&self, block_hash: &H256) -> Option<H256>;
+    /// Whether the block is left without a filter since one of its ancestors cannot be filtered
+    fn is_block_unfiltered(&self, block_hash: &H256) -> bool;
+    /// Get the number of the highest main chain block whose body is pruned
+    fn get_pruned_number(&self) -> Option<BlockNumber>;
+    /// Whether the header of the block is stored but its body was pruned
+    fn is_block_pruned(&self, block_hash: &H256) -> bool;
+    /// Get the liveness of the cells of the main chain transaction
+    fn get_tx_meta(&self, tx_hash: &H256) -> Option<TransactionMeta>;
+    /// Get the cell set of the main chain tip, the transactions with the liveness of their cells
+    fn get_cell_set(&self) -> Vec<(H256, TransactionMeta)>;
+    /// Get the meta of the cell if it is live in the main chain
+    fn get_live_cell_meta(&self, tx_hash: &H256, index: u32) -> Option<CellMeta>;
+    /// Get the number of the blocks moved into the freezer, None without a freezer
+    fn get_frozen_number(&self) -> Option<BlockNumber>;
+    /// Move the transactions, uncles and proposals of the main chain blocks
+    /// below `number` into the freezer
+    fn freeze(&self, number: BlockNumber) -> Result<(), Error>;
+    /// Copies a point-in-time snapshot of the store into the directory, which
+    /// must not exist, while it keeps serving
+    fn backup(&self, path: &Path) -> Result<(), Error>;
+    /// Get the usage of the caches of the store
+    fn get_cache_stats(&self) -> StoreCacheStats;
+    /// Get the root of the MMR over the headers of the main chain blocks up to
+    /// the number
+    fn get_header_mmr_root(&self, number: BlockNumber) -> Option<H256>;
+    /// Get the proof that the main chain block numbered `number` is an
+    /// ancestor of the one numbered `tip_number`, checked against the MMR root
+    /// at `tip_number`
+    fn get_header_proof(&self, number: BlockNumber, tip_number: BlockNumber)
+        -> Option<HeaderProof>;
+    /// Compute the MMR nodes added by attaching the headers, which are ordered
+    /// by number and extend a main chain block
+    fn header_mmr_nodes(&self, headers: &[Header]) -> Vec<(u64, H256)>;
+
+    /// Iterate the main chain blocks from the number
+    fn iter_blocks(&self, from: BlockNumber) -> BlockIter<'_, Self> {
+        BlockIter::new(self, from)
+    }
+    /// Iterate the main chain headers from the number
+    fn iter_headers(&self, from: BlockNumber) -> HeaderIter<'_, Self> {
+        HeaderIter::new(self, from)
+    }
 }
 
+/// Usage of the caches configured by `StoreConfig`, None for a disabled cache
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoreCacheStats {
+    pub header: Option<CacheStats>,
+    pub block: Option<CacheStats>,
+    pub cell_meta: Option<CacheStats>,
+}
+
+/// Writes to the columns of the store, applied atomically by `commit`. A batch
+/// dropped without committing writes nothing.
 pub trait StoreBatch {
     fn insert_block(&mut self, block: &Block) -> Result<(), Error>;
     fn insert_block_ext(&mut self, block_hash: &H256, ext: &BlockExt) -> Result<(), Error>;
@@ -97,14 +225,39 @@ pub trait StoreBatch {
         epoch_hash: &H256,
     ) -> Result<(), Error>;
     fn insert_epoch_ext(&mut self, hash: &H256, epoch: &EpochExt) -> Result<(), Error>;
+    fn insert_versionbits_states(
+        &mut self,
+        epoch_hash: &H256,
+        states: &[ThresholdState],
+    ) -> Result<(), Error>;
+
+    fn insert_block_filter(
+        &mut self,
+        block_hash: &H256,
+        filter: &[u8],
+        filter_header: &H256,
+    ) -> Result<(), Error>;
+    fn insert_unfiltered_block(&mut self, block_hash: &H256) -> Result<(), Error>;
 
     fn attach_block(&mut self, block: &Block) -> Result<(), Error>;
     fn detach_block(&mut self, block: &Block) -> Result<(), Error>;
 
+    /// Discard the transactions, uncles and proposals of an attached block,
+    /// keeping the outputs which its successors have not spent yet
+    fn prune_block(&mut self, block: &Block) -> Result<(), Error>;
+    /// Delete the transactions, uncles and proposals of a block, its header
+    /// and ext are kept
+    fn delete_block_data(&mut self, block_hash: &H256) -> Result<(), Error>;
+    /// Saves the transactions of the cell set changed by attaching and detaching
+    /// blocks, a transaction without meta leaves the set
+    fn update_cell_set(&mut self, changes: &[(H256, Option<TransactionMeta>)])
+        -> Result<(), Error>;
+    fn insert_header_mmr_nodes(&mut self, nodes: &[(u64, H256)]) -> Result<(), Error>;
+
     fn commit(self) -> Result<(), Error>;
 }
 
-impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
+impl<T: IterableKeyValueDB> ChainStore for ChainKVStore<T> {
     type Batch = DefaultStoreBatch<T::Batch>;
 
     fn new_batch(&self) -> Result<Self::Batch, Error> {
@@ -114,22 +267,22 @@ impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
     }
 
     fn get_block(&self, h: &H256) -> Option<Block> {
-        self.get_header(h).map(|header| {
-            let transactions = self
-                .get_block_body(h)
-                .expect("block transactions must be stored");
+        self.get_header(h).and_then(|header| {
+            // the body is absent once the block is pruned
+            let transactions = self.get_block_body(h)?;
             let uncles = self
                 .get_block_uncles(h)
                 .expect("block uncles must be stored");
             let proposals = self
                 .get_block_proposal_txs_ids(h)
                 .expect("block proposal_ids must be stored");
-            BlockBuilder::default()
+            let block = BlockBuilder::default()
                 .header(header)
                 .uncles(uncles)
                 .transactions(transactions)
                 .proposals(proposals)
-                .build()
+                .build();
+            Some(block)
         })
     }
 
@@ -140,21 +293,21 @@ impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
 
     fn get_block_uncles(&self, h: &H256) -> Option<Vec<UncleBlock>> {
         // TODO Q use builder
-        self.get(COLUMN_BLOCK_UNCLE, h.as_bytes())
+        self.get_block_data(COLUMN_BLOCK_UNCLE, h)
             .map(|raw| deserialize(&raw[..]).expect("deserialize uncle should be ok"))
     }
 
     fn get_block_proposal_txs_ids(&self, h: &H256) -> Option<Vec<ProposalShortId>> {
-        self.get(COLUMN_BLOCK_PROPOSAL_IDS, h.as_bytes())
+        self.get_block_data(COLUMN_BLOCK_PROPOSAL_IDS, h)
             .map(|raw| deserialize(&raw[..]).expect("deserialize proposal txs id should be ok"))
     }
 
     fn get_block_body(&self, h: &H256) -> Option<Vec<Transaction>> {
-        self.get(COLUMN_BLOCK_TRANSACTION_ADDRESSES, h.as_bytes())
+        self.get_block_data(COLUMN_BLOCK_TRANSACTION_ADDRESSES, h)
             .and_then(|serialized_addresses| {
                 let addresses: Vec<Address> =
                     deserialize(&serialized_addresses).expect("deserialize address should be ok");
-                self.get(COLUMN_BLOCK_BODY, h.as_bytes())
+                self.get_block_data(COLUMN_BLOCK_BODY, h)
                     .map(|serialized_body| {
                         let txs: Vec<TransactionBuilder> = addresses
                             .iter()
@@ -186,19 +339,32 @@ impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
             total_difficulty: genesis.header().difficulty().clone(),
             total_uncles_count: 0,
             txs_verified: Some(true),
+            txs_fees: Vec::new(),
+            txs_cycles: None,
         };
 
-        let mut cells = Vec::with_capacity(genesis.transactions().len());
-
+        let mut cells: Vec<(H256, Option<TransactionMeta>)> =
+            Vec::with_capacity(genesis.transactions().len());
         for tx in genesis.transactions() {
-            let ins = if tx.is_cellbase() {
-                Vec::new()
+            if !tx.is_cellbase() {
+                for cell in tx
+                    .inputs()
+                    .iter()
+                    .filter_map(|input| input.previous_output.cell.as_ref())
+                {
+                    if let Some((_, Some(meta))) =
+                        cells.iter_mut().find(|(hash, _)| hash == &cell.tx_hash)
+                    {
+                        meta.set_dead(cell.index as usize);
+                    }
+                }
+            }
+            let meta = if tx.is_cellbase() {
+                TransactionMeta::new_cellbase(0, tx.outputs().len())
             } else {
-                tx.input_pts()
+                TransactionMeta::new(0, tx.outputs().len())
             };
-            let outs = tx.output_pts();
-
-            cells.push((ins, outs));
+            cells.push((tx.hash().to_owned(), Some(meta)));
         }
 
         batch.insert_block(genesis)?;
@@ -208,6 +374,9 @@ impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
         batch.insert_block_epoch_index(&genesis_hash, epoch.last_block_hash_in_previous_epoch())?;
         batch.insert_epoch_ext(epoch.last_block_hash_in_previous_epoch(), &epoch)?;
         batch.attach_block(genesis)?;
+        insert_block_filter(self, &mut batch, genesis)?;
+        batch.insert_header_mmr_nodes(&self.header_mmr_nodes(&[genesis.header().to_owned()]))?;
+        batch.update_cell_set(&cells)?;
         batch.commit()
     }
 
@@ -238,19 +407,171 @@ impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
             .map(|raw| deserialize(&raw[..]).expect("db safe access"))
     }
 
-    fn get_transaction(&self, h: &H256) -> Option<(Transaction, H256)> {
-        self.get_transaction_address(h).and_then(|d| {
-            self.partial_get(
-                COLUMN_BLOCK_BODY,
-                d.block_hash.as_bytes(),
-                &(d.offset..(d.offset + d.length)),
-            )
-            .map(|ref serialized_transaction| {
+    fn get_versionbits_states(&self, epoch_hash: &H256) -> Option<Vec<ThresholdState>> {
+        self.get(COLUMN_VERSIONBITS, epoch_hash.as_bytes())
+            .map(|raw| deserialize(&raw[..]).expect("db safe access"))
+    }
+
+    fn get_block_filter(&self, block_hash: &H256) -> Option<Vec<u8>> {
+        self.get(COLUMN_BLOCK_FILTER, block_hash.as_bytes())
+    }
+
+    fn get_block_filter_header(&self, block_hash: &H256) -> Option<H256> {
+        self.get(COLUMN_BLOCK_FILTER_HEADER, block_hash.as_bytes())
+            .map(|raw| H256::from_slice(&raw[..]).expect("db safe access"))
+    }
+
+    fn is_block_unfiltered(&self, block_hash: &H256) -> bool {
+        self.get(COLUMN_UNFILTERED_BLOCK, block_hash.as_bytes())
+            .is_some()
+    }
+
+    fn get_pruned_number(&self) -> Option<BlockNumber> {
+        self.get(COLUMN_META, META_PRUNED_NUMBER_KEY)
+            .map(|raw| deserialize(&raw[..]).expect("db safe access"))
+    }
+
+    fn is_block_pruned(&self, block_hash: &H256) -> bool {
+        self.get_block_data(COLUMN_BLOCK_TRANSACTION_ADDRESSES, block_hash)
+            .is_none()
+            && self.get_header(block_hash).is_some()
+    }
+
+    fn get_tx_meta(&self, tx_hash: &H256) -> Option<TransactionMeta> {
+        self.get(COLUMN_CELL_SET, tx_hash.as_bytes())
+            .map(|raw| deserialize(&raw[..]).expect("db safe access"))
+    }
+
+    fn get_cell_set(&self) -> Vec<(H256, TransactionMeta)> {
+        self.db
+            .iter(COLUMN_CELL_SET, &[], Direction::Forward)
+            .expect("db operation should be ok")
+            .map(|(key, value)| {
                 (
-                    TransactionBuilder::new(serialized_transaction).build(),
-                    d.block_hash,
+                    H256::from_slice(&key[..]).expect("db safe access"),
+                    deserialize(&value[..]).expect("db safe access"),
                 )
             })
+            .collect()
+    }
+
+    fn get_live_cell_meta(&self, tx_hash: &H256, index: u32) -> Option<CellMeta> {
+        self.get_tx_meta(tx_hash)
+            .filter(|meta| (index as usize) < meta.len() && !meta.is_dead(index as usize))
+            .and_then(|_| self.get_cell_meta(tx_hash, index))
+    }
+
+    fn get_frozen_number(&self) -> Option<BlockNumber> {
+        self.freezer.as_ref().map(Freezer::number)
+    }
+
+    fn freeze(&self, number: BlockNumber) -> Result<(), Error> {
+        let freezer = match self.freezer {
+            Some(ref freezer) => freezer,
+            None => return Ok(()),
+        };
+        self.write(|batch| {
+            for frozen_number in freezer.number()..number {
+                let hash = self
+                    .get_block_hash(frozen_number)
+                    .expect("main chain block stored");
+                // the data of a pruned block is absent, it is frozen empty
+                let data = |col| self.get(col, hash.as_bytes()).unwrap_or_default();
+                freezer.append(&[
+                    hash.as_bytes(),
+                    &data(COLUMN_BLOCK_BODY)[..],
+                    &data(COLUMN_BLOCK_TRANSACTION_ADDRESSES)[..],
+                    &data(COLUMN_BLOCK_UNCLE)[..],
+                    &data(COLUMN_BLOCK_PROPOSAL_IDS)[..],
+                ])?;
+                batch.delete_block_data(&hash)?;
+            }
+            // the db copies are kept until the freezer is durable
+            freezer.sync()
+        })
+    }
+
+    fn backup(&self, path: &Path) -> Result<(), Error> {
+        if path.exists() {
+            Err(Error::DBError(format!(
+                "backup target {} already exists",
+                path.display()
+            )))?;
+        }
+        fs::create_dir_all(path).map_err(|err| Error::DBError(format!("backup target {}", err)))?;
+        self.db.checkpoint(&path.join(BACKUP_DB_DIR))?;
+        // Blocks are deleted from the db only after being frozen, so the freezer
+        // copied after the db still holds every block missing in the copy
+        if let Some(ref freezer) = self.freezer {
+            freezer.copy_to(&path.join(BACKUP_FREEZER_DIR))?;
+        }
+        Ok(())
+    }
+
+    fn get_cache_stats(&self) -> StoreCacheStats {
+        StoreCacheStats {
+            header: self.db.cache_stats(COLUMN_BLOCK_HEADER),
+            block: self.db.cache_stats(COLUMN_BLOCK_BODY),
+            cell_meta: self.db.cache_stats(COLUMN_CELL_META),
+        }
+    }
+
+    fn get_header_mmr_root(&self, number: BlockNumber) -> Option<H256> {
+        let peaks = mmr::peak_positions(number + 1)
+            .into_iter()
+            .map(|pos| self.get_header_mmr_node(pos))
+            .collect::<Option<Vec<_>>>()?;
+        Some(mmr::bag_peaks(&peaks))
+    }
+
+    fn get_header_proof(
+        &self,
+        number: BlockNumber,
+        tip_number: BlockNumber,
+    ) -> Option<HeaderProof> {
+        if number > tip_number {
+            return None;
+        }
+        let leaves = tip_number + 1;
+        let (path, peak) = mmr::proof_positions(number, leaves);
+        let path = path
+            .into_iter()
+            .map(|pos| self.get_header_mmr_node(pos))
+            .collect::<Option<Vec<_>>>()?;
+        let peaks = mmr::peak_positions(leaves)
+            .into_iter()
+            .filter(|pos| *pos != peak)
+            .map(|pos| self.get_header_mmr_node(pos))
+            .collect::<Option<Vec<_>>>()?;
+        Some(HeaderProof::new(number, leaves, path, peaks))
+    }
+
+    fn header_mmr_nodes(&self, headers: &[Header]) -> Vec<(u64, H256)> {
+        match headers.first() {
+            Some(first) => {
+                let hashes: Vec<_> = headers.iter().map(|h| h.hash().to_owned()).collect();
+                mmr::append_leaves(first.number(), &hashes, |pos| self.get_header_mmr_node(pos))
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn get_transaction(&self, h: &H256) -> Option<(Transaction, H256)> {
+        self.get_transaction_address(h).and_then(|d| {
+            let range = d.offset..(d.offset + d.length);
+            self.partial_get(COLUMN_BLOCK_BODY, d.block_hash.as_bytes(), &range)
+                .or_else(|| {
+                    let (freezer, number) = self.frozen_block_number(&d.block_hash)?;
+                    freezer
+                        .partial_retrieve(FreezerTable::Body, number, &range)
+                        .expect("freezer operation should be ok")
+                })
+                .map(|ref serialized_transaction| {
+                    (
+                        TransactionBuilder::new(serialized_transaction).build(),
+                        d.block_hash,
+                    )
+                })
         })
     }
 
@@ -268,6 +589,10 @@ impl<T: KeyValueDB> ChainStore for ChainKVStore<T> {
     fn get_cell_output(&self, tx_hash: &H256, index: u32) -> Option<CellOutput> {
         self.get_transaction(tx_hash)
             .and_then(|(tx, _)| tx.outputs().get(index as usize).map(ToOwned::to_owned))
+            .or_else(|| {
+                self.get(COLUMN_PRUNED_CELL_OUTPUT, &cell_store_key(tx_hash, index))
+                    .map(|raw| deserialize(&raw[..]).expect("db safe access"))
+            })
     }
 }
 
@@ -326,6 +651,9 @@ impl<B: DbBatch> StoreBatch for DefaultStoreBatch<B> {
         for (id, tx) in block.transactions().iter().enumerate() {
             let address = TransactionAddress {
                 block_hash: hash.clone(),
+                block_number: block.header().number(),
+                block_epoch: block.header().epoch(),
+                index: id,
                 offset: addresses[id].offset,
                 length: addresses[id].length,
             };
@@ -345,6 +673,7 @@ impl<B: DbBatch> StoreBatch for DefaultStoreBatch<B> {
                     cellbase,
                     capacity: output.capacity,
                     data_hash: Some(output.data_hash()),
+                    dep_group: output.is_dep_group(),
                 };
                 self.insert_serialize(COLUMN_CELL_META, &store_key, &cell_meta)?;
             }
@@ -364,10 +693,75 @@ impl<B: DbBatch> StoreBatch for DefaultStoreBatch<B> {
                 self.delete(COLUMN_CELL_META, &store_key)?;
             }
         }
-        self.delete(COLUMN_INDEX, &block.header().number().to_le_bytes())?;
+        // the MMR nodes appended with the block
+        let number = block.header().number();
+        for pos in mmr::leaf_position(number)..mmr::leaf_position(number + 1) {
+            self.delete(COLUMN_HEADER_MMR, &pos.to_le_bytes())?;
+        }
+        self.delete(COLUMN_INDEX, &number.to_le_bytes())?;
         self.delete(COLUMN_INDEX, block.header().hash().as_bytes())
     }
 
+    fn prune_block(&mut self, block: &Block) -> Result<(), Error> {
+        for tx in block.transactions() {
+            // the outputs spent by a pruned block can never be unspent again
+            if !tx.is_cellbase() {
+                for cell in tx
+                    .inputs()
+                    .iter()
+                    .filter_map(|input| input.previous_output.cell.as_ref())
+                {
+                    self.delete(
+                        COLUMN_PRUNED_CELL_OUTPUT,
+                        &cell_store_key(&cell.tx_hash, cell.index),
+                    )?;
+                }
+            }
+            let tx_hash = tx.hash();
+            for (index, output) in tx.outputs().iter().enumerate() {
+                self.insert_serialize(
+                    COLUMN_PRUNED_CELL_OUTPUT,
+                    &cell_store_key(&tx_hash, index as u32),
+                    output,
+                )?;
+            }
+        }
+
+        self.delete_block_data(block.header().hash())?;
+        self.insert_serialize(
+            COLUMN_META,
+            META_PRUNED_NUMBER_KEY,
+            &block.header().number(),
+        )
+    }
+
+    fn delete_block_data(&mut self, block_hash: &H256) -> Result<(), Error> {
+        self.delete(COLUMN_BLOCK_BODY, block_hash.as_bytes())?;
+        self.delete(COLUMN_BLOCK_TRANSACTION_ADDRESSES, block_hash.as_bytes())?;
+        self.delete(COLUMN_BLOCK_UNCLE, block_hash.as_bytes())?;
+        self.delete(COLUMN_BLOCK_PROPOSAL_IDS, block_hash.as_bytes())
+    }
+
+    fn update_cell_set(
+        &mut self,
+        changes: &[(H256, Option<TransactionMeta>)],
+    ) -> Result<(), Error> {
+        for (tx_hash, meta) in changes {
+            match meta {
+                Some(meta) => self.insert_serialize(COLUMN_CELL_SET, tx_hash.as_bytes(), meta)?,
+                None => self.delete(COLUMN_CELL_SET, tx_hash.as_bytes())?,
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_header_mmr_nodes(&mut self, nodes: &[(u64, H256)]) -> Result<(), Error> {
+        for (pos, hash) in nodes {
+            self.insert_raw(COLUMN_HEADER_MMR, &pos.to_le_bytes(), hash.as_bytes())?;
+        }
+        Ok(())
+    }
+
     fn insert_tip_header(&mut self, h: &Header) -> Result<(), Error> {
         self.insert_raw(COLUMN_META, META_TIP_HEADER_KEY, h.hash().as_bytes())
     }
@@ -388,10 +782,36 @@ impl<B: DbBatch> StoreBatch for DefaultStoreBatch<B> {
         self.insert_serialize(COLUMN_EPOCH, hash.as_bytes(), epoch)
     }
 
+    fn insert_versionbits_states(
+        &mut self,
+        epoch_hash: &H256,
+        states: &[ThresholdState],
+    ) -> Result<(), Error> {
+        self.insert_serialize(COLUMN_VERSIONBITS, epoch_hash.as_bytes(), states)
+    }
+
     fn insert_current_epoch_ext(&mut self, epoch: &EpochExt) -> Result<(), Error> {
         self.insert_serialize(COLUMN_META, META_CURRENT_EPOCH_KEY, epoch)
     }
 
+    fn insert_block_filter(
+        &mut self,
+        block_hash: &H256,
+        filter: &[u8],
+        filter_header: &H256,
+    ) -> Result<(), Error> {
+        self.insert_raw(COLUMN_BLOCK_FILTER, block_hash.as_bytes(), filter)?;
+        self.insert_raw(
+            COLUMN_BLOCK_FILTER_HEADER,
+            block_hash.as_bytes(),
+            filter_header.as_bytes(),
+        )
+    }
+
+    fn insert_unfiltered_block(&mut self, block_hash: &H256) -> Result<(), Error> {
+        self.insert_raw(COLUMN_UNFILTERED_BLOCK, block_hash.as_bytes(), &[])
+    }
+
     fn commit(self) -> Result<(), Error> {
         self.inner.commit()
     }
@@ -402,8 +822,12 @@ mod tests {
     use super::super::COLUMNS;
     use super::*;
     use crate::store::StoreBatch;
+    use crate::StoreConfig;
     use ckb_chain_spec::consensus::Consensus;
-    use ckb_db::{DBConfig, RocksDB};
+    use ckb_core::header::HeaderBuilder;
+    use ckb_core::transaction::{CellInput, OutPoint};
+    use ckb_core::{Bytes, Capacity};
+    use ckb_db::{CacheDB, DBConfig, RocksDB};
     use tempfile;
 
     fn setup_db(prefix: &str, columns: u32) -> RocksDB {
@@ -430,6 +854,53 @@ mod tests {
         assert_eq!(block, &store.get_block(&hash).unwrap());
     }
 
+    #[test]
+    fn write_discards_failed_batch() {
+        let db = setup_db("write_discards_failed_batch", COLUMNS);
+        let store = ChainKVStore::new(db);
+        let consensus = Consensus::default();
+        let block = consensus.genesis_block();
+
+        let hash = block.header().hash();
+        let ret: Result<(), Error> = store.write(|batch| {
+            batch.insert_block(&block)?;
+            Err(Error::DBError("abort".to_owned()))
+        });
+        assert!(ret.is_err());
+        assert_eq!(store.get_block(&hash), None);
+
+        store.write(|batch| batch.insert_block(&block)).unwrap();
+        assert_eq!(store.get_block(&hash).as_ref(), Some(block));
+    }
+
+    #[test]
+    fn cache_stats() {
+        let db = setup_db("cache_stats", COLUMNS);
+        let config = StoreConfig {
+            cell_meta_cache_size: 0,
+            ..Default::default()
+        };
+        let store = ChainKVStore::new(CacheDB::new(db, &config.cache_cols()));
+        let consensus = Consensus::default();
+        store.init(&consensus).unwrap();
+        let hash = consensus.genesis_block().header().hash();
+        assert!(store.get_header(&hash).is_some());
+        assert!(store.get_header(&H256::zero()).is_none());
+
+        let stats = store.get_cache_stats();
+        assert_eq!(
+            stats.header,
+            Some(CacheStats {
+                capacity: 4096,
+                size: 1,
+                hits: 1,
+                misses: 1,
+            })
+        );
+        assert!(stats.block.is_some());
+        assert_eq!(stats.cell_meta, None);
+    }
+
     #[test]
     fn save_and_get_block_with_transactions() {
         let db = setup_db("save_and_get_block_with_transactions", COLUMNS);
@@ -459,6 +930,8 @@ mod tests {
             total_difficulty: block.header().difficulty().to_owned(),
             total_uncles_count: block.uncles().len() as u64,
             txs_verified: Some(true),
+            txs_fees: Vec::new(),
+            txs_cycles: None,
         };
 
         let hash = block.header().hash();
@@ -468,6 +941,285 @@ mod tests {
         assert_eq!(ext, store.get_block_ext(&hash).unwrap());
     }
 
+    #[test]
+    fn attach_and_detach_transaction_address() {
+        let db = setup_db("attach_and_detach_transaction_address", COLUMNS);
+        let store = ChainKVStore::new(db);
+        let tx = TransactionBuilder::default()
+            .output(CellOutput::new(
+                Capacity::bytes(1).unwrap(),
+                Bytes::default(),
+                Default::default(),
+                None,
+            ))
+            .build();
+        let block = BlockBuilder::default()
+            .header_builder(HeaderBuilder::default().number(3).epoch(1))
+            .transaction(TransactionBuilder::default().build())
+            .transaction(tx.clone())
+            .build();
+
+        let mut batch = store.new_batch().unwrap();
+        batch.insert_block(&block).unwrap();
+        batch.attach_block(&block).unwrap();
+        batch.commit().unwrap();
+        let address = store.get_transaction_address(tx.hash()).unwrap();
+        assert_eq!(&address.block_hash, block.header().hash());
+        assert_eq!(
+            (address.block_number, address.block_epoch, address.index),
+            (3, 1, 1)
+        );
+
+        let mut batch = store.new_batch().unwrap();
+        batch.detach_block(&block).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(store.get_transaction_address(tx.hash()), None);
+    }
+
+    #[test]
+    fn prune_block() {
+        let db = setup_db("prune_block", COLUMNS);
+        let store = ChainKVStore::new(db);
+        let parent = TransactionBuilder::default()
+            .output(CellOutput::new(
+                Capacity::bytes(1).unwrap(),
+                Bytes::default(),
+                Default::default(),
+                None,
+            ))
+            .output(CellOutput::new(
+                Capacity::bytes(2).unwrap(),
+                Bytes::default(),
+                Default::default(),
+                None,
+            ))
+            .build();
+        let child = TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(parent.hash().to_owned(), 0),
+                0,
+                vec![],
+            ))
+            .build();
+        let block = BlockBuilder::default()
+            .transaction(TransactionBuilder::default().build())
+            .transaction(parent.clone())
+            .transaction(child)
+            .build();
+
+        let hash = block.header().hash();
+        let mut batch = store.new_batch().unwrap();
+        batch.insert_block(&block).unwrap();
+        batch.attach_block(&block).unwrap();
+        batch.commit().unwrap();
+        assert!(!store.is_block_pruned(&hash));
+
+        let mut batch = store.new_batch().unwrap();
+        batch.prune_block(&block).unwrap();
+        batch.commit().unwrap();
+        assert!(store.is_block_pruned(&hash));
+        assert_eq!(store.get_pruned_number(), Some(block.header().number()));
+        assert_eq!(store.get_block(&hash), None);
+        assert_eq!(store.get_header(&hash).as_ref(), Some(block.header()));
+        assert_eq!(store.get_transaction(parent.hash()), None);
+        let address = store.get_transaction_address(parent.hash()).unwrap();
+        assert_eq!(&address.block_hash, hash);
+        assert_eq!(
+            (address.block_number, address.block_epoch, address.index),
+            (block.header().number(), block.header().epoch(), 1)
+        );
+        // the spent output is discarded, the unspent one is kept
+        assert_eq!(store.get_cell_output(parent.hash(), 0), None);
+        assert_eq!(
+            store.get_cell_output(parent.hash(), 1),
+            Some(parent.outputs()[1].clone())
+        );
+    }
+
+    #[test]
+    fn freeze_blocks() {
+        let db = setup_db("freeze_blocks", COLUMNS);
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("freeze_blocks_freezer")
+            .tempdir()
+            .unwrap();
+        let store = ChainKVStore::new(db).with_freezer(Freezer::open(tmp_dir.path()).unwrap());
+        let consensus = Consensus::default();
+        store.init(&consensus).unwrap();
+        let genesis = consensus.genesis_block();
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(
+                OutPoint::new_cell(genesis.transactions()[0].hash().to_owned(), 0),
+                0,
+                vec![],
+            ))
+            .build();
+        let block = BlockBuilder::default()
+            .header_builder(
+                HeaderBuilder::default()
+                    .parent_hash(genesis.header().hash().to_owned())
+                    .number(1),
+            )
+            .transaction(TransactionBuilder::default().build())
+            .transaction(tx.clone())
+            .build();
+        let hash = block.header().hash();
+        let mut batch = store.new_batch().unwrap();
+        batch.insert_block(&block).unwrap();
+        batch.attach_block(&block).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(store.get_frozen_number(), Some(0));
+
+        store.freeze(2).unwrap();
+        assert_eq!(store.get_frozen_number(), Some(2));
+        assert_eq!(store.get(COLUMN_BLOCK_BODY, hash.as_bytes()), None);
+        assert_eq!(store.get_block(&hash), Some(block.clone()));
+        assert_eq!(
+            store.get_block(genesis.header().hash()).as_ref(),
+            Some(genesis)
+        );
+        assert_eq!(store.get_transaction(tx.hash()), Some((tx, hash.clone())));
+        assert!(!store.is_block_pruned(&hash));
+    }
+
+    #[test]
+    fn iter_main_chain() {
+        let db = setup_db("iter_main_chain", COLUMNS);
+        let store = ChainKVStore::new(db);
+        let consensus = Consensus::default();
+        store.init(&consensus).unwrap();
+        let genesis = consensus.genesis_block();
+        let block = BlockBuilder::default()
+            .header_builder(
+                HeaderBuilder::default()
+                    .parent_hash(genesis.header().hash().to_owned())
+                    .number(1),
+            )
+            .build();
+        let mut batch = store.new_batch().unwrap();
+        batch.insert_block(&block).unwrap();
+        batch.attach_block(&block).unwrap();
+        batch.commit().unwrap();
+
+        assert_eq!(
+            store.iter_blocks(0).collect::<Vec<_>>(),
+            vec![genesis.clone(), block.clone()]
+        );
+        assert_eq!(
+            store.iter_headers(1).collect::<Vec<_>>(),
+            vec![block.header().to_owned()]
+        );
+        assert_eq!(store.iter_blocks(2).next(), None);
+    }
+
+    #[test]
+    fn update_cell_set() {
+        let db = setup_db("update_cell_set", COLUMNS);
+        let store = ChainKVStore::new(db);
+        let consensus = Consensus::default();
+        store.init(&consensus).unwrap();
+        let cells = store.get_cell_set();
+        assert_eq!(cells.len(), consensus.genesis_block().transactions().len());
+
+        let tx_hash = H256::from_trimmed_hex_str("1").unwrap();
+        let mut meta = TransactionMeta::new(1, 2);
+        meta.set_dead(0);
+        let mut batch = store.new_batch().unwrap();
+        batch
+            .update_cell_set(&[(tx_hash.clone(), Some(meta.clone()))])
+            .unwrap();
+        batch.commit().unwrap();
+        assert_eq!(store.get_tx_meta(&tx_hash), Some(meta));
+        assert_eq!(store.get_cell_set().len(), cells.len() + 1);
+        assert_eq!(store.get_live_cell_meta(&tx_hash, 0), None);
+
+        let mut batch = store.new_batch().unwrap();
+        batch.update_cell_set(&[(tx_hash.clone(), None)]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(store.get_tx_meta(&tx_hash), None);
+        assert_eq!(store.get_cell_set(), cells);
+    }
+
+    #[test]
+    fn header_mmr() {
+        let db = setup_db("header_mmr", COLUMNS);
+        let store = ChainKVStore::new(db);
+        let consensus = Consensus::default();
+        store.init(&consensus).unwrap();
+        let mut headers = vec![consensus.genesis_block().header().to_owned()];
+        let mut blocks = Vec::new();
+        for number in 1..5 {
+            let block = BlockBuilder::default()
+                .header_builder(
+                    HeaderBuilder::default()
+                        .parent_hash(headers[number - 1].hash().to_owned())
+                        .number(number as u64),
+                )
+                .build();
+            headers.push(block.header().to_owned());
+            blocks.push(block);
+        }
+        let mut batch = store.new_batch().unwrap();
+        for block in &blocks {
+            batch.insert_block(block).unwrap();
+            batch.attach_block(block).unwrap();
+        }
+        batch
+            .insert_header_mmr_nodes(&store.header_mmr_nodes(&headers[1..]))
+            .unwrap();
+        batch.commit().unwrap();
+
+        for tip_number in 0..5 {
+            let root = store.get_header_mmr_root(tip_number).unwrap();
+            for number in 0..=tip_number {
+                let proof = store.get_header_proof(number, tip_number).unwrap();
+                assert!(proof.verify(&root, headers[number as usize].hash()));
+                assert!(!proof.verify(&root, headers[(number as usize + 1) % 5].hash()));
+            }
+        }
+        assert!(store.get_header_proof(5, 4).is_none());
+
+        // the nodes of the detached block are discarded
+        let root = store.get_header_mmr_root(3).unwrap();
+        let mut batch = store.new_batch().unwrap();
+        batch.detach_block(&blocks[3]).unwrap();
+        batch.commit().unwrap();
+        assert_eq!(store.get_header_mmr_root(4), None);
+        assert_eq!(store.get_header_mmr_root(3), Some(root));
+    }
+
+    #[test]
+    fn backup() {
+        let db = setup_db("backup", COLUMNS);
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("backup_target")
+            .tempdir()
+            .unwrap();
+        let store = ChainKVStore::new(db)
+            .with_freezer(Freezer::open(&tmp_dir.path().join("freezer")).unwrap());
+        let consensus = Consensus::default();
+        store.init(&consensus).unwrap();
+        store.freeze(1).unwrap();
+
+        let path = tmp_dir.path().join("backup");
+        store.backup(&path).unwrap();
+        assert!(store.backup(&path).is_err());
+
+        let config = DBConfig {
+            path: path.join(BACKUP_DB_DIR),
+            ..Default::default()
+        };
+        let copy = ChainKVStore::new(RocksDB::open(&config, COLUMNS))
+            .with_freezer(Freezer::open(&path.join(BACKUP_FREEZER_DIR)).unwrap());
+        assert_eq!(copy.get_tip_header(), store.get_tip_header());
+        assert_eq!(copy.get_frozen_number(), Some(1));
+        assert_eq!(
+            copy.get_block(consensus.genesis_block().header().hash())
+                .as_ref(),
+            Some(consensus.genesis_block())
+        );
+    }
+
     #[test]
     fn index_store() {
         let tmp_dir = tempfile::Builder::new()
@@ -0,0 +1,217 @@
+use crate::{BAD_MESSAGE_BAN_TIME, MAX_GET_BLOCK_FILTERS, MAX_GET_BLOCK_FILTER_HEADERS};
+use ckb_core::header::BlockNumber;
+use ckb_network::{CKBProtocolContext, CKBProtocolHandler, PeerIndex};
+use ckb_protocol::{cast, get_root, FilterMessage, FilterPayload};
+use ckb_shared::shared::Shared;
+use ckb_store::{filter_hash, ChainStore};
+use ckb_traits::ChainProvider;
+use failure::Error as FailureError;
+use flatbuffers::FlatBufferBuilder;
+use log::{debug, info};
+use numext_fixed_hash::H256;
+use std::convert::TryInto;
+
+/// Serves the compact filters of the main chain blocks to light clients.
+///
+/// The filters and the filter headers chaining them are built by the chain
+/// when the blocks are stored, requests for the missing ones are not served.
+pub struct BlockFilterProtocol<CS> {
+    shared: Shared<CS>,
+}
+
+impl<CS: ChainStore> Clone for BlockFilterProtocol<CS> {
+    fn clone(&self) -> Self {
+        BlockFilterProtocol {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<CS: ChainStore> BlockFilterProtocol<CS> {
+    pub fn new(shared: Shared<CS>) -> Self {
+        BlockFilterProtocol { shared }
+    }
+
+    /// Returns the stored filter and filter header of the block
+    pub fn block_filter(&self, block_hash: &H256) -> Option<(Vec<u8>, H256)> {
+        let store = self.shared.store();
+        Some((
+            store.get_block_filter(block_hash)?,
+            store.get_block_filter_header(block_hash)?,
+        ))
+    }
+
+    // Main chain blocks from `start_number` to `stop_hash`, at most `limit`
+    fn main_chain_range(
+        &self,
+        start_number: BlockNumber,
+        stop_hash: &H256,
+        limit: u64,
+    ) -> Option<Vec<H256>> {
+        let stop_number = self.shared.block_number(stop_hash)?;
+        if stop_number < start_number
+            || stop_number - start_number >= limit
+            || self.shared.block_hash(stop_number).as_ref() != Some(stop_hash)
+        {
+            return None;
+        }
+        (start_number..=stop_number)
+            .map(|number| self.shared.block_hash(number))
+            .collect()
+    }
+
+    fn get_block_filters(
+        &self,
+        nc: &CKBProtocolContext,
+        peer: PeerIndex,
+        start_number: BlockNumber,
+        stop_hash: &H256,
+    ) {
+        let hashes = match self.main_chain_range(start_number, stop_hash, MAX_GET_BLOCK_FILTERS) {
+            Some(hashes) => hashes,
+            None => {
+                debug!(target: "filter", "peer {} requests filters of invalid range {} {:x}", peer, start_number, stop_hash);
+                return;
+            }
+        };
+        let mut filters = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            match self.block_filter(&hash) {
+                Some((filter, _)) => filters.push((hash, filter)),
+                None => return,
+            }
+        }
+
+        let fbb = &mut FlatBufferBuilder::new();
+        let message = FilterMessage::build_block_filters(fbb, stop_hash, &filters);
+        fbb.finish(message, None);
+        nc.send_message_to(peer, fbb.finished_data().into());
+    }
+
+    fn get_block_filter_headers(
+        &self,
+        nc: &CKBProtocolContext,
+        peer: PeerIndex,
+        start_number: BlockNumber,
+        stop_hash: &H256,
+    ) {
+        let hashes = match self.main_chain_range(
+            start_number,
+            stop_hash,
+            MAX_GET_BLOCK_FILTER_HEADERS,
+        ) {
+            Some(hashes) => hashes,
+            None => {
+                debug!(target: "filter", "peer {} requests filter headers of invalid range {} {:x}", peer, start_number, stop_hash);
+                return;
+            }
+        };
+        let previous_filter_header = if start_number == 0 {
+            Some(H256::zero())
+        } else {
+            self.shared
+                .block_hash(start_number - 1)
+                .and_then(|hash| self.block_filter(&hash))
+                .map(|(_, header)| header)
+        };
+        let previous_filter_header = match previous_filter_header {
+            Some(header) => header,
+            None => return,
+        };
+        let mut filter_hashes = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            match self.block_filter(&hash) {
+                Some((filter, _)) => filter_hashes.push(filter_hash(&filter)),
+                None => return,
+            }
+        }
+
+        let fbb = &mut FlatBufferBuilder::new();
+        let message = FilterMessage::build_block_filter_headers(
+            fbb,
+            stop_hash,
+            &previous_filter_header,
+            &filter_hashes,
+        );
+        fbb.finish(message, None);
+        nc.send_message_to(peer, fbb.finished_data().into());
+    }
+
+    fn try_process(
+        &self,
+        nc: &CKBProtocolContext,
+        peer: PeerIndex,
+        message: FilterMessage,
+    ) -> Result<(), FailureError> {
+        match message.payload_type() {
+            FilterPayload::GetBlockFilters => {
+                let request = cast!(message.payload_as_get_block_filters())?;
+                let stop_hash = cast!(request.stop_hash())?.try_into()?;
+                self.get_block_filters(nc, peer, request.start_number(), &stop_hash);
+            }
+            FilterPayload::GetBlockFilterHeaders => {
+                let request = cast!(message.payload_as_get_block_filter_headers())?;
+                let stop_hash = cast!(request.stop_hash())?.try_into()?;
+                self.get_block_filter_headers(nc, peer, request.start_number(), &stop_hash);
+            }
+            FilterPayload::BlockFilters | FilterPayload::BlockFilterHeaders => {
+                debug!(target: "filter", "peer {} sends us unrequested filters", peer);
+            }
+            FilterPayload::NONE => {
+                cast!(None)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<CS: ChainStore> CKBProtocolHandler for BlockFilterProtocol<CS> {
+    fn init(&mut self, _nc: Box<dyn CKBProtocolContext>) {}
+
+    fn received(
+        &mut self,
+        nc: Box<dyn CKBProtocolContext>,
+        peer_index: PeerIndex,
+        data: bytes::Bytes,
+    ) {
+        let message = match get_root::<FilterMessage>(&data) {
+            Ok(message) => message,
+            _ => {
+                info!(target: "filter", "Peer {} sends us a malformed message", peer_index);
+                nc.ban_peer(peer_index, BAD_MESSAGE_BAN_TIME);
+                return;
+            }
+        };
+
+        debug!(target: "filter", "received msg {:?} from {}", message.payload_type(), peer_index);
+        if let Err(err) = self.try_process(nc.as_ref(), peer_index, message) {
+            info!(target: "filter", "Peer {} sends us a malformed message: {}", peer_index, err);
+            nc.ban_peer(peer_index, BAD_MESSAGE_BAN_TIME);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_db::memorydb::MemoryKeyValueDB;
+    use ckb_shared::shared::SharedBuilder;
+    use ckb_store::{build_filter, filter_header};
+
+    #[test]
+    fn test_serve_stored_block_filter() {
+        let shared = SharedBuilder::<MemoryKeyValueDB>::new().build().unwrap();
+        let genesis_hash = shared.genesis_hash();
+        let protocol = BlockFilterProtocol::new(shared.clone());
+
+        let (filter, header) = protocol.block_filter(&genesis_hash).unwrap();
+        assert_eq!(filter, build_filter(&genesis_hash, &[]));
+        assert_eq!(header, filter_header(&filter_hash(&filter), &H256::zero()));
+        assert_eq!(
+            shared.store().get_block_filter_header(&genesis_hash),
+            Some(header)
+        );
+        // the filters are never built on request
+        assert_eq!(protocol.block_filter(&H256::zero()), None);
+    }
+}
@@ -1,14 +1,130 @@
+use crate::net_time_checker::TOLERANT_OFFSET;
+use crate::synchronizer::DEFAULT_ORPHAN_BLOCK_EXPIRY;
+use crate::{
+    RateLimit, DEFAULT_BAN_SCORE_THRESHOLD, DEFAULT_GET_BLOCKS_RATE_LIMIT,
+    DEFAULT_GET_HEADERS_RATE_LIMIT, DEFAULT_GET_TRANSACTIONS_RATE_LIMIT,
+    DEFAULT_LIGHT_CLIENT_PROOFS_RATE_LIMIT, DEFAULT_MISBEHAVIOR_BAN_TIME,
+    DEFAULT_TRANSACTIONS_RATE_LIMIT, MAX_BLOCKS_IN_TRANSIT, MAX_BLOCKS_IN_TRANSIT_PER_PEER,
+    MIN_BLOCKS_IN_TRANSIT_PER_PEER,
+};
+use numext_fixed_uint::U256;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
     pub orphan_block_limit: usize,
+    /// Seconds to keep an orphan block waiting for its parent
+    #[serde(default = "default_orphan_block_expiry")]
+    pub orphan_block_expiry: u64,
+    /// Lower bound of the blocks requested from a single peer at once
+    #[serde(default = "default_min_blocks_in_transit_per_peer")]
+    pub min_blocks_in_transit_per_peer: usize,
+    /// Upper bound of the blocks requested from a single peer at once
+    #[serde(default = "default_max_blocks_in_transit_per_peer")]
+    pub max_blocks_in_transit_per_peer: usize,
+    /// Upper bound of the blocks requested from all peers at once
+    #[serde(default = "default_max_blocks_in_transit")]
+    pub max_blocks_in_transit: usize,
+    /// Accept headers conflicting with the checkpoints of the chain spec, for dev chains
+    #[serde(default)]
+    pub disable_checkpoints: bool,
+    /// Misbehavior score at which a peer is banned
+    #[serde(default = "default_ban_score_threshold")]
+    pub ban_score_threshold: u32,
+    /// Seconds a misbehaving peer stays banned
+    #[serde(default = "default_misbehavior_ban_time")]
+    pub misbehavior_ban_time: u64,
+    /// Overrides the minimum total difficulty of the chain spec a peer's best
+    /// header must reach before blocks are downloaded from it
+    #[serde(default)]
+    pub min_chain_work: Option<U256>,
+    /// GetHeaders messages a peer may send per second, exceeding it is misbehavior
+    #[serde(default = "default_get_headers_rate_limit")]
+    pub get_headers_rate_limit: RateLimit,
+    /// Blocks a peer may request per second
+    #[serde(default = "default_get_blocks_rate_limit")]
+    pub get_blocks_rate_limit: RateLimit,
+    /// Transactions a peer may relay per second
+    #[serde(default = "default_transactions_rate_limit")]
+    pub transactions_rate_limit: RateLimit,
+    /// Transactions a peer may request per second
+    #[serde(default = "default_get_transactions_rate_limit")]
+    pub get_transactions_rate_limit: RateLimit,
+    /// Transaction proofs and cells status a light client may request per second
+    #[serde(default = "default_light_client_proofs_rate_limit")]
+    pub light_client_proofs_rate_limit: RateLimit,
+    /// Milliseconds the local clock may be off the median of the peers' ones
+    /// before the operator is alerted
+    #[serde(default = "default_tolerant_time_offset")]
+    pub tolerant_time_offset: u64,
+}
+
+fn default_orphan_block_expiry() -> u64 {
+    DEFAULT_ORPHAN_BLOCK_EXPIRY.as_secs()
+}
+
+fn default_min_blocks_in_transit_per_peer() -> usize {
+    MIN_BLOCKS_IN_TRANSIT_PER_PEER
+}
+
+fn default_max_blocks_in_transit_per_peer() -> usize {
+    MAX_BLOCKS_IN_TRANSIT_PER_PEER
+}
+
+fn default_max_blocks_in_transit() -> usize {
+    MAX_BLOCKS_IN_TRANSIT
+}
+
+fn default_ban_score_threshold() -> u32 {
+    DEFAULT_BAN_SCORE_THRESHOLD
+}
+
+fn default_misbehavior_ban_time() -> u64 {
+    DEFAULT_MISBEHAVIOR_BAN_TIME.as_secs()
+}
+
+fn default_get_headers_rate_limit() -> RateLimit {
+    DEFAULT_GET_HEADERS_RATE_LIMIT
+}
+
+fn default_get_blocks_rate_limit() -> RateLimit {
+    DEFAULT_GET_BLOCKS_RATE_LIMIT
+}
+
+fn default_transactions_rate_limit() -> RateLimit {
+    DEFAULT_TRANSACTIONS_RATE_LIMIT
+}
+
+fn default_get_transactions_rate_limit() -> RateLimit {
+    DEFAULT_GET_TRANSACTIONS_RATE_LIMIT
+}
+
+fn default_light_client_proofs_rate_limit() -> RateLimit {
+    DEFAULT_LIGHT_CLIENT_PROOFS_RATE_LIMIT
+}
+
+fn default_tolerant_time_offset() -> u64 {
+    TOLERANT_OFFSET
 }
 
 impl Config {
     pub fn default() -> Self {
         Config {
             orphan_block_limit: 1024,
+            orphan_block_expiry: default_orphan_block_expiry(),
+            min_blocks_in_transit_per_peer: default_min_blocks_in_transit_per_peer(),
+            max_blocks_in_transit_per_peer: default_max_blocks_in_transit_per_peer(),
+            max_blocks_in_transit: default_max_blocks_in_transit(),
+            disable_checkpoints: false,
+            ban_score_threshold: default_ban_score_threshold(),
+            misbehavior_ban_time: default_misbehavior_ban_time(),
+            min_chain_work: None,
+            get_headers_rate_limit: default_get_headers_rate_limit(),
+            get_blocks_rate_limit: default_get_blocks_rate_limit(),
+            transactions_rate_limit: default_transactions_rate_limit(),
+            get_transactions_rate_limit: default_get_transactions_rate_limit(),
+            light_client_proofs_rate_limit: default_light_client_proofs_rate_limit(),
+            tolerant_time_offset: default_tolerant_time_offset(),
         }
     }
 }
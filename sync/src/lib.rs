@@ -3,8 +3,11 @@
 //! Sync module implement ckb sync protocol as specified here:
 //! https://github.com/nervosnetwork/rfcs/tree/master/rfcs/0000-block-sync-protocol
 
+mod block_filter;
 mod config;
+mod light_client;
 mod net_time_checker;
+mod rate_limiter;
 mod relayer;
 mod synchronizer;
 mod types;
@@ -12,11 +15,18 @@ mod types;
 #[cfg(test)]
 mod tests;
 
+pub use crate::block_filter::BlockFilterProtocol;
 pub use crate::config::Config;
+pub use crate::light_client::{
+    LightClientProtocol, CELL_STATUS_DEAD, CELL_STATUS_LIVE, CELL_STATUS_UNKNOWN,
+};
 pub use crate::net_time_checker::NetTimeProtocol;
+pub use crate::rate_limiter::RateLimit;
 pub use crate::relayer::Relayer;
 pub use crate::synchronizer::Synchronizer;
-pub use crate::types::SyncSharedState;
+pub use crate::types::{
+    ForkStats, PeerFeatures, PeerSyncInfo, Peers, ReorgInfo, SyncSharedState, SyncState,
+};
 use std::time::Duration;
 
 pub const MAX_HEADERS_LEN: usize = 2_000;
@@ -24,18 +34,36 @@ pub const MAX_INVENTORY_LEN: usize = 50_000;
 pub const MAX_SCHEDULED_LEN: usize = 4 * 1024;
 pub const MAX_BLOCKS_TO_ANNOUNCE: usize = 8;
 pub const MAX_UNCONNECTING_HEADERS: usize = 10;
-pub const MAX_BLOCKS_IN_TRANSIT_PER_PEER: usize = 16;
+// Bounds of the per peer download window, it starts at
+// `INITIAL_BLOCKS_IN_TRANSIT_PER_PEER` and adapts to the peer's response latency
+pub const MIN_BLOCKS_IN_TRANSIT_PER_PEER: usize = 4;
+pub const INITIAL_BLOCKS_IN_TRANSIT_PER_PEER: usize = 16;
+pub const MAX_BLOCKS_IN_TRANSIT_PER_PEER: usize = 128;
+pub const MAX_BLOCKS_IN_TRANSIT: usize = 1024;
 pub const MAX_TIP_AGE: u64 = 60 * 60 * 1000;
+// Best known header this many blocks ahead of the tip also means initial block download
+pub const IBD_MAX_BLOCKS_BEHIND: u64 = 1024;
 pub const STALE_RELAY_AGE_LIMIT: u64 = 30 * 24 * 60 * 60 * 1000;
 pub const BLOCK_DOWNLOAD_WINDOW: u64 = 1024;
 pub const PER_FETCH_BLOCK_LIMIT: usize = 128;
+// Blocks covered by a single block filters request
+pub const MAX_GET_BLOCK_FILTERS: u64 = 1_000;
+pub const MAX_GET_BLOCK_FILTER_HEADERS: u64 = 2_000;
+// Transactions or cells covered by a single light client proofs request
+pub const MAX_LIGHT_CLIENT_PROOFS: usize = 1_000;
 
 use ckb_network::ProtocolId;
+use ckb_protocol::{
+    enum_name_relay_payload, enum_name_sync_payload, get_root, RelayMessage, SyncMessage,
+};
+use ckb_shared::error::SharedError;
 
 pub enum NetworkProtocol {
     SYNC = 100,
     RELAY = 101,
     TIME = 102,
+    FILTER = 103,
+    LIGHT_CLIENT = 104,
 }
 
 impl Into<ProtocolId> for NetworkProtocol {
@@ -44,6 +72,62 @@ impl Into<ProtocolId> for NetworkProtocol {
     }
 }
 
+// Versions of the sync and relay protocols, the highest one supported by both
+// sides is negotiated. Since version 2 the synchronizers exchange their
+// capability bits right after connecting.
+pub const PROTOCOL_VERSION_1: u32 = 1;
+pub const PROTOCOL_VERSION_2: u32 = 2;
+pub const SUPPORTED_PROTOCOL_VERSIONS: [u32; 2] = [PROTOCOL_VERSION_2, PROTOCOL_VERSION_1];
+
+/// Versions to register the sync and relay protocols with
+pub fn supported_protocol_versions() -> Vec<String> {
+    SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Parses a negotiated version, an unknown one is handled as version 1
+pub fn parse_protocol_version(version: &str) -> u32 {
+    version.parse().unwrap_or(PROTOCOL_VERSION_1)
+}
+
+// Since version 2 the large messages are compressed
+pub const COMPRESSION_PROTOCOL_VERSION: u32 = PROTOCOL_VERSION_2;
+// Since version 2 the relayers announce and fetch the transactions in batches,
+// version 1 peers get a message per transaction
+pub const TX_BATCH_PROTOCOL_VERSION: u32 = PROTOCOL_VERSION_2;
+
+/// Names the sync messages in the compression statistics
+pub fn sync_message_type(data: &[u8]) -> &'static str {
+    get_root::<SyncMessage>(data)
+        .map(|message| enum_name_sync_payload(message.payload_type()))
+        .unwrap_or("Malformed")
+}
+
+/// Names the relay messages in the compression statistics
+pub fn relay_message_type(data: &[u8]) -> &'static str {
+    get_root::<RelayMessage>(data)
+        .map(|message| enum_name_relay_payload(message.payload_type()))
+        .unwrap_or("Malformed")
+}
+
+/// Whether the request failed on the body of a block we pruned, which is not
+/// the fault of the requesting peer
+pub(crate) fn is_block_pruned_error(err: &failure::Error) -> bool {
+    match err.downcast_ref::<SharedError>() {
+        Some(SharedError::BlockPruned(_)) => true,
+        _ => false,
+    }
+}
+
+// Capability bits announced in the handshake, new message types are only sent
+// to the peers announcing them
+pub const CAPABILITY_COMPACT_BLOCK: u64 = 1;
+pub const LOCAL_CAPABILITIES: u64 = CAPABILITY_COMPACT_BLOCK;
+// Capabilities of the peers which have not announced theirs, e.g. version 1 peers
+pub const LEGACY_CAPABILITIES: u64 = CAPABILITY_COMPACT_BLOCK;
+
 //  Timeout = base + per_header * (expected number of headers)
 pub const HEADERS_DOWNLOAD_TIMEOUT_BASE: u64 = 15 * 60 * 1000; // 15 minutes
 pub const HEADERS_DOWNLOAD_TIMEOUT_PER_HEADER: u64 = 1; // 1ms/header
@@ -60,8 +144,36 @@ pub const MAX_LOCATOR_SIZE: usize = 101;
 
 pub const BLOCK_DOWNLOAD_TIMEOUT: u64 = 30 * 1000; // 30s
 
+// Blocks answered faster grow the peer's download window, slower ones shrink it
+pub const FAST_BLOCK_RESPONSE: u64 = 2 * 1000; // 2s
+pub const SLOW_BLOCK_RESPONSE: u64 = 10 * 1000; // 10s
+
+// Blocks requested longer ago may be requested from another peer
+pub const BLOCK_STALLING_TIMEOUT: u64 = 5 * 1000; // 5s
+
 // ban time
 // 5 minutes
 pub const BAD_MESSAGE_BAN_TIME: Duration = Duration::from_secs(5 * 60);
 // 10 minutes, peer have no common ancestor block
 pub const SYNC_USELESS_BAN_TIME: Duration = Duration::from_secs(10 * 60);
+
+// misbehavior scores, a peer is banned once its accumulated score reaches
+// the configured threshold
+pub const UNREQUESTED_BLOCK_SCORE: u32 = 5;
+pub const UNREQUESTED_TX_SCORE: u32 = 5;
+pub const BLOCK_DOWNLOAD_TIMEOUT_SCORE: u32 = 10;
+pub const RATE_LIMIT_EXCEEDED_SCORE: u32 = 10;
+pub const DEFAULT_BAN_SCORE_THRESHOLD: u32 = 100;
+// 24 hours
+pub const DEFAULT_MISBEHAVIOR_BAN_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Default rate limits of the expensive messages a peer sends us. GetBlocks
+// and GetTransactions are counted per requested block or transaction,
+// transactions per relayed transaction.
+pub const DEFAULT_GET_HEADERS_RATE_LIMIT: RateLimit = RateLimit::new(10, 50);
+pub const DEFAULT_GET_BLOCKS_RATE_LIMIT: RateLimit = RateLimit::new(256, 1024);
+pub const DEFAULT_TRANSACTIONS_RATE_LIMIT: RateLimit = RateLimit::new(200, 2000);
+pub const DEFAULT_GET_TRANSACTIONS_RATE_LIMIT: RateLimit = RateLimit::new(200, 2000);
+// Counted per proven transaction or cell, a burst covers a full request
+pub const DEFAULT_LIGHT_CLIENT_PROOFS_RATE_LIMIT: RateLimit =
+    RateLimit::new(100, MAX_LIGHT_CLIENT_PROOFS as u32);
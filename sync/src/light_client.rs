@@ -0,0 +1,431 @@
+use crate::rate_limiter::{RateLimit, RateLimiter};
+use crate::{
+    is_block_pruned_error, BAD_MESSAGE_BAN_TIME, DEFAULT_LIGHT_CLIENT_PROOFS_RATE_LIMIT,
+    MAX_HEADERS_LEN, MAX_LIGHT_CLIENT_PROOFS,
+};
+use ckb_core::cell::{CellProvider, CellStatus};
+use ckb_core::header::{BlockNumber, Header};
+use ckb_core::transaction::OutPoint;
+use ckb_merkle_tree::MerkleProof;
+use ckb_network::{CKBProtocolContext, CKBProtocolHandler, PeerIndex};
+use ckb_protocol::{
+    cast, get_root, FlatbuffersVectorIterator, LightClientMessage, LightClientPayload,
+};
+use ckb_shared::error::SharedError;
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_traits::ChainProvider;
+use ckb_verification::TransactionsProof;
+use failure::Error as FailureError;
+use flatbuffers::FlatBufferBuilder;
+use log::{debug, info};
+use numext_fixed_hash::H256;
+use std::cmp;
+use std::convert::TryInto;
+use std::sync::Arc;
+
+/// The cell is not known by the main chain of the serving node
+pub const CELL_STATUS_UNKNOWN: u8 = 0;
+/// The cell is created and not spent in the main chain of the serving node
+pub const CELL_STATUS_LIVE: u8 = 1;
+/// The cell is created and spent in the main chain of the serving node
+pub const CELL_STATUS_DEAD: u8 = 2;
+
+/// Serves the main chain headers, and proofs against them, to light clients.
+///
+/// A light client follows the chain by its headers only, and checks the
+/// transactions it cares about with their inclusion proofs against the
+/// `transactions_root` of the headers. The liveness of a cell can not be
+/// proven by the headers, it is the view of the serving node, only the
+/// inclusion of the transaction creating the cell is proven.
+pub struct LightClientProtocol<CS> {
+    shared: Shared<CS>,
+    proofs_limiter: Arc<RateLimiter>,
+}
+
+impl<CS: ChainStore> Clone for LightClientProtocol<CS> {
+    fn clone(&self) -> Self {
+        LightClientProtocol {
+            shared: self.shared.clone(),
+            proofs_limiter: Arc::clone(&self.proofs_limiter),
+        }
+    }
+}
+
+impl<CS: ChainStore> LightClientProtocol<CS> {
+    pub fn new(shared: Shared<CS>) -> Self {
+        LightClientProtocol {
+            shared,
+            proofs_limiter: Arc::new(RateLimiter::new(DEFAULT_LIGHT_CLIENT_PROOFS_RATE_LIMIT)),
+        }
+    }
+
+    /// Limits the transaction proofs and cells status requested by a single peer
+    pub fn proofs_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.proofs_limiter = Arc::new(RateLimiter::new(limit));
+        self
+    }
+
+    // Proofs load blocks from the store, their budget is spent before any of them
+    fn check_proofs_budget(&self, peer: PeerIndex, count: usize) -> bool {
+        if self.proofs_limiter.check(peer, count) {
+            return true;
+        }
+        debug!(target: "light_client", "peer {} exceeds the proofs rate limit, drop its request", peer);
+        false
+    }
+
+    /// Main chain headers from `start_number`, at most `count` of them and
+    /// `MAX_HEADERS_LEN`
+    pub fn light_headers(&self, start_number: BlockNumber, count: u64) -> Vec<Header> {
+        let count = cmp::min(count, MAX_HEADERS_LEN as u64);
+        let mut headers = Vec::new();
+        for number in start_number..start_number.saturating_add(count) {
+            match self
+                .shared
+                .block_hash(number)
+                .and_then(|hash| self.shared.block_header(&hash))
+            {
+                Some(header) => headers.push(header),
+                None => break,
+            }
+        }
+        headers
+    }
+
+    /// Inclusion proofs of the main chain transactions among `tx_hashes`,
+    /// grouped by block. Unknown transactions are left out, the transactions
+    /// of the pruned blocks can not be proven.
+    pub fn transaction_proofs(
+        &self,
+        tx_hashes: &[H256],
+    ) -> Result<Vec<(H256, Vec<H256>, MerkleProof)>, SharedError> {
+        let mut blocks: Vec<(H256, Vec<H256>)> = Vec::new();
+        for tx_hash in tx_hashes {
+            let block_hash = match self.main_chain_block_of(tx_hash)? {
+                Some(block_hash) => block_hash,
+                None => continue,
+            };
+            match blocks.iter_mut().find(|(hash, _)| hash == &block_hash) {
+                Some((_, hashes)) => hashes.push(tx_hash.to_owned()),
+                None => blocks.push((block_hash, vec![tx_hash.to_owned()])),
+            }
+        }
+        Ok(blocks
+            .into_iter()
+            .filter_map(|(block_hash, hashes)| {
+                self.build_proof(&block_hash, &hashes)
+                    .map(|(proven, proof)| (block_hash, proven, proof))
+            })
+            .collect())
+    }
+
+    /// Status of each of `out_points`, along with the inclusion proof of the
+    /// transaction creating the cell when it is known
+    pub fn cells_status(
+        &self,
+        out_points: &[OutPoint],
+    ) -> Result<Vec<(u8, Option<(H256, MerkleProof)>)>, SharedError> {
+        let statuses = {
+            let chain_state = self.shared.chain_state().lock();
+            out_points
+                .iter()
+                .map(|out_point| match chain_state.cell(out_point) {
+                    CellStatus::Live(_) => CELL_STATUS_LIVE,
+                    CellStatus::Dead => CELL_STATUS_DEAD,
+                    CellStatus::Unknown | CellStatus::Unspecified => CELL_STATUS_UNKNOWN,
+                })
+                .collect::<Vec<_>>()
+        };
+        out_points
+            .iter()
+            .zip(statuses)
+            .map(|(out_point, status)| {
+                let cell = match out_point.cell {
+                    Some(ref cell) if status != CELL_STATUS_UNKNOWN => cell,
+                    _ => return Ok((status, None)),
+                };
+                let proof = self
+                    .main_chain_block_of(&cell.tx_hash)?
+                    .and_then(|block_hash| {
+                        self.build_proof(&block_hash, &[cell.tx_hash.to_owned()])
+                            .map(|(_, proof)| (block_hash, proof))
+                    });
+                Ok((status, proof))
+            })
+            .collect()
+    }
+
+    // The main chain block of the transaction, whose body must not be pruned
+    fn main_chain_block_of(&self, tx_hash: &H256) -> Result<Option<H256>, SharedError> {
+        let block_hash = match self.shared.store().get_transaction_address(tx_hash) {
+            Some(address) => address.block_hash,
+            None => return Ok(None),
+        };
+        match self.shared.block_number(&block_hash) {
+            Some(number) if self.shared.block_hash(number).as_ref() == Some(&block_hash) => {
+                if self.shared.store().is_block_pruned(&block_hash) {
+                    Err(SharedError::BlockPruned(block_hash))
+                } else {
+                    Ok(Some(block_hash))
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // Proof of `tx_hashes` in the block, along with the proven hashes in the
+    // order of the proof
+    fn build_proof(
+        &self,
+        block_hash: &H256,
+        tx_hashes: &[H256],
+    ) -> Option<(Vec<H256>, MerkleProof)> {
+        let block = self.shared.block(block_hash)?;
+        let indices = block
+            .transactions()
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| tx_hashes.contains(tx.hash()))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let (proof, proven) = TransactionsProof::build(&block, &indices)?.into_parts();
+        Some((proven, proof))
+    }
+
+    fn try_process(
+        &self,
+        nc: &CKBProtocolContext,
+        peer: PeerIndex,
+        message: LightClientMessage,
+    ) -> Result<(), FailureError> {
+        match message.payload_type() {
+            LightClientPayload::GetLightHeaders => {
+                let request = cast!(message.payload_as_get_light_headers())?;
+                let headers = self.light_headers(request.start_number(), request.count());
+
+                let fbb = &mut FlatBufferBuilder::new();
+                let message = LightClientMessage::build_light_headers(fbb, &headers);
+                fbb.finish(message, None);
+                nc.send_message_to(peer, fbb.finished_data().into());
+            }
+            LightClientPayload::GetTransactionProofs => {
+                let request = cast!(message.payload_as_get_transaction_proofs())?;
+                let fbs_tx_hashes = cast!(request.tx_hashes())?;
+                if fbs_tx_hashes.len() > MAX_LIGHT_CLIENT_PROOFS {
+                    debug!(target: "light_client", "peer {} requests {} transaction proofs, more than {}", peer, fbs_tx_hashes.len(), MAX_LIGHT_CLIENT_PROOFS);
+                    cast!(None)?;
+                }
+                if !self.check_proofs_budget(peer, fbs_tx_hashes.len()) {
+                    return Ok(());
+                }
+                let tx_hashes = fbs_tx_hashes
+                    .iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<H256>, _>>()?;
+                let proofs = self.transaction_proofs(&tx_hashes)?;
+
+                let fbb = &mut FlatBufferBuilder::new();
+                let message = LightClientMessage::build_transaction_proofs(fbb, &proofs);
+                fbb.finish(message, None);
+                nc.send_message_to(peer, fbb.finished_data().into());
+            }
+            LightClientPayload::GetCellsStatus => {
+                let request = cast!(message.payload_as_get_cells_status())?;
+                let fbs_out_points = cast!(request.out_points())?;
+                if fbs_out_points.len() > MAX_LIGHT_CLIENT_PROOFS {
+                    debug!(target: "light_client", "peer {} requests {} cells status, more than {}", peer, fbs_out_points.len(), MAX_LIGHT_CLIENT_PROOFS);
+                    cast!(None)?;
+                }
+                if !self.check_proofs_budget(peer, fbs_out_points.len()) {
+                    return Ok(());
+                }
+                let out_points = FlatbuffersVectorIterator::new(fbs_out_points)
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<OutPoint>, FailureError>>()?;
+                let cells = self.cells_status(&out_points)?;
+
+                let fbb = &mut FlatBufferBuilder::new();
+                let message = LightClientMessage::build_cells_status(fbb, &cells);
+                fbb.finish(message, None);
+                nc.send_message_to(peer, fbb.finished_data().into());
+            }
+            LightClientPayload::LightHeaders
+            | LightClientPayload::TransactionProofs
+            | LightClientPayload::CellsStatus => {
+                debug!(target: "light_client", "peer {} sends us unrequested light client data", peer);
+            }
+            LightClientPayload::NONE => {
+                cast!(None)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<CS: ChainStore> CKBProtocolHandler for LightClientProtocol<CS> {
+    fn init(&mut self, _nc: Box<dyn CKBProtocolContext>) {}
+
+    fn received(
+        &mut self,
+        nc: Box<dyn CKBProtocolContext>,
+        peer_index: PeerIndex,
+        data: bytes::Bytes,
+    ) {
+        let message = match get_root::<LightClientMessage>(&data) {
+            Ok(message) => message,
+            _ => {
+                info!(target: "light_client", "Peer {} sends us a malformed message", peer_index);
+                nc.ban_peer(peer_index, BAD_MESSAGE_BAN_TIME);
+                return;
+            }
+        };
+
+        debug!(target: "light_client", "received msg {:?} from {}", message.payload_type(), peer_index);
+        if let Err(err) = self.try_process(nc.as_ref(), peer_index, message) {
+            if is_block_pruned_error(&err) {
+                debug!(target: "light_client", "Peer {} requests proofs in a pruned block: {}", peer_index, err);
+            } else {
+                info!(target: "light_client", "Peer {} sends us a malformed message: {}", peer_index, err);
+                nc.ban_peer(peer_index, BAD_MESSAGE_BAN_TIME);
+            }
+        }
+    }
+
+    fn disconnected(&mut self, _nc: Box<dyn CKBProtocolContext>, peer_index: PeerIndex) {
+        self.proofs_limiter.remove(peer_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_chain_spec::consensus::Consensus;
+    use ckb_core::block::BlockBuilder;
+    use ckb_core::script::Script;
+    use ckb_core::transaction::{CellOutPoint, CellOutput, TransactionBuilder};
+    use ckb_core::{Bytes, Capacity};
+    use ckb_db::memorydb::MemoryKeyValueDB;
+    use ckb_shared::shared::SharedBuilder;
+    use ckb_store::StoreBatch;
+
+    #[test]
+    fn test_genesis_light_client_proofs() {
+        let txs = (1..=3)
+            .map(|capacity| {
+                TransactionBuilder::default()
+                    .output(CellOutput::new(
+                        Capacity::bytes(capacity).unwrap(),
+                        Bytes::default(),
+                        Script::always_success(),
+                        None,
+                    ))
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let genesis = BlockBuilder::default().transactions(txs.clone()).build();
+        let consensus = Consensus::default().set_genesis_block(genesis.clone());
+        let shared = SharedBuilder::<MemoryKeyValueDB>::new()
+            .consensus(consensus)
+            .build()
+            .unwrap();
+        let genesis_hash = genesis.header().hash().to_owned();
+        let protocol = LightClientProtocol::new(shared.clone());
+
+        let headers = protocol.light_headers(0, 10);
+        assert_eq!(headers, vec![genesis.header().to_owned()]);
+        assert!(protocol.light_headers(1, 10).is_empty());
+
+        // the proof covers the known transactions only, in the block order
+        let proofs = protocol
+            .transaction_proofs(&[
+                txs[2].hash().to_owned(),
+                H256::zero(),
+                txs[0].hash().to_owned(),
+            ])
+            .unwrap();
+        assert_eq!(proofs.len(), 1);
+        let (block_hash, proven, proof) = proofs.into_iter().next().unwrap();
+        let (expected_proof, expected_proven) = TransactionsProof::build(&genesis, &[0, 2])
+            .unwrap()
+            .into_parts();
+        assert_eq!(block_hash, genesis_hash);
+        assert_eq!(
+            proven,
+            vec![txs[0].hash().to_owned(), txs[2].hash().to_owned()]
+        );
+        assert_eq!(proven, expected_proven);
+        assert_eq!(proof.indices(), expected_proof.indices());
+        assert_eq!(proof.lemmas(), expected_proof.lemmas());
+        assert!(TransactionsProof::from_parts(proof, proven).verify(genesis.header()));
+
+        let out_points = vec![
+            OutPoint {
+                cell: Some(CellOutPoint {
+                    tx_hash: H256::zero(),
+                    index: 0,
+                }),
+                block_hash: None,
+            },
+            OutPoint::new_cell(txs[1].hash().to_owned(), 0),
+        ];
+        let cells = protocol.cells_status(&out_points).unwrap();
+        assert_eq!(cells[0].0, CELL_STATUS_UNKNOWN);
+        assert!(cells[0].1.is_none());
+        assert_eq!(cells[1].0, CELL_STATUS_LIVE);
+        let (block_hash, proof) = cells[1].1.as_ref().unwrap();
+        let (expected_proof, _) = TransactionsProof::build(&genesis, &[1])
+            .unwrap()
+            .into_parts();
+        assert_eq!(block_hash, &genesis_hash);
+        assert_eq!(proof.indices(), expected_proof.indices());
+        assert_eq!(proof.lemmas(), expected_proof.lemmas());
+    }
+
+    #[test]
+    fn test_pruned_block_proofs() {
+        let tx = TransactionBuilder::default()
+            .output(CellOutput::new(
+                Capacity::bytes(1).unwrap(),
+                Bytes::default(),
+                Script::always_success(),
+                None,
+            ))
+            .build();
+        let genesis = BlockBuilder::default().transaction(tx.clone()).build();
+        let consensus = Consensus::default().set_genesis_block(genesis.clone());
+        let shared = SharedBuilder::<MemoryKeyValueDB>::new()
+            .consensus(consensus)
+            .build()
+            .unwrap();
+        shared
+            .store()
+            .write(|batch| batch.prune_block(&genesis))
+            .unwrap();
+        let protocol = LightClientProtocol::new(shared.clone());
+
+        let expected = Err(SharedError::BlockPruned(shared.genesis_hash().to_owned()));
+        assert_eq!(
+            protocol
+                .transaction_proofs(&[tx.hash().to_owned()])
+                .map(|proofs| proofs.len()),
+            expected
+        );
+        assert_eq!(
+            protocol
+                .cells_status(&[OutPoint::new_cell(tx.hash().to_owned(), 0)])
+                .map(|cells| cells.len()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_proofs_rate_limit() {
+        let shared = SharedBuilder::<MemoryKeyValueDB>::new().build().unwrap();
+        let protocol = LightClientProtocol::new(shared).proofs_rate_limit(RateLimit::new(1, 2));
+        let peer: PeerIndex = 1.into();
+        assert!(protocol.check_proofs_budget(peer, 2));
+        assert!(!protocol.check_proofs_budget(peer, 1));
+        assert!(protocol.check_proofs_budget(2.into(), 1));
+    }
+}
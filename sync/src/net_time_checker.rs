@@ -5,8 +5,9 @@ use ckb_util::RwLock;
 use flatbuffers::FlatBufferBuilder;
 use log::{debug, info, warn};
 use std::collections::VecDeque;
+use std::sync::Arc;
 
-const TOLERANT_OFFSET: u64 = 7_200_000;
+pub const TOLERANT_OFFSET: u64 = 7_200_000;
 const MIN_SAMPLES: usize = 5;
 const MAX_SAMPLES: usize = 11;
 
@@ -53,6 +54,11 @@ impl NetTimeChecker {
         }
     }
 
+    /// Median offset of the local clock to the peers' ones, in milliseconds
+    pub fn offset(&self) -> Option<i64> {
+        self.median_offset()
+    }
+
     pub fn check(&self) -> Result<(), i64> {
         let network_offset = match self.median_offset() {
             Some(offset) => offset,
@@ -71,34 +77,53 @@ impl Default for NetTimeChecker {
     }
 }
 
+/// Called with the median offset, in milliseconds, when it exceeds the tolerated one
+pub type TimeOffsetAlert = Arc<dyn Fn(i64) + Send + Sync>;
+
+fn log_offset_alert(offset: i64) {
+    warn!(target: "network", "Please check your computer's local clock({}ms offset from network peers), If your clock is wrong, it may cause unexpected errors.", offset);
+}
+
 /// Collect time offset samples from network peers and send notify to user if offset is too large
+#[derive(Clone)]
 pub struct NetTimeProtocol {
-    checker: RwLock<NetTimeChecker>,
+    checker: Arc<RwLock<NetTimeChecker>>,
+    alert: TimeOffsetAlert,
 }
 
-impl Clone for NetTimeProtocol {
-    fn clone(&self) -> Self {
+impl NetTimeProtocol {
+    pub fn new(min_samples: usize, max_samples: usize, tolerant_offset: u64) -> Self {
+        let checker = NetTimeChecker::new(min_samples, max_samples, tolerant_offset);
         NetTimeProtocol {
-            checker: RwLock::new(self.checker.read().to_owned()),
+            checker: Arc::new(RwLock::new(checker)),
+            alert: Arc::new(log_offset_alert),
         }
     }
-}
 
-impl NetTimeProtocol {
-    pub fn new(min_samples: usize, max_samples: usize, tolerant_offset: u64) -> Self {
-        let checker = RwLock::new(NetTimeChecker::new(
-            min_samples,
-            max_samples,
-            tolerant_offset,
-        ));
-        NetTimeProtocol { checker }
+    /// Tolerates a local clock offset up to `tolerant_offset` milliseconds
+    pub fn with_tolerant_offset(tolerant_offset: u64) -> Self {
+        NetTimeProtocol::new(MIN_SAMPLES, MAX_SAMPLES, tolerant_offset)
+    }
+
+    /// Replaces the default alert, which logs a warning
+    pub fn alert<F>(mut self, alert: F) -> Self
+    where
+        F: Fn(i64) + Send + Sync + 'static,
+    {
+        self.alert = Arc::new(alert);
+        self
+    }
+
+    /// Current median offset of the local clock to the peers' ones, in
+    /// milliseconds, None until enough samples are collected
+    pub fn offset(&self) -> Option<i64> {
+        self.checker.read().offset()
     }
 }
 
 impl Default for NetTimeProtocol {
     fn default() -> Self {
-        let checker = RwLock::new(NetTimeChecker::default());
-        NetTimeProtocol { checker }
+        NetTimeProtocol::with_tolerant_offset(TOLERANT_OFFSET)
     }
 }
 
@@ -146,11 +171,14 @@ impl CKBProtocolHandler for NetTimeProtocol {
 
         let now: u64 = faketime::unix_time_as_millis();
         let offset: i64 = (i128::from(now) - i128::from(timestamp)) as i64;
-        let mut net_time_checker = self.checker.write();
         debug!(target: "network", "new net time offset sample {}ms", offset);
-        net_time_checker.add_sample(offset);
-        if let Err(offset) = net_time_checker.check() {
-            warn!(target: "network", "Please check your computer's local clock({}ms offset from network peers), If your clock is wrong, it may cause unexpected errors.", offset);
+        let result = {
+            let mut net_time_checker = self.checker.write();
+            net_time_checker.add_sample(offset);
+            net_time_checker.check()
+        };
+        if let Err(offset) = result {
+            (self.alert)(offset);
         }
     }
 }
@@ -189,4 +217,13 @@ mod tests {
         ntc.add_sample(-(TOLERANT_OFFSET as i64) - 3);
         assert_eq!(ntc.check().unwrap_err(), -(TOLERANT_OFFSET as i64) - 1);
     }
+
+    #[test]
+    fn test_shared_offset() {
+        let protocol = NetTimeProtocol::new(1, 3, 100);
+        let cloned = protocol.clone();
+        assert_eq!(protocol.offset(), None);
+        cloned.checker.write().add_sample(42);
+        assert_eq!(protocol.offset(), Some(42));
+    }
 }
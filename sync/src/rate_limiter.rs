@@ -0,0 +1,113 @@
+use ckb_network::PeerIndex;
+use ckb_util::Mutex;
+use faketime::unix_time_as_millis;
+use fnv::FnvHashMap;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp;
+
+/// Sustained rate and burst of a rate limited message, counted in items per
+/// second. A rate of 0 disables the limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub rate: u32,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub const fn new(rate: u32, burst: u32) -> Self {
+        RateLimit { rate, burst }
+    }
+}
+
+struct Bucket {
+    // In thousandths of a token, refilled by `rate` every millisecond
+    tokens: u64,
+    updated_at: u64,
+}
+
+/// Token buckets of the peers for one kind of message
+pub struct RateLimiter {
+    limit: RateLimit,
+    buckets: Mutex<FnvHashMap<PeerIndex, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        RateLimiter {
+            limit,
+            buckets: Mutex::new(FnvHashMap::default()),
+        }
+    }
+
+    /// Takes `count` tokens from the bucket of `peer`, returns false and
+    /// takes nothing if there are not enough of them
+    pub fn check(&self, peer: PeerIndex, count: usize) -> bool {
+        if self.limit.rate == 0 {
+            return true;
+        }
+        let now = unix_time_as_millis();
+        let capacity = u64::from(self.limit.burst) * 1000;
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets.entry(peer).or_insert_with(|| Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        let elapsed = now.saturating_sub(bucket.updated_at);
+        bucket.tokens = cmp::min(
+            capacity,
+            bucket
+                .tokens
+                .saturating_add(elapsed.saturating_mul(u64::from(self.limit.rate))),
+        );
+        bucket.updated_at = now;
+
+        let cost = (count as u64).saturating_mul(1000);
+        if bucket.tokens >= cost {
+            bucket.tokens -= cost;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn remove(&self, peer: PeerIndex) {
+        self.buckets.lock().remove(&peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(disable_faketime))]
+    use faketime;
+
+    #[cfg(not(disable_faketime))]
+    #[test]
+    fn test_rate_limiter() {
+        let faketime_file = faketime::millis_tempfile(0).expect("create faketime file");
+        faketime::enable(&faketime_file);
+
+        let limiter = RateLimiter::new(RateLimit::new(10, 20));
+        let peer: PeerIndex = 1.into();
+        assert!(limiter.check(peer, 15));
+        assert!(!limiter.check(peer, 6));
+        assert!(limiter.check(peer, 5));
+        assert!(!limiter.check(peer, 1));
+        // Other peers have their own bucket
+        assert!(limiter.check(2.into(), 20));
+
+        // Refilled by 10 tokens per second, up to the burst
+        faketime::write_millis(&faketime_file, 500).expect("write millis");
+        assert!(limiter.check(peer, 5));
+        assert!(!limiter.check(peer, 1));
+        faketime::write_millis(&faketime_file, 100_000).expect("write millis");
+        assert!(!limiter.check(peer, 21));
+        assert!(limiter.check(peer, 20));
+    }
+
+    #[test]
+    fn test_disabled_rate_limiter() {
+        let limiter = RateLimiter::new(RateLimit::new(0, 0));
+        assert!(limiter.check(1.into(), 1_000_000));
+    }
+}
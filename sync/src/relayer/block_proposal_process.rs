@@ -42,9 +42,11 @@ impl<'a, CS: ChainStore> BlockProposalProcess<'a, CS> {
         for (tx_hash, tx) in unknown_txs {
             if inflight.remove(&ProposalShortId::from_tx_hash(&tx_hash)) {
                 self.relayer.state.insert_tx(tx_hash);
-                let ret = chain_state.add_tx_to_pool(tx);
-                if ret.is_err() {
-                    warn!(target: "relay", "BlockProposal add_tx_to_pool error {:?}", ret)
+                match chain_state.add_tx_to_pool(tx.clone()) {
+                    Ok(_) => self.relayer.state.insert_recent_tx(&tx),
+                    Err(err) => {
+                        warn!(target: "relay", "BlockProposal add_tx_to_pool error {:?}", err)
+                    }
                 }
             }
         }
@@ -4,6 +4,8 @@ use ckb_network::{CKBProtocolContext, PeerIndex};
 use ckb_protocol::{cast, BlockTransactions, FlatbuffersVectorIterator};
 use ckb_store::ChainStore;
 use failure::Error as FailureError;
+use log::debug;
+use numext_fixed_hash::H256;
 use std::convert::TryInto;
 use std::sync::Arc;
 
@@ -30,7 +32,7 @@ impl<'a, CS: ChainStore> BlockTransactionsProcess<'a, CS> {
     }
 
     pub fn execute(self) -> Result<(), FailureError> {
-        let block_hash = cast!(self.message.block_hash())?.try_into()?;
+        let block_hash: H256 = cast!(self.message.block_hash())?.try_into()?;
         if let Some(compact_block) = self
             .relayer
             .state
@@ -49,9 +51,17 @@ impl<'a, CS: ChainStore> BlockTransactionsProcess<'a, CS> {
                     .reconstruct_block(&chain_state, &compact_block, transactions)
             };
 
-            if let Ok(block) = ret {
-                self.relayer
-                    .accept_block(self.nc, self.peer, &Arc::new(block));
+            match ret {
+                Ok(block) => self
+                    .relayer
+                    .accept_block(self.nc, self.peer, &Arc::new(block)),
+                Err(missing) => debug!(
+                    target: "relay",
+                    "peer {} did not send the missing transactions {:?} of block {:#x}",
+                    self.peer,
+                    missing,
+                    block_hash
+                ),
             }
         }
         Ok(())
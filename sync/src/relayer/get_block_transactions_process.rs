@@ -1,6 +1,7 @@
 use crate::relayer::Relayer;
 use ckb_network::{CKBProtocolContext, PeerIndex};
 use ckb_protocol::{cast, GetBlockTransactions, RelayMessage};
+use ckb_shared::error::SharedError;
 use ckb_store::ChainStore;
 use failure::Error as FailureError;
 use flatbuffers::FlatBufferBuilder;
@@ -34,6 +35,15 @@ impl<'a, CS: ChainStore> GetBlockTransactionsProcess<'a, CS> {
         debug!(target: "relay", "get_block_transactions {:?}", block_hash);
 
         let indexes = cast!(self.message.indexes())?;
+        if self
+            .relayer
+            .shared
+            .shared()
+            .store()
+            .is_block_pruned(&block_hash)
+        {
+            Err(SharedError::BlockPruned(block_hash.clone()))?;
+        }
 
         if let Some(block) = self.relayer.shared.get_block(&block_hash) {
             let transactions = indexes
@@ -0,0 +1,88 @@
+use crate::relayer::{Relayer, MAX_RELAY_TXS_NUM_PER_BATCH};
+use ckb_core::transaction::ProposalShortId;
+use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_protocol::{cast, GetRelayTransactions as FbsGetRelayTransactions, RelayMessage};
+use ckb_store::ChainStore;
+use failure::Error as FailureError;
+use flatbuffers::FlatBufferBuilder;
+use log::{debug, trace};
+use numext_fixed_hash::H256;
+use std::convert::TryInto;
+
+pub struct GetTransactionsProcess<'a, CS> {
+    message: &'a FbsGetRelayTransactions<'a>,
+    relayer: &'a Relayer<CS>,
+    nc: &'a CKBProtocolContext,
+    peer: PeerIndex,
+}
+
+impl<'a, CS: ChainStore> GetTransactionsProcess<'a, CS> {
+    pub fn new(
+        message: &'a FbsGetRelayTransactions,
+        relayer: &'a Relayer<CS>,
+        nc: &'a CKBProtocolContext,
+        peer: PeerIndex,
+    ) -> Self {
+        GetTransactionsProcess {
+            message,
+            relayer,
+            nc,
+            peer,
+        }
+    }
+
+    pub fn execute(self) -> Result<(), FailureError> {
+        let fbs_tx_hashes = cast!(self.message.tx_hashes())?;
+        if fbs_tx_hashes.len() > MAX_RELAY_TXS_NUM_PER_BATCH {
+            debug!(
+                target: "relay",
+                "peer {} requests {} transactions, more than {}",
+                self.peer,
+                fbs_tx_hashes.len(),
+                MAX_RELAY_TXS_NUM_PER_BATCH,
+            );
+            cast!(None)?;
+        }
+        if !self.relayer.check_rate_limit(
+            &self.relayer.get_transactions_limiter,
+            self.peer,
+            fbs_tx_hashes.len(),
+        ) {
+            return Ok(());
+        }
+        let tx_hashes = fbs_tx_hashes
+            .iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<H256>, _>>()?;
+        trace!(target: "relay", "{} request {} transactions", self.peer, tx_hashes.len());
+
+        let transactions = {
+            let chain_state = self.relayer.shared.chain_state().lock();
+            tx_hashes
+                .iter()
+                .filter_map(|tx_hash| {
+                    chain_state
+                        .get_entry_from_pool(&ProposalShortId::from_tx_hash(tx_hash))
+                        .and_then(|entry| entry.cycles.map(|cycles| (entry.transaction, cycles)))
+                })
+                .collect::<Vec<_>>()
+        };
+        if transactions.len() < tx_hashes.len() {
+            debug!(
+                target: "relay",
+                "{} request {} transactions, {} not found or without cycles",
+                self.peer,
+                tx_hashes.len(),
+                tx_hashes.len() - transactions.len(),
+            );
+        }
+        if !transactions.is_empty() {
+            let fbb = &mut FlatBufferBuilder::new();
+            let message = RelayMessage::build_transactions(fbb, &transactions);
+            fbb.finish(message, None);
+            self.nc
+                .send_message_to(self.peer, fbb.finished_data().into());
+        }
+        Ok(())
+    }
+}
@@ -7,10 +7,13 @@ mod error;
 mod get_block_proposal_process;
 mod get_block_transactions_process;
 mod get_transaction_process;
+mod get_transactions_process;
 #[cfg(test)]
 mod tests;
 mod transaction_hash_process;
+mod transaction_hashes_process;
 mod transaction_process;
+mod transactions_process;
 
 use self::block_proposal_process::BlockProposalProcess;
 use self::block_transactions_process::BlockTransactionsProcess;
@@ -20,16 +23,25 @@ pub use self::error::Error;
 use self::get_block_proposal_process::GetBlockProposalProcess;
 use self::get_block_transactions_process::GetBlockTransactionsProcess;
 use self::get_transaction_process::GetTransactionProcess;
+use self::get_transactions_process::GetTransactionsProcess;
 use self::transaction_hash_process::TransactionHashProcess;
+use self::transaction_hashes_process::TransactionHashesProcess;
 use self::transaction_process::TransactionProcess;
+use self::transactions_process::TransactionsProcess;
+use crate::rate_limiter::{RateLimit, RateLimiter};
 use crate::relayer::compact_block::ShortTransactionID;
 use crate::types::{Peers, SyncSharedState};
-use crate::BAD_MESSAGE_BAN_TIME;
+use crate::{
+    is_block_pruned_error, parse_protocol_version, BAD_MESSAGE_BAN_TIME, CAPABILITY_COMPACT_BLOCK,
+    DEFAULT_GET_TRANSACTIONS_RATE_LIMIT, DEFAULT_TRANSACTIONS_RATE_LIMIT,
+    RATE_LIMIT_EXCEEDED_SCORE, TX_BATCH_PROTOCOL_VERSION,
+};
 use ckb_chain::chain::ChainController;
 use ckb_core::block::{Block, BlockBuilder};
 use ckb_core::transaction::{ProposalShortId, Transaction};
 use ckb_core::uncle::UncleBlock;
 use ckb_network::{CKBProtocolContext, CKBProtocolHandler, PeerIndex};
+use ckb_notify::NotifyController;
 use ckb_protocol::{
     cast, get_root, short_transaction_id, short_transaction_id_keys, RelayMessage, RelayPayload,
 };
@@ -49,10 +61,14 @@ use std::time::{Duration, Instant};
 
 pub const TX_PROPOSAL_TOKEN: u64 = 0;
 pub const ASK_FOR_TXS_TOKEN: u64 = 1;
+pub const TX_HASHES_TOKEN: u64 = 2;
 
 pub const MAX_RELAY_PEERS: usize = 128;
+// Transactions announced, requested or relayed in a single message
+pub const MAX_RELAY_TXS_NUM_PER_BATCH: usize = 1000;
 pub const TX_FILTER_SIZE: usize = 50000;
 pub const TX_ASKED_SIZE: usize = TX_FILTER_SIZE;
+pub const RECENT_TXS_SIZE: usize = 5000;
 
 pub struct Relayer<CS> {
     chain: ChainController,
@@ -60,6 +76,9 @@ pub struct Relayer<CS> {
     pub(crate) state: Arc<RelayState>,
     // TODO refactor shared Peers struct with Synchronizer
     peers: Arc<Peers>,
+    transactions_limiter: Arc<RateLimiter>,
+    get_transactions_limiter: Arc<RateLimiter>,
+    notify_controller: Option<NotifyController>,
 }
 
 impl<CS: ChainStore> Clone for Relayer<CS> {
@@ -69,6 +88,9 @@ impl<CS: ChainStore> Clone for Relayer<CS> {
             shared: Arc::clone(&self.shared),
             state: Arc::clone(&self.state),
             peers: Arc::clone(&self.peers),
+            transactions_limiter: Arc::clone(&self.transactions_limiter),
+            get_transactions_limiter: Arc::clone(&self.get_transactions_limiter),
+            notify_controller: self.notify_controller.clone(),
         }
     }
 }
@@ -84,15 +106,66 @@ impl<CS: ChainStore> Relayer<CS> {
             shared,
             state: Arc::new(RelayState::default()),
             peers,
+            transactions_limiter: Arc::new(RateLimiter::new(DEFAULT_TRANSACTIONS_RATE_LIMIT)),
+            get_transactions_limiter: Arc::new(RateLimiter::new(
+                DEFAULT_GET_TRANSACTIONS_RATE_LIMIT,
+            )),
+            notify_controller: None,
         }
     }
 
+    /// Publishes the relayed transactions accepted or rejected by the pool
+    pub fn notify_controller(mut self, notify_controller: NotifyController) -> Self {
+        self.notify_controller = Some(notify_controller);
+        self
+    }
+
+    /// Limits the transactions relayed by a single peer
+    pub fn transactions_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.transactions_limiter = Arc::new(RateLimiter::new(limit));
+        self
+    }
+
+    /// Limits the transactions requested by a single peer
+    pub fn get_transactions_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.get_transactions_limiter = Arc::new(RateLimiter::new(limit));
+        self
+    }
+
+    /// Takes `count` tokens of `limiter` for `peer`. The synchronizer bans the
+    /// peers scored here once over the threshold.
+    pub(crate) fn check_rate_limit(
+        &self,
+        limiter: &RateLimiter,
+        peer: PeerIndex,
+        count: usize,
+    ) -> bool {
+        if limiter.check(peer, count) {
+            return true;
+        }
+        debug!(target: "relay", "peer {} exceeds the rate limit", peer);
+        self.peers.misbehavior(peer, RATE_LIMIT_EXCEEDED_SCORE);
+        false
+    }
+
     fn try_process(
         &self,
         nc: &CKBProtocolContext,
         peer: PeerIndex,
         message: RelayMessage,
     ) -> Result<(), FailureError> {
+        // The batch messages of version 2, a version 1 peer can not send them
+        match message.payload_type() {
+            RelayPayload::RelayTransactionHashes
+            | RelayPayload::GetRelayTransactions
+            | RelayPayload::RelayTransactions
+                if !self.supports_tx_batch(peer) =>
+            {
+                cast!(None)?;
+            }
+            _ => (),
+        }
+
         match message.payload_type() {
             RelayPayload::CompactBlock => {
                 CompactBlockProcess::new(
@@ -161,6 +234,33 @@ impl<CS: ChainStore> Relayer<CS> {
                 BlockProposalProcess::new(&cast!(message.payload_as_block_proposal())?, self)
                     .execute()?;
             }
+            RelayPayload::RelayTransactionHashes => {
+                TransactionHashesProcess::new(
+                    &cast!(message.payload_as_relay_transaction_hashes())?,
+                    self,
+                    nc,
+                    peer,
+                )
+                .execute()?;
+            }
+            RelayPayload::GetRelayTransactions => {
+                GetTransactionsProcess::new(
+                    &cast!(message.payload_as_get_relay_transactions())?,
+                    self,
+                    nc,
+                    peer,
+                )
+                .execute()?;
+            }
+            RelayPayload::RelayTransactions => {
+                TransactionsProcess::new(
+                    &cast!(message.payload_as_relay_transactions())?,
+                    self,
+                    nc,
+                    peer,
+                )
+                .execute()?;
+            }
             RelayPayload::NONE => {
                 cast!(None)?;
             }
@@ -171,7 +271,10 @@ impl<CS: ChainStore> Relayer<CS> {
     fn process(&self, nc: &CKBProtocolContext, peer: PeerIndex, message: RelayMessage) {
         if let Err(err) = self.try_process(nc, peer, message) {
             debug!(target: "relay", "try_process error {}", err);
-            nc.ban_peer(peer, BAD_MESSAGE_BAN_TIME);
+            // the peer can not know the bodies we pruned
+            if !is_block_pruned_error(&err) {
+                nc.ban_peer(peer, BAD_MESSAGE_BAN_TIME);
+            }
         }
     }
 
@@ -202,9 +305,14 @@ impl<CS: ChainStore> Relayer<CS> {
     }
 
     pub fn accept_block(&self, nc: &CKBProtocolContext, peer: PeerIndex, block: &Arc<Block>) {
+        let old_tip = self.shared.tip_header();
         let ret = self.chain.process_block(Arc::clone(&block));
 
         if ret.is_ok() {
+            self.shared.record_processed_block(&old_tip, &block);
+            if self.shared.tip_header().hash() == block.header().hash() {
+                nc.record_best_block(peer);
+            }
             debug!(target: "relay", "[block_relay] relayer accept_block {} {}", block.header().hash(), unix_time_as_millis());
             let block_hash = block.header().hash();
             self.shared.remove_header_view(&block_hash);
@@ -217,7 +325,11 @@ impl<CS: ChainStore> Relayer<CS> {
                 .connected_peers()
                 .into_iter()
                 .filter(|target_peer| {
-                    known_blocks.insert(*target_peer, block_hash.clone()) && (peer != *target_peer)
+                    known_blocks.insert(*target_peer, block_hash.clone())
+                        && (peer != *target_peer)
+                        && self
+                            .peers
+                            .has_capabilities(*target_peer, CAPABILITY_COMPACT_BLOCK)
                 })
                 .take(MAX_RELAY_PEERS)
                 .collect();
@@ -254,7 +366,7 @@ impl<CS: ChainStore> Relayer<CS> {
             })
             .collect();
 
-        if short_ids_set.is_empty() {
+        if !short_ids_set.is_empty() {
             let tx_pool = chain_state.tx_pool();
             for entry in tx_pool.staging_txs_iter() {
                 let short_id = short_transaction_id(key0, key1, &entry.transaction.witness_hash());
@@ -269,6 +381,21 @@ impl<CS: ChainStore> Relayer<CS> {
             }
         }
 
+        // Relayed txs which did not make it into the staging pool, e.g. orphans or txs
+        // proposed by other miners
+        if !short_ids_set.is_empty() {
+            for tx in self.state.recent_txs.lock().iter().map(|(_, tx)| tx) {
+                let short_id = short_transaction_id(key0, key1, &tx.witness_hash());
+                if short_ids_set.remove(&short_id) {
+                    txs_map.insert(short_id, tx.clone());
+
+                    if short_ids_set.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+
         let txs_len = compact_block.prefilled_transactions.len() + compact_block.short_ids.len();
         let mut block_transactions: Vec<Option<Transaction>> = Vec::with_capacity(txs_len);
 
@@ -371,9 +498,18 @@ impl<CS: ChainStore> Relayer<CS> {
                     peer,
                 );
             }
-            for tx_hash in tx_hashes {
+            if !self.supports_tx_batch(*peer) {
+                for tx_hash in &tx_hashes {
+                    let fbb = &mut FlatBufferBuilder::new();
+                    let message = RelayMessage::build_get_transaction(fbb, tx_hash);
+                    fbb.finish(message, None);
+                    nc.send_message_to(*peer, fbb.finished_data().into());
+                }
+                continue;
+            }
+            for tx_hashes in tx_hashes.chunks(MAX_RELAY_TXS_NUM_PER_BATCH) {
                 let fbb = &mut FlatBufferBuilder::new();
-                let message = RelayMessage::build_get_transaction(fbb, &tx_hash);
+                let message = RelayMessage::build_get_transactions(fbb, tx_hashes);
                 fbb.finish(message, None);
                 let data = fbb.finished_data().into();
                 nc.send_message_to(*peer, data);
@@ -381,15 +517,68 @@ impl<CS: ChainStore> Relayer<CS> {
         }
     }
 
+    // Announce the hashes of the newly accepted transactions to the peers
+    // which do not know them yet, batched per peer
+    pub fn send_tx_hashes(&self, nc: &CKBProtocolContext) {
+        let announcements = self.state.take_tx_announcements();
+        if announcements.is_empty() {
+            return;
+        }
+
+        let connected_peers = nc.connected_peers();
+        let mut peer_tx_hashes: FnvHashMap<PeerIndex, Vec<H256>> = FnvHashMap::default();
+        {
+            let mut known_txs = self.peers.known_txs.lock();
+            for (source, tx_hash) in announcements {
+                for target_peer in connected_peers
+                    .iter()
+                    .filter(|target_peer| {
+                        Some(**target_peer) != source
+                            && known_txs.insert(**target_peer, tx_hash.clone())
+                    })
+                    .take(MAX_RELAY_PEERS)
+                {
+                    peer_tx_hashes
+                        .entry(*target_peer)
+                        .or_insert_with(Vec::new)
+                        .push(tx_hash.clone());
+                }
+            }
+        }
+
+        for (peer, tx_hashes) in peer_tx_hashes {
+            if !self.supports_tx_batch(peer) {
+                for tx_hash in &tx_hashes {
+                    let fbb = &mut FlatBufferBuilder::new();
+                    let message = RelayMessage::build_transaction_hash(fbb, tx_hash);
+                    fbb.finish(message, None);
+                    nc.send_message_to(peer, fbb.finished_data().into());
+                }
+                continue;
+            }
+            for tx_hashes in tx_hashes.chunks(MAX_RELAY_TXS_NUM_PER_BATCH) {
+                let fbb = &mut FlatBufferBuilder::new();
+                let message = RelayMessage::build_transaction_hashes(fbb, tx_hashes);
+                fbb.finish(message, None);
+                nc.send_message_to(peer, fbb.finished_data().into());
+            }
+        }
+    }
+
     pub fn peers(&self) -> Arc<Peers> {
         Arc::clone(&self.peers)
     }
+
+    fn supports_tx_batch(&self, peer: PeerIndex) -> bool {
+        self.peers.features(peer).relay_version >= TX_BATCH_PROTOCOL_VERSION
+    }
 }
 
 impl<CS: ChainStore> CKBProtocolHandler for Relayer<CS> {
     fn init(&mut self, nc: Box<dyn CKBProtocolContext>) {
         nc.set_notify(Duration::from_millis(100), TX_PROPOSAL_TOKEN);
         nc.set_notify(Duration::from_millis(100), ASK_FOR_TXS_TOKEN);
+        nc.set_notify(Duration::from_millis(100), TX_HASHES_TOKEN);
     }
 
     fn received(
@@ -418,18 +607,21 @@ impl<CS: ChainStore> CKBProtocolHandler for Relayer<CS> {
         version: &str,
     ) {
         info!(target: "relay", "RelayProtocol({}).connected peer={}", version, peer_index);
-        // do nothing
+        self.peers
+            .set_relay_version(peer_index, parse_protocol_version(version));
     }
 
     fn disconnected(&mut self, _nc: Box<dyn CKBProtocolContext>, peer_index: PeerIndex) {
         info!(target: "relay", "RelayProtocol.disconnected peer={}", peer_index);
-        // TODO
+        self.transactions_limiter.remove(peer_index);
+        self.get_transactions_limiter.remove(peer_index);
     }
 
     fn notify(&mut self, nc: Box<dyn CKBProtocolContext>, token: u64) {
         match token {
             TX_PROPOSAL_TOKEN => self.prune_tx_proposal_request(nc.as_ref()),
             ASK_FOR_TXS_TOKEN => self.ask_for_txs(nc.as_ref()),
+            TX_HASHES_TOKEN => self.send_tx_hashes(nc.as_ref()),
             _ => unreachable!(),
         }
     }
@@ -441,6 +633,10 @@ pub struct RelayState {
     pub pending_proposals_request: Mutex<FnvHashMap<ProposalShortId, FnvHashSet<PeerIndex>>>,
     pub tx_filter: Mutex<LruCache<H256, ()>>,
     pub tx_already_asked: Mutex<LruCache<H256, Instant>>,
+    /// Recently relayed txs, used to reconstruct compact blocks
+    pub recent_txs: Mutex<LruCache<H256, Transaction>>,
+    /// Accepted transactions waiting to be announced, with the peer which relayed them
+    pub tx_hashes_to_announce: Mutex<Vec<(Option<PeerIndex>, H256)>>,
 }
 
 impl Default for RelayState {
@@ -451,6 +647,8 @@ impl Default for RelayState {
             pending_proposals_request: Mutex::new(FnvHashMap::default()),
             tx_filter: Mutex::new(LruCache::new(TX_FILTER_SIZE)),
             tx_already_asked: Mutex::new(LruCache::new(TX_ASKED_SIZE)),
+            recent_txs: Mutex::new(LruCache::new(RECENT_TXS_SIZE)),
+            tx_hashes_to_announce: Mutex::new(Vec::new()),
         }
     }
 }
@@ -461,7 +659,21 @@ impl RelayState {
         self.tx_filter.lock().insert(hash, ());
     }
 
+    fn insert_recent_tx(&self, tx: &Transaction) {
+        self.recent_txs
+            .lock()
+            .insert(tx.hash().to_owned(), tx.clone());
+    }
+
     fn already_known(&self, hash: &H256) -> bool {
         self.tx_filter.lock().contains_key(hash)
     }
+
+    fn announce_tx(&self, source: Option<PeerIndex>, hash: H256) {
+        self.tx_hashes_to_announce.lock().push((source, hash));
+    }
+
+    fn take_tx_announcements(&self) -> Vec<(Option<PeerIndex>, H256)> {
+        ::std::mem::replace(&mut *self.tx_hashes_to_announce.lock(), Vec::new())
+    }
 }
@@ -0,0 +1,55 @@
+use super::helper::{build_chain, new_transaction, MockProtocolContext};
+use crate::relayer::get_transactions_process::GetTransactionsProcess;
+use crate::{RateLimit, PROTOCOL_VERSION_1, PROTOCOL_VERSION_2, RATE_LIMIT_EXCEEDED_SCORE};
+use ckb_network::PeerIndex;
+use ckb_protocol::{get_root, RelayMessage};
+use flatbuffers::FlatBufferBuilder;
+
+#[test]
+fn test_get_transactions_rate_limit() {
+    let relayer = build_chain(5).get_transactions_rate_limit(RateLimit::new(1, 2));
+    let nc = MockProtocolContext::default();
+    let peer: PeerIndex = 1.into();
+    let tx_hashes: Vec<_> = (0..3)
+        .map(|i| new_transaction(&relayer, i).hash().to_owned())
+        .collect();
+
+    let fbb = &mut FlatBufferBuilder::new();
+    let message = RelayMessage::build_get_transactions(fbb, &tx_hashes);
+    fbb.finish(message, None);
+    let message = get_root::<RelayMessage>(fbb.finished_data()).unwrap();
+    GetTransactionsProcess::new(
+        &message.payload_as_get_relay_transactions().unwrap(),
+        &relayer,
+        &nc,
+        peer,
+    )
+    .execute()
+    .unwrap();
+
+    assert_eq!(
+        relayer.peers.misbehavior_score(peer),
+        RATE_LIMIT_EXCEEDED_SCORE
+    );
+    assert!(nc.sent.lock().is_empty());
+}
+
+#[test]
+fn test_get_transactions_needs_relay_version_2() {
+    let relayer = build_chain(5);
+    let nc = MockProtocolContext::default();
+    let peer: PeerIndex = 1.into();
+    let tx_hashes = vec![new_transaction(&relayer, 0).hash().to_owned()];
+
+    let fbb = &mut FlatBufferBuilder::new();
+    let message = RelayMessage::build_get_transactions(fbb, &tx_hashes);
+    fbb.finish(message, None);
+    let message = get_root::<RelayMessage>(fbb.finished_data()).unwrap();
+
+    // A version 1 peer can not send the batch messages
+    relayer.peers.set_relay_version(peer, PROTOCOL_VERSION_1);
+    assert!(relayer.try_process(&nc, peer, message).is_err());
+
+    relayer.peers.set_relay_version(peer, PROTOCOL_VERSION_2);
+    assert!(relayer.try_process(&nc, peer, message).is_ok());
+}
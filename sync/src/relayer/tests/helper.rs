@@ -0,0 +1,167 @@
+use crate::{NetworkProtocol, Relayer, SyncSharedState};
+use ckb_chain::chain::ChainBuilder;
+use ckb_chain_spec::consensus::Consensus;
+use ckb_core::block::{Block, BlockBuilder};
+use ckb_core::header::HeaderBuilder;
+use ckb_core::script::Script;
+use ckb_core::transaction::{CellInput, CellOutput, Transaction, TransactionBuilder};
+use ckb_core::{capacity_bytes, BlockNumber, Bytes, Capacity};
+use ckb_db::memorydb::MemoryKeyValueDB;
+use ckb_network::{
+    Behaviour, CKBProtocolContext, Peer, PeerId, PeerIndex, ProtocolId, SessionType, TargetSession,
+};
+use ckb_notify::NotifyService;
+use ckb_shared::shared::{Shared, SharedBuilder};
+use ckb_store::ChainKVStore;
+use ckb_traits::ChainProvider;
+use ckb_util::Mutex;
+use faketime::unix_time_as_millis;
+use fnv::FnvHashSet;
+use numext_fixed_uint::U256;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn new_header_builder(
+    shared: &Shared<ChainKVStore<MemoryKeyValueDB>>,
+    parent: &Block,
+) -> HeaderBuilder {
+    let parent_hash = parent.header().hash();
+    let parent_epoch = shared.get_epoch_ext(&parent_hash).unwrap();
+    let epoch = shared
+        .next_epoch_ext(&parent_epoch, parent.header())
+        .unwrap_or(parent_epoch);
+    HeaderBuilder::default()
+        .parent_hash(parent_hash.to_owned())
+        .number(parent.header().number() + 1)
+        .timestamp(parent.header().timestamp() + 1)
+        .epoch(epoch.number())
+        .difficulty(epoch.difficulty().to_owned())
+}
+
+pub(crate) fn new_transaction(
+    relayer: &Relayer<ChainKVStore<MemoryKeyValueDB>>,
+    index: usize,
+) -> Transaction {
+    let previous_output = {
+        let chain_state = relayer.shared.shared().chain_state().lock();
+        let tip_hash = chain_state.tip_hash();
+        let block = relayer
+            .shared
+            .shared()
+            .block(&tip_hash)
+            .expect("getting tip block");
+        let cellbase = block
+            .transactions()
+            .first()
+            .expect("getting cellbase from tip block");
+        cellbase.output_pts()[0].clone()
+    };
+
+    TransactionBuilder::default()
+        .input(CellInput::new(previous_output, 0, Default::default()))
+        .output(CellOutput::new(
+            Capacity::bytes(500 + index).unwrap(), // use capacity to identify transactions
+            Default::default(),
+            Default::default(),
+            None,
+        ))
+        .build()
+}
+
+pub(crate) fn build_chain(tip: BlockNumber) -> Relayer<ChainKVStore<MemoryKeyValueDB>> {
+    let shared = {
+        let genesis = BlockBuilder::from_header_builder(
+            HeaderBuilder::default()
+                .timestamp(unix_time_as_millis())
+                .difficulty(U256::from(1000u64)),
+        )
+        .build();
+        let consensus = Consensus::default()
+            .set_genesis_block(genesis)
+            .set_cellbase_maturity(0);
+        SharedBuilder::<MemoryKeyValueDB>::new()
+            .consensus(consensus)
+            .build()
+            .unwrap()
+    };
+    let chain_controller = {
+        let notify_controller = NotifyService::default().start::<&str>(None);
+        let chain_service = ChainBuilder::new(shared.clone(), notify_controller)
+            .verification(false)
+            .build();
+        chain_service.start::<&str>(None)
+    };
+
+    // Build 1 ~ (tip-1) heights
+    for i in 0..tip {
+        let parent = shared
+            .block_hash(i)
+            .and_then(|block_hash| shared.block(&block_hash))
+            .unwrap();
+        let cellbase = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(parent.header().number() + 1))
+            .output(CellOutput::new(
+                capacity_bytes!(50000),
+                Bytes::default(),
+                Script::always_success(),
+                None,
+            ))
+            .build();
+        let block = BlockBuilder::from_header_builder(new_header_builder(&shared, &parent))
+            .transaction(cellbase)
+            .build();
+        chain_controller
+            .process_block(Arc::new(block))
+            .expect("processing block should be ok");
+    }
+
+    let sync_shared_state = Arc::new(SyncSharedState::new(shared));
+    Relayer::new(
+        chain_controller,
+        sync_shared_state,
+        Arc::new(Default::default()),
+    )
+}
+
+/// Records the peers banned or disconnected by the relayer, every peer is an
+/// outbound one.
+#[derive(Default)]
+pub(crate) struct MockProtocolContext {
+    pub banned: Mutex<FnvHashSet<PeerIndex>>,
+    pub disconnected: Mutex<FnvHashSet<PeerIndex>>,
+    pub sent: Mutex<Vec<(PeerIndex, bytes::Bytes)>>,
+}
+
+impl CKBProtocolContext for MockProtocolContext {
+    fn set_notify(&self, _interval: Duration, _token: u64) {}
+    fn send_message(&self, _proto_id: ProtocolId, peer_index: PeerIndex, data: bytes::Bytes) {
+        self.sent.lock().push((peer_index, data));
+    }
+    fn send_message_to(&self, peer_index: PeerIndex, data: bytes::Bytes) {
+        self.sent.lock().push((peer_index, data));
+    }
+    fn filter_broadcast(&self, _target: TargetSession, _data: bytes::Bytes) {}
+    fn disconnect(&self, peer_index: PeerIndex) {
+        self.disconnected.lock().insert(peer_index);
+    }
+    fn get_peer(&self, peer_index: PeerIndex) -> Option<Peer> {
+        Some(Peer::new(
+            peer_index,
+            SessionType::Outbound,
+            PeerId::random(),
+            "/ip4/127.0.0.1".parse().expect("parse multiaddr"),
+            false,
+        ))
+    }
+    fn connected_peers(&self) -> Vec<PeerIndex> {
+        Vec::new()
+    }
+    fn report_peer(&self, _peer_index: PeerIndex, _behaviour: Behaviour) {}
+    fn ban_peer(&self, peer_index: PeerIndex, _timeout: Duration) {
+        self.banned.lock().insert(peer_index);
+    }
+    fn record_best_block(&self, _peer_index: PeerIndex) {}
+    fn protocol_id(&self) -> ProtocolId {
+        NetworkProtocol::RELAY.into()
+    }
+}
@@ -1,2 +1,6 @@
 mod compact_block_process;
 mod compact_block_verifier;
+mod get_transactions_process;
+mod helper;
+mod transaction_process;
+mod transactions_process;
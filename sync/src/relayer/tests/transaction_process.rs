@@ -0,0 +1,30 @@
+use super::helper::{build_chain, new_transaction, MockProtocolContext};
+use crate::relayer::transaction_process::process_transaction;
+use ckb_core::transaction::{CellOutput, TransactionBuilder};
+use ckb_core::{capacity_bytes, Capacity};
+use ckb_network::PeerIndex;
+
+#[test]
+fn test_recent_txs_only_accepted() {
+    let relayer = build_chain(5);
+    let nc = MockProtocolContext::default();
+    let peer: PeerIndex = 1.into();
+
+    // spends more than the input holds
+    let invalid = TransactionBuilder::from_transaction(new_transaction(&relayer, 0))
+        .outputs_clear()
+        .output(CellOutput::new(
+            capacity_bytes!(60000),
+            Default::default(),
+            Default::default(),
+            None,
+        ))
+        .build();
+    process_transaction(&relayer, &nc, peer, invalid.clone(), 0);
+    assert!(!relayer.state.recent_txs.lock().contains_key(invalid.hash()));
+    assert!(nc.banned.lock().contains(&peer));
+
+    let valid = new_transaction(&relayer, 1);
+    process_transaction(&relayer, &nc, peer, valid.clone(), 0);
+    assert!(relayer.state.recent_txs.lock().contains_key(valid.hash()));
+}
@@ -0,0 +1,42 @@
+use super::helper::{build_chain, new_transaction, MockProtocolContext};
+use crate::relayer::transactions_process::TransactionsProcess;
+use crate::types::PeerState;
+use crate::UNREQUESTED_TX_SCORE;
+use ckb_network::PeerIndex;
+use ckb_protocol::{get_root, RelayMessage};
+use flatbuffers::FlatBufferBuilder;
+
+#[test]
+fn test_drop_unrequested_txs() {
+    let relayer = build_chain(5);
+    let nc = MockProtocolContext::default();
+    let peer: PeerIndex = 1.into();
+    let requested = new_transaction(&relayer, 0);
+    let unrequested = new_transaction(&relayer, 1);
+    relayer
+        .peers
+        .state
+        .write()
+        .entry(peer)
+        .or_insert_with(|| PeerState::new(None, Default::default()))
+        .add_ask_for_tx(requested.hash().to_owned(), None);
+
+    let fbb = &mut FlatBufferBuilder::new();
+    let message =
+        RelayMessage::build_transactions(fbb, &[(unrequested.clone(), 0), (requested.clone(), 0)]);
+    fbb.finish(message, None);
+    let message = get_root::<RelayMessage>(fbb.finished_data()).unwrap();
+    TransactionsProcess::new(
+        &message.payload_as_relay_transactions().unwrap(),
+        &relayer,
+        &nc,
+        peer,
+    )
+    .execute()
+    .unwrap();
+
+    assert!(!relayer.state.already_known(unrequested.hash()));
+    assert!(relayer.state.already_known(requested.hash()));
+    assert!(!relayer.peers.state.read()[&peer].asked_for_tx(requested.hash()));
+    assert_eq!(relayer.peers.misbehavior_score(peer), UNREQUESTED_TX_SCORE);
+}
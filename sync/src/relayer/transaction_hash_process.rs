@@ -37,60 +37,63 @@ impl<'a, CS: ChainStore> TransactionHashProcess<'a, CS> {
         }
 
         let tx_hash: H256 = (*self.message).try_into()?;
-        let short_id = ProposalShortId::from_tx_hash(&tx_hash);
-        if self.relayer.state.already_known(&tx_hash) {
-            debug!(
-                target: "relay",
-                "transaction({}) from {} already known, ignore it",
-                tx_hash,
-                self.peer,
-            );
-        } else if self
-            .relayer
-            .shared
-            .chain_state()
-            .lock()
-            .tx_pool()
-            .get_entry(&short_id)
-            .is_some()
+        process_transaction_hash(self.relayer, self.peer, tx_hash);
+        Ok(())
+    }
+}
+
+/// Handles a transaction hash announced by `peer`, the transaction is asked
+/// for later unless it is already known
+pub(crate) fn process_transaction_hash<CS: ChainStore>(
+    relayer: &Relayer<CS>,
+    peer: PeerIndex,
+    tx_hash: H256,
+) {
+    // The peer knows it, never announce it back
+    relayer.peers.known_txs.lock().insert(peer, tx_hash.clone());
+    let short_id = ProposalShortId::from_tx_hash(&tx_hash);
+    if relayer.state.already_known(&tx_hash) {
+        debug!(
+            target: "relay",
+            "transaction({}) from {} already known, ignore it",
+            tx_hash,
+            peer,
+        );
+    } else if relayer
+        .shared
+        .chain_state()
+        .lock()
+        .tx_pool()
+        .get_entry(&short_id)
+        .is_some()
+    {
+        debug!(
+            target: "relay",
+            "transaction({}) from {} already in transaction pool, ignore it",
+            tx_hash,
+            peer,
+        );
+        relayer.state.insert_tx(tx_hash.clone());
+    } else {
+        debug!(
+            target: "relay",
+            "transaction({}) from {} not known, get it from the peer",
+            tx_hash,
+            peer,
+        );
+        let last_ask_timeout = relayer.state.tx_already_asked.lock().get(&tx_hash).cloned();
+        if let Some(next_ask_timeout) = relayer
+            .peers
+            .state
+            .write()
+            .get_mut(&peer)
+            .and_then(|peer_state| peer_state.add_ask_for_tx(tx_hash.clone(), last_ask_timeout))
         {
-            debug!(
-                target: "relay",
-                "transaction({}) from {} already in transaction pool, ignore it",
-                tx_hash,
-                self.peer,
-            );
-            self.relayer.state.insert_tx(tx_hash.clone());
-        } else {
-            debug!(
-                target: "relay",
-                "transaction({}) from {} not known, get it from the peer",
-                tx_hash,
-                self.peer,
-            );
-            let last_ask_timeout = self
-                .relayer
+            relayer
                 .state
                 .tx_already_asked
                 .lock()
-                .get(&tx_hash)
-                .cloned();
-            if let Some(next_ask_timeout) = self
-                .relayer
-                .peers
-                .state
-                .write()
-                .get_mut(&self.peer)
-                .and_then(|peer_state| peer_state.add_ask_for_tx(tx_hash.clone(), last_ask_timeout))
-            {
-                self.relayer
-                    .state
-                    .tx_already_asked
-                    .lock()
-                    .insert(tx_hash.clone(), next_ask_timeout);
-            }
+                .insert(tx_hash.clone(), next_ask_timeout);
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,54 @@
+use crate::relayer::transaction_hash_process::process_transaction_hash;
+use crate::relayer::{Relayer, MAX_RELAY_TXS_NUM_PER_BATCH};
+use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_protocol::{cast, RelayTransactionHashes as FbsRelayTransactionHashes};
+use ckb_store::ChainStore;
+use failure::Error as FailureError;
+use log::debug;
+use std::convert::TryInto;
+
+pub struct TransactionHashesProcess<'a, CS> {
+    message: &'a FbsRelayTransactionHashes<'a>,
+    relayer: &'a Relayer<CS>,
+    _nc: &'a CKBProtocolContext,
+    peer: PeerIndex,
+}
+
+impl<'a, CS: ChainStore> TransactionHashesProcess<'a, CS> {
+    pub fn new(
+        message: &'a FbsRelayTransactionHashes,
+        relayer: &'a Relayer<CS>,
+        nc: &'a CKBProtocolContext,
+        peer: PeerIndex,
+    ) -> Self {
+        TransactionHashesProcess {
+            message,
+            relayer,
+            _nc: nc,
+            peer,
+        }
+    }
+
+    pub fn execute(self) -> Result<(), FailureError> {
+        if self.relayer.shared.is_initial_block_download() {
+            debug!(target: "relay", "Do not ask for transactions when initial block download");
+            return Ok(());
+        }
+
+        let tx_hashes = cast!(self.message.tx_hashes())?;
+        if tx_hashes.len() > MAX_RELAY_TXS_NUM_PER_BATCH {
+            debug!(
+                target: "relay",
+                "peer {} announces {} transactions, more than {}",
+                self.peer,
+                tx_hashes.len(),
+                MAX_RELAY_TXS_NUM_PER_BATCH,
+            );
+            cast!(None)?;
+        }
+        for tx_hash in tx_hashes {
+            process_transaction_hash(self.relayer, self.peer, tx_hash.try_into()?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::relayer::transaction_process::process_transaction;
+use crate::relayer::{Relayer, MAX_RELAY_TXS_NUM_PER_BATCH};
+use crate::UNREQUESTED_TX_SCORE;
+use ckb_core::{transaction::Transaction, Cycle};
+use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_protocol::{cast, FlatbuffersVectorIterator, RelayTransactions as FbsRelayTransactions};
+use ckb_store::ChainStore;
+use failure::Error as FailureError;
+use log::debug;
+use std::convert::TryInto;
+
+pub struct TransactionsProcess<'a, CS> {
+    message: &'a FbsRelayTransactions<'a>,
+    relayer: &'a Relayer<CS>,
+    nc: &'a CKBProtocolContext,
+    peer: PeerIndex,
+}
+
+impl<'a, CS: ChainStore> TransactionsProcess<'a, CS> {
+    pub fn new(
+        message: &'a FbsRelayTransactions,
+        relayer: &'a Relayer<CS>,
+        nc: &'a CKBProtocolContext,
+        peer: PeerIndex,
+    ) -> Self {
+        TransactionsProcess {
+            message,
+            relayer,
+            nc,
+            peer,
+        }
+    }
+
+    pub fn execute(self) -> Result<(), FailureError> {
+        if self.relayer.shared.is_initial_block_download() {
+            debug!(target: "relay", "Do not accept relayed transactions when initial block download");
+            return Ok(());
+        }
+
+        let fbs_txs = cast!(self.message.transactions())?;
+        if fbs_txs.len() > MAX_RELAY_TXS_NUM_PER_BATCH {
+            debug!(
+                target: "relay",
+                "peer {} relays {} transactions, more than {}",
+                self.peer,
+                fbs_txs.len(),
+                MAX_RELAY_TXS_NUM_PER_BATCH,
+            );
+            cast!(None)?;
+        }
+        if !self.relayer.check_rate_limit(
+            &self.relayer.transactions_limiter,
+            self.peer,
+            fbs_txs.len(),
+        ) {
+            return Ok(());
+        }
+        let txs = FlatbuffersVectorIterator::new(fbs_txs)
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<(Transaction, Cycle)>, FailureError>>()?;
+        for (tx, relay_cycles) in txs {
+            // Only the txs asked for by getdata are relayed in batches
+            let requested = self
+                .relayer
+                .peers
+                .state
+                .read()
+                .get(&self.peer)
+                .map_or(false, |state| state.asked_for_tx(tx.hash()));
+            if !requested {
+                debug!(target: "relay", "peer {} relays unrequested transaction {:#x}", self.peer, tx.hash());
+                self.relayer
+                    .peers
+                    .misbehavior(self.peer, UNREQUESTED_TX_SCORE);
+                continue;
+            }
+            process_transaction(self.relayer, self.nc, self.peer, tx, relay_cycles);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::types::SyncSharedState;
+use ckb_core::header::{BlockNumber, Header};
+use ckb_store::ChainStore;
+use ckb_util::Mutex;
+use numext_fixed_hash::H256;
+
+// Only the ancestors of the target at the multiples of the step are kept, the
+// others are found by walking back from the next kept one
+const ANCESTOR_INDEX_STEP: BlockNumber = 1024;
+
+/// The chain of a trusted block, whose ancestors are accepted without running
+/// the scripts of their transactions.
+pub struct AssumeValid {
+    target: H256,
+    // Hashes of the ancestors of the target numbered at the multiples of
+    // `ANCESTOR_INDEX_STEP`, collected once the header of the target is known
+    index: Mutex<Option<Vec<H256>>>,
+}
+
+impl AssumeValid {
+    pub fn new(target: H256) -> Self {
+        AssumeValid {
+            target,
+            index: Mutex::new(None),
+        }
+    }
+
+    pub fn target(&self) -> &H256 {
+        &self.target
+    }
+
+    /// Whether `header` is the target or one of its ancestors, always false
+    /// until the headers down to the target have been downloaded.
+    pub fn contains<CS: ChainStore>(&self, shared: &SyncSharedState<CS>, header: &Header) -> bool {
+        let base = {
+            let mut index = self.index.lock();
+            if index.is_none() {
+                *index = self.collect_index(shared);
+            }
+            match index.as_ref() {
+                Some(index) => {
+                    // The nearest kept ancestor not below the header, at most
+                    // a step away from it
+                    let slot = (header.number() + ANCESTOR_INDEX_STEP - 1) / ANCESTOR_INDEX_STEP;
+                    index.get(slot as usize).unwrap_or(&self.target).to_owned()
+                }
+                None => return false,
+            }
+        };
+        shared
+            .get_ancestor(&base, header.number())
+            .map_or(false, |ancestor| ancestor.hash() == header.hash())
+    }
+
+    fn collect_index<CS: ChainStore>(&self, shared: &SyncSharedState<CS>) -> Option<Vec<H256>> {
+        let mut header = shared.get_header(&self.target)?;
+        let mut index = Vec::with_capacity((header.number() / ANCESTOR_INDEX_STEP) as usize + 1);
+        loop {
+            if header.number() % ANCESTOR_INDEX_STEP == 0 {
+                index.push(header.hash().to_owned());
+            }
+            if header.number() == 0 {
+                break;
+            }
+            header = shared.get_header(header.parent_hash())?;
+        }
+        index.reverse();
+        Some(index)
+    }
+}
@@ -1,14 +1,15 @@
 use crate::synchronizer::{BlockStatus, Synchronizer};
-use crate::types::HeaderView;
+use crate::types::{BlocksInflight, HeaderView};
 use crate::{
-    BLOCK_DOWNLOAD_TIMEOUT, BLOCK_DOWNLOAD_WINDOW, MAX_BLOCKS_IN_TRANSIT_PER_PEER,
-    PER_FETCH_BLOCK_LIMIT,
+    BLOCK_DOWNLOAD_TIMEOUT, BLOCK_DOWNLOAD_TIMEOUT_SCORE, BLOCK_DOWNLOAD_WINDOW,
+    BLOCK_STALLING_TIMEOUT, PER_FETCH_BLOCK_LIMIT,
 };
 use ckb_core::header::Header;
 use ckb_network::PeerIndex;
 use ckb_store::ChainStore;
 use ckb_util::try_option;
 use faketime::unix_time_as_millis;
+use fnv::{FnvHashMap, FnvHashSet};
 use log::{debug, trace};
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
@@ -41,18 +42,30 @@ where
         }
     }
     pub fn initial_and_check_inflight(&self) -> bool {
+        let config = &self.synchronizer.config;
         let mut blocks_inflight = self.synchronizer.peers.blocks_inflight.write();
+        let total: usize = blocks_inflight.values().map(BlocksInflight::len).sum();
         let inflight = blocks_inflight
             .entry(self.peer)
             .or_insert_with(Default::default);
 
         if inflight.timestamp < unix_time_as_millis().saturating_sub(BLOCK_DOWNLOAD_TIMEOUT) {
             trace!(target: "sync", "[block downloader] inflight block download timeout");
+            if !inflight.is_empty() {
+                self.synchronizer
+                    .peers
+                    .misbehavior(self.peer, BLOCK_DOWNLOAD_TIMEOUT_SCORE);
+            }
             inflight.clear();
+            inflight.shrink_window();
         }
+        inflight.clamp_window(
+            config.min_blocks_in_transit_per_peer,
+            config.max_blocks_in_transit_per_peer,
+        );
 
         // current peer block blocks_inflight reach limit
-        inflight.len() >= MAX_BLOCKS_IN_TRANSIT_PER_PEER
+        inflight.len() >= inflight.window || total >= config.max_blocks_in_transit
     }
 
     pub fn is_better_chain(&self, header: &HeaderView) -> bool {
@@ -145,6 +158,16 @@ where
             return None;
         }
 
+        if !self.synchronizer.has_min_chain_work(&best_known_header) {
+            debug!(
+                target: "sync",
+                "[block downloader] peer={} best_known_header {} has not enough work",
+                self.peer,
+                best_known_header.total_difficulty()
+            );
+            return None;
+        }
+
         if !self.is_known_best(&best_known_header) {
             return None;
         }
@@ -174,10 +197,34 @@ where
         let mut v_fetch = Vec::with_capacity(PER_FETCH_BLOCK_LIMIT);
 
         {
+            let now = unix_time_as_millis();
             let mut guard = self.synchronizer.peers.blocks_inflight.write();
-            let inflight = guard.get_mut(&self.peer).expect("inflight already init");
+            let total: usize = guard.values().map(BlocksInflight::len).sum();
+            let limit = {
+                let inflight = guard.get(&self.peer).expect("inflight already init");
+                cmp::min(
+                    inflight.window.saturating_sub(inflight.len()),
+                    self.synchronizer
+                        .config
+                        .max_blocks_in_transit
+                        .saturating_sub(total),
+                )
+                .min(PER_FETCH_BLOCK_LIMIT)
+            };
+            // Blocks requested from the other peers, and when
+            let requested: FnvHashMap<H256, (PeerIndex, u64)> = guard
+                .iter()
+                .filter(|(peer, _)| **peer != self.peer)
+                .flat_map(|(peer, inflight)| {
+                    inflight
+                        .blocks
+                        .iter()
+                        .map(move |(hash, requested_at)| (hash.to_owned(), (*peer, *requested_at)))
+                })
+                .collect();
+            let mut stalling_peers = FnvHashSet::default();
 
-            while n_height < max_height && v_fetch.len() < PER_FETCH_BLOCK_LIMIT {
+            while n_height < max_height && v_fetch.len() < limit {
                 n_height += 1;
                 let to_fetch = self
                     .synchronizer
@@ -186,9 +233,23 @@ where
                 let to_fetch_hash = to_fetch.hash();
 
                 let block_status = self.synchronizer.get_block_status(to_fetch_hash);
-                if block_status == BlockStatus::VALID_MASK
-                    && inflight.insert(to_fetch_hash.to_owned())
-                {
+                if block_status != BlockStatus::VALID_MASK {
+                    continue;
+                }
+
+                // Leave the block to the peer it was requested from, unless that peer stalls
+                if let Some((peer, requested_at)) = requested.get(to_fetch_hash) {
+                    if now.saturating_sub(*requested_at) < BLOCK_STALLING_TIMEOUT {
+                        continue;
+                    }
+                    if let Some(inflight) = guard.get_mut(peer) {
+                        inflight.steal(to_fetch_hash);
+                    }
+                    stalling_peers.insert(*peer);
+                }
+
+                let inflight = guard.get_mut(&self.peer).expect("inflight already init");
+                if inflight.insert(to_fetch_hash.to_owned()) {
                     trace!(
                         target: "sync", "[Synchronizer] inflight insert {:?}------------{:x}",
                         to_fetch.number(),
@@ -197,6 +258,13 @@ where
                     v_fetch.push(to_fetch_hash.to_owned());
                 }
             }
+
+            for peer in stalling_peers {
+                debug!(target: "sync", "[block downloader] peer {} stalls the download, move its blocks to {}", peer, self.peer);
+                if let Some(inflight) = guard.get_mut(&peer) {
+                    inflight.shrink_window();
+                }
+            }
         }
         Some(v_fetch)
     }
@@ -1,64 +1,170 @@
 use ckb_core::block::Block;
 use ckb_util::RwLock;
-use fnv::{FnvHashMap, FnvHashSet};
+use fnv::FnvHashMap;
 use numext_fixed_hash::H256;
 use std::collections::hash_map::Entry;
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 pub type ParentHash = H256;
 
+pub const DEFAULT_ORPHAN_BLOCK_EXPIRY: Duration = Duration::from_secs(20 * 60);
+
+struct OrphanBlock {
+    block: Block,
+    inserted_at: Instant,
+}
+
 #[derive(Default)]
+struct InnerPool {
+    // Orphans indexed by their parents, to be re-processed once the parent arrives
+    blocks: FnvHashMap<ParentHash, FnvHashMap<H256, OrphanBlock>>,
+    // The parent of every orphan in the pool
+    parents: FnvHashMap<H256, ParentHash>,
+}
+
+impl InnerPool {
+    fn remove(&mut self, hash: &H256) -> Option<Block> {
+        let parent_hash = self.parents.remove(hash)?;
+        if let Entry::Occupied(mut entry) = self.blocks.entry(parent_hash) {
+            let orphan = entry.get_mut().remove(hash);
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+            orphan.map(|orphan| orphan.block)
+        } else {
+            None
+        }
+    }
+
+    fn expired(&self, expiry: Duration) -> Vec<H256> {
+        self.blocks
+            .values()
+            .flat_map(|orphans| orphans.iter())
+            .filter(|(_, orphan)| orphan.inserted_at.elapsed() >= expiry)
+            .map(|(hash, _)| hash.to_owned())
+            .collect()
+    }
+
+    // The orphan with the lowest difficulty, the oldest one among them
+    fn weakest(&self) -> Option<H256> {
+        self.blocks
+            .values()
+            .flat_map(|orphans| orphans.iter())
+            .min_by(|(_, a), (_, b)| {
+                a.block
+                    .header()
+                    .difficulty()
+                    .cmp(b.block.header().difficulty())
+                    .then(a.inserted_at.cmp(&b.inserted_at))
+            })
+            .map(|(hash, _)| hash.to_owned())
+    }
+}
+
+/// Blocks whose parents are unknown yet, bounded by `capacity` blocks which
+/// are kept at most `expiry`.
 pub struct OrphanBlockPool {
-    blocks: RwLock<FnvHashMap<ParentHash, FnvHashSet<Block>>>,
+    inner: RwLock<InnerPool>,
+    capacity: usize,
+    expiry: Duration,
+    evicted: AtomicUsize,
 }
 
 impl OrphanBlockPool {
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn new(capacity: usize, expiry: Duration) -> Self {
         OrphanBlockPool {
-            blocks: RwLock::new(FnvHashMap::with_capacity_and_hasher(
-                capacity,
-                Default::default(),
-            )),
+            inner: RwLock::new(InnerPool::default()),
+            capacity,
+            expiry,
+            evicted: AtomicUsize::new(0),
         }
     }
 
-    /// Insert orphaned block, for which we have already requested its parent block
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::new(capacity, DEFAULT_ORPHAN_BLOCK_EXPIRY)
+    }
+
+    /// Insert orphaned block, for which we have already requested its parent block.
+    ///
+    /// Expired orphans are dropped first, then the ones with the lowest
+    /// difficulty until there is room for `block`.
     pub fn insert(&self, block: Block) {
-        self.blocks
-            .write()
-            .entry(block.header().parent_hash().to_owned())
-            .or_insert_with(FnvHashSet::default)
-            .insert(block);
+        let hash = block.header().hash().to_owned();
+        let mut guard = self.inner.write();
+        if guard.parents.contains_key(&hash) || self.capacity == 0 {
+            return;
+        }
+
+        let mut evicted = 0;
+        for expired in guard.expired(self.expiry) {
+            guard.remove(&expired);
+            evicted += 1;
+        }
+        while guard.parents.len() >= self.capacity {
+            match guard.weakest() {
+                Some(weakest) => {
+                    guard.remove(&weakest);
+                    evicted += 1;
+                }
+                None => break,
+            }
+        }
+        self.evicted.fetch_add(evicted, Ordering::Relaxed);
+
+        let parent_hash = block.header().parent_hash().to_owned();
+        guard.parents.insert(hash.clone(), parent_hash.clone());
+        guard.blocks.entry(parent_hash).or_default().insert(
+            hash,
+            OrphanBlock {
+                block,
+                inserted_at: Instant::now(),
+            },
+        );
     }
 
     pub fn remove_blocks_by_parent(&self, hash: &H256) -> VecDeque<Block> {
-        let mut guard = self.blocks.write();
+        let mut guard = self.inner.write();
         let mut queue: VecDeque<H256> = VecDeque::new();
         queue.push_back(hash.clone());
 
         let mut removed: VecDeque<Block> = VecDeque::new();
         while let Some(parent_hash) = queue.pop_front() {
-            if let Entry::Occupied(entry) = guard.entry(parent_hash) {
-                let (_, orphaned) = entry.remove_entry();
-                queue.extend(orphaned.iter().map(|b| b.header().hash().to_owned()));
-                removed.extend(orphaned.into_iter());
+            if let Some(orphaned) = guard.blocks.remove(&parent_hash) {
+                for (hash, orphan) in orphaned {
+                    guard.parents.remove(&hash);
+                    queue.push_back(hash);
+                    removed.push_back(orphan.block);
+                }
             }
         }
         removed
     }
 
+    /// Number of orphans in the pool
     pub fn len(&self) -> usize {
-        self.blocks.read().len()
+        self.inner.read().parents.len()
     }
 
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of orphans dropped, either expired or to make room for new ones
+    pub fn evicted(&self) -> usize {
+        self.evicted.load(Ordering::Relaxed)
+    }
+
     pub fn contains(&self, block: &Block) -> bool {
-        self.blocks
+        self.inner
             .read()
-            .contains_key(block.header().parent_hash())
+            .parents
+            .contains_key(block.header().hash())
     }
 }
 
@@ -69,6 +175,7 @@ mod tests {
     use ckb_core::block::BlockBuilder;
     use ckb_core::header::{Header, HeaderBuilder};
     use faketime::unix_time_as_millis;
+    use numext_fixed_uint::U256;
     use std::collections::HashSet;
     use std::iter::FromIterator;
 
@@ -78,6 +185,7 @@ mod tests {
             .timestamp(unix_time_as_millis())
             .number(parent_header.number() + 1)
             .nonce(parent_header.nonce() + 1)
+            .difficulty(U256::one())
             .build();
 
         BlockBuilder::default().header(header).build()
@@ -100,6 +208,51 @@ mod tests {
         let orphan = pool.remove_blocks_by_parent(&consensus.genesis_block().header().hash());
         let orphan: HashSet<Block> = HashSet::from_iter(orphan.into_iter());
         let block: HashSet<Block> = HashSet::from_iter(blocks.into_iter());
-        assert_eq!(orphan, block)
+        assert_eq!(orphan, block);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_evict_weakest_orphan() {
+        let consensus = Consensus::default();
+        let genesis = consensus.genesis_block().header().to_owned();
+        let pool = OrphanBlockPool::with_capacity(2);
+
+        let block1 = gen_block(&genesis);
+        let weak = BlockBuilder::default()
+            .header(
+                HeaderBuilder::from_header(gen_block(&genesis).header().to_owned())
+                    .nonce(100)
+                    .difficulty(U256::zero())
+                    .build(),
+            )
+            .build();
+        let block2 = gen_block(block1.header());
+        pool.insert(block1.clone());
+        pool.insert(weak.clone());
+        pool.insert(block2.clone());
+
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.evicted(), 1);
+        assert!(!pool.contains(&weak));
+        assert!(pool.contains(&block1));
+        assert!(pool.contains(&block2));
+    }
+
+    #[test]
+    fn test_expire_orphan() {
+        let consensus = Consensus::default();
+        let genesis = consensus.genesis_block().header().to_owned();
+        let pool = OrphanBlockPool::new(10, Duration::from_secs(0));
+
+        let block1 = gen_block(&genesis);
+        let block2 = gen_block(block1.header());
+        pool.insert(block1.clone());
+        pool.insert(block2.clone());
+
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.evicted(), 1);
+        assert!(!pool.contains(&block1));
+        assert!(pool.contains(&block2));
     }
 }
@@ -1,8 +1,10 @@
-use crate::synchronizer::Synchronizer;
+use crate::synchronizer::{BlockStatus, Synchronizer};
+use crate::UNREQUESTED_BLOCK_SCORE;
 use ckb_core::block::Block;
 use ckb_network::{CKBProtocolContext, PeerIndex};
 use ckb_protocol::Block as PBlock;
 use ckb_store::ChainStore;
+use ckb_verification::{MerkleRootVerifier, NonContextualBlockVerifier, Verifier};
 use failure::Error as FailureError;
 use log::debug;
 use std::convert::TryInto;
@@ -11,6 +13,7 @@ pub struct BlockProcess<'a, CS: ChainStore + 'a> {
     message: &'a PBlock<'a>,
     synchronizer: &'a Synchronizer<CS>,
     peer: PeerIndex,
+    nc: &'a CKBProtocolContext,
 }
 
 impl<'a, CS> BlockProcess<'a, CS>
@@ -21,12 +24,13 @@ where
         message: &'a PBlock,
         synchronizer: &'a Synchronizer<CS>,
         peer: PeerIndex,
-        _nc: &'a CKBProtocolContext,
+        nc: &'a CKBProtocolContext,
     ) -> Self {
         BlockProcess {
             message,
             synchronizer,
             peer,
+            nc,
         }
     }
 
@@ -39,8 +43,37 @@ where
             .peers
             .new_block_received(self.peer, &block)
         {
+            // Checks needing no chain state run before the block is queued,
+            // so malformed blocks are dropped without touching the orphan pool
+            let consensus = self.synchronizer.shared.consensus();
+            if let Err(error) = NonContextualBlockVerifier::new(consensus).verify(&block) {
+                debug!(target: "sync", "BlockProcess invalid block {:x} {:?}", block.header().hash(), error);
+                self.synchronizer.misbehavior(self.nc, self.peer, 100);
+                // A body not matching the header may be tampered by the peer,
+                // only failures the header commits to condemn the block hash
+                if body_committed(&block) {
+                    self.synchronizer.insert_block_status(
+                        block.header().hash().to_owned(),
+                        BlockStatus::FAILED_MASK,
+                    );
+                }
+                return Ok(());
+            }
+            let old_tip = self.synchronizer.shared.tip_header().hash().to_owned();
             self.synchronizer.process_new_block(self.peer, block);
+            if self.synchronizer.shared.tip_header().hash() != &old_tip {
+                self.nc.record_best_block(self.peer);
+            }
+        } else {
+            debug!(target: "sync", "BlockProcess unrequested block {:x}", block.header().hash());
+            self.synchronizer
+                .misbehavior(self.nc, self.peer, UNREQUESTED_BLOCK_SCORE);
         }
         Ok(())
     }
 }
+
+fn body_committed(block: &Block) -> bool {
+    MerkleRootVerifier::new().verify(block).is_ok()
+        && &block.cal_uncles_hash() == block.header().uncles_hash()
+}
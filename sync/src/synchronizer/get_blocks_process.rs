@@ -36,6 +36,14 @@ where
 
     pub fn execute(self) -> Result<(), FailureError> {
         let block_hashes = cast!(self.message.block_hashes())?;
+        if !self.synchronizer.check_rate_limit(
+            self.nc,
+            self.peer,
+            &self.synchronizer.get_blocks_limiter,
+            block_hashes.len(),
+        ) {
+            return Ok(());
+        }
 
         // bitcoin limits 500
         let n_limit = min(BLOCK_DOWNLOAD_WINDOW as usize, block_hashes.len());
@@ -49,6 +57,15 @@ where
                 fbb.finish(message, None);
                 self.nc
                     .send_message_to(self.peer, fbb.finished_data().into());
+            } else if self
+                .synchronizer
+                .shared
+                .shared()
+                .store()
+                .is_block_pruned(&block_hash)
+            {
+                debug!(target: "sync", "getblocks stopping since {} is pruned", block_hash);
+                break;
             } else {
                 // TODO response not found
                 // TODO add timeout check in synchronizer
@@ -36,6 +36,15 @@ where
     }
 
     pub fn execute(self) -> Result<(), FailureError> {
+        if !self.synchronizer.check_rate_limit(
+            self.nc,
+            self.peer,
+            &self.synchronizer.get_headers_limiter,
+            1,
+        ) {
+            return Ok(());
+        }
+
         if self.synchronizer.shared.is_initial_block_download() {
             info!(target: "sync", "Ignoring getheaders from peer={} because node is in initial block download", self.peer);
             return Ok(());
@@ -7,7 +7,9 @@ use ckb_network::{CKBProtocolContext, PeerIndex};
 use ckb_protocol::{cast, FlatbuffersVectorIterator, Headers};
 use ckb_store::ChainStore;
 use ckb_traits::BlockMedianTimeContext;
-use ckb_verification::{Error as VerifyError, HeaderResolver, HeaderVerifier, Verifier};
+use ckb_verification::{
+    Error as VerifyError, HeaderResolver, HeaderVerifier, HeadersVerifier, Verifier,
+};
 use failure::Error as FailureError;
 use log::{self, debug, log_enabled, warn};
 use std::convert::TryInto;
@@ -148,6 +150,40 @@ where
         true
     }
 
+    // The first header whose hash differs from the checkpoint at its number
+    fn conflicting_checkpoint<'h>(&self, headers: &'h [Header]) -> Option<&'h Header> {
+        if self.synchronizer.config.disable_checkpoints {
+            return None;
+        }
+        let checkpoints = self.synchronizer.shared.consensus().checkpoints();
+        if checkpoints.is_empty() {
+            return None;
+        }
+        headers.iter().find(|header| {
+            checkpoints
+                .get(&header.number())
+                .map_or(false, |hash| hash != header.hash())
+        })
+    }
+
+    // Punishes the peer for the header rejected by `HeadersVerifier`, the
+    // same way `HeaderAcceptor` does for a single header.
+    fn reject_invalid(&self, headers: &[Header], invalid: Option<(usize, VerifyError)>) {
+        if let Some((index, error)) = invalid {
+            let header = &headers[index];
+            debug!(target: "sync", "HeadersProcess batch verify {:?} error {:?}", header.number(), error);
+            let misbehavior = match error {
+                VerifyError::Pow(_) => 100,
+                VerifyError::Epoch(_) => 50,
+                _ => 20,
+            };
+            self.synchronizer
+                .misbehavior(self.nc, self.peer, misbehavior);
+            self.synchronizer
+                .insert_block_status(header.hash().to_owned(), BlockStatus::FAILED_MASK);
+        }
+    }
+
     fn received_new_header(&self, headers: &[Header]) -> bool {
         let last = headers.last().expect("empty checked");
         self.synchronizer.get_block_status(&last.hash()) == BlockStatus::UNKNOWN
@@ -159,7 +195,8 @@ where
         let verifier = HeaderVerifier::new(
             resolver.clone(),
             Arc::clone(&self.synchronizer.shared.consensus().pow_engine()),
-        );
+        )
+        .skip_pow();
         let acceptor =
             HeaderAcceptor::new(first, self.peer, &self.synchronizer, resolver, verifier);
         acceptor.accept()
@@ -171,7 +208,7 @@ where
         let headers = cast!(self.message.headers())?;
 
         if headers.len() > MAX_HEADERS_LEN {
-            self.synchronizer.peers.misbehavior(self.peer, 20);
+            self.synchronizer.misbehavior(self.nc, self.peer, 20);
             warn!(target: "sync", "HeadersProcess is_oversize");
             return Ok(());
         }
@@ -201,29 +238,53 @@ where
             .collect::<Result<Vec<Header>, FailureError>>()?;
 
         if !self.is_continuous(&headers) {
-            self.synchronizer.peers.misbehavior(self.peer, 20);
+            self.synchronizer.misbehavior(self.nc, self.peer, 20);
             debug!(target: "sync", "HeadersProcess is not continuous");
             return Ok(());
         }
 
+        if let Some(header) = self.conflicting_checkpoint(&headers) {
+            warn!(
+                target: "sync",
+                "Disconnect peer({}) serving header {} {:x} conflicting with the checkpoint",
+                self.peer,
+                header.number(),
+                header.hash()
+            );
+            self.synchronizer
+                .insert_block_status(header.hash().to_owned(), BlockStatus::FAILED_MASK);
+            self.nc.disconnect(self.peer);
+            return Ok(());
+        }
+
+        // Proofs of work of the whole batch are verified in parallel up front,
+        // the headers before the first invalid one are still accepted.
+        let pow_engine = self.synchronizer.shared.consensus().pow_engine();
+        let invalid = HeadersVerifier::new(&pow_engine).verify(&headers).err();
+        let valid_len = invalid
+            .as_ref()
+            .map(|(index, _)| *index)
+            .unwrap_or_else(|| headers.len());
+        if valid_len == 0 {
+            self.reject_invalid(&headers, invalid);
+            return Ok(());
+        }
+
         let result = self.accept_first(&headers[0]);
         if !result.is_valid() {
             if result.misbehavior > 0 {
                 self.synchronizer
-                    .peers
-                    .misbehavior(self.peer, result.misbehavior);
+                    .misbehavior(self.nc, self.peer, result.misbehavior);
             }
             debug!(target: "sync", "\n\nHeadersProcess accept_first is_valid {:?} headers = {:?}\n\n", result, headers[0]);
             return Ok(());
         }
 
-        for window in headers.windows(2) {
+        for window in headers[..valid_len].windows(2) {
             if let [parent, header] = &window {
                 let resolver = VerifierResolver::new(Some(&parent), &header, &self.synchronizer);
-                let verifier = HeaderVerifier::new(
-                    resolver.clone(),
-                    Arc::clone(&self.synchronizer.shared.consensus().pow_engine()),
-                );
+                let verifier =
+                    HeaderVerifier::new(resolver.clone(), Arc::clone(&pow_engine)).skip_pow();
                 let acceptor =
                     HeaderAcceptor::new(&header, self.peer, &self.synchronizer, resolver, verifier);
                 let result = acceptor.accept();
@@ -231,8 +292,7 @@ where
                 if !result.is_valid() {
                     if result.misbehavior > 0 {
                         self.synchronizer
-                            .peers
-                            .misbehavior(self.peer, result.misbehavior);
+                            .misbehavior(self.nc, self.peer, result.misbehavior);
                     }
                     debug!(target: "sync", "HeadersProcess accept is invalid {:?}", result);
                     return Ok(());
@@ -240,6 +300,11 @@ where
             }
         }
 
+        if invalid.is_some() {
+            self.reject_invalid(&headers, invalid);
+            return Ok(());
+        }
+
         if log_enabled!(target: "sync", log::Level::Debug) {
             let chain_state = self.synchronizer.shared.chain_state().lock();
             let peer_state = self.synchronizer.peers.best_known_header(self.peer);
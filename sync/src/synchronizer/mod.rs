@@ -1,3 +1,4 @@
+mod assume_valid;
 mod block_fetcher;
 mod block_pool;
 mod block_process;
@@ -5,18 +6,22 @@ mod get_blocks_process;
 mod get_headers_process;
 mod headers_process;
 
+pub use self::assume_valid::AssumeValid;
 use self::block_fetcher::BlockFetcher;
-use self::block_pool::OrphanBlockPool;
+pub use self::block_pool::{OrphanBlockPool, DEFAULT_ORPHAN_BLOCK_EXPIRY};
 use self::block_process::BlockProcess;
 use self::get_blocks_process::GetBlocksProcess;
 use self::get_headers_process::GetHeadersProcess;
 use self::headers_process::HeadersProcess;
 use crate::config::Config;
-use crate::types::{HeaderView, Peers, SyncSharedState};
+use crate::parse_protocol_version;
+use crate::rate_limiter::RateLimiter;
+use crate::types::{BlocksInflight, HeaderView, Peers, SyncSharedState};
 use crate::{
     BAD_MESSAGE_BAN_TIME, CHAIN_SYNC_TIMEOUT, EVICTION_HEADERS_RESPONSE_TIME,
-    HEADERS_DOWNLOAD_TIMEOUT_BASE, HEADERS_DOWNLOAD_TIMEOUT_PER_HEADER,
-    MAX_OUTBOUND_PEERS_TO_PROTECT_FROM_DISCONNECT, POW_SPACE,
+    HEADERS_DOWNLOAD_TIMEOUT_BASE, HEADERS_DOWNLOAD_TIMEOUT_PER_HEADER, LOCAL_CAPABILITIES,
+    MAX_OUTBOUND_PEERS_TO_PROTECT_FROM_DISCONNECT, POW_SPACE, PROTOCOL_VERSION_2,
+    RATE_LIMIT_EXCEEDED_SCORE,
 };
 use bitflags::bitflags;
 use ckb_chain::chain::ChainController;
@@ -73,6 +78,9 @@ pub struct Synchronizer<CS: ChainStore> {
     pub config: Arc<Config>,
     pub orphan_block_pool: Arc<OrphanBlockPool>,
     pub outbound_peers_with_protect: Arc<AtomicUsize>,
+    pub assume_valid: Option<Arc<AssumeValid>>,
+    pub(crate) get_headers_limiter: Arc<RateLimiter>,
+    pub(crate) get_blocks_limiter: Arc<RateLimiter>,
     last_notify_times: HashMap<u64, Instant>,
 }
 
@@ -88,6 +96,9 @@ impl<CS: ChainStore> ::std::clone::Clone for Synchronizer<CS> {
             config: Arc::clone(&self.config),
             orphan_block_pool: Arc::clone(&self.orphan_block_pool),
             outbound_peers_with_protect: Arc::clone(&self.outbound_peers_with_protect),
+            assume_valid: self.assume_valid.clone(),
+            get_headers_limiter: Arc::clone(&self.get_headers_limiter),
+            get_blocks_limiter: Arc::clone(&self.get_blocks_limiter),
             last_notify_times: self.last_notify_times.clone(),
         }
     }
@@ -99,20 +110,34 @@ impl<CS: ChainStore> Synchronizer<CS> {
         shared: Arc<SyncSharedState<CS>>,
         config: Config,
     ) -> Synchronizer<CS> {
-        let orphan_block_limit = config.orphan_block_limit;
+        let orphan_block_pool = OrphanBlockPool::new(
+            config.orphan_block_limit,
+            Duration::from_secs(config.orphan_block_expiry),
+        );
+        let get_headers_limiter = RateLimiter::new(config.get_headers_rate_limit);
+        let get_blocks_limiter = RateLimiter::new(config.get_blocks_rate_limit);
         Synchronizer {
             config: Arc::new(config),
             chain,
             shared,
             peers: Arc::new(Peers::default()),
-            orphan_block_pool: Arc::new(OrphanBlockPool::with_capacity(orphan_block_limit)),
+            orphan_block_pool: Arc::new(orphan_block_pool),
             status_map: Arc::new(Mutex::new(HashMap::new())),
             n_sync: Arc::new(AtomicUsize::new(0)),
             outbound_peers_with_protect: Arc::new(AtomicUsize::new(0)),
+            assume_valid: None,
+            get_headers_limiter: Arc::new(get_headers_limiter),
+            get_blocks_limiter: Arc::new(get_blocks_limiter),
             last_notify_times: HashMap::default(),
         }
     }
 
+    /// Skips running the scripts of the blocks on the chain of `target`
+    pub fn assume_valid_target(mut self, target: Option<H256>) -> Self {
+        self.assume_valid = target.map(|target| Arc::new(AssumeValid::new(target)));
+        self
+    }
+
     fn try_process(
         &self,
         nc: &CKBProtocolContext,
@@ -135,6 +160,16 @@ impl<CS: ChainStore> Synchronizer<CS> {
             SyncPayload::Block => {
                 BlockProcess::new(&cast!(message.payload_as_block())?, self, peer, nc).execute()?;
             }
+            SyncPayload::Capabilities => {
+                // The handshake message of version 2, a version 1 peer can
+                // not send it
+                if self.peers.features(peer).sync_version < PROTOCOL_VERSION_2 {
+                    cast!(None)?;
+                }
+                let capabilities = cast!(message.payload_as_capabilities())?.capabilities();
+                debug!(target: "sync", "peer {} capabilities {:#b}", peer, capabilities);
+                self.peers.set_capabilities(peer, capabilities);
+            }
             SyncPayload::NONE => {
                 cast!(None)?;
             }
@@ -152,6 +187,84 @@ impl<CS: ChainStore> Synchronizer<CS> {
         }
     }
 
+    /// Punishes `peer` with `score`, it is banned once the accumulated score
+    /// reaches the threshold
+    pub fn misbehavior(&self, nc: &CKBProtocolContext, peer: PeerIndex, score: u32) {
+        if score > 0 {
+            self.peers.misbehavior(peer, score);
+        }
+        self.check_misbehavior(nc, peer);
+    }
+
+    /// Takes `count` tokens of `limiter` for the message of `peer`, a peer
+    /// over the limit is scored and its message should be dropped
+    pub(crate) fn check_rate_limit(
+        &self,
+        nc: &CKBProtocolContext,
+        peer: PeerIndex,
+        limiter: &RateLimiter,
+        count: usize,
+    ) -> bool {
+        if limiter.check(peer, count) {
+            return true;
+        }
+        debug!(target: "sync", "peer {} exceeds the rate limit", peer);
+        self.misbehavior(nc, peer, RATE_LIMIT_EXCEEDED_SCORE);
+        false
+    }
+
+    // The relayer scores peers through the shared `Peers`, they are banned here
+    fn check_misbehaviors(&self, nc: &CKBProtocolContext) {
+        let peers: Vec<PeerIndex> = self
+            .peers
+            .misbehavior
+            .read()
+            .iter()
+            .filter(|(_, score)| **score >= self.config.ban_score_threshold)
+            .map(|(peer, _)| *peer)
+            .collect();
+        for peer in peers {
+            self.check_misbehavior(nc, peer);
+        }
+    }
+
+    fn check_misbehavior(&self, nc: &CKBProtocolContext, peer: PeerIndex) {
+        let score = self.peers.misbehavior_score(peer);
+        if score < self.config.ban_score_threshold {
+            return;
+        }
+        self.peers.misbehavior.write().remove(&peer);
+        if nc.get_peer(peer).map_or(false, |info| info.is_whitelisted) {
+            info!(target: "sync", "whitelisted peer {} misbehaves with score {}", peer, score);
+            return;
+        }
+        info!(target: "sync", "ban peer {} with misbehavior score {}", peer, score);
+        // Banned by the network layer, which disconnects the peer and refuses
+        // its address until the ban expires
+        nc.ban_peer(peer, Duration::from_secs(self.config.misbehavior_ban_time));
+    }
+
+    /// Whether `header` has enough work to download blocks towards it: its
+    /// total difficulty reaches the minimum chain work and it is not below the
+    /// last checkpoint. Low work chains are only worth syncing headers for.
+    pub fn has_min_chain_work(&self, header: &HeaderView) -> bool {
+        let consensus = self.shared.consensus();
+        let min_chain_work = self
+            .config
+            .min_chain_work
+            .as_ref()
+            .unwrap_or_else(|| consensus.min_chain_work());
+        if header.total_difficulty() < min_chain_work {
+            return false;
+        }
+        self.config.disable_checkpoints
+            || consensus
+                .checkpoints()
+                .keys()
+                .next_back()
+                .map_or(true, |number| header.number() >= *number)
+    }
+
     pub fn get_block_status(&self, hash: &H256) -> BlockStatus {
         let mut guard = self.status_map.lock();
         match guard.get(hash).cloned() {
@@ -233,7 +346,16 @@ impl<CS: ChainStore> Synchronizer<CS> {
     }
 
     fn accept_block(&self, peer: PeerIndex, block: &Arc<Block>) -> Result<(), FailureError> {
-        self.chain.process_block(Arc::clone(&block))?;
+        let assumed_valid = self.assume_valid.as_ref().map_or(false, |assume_valid| {
+            assume_valid.contains(&self.shared, block.header())
+        });
+        let old_tip = self.shared.tip_header();
+        if assumed_valid {
+            self.chain.process_assumed_valid_block(Arc::clone(&block))?;
+        } else {
+            self.chain.process_block(Arc::clone(&block))?;
+        }
+        self.shared.record_processed_block(&old_tip, &block);
         self.shared.remove_header_view(block.header().hash());
         self.mark_block_stored(block.header().hash().to_owned());
         self.peers.set_last_common_header(peer, &block.header());
@@ -299,7 +421,7 @@ impl<CS: ChainStore> Synchronizer<CS> {
         BlockFetcher::new(self.clone(), peer).fetch()
     }
 
-    fn on_connected(&self, nc: &CKBProtocolContext, peer: PeerIndex) {
+    fn on_connected(&self, nc: &CKBProtocolContext, peer: PeerIndex, version: u32) {
         let tip = self.shared.tip_header();
         let predicted_headers_sync_time = self.predict_headers_sync_time(&tip);
 
@@ -318,6 +440,13 @@ impl<CS: ChainStore> Synchronizer<CS> {
 
         self.peers
             .on_connected(peer, predicted_headers_sync_time, protect_outbound);
+        self.peers.set_sync_version(peer, version);
+        if version >= PROTOCOL_VERSION_2 {
+            let fbb = &mut FlatBufferBuilder::new();
+            let message = SyncMessage::build_capabilities(fbb, LOCAL_CAPABILITIES);
+            fbb.finish(message, None);
+            nc.send_message_to(peer, fbb.finished_data().into());
+        }
     }
 
     //   - If at timeout their best known block now has more work than our tip
@@ -334,6 +463,9 @@ impl<CS: ChainStore> Synchronizer<CS> {
         let is_initial_block_download = self.shared.is_initial_block_download();
         let mut eviction = Vec::new();
         for (peer, state) in peer_state.iter_mut() {
+            if nc.get_peer(*peer).map_or(false, |peer| peer.is_whitelisted) {
+                continue;
+            }
             let now = unix_time_as_millis();
             // headers_sync_timeout
             if let Some(timeout) = state.headers_sync_timeout {
@@ -469,12 +601,29 @@ impl<CS: ChainStore> Synchronizer<CS> {
 
         trace!(target: "sync", "poll find_blocks_to_fetch select peers");
         for peer in peers {
-            if let Some(v_fetch) = self.get_blocks_to_fetch(peer) {
+            let v_fetch = self.get_blocks_to_fetch(peer);
+            // Timed out downloads are scored by the fetcher
+            self.check_misbehavior(nc, peer);
+            if let Some(v_fetch) = v_fetch {
                 if !v_fetch.is_empty() {
                     self.send_getblocks(&v_fetch, nc, peer);
                 }
             }
         }
+        self.update_sync_state();
+    }
+
+    /// Refreshes the sync progress snapshot kept in `SyncSharedState`
+    pub fn update_sync_state(&self) {
+        let inflight_blocks = self
+            .peers
+            .blocks_inflight
+            .read()
+            .values()
+            .map(BlocksInflight::len)
+            .sum();
+        self.shared
+            .update_sync_state(inflight_blocks, self.orphan_block_pool.len());
     }
 
     fn send_getblocks(&self, v_fetch: &[H256], nc: &CKBProtocolContext, peer: PeerIndex) {
@@ -513,9 +662,9 @@ impl<CS: ChainStore> CKBProtocolHandler for Synchronizer<CS> {
         self.process(nc.as_ref(), peer_index, msg);
     }
 
-    fn connected(&mut self, nc: Box<CKBProtocolContext>, peer_index: PeerIndex, _version: &str) {
-        info!(target: "sync", "SyncProtocol.connected peer={}", peer_index);
-        self.on_connected(nc.as_ref(), peer_index);
+    fn connected(&mut self, nc: Box<CKBProtocolContext>, peer_index: PeerIndex, version: &str) {
+        info!(target: "sync", "SyncProtocol({}).connected peer={}", version, peer_index);
+        self.on_connected(nc.as_ref(), peer_index, parse_protocol_version(version));
     }
 
     fn disconnected(&mut self, _nc: Box<CKBProtocolContext>, peer_index: PeerIndex) {
@@ -531,6 +680,8 @@ impl<CS: ChainStore> CKBProtocolHandler for Synchronizer<CS> {
         }
         state.remove(&peer_index);
         self.peers.disconnected(peer_index);
+        self.get_headers_limiter.remove(peer_index);
+        self.get_blocks_limiter.remove(peer_index);
     }
 
     fn notify(&mut self, nc: Box<dyn CKBProtocolContext>, token: u64) {
@@ -555,6 +706,7 @@ impl<CS: ChainStore> CKBProtocolHandler for Synchronizer<CS> {
                 }
                 TIMEOUT_EVICTION_TOKEN => {
                     self.eviction(nc.as_ref());
+                    self.check_misbehaviors(nc.as_ref());
                 }
                 _ => unreachable!(),
             }
@@ -569,7 +721,10 @@ mod tests {
     use self::block_process::BlockProcess;
     use self::headers_process::HeadersProcess;
     use super::*;
-    use crate::{SyncSharedState, MAX_TIP_AGE};
+    use crate::{
+        PeerSyncInfo, RateLimit, SyncSharedState, CAPABILITY_COMPACT_BLOCK,
+        INITIAL_BLOCKS_IN_TRANSIT_PER_PEER, LEGACY_CAPABILITIES, MAX_TIP_AGE, PROTOCOL_VERSION_1,
+    };
     use ckb_chain::chain::ChainBuilder;
     use ckb_chain_spec::consensus::Consensus;
     use ckb_core::block::BlockBuilder;
@@ -596,6 +751,7 @@ mod tests {
     use flatbuffers::{get_root, FlatBufferBuilder};
     use fnv::{FnvHashMap, FnvHashSet};
     use numext_fixed_uint::U256;
+    use std::collections::BTreeMap;
     use std::ops::Deref;
     use std::time::Duration;
 
@@ -719,6 +875,23 @@ mod tests {
         assert_eq!(expect, locator);
     }
 
+    #[test]
+    fn test_reset_best_known_header_after_truncate() {
+        let (chain_controller, shared, _notify) = start_chain(None, None);
+        for i in 1..10 {
+            insert_block(&chain_controller, &shared, i, i);
+        }
+        let synchronizer = gen_synchronizer(chain_controller.clone(), shared.clone());
+        assert_eq!(synchronizer.shared.best_known_header().number(), 9);
+
+        let target = shared.block_hash(5).unwrap();
+        chain_controller.truncate(target.clone()).unwrap();
+        synchronizer.shared.reset_best_known_header();
+        let best_known_header = synchronizer.shared.best_known_header();
+        assert_eq!(best_known_header.number(), 5);
+        assert_eq!(best_known_header.hash(), &target);
+    }
+
     #[test]
     fn test_locate_latest_common_block() {
         let consensus = Consensus::default();
@@ -938,6 +1111,7 @@ mod tests {
     struct DummyNetworkContext {
         pub peers: FnvHashMap<PeerIndex, Peer>,
         pub disconnected: Arc<Mutex<FnvHashSet<PeerIndex>>>,
+        pub banned: Arc<Mutex<FnvHashSet<PeerIndex>>>,
     }
 
     fn mock_peer_info() -> Peer {
@@ -980,7 +1154,10 @@ mod tests {
             unimplemented!();
         }
         fn report_peer(&self, _peer_index: PeerIndex, _behaviour: Behaviour) {}
-        fn ban_peer(&self, _peer_index: PeerIndex, _timeout: Duration) {}
+        fn ban_peer(&self, peer_index: PeerIndex, _timeout: Duration) {
+            self.banned.lock().insert(peer_index);
+        }
+        fn record_best_block(&self, _peer_index: PeerIndex) {}
         // Other methods
         fn protocol_id(&self) -> ProtocolId {
             unimplemented!();
@@ -995,6 +1172,7 @@ mod tests {
         DummyNetworkContext {
             peers,
             disconnected: Arc::new(Mutex::new(FnvHashSet::default())),
+            banned: Arc::new(Mutex::new(FnvHashSet::default())),
         }
     }
 
@@ -1065,8 +1243,8 @@ mod tests {
         let mock_nc = mock_network_context(4);
         let peer1: PeerIndex = 1.into();
         let peer2: PeerIndex = 2.into();
-        synchronizer1.on_connected(&mock_nc, peer1);
-        synchronizer1.on_connected(&mock_nc, peer2);
+        synchronizer1.on_connected(&mock_nc, peer1, PROTOCOL_VERSION_1);
+        synchronizer1.on_connected(&mock_nc, peer2, PROTOCOL_VERSION_1);
         HeadersProcess::new(&fbs_headers, &synchronizer1, peer1, &mock_nc)
             .execute()
             .expect("Process headers from peer1 failed");
@@ -1127,6 +1305,259 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_checkpoint_conflict() {
+        let notify = NotifyService::default().start::<&str>(None);
+        let (chain_controller2, shared2, _) = start_chain(None, Some(notify.clone()));
+        for i in 1..=5 {
+            insert_block(&chain_controller2, &shared2, i, i);
+        }
+        let synchronizer2 = gen_synchronizer(chain_controller2.clone(), shared2.clone());
+        let headers = synchronizer2.shared.get_locator_response(0, &H256::zero());
+
+        let fbb = &mut FlatBufferBuilder::new();
+        let fbs_headers = FbsHeaders::build(fbb, &headers);
+        fbb.finish(fbs_headers, None);
+        let fbs_headers = get_root::<FbsHeaders>(fbb.finished_data());
+
+        let mut checkpoints = BTreeMap::new();
+        checkpoints.insert(3, H256::zero());
+        let consensus = Consensus::default().set_checkpoints(checkpoints);
+        let peer: PeerIndex = 1.into();
+
+        let (chain_controller1, shared1, _) =
+            start_chain(Some(consensus.clone()), Some(notify.clone()));
+        let synchronizer1 = gen_synchronizer(chain_controller1, shared1);
+        let mock_nc = mock_network_context(4);
+        synchronizer1.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+        HeadersProcess::new(&fbs_headers, &synchronizer1, peer, &mock_nc)
+            .execute()
+            .expect("Process headers failed");
+        assert!(mock_nc.disconnected.lock().contains(&peer));
+        assert_eq!(
+            synchronizer1.get_block_status(headers[2].hash()),
+            BlockStatus::FAILED_MASK
+        );
+
+        let mut config = Config::default();
+        config.disable_checkpoints = true;
+        let (chain_controller3, shared3, _) = start_chain(Some(consensus), Some(notify));
+        let synchronizer3 = Synchronizer::new(
+            chain_controller3,
+            Arc::new(SyncSharedState::new(shared3)),
+            config,
+        );
+        let mock_nc = mock_network_context(4);
+        synchronizer3.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+        HeadersProcess::new(&fbs_headers, &synchronizer3, peer, &mock_nc)
+            .execute()
+            .expect("Process headers failed");
+        assert!(!mock_nc.disconnected.lock().contains(&peer));
+        assert_eq!(
+            synchronizer3.peers.best_known_header(peer).unwrap().inner(),
+            headers.last().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_min_chain_work() {
+        let header_view = |number: BlockNumber, total_difficulty: u64| {
+            HeaderView::new(
+                HeaderBuilder::default().number(number).build(),
+                U256::from(total_difficulty),
+                0,
+            )
+        };
+        let mut checkpoints = BTreeMap::new();
+        checkpoints.insert(10, H256::zero());
+        let consensus = Consensus::default()
+            .set_checkpoints(checkpoints)
+            .set_min_chain_work(U256::from(100u64));
+        let (chain_controller, shared, _notify) = start_chain(Some(consensus), None);
+
+        let synchronizer = gen_synchronizer(chain_controller.clone(), shared.clone());
+        assert!(!synchronizer.has_min_chain_work(&header_view(20, 99)));
+        assert!(!synchronizer.has_min_chain_work(&header_view(9, 100)));
+        assert!(synchronizer.has_min_chain_work(&header_view(10, 100)));
+
+        let mut config = Config::default();
+        config.min_chain_work = Some(U256::from(50u64));
+        config.disable_checkpoints = true;
+        let synchronizer = Synchronizer::new(
+            chain_controller,
+            Arc::new(SyncSharedState::new(shared)),
+            config,
+        );
+        assert!(!synchronizer.has_min_chain_work(&header_view(20, 49)));
+        assert!(synchronizer.has_min_chain_work(&header_view(9, 50)));
+    }
+
+    #[test]
+    fn test_assume_valid() {
+        let notify = NotifyService::default().start::<&str>(None);
+        let (chain_controller2, shared2, _) = start_chain(None, Some(notify.clone()));
+        for i in 1..=5 {
+            insert_block(&chain_controller2, &shared2, i, i);
+        }
+        let synchronizer2 = gen_synchronizer(chain_controller2.clone(), shared2.clone());
+        let headers = synchronizer2.shared.get_locator_response(0, &H256::zero());
+
+        let (chain_controller1, shared1, _) = start_chain(None, Some(notify));
+        let synchronizer1 = gen_synchronizer(chain_controller1, shared1)
+            .assume_valid_target(Some(headers[2].hash().to_owned()));
+        let assume_valid = synchronizer1.assume_valid.clone().unwrap();
+        // The target is not known yet
+        assert!(!assume_valid.contains(&synchronizer1.shared, &headers[0]));
+
+        let fbb = &mut FlatBufferBuilder::new();
+        let fbs_headers = FbsHeaders::build(fbb, &headers);
+        fbb.finish(fbs_headers, None);
+        let fbs_headers = get_root::<FbsHeaders>(fbb.finished_data());
+        let mock_nc = mock_network_context(4);
+        let peer: PeerIndex = 1.into();
+        synchronizer1.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+        HeadersProcess::new(&fbs_headers, &synchronizer1, peer, &mock_nc)
+            .execute()
+            .expect("Process headers failed");
+
+        assert!(assume_valid.contains(&synchronizer1.shared, &headers[0]));
+        assert!(assume_valid.contains(&synchronizer1.shared, &headers[2]));
+        assert!(!assume_valid.contains(&synchronizer1.shared, &headers[3]));
+
+        for header in &headers {
+            let block = shared2.block(header.hash()).unwrap();
+            synchronizer1.accept_block(peer, &Arc::new(block)).unwrap();
+        }
+        assert_eq!(
+            synchronizer1.shared.tip_header().hash(),
+            headers.last().unwrap().hash()
+        );
+    }
+
+    #[test]
+    fn test_steal_stalled_blocks() {
+        let consensus = Consensus::default();
+        let notify = NotifyService::default().start::<&str>(None);
+        let (chain_controller1, shared1, _) =
+            start_chain(Some(consensus.clone()), Some(notify.clone()));
+        let (chain_controller2, shared2, _) =
+            start_chain(Some(consensus.clone()), Some(notify.clone()));
+        let num = 20;
+
+        for i in 1..=num {
+            insert_block(&chain_controller2, &shared2, i, i);
+        }
+
+        let synchronizer1 = gen_synchronizer(chain_controller1.clone(), shared1.clone());
+        let synchronizer2 = gen_synchronizer(chain_controller2.clone(), shared2.clone());
+        let headers = synchronizer2.shared.get_locator_response(0, &H256::zero());
+
+        let fbb = &mut FlatBufferBuilder::new();
+        let fbs_headers = FbsHeaders::build(fbb, &headers);
+        fbb.finish(fbs_headers, None);
+        let fbs_headers = get_root::<FbsHeaders>(fbb.finished_data());
+
+        let mock_nc = mock_network_context(4);
+        let peer1: PeerIndex = 1.into();
+        let peer2: PeerIndex = 2.into();
+        synchronizer1.on_connected(&mock_nc, peer1, PROTOCOL_VERSION_1);
+        synchronizer1.on_connected(&mock_nc, peer2, PROTOCOL_VERSION_1);
+        for peer in &[peer1, peer2] {
+            HeadersProcess::new(&fbs_headers, &synchronizer1, *peer, &mock_nc)
+                .execute()
+                .expect("Process headers failed");
+        }
+
+        let blocks_to_fetch = synchronizer1.get_blocks_to_fetch(peer1).unwrap();
+        assert_eq!(blocks_to_fetch.len(), INITIAL_BLOCKS_IN_TRANSIT_PER_PEER);
+        // peer2 gets the blocks following the ones requested from peer1
+        let blocks_to_fetch2 = synchronizer1.get_blocks_to_fetch(peer2).unwrap();
+        assert_eq!(
+            blocks_to_fetch2.first().unwrap(),
+            &shared2
+                .block_hash(INITIAL_BLOCKS_IN_TRANSIT_PER_PEER as u64 + 1)
+                .unwrap()
+        );
+        assert!(blocks_to_fetch2
+            .iter()
+            .all(|hash| !blocks_to_fetch.contains(hash)));
+
+        // peer1 stalls, its blocks are requested from peer2 instead
+        for requested_at in synchronizer1
+            .peers
+            .blocks_inflight
+            .write()
+            .get_mut(&peer1)
+            .unwrap()
+            .blocks
+            .values_mut()
+        {
+            *requested_at = 0;
+        }
+        let stolen = synchronizer1.get_blocks_to_fetch(peer2).unwrap();
+        assert_eq!(stolen.first(), blocks_to_fetch.first());
+        let blocks_inflight = synchronizer1.peers.blocks_inflight.read();
+        let inflight1 = blocks_inflight.get(&peer1).unwrap();
+        assert!(stolen.iter().all(|hash| !inflight1.contains(hash)));
+        assert_eq!(inflight1.window, INITIAL_BLOCKS_IN_TRANSIT_PER_PEER / 2);
+        drop(blocks_inflight);
+
+        // the late answer of peer1 is still a requested block
+        let late = shared2.block(stolen.first().unwrap()).unwrap();
+        assert!(synchronizer1.peers.new_block_received(peer1, &late));
+        assert!(!synchronizer1.peers.new_block_received(peer1, &late));
+    }
+
+    #[test]
+    fn test_tampered_block_body() {
+        let consensus = Consensus::default();
+        let (chain_controller1, shared1, _) = start_chain(Some(consensus.clone()), None);
+        let (chain_controller2, shared2, _) = start_chain(Some(consensus.clone()), None);
+        insert_block(&chain_controller2, &shared2, 1, 1);
+
+        let synchronizer1 = gen_synchronizer(chain_controller1.clone(), shared1.clone());
+        let synchronizer2 = gen_synchronizer(chain_controller2.clone(), shared2.clone());
+        let headers = synchronizer2.shared.get_locator_response(0, &H256::zero());
+        let fbb = &mut FlatBufferBuilder::new();
+        let fbs_headers = FbsHeaders::build(fbb, &headers);
+        fbb.finish(fbs_headers, None);
+        let fbs_headers = get_root::<FbsHeaders>(fbb.finished_data());
+
+        let mock_nc = mock_network_context(2);
+        let peer: PeerIndex = 1.into();
+        synchronizer1.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+        HeadersProcess::new(&fbs_headers, &synchronizer1, peer, &mock_nc)
+            .execute()
+            .expect("Process headers failed");
+        let hash = shared2.block_hash(1).unwrap();
+        assert_eq!(
+            synchronizer1.get_blocks_to_fetch(peer).unwrap(),
+            vec![hash.clone()]
+        );
+
+        // the header is kept, the body carries a second cellbase
+        let block = shared2.block(&hash).unwrap();
+        let cellbase = block.transactions()[0].clone();
+        let tampered = BlockBuilder::from_block(block)
+            .transaction(cellbase)
+            .unsafe_build();
+        assert_eq!(tampered.header().hash(), &hash);
+
+        let fbb = &mut FlatBufferBuilder::new();
+        let fbs_block = FbsBlock::build(fbb, &tampered);
+        fbb.finish(fbs_block, None);
+        let fbs_block = get_root::<FbsBlock>(fbb.finished_data());
+        BlockProcess::new(&fbs_block, &synchronizer1, peer, &mock_nc)
+            .execute()
+            .unwrap();
+
+        // the peer is punished, the valid block can still be fetched
+        assert!(mock_nc.banned.lock().contains(&peer));
+        assert!(!synchronizer1
+            .get_block_status(&hash)
+            .intersects(BlockStatus::FAILED_MASK));
+    }
+
     #[cfg(not(disable_faketime))]
     #[test]
     fn test_header_sync_timeout() {
@@ -1154,6 +1585,60 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_update_sync_state() {
+        let (chain_controller, shared, _notify) = start_chain(None, None);
+        let synchronizer = gen_synchronizer(chain_controller.clone(), shared.clone());
+        let tip = synchronizer.shared.tip_header();
+
+        let best_known_header = HeaderView::new(
+            HeaderBuilder::default().number(20).build(),
+            U256::from(1u64),
+            0,
+        );
+        synchronizer
+            .shared
+            .set_best_known_header(best_known_header.clone());
+        synchronizer
+            .peers
+            .blocks_inflight
+            .write()
+            .entry(0.into())
+            .or_insert_with(Default::default)
+            .insert(H256::zero());
+        synchronizer.update_sync_state();
+
+        let state = synchronizer.shared.sync_state();
+        assert_eq!(state.tip_number, tip.number());
+        assert_eq!(&state.tip_hash, tip.hash());
+        assert_eq!(state.best_known_header_number, 20);
+        assert_eq!(&state.best_known_header_hash, best_known_header.hash());
+        assert_eq!(state.inflight_blocks, 1);
+        assert_eq!(state.orphan_blocks, 0);
+        // nothing downloaded yet, the speed is unknown
+        assert_eq!(state.estimated_remaining_time, None);
+    }
+
+    #[cfg(not(disable_faketime))]
+    #[test]
+    fn test_leave_initial_block_download() {
+        let faketime_file = faketime::millis_tempfile(0).expect("create faketime file");
+        faketime::enable(&faketime_file);
+
+        let (chain_controller, shared, _notify) = start_chain(None, None);
+        let synchronizer = gen_synchronizer(chain_controller.clone(), shared.clone());
+
+        faketime::write_millis(&faketime_file, MAX_TIP_AGE * 2).expect("write millis");
+        assert!(synchronizer.shared.is_initial_block_download());
+
+        faketime::write_millis(&faketime_file, 0).expect("write millis");
+        assert!(!synchronizer.shared.is_initial_block_download());
+
+        // a stalled tip does not bring the node back to initial block download
+        faketime::write_millis(&faketime_file, MAX_TIP_AGE * 2).expect("write millis");
+        assert!(!synchronizer.shared.is_initial_block_download());
+    }
+
     #[cfg(not(disable_faketime))]
     #[test]
     fn test_chain_sync_timeout() {
@@ -1282,4 +1767,150 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_misbehavior_ban() {
+        let (chain_controller, shared, _) = start_chain(None, None);
+        let synchronizer = gen_synchronizer(chain_controller, shared);
+        let peer: PeerIndex = 1.into();
+        let mock_nc = mock_network_context(2);
+        synchronizer.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+
+        synchronizer.misbehavior(&mock_nc, peer, 60);
+        assert!(!mock_nc.banned.lock().contains(&peer));
+
+        synchronizer.misbehavior(&mock_nc, peer, 40);
+        assert!(mock_nc.banned.lock().contains(&peer));
+        assert_eq!(synchronizer.peers.misbehavior_score(peer), 0);
+    }
+
+    #[test]
+    fn test_rate_limit_misbehavior() {
+        let (chain_controller, shared, _) = start_chain(None, None);
+        let mut config = Config::default();
+        config.get_headers_rate_limit = RateLimit::new(1, 2);
+        let synchronizer = Synchronizer::new(
+            chain_controller,
+            Arc::new(SyncSharedState::new(shared)),
+            config,
+        );
+        let peer: PeerIndex = 1.into();
+        let mock_nc = mock_network_context(2);
+        synchronizer.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+
+        let limiter = Arc::clone(&synchronizer.get_headers_limiter);
+        assert!(synchronizer.check_rate_limit(&mock_nc, peer, &limiter, 2));
+        assert_eq!(synchronizer.peers.misbehavior_score(peer), 0);
+        assert!(!synchronizer.check_rate_limit(&mock_nc, peer, &limiter, 1));
+        assert_eq!(
+            synchronizer.peers.misbehavior_score(peer),
+            RATE_LIMIT_EXCEEDED_SCORE
+        );
+
+        // Peers scored by the relayer are banned by the synchronizer
+        synchronizer.peers.misbehavior(peer, 100);
+        synchronizer.check_misbehaviors(&mock_nc);
+        assert!(mock_nc.banned.lock().contains(&peer));
+    }
+
+    #[test]
+    fn test_whitelisted_peer_not_banned() {
+        let (chain_controller, shared, _) = start_chain(None, None);
+        let synchronizer = gen_synchronizer(chain_controller, shared);
+        let peer: PeerIndex = 1.into();
+        let mut mock_nc = mock_network_context(2);
+        mock_nc.peers.get_mut(&peer).unwrap().is_whitelisted = true;
+        synchronizer.on_connected(&mock_nc, peer, PROTOCOL_VERSION_1);
+
+        synchronizer.misbehavior(&mock_nc, peer, 100);
+        assert!(!mock_nc.banned.lock().contains(&peer));
+        assert_eq!(synchronizer.peers.misbehavior_score(peer), 0);
+    }
+
+    #[test]
+    fn test_capabilities_handshake() {
+        let (chain_controller, shared, _) = start_chain(None, None);
+        let synchronizer = gen_synchronizer(chain_controller, shared);
+        let mock_nc = mock_network_context(3);
+        let (peer1, peer2): (PeerIndex, PeerIndex) = (1.into(), 2.into());
+        synchronizer.on_connected(&mock_nc, peer1, PROTOCOL_VERSION_1);
+        synchronizer.on_connected(&mock_nc, peer2, PROTOCOL_VERSION_2);
+        assert_eq!(synchronizer.peers.features(peer2).sync_version, 2);
+        assert_eq!(
+            synchronizer.peers.features(peer2).capabilities(),
+            LEGACY_CAPABILITIES
+        );
+
+        let fbb = &mut FlatBufferBuilder::new();
+        // no capability at all
+        let message = SyncMessage::build_capabilities(fbb, 0);
+        fbb.finish(message, None);
+        let message = get_root::<SyncMessage>(fbb.finished_data());
+
+        // Only the version 2 peers can send the handshake
+        assert!(synchronizer.try_process(&mock_nc, peer1, message).is_err());
+        assert!(synchronizer
+            .peers
+            .has_capabilities(peer1, CAPABILITY_COMPACT_BLOCK));
+
+        assert!(synchronizer.try_process(&mock_nc, peer2, message).is_ok());
+        assert_eq!(synchronizer.peers.features(peer2).capabilities(), 0);
+        assert!(!synchronizer
+            .peers
+            .has_capabilities(peer2, CAPABILITY_COMPACT_BLOCK));
+    }
+
+    #[test]
+    fn test_peer_sync_info() {
+        let (chain_controller, shared, _) = start_chain(None, None);
+        let synchronizer = gen_synchronizer(chain_controller, shared);
+        let mock_nc = mock_network_context(3);
+        let peer: PeerIndex = 1.into();
+        assert_eq!(synchronizer.peers.sync_info(peer), PeerSyncInfo::default());
+
+        synchronizer.on_connected(&mock_nc, peer, PROTOCOL_VERSION_2);
+        let header = HeaderBuilder::default().number(3).build();
+        let header_view = HeaderView::new(header.clone(), U256::from(3u64), 0);
+        synchronizer.peers.new_header_received(peer, &header_view);
+        synchronizer.peers.set_last_common_header(peer, &header);
+
+        let sync_info = synchronizer.peers.sync_info(peer);
+        let expected = Some((3, header.hash().to_owned()));
+        assert_eq!(sync_info.best_known_header, expected);
+        assert_eq!(sync_info.last_common_header, expected);
+        assert_eq!(sync_info.inflight_blocks, 0);
+    }
+
+    #[test]
+    fn test_fork_stats() {
+        let (chain_controller, shared, _) = start_chain(None, None);
+        for i in 1..4 {
+            insert_block(&chain_controller, &shared, 0, i);
+        }
+        let synchronizer = gen_synchronizer(chain_controller.clone(), shared.clone());
+        let peer: PeerIndex = 1.into();
+        let old_tip = shared.chain_state().lock().tip_header().to_owned();
+
+        // A side chain forking off block 1 overtakes the main chain at block 4
+        let mut parent = shared.block_header(&shared.block_hash(1).unwrap()).unwrap();
+        let epoch = shared.get_epoch_ext(&parent.hash()).unwrap();
+        for _ in 2..5 {
+            let block = gen_block(&parent, &epoch, 1);
+            parent = block.header().to_owned();
+            synchronizer
+                .accept_block(peer, &Arc::new(block))
+                .expect("accept block");
+        }
+
+        let fork_stats = synchronizer.shared.fork_stats();
+        assert_eq!(fork_stats.competing_tips, 1);
+        assert_eq!(fork_stats.stale_blocks, 2);
+        assert_eq!(fork_stats.reorgs, 1);
+        assert_eq!(fork_stats.reorg_depths.get(&2), Some(&1));
+        let last_reorg = fork_stats.last_reorg.unwrap();
+        assert_eq!(last_reorg.old_tip_hash, old_tip.hash().to_owned());
+        assert_eq!(last_reorg.new_tip_hash, parent.hash().to_owned());
+        assert_eq!(last_reorg.fork_number, 1);
+        assert_eq!(last_reorg.depth, 2);
+    }
 }
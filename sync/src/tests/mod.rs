@@ -193,6 +193,7 @@ impl CKBProtocolContext for TestNetworkContext {
     }
     fn report_peer(&self, _peer_index: PeerIndex, _behaviour: Behaviour) {}
     fn ban_peer(&self, _peer_index: PeerIndex, _timeout: Duration) {}
+    fn record_best_block(&self, _peer_index: PeerIndex) {}
     // Other methods
     fn protocol_id(&self) -> ProtocolId {
         self.protocol
@@ -1,5 +1,9 @@
 use crate::NetworkProtocol;
-use crate::{MAX_HEADERS_LEN, MAX_TIP_AGE};
+use crate::{
+    FAST_BLOCK_RESPONSE, IBD_MAX_BLOCKS_BEHIND, INITIAL_BLOCKS_IN_TRANSIT_PER_PEER,
+    LEGACY_CAPABILITIES, MAX_BLOCKS_IN_TRANSIT_PER_PEER, MAX_HEADERS_LEN, MAX_TIP_AGE,
+    PROTOCOL_VERSION_1, SLOW_BLOCK_RESPONSE,
+};
 use ckb_chain_spec::consensus::Consensus;
 use ckb_core::block::Block;
 use ckb_core::extras::BlockExt;
@@ -16,7 +20,7 @@ use ckb_util::RwLock;
 use faketime::unix_time_as_millis;
 use flatbuffers::FlatBufferBuilder;
 use fnv::{FnvHashMap, FnvHashSet};
-use log::debug;
+use log::{debug, info};
 use lru_cache::LruCache;
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
@@ -26,6 +30,7 @@ use std::collections::{
     hash_set::HashSet,
     BTreeMap,
 };
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 const FILTER_SIZE: usize = 20000;
@@ -126,6 +131,11 @@ impl PeerState {
         Some(next_ask_timeout)
     }
 
+    /// Whether the tx was announced by this peer and is asked for
+    pub fn asked_for_tx(&self, tx_hash: &H256) -> bool {
+        self.tx_ask_for_set.contains(tx_hash)
+    }
+
     pub fn remove_ask_for_tx(&mut self, tx_hash: &H256) {
         self.tx_ask_for_set.remove(tx_hash);
     }
@@ -179,19 +189,70 @@ pub struct Peers {
     pub last_common_headers: RwLock<FnvHashMap<PeerIndex, Header>>,
     pub known_txs: Mutex<KnownFilter>,
     pub known_blocks: Mutex<KnownFilter>,
+    pub features: RwLock<FnvHashMap<PeerIndex, PeerFeatures>>,
+}
+
+/// Sync progress of a connected peer, for the RPC layer
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerSyncInfo {
+    pub best_known_header: Option<(BlockNumber, H256)>,
+    pub last_common_header: Option<(BlockNumber, H256)>,
+    pub inflight_blocks: usize,
+    pub sync_started: bool,
+}
+
+/// Protocol versions negotiated with a peer and the capabilities it announced
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerFeatures {
+    pub sync_version: u32,
+    pub relay_version: u32,
+    /// None until the peer announces them
+    pub capabilities: Option<u64>,
+}
+
+impl Default for PeerFeatures {
+    fn default() -> Self {
+        PeerFeatures {
+            sync_version: PROTOCOL_VERSION_1,
+            relay_version: PROTOCOL_VERSION_1,
+            capabilities: None,
+        }
+    }
+}
+
+impl PeerFeatures {
+    pub fn capabilities(&self) -> u64 {
+        self.capabilities.unwrap_or(LEGACY_CAPABILITIES)
+    }
+
+    pub fn has_capabilities(&self, capabilities: u64) -> bool {
+        self.capabilities() & capabilities == capabilities
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct BlocksInflight {
     pub timestamp: u64,
-    pub blocks: FnvHashSet<H256>,
+    /// Requested blocks and when they were requested
+    pub blocks: FnvHashMap<H256, u64>,
+    /// Number of blocks which may be requested at once, grown while the peer
+    /// answers quickly and shrunk while it is slow
+    pub window: usize,
+    /// Average response latency in milliseconds
+    pub latency: u64,
+    /// Blocks requested from another peer after this one stalled, they are
+    /// still accepted from this peer
+    pub stolen: FnvHashSet<H256>,
 }
 
 impl Default for BlocksInflight {
     fn default() -> Self {
         BlocksInflight {
-            blocks: FnvHashSet::default(),
+            blocks: FnvHashMap::default(),
             timestamp: unix_time_as_millis(),
+            window: INITIAL_BLOCKS_IN_TRANSIT_PER_PEER,
+            latency: 0,
+            stolen: FnvHashSet::default(),
         }
     }
 }
@@ -205,12 +266,62 @@ impl BlocksInflight {
         self.len() == 0
     }
 
+    pub fn contains(&self, hash: &H256) -> bool {
+        self.blocks.contains_key(hash)
+    }
+
     pub fn insert(&mut self, hash: H256) -> bool {
-        self.blocks.insert(hash)
+        match self.blocks.entry(hash) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(unix_time_as_millis());
+                true
+            }
+        }
     }
 
     pub fn remove(&mut self, hash: &H256) -> bool {
-        self.blocks.remove(hash)
+        self.blocks.remove(hash).is_some()
+    }
+
+    /// Moves a stalled block out of the requested ones, a late answer is
+    /// neither scored as unrequested nor counted in the window
+    pub fn steal(&mut self, hash: &H256) {
+        if self.remove(hash) && self.stolen.len() < MAX_BLOCKS_IN_TRANSIT_PER_PEER {
+            self.stolen.insert(hash.to_owned());
+        }
+    }
+
+    /// Removes a received block and adapts the window to its response latency
+    pub fn received(&mut self, hash: &H256) -> bool {
+        if self.stolen.remove(hash) {
+            return true;
+        }
+        match self.blocks.remove(hash) {
+            Some(requested_at) => {
+                let latency = unix_time_as_millis().saturating_sub(requested_at);
+                self.latency = if self.latency == 0 {
+                    latency
+                } else {
+                    (self.latency * 7 + latency) / 8
+                };
+                if latency <= FAST_BLOCK_RESPONSE {
+                    self.window += 1;
+                } else if latency >= SLOW_BLOCK_RESPONSE {
+                    self.window = self.window.saturating_sub(1);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn shrink_window(&mut self) {
+        self.window /= 2;
+    }
+
+    pub fn clamp_window(&mut self, min: usize, max: usize) {
+        self.window = cmp::max(cmp::min(self.window, max), min);
     }
 
     pub fn update_timestamp(&mut self) {
@@ -219,19 +330,21 @@ impl BlocksInflight {
 
     pub fn clear(&mut self) {
         self.blocks.clear();
+        self.stolen.clear();
     }
 }
 
 impl Peers {
-    pub fn misbehavior(&self, peer: PeerIndex, score: u32) {
-        if score == 0 {
-            return;
-        }
-
+    /// Adds `score` to the misbehavior score of `peer`, returns the accumulated score
+    pub fn misbehavior(&self, peer: PeerIndex, score: u32) -> u32 {
         let mut map = self.misbehavior.write();
-        map.entry(peer)
-            .and_modify(|e| *e += score)
-            .or_insert_with(|| score);
+        let total = map.entry(peer).or_insert(0);
+        *total = total.saturating_add(score);
+        *total
+    }
+
+    pub fn misbehavior_score(&self, peer: PeerIndex) -> u32 {
+        self.misbehavior.read().get(&peer).cloned().unwrap_or(0)
     }
 
     pub fn on_connected(&self, peer: PeerIndex, predicted_headers_sync_time: u64, protect: bool) {
@@ -249,6 +362,26 @@ impl Peers {
             });
     }
 
+    pub fn features(&self, peer: PeerIndex) -> PeerFeatures {
+        self.features.read().get(&peer).cloned().unwrap_or_default()
+    }
+
+    pub fn set_sync_version(&self, peer: PeerIndex, version: u32) {
+        self.features.write().entry(peer).or_default().sync_version = version;
+    }
+
+    pub fn set_relay_version(&self, peer: PeerIndex, version: u32) {
+        self.features.write().entry(peer).or_default().relay_version = version;
+    }
+
+    pub fn set_capabilities(&self, peer: PeerIndex, capabilities: u64) {
+        self.features.write().entry(peer).or_default().capabilities = Some(capabilities);
+    }
+
+    pub fn has_capabilities(&self, peer: PeerIndex, capabilities: u64) -> bool {
+        self.features(peer).has_capabilities(capabilities)
+    }
+
     pub fn best_known_header(&self, peer: PeerIndex) -> Option<HeaderView> {
         self.best_known_headers.read().get(&peer).cloned()
     }
@@ -268,15 +401,39 @@ impl Peers {
             .or_insert_with(|| header_view.clone());
     }
 
+    pub fn sync_info(&self, peer: PeerIndex) -> PeerSyncInfo {
+        PeerSyncInfo {
+            best_known_header: self
+                .best_known_header(peer)
+                .map(|header| (header.number(), header.hash().to_owned())),
+            last_common_header: self
+                .last_common_headers
+                .read()
+                .get(&peer)
+                .map(|header| (header.number(), header.hash().to_owned())),
+            inflight_blocks: self
+                .blocks_inflight
+                .read()
+                .get(&peer)
+                .map_or(0, BlocksInflight::len),
+            sync_started: self
+                .state
+                .read()
+                .get(&peer)
+                .map_or(false, |state| state.sync_started),
+        }
+    }
+
     pub fn getheaders_received(&self, _peer: PeerIndex) {
         // TODO:
     }
 
     pub fn disconnected(&self, peer: PeerIndex) {
         self.best_known_headers.write().remove(&peer);
-        // self.misbehavior.write().remove(peer);
+        self.misbehavior.write().remove(&peer);
         self.blocks_inflight.write().remove(&peer);
         self.last_common_headers.write().remove(&peer);
+        self.features.write().remove(&peer);
     }
 
     // Return true when the block is that we have requested and received first time.
@@ -285,7 +442,7 @@ impl Peers {
         let mut is_new = false;
         debug!(target: "sync", "block_received from peer {} {} {:x}", peer, block.header().number(), block.header().hash());
         blocks_inflight.entry(peer).and_modify(|inflight| {
-            if inflight.remove(&block.header().hash()) {
+            if inflight.received(&block.header().hash()) {
                 is_new = true;
                 inflight.update_timestamp();
             }
@@ -363,12 +520,59 @@ impl EpochIndices {
     }
 }
 
+/// Snapshot of the sync progress, refreshed by the synchronizer
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SyncState {
+    pub best_known_header_number: BlockNumber,
+    pub best_known_header_hash: H256,
+    pub tip_number: BlockNumber,
+    pub tip_hash: H256,
+    pub inflight_blocks: usize,
+    pub orphan_blocks: usize,
+    /// Milliseconds to reach the best known header at the average speed since
+    /// the node started, unknown until some blocks are downloaded
+    pub estimated_remaining_time: Option<u64>,
+    pub updated_at: u64,
+}
+
+/// Details of the last chain reorganization
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReorgInfo {
+    pub old_tip_number: BlockNumber,
+    pub old_tip_hash: H256,
+    pub new_tip_number: BlockNumber,
+    pub new_tip_hash: H256,
+    /// Number of the last block shared by the old and the new main chain
+    pub fork_number: BlockNumber,
+    /// Main chain blocks detached by the reorganization
+    pub depth: u64,
+    pub timestamp: u64,
+}
+
+/// Forks observed while processing blocks
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForkStats {
+    /// Side chains started by a block forking off the main chain
+    pub competing_tips: u64,
+    /// Blocks stored without becoming the tip
+    pub stale_blocks: u64,
+    pub reorgs: u64,
+    /// Number of reorganizations per depth
+    pub reorg_depths: BTreeMap<u64, u64>,
+    pub last_reorg: Option<ReorgInfo>,
+}
+
 pub struct SyncSharedState<CS> {
     shared: Shared<CS>,
     epoch_map: RwLock<EpochIndices>,
     header_map: RwLock<HashMap<H256, HeaderView>>,
     best_known_header: RwLock<HeaderView>,
     get_headers_cache: RwLock<LruCache<(PeerIndex, H256), Instant>>,
+    ibd_finished: AtomicBool,
+    sync_state: RwLock<SyncState>,
+    fork_stats: RwLock<ForkStats>,
+    // Tip number and time when the node started, to estimate the download speed
+    sync_start: (BlockNumber, u64),
 }
 
 impl<CS: ChainStore> SyncSharedState<CS> {
@@ -392,6 +596,19 @@ impl<CS: ChainStore> SyncSharedState<CS> {
         let header_map = RwLock::new(HashMap::new());
         let get_headers_cache = RwLock::new(LruCache::new(GET_HEADERS_CACHE_SIZE));
         let epoch_map = RwLock::new(EpochIndices::default());
+        let now = unix_time_as_millis();
+        let sync_state = {
+            let tip = best_known_header.read();
+            SyncState {
+                best_known_header_number: tip.number(),
+                best_known_header_hash: tip.hash().to_owned(),
+                tip_number: tip.number(),
+                tip_hash: tip.hash().to_owned(),
+                updated_at: now,
+                ..Default::default()
+            }
+        };
+        let sync_start = (sync_state.tip_number, now);
 
         SyncSharedState {
             shared,
@@ -399,6 +616,10 @@ impl<CS: ChainStore> SyncSharedState<CS> {
             epoch_map,
             best_known_header,
             get_headers_cache,
+            ibd_finished: AtomicBool::new(false),
+            sync_state: RwLock::new(sync_state),
+            fork_stats: RwLock::new(ForkStats::default()),
+            sync_start,
         }
     }
 
@@ -426,10 +647,112 @@ impl<CS: ChainStore> SyncSharedState<CS> {
     pub fn consensus(&self) -> &Consensus {
         self.shared.consensus()
     }
+    /// The node is in initial block download while its tip is older than
+    /// `MAX_TIP_AGE` or far behind the best known header. Once caught up it
+    /// stays out of it, a stalled tip later does not suppress relay again.
     pub fn is_initial_block_download(&self) -> bool {
-        unix_time_as_millis()
-            .saturating_sub(self.shared.chain_state().lock().tip_header().timestamp())
-            > MAX_TIP_AGE
+        if self.ibd_finished.load(Ordering::Acquire) {
+            return false;
+        }
+        let tip_header = self.tip_header();
+        if unix_time_as_millis().saturating_sub(tip_header.timestamp()) > MAX_TIP_AGE
+            || self.best_known_header().number()
+                > tip_header.number().saturating_add(IBD_MAX_BLOCKS_BEHIND)
+        {
+            return true;
+        }
+        if !self.ibd_finished.swap(true, Ordering::AcqRel) {
+            info!(target: "sync", "leave initial block download at block {}", tip_header.number());
+        }
+        false
+    }
+
+    pub fn sync_state(&self) -> SyncState {
+        self.sync_state.read().to_owned()
+    }
+
+    pub fn update_sync_state(&self, inflight_blocks: usize, orphan_blocks: usize) {
+        let tip_header = self.tip_header();
+        let best_known_header = self.best_known_header();
+        let now = unix_time_as_millis();
+        let (start_number, start_time) = self.sync_start;
+        let downloaded = tip_header.number().saturating_sub(start_number);
+        let remaining = best_known_header
+            .number()
+            .saturating_sub(tip_header.number());
+        let estimated_remaining_time = if remaining == 0 {
+            Some(0)
+        } else if downloaded == 0 {
+            None
+        } else {
+            Some(remaining.saturating_mul(now.saturating_sub(start_time)) / downloaded)
+        };
+        *self.sync_state.write() = SyncState {
+            best_known_header_number: best_known_header.number(),
+            best_known_header_hash: best_known_header.hash().to_owned(),
+            tip_number: tip_header.number(),
+            tip_hash: tip_header.hash().to_owned(),
+            inflight_blocks,
+            orphan_blocks,
+            estimated_remaining_time,
+            updated_at: now,
+        };
+    }
+
+    pub fn fork_stats(&self) -> ForkStats {
+        self.fork_stats.read().to_owned()
+    }
+
+    /// Updates the fork stats once `block` is processed, `old_tip` is the tip
+    /// before processing it
+    pub fn record_processed_block(&self, old_tip: &Header, block: &Block) {
+        let new_tip = self.tip_header();
+        let header = block.header();
+        if new_tip.hash() != header.hash() {
+            let mut fork_stats = self.fork_stats.write();
+            fork_stats.stale_blocks += 1;
+            let forks_main_chain = header.number() > 0
+                && self.shared.block_hash(header.number() - 1).as_ref()
+                    == Some(header.parent_hash());
+            if forks_main_chain {
+                fork_stats.competing_tips += 1;
+            }
+            return;
+        }
+        if header.parent_hash() == old_tip.hash() {
+            return;
+        }
+
+        // Walk back the old chain to the first block still on the main chain
+        let mut fork_point = old_tip.to_owned();
+        while self.shared.block_hash(fork_point.number()).as_ref() != Some(fork_point.hash()) {
+            match self.get_header(fork_point.parent_hash()) {
+                Some(parent) => fork_point = parent,
+                None => return,
+            }
+        }
+        let depth = old_tip.number() - fork_point.number();
+        info!(
+            target: "sync",
+            "reorganize from {} {:x} to {} {:x}, fork at {}",
+            old_tip.number(),
+            old_tip.hash(),
+            new_tip.number(),
+            new_tip.hash(),
+            fork_point.number(),
+        );
+        let mut fork_stats = self.fork_stats.write();
+        fork_stats.reorgs += 1;
+        *fork_stats.reorg_depths.entry(depth).or_insert(0) += 1;
+        fork_stats.last_reorg = Some(ReorgInfo {
+            old_tip_number: old_tip.number(),
+            old_tip_hash: old_tip.hash().to_owned(),
+            new_tip_number: new_tip.number(),
+            new_tip_hash: new_tip.hash().to_owned(),
+            fork_number: fork_point.number(),
+            depth,
+            timestamp: unix_time_as_millis(),
+        });
     }
 
     pub fn best_known_header(&self) -> HeaderView {
@@ -438,6 +761,20 @@ impl<CS: ChainStore> SyncSharedState<CS> {
     pub fn set_best_known_header(&self, header: HeaderView) {
         *self.best_known_header.write() = header;
     }
+    /// Resets the best known header to the tip once the main chain is
+    /// truncated below it, the peers announce their best headers again
+    pub fn reset_best_known_header(&self) {
+        let chain_state = self.shared.chain_state().lock();
+        let block_ext = self
+            .shared
+            .block_ext(chain_state.tip_hash())
+            .expect("tip block_ext must exist");
+        self.set_best_known_header(HeaderView::new(
+            chain_state.tip_header().to_owned(),
+            chain_state.total_difficulty().to_owned(),
+            block_ext.total_uncles_count,
+        ));
+    }
 
     pub fn insert_header_view(&self, hash: H256, header: HeaderView) {
         self.header_map.write().insert(hash, header);
@@ -31,6 +31,8 @@ fn main() {
             "depent_tx_in_same_block" => Box::new(DepentTxInSameBlock),
             "cellbase_immature_tx" => Box::new(CellbaseImmatureTx),
             "different_txs_with_same_input" => Box::new(DifferentTxsWithSameInput),
+            "resurrect_detached_tx" => Box::new(ResurrectDetachedTx),
+            "submit_transaction" => Box::new(SubmitTransaction),
             _ => panic!("invalid spec"),
         };
         let net = spec.setup_net(&binary, start_port);
@@ -49,6 +51,8 @@ fn main() {
             Box::new(DepentTxInSameBlock),
             Box::new(CellbaseImmatureTx),
             Box::new(DifferentTxsWithSameInput),
+            Box::new(ResurrectDetachedTx),
+            Box::new(SubmitTransaction),
         ];
 
         specs.iter().for_each(|spec| {
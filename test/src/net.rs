@@ -52,6 +52,19 @@ impl Net {
                 bootnodes: vec![],
                 dns_seeds: vec![],
                 reserved_peers: vec![],
+                whitelist_peers: vec![],
+                max_anchor_peers: 0,
+                port_mapping: false,
+                nat_pmp_gateway: None,
+                max_peer_upload_rate: 0,
+                max_upload_rate: 0,
+                ws_listen_address: None,
+                max_ws_peers: 0,
+                allowlist_only: false,
+                allowlist: vec![],
+                outbound_rotation_interval_secs: 0,
+                outbound_rotation_count: 0,
+                max_outbound_peers_per_group: 2,
                 reserved_only: false,
                 max_peers: 1,
                 max_outbound_peers: 1,
@@ -1,13 +1,13 @@
 use jsonrpc_client_core::{expand_params, jsonrpc_client};
 use jsonrpc_types::{
-    Block, BlockTemplate, BlockView, HeaderView, Node, Transaction, TransactionWithStatus,
-    TxPoolInfo, TxTrace,
+    Block, BlockTemplate, BlockView, ChainInfo, HeaderView, Node, RemoteNode, SyncState,
+    Transaction, TransactionWithStatus, TxPoolInfo, TxStatus, TxTrace,
 };
 use numext_fixed_hash::H256;
 
 jsonrpc_client!(pub struct RpcClient {
     pub fn local_node_info(&mut self) -> RpcRequest<Node>;
-    pub fn get_peers(&mut self) -> RpcRequest<Vec<Node>>;
+    pub fn get_peers(&mut self) -> RpcRequest<Vec<RemoteNode>>;
 
     pub fn add_node(&mut self, peer_id: String, address: String) -> RpcRequest<()>;
 
@@ -21,7 +21,11 @@ jsonrpc_client!(pub struct RpcClient {
     pub fn submit_block(&mut self, work_id: String, data: Block) -> RpcRequest<Option<H256>>;
 
     pub fn send_transaction(&mut self, tx: Transaction) -> RpcRequest<H256>;
+    pub fn submit_transaction(&mut self, tx: Transaction) -> RpcRequest<H256>;
+    pub fn get_transaction_status(&mut self, hash: H256) -> RpcRequest<Option<TxStatus>>;
     pub fn tx_pool_info(&mut self) -> RpcRequest<TxPoolInfo>;
+    pub fn get_blockchain_info(&mut self) -> RpcRequest<ChainInfo>;
+    pub fn sync_state(&mut self) -> RpcRequest<SyncState>;
     pub fn trace_transaction(&mut self, tx: Transaction) -> RpcRequest<H256>;
     pub fn get_transaction_trace(&mut self, hash: H256) -> RpcRequest<Option<Vec<TxTrace>>>;
 
@@ -31,4 +35,5 @@ jsonrpc_client!(pub struct RpcClient {
     pub fn get_tip_header(&mut self) -> RpcRequest<HeaderView>;
     pub fn get_tip_block_number(&mut self) -> RpcRequest<String>;
     pub fn enqueue_test_transaction(&mut self, tx: Transaction) -> RpcRequest<H256>;
+    pub fn truncate(&mut self, target_tip_hash: H256) -> RpcRequest<()>;
 });
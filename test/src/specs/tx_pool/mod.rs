@@ -1,7 +1,11 @@
 mod cellbase_immature_tx;
 mod depend_tx_in_same_block;
 mod different_txs_with_same_input;
+mod resurrect_detached_tx;
+mod submit_transaction;
 
 pub use cellbase_immature_tx::CellbaseImmatureTx;
 pub use depend_tx_in_same_block::DepentTxInSameBlock;
 pub use different_txs_with_same_input::DifferentTxsWithSameInput;
+pub use resurrect_detached_tx::ResurrectDetachedTx;
+pub use submit_transaction::SubmitTransaction;
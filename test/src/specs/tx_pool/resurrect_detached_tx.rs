@@ -0,0 +1,63 @@
+use crate::{Net, Spec};
+use log::info;
+
+pub struct ResurrectDetachedTx;
+
+impl Spec for ResurrectDetachedTx {
+    fn run(&self, net: Net) {
+        info!("Running ResurrectDetachedTx");
+        let node0 = &net.nodes[0];
+
+        node0.generate_block();
+        let parent_hash = node0.generate_transaction();
+        node0.generate_block();
+        node0.generate_block();
+        node0.generate_block();
+        let commit_block = node0.get_tip_block();
+        assert!(commit_block
+            .transactions()
+            .iter()
+            .any(|tx| tx.hash() == &parent_hash));
+
+        info!("Send a tx spending the committed one");
+        let child = node0.new_transaction(parent_hash.clone());
+        let child_hash = node0
+            .rpc_client()
+            .send_transaction((&child).into())
+            .call()
+            .unwrap();
+
+        info!("Detach the block committing the parent tx");
+        node0
+            .rpc_client()
+            .truncate(commit_block.header().parent_hash().to_owned())
+            .call()
+            .unwrap();
+
+        info!("The parent tx is resurrected and the child tx kept in the pool");
+        for hash in &[&parent_hash, &child_hash] {
+            assert!(node0
+                .rpc_client()
+                .get_transaction((*hash).clone())
+                .call()
+                .unwrap()
+                .expect("tx in the pool")
+                .tx_status
+                .block_hash
+                .is_none());
+        }
+
+        info!("Both txs are committed in the next + 2 block");
+        node0.generate_block();
+        node0.generate_block();
+        node0.generate_block();
+        let tip_block = node0.get_tip_block();
+        for hash in &[&parent_hash, &child_hash] {
+            assert!(tip_block.transactions().iter().any(|tx| tx.hash() == *hash));
+        }
+    }
+
+    fn num_nodes(&self) -> usize {
+        1
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{sleep, Net, Spec};
+use jsonrpc_types::Status;
+use log::info;
+
+pub struct SubmitTransaction;
+
+impl Spec for SubmitTransaction {
+    fn run(&self, net: Net) {
+        info!("Running SubmitTransaction");
+        let node0 = &net.nodes[0];
+
+        node0.generate_block();
+        let cellbase = node0.get_tip_block().transactions()[0].clone();
+        let tx = node0.new_transaction(cellbase.hash().to_owned());
+
+        info!("Submit a tx, its hash is returned before the verification");
+        let hash = node0
+            .rpc_client()
+            .submit_transaction((&tx).into())
+            .call()
+            .unwrap();
+        assert_eq!(&hash, tx.hash());
+
+        info!("The verified tx is pending");
+        let mut status = Status::PendingVerification;
+        for _ in 0..10 {
+            status = node0
+                .rpc_client()
+                .get_transaction_status(hash.clone())
+                .call()
+                .unwrap()
+                .expect("submitted tx status")
+                .status;
+            if status != Status::PendingVerification {
+                break;
+            }
+            sleep(1);
+        }
+        assert_eq!(status, Status::Pending);
+
+        info!("The tx is committed in the next + 2 block");
+        node0.generate_block();
+        node0.generate_block();
+        node0.generate_block();
+        let tx_status = node0
+            .rpc_client()
+            .get_transaction_status(hash.clone())
+            .call()
+            .unwrap()
+            .expect("committed tx status");
+        assert_eq!(tx_status.status, Status::Committed);
+        assert!(tx_status.block_hash.is_some());
+    }
+
+    fn num_nodes(&self) -> usize {
+        1
+    }
+}
@@ -1,4 +1,4 @@
-use ckb_core::BlockNumber;
+use ckb_core::{BlockNumber, EpochNumber};
 
 /// The invoker should only rely on `block_median_time` function
 /// the other functions only use to help the default `block_median_time`, and maybe unimplemented.
@@ -6,6 +6,10 @@ pub trait BlockMedianTimeContext {
     fn median_block_count(&self) -> u64;
     /// block timestamp
     fn timestamp(&self, block_number: BlockNumber) -> Option<u64>;
+    /// epoch number of a block, required by epoch based `since` locks
+    fn epoch_number(&self, _block_number: BlockNumber) -> Option<EpochNumber> {
+        None
+    }
     /// ancestor timestamps from a block
     fn ancestor_timestamps(&self, block_number: BlockNumber) -> Vec<u64> {
         let count = self.median_block_count();
@@ -1,4 +1,5 @@
 use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::versionbits::ThresholdState;
 use ckb_core::block::Block;
 use ckb_core::extras::{BlockExt, EpochExt};
 use ckb_core::header::{BlockNumber, Header};
@@ -35,5 +36,16 @@ pub trait ChainProvider: Sync + Send {
 
     fn next_epoch_ext(&self, last_epoch: &EpochExt, header: &Header) -> Option<EpochExt>;
 
+    /// Deployment states in `epoch`, in the order of `Consensus::deployments`
+    fn versionbits_states(&self, epoch: &EpochExt) -> Vec<ThresholdState>;
+
+    /// Deployment states in the epoch following `last_epoch`, `header` is the
+    /// last block of `last_epoch`
+    fn next_versionbits_states(
+        &self,
+        last_epoch: &EpochExt,
+        header: &Header,
+    ) -> Vec<ThresholdState>;
+
     fn consensus(&self) -> &Consensus;
 }
@@ -13,11 +13,13 @@ use ckb_chain_spec::ChainSpec;
 use ckb_db::DBConfig;
 use ckb_miner::BlockAssemblerConfig;
 use ckb_miner::MinerConfig;
+use ckb_miner::StratumConfig;
 use ckb_network::NetworkConfig;
 use ckb_resource::{Resource, ResourceLocator};
 use ckb_rpc::Config as RpcConfig;
 use ckb_script::ScriptConfig;
 use ckb_shared::tx_pool::TxPoolConfig;
+use ckb_store::StoreConfig;
 use ckb_sync::Config as SyncConfig;
 use logger::Config as LogConfig;
 
@@ -43,13 +45,19 @@ pub struct CKBAppConfig {
     pub chain: ChainConfig,
 
     pub block_assembler: BlockAssemblerConfig,
-    #[serde(skip)]
+    #[serde(default)]
     pub db: DBConfig,
+    #[serde(skip)]
+    pub indexer_db: DBConfig,
     pub network: NetworkConfig,
     pub rpc: RpcConfig,
     pub sync: SyncConfig,
     pub tx_pool: TxPoolConfig,
     pub script: ScriptConfig,
+    #[serde(default)]
+    pub store: StoreConfig,
+    #[serde(default)]
+    pub stratum: Option<StratumConfig>,
 }
 
 // change the order of fields will break integration test, see module doc.
@@ -66,6 +74,13 @@ pub struct MinerAppConfig {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChainConfig {
     pub spec: PathBuf,
+    /// Discard the bodies of the main chain blocks deeper than this, keep all of them when absent
+    #[serde(default)]
+    pub prune_depth: Option<u64>,
+    /// Move the main chain blocks older than this number of epochs into the
+    /// flat files of `data_dir/freezer`, keep all of them in the db when absent
+    #[serde(default)]
+    pub freeze_epochs: Option<u64>,
 }
 
 impl AppConfig {
@@ -158,6 +173,22 @@ impl AppConfigContent {
 }
 
 impl CKBAppConfig {
+    /// The directory of the freezer holding the ancient blocks
+    pub fn freezer_dir(&self) -> PathBuf {
+        self.data_dir.join("freezer")
+    }
+
+    /// The directory of the freezer, absent when no block was ever frozen. The
+    /// frozen blocks are still served once the freezing is disabled.
+    pub fn freezer_path(&self) -> Option<PathBuf> {
+        let path = self.freezer_dir();
+        if self.chain.freeze_epochs.is_some() || path.exists() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
     fn derive_options(mut self, root_dir: &Path, subcommand_name: &str) -> Result<Self, ExitCode> {
         self.data_dir = canonicalize_data_dir(self.data_dir, root_dir)?;
         if self.logger.log_to_file {
@@ -166,6 +197,7 @@ impl CKBAppConfig {
             )?);
         }
         self.db.path = mkdir(self.data_dir.join("db"))?;
+        self.indexer_db.path = mkdir(self.data_dir.join("indexer_db"))?;
         self.network.path = mkdir(self.data_dir.join("network"))?;
 
         Ok(self)
@@ -232,6 +264,10 @@ mod tests {
             Some(locator.root_dir().join("data/logs/run.log"))
         );
         assert_eq!(ckb_config.db.path, locator.root_dir().join("data/db"));
+        assert_eq!(
+            ckb_config.indexer_db.path,
+            locator.root_dir().join("data/indexer_db")
+        );
         assert_eq!(
             ckb_config.network.path,
             locator.root_dir().join("data/network")
@@ -276,6 +312,8 @@ mod tests {
             );
             assert_eq!(ckb_config.network.connect_outbound_interval_secs, 15);
             assert_eq!(ckb_config.rpc.listen_address, "0.0.0.0:7000");
+            assert!(ckb_config.rpc.http_enable);
+            assert_eq!(ckb_config.rpc.tcp_listen_address, None);
         }
         {
             locator.export_miner(&context).expect("export config files");
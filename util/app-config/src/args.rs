@@ -1,9 +1,12 @@
 use super::app_config::CKBAppConfig;
 use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::Genesis;
+use ckb_db::DBBackend;
 use ckb_instrument::Format;
 use ckb_miner::MinerConfig;
 use ckb_pow::PowEngine;
 use ckb_resource::ResourceLocator;
+use numext_fixed_hash::H256;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -19,11 +22,41 @@ pub struct ImportArgs {
     pub consensus: Consensus,
     pub format: Format,
     pub source: PathBuf,
+    /// Skip running the scripts of the imported transactions
+    pub fast: bool,
+}
+
+pub struct BackupArgs {
+    pub config: Box<CKBAppConfig>,
+    pub target: PathBuf,
+}
+
+pub struct RestoreArgs {
+    pub config: Box<CKBAppConfig>,
+    pub source: PathBuf,
+}
+
+pub struct CompactArgs {
+    pub config: Box<CKBAppConfig>,
+    pub column: Option<u32>,
+}
+
+pub struct StatsArgs {
+    pub config: Box<CKBAppConfig>,
+}
+
+pub struct ConvertArgs {
+    pub config: Box<CKBAppConfig>,
+    pub backend: DBBackend,
+    pub target: PathBuf,
 }
 
 pub struct RunArgs {
     pub config: Box<CKBAppConfig>,
     pub consensus: Consensus,
+    /// The `genesis` section of the chain spec the consensus is built from
+    pub genesis: Genesis,
+    pub assume_valid_target: Option<H256>,
 }
 
 pub struct ProfArgs {
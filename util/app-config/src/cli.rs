@@ -11,6 +11,15 @@ pub const CMD_PROF: &str = "prof";
 pub const CMD_CLI: &str = "cli";
 pub const CMD_KEYGEN: &str = "keygen";
 pub const CMD_HASHES: &str = "hashes";
+pub const CMD_DEBUG_SCRIPT: &str = "debug-script";
+pub const CMD_SCRIPT_CONSTANTS: &str = "script-constants";
+pub const CMD_RPC_SCHEMA: &str = "rpc-schema";
+pub const CMD_DB: &str = "db";
+pub const CMD_BACKUP: &str = "backup";
+pub const CMD_RESTORE: &str = "restore";
+pub const CMD_CONVERT: &str = "convert";
+pub const CMD_COMPACT: &str = "compact";
+pub const CMD_STATS: &str = "stats";
 
 pub const ARG_CONFIG_DIR: &str = "config-dir";
 pub const ARG_FORMAT: &str = "format";
@@ -24,6 +33,15 @@ pub const ARG_RPC_PORT: &str = "rpc-port";
 pub const ARG_FORCE: &str = "force";
 pub const ARG_LOG_TO: &str = "log-to";
 pub const ARG_BUNDLED: &str = "bundled";
+pub const ARG_TX_FILE: &str = "tx-file";
+pub const ARG_INPUT: &str = "input";
+pub const ARG_OUTPUT: &str = "output";
+pub const ARG_LISTEN: &str = "listen";
+pub const ARG_MAX_CYCLES: &str = "max-cycles";
+pub const ARG_ASSUME_VALID_TARGET: &str = "assume-valid-target";
+pub const ARG_FAST: &str = "fast";
+pub const ARG_BACKEND: &str = "backend";
+pub const ARG_COLUMN: &str = "column";
 
 pub fn get_matches() -> ArgMatches<'static> {
     let version = get_version!();
@@ -49,13 +67,25 @@ pub fn get_matches() -> ArgMatches<'static> {
         .subcommand(export())
         .subcommand(import())
         .subcommand(cli())
+        .subcommand(db())
         .subcommand(init())
         .subcommand(prof())
         .get_matches()
 }
 
 fn run() -> App<'static, 'static> {
-    SubCommand::with_name(CMD_RUN).about("Running ckb node")
+    SubCommand::with_name(CMD_RUN)
+        .about("Running ckb node")
+        .arg(
+            Arg::with_name(ARG_ASSUME_VALID_TARGET)
+                .long(ARG_ASSUME_VALID_TARGET)
+                .value_name("hash")
+                .takes_value(true)
+                .help(
+                    "Skip running the scripts of the blocks which are ancestors of the block \
+                     <hash>, to speed up the initial sync",
+                ),
+        )
 }
 
 fn miner() -> App<'static, 'static> {
@@ -121,6 +151,80 @@ fn import() -> App<'static, 'static> {
                 .index(1)
                 .help("Specify the exported data path."),
         )
+        .arg(
+            Arg::with_name(ARG_FAST)
+                .long(ARG_FAST)
+                .help("Skip running the scripts of the imported transactions"),
+        )
+}
+
+fn db() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_DB)
+        .about("Database tools")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(CMD_BACKUP)
+                .about("Copy a consistent snapshot of the chain database, the node must be stopped")
+                .arg(
+                    Arg::with_name(ARG_TARGET)
+                        .short("t")
+                        .long(ARG_TARGET)
+                        .value_name("path")
+                        .required(true)
+                        .index(1)
+                        .help("Specify the backup directory, which must not exist."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_RESTORE)
+                .about("Replace the empty chain database with a backup, the node must be stopped")
+                .arg(
+                    Arg::with_name(ARG_SOURCE)
+                        .short("s")
+                        .long(ARG_SOURCE)
+                        .value_name("path")
+                        .required(true)
+                        .index(1)
+                        .help("Specify the backup directory."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CONVERT)
+                .about("Copy the chain database into another backend, the node must be stopped")
+                .arg(
+                    Arg::with_name(ARG_BACKEND)
+                        .short("b")
+                        .long(ARG_BACKEND)
+                        .possible_values(&["rocksdb", "sled"])
+                        .required(true)
+                        .takes_value(true)
+                        .help("Specify the backend of the converted database."),
+                )
+                .arg(
+                    Arg::with_name(ARG_TARGET)
+                        .short("t")
+                        .long(ARG_TARGET)
+                        .value_name("path")
+                        .required(true)
+                        .index(1)
+                        .help("Specify the converted database directory, which must be empty."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_COMPACT)
+                .about("Reclaim the space of the deleted entries, the node must be stopped")
+                .arg(
+                    Arg::with_name(ARG_COLUMN)
+                        .short("c")
+                        .long(ARG_COLUMN)
+                        .value_name("column")
+                        .takes_value(true)
+                        .help("Specify the column to compact, all the columns when absent."),
+                ),
+        )
+        .subcommand(SubCommand::with_name(CMD_STATS).about(
+            "Print the size and the storage metrics of the columns, the node must be stopped",
+        ))
 }
 
 fn cli() -> App<'static, 'static> {
@@ -140,6 +244,61 @@ fn cli() -> App<'static, 'static> {
                         ),
                 ),
         )
+        .subcommand(debug_script())
+        .subcommand(SubCommand::with_name(CMD_SCRIPT_CONSTANTS).about(
+            "Print a C header of the syscall numbers and constants used by scripts\n\
+             Example: ckb cli script-constants > ckb_consts.h",
+        ))
+        .subcommand(SubCommand::with_name(CMD_RPC_SCHEMA).about(
+            "Print the OpenRPC document describing all the RPC methods\n\
+             Example: ckb cli rpc-schema > openrpc.json",
+        ))
+}
+
+fn debug_script() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_DEBUG_SCRIPT)
+        .about(
+            "Run a single script of a mock transaction under a GDB remote stub\n\
+             Example: ckb cli debug-script --tx-file tx.json --input 0\n\
+             Then connect with: riscv64-unknown-elf-gdb -ex 'target remote 127.0.0.1:2000'",
+        )
+        .arg(
+            Arg::with_name(ARG_TX_FILE)
+                .long(ARG_TX_FILE)
+                .value_name("path")
+                .required(true)
+                .takes_value(true)
+                .help("Mock transaction JSON file, containing the transaction and its resolved inputs and deps."),
+        )
+        .arg(
+            Arg::with_name(ARG_INPUT)
+                .long(ARG_INPUT)
+                .value_name("index")
+                .takes_value(true)
+                .required_unless(ARG_OUTPUT)
+                .conflicts_with(ARG_OUTPUT)
+                .help("Debug the lock script of the input at <index>."),
+        )
+        .arg(
+            Arg::with_name(ARG_OUTPUT)
+                .long(ARG_OUTPUT)
+                .value_name("index")
+                .takes_value(true)
+                .help("Debug the type script of the output at <index>."),
+        )
+        .arg(
+            Arg::with_name(ARG_LISTEN)
+                .long(ARG_LISTEN)
+                .value_name("address")
+                .default_value("127.0.0.1:2000")
+                .help("Address the GDB remote stub listens on."),
+        )
+        .arg(
+            Arg::with_name(ARG_MAX_CYCLES)
+                .long(ARG_MAX_CYCLES)
+                .default_value("1000000000")
+                .help("Maximum cycles the script may consume."),
+        )
 }
 
 fn init() -> App<'static, 'static> {
@@ -5,15 +5,20 @@ mod exit_code;
 mod sentry_config;
 
 pub use app_config::{AppConfig, CKBAppConfig, MinerAppConfig};
-pub use args::{ExportArgs, ImportArgs, InitArgs, MinerArgs, ProfArgs, RunArgs};
+pub use args::{
+    BackupArgs, CompactArgs, ConvertArgs, ExportArgs, ImportArgs, InitArgs, MinerArgs, ProfArgs,
+    RestoreArgs, RunArgs, StatsArgs,
+};
 pub use exit_code::ExitCode;
 
 use ckb_chain_spec::{consensus::Consensus, ChainSpec};
+use ckb_db::DBBackend;
 use ckb_instrument::Format;
 use ckb_resource::ResourceLocator;
 use clap::{value_t, ArgMatches};
 use log::info;
 use logger::LoggerInitGuard;
+use numext_fixed_hash::H256;
 use std::path::PathBuf;
 
 pub struct Setup {
@@ -89,11 +94,32 @@ impl Setup {
         })
     }
 
-    pub fn run(self) -> Result<RunArgs, ExitCode> {
-        let consensus = self.consensus()?;
-        let config = self.config.into_ckb()?;
+    pub fn run<'m>(self, matches: &ArgMatches<'m>) -> Result<RunArgs, ExitCode> {
+        let spec = self.chain_spec()?;
+        let consensus = self.spec_consensus(&spec)?;
+        let mut config = self.config.into_ckb()?;
+        for seed in spec.dns_seeds {
+            if !config.network.dns_seeds.contains(&seed) {
+                config.network.dns_seeds.push(seed);
+            }
+        }
+        let assume_valid_target = match matches.value_of(cli::ARG_ASSUME_VALID_TARGET) {
+            Some(hash) => {
+                let hash = hash.trim_start_matches("0x");
+                Some(H256::from_hex_str(hash).map_err(|err| {
+                    eprintln!("Invalid --{}: {:?}", cli::ARG_ASSUME_VALID_TARGET, err);
+                    ExitCode::Cli
+                })?)
+            }
+            None => None,
+        };
 
-        Ok(RunArgs { config, consensus })
+        Ok(RunArgs {
+            config,
+            consensus,
+            genesis: spec.genesis,
+            assume_valid_target,
+        })
     }
 
     pub fn miner(self) -> Result<MinerArgs, ExitCode> {
@@ -126,12 +152,14 @@ impl Setup {
         let config = self.config.into_ckb()?;
         let format = value_t!(matches.value_of(cli::ARG_FORMAT), Format)?;
         let source = value_t!(matches.value_of(cli::ARG_SOURCE), PathBuf)?;
+        let fast = matches.is_present(cli::ARG_FAST);
 
         Ok(ImportArgs {
             config,
             consensus,
             format,
             source,
+            fast,
         })
     }
 
@@ -149,6 +177,52 @@ impl Setup {
         })
     }
 
+    pub fn backup<'m>(self, matches: &ArgMatches<'m>) -> Result<BackupArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let target = value_t!(matches.value_of(cli::ARG_TARGET), PathBuf)?;
+
+        Ok(BackupArgs { config, target })
+    }
+
+    pub fn restore<'m>(self, matches: &ArgMatches<'m>) -> Result<RestoreArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let source = value_t!(matches.value_of(cli::ARG_SOURCE), PathBuf)?;
+
+        Ok(RestoreArgs { config, source })
+    }
+
+    pub fn convert<'m>(self, matches: &ArgMatches<'m>) -> Result<ConvertArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let backend = match matches.value_of(cli::ARG_BACKEND) {
+            Some("sled") => DBBackend::Sled,
+            _ => DBBackend::RocksDB,
+        };
+        let target = value_t!(matches.value_of(cli::ARG_TARGET), PathBuf)?;
+
+        Ok(ConvertArgs {
+            config,
+            backend,
+            target,
+        })
+    }
+
+    pub fn compact<'m>(self, matches: &ArgMatches<'m>) -> Result<CompactArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let column = if matches.is_present(cli::ARG_COLUMN) {
+            Some(value_t!(matches.value_of(cli::ARG_COLUMN), u32)?)
+        } else {
+            None
+        };
+
+        Ok(CompactArgs { config, column })
+    }
+
+    pub fn stats(self) -> Result<StatsArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+
+        Ok(StatsArgs { config })
+    }
+
     pub fn init<'m>(matches: &ArgMatches<'m>) -> Result<InitArgs, ExitCode> {
         let locator = Self::locator_from_matches(matches)?;
         let export_specs = matches.is_present(cli::ARG_EXPORT_SPECS);
@@ -200,7 +274,11 @@ impl Setup {
     }
 
     fn consensus(&self) -> Result<Consensus, ExitCode> {
-        let result = consensus_from_spec(&self.chain_spec()?);
+        self.spec_consensus(&self.chain_spec()?)
+    }
+
+    fn spec_consensus(&self, spec: &ChainSpec) -> Result<Consensus, ExitCode> {
+        let result = consensus_from_spec(spec);
 
         if let Ok(consensus) = &result {
             if self.is_sentry_enabled {
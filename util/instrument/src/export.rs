@@ -1,7 +1,7 @@
-use crate::format::Format;
-use crate::iter::ChainIterator;
+use crate::format::{write_binary_block, Format};
+use ckb_core::block::Block;
 use ckb_shared::shared::Shared;
-use ckb_store::ChainStore;
+use ckb_store::{BlockIter, ChainStore};
 use ckb_traits::ChainProvider;
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
@@ -30,9 +30,9 @@ impl<CS: ChainStore> Export<CS> {
         }
     }
 
-    /// Returning ChainIterator dealing with blocks iterate.
-    pub fn iter(&self) -> ChainIterator<CS> {
-        ChainIterator::new(self.shared.clone())
+    /// Returning the iterator over the main chain blocks.
+    pub fn iter(&self) -> BlockIter<'_, CS> {
+        self.shared.store().iter_blocks(0)
     }
 
     /// export file name
@@ -41,53 +41,76 @@ impl<CS: ChainStore> Export<CS> {
     }
 
     pub fn execute(self) -> Result<(), Box<Error>> {
+        // the export must replay the whole chain on import
+        if let Some(pruned_number) = self.shared.store().get_pruned_number() {
+            Err(format!(
+                "the blocks up to {} are pruned, the chain can not be exported",
+                pruned_number
+            ))?;
+        }
         fs::create_dir_all(&self.target)?;
         match self.format {
             Format::Json => self.write_to_json(),
-            _ => Ok(()),
+            Format::Binary => self.write_to_binary(),
         }
     }
 
-    #[cfg(not(feature = "progress_bar"))]
-    pub fn write_to_json(self) -> Result<(), Box<Error>> {
+    fn create_file(&self) -> Result<fs::File, Box<Error>> {
         let f = fs::OpenOptions::new()
             .create_new(true)
             .read(true)
             .write(true)
             .open(&self.target.join(self.file_name()))?;
-        let mut writer = io::BufWriter::new(f);
+        Ok(f)
+    }
 
-        for block in self.iter() {
-            let block: Block = block.into();
-            let encoded = serde_json::to_vec(&block)?;
+    pub fn write_to_json(self) -> Result<(), Box<Error>> {
+        let writer = io::BufWriter::new(self.create_file()?);
+        self.write_blocks(writer, |writer, block| {
+            let encoded = serde_json::to_vec(block)?;
             writer.write_all(&encoded)?;
             writer.write_all(b"\n")?;
+            Ok(())
+        })
+    }
+
+    /// Writes the blocks into a snappy compressed stream
+    pub fn write_to_binary(self) -> Result<(), Box<Error>> {
+        let writer = snap::Writer::new(self.create_file()?);
+        self.write_blocks(writer, write_binary_block)
+    }
+
+    #[cfg(not(feature = "progress_bar"))]
+    fn write_blocks<W, F>(&self, mut writer: W, write_block: F) -> Result<(), Box<Error>>
+    where
+        W: Write,
+        F: Fn(&mut W, &Block) -> Result<(), Box<Error>>,
+    {
+        for block in self.iter() {
+            write_block(&mut writer, &block)?;
         }
+        writer.flush()?;
         Ok(())
     }
 
     #[cfg(feature = "progress_bar")]
-    pub fn write_to_json(self) -> Result<(), Box<Error>> {
-        let f = fs::OpenOptions::new()
-            .create_new(true)
-            .read(true)
-            .write(true)
-            .open(&self.target.join(self.file_name()))?;
-        let mut writer = io::BufWriter::new(f);
-
-        let blocks_iter = self.iter();
-        let progress_bar = ProgressBar::new(blocks_iter.len());
+    fn write_blocks<W, F>(&self, mut writer: W, write_block: F) -> Result<(), Box<Error>>
+    where
+        W: Write,
+        F: Fn(&mut W, &Block) -> Result<(), Box<Error>>,
+    {
+        let tip_number = self.shared.chain_state().lock().tip_number();
+        let progress_bar = ProgressBar::new(tip_number + 1);
         progress_bar.set_style(
             ProgressStyle::default_bar()
                 .template("[{elapsed_precise}] {bar:50.cyan/blue} {pos:>6}/{len:6} {msg}")
                 .progress_chars("##-"),
         );
-        for block in blocks_iter {
-            let encoded = serde_json::to_vec(&block)?;
-            writer.write_all(&encoded)?;
-            writer.write_all(b"\n")?;
+        for block in self.iter() {
+            write_block(&mut writer, &block)?;
             progress_bar.inc(1);
         }
+        writer.flush()?;
         progress_bar.finish_with_message("done!");
         Ok(())
     }
@@ -1,4 +1,7 @@
+use ckb_core::block::Block;
+use std::error::Error;
 use std::fmt;
+use std::io::{self, Read, Write};
 use std::str::FromStr;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -27,3 +30,88 @@ impl FromStr for Format {
         }
     }
 }
+
+/// Writes the block prefixed by the length of its bincode encoding
+pub(crate) fn write_binary_block<W: Write>(
+    writer: &mut W,
+    block: &Block,
+) -> Result<(), Box<Error>> {
+    let encoded = bincode::serialize(block)?;
+    writer.write_all(&(encoded.len() as u32).to_le_bytes())?;
+    writer.write_all(&encoded)?;
+    Ok(())
+}
+
+// The bincode encoding also prefixes the lengths of the vectors, it is larger
+// than the serialized size of the block bounded by `max_block_bytes`
+const MAX_ENCODING_OVERHEAD: u64 = 2;
+
+/// Reads the block written by `write_binary_block`, `None` at the end of the stream.
+///
+/// A length prefix beyond the encoding of a block of `max_block_bytes` is
+/// refused before its buffer is allocated.
+pub(crate) fn read_binary_block<R: Read>(
+    reader: &mut R,
+    max_block_bytes: u64,
+) -> Result<Option<Block>, Box<Error>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => Err(err)?,
+    }
+    let length = u64::from(u32::from_le_bytes(length));
+    let max_length = max_block_bytes.saturating_mul(MAX_ENCODING_OVERHEAD);
+    if length > max_length {
+        Err(format!(
+            "the encoded block of {} bytes exceeds {} bytes",
+            length, max_length
+        ))?;
+    }
+    let mut encoded = vec![0; length as usize];
+    reader.read_exact(&mut encoded)?;
+    Ok(Some(bincode::deserialize(&encoded)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_core::block::BlockBuilder;
+    use ckb_core::header::HeaderBuilder;
+
+    #[test]
+    fn test_binary_block_round_trip() {
+        let blocks: Vec<Block> = (0..3)
+            .map(|number| {
+                BlockBuilder::default()
+                    .header_builder(HeaderBuilder::default().number(number))
+                    .build()
+            })
+            .collect();
+        let mut stream = Vec::new();
+        for block in &blocks {
+            write_binary_block(&mut stream, block).unwrap();
+        }
+
+        let mut reader = &stream[..];
+        let mut read = Vec::new();
+        while let Some(block) = read_binary_block(&mut reader, 1000).unwrap() {
+            read.push(block);
+        }
+        assert_eq!(read, blocks);
+    }
+
+    #[test]
+    fn test_read_oversized_binary_block() {
+        let mut stream = Vec::new();
+        write_binary_block(&mut stream, &BlockBuilder::default().build()).unwrap();
+        let length = stream.len() as u64 - 4;
+        let max_block_bytes = length / MAX_ENCODING_OVERHEAD;
+        assert!(read_binary_block(&mut &stream[..], max_block_bytes + 1).is_ok());
+        assert!(read_binary_block(&mut &stream[..], max_block_bytes - 1).is_err());
+
+        // the length prefix is refused before the block is read
+        let stream = u32::max_value().to_le_bytes();
+        assert!(read_binary_block(&mut &stream[..], 1000).is_err());
+    }
+}
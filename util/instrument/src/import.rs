@@ -1,8 +1,9 @@
-use crate::format::Format;
+use crate::format::{read_binary_block, Format};
 use ckb_chain::chain::ChainController;
 use ckb_core::block::Block;
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
+use numext_fixed_hash::H256;
 use serde_json;
 use std::error::Error;
 use std::fs;
@@ -18,22 +19,65 @@ pub struct Import {
     chain: ChainController,
     /// source file format
     format: Format,
+    /// the genesis hash of the chain the blocks must belong to
+    genesis_hash: H256,
+    /// the consensus limit of the size of the blocks
+    max_block_bytes: u64,
+    /// skip running the scripts of the imported transactions
+    fast: bool,
 }
 
 impl Import {
-    pub fn new(chain: ChainController, format: Format, source: PathBuf) -> Self {
+    pub fn new(
+        chain: ChainController,
+        format: Format,
+        source: PathBuf,
+        genesis_hash: H256,
+        max_block_bytes: u64,
+    ) -> Self {
         Import {
             format,
             chain,
             source,
+            genesis_hash,
+            max_block_bytes,
+            fast: false,
         }
     }
 
+    /// Trusts the scripts of the source, only the blocks and the transactions
+    /// structure are verified
+    pub fn fast(mut self, fast: bool) -> Self {
+        self.fast = fast;
+        self
+    }
+
     pub fn execute(self) -> Result<(), Box<Error>> {
         match self.format {
             Format::Json => self.read_from_json(),
-            _ => Ok(()),
+            Format::Binary => self.read_from_binary(),
+        }
+    }
+
+    fn import_block(&self, block: Block) -> Result<(), Box<Error>> {
+        if block.is_genesis() {
+            if block.header().hash() != &self.genesis_hash {
+                Err(format!(
+                    "the genesis block {:#x} does not match {:#x}",
+                    block.header().hash(),
+                    self.genesis_hash
+                ))?;
+            }
+            return Ok(());
         }
+        let number = block.header().number();
+        let block = Arc::new(block);
+        let result = if self.fast {
+            self.chain.process_assumed_valid_block(block)
+        } else {
+            self.chain.process_block(block)
+        };
+        result.map_err(|err| format!("import block {} error: {}", number, err).into())
     }
 
     #[cfg(not(feature = "progress_bar"))]
@@ -43,12 +87,7 @@ impl Import {
 
         for line in reader.lines() {
             let s = line?;
-            let block: Arc<Block> = Arc::new(serde_json::from_str(&s)?);
-            if !block.is_genesis() {
-                self.chain
-                    .process_block(block)
-                    .expect("import occur malformation data");
-            }
+            self.import_block(serde_json::from_str(&s)?)?;
         }
         Ok(())
     }
@@ -66,15 +105,38 @@ impl Import {
         );
         for line in reader.lines() {
             let s = line?;
-            let block: Arc<Block> = Arc::new(serde_json::from_str(&s)?);
-            if !block.is_genesis() {
-                self.chain
-                    .process_block(block)
-                    .expect("import occur malformation data");
-            }
+            self.import_block(serde_json::from_str(&s)?)?;
             progress_bar.inc(s.as_bytes().len() as u64);
         }
         progress_bar.finish_with_message("done!");
         Ok(())
     }
+
+    #[cfg(not(feature = "progress_bar"))]
+    pub fn read_from_binary(&self) -> Result<(), Box<Error>> {
+        let f = fs::File::open(&self.source)?;
+        let mut reader = snap::Reader::new(io::BufReader::new(f));
+
+        while let Some(block) = read_binary_block(&mut reader, self.max_block_bytes)? {
+            self.import_block(block)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "progress_bar")]
+    pub fn read_from_binary(&self) -> Result<(), Box<Error>> {
+        let f = fs::File::open(&self.source)?;
+        let mut reader = snap::Reader::new(io::BufReader::new(f));
+        // the number of blocks is unknown until the stream is decompressed
+        let progress_bar = ProgressBar::new_spinner();
+        progress_bar.set_style(
+            ProgressStyle::default_spinner().template("[{elapsed_precise}] {spinner} {pos} {msg}"),
+        );
+        while let Some(block) = read_binary_block(&mut reader, self.max_block_bytes)? {
+            self.import_block(block)?;
+            progress_bar.inc(1);
+        }
+        progress_bar.finish_with_message("blocks, done!");
+        Ok(())
+    }
 }
@@ -6,11 +6,13 @@
 //!   export function.
 //! - [Import](instrument::import::Import) import block data which
 //!   export from `Export`.
+//!
+//! The `json` format writes a block per line, the `bin` format writes a snappy
+//! compressed stream of length prefixed bincode blocks.
 
 mod export;
 mod format;
 mod import;
-mod iter;
 
 pub use crate::export::Export;
 pub use crate::format::Format;
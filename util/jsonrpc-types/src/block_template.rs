@@ -5,6 +5,7 @@ use failure::Error as FailureError;
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -85,3 +86,180 @@ impl TryFrom<TransactionTemplate> for CoreTransaction {
         data.try_into()
     }
 }
+
+/// Changes of a block template relative to a previously issued one, fields
+/// are `None` when unchanged.
+///
+/// The transactions of the new template are the ones of the base template
+/// without `removed_transactions`, in the same order, followed by
+/// `added_transactions`.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct BlockTemplateDelta {
+    pub base_work_id: String,
+    pub work_id: String,
+    pub version: Option<Version>,
+    pub difficulty: Option<U256>,
+    pub current_time: Option<String>,
+    pub number: Option<String>,
+    pub epoch: Option<String>,
+    pub parent_hash: Option<H256>,
+    pub cycles_limit: Option<String>,
+    pub bytes_limit: Option<String>,
+    pub uncles_count_limit: Option<u32>,
+    pub uncles: Option<Vec<UncleTemplate>>,
+    pub proposals: Option<Vec<ProposalShortId>>,
+    pub cellbase: Option<CellbaseTemplate>,
+    pub added_transactions: Vec<TransactionTemplate>,
+    pub removed_transactions: Vec<H256>,
+}
+
+fn changed<T: PartialEq + Clone>(base: &T, new: &T) -> Option<T> {
+    if base == new {
+        None
+    } else {
+        Some(new.clone())
+    }
+}
+
+impl BlockTemplateDelta {
+    pub fn new(base: &BlockTemplate, new: &BlockTemplate) -> Self {
+        let base_hashes: HashSet<&H256> = base.transactions.iter().map(|tx| &tx.hash).collect();
+        // The longest prefix of the new transactions also found in the base
+        // template, the rest is appended
+        let kept = new
+            .transactions
+            .iter()
+            .take_while(|tx| base_hashes.contains(&tx.hash))
+            .count();
+        let kept_hashes: HashSet<&H256> =
+            new.transactions[..kept].iter().map(|tx| &tx.hash).collect();
+        let in_order = base
+            .transactions
+            .iter()
+            .filter(|tx| kept_hashes.contains(&tx.hash))
+            .map(|tx| &tx.hash)
+            .eq(new.transactions[..kept].iter().map(|tx| &tx.hash));
+        // Reordered transactions can not be expressed as removals followed by
+        // additions, replace all of them
+        let (kept_hashes, added) = if in_order {
+            (kept_hashes, &new.transactions[kept..])
+        } else {
+            (HashSet::new(), &new.transactions[..])
+        };
+
+        BlockTemplateDelta {
+            base_work_id: base.work_id.clone(),
+            work_id: new.work_id.clone(),
+            version: changed(&base.version, &new.version),
+            difficulty: changed(&base.difficulty, &new.difficulty),
+            current_time: changed(&base.current_time, &new.current_time),
+            number: changed(&base.number, &new.number),
+            epoch: changed(&base.epoch, &new.epoch),
+            parent_hash: changed(&base.parent_hash, &new.parent_hash),
+            cycles_limit: changed(&base.cycles_limit, &new.cycles_limit),
+            bytes_limit: changed(&base.bytes_limit, &new.bytes_limit),
+            uncles_count_limit: changed(&base.uncles_count_limit, &new.uncles_count_limit),
+            uncles: changed(&base.uncles, &new.uncles),
+            proposals: changed(&base.proposals, &new.proposals),
+            cellbase: changed(&base.cellbase, &new.cellbase),
+            added_transactions: added.to_vec(),
+            removed_transactions: base
+                .transactions
+                .iter()
+                .filter(|tx| !kept_hashes.contains(&tx.hash))
+                .map(|tx| tx.hash.clone())
+                .collect(),
+        }
+    }
+
+    /// Rebuilds the new template from the base one
+    pub fn apply(&self, base: &BlockTemplate) -> BlockTemplate {
+        let removed: HashSet<&H256> = self.removed_transactions.iter().collect();
+        let transactions = base
+            .transactions
+            .iter()
+            .filter(|tx| !removed.contains(&tx.hash))
+            .chain(self.added_transactions.iter())
+            .cloned()
+            .collect();
+        BlockTemplate {
+            version: self.version.unwrap_or(base.version),
+            difficulty: self
+                .difficulty
+                .clone()
+                .unwrap_or_else(|| base.difficulty.clone()),
+            current_time: self
+                .current_time
+                .clone()
+                .unwrap_or_else(|| base.current_time.clone()),
+            number: self.number.clone().unwrap_or_else(|| base.number.clone()),
+            epoch: self.epoch.clone().unwrap_or_else(|| base.epoch.clone()),
+            parent_hash: self
+                .parent_hash
+                .clone()
+                .unwrap_or_else(|| base.parent_hash.clone()),
+            cycles_limit: self
+                .cycles_limit
+                .clone()
+                .unwrap_or_else(|| base.cycles_limit.clone()),
+            bytes_limit: self
+                .bytes_limit
+                .clone()
+                .unwrap_or_else(|| base.bytes_limit.clone()),
+            uncles_count_limit: self.uncles_count_limit.unwrap_or(base.uncles_count_limit),
+            uncles: self.uncles.clone().unwrap_or_else(|| base.uncles.clone()),
+            transactions,
+            proposals: self
+                .proposals
+                .clone()
+                .unwrap_or_else(|| base.proposals.clone()),
+            cellbase: self
+                .cellbase
+                .clone()
+                .unwrap_or_else(|| base.cellbase.clone()),
+            work_id: self.work_id.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(work_id: &str, number: &str, txs: &[u8]) -> BlockTemplate {
+        BlockTemplate {
+            number: number.to_owned(),
+            work_id: work_id.to_owned(),
+            transactions: txs
+                .iter()
+                .map(|byte| TransactionTemplate {
+                    hash: H256::from_slice(&[*byte; 32]).unwrap(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_block_template_delta() {
+        let base = template("1", "10", &[1, 2, 3]);
+
+        let new = template("2", "10", &[1, 3, 4, 5]);
+        let delta = BlockTemplateDelta::new(&base, &new);
+        assert_eq!(delta.number, None);
+        assert_eq!(
+            delta.removed_transactions,
+            vec![H256::from_slice(&[2; 32]).unwrap()]
+        );
+        assert_eq!(delta.added_transactions, new.transactions[2..].to_vec());
+        assert_eq!(delta.apply(&base), new);
+
+        // reordered
+        let new = template("3", "11", &[3, 1]);
+        let delta = BlockTemplateDelta::new(&base, &new);
+        assert_eq!(delta.number, Some("11".to_owned()));
+        assert_eq!(delta.removed_transactions.len(), 3);
+        assert_eq!(delta.apply(&base), new);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::bytes::JsonBytes;
-use crate::{BlockNumber, Capacity, EpochNumber, ProposalShortId};
+use crate::verbosity::ResponseFormat;
+use crate::{BlockNumber, Capacity, Cycle, EpochNumber, ProposalShortId};
 use ckb_core::block::{Block as CoreBlock, BlockBuilder};
 use ckb_core::extras::EpochExt as CoreEpochExt;
 use ckb_core::header::{Header as CoreHeader, HeaderBuilder, Seal as CoreSeal};
@@ -283,7 +284,8 @@ impl TryFrom<TransactionView> for CoreTransaction {
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct TransactionWithStatus {
-    pub transaction: TransactionView,
+    /// Null for the rejected transactions, the pool keeps only their hashes
+    pub transaction: Option<ResponseFormat<TransactionView>>,
     /// Indicate the Transaction status
     pub tx_status: TxStatus,
 }
@@ -293,7 +295,7 @@ impl TransactionWithStatus {
     pub fn with_pending(tx: CoreTransaction) -> Self {
         Self {
             tx_status: TxStatus::pending(),
-            transaction: (&tx).into(),
+            transaction: Some(ResponseFormat::Json((&tx).into())),
         }
     }
 
@@ -301,7 +303,15 @@ impl TransactionWithStatus {
     pub fn with_proposed(tx: CoreTransaction) -> Self {
         Self {
             tx_status: TxStatus::proposed(),
-            transaction: (&tx).into(),
+            transaction: Some(ResponseFormat::Json((&tx).into())),
+        }
+    }
+
+    /// Build with rejected status, without the transaction
+    pub fn with_rejected(reason: String) -> Self {
+        Self {
+            tx_status: TxStatus::rejected(reason),
+            transaction: None,
         }
     }
 
@@ -309,7 +319,7 @@ impl TransactionWithStatus {
     pub fn with_committed(tx: CoreTransaction, hash: H256) -> Self {
         Self {
             tx_status: TxStatus::committed(hash),
-            transaction: (&tx).into(),
+            transaction: Some(ResponseFormat::Json((&tx).into())),
         }
     }
 }
@@ -318,25 +328,42 @@ impl TransactionWithStatus {
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum Status {
+    /// Transaction submitted, its scripts are being verified
+    #[serde(rename = "pending_verification")]
+    PendingVerification,
     /// Transaction on pool, not proposed
     Pending,
     /// Transaction on pool, proposed
     Proposed,
     /// Transaction commit on block
     Committed,
+    /// Transaction recently refused by or evicted from the pool
+    Rejected,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct TxStatus {
     pub status: Status,
     pub block_hash: Option<H256>,
+    /// Why the transaction is rejected, absent unless `rejected`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 impl TxStatus {
+    pub fn pending_verification() -> Self {
+        Self {
+            status: Status::PendingVerification,
+            block_hash: None,
+            reason: None,
+        }
+    }
+
     pub fn pending() -> Self {
         Self {
             status: Status::Pending,
             block_hash: None,
+            reason: None,
         }
     }
 
@@ -344,6 +371,7 @@ impl TxStatus {
         Self {
             status: Status::Proposed,
             block_hash: None,
+            reason: None,
         }
     }
 
@@ -351,6 +379,15 @@ impl TxStatus {
         Self {
             status: Status::Committed,
             block_hash: Some(hash),
+            reason: None,
+        }
+    }
+
+    pub fn rejected(reason: String) -> Self {
+        Self {
+            status: Status::Rejected,
+            block_hash: None,
+            reason: Some(reason),
         }
     }
 }
@@ -551,6 +588,28 @@ pub struct BlockView {
     pub proposals: Vec<ProposalShortId>,
 }
 
+/// The block view with the fees and cycles of the transactions except the
+/// cellbase, absent unless requested or not recorded yet
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct BlockWithExtView {
+    #[serde(flatten)]
+    pub block: BlockView,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txs_fees: Option<Vec<Capacity>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txs_cycles: Option<Vec<Cycle>>,
+}
+
+impl From<BlockView> for BlockWithExtView {
+    fn from(block: BlockView) -> Self {
+        Self {
+            block,
+            txs_fees: None,
+            txs_cycles: None,
+        }
+    }
+}
+
 impl<'a> From<&'a CoreBlock> for Block {
     fn from(core: &CoreBlock) -> Self {
         Self {
@@ -654,6 +713,16 @@ pub struct EpochExt {
     pub remainder_reward: String,
 }
 
+/// The capacity the cellbase of a block may claim, the primary reward of its
+/// epoch plus the fees of the other transactions of the block
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct CellbaseOutputCapacityDetails {
+    pub primary: Capacity,
+    /// Reward of the proposals of the block, always 0 in the current consensus
+    pub proposal_reward: Capacity,
+    pub tx_fee: Capacity,
+}
+
 impl From<CoreEpochExt> for EpochExt {
     fn from(core: CoreEpochExt) -> EpochExt {
         let (
@@ -762,6 +831,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_block_with_ext_view() {
+        let block = mock_full_block(Bytes::from(vec![1]), Bytes::from(vec![2]));
+        let view: BlockView = (&block).into();
+        let mut with_ext = BlockWithExtView::from(view.clone());
+        // Without the ext it is serialized the same as the block view
+        assert_eq!(
+            serde_json::to_value(&with_ext).unwrap(),
+            serde_json::to_value(&view).unwrap()
+        );
+
+        with_ext.txs_fees = Some(vec!["100".to_owned()]);
+        with_ext.txs_cycles = Some(vec!["1000".to_owned()]);
+        let encoded = serde_json::to_string(&with_ext).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BlockWithExtView>(&encoded).unwrap(),
+            with_ext
+        );
+    }
+
     proptest! {
         #[test]
         fn test_block_convert(
@@ -0,0 +1,29 @@
+use crate::{BlockNumber, CellOutput};
+use numext_fixed_hash::H256;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TransactionPoint {
+    pub block_number: BlockNumber,
+    pub tx_hash: H256,
+    pub index: u32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct LiveCell {
+    pub created_by: TransactionPoint,
+    pub cell_output: CellOutput,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct CellTransaction {
+    pub created_by: TransactionPoint,
+    pub consumed_by: Option<TransactionPoint>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct LockHashIndexState {
+    pub lock_hash: H256,
+    pub block_number: BlockNumber,
+    pub block_hash: H256,
+}
@@ -2,10 +2,15 @@ mod block_template;
 mod blockchain;
 mod bytes;
 mod cell;
+mod indexer;
+mod mining;
 mod net;
 mod pool;
 mod proposal_short_id;
+mod schema;
+mod stats;
 mod trace;
+mod verbosity;
 
 pub type BlockNumber = String;
 pub type Capacity = String;
@@ -13,18 +18,31 @@ pub type Cycle = String;
 pub type EpochNumber = String;
 
 pub use self::block_template::{
-    BlockTemplate, CellbaseTemplate, TransactionTemplate, UncleTemplate,
+    BlockTemplate, BlockTemplateDelta, CellbaseTemplate, TransactionTemplate, UncleTemplate,
 };
 pub use self::blockchain::{
-    Block, BlockView, CellInput, CellOutPoint, CellOutput, EpochExt, Header, HeaderView, OutPoint,
-    Script, Seal, Transaction, TransactionView, TransactionWithStatus, TxStatus, UncleBlock,
-    UncleBlockView, Witness,
+    Block, BlockView, BlockWithExtView, CellInput, CellOutPoint, CellOutput,
+    CellbaseOutputCapacityDetails, EpochExt, Header, HeaderView, OutPoint, Script, Seal, Status,
+    Transaction, TransactionView, TransactionWithStatus, TxStatus, UncleBlock, UncleBlockView,
+    Witness,
 };
 pub use self::bytes::JsonBytes;
 pub use self::cell::{CellOutputWithOutPoint, CellWithStatus};
-pub use self::net::{Node, NodeAddress};
-pub use self::pool::TxPoolInfo;
+pub use self::indexer::{CellTransaction, LiveCell, LockHashIndexState, TransactionPoint};
+pub use self::mining::MiningInfo;
+pub use self::net::{
+    BannedAddress, Node, NodeAddress, NodeProtocol, PeerSyncState, PeerTraffic, RemoteNode,
+};
+pub use self::pool::{
+    DryRunResult, FeeRate, RawTxPool, RejectedTransaction, TxPoolEntries, TxPoolEntry, TxPoolIds,
+    TxPoolInfo, TxRejection,
+};
 pub use self::proposal_short_id::ProposalShortId;
+pub use self::schema::{object_schema, JsonSchema};
+pub use self::stats::{CacheStats, ChainInfo, StoreCacheStats, SyncState};
 pub use self::trace::{Action, TxTrace};
+pub use self::verbosity::{
+    ResponseFormat, VERBOSITY_JSON, VERBOSITY_JSON_WITH_EXT, VERBOSITY_SERIALIZED,
+};
 pub use ckb_core::Version;
 pub use jsonrpc_core::types::{error, id, params, request, response, version};
@@ -0,0 +1,16 @@
+use numext_fixed_uint::U256;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct MiningInfo {
+    pub blocks_solved: String,
+    pub blocks_submitted: String,
+    pub blocks_accepted: String,
+    // submitted blocks which no longer extended the tip, or seals found for replaced works
+    pub blocks_stale: String,
+    pub templates_generated: String,
+    // milliseconds spent assembling a template, on average
+    pub average_template_latency: String,
+    // difficulty per second of the recent blocks
+    pub network_hashrate: U256,
+}
@@ -1,3 +1,4 @@
+use numext_fixed_hash::H256;
 use serde_derive::{Deserialize, Serialize};
 
 // TODO add more fields from PeerIdentifyInfo
@@ -7,6 +8,9 @@ pub struct Node {
     pub node_id: String,
     pub addresses: Vec<NodeAddress>,
     pub is_outbound: Option<bool>,
+    pub listen_addresses: Vec<String>,
+    /// Addresses of the node observed by its outbound peers
+    pub observed_addresses: Vec<String>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -14,3 +18,54 @@ pub struct NodeAddress {
     pub address: String,
     pub score: u8,
 }
+
+/// A connected peer
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct RemoteNode {
+    pub version: String,
+    pub node_id: String,
+    pub addresses: Vec<NodeAddress>,
+    pub is_outbound: bool,
+    pub connected_address: String,
+    pub protocols: Vec<NodeProtocol>,
+    // milliseconds
+    pub connected_duration: String,
+    // milliseconds
+    pub last_ping_duration: Option<String>,
+    pub sync_state: PeerSyncState,
+    pub traffic: Option<PeerTraffic>,
+}
+
+/// A protocol opened with the peer and its negotiated version
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct NodeProtocol {
+    pub id: String,
+    pub version: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct PeerSyncState {
+    pub best_known_header_number: Option<String>,
+    pub best_known_header_hash: Option<H256>,
+    pub last_common_header_number: Option<String>,
+    pub last_common_header_hash: Option<H256>,
+    pub inflight_blocks: String,
+    pub sync_started: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct PeerTraffic {
+    pub bytes_sent: String,
+    pub bytes_received: String,
+}
+
+/// A banned IP address or subnet
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct BannedAddress {
+    pub address: String,
+    // unix time in milliseconds
+    pub ban_until: String,
+    pub ban_reason: String,
+    // unix time in milliseconds
+    pub created_at: String,
+}
@@ -1,3 +1,6 @@
+use crate::blockchain::TransactionView;
+use crate::{Capacity, Cycle};
+use numext_fixed_hash::H256;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -7,4 +10,74 @@ pub struct TxPoolInfo {
     pub orphan: u32,
     // timestamp(u64)
     pub last_txs_updated_at: String,
+    /// Serialized size of the pending and the staging transactions
+    pub total_tx_size: String,
+    /// Verification cycles of the pending and the staging transactions
+    pub total_tx_cycles: String,
+    /// Transactions paying a lower fee rate, in shannons per KB, are rejected
+    pub min_fee_rate: String,
+}
+
+/// The transactions of the pool, the hashes or the entries with `verbose`
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(untagged)]
+pub enum RawTxPool {
+    Ids(TxPoolIds),
+    Verbose(TxPoolEntries),
+}
+
+/// Hashes of the pending and the proposed transactions of the pool
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxPoolIds {
+    pub pending: Vec<H256>,
+    pub proposed: Vec<H256>,
+}
+
+/// The pending and the proposed transactions of the pool
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxPoolEntries {
+    pub pending: Vec<TxPoolEntry>,
+    pub proposed: Vec<TxPoolEntry>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxPoolEntry {
+    pub transaction: TransactionView,
+    /// Absent until the transaction is verified
+    pub cycles: Option<Cycle>,
+    /// Absent when the inputs are not found in the pool or the chain
+    pub fee: Option<Capacity>,
+    /// Serialized size
+    pub size: String,
+    /// Timestamp in milliseconds the transaction entered the pending or the
+    /// proposed queue
+    pub timestamp: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct DryRunResult {
+    pub cycles: Cycle,
+}
+
+/// Recommended fee rate, in shannons per KB of the serialized transaction
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct FeeRate {
+    pub fee_rate: String,
+}
+
+/// A transaction rejected by the pool, published to the `rejected_transaction`
+/// subscribers
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct RejectedTransaction {
+    pub transaction: TransactionView,
+    pub reason: String,
+}
+
+/// A transaction recently refused by or evicted from the pool
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxRejection {
+    pub hash: H256,
+    pub reason: String,
+    /// Timestamp in milliseconds the transaction is rejected
+    pub timestamp: String,
 }
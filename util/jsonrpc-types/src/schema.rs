@@ -0,0 +1,708 @@
+use crate::blockchain::Status;
+use crate::{
+    Action, BannedAddress, Block, BlockTemplate, BlockTemplateDelta, BlockView, BlockWithExtView,
+    CacheStats, CellInput, CellOutPoint, CellOutput, CellOutputWithOutPoint, CellTransaction,
+    CellWithStatus, CellbaseOutputCapacityDetails, CellbaseTemplate, ChainInfo, DryRunResult,
+    EpochExt, FeeRate, Header, HeaderView, JsonBytes, LiveCell, LockHashIndexState, MiningInfo,
+    Node, NodeAddress, NodeProtocol, OutPoint, PeerSyncState, PeerTraffic, ProposalShortId,
+    RawTxPool, RejectedTransaction, RemoteNode, ResponseFormat, Script, Seal, StoreCacheStats,
+    SyncState, Transaction, TransactionPoint, TransactionTemplate, TransactionView,
+    TransactionWithStatus, TxPoolEntries, TxPoolEntry, TxPoolIds, TxPoolInfo, TxRejection,
+    TxStatus, TxTrace, UncleBlock, UncleBlockView, UncleTemplate, Witness,
+};
+use numext_fixed_hash::H256;
+use numext_fixed_uint::U256;
+use serde_json::{json, Map, Value};
+
+/// Describes how a type is encoded in JSON, as a JSON Schema
+pub trait JsonSchema {
+    fn json_schema() -> Value;
+
+    /// Whether the field of this type may be absent or null
+    fn is_optional() -> bool {
+        false
+    }
+}
+
+/// Implements `JsonSchema` for a struct from its serialized field names and types.
+///
+/// A base type before the fields describes a `#[serde(flatten)]` inner struct.
+#[macro_export]
+macro_rules! impl_json_schema {
+    ($name:ident { $($field:literal : $ty:ty),* $(,)? }) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> serde_json::Value {
+                $crate::object_schema(
+                    stringify!($name),
+                    vec![$((
+                        $field,
+                        <$ty as $crate::JsonSchema>::json_schema(),
+                        <$ty as $crate::JsonSchema>::is_optional(),
+                    )),*],
+                )
+            }
+        }
+    };
+    ($name:ident : $base:ty { $($field:literal : $ty:ty),* $(,)? }) => {
+        impl $crate::JsonSchema for $name {
+            fn json_schema() -> serde_json::Value {
+                serde_json::json!({
+                    "title": stringify!($name),
+                    "allOf": [
+                        <$base as $crate::JsonSchema>::json_schema(),
+                        $crate::object_schema(
+                            stringify!($name),
+                            vec![$((
+                                $field,
+                                <$ty as $crate::JsonSchema>::json_schema(),
+                                <$ty as $crate::JsonSchema>::is_optional(),
+                            )),*],
+                        ),
+                    ],
+                })
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn object_schema(title: &str, fields: Vec<(&str, Value, bool)>) -> Value {
+    let required = fields
+        .iter()
+        .filter(|(_, _, optional)| !optional)
+        .map(|(name, _, _)| *name)
+        .collect::<Vec<_>>();
+    let properties = fields
+        .into_iter()
+        .map(|(name, schema, _)| (name.to_owned(), schema))
+        .collect::<Map<_, _>>();
+    json!({
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn hex_schema(pattern: &str) -> Value {
+    json!({
+        "type": "string",
+        "pattern": pattern,
+    })
+}
+
+impl JsonSchema for () {
+    fn json_schema() -> Value {
+        json!({ "type": "null" })
+    }
+}
+
+impl JsonSchema for bool {
+    fn json_schema() -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl JsonSchema for String {
+    fn json_schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+macro_rules! impl_json_schema_for_uint {
+    ($($ty:ty),*) => {
+        $(impl JsonSchema for $ty {
+            fn json_schema() -> Value {
+                json!({ "type": "integer", "minimum": 0 })
+            }
+        })*
+    };
+}
+
+impl_json_schema_for_uint!(u8, u32, u64);
+
+impl JsonSchema for H256 {
+    fn json_schema() -> Value {
+        hex_schema("^0x[0-9a-f]{64}$")
+    }
+}
+
+impl JsonSchema for U256 {
+    fn json_schema() -> Value {
+        hex_schema("^0x[0-9a-f]+$")
+    }
+}
+
+impl JsonSchema for JsonBytes {
+    fn json_schema() -> Value {
+        hex_schema("^0x([0-9a-f]{2})*$")
+    }
+}
+
+impl JsonSchema for ProposalShortId {
+    fn json_schema() -> Value {
+        hex_schema("^0x[0-9a-f]{20}$")
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [T::json_schema(), { "type": "null" }] })
+    }
+
+    fn is_optional() -> bool {
+        true
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Value {
+        json!({
+            "type": "array",
+            "items": T::json_schema(),
+        })
+    }
+}
+
+impl<V: JsonSchema> JsonSchema for ResponseFormat<V> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [V::json_schema(), JsonBytes::json_schema()] })
+    }
+}
+
+impl JsonSchema for Status {
+    fn json_schema() -> Value {
+        json!({
+            "type": "string",
+            "enum": [
+                "pending_verification",
+                "pending",
+                "proposed",
+                "committed",
+                "rejected",
+            ],
+        })
+    }
+}
+
+impl JsonSchema for Action {
+    fn json_schema() -> Value {
+        json!({
+            "type": "string",
+            "enum": [
+                "AddPending",
+                "Proposed",
+                "Staged",
+                "Expired",
+                "AddOrphan",
+                "Committed",
+                "Removed",
+            ],
+        })
+    }
+}
+
+impl JsonSchema for RawTxPool {
+    fn json_schema() -> Value {
+        json!({
+            "title": "RawTxPool",
+            "anyOf": [TxPoolIds::json_schema(), TxPoolEntries::json_schema()],
+        })
+    }
+}
+
+impl_json_schema!(Script {
+    "args": Vec<JsonBytes>,
+    "code_hash": H256,
+});
+
+impl_json_schema!(CellOutput {
+    "capacity": String,
+    "data": JsonBytes,
+    "lock": Script,
+    "type": Option<Script>,
+});
+
+impl_json_schema!(CellOutPoint {
+    "tx_hash": H256,
+    "index": u32,
+});
+
+impl_json_schema!(OutPoint {
+    "cell": Option<CellOutPoint>,
+    "block_hash": Option<H256>,
+});
+
+impl_json_schema!(CellInput {
+    "previous_output": OutPoint,
+    "since": String,
+    "args": Vec<JsonBytes>,
+});
+
+impl_json_schema!(Witness {
+    "data": Vec<JsonBytes>,
+});
+
+impl_json_schema!(Transaction {
+    "version": u32,
+    "deps": Vec<OutPoint>,
+    "inputs": Vec<CellInput>,
+    "outputs": Vec<CellOutput>,
+    "witnesses": Vec<Witness>,
+});
+
+impl_json_schema!(TransactionView: Transaction {
+    "hash": H256,
+});
+
+impl_json_schema!(TransactionWithStatus {
+    "transaction": Option<ResponseFormat<TransactionView>>,
+    "tx_status": TxStatus,
+});
+
+impl_json_schema!(TxStatus {
+    "status": Status,
+    "block_hash": Option<H256>,
+    "reason": Option<String>,
+});
+
+impl_json_schema!(Seal {
+    "nonce": String,
+    "proof": JsonBytes,
+});
+
+impl_json_schema!(Header {
+    "version": u32,
+    "parent_hash": H256,
+    "timestamp": String,
+    "number": String,
+    "epoch": String,
+    "transactions_root": H256,
+    "proposals_root": H256,
+    "witnesses_root": H256,
+    "difficulty": U256,
+    "uncles_hash": H256,
+    "uncles_count": u32,
+    "seal": Seal,
+});
+
+impl_json_schema!(HeaderView: Header {
+    "hash": H256,
+});
+
+impl_json_schema!(UncleBlock {
+    "header": Header,
+    "proposals": Vec<ProposalShortId>,
+});
+
+impl_json_schema!(UncleBlockView {
+    "header": HeaderView,
+    "proposals": Vec<ProposalShortId>,
+});
+
+impl_json_schema!(Block {
+    "header": Header,
+    "uncles": Vec<UncleBlock>,
+    "transactions": Vec<Transaction>,
+    "proposals": Vec<ProposalShortId>,
+});
+
+impl_json_schema!(BlockView {
+    "header": HeaderView,
+    "uncles": Vec<UncleBlockView>,
+    "transactions": Vec<TransactionView>,
+    "proposals": Vec<ProposalShortId>,
+});
+
+impl_json_schema!(BlockWithExtView: BlockView {
+    "txs_fees": Option<Vec<String>>,
+    "txs_cycles": Option<Vec<String>>,
+});
+
+impl_json_schema!(EpochExt {
+    "number": String,
+    "block_reward": String,
+    "last_block_hash_in_previous_epoch": H256,
+    "start_number": String,
+    "length": String,
+    "difficulty": U256,
+    "remainder_reward": String,
+});
+
+impl_json_schema!(CellbaseOutputCapacityDetails {
+    "primary": String,
+    "proposal_reward": String,
+    "tx_fee": String,
+});
+
+impl_json_schema!(BlockTemplate {
+    "version": u32,
+    "difficulty": U256,
+    "current_time": String,
+    "number": String,
+    "epoch": String,
+    "parent_hash": H256,
+    "cycles_limit": String,
+    "bytes_limit": String,
+    "uncles_count_limit": u32,
+    "uncles": Vec<UncleTemplate>,
+    "transactions": Vec<TransactionTemplate>,
+    "proposals": Vec<ProposalShortId>,
+    "cellbase": CellbaseTemplate,
+    "work_id": String,
+});
+
+impl_json_schema!(UncleTemplate {
+    "hash": H256,
+    "required": bool,
+    "proposals": Vec<ProposalShortId>,
+    "header": Header,
+});
+
+impl_json_schema!(CellbaseTemplate {
+    "hash": H256,
+    "cycles": Option<String>,
+    "data": Transaction,
+});
+
+impl_json_schema!(TransactionTemplate {
+    "hash": H256,
+    "required": bool,
+    "cycles": Option<String>,
+    "depends": Option<Vec<u32>>,
+    "data": Transaction,
+});
+
+impl_json_schema!(BlockTemplateDelta {
+    "base_work_id": String,
+    "work_id": String,
+    "version": Option<u32>,
+    "difficulty": Option<U256>,
+    "current_time": Option<String>,
+    "number": Option<String>,
+    "epoch": Option<String>,
+    "parent_hash": Option<H256>,
+    "cycles_limit": Option<String>,
+    "bytes_limit": Option<String>,
+    "uncles_count_limit": Option<u32>,
+    "uncles": Option<Vec<UncleTemplate>>,
+    "proposals": Option<Vec<ProposalShortId>>,
+    "cellbase": Option<CellbaseTemplate>,
+    "added_transactions": Vec<TransactionTemplate>,
+    "removed_transactions": Vec<H256>,
+});
+
+impl_json_schema!(CellOutputWithOutPoint {
+    "out_point": OutPoint,
+    "capacity": String,
+    "lock": Script,
+});
+
+impl_json_schema!(CellWithStatus {
+    "cell": Option<CellOutput>,
+    "status": String,
+});
+
+impl_json_schema!(TransactionPoint {
+    "block_number": String,
+    "tx_hash": H256,
+    "index": u32,
+});
+
+impl_json_schema!(LiveCell {
+    "created_by": TransactionPoint,
+    "cell_output": CellOutput,
+});
+
+impl_json_schema!(CellTransaction {
+    "created_by": TransactionPoint,
+    "consumed_by": Option<TransactionPoint>,
+});
+
+impl_json_schema!(LockHashIndexState {
+    "lock_hash": H256,
+    "block_number": String,
+    "block_hash": H256,
+});
+
+impl_json_schema!(MiningInfo {
+    "blocks_solved": String,
+    "blocks_submitted": String,
+    "blocks_accepted": String,
+    "blocks_stale": String,
+    "templates_generated": String,
+    "average_template_latency": String,
+    "network_hashrate": U256,
+});
+
+impl_json_schema!(Node {
+    "version": String,
+    "node_id": String,
+    "addresses": Vec<NodeAddress>,
+    "is_outbound": Option<bool>,
+    "listen_addresses": Vec<String>,
+    "observed_addresses": Vec<String>,
+});
+
+impl_json_schema!(NodeAddress {
+    "address": String,
+    "score": u8,
+});
+
+impl_json_schema!(RemoteNode {
+    "version": String,
+    "node_id": String,
+    "addresses": Vec<NodeAddress>,
+    "is_outbound": bool,
+    "connected_address": String,
+    "protocols": Vec<NodeProtocol>,
+    "connected_duration": String,
+    "last_ping_duration": Option<String>,
+    "sync_state": PeerSyncState,
+    "traffic": Option<PeerTraffic>,
+});
+
+impl_json_schema!(NodeProtocol {
+    "id": String,
+    "version": String,
+});
+
+impl_json_schema!(PeerSyncState {
+    "best_known_header_number": Option<String>,
+    "best_known_header_hash": Option<H256>,
+    "last_common_header_number": Option<String>,
+    "last_common_header_hash": Option<H256>,
+    "inflight_blocks": String,
+    "sync_started": bool,
+});
+
+impl_json_schema!(PeerTraffic {
+    "bytes_sent": String,
+    "bytes_received": String,
+});
+
+impl_json_schema!(BannedAddress {
+    "address": String,
+    "ban_until": String,
+    "ban_reason": String,
+    "created_at": String,
+});
+
+impl_json_schema!(TxPoolInfo {
+    "pending": u32,
+    "staging": u32,
+    "orphan": u32,
+    "last_txs_updated_at": String,
+    "total_tx_size": String,
+    "total_tx_cycles": String,
+    "min_fee_rate": String,
+});
+
+impl_json_schema!(TxPoolIds {
+    "pending": Vec<H256>,
+    "proposed": Vec<H256>,
+});
+
+impl_json_schema!(TxPoolEntries {
+    "pending": Vec<TxPoolEntry>,
+    "proposed": Vec<TxPoolEntry>,
+});
+
+impl_json_schema!(TxPoolEntry {
+    "transaction": TransactionView,
+    "cycles": Option<String>,
+    "fee": Option<String>,
+    "size": String,
+    "timestamp": String,
+});
+
+impl_json_schema!(DryRunResult {
+    "cycles": String,
+});
+
+impl_json_schema!(FeeRate {
+    "fee_rate": String,
+});
+
+impl_json_schema!(TxRejection {
+    "hash": H256,
+    "reason": String,
+    "timestamp": String,
+});
+
+impl_json_schema!(RejectedTransaction {
+    "transaction": TransactionView,
+    "reason": String,
+});
+
+impl_json_schema!(ChainInfo {
+    "chain": String,
+    "tip_number": String,
+    "epoch": String,
+    "difficulty": U256,
+    "is_initial_block_download": bool,
+});
+
+impl_json_schema!(SyncState {
+    "best_known_header_number": String,
+    "best_known_header_hash": H256,
+    "tip_number": String,
+    "tip_hash": H256,
+    "inflight_blocks": String,
+    "orphan_blocks": String,
+    "estimated_remaining_time": Option<String>,
+    "updated_at": String,
+});
+
+impl_json_schema!(CacheStats {
+    "capacity": String,
+    "size": String,
+    "hits": String,
+    "misses": String,
+});
+
+impl_json_schema!(StoreCacheStats {
+    "header": Option<CacheStats>,
+    "block": Option<CacheStats>,
+    "cell_meta": Option<CacheStats>,
+});
+
+impl_json_schema!(TxTrace {
+    "action": Action,
+    "info": String,
+    "time": u64,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_object_schema() {
+        let schema = OutPoint::json_schema();
+        assert_eq!(schema["title"], "OutPoint");
+        assert_eq!(schema["required"], json!([]));
+        assert_eq!(
+            schema["properties"]["cell"]["anyOf"][0],
+            CellOutPoint::json_schema()
+        );
+
+        let schema = CellOutPoint::json_schema();
+        assert_eq!(schema["required"], json!(["tx_hash", "index"]));
+        assert_eq!(schema["properties"]["index"]["type"], "integer");
+    }
+
+    // The properties of an object schema, with the optional ones, following `allOf`
+    fn schema_properties(schema: &Value) -> (BTreeSet<String>, BTreeSet<String>) {
+        if let Some(parts) = schema["allOf"].as_array() {
+            return parts.iter().map(schema_properties).fold(
+                (BTreeSet::new(), BTreeSet::new()),
+                |(mut all, mut optional), (a, o)| {
+                    all.extend(a);
+                    optional.extend(o);
+                    (all, optional)
+                },
+            );
+        }
+        let all = schema["properties"]
+            .as_object()
+            .expect("object schema")
+            .keys()
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let required = schema["required"]
+            .as_array()
+            .expect("required fields")
+            .iter()
+            .map(|name| name.as_str().unwrap().to_owned())
+            .collect::<BTreeSet<_>>();
+        let optional = all.difference(&required).cloned().collect();
+        (all, optional)
+    }
+
+    fn assert_schema_matches<T: JsonSchema + Default + Serialize>() {
+        let schema = T::json_schema();
+        let (properties, optional) = schema_properties(&schema);
+        let keys = serde_json::to_value(T::default())
+            .unwrap()
+            .as_object()
+            .expect("serialized as an object")
+            .keys()
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        assert!(
+            keys.is_subset(&properties),
+            "{} fields missing from the schema: {:?}",
+            schema["title"],
+            keys.difference(&properties).collect::<Vec<_>>()
+        );
+        assert!(
+            properties
+                .difference(&keys)
+                .all(|name| optional.contains(name)),
+            "{} schema properties never serialized: {:?}",
+            schema["title"],
+            properties.difference(&keys).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_schema_matches_serialized_fields() {
+        assert_schema_matches::<Script>();
+        assert_schema_matches::<CellOutput>();
+        assert_schema_matches::<CellOutPoint>();
+        assert_schema_matches::<OutPoint>();
+        assert_schema_matches::<CellInput>();
+        assert_schema_matches::<Witness>();
+        assert_schema_matches::<Transaction>();
+        assert_schema_matches::<TransactionView>();
+        assert_schema_matches::<Seal>();
+        assert_schema_matches::<Header>();
+        assert_schema_matches::<HeaderView>();
+        assert_schema_matches::<UncleBlock>();
+        assert_schema_matches::<UncleBlockView>();
+        assert_schema_matches::<Block>();
+        assert_schema_matches::<BlockView>();
+        assert_schema_matches::<BlockWithExtView>();
+        assert_schema_matches::<EpochExt>();
+        assert_schema_matches::<CellbaseOutputCapacityDetails>();
+        assert_schema_matches::<BlockTemplate>();
+        assert_schema_matches::<UncleTemplate>();
+        assert_schema_matches::<CellbaseTemplate>();
+        assert_schema_matches::<TransactionTemplate>();
+        assert_schema_matches::<BlockTemplateDelta>();
+        assert_schema_matches::<TransactionPoint>();
+        assert_schema_matches::<LiveCell>();
+        assert_schema_matches::<CellTransaction>();
+        assert_schema_matches::<LockHashIndexState>();
+        assert_schema_matches::<MiningInfo>();
+        assert_schema_matches::<Node>();
+        assert_schema_matches::<NodeAddress>();
+        assert_schema_matches::<RemoteNode>();
+        assert_schema_matches::<NodeProtocol>();
+        assert_schema_matches::<PeerSyncState>();
+        assert_schema_matches::<PeerTraffic>();
+        assert_schema_matches::<BannedAddress>();
+        assert_schema_matches::<TxPoolInfo>();
+        assert_schema_matches::<TxPoolIds>();
+        assert_schema_matches::<TxPoolEntries>();
+        assert_schema_matches::<TxPoolEntry>();
+        assert_schema_matches::<DryRunResult>();
+        assert_schema_matches::<FeeRate>();
+        assert_schema_matches::<TxRejection>();
+        assert_schema_matches::<RejectedTransaction>();
+        assert_schema_matches::<ChainInfo>();
+        assert_schema_matches::<SyncState>();
+        assert_schema_matches::<CacheStats>();
+        assert_schema_matches::<StoreCacheStats>();
+    }
+
+    #[test]
+    fn test_flatten_schema() {
+        let schema = HeaderView::json_schema();
+        assert_eq!(schema["allOf"][0], Header::json_schema());
+        assert_eq!(schema["allOf"][1]["required"], json!(["hash"]));
+    }
+}
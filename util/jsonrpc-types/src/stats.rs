@@ -0,0 +1,47 @@
+use crate::{BlockNumber, EpochNumber};
+use numext_fixed_hash::H256;
+use numext_fixed_uint::U256;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct ChainInfo {
+    // name of the chain spec
+    pub chain: String,
+    pub tip_number: BlockNumber,
+    pub epoch: EpochNumber,
+    pub difficulty: U256,
+    // transactions are not relayed while the node is in initial block download
+    pub is_initial_block_download: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct SyncState {
+    pub best_known_header_number: BlockNumber,
+    pub best_known_header_hash: H256,
+    pub tip_number: BlockNumber,
+    pub tip_hash: H256,
+    // blocks requested from peers and not received yet
+    pub inflight_blocks: String,
+    // received blocks whose parents are unknown yet
+    pub orphan_blocks: String,
+    // milliseconds to reach the best known header, null while unknown
+    pub estimated_remaining_time: Option<String>,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct CacheStats {
+    // maximum number of entries
+    pub capacity: String,
+    pub size: String,
+    pub hits: String,
+    pub misses: String,
+}
+
+// null for a disabled cache
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct StoreCacheStats {
+    pub header: Option<CacheStats>,
+    pub block: Option<CacheStats>,
+    pub cell_meta: Option<CacheStats>,
+}
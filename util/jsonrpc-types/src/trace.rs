@@ -9,6 +9,7 @@ pub enum Action {
     Expired,
     AddOrphan,
     Committed,
+    Removed,
 }
 
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Hash)]
@@ -0,0 +1,49 @@
+use crate::JsonBytes;
+use serde_derive::{Deserialize, Serialize};
+
+/// The result of a getter taking a verbosity: the serialized bytes for
+/// `VERBOSITY_SERIALIZED`, or the JSON view
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(untagged)]
+pub enum ResponseFormat<V> {
+    Json(V),
+    Serialized(JsonBytes),
+}
+
+/// Serialized bytes in hex
+pub const VERBOSITY_SERIALIZED: u32 = 0;
+/// The JSON view, the default verbosity
+pub const VERBOSITY_JSON: u32 = 1;
+/// The JSON view with the extra details recorded by the node, such as the
+/// fees and cycles of the transactions of a block
+pub const VERBOSITY_JSON_WITH_EXT: u32 = 2;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NodeAddress;
+
+    #[test]
+    fn test_response_format() {
+        let serialized = ResponseFormat::<NodeAddress>::Serialized(JsonBytes::from_vec(vec![1, 2]));
+        assert_eq!(serde_json::to_string(&serialized).unwrap(), r#""0x0102""#);
+        assert_eq!(
+            serde_json::from_str::<ResponseFormat<NodeAddress>>(r#""0x0102""#).unwrap(),
+            serialized
+        );
+
+        let json = ResponseFormat::Json(NodeAddress {
+            address: "/ip4/127.0.0.1/tcp/8115".to_owned(),
+            score: 1,
+        });
+        let json_str = serde_json::to_string(&json).unwrap();
+        assert_eq!(
+            json_str,
+            r#"{"address":"/ip4/127.0.0.1/tcp/8115","score":1}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ResponseFormat<NodeAddress>>(&json_str).unwrap(),
+            json
+        );
+    }
+}
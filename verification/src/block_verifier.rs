@@ -1,25 +1,30 @@
 use crate::error::{CellbaseError, CommitError, Error, UnclesError};
 use crate::header_verifier::HeaderResolver;
-use crate::{TransactionVerifier, Verifier};
+use crate::pipeline::VerifierPipeline;
+use crate::{ContextualVerifier, TransactionVerifier, Verifier};
+use ckb_chain_spec::consensus::Consensus;
 use ckb_core::cell::ResolvedTransaction;
 use ckb_core::extras::EpochExt;
 use ckb_core::header::Header;
-use ckb_core::transaction::{Capacity, CellInput, Transaction};
+use ckb_core::transaction::{Capacity, CellInput, ProposalShortId, Transaction};
 use ckb_core::Cycle;
 use ckb_core::{block::Block, BlockNumber};
-use ckb_script::ScriptConfig;
+use ckb_script::{ScriptConfig, ScriptDataCache};
 use ckb_store::ChainStore;
 use ckb_traits::{BlockMedianTimeContext, ChainProvider};
 use fnv::FnvHashSet;
 use log::error;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::ThreadPool;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 //TODO: cellbase, witness
 #[derive(Clone)]
 pub struct BlockVerifier<P> {
     provider: P,
+    skipped: Vec<&'static str>,
 }
 
 fn prepare_epoch_ext<P: ChainProvider>(provider: &P, block: &Block) -> Result<EpochExt, Error> {
@@ -43,7 +48,32 @@ where
     P: ChainProvider + Clone,
 {
     pub fn new(provider: P) -> Self {
-        BlockVerifier { provider }
+        BlockVerifier {
+            provider,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Skips the pipeline stage named `stage`, see `pipeline`
+    pub fn skip(mut self, stage: &'static str) -> Self {
+        self.skipped.push(stage);
+        self
+    }
+
+    /// Builds the stages verifying a block of epoch `epoch_ext`, the ones of
+    /// `NonContextualBlockVerifier::pipeline` followed by `commit` and `uncles`.
+    pub fn pipeline<'a>(&'a self, epoch_ext: &'a EpochExt) -> VerifierPipeline<'a, Block> {
+        let pipeline = NonContextualBlockVerifier::new(self.provider.consensus())
+            .pipeline()
+            .stage("commit", move |block| {
+                CommitVerifier::new(self.provider.clone()).verify(block)
+            })
+            .stage("uncles", move |block| {
+                UnclesVerifier::new(self.provider.clone(), epoch_ext).verify(block)
+            });
+        self.skipped
+            .iter()
+            .fold(pipeline, |pipeline, stage| pipeline.skip(stage))
     }
 }
 
@@ -54,27 +84,87 @@ where
     type Target = Block;
 
     fn verify(&self, target: &Block) -> Result<(), Error> {
-        let consensus = self.provider.consensus();
-        let proof_size = consensus.pow_engine().proof_size();
-        let max_block_proposals_limit = consensus.max_block_proposals_limit();
-        let max_block_bytes = consensus.max_block_bytes();
         let epoch_ext = prepare_epoch_ext(&self.provider, target)?;
-        BlockProposalsLimitVerifier::new(max_block_proposals_limit).verify(target)?;
-        BlockBytesVerifier::new(max_block_bytes, proof_size).verify(target)?;
-        CellbaseVerifier::new().verify(target)?;
-        DuplicateVerifier::new().verify(target)?;
-        MerkleRootVerifier::new().verify(target)?;
-        CommitVerifier::new(self.provider.clone()).verify(target)?;
-        UnclesVerifier::new(self.provider.clone(), &epoch_ext).verify(target)
+        self.pipeline(&epoch_ext).verify(target)
     }
 }
 
+/// Block checks needing only the block and consensus parameters, cheap enough
+/// to run as soon as a block is received.
 #[derive(Clone)]
-pub struct CellbaseVerifier {}
+pub struct NonContextualBlockVerifier<'a> {
+    consensus: &'a Consensus,
+}
 
-impl CellbaseVerifier {
+impl<'a> NonContextualBlockVerifier<'a> {
+    pub fn new(consensus: &'a Consensus) -> Self {
+        NonContextualBlockVerifier { consensus }
+    }
+
+    /// Builds the stages named `proposals_limit`, `block_bytes`, `cellbase`,
+    /// `duplicate` and `merkle_root` in this order.
+    pub fn pipeline(&self) -> VerifierPipeline<'a, Block> {
+        let proof_size = self.consensus.pow_engine().proof_size();
+        let max_block_proposals_limit = self.consensus.max_block_proposals_limit();
+        let max_block_bytes = self.consensus.max_block_bytes();
+        let max_cellbase_message_bytes = self.consensus.max_cellbase_message_bytes();
+        VerifierPipeline::new()
+            .stage("proposals_limit", move |block| {
+                BlockProposalsLimitVerifier::new(max_block_proposals_limit).verify(block)
+            })
+            .stage("block_bytes", move |block| {
+                BlockBytesVerifier::new(max_block_bytes, proof_size).verify(block)
+            })
+            .stage("cellbase", move |block| {
+                CellbaseVerifier::new(max_cellbase_message_bytes).verify(block)
+            })
+            .stage("duplicate", |block| DuplicateVerifier::new().verify(block))
+            .stage("merkle_root", |block| {
+                MerkleRootVerifier::new().verify(block)
+            })
+    }
+}
+
+impl<'a> Verifier for NonContextualBlockVerifier<'a> {
+    type Target = Block;
+
+    fn verify(&self, target: &Block) -> Result<(), Error> {
+        self.pipeline().verify(target)
+    }
+}
+
+/// Block checks against the chain the block extends, the commit window and
+/// the uncles.
+#[derive(Clone, Default)]
+pub struct ContextualBlockVerifier {}
+
+impl ContextualBlockVerifier {
     pub fn new() -> Self {
-        CellbaseVerifier {}
+        ContextualBlockVerifier {}
+    }
+}
+
+impl<P> ContextualVerifier<P> for ContextualBlockVerifier
+where
+    P: ChainProvider + Clone,
+{
+    type Target = Block;
+
+    fn verify(&self, provider: &P, target: &Block) -> Result<(), Error> {
+        let epoch_ext = prepare_epoch_ext(provider, target)?;
+        CommitVerifier::new(provider.clone()).verify(target)?;
+        UnclesVerifier::new(provider.clone(), &epoch_ext).verify(target)
+    }
+}
+
+#[derive(Clone)]
+pub struct CellbaseVerifier {
+    max_message_bytes: u64,
+}
+
+impl CellbaseVerifier {
+    pub fn new(max_message_bytes: u64) -> Self {
+        CellbaseVerifier { max_message_bytes }
     }
 
     pub fn verify(&self, block: &Block) -> Result<(), Error> {
@@ -99,6 +189,20 @@ impl CellbaseVerifier {
             return Err(Error::Cellbase(CellbaseError::InvalidInput));
         }
 
+        // The message is the only witness of the cellbase
+        let witnesses = cellbase_transaction.witnesses();
+        let message_bytes = witnesses
+            .iter()
+            .flat_map(|witness| witness.iter())
+            .map(|item| item.len() as u64)
+            .sum::<u64>();
+        if witnesses.len() > 1 || message_bytes > self.max_message_bytes {
+            return Err(Error::Cellbase(CellbaseError::InvalidMessage {
+                max: self.max_message_bytes,
+                actual: message_bytes,
+            }));
+        }
+
         Ok(())
     }
 }
@@ -260,11 +364,12 @@ where
 
         // verify uncles age
         let max_uncles_age = self.provider.consensus().max_uncles_age() as u64;
-        for uncle in block.uncles() {
+        for (index, uncle) in block.uncles().iter().enumerate() {
             let depth = block.header().number().saturating_sub(uncle.number());
 
             if depth > max_uncles_age || depth < 1 {
                 return Err(Error::Uncles(UnclesError::InvalidDepth {
+                    index,
                     min: block.header().number().saturating_sub(max_uncles_age),
                     max: block.header().number().saturating_sub(1),
                     actual: uncle.number(),
@@ -303,35 +408,52 @@ where
             }
         }
 
-        for uncle in block.uncles() {
+        for (index, uncle) in block.uncles().iter().enumerate() {
             if uncle.header().difficulty() != self.epoch.difficulty() {
-                return Err(Error::Uncles(UnclesError::InvalidDifficulty));
+                return Err(Error::Uncles(UnclesError::InvalidDifficulty {
+                    index,
+                    expected: self.epoch.difficulty().to_owned(),
+                    actual: uncle.header().difficulty().to_owned(),
+                }));
             }
 
             if self.epoch.number() != uncle.header().epoch() {
-                return Err(Error::Uncles(UnclesError::InvalidDifficultyEpoch));
+                return Err(Error::Uncles(UnclesError::InvalidDifficultyEpoch {
+                    index,
+                    expected: self.epoch.number(),
+                    actual: uncle.header().epoch(),
+                }));
             }
 
             let uncle_header = uncle.header.clone();
 
             let uncle_hash = uncle_header.hash().to_owned();
             if included.contains(&uncle_hash) {
-                return Err(Error::Uncles(UnclesError::Duplicate(uncle_hash.clone())));
+                return Err(Error::Uncles(UnclesError::Duplicate {
+                    index,
+                    hash: uncle_hash,
+                }));
             }
 
             if excluded.contains(&uncle_hash) {
-                return Err(Error::Uncles(UnclesError::InvalidInclude(
-                    uncle_hash.clone(),
-                )));
+                return Err(Error::Uncles(UnclesError::InvalidInclude {
+                    index,
+                    hash: uncle_hash,
+                }));
             }
 
-            if uncle_header.proposals_root() != &uncle.cal_proposals_root() {
-                return Err(Error::Uncles(UnclesError::ProposalsRoot));
+            let proposals_root = uncle.cal_proposals_root();
+            if uncle_header.proposals_root() != &proposals_root {
+                return Err(Error::Uncles(UnclesError::ProposalsRoot {
+                    index,
+                    expected: uncle_header.proposals_root().to_owned(),
+                    actual: proposals_root,
+                }));
             }
 
             let mut seen = HashSet::with_capacity(uncle.proposals().len());
             if !uncle.proposals().iter().all(|id| seen.insert(id)) {
-                return Err(Error::Uncles(UnclesError::ProposalDuplicate));
+                return Err(Error::Uncles(UnclesError::ProposalDuplicate { index }));
             }
 
             if !self
@@ -340,7 +462,7 @@ where
                 .pow_engine()
                 .verify_header(&uncle_header)
             {
-                return Err(Error::Uncles(UnclesError::InvalidProof));
+                return Err(Error::Uncles(UnclesError::InvalidProof { index }));
             }
 
             included.insert(uncle_hash);
@@ -350,20 +472,74 @@ where
     }
 }
 
+/// Checks the cellbase does not claim more than the epoch block reward plus
+/// the fees of all the other transactions in the block.
+pub struct RewardVerifier<'a> {
+    resolved: &'a [ResolvedTransaction<'a>],
+    block_reward: Capacity,
+}
+
+impl<'a> RewardVerifier<'a> {
+    pub fn new(resolved: &'a [ResolvedTransaction], block_reward: Capacity) -> Self {
+        RewardVerifier {
+            resolved,
+            block_reward,
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let cellbase = match self.resolved.first() {
+            Some(cellbase) => cellbase,
+            None => return Err(Error::Cellbase(CellbaseError::InvalidQuantity)),
+        };
+        let fee: Capacity = self
+            .resolved
+            .iter()
+            .skip(1)
+            .map(ResolvedTransaction::fee)
+            .try_fold(Capacity::zero(), |acc, rhs| {
+                rhs.and_then(|x| acc.safe_add(x))
+            })?;
+        if cellbase.transaction.outputs_capacity()? > self.block_reward.safe_add(fee)? {
+            return Err(Error::Cellbase(CellbaseError::InvalidReward));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct TransactionsVerifier<'a> {
     max_cycles: Cycle,
+    max_tx_bytes: u64,
     script_config: &'a ScriptConfig,
+    thread_pool: Option<&'a ThreadPool>,
+    skip_script: bool,
 }
 
 impl<'a> TransactionsVerifier<'a> {
-    pub fn new(max_cycles: Cycle, script_config: &'a ScriptConfig) -> Self {
+    pub fn new(max_cycles: Cycle, max_tx_bytes: u64, script_config: &'a ScriptConfig) -> Self {
         TransactionsVerifier {
             max_cycles,
+            max_tx_bytes,
             script_config,
+            thread_pool: None,
+            skip_script: false,
         }
     }
 
+    /// Skips running the scripts of the transactions, for blocks assumed valid.
+    pub fn skip_script(mut self) -> Self {
+        self.skip_script = true;
+        self
+    }
+
+    /// Verifies transactions on `thread_pool` instead of the global rayon pool.
+    pub fn with_thread_pool(mut self, thread_pool: &'a ThreadPool) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// Returns the cycles of each transaction, zero for the skipped scripts.
     pub fn verify<M, CS: ChainStore>(
         &self,
         resolved: &[ResolvedTransaction],
@@ -372,48 +548,83 @@ impl<'a> TransactionsVerifier<'a> {
         block_median_time_context: M,
         tip_number: BlockNumber,
         cellbase_maturity: BlockNumber,
-    ) -> Result<(), Error>
+    ) -> Result<Vec<Cycle>, Error>
     where
         M: BlockMedianTimeContext + Sync,
     {
-        // verify cellbase reward
-        let cellbase = &resolved[0];
-        let fee: Capacity = resolved
-            .iter()
-            .skip(1)
-            .map(ResolvedTransaction::fee)
-            .try_fold(Capacity::zero(), |acc, rhs| {
-                rhs.and_then(|x| acc.safe_add(x))
-            })?;
-        if cellbase.transaction.outputs_capacity()? > block_reward.safe_add(fee)? {
-            return Err(Error::Cellbase(CellbaseError::InvalidReward));
+        RewardVerifier::new(resolved, block_reward).verify()?;
+
+        // make verifiers orthogonal, dep cell data is shared across the block
+        let data_cache = ScriptDataCache::new();
+        // index of the first invalid transaction seen so far, the transactions
+        // after it are skipped since their result can't be reported anyway
+        let first_failed = AtomicUsize::new(usize::max_value());
+        let verify_all = || {
+            resolved
+                .par_iter()
+                .enumerate()
+                .map(|(index, tx)| {
+                    if index > first_failed.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    let verifier = TransactionVerifier::new(
+                        &tx,
+                        Arc::clone(&store),
+                        &block_median_time_context,
+                        tip_number,
+                        cellbase_maturity,
+                        self.max_tx_bytes,
+                        &self.script_config,
+                    )
+                    .with_data_cache(&data_cache);
+                    let result = if self.skip_script {
+                        verifier.skip_script().verify(self.max_cycles)
+                    } else {
+                        verifier.verify(self.max_cycles)
+                    };
+                    if result.is_err() {
+                        let mut current = first_failed.load(Ordering::Relaxed);
+                        while index < current {
+                            match first_failed.compare_exchange_weak(
+                                current,
+                                index,
+                                Ordering::Relaxed,
+                                Ordering::Relaxed,
+                            ) {
+                                Ok(_) => break,
+                                Err(actual) => current = actual,
+                            }
+                        }
+                    }
+                    Some(result)
+                })
+                .collect::<Vec<_>>()
+        };
+        let results = match self.thread_pool {
+            Some(thread_pool) => thread_pool.install(verify_all),
+            None => verify_all(),
+        };
+
+        // Results keep the transactions order, so the reported error is always
+        // the one of the first invalid transaction regardless of scheduling.
+        // Only transactions after a failed one are skipped, so a skipped
+        // result is never reached before that error.
+        let mut sum: Cycle = 0;
+        let mut txs_cycles = Vec::with_capacity(results.len());
+        for (index, result) in results.into_iter().enumerate() {
+            let cycles = result
+                .expect("skipped only after an invalid transaction")
+                .map_err(|e| Error::Transactions((index, e)))?;
+            sum = sum
+                .checked_add(cycles)
+                .ok_or(Error::ExceededMaximumCycles)?;
+            txs_cycles.push(cycles);
         }
 
-        // make verifiers orthogonal
-        let cycles_set = resolved
-            .par_iter()
-            .enumerate()
-            .map(|(index, tx)| {
-                TransactionVerifier::new(
-                    &tx,
-                    Arc::clone(&store),
-                    &block_median_time_context,
-                    tip_number,
-                    cellbase_maturity,
-                    &self.script_config,
-                )
-                .verify(self.max_cycles)
-                .map_err(|e| Error::Transactions((index, e)))
-                .map(|cycles| cycles)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let sum: Cycle = cycles_set.iter().sum();
-
         if sum > self.max_cycles {
             Err(Error::ExceededMaximumCycles)
         } else {
-            Ok(())
+            Ok(txs_cycles)
         }
     }
 }
@@ -428,24 +639,27 @@ impl<CP: ChainProvider + Clone> CommitVerifier<CP> {
         CommitVerifier { provider }
     }
 
-    pub fn verify(&self, block: &Block) -> Result<(), Error> {
-        if block.is_genesis() {
-            return Ok(());
+    // Collects the ids proposed by the ancestors of `block` numbered from
+    // `from` down to `to`, including the proposals of their uncles.
+    fn collect_proposals(
+        &self,
+        block: &Block,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<FnvHashSet<ProposalShortId>, Error> {
+        let mut proposal_txs_ids = FnvHashSet::default();
+        if from < to || from >= block.header().number() {
+            return Ok(proposal_txs_ids);
         }
-        let block_number = block.header().number();
-        let proposal_window = self.provider.consensus().tx_proposal_window();
-        let proposal_start = block_number.saturating_sub(proposal_window.start());
-        let mut proposal_end = block_number.saturating_sub(proposal_window.end());
 
         let mut block_hash = self
             .provider
-            .get_ancestor(&block.header().parent_hash(), proposal_end)
+            .get_ancestor(&block.header().parent_hash(), from)
             .map(|h| h.hash().to_owned())
             .ok_or_else(|| Error::Commit(CommitError::AncestorNotFound))?;
+        let mut number = from;
 
-        let mut proposal_txs_ids = FnvHashSet::default();
-
-        while proposal_end >= proposal_start {
+        while number >= to {
             let header = self
                 .provider
                 .block_header(&block_hash)
@@ -464,8 +678,21 @@ impl<CP: ChainProvider + Clone> CommitVerifier<CP> {
             }
 
             block_hash = header.parent_hash().to_owned();
-            proposal_end -= 1;
+            number -= 1;
         }
+        Ok(proposal_txs_ids)
+    }
+
+    pub fn verify(&self, block: &Block) -> Result<(), Error> {
+        if block.is_genesis() {
+            return Ok(());
+        }
+        let block_number = block.header().number();
+        let proposal_window = self.provider.consensus().tx_proposal_window();
+        let proposal_start = block_number.saturating_sub(proposal_window.start());
+        let proposal_end = block_number.saturating_sub(proposal_window.end());
+
+        let proposal_txs_ids = self.collect_proposals(block, proposal_end, proposal_start)?;
 
         let committed_ids: FnvHashSet<_> = block
             .transactions()
@@ -481,7 +708,23 @@ impl<CP: ChainProvider + Clone> CommitVerifier<CP> {
             error!(target: "chain",  "proposal_window proposal_start {}", proposal_start);
             error!(target: "chain",  "committed_ids {} ", serde_json::to_string(&committed_ids).unwrap());
             error!(target: "chain",  "proposal_txs_ids {} ", serde_json::to_string(&proposal_txs_ids).unwrap());
-            return Err(Error::Commit(CommitError::Invalid));
+
+            // Tell transactions proposed too recently or too long ago, looking
+            // back as far again as the window, from never proposed ones.
+            let mut nearby_ids =
+                self.collect_proposals(block, block_number - 1, proposal_end + 1)?;
+            if proposal_start > 0 {
+                nearby_ids.extend(self.collect_proposals(
+                    block,
+                    proposal_start - 1,
+                    proposal_start.saturating_sub(proposal_window.start()),
+                )?);
+            }
+            if difference.iter().all(|id| nearby_ids.contains(id)) {
+                return Err(Error::Commit(CommitError::WindowExceeded));
+            } else {
+                return Err(Error::Commit(CommitError::NotProposed));
+            }
         }
         Ok(())
     }
@@ -1,7 +1,8 @@
-use ckb_core::BlockNumber;
+use ckb_core::{BlockNumber, Capacity, EpochNumber};
 use ckb_script::ScriptError;
 use numext_fixed_hash::H256;
 use numext_fixed_uint::U256;
+use serde_derive::Serialize;
 use std::error::Error as StdError;
 use std::fmt;
 
@@ -11,7 +12,7 @@ use std::fmt;
 /// Those error kind carry some data that provide additional information,
 /// ErrorKind pattern should only carry stateless data. And, our ErrorKind can not be `Eq`.
 /// If the Rust community has better patterns in the future, then look back here
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
 pub enum Error {
     /// PoW proof is corrupt or does not meet the difficulty target.
     Pow(PowError),
@@ -57,6 +58,38 @@ pub enum Error {
     Version,
     /// Overflow when do computation for capacity.
     CapacityOverflow,
+    /// The genesis block does not meet the genesis-only rules.
+    Genesis(GenesisError),
+}
+
+impl Error {
+    /// Stable numeric code of the error kind, reported by RPC along with the
+    /// serialized error. Codes must never be reused, give new variants new codes.
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::Pow(_) => 1,
+            Error::Timestamp(_) => 2,
+            Error::Number(_) => 3,
+            Error::Epoch(_) => 4,
+            Error::Transactions(_) => 5,
+            Error::Chain(_) => 6,
+            Error::ProposalTransactionDuplicate => 7,
+            Error::CommitTransactionDuplicate => 8,
+            Error::ProposalTransactionsRoot => 9,
+            Error::CommitTransactionsRoot => 10,
+            Error::WitnessesMerkleRoot => 11,
+            Error::UnknownParent(_) => 12,
+            Error::Uncles(_) => 13,
+            Error::Cellbase(_) => 14,
+            Error::Commit(_) => 15,
+            Error::ExceededMaximumCycles => 16,
+            Error::ExceededMaximumProposalsLimit => 17,
+            Error::ExceededMaximumBlockBytes => 18,
+            Error::Version => 19,
+            Error::CapacityOverflow => 20,
+            Error::Genesis(_) => 21,
+        }
+    }
 }
 
 impl StdError for Error {}
@@ -67,23 +100,52 @@ impl fmt::Display for Error {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize)]
 pub enum CommitError {
     /// Ancestor not found, should not happen, we check header first and check ancestor.
     AncestorNotFound,
-    /// Break propose-then-commit consensus rule.
-    Invalid,
+    /// Break propose-then-commit consensus rule, the transaction was never proposed.
+    NotProposed,
+    /// Break propose-then-commit consensus rule, the transaction was proposed
+    /// too recently or its proposal has expired.
+    WindowExceeded,
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize)]
+pub enum GenesisError {
+    /// Genesis block number must be zero
+    Number { actual: BlockNumber },
+    /// Genesis block parent hash must be zero
+    ParentHash { actual: H256 },
+    /// Genesis block hash differs from the one of the chain spec
+    Hash { expected: H256, actual: H256 },
+    /// Genesis header field differs from the one declared in the chain spec
+    SpecField { field: &'static str },
+    /// Genesis block must not have uncles
+    Uncles { count: usize },
+    /// Genesis epoch must be the epoch 0 starting from block 0
+    EpochExt {
+        number: EpochNumber,
+        start: BlockNumber,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Eq, Serialize)]
 pub enum CellbaseError {
     InvalidInput,
     InvalidReward,
     InvalidQuantity,
     InvalidPosition,
+    /// Cellbase carries more than one witness, or a message longer than the
+    /// consensus `max_cellbase_message_bytes`
+    InvalidMessage {
+        max: u64,
+        actual: u64,
+    },
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+/// Uncles errors, the ones about a single uncle carry its `index` in the block
+#[derive(Debug, PartialEq, Clone, Eq, Serialize)]
 pub enum UnclesError {
     OverCount {
         max: u32,
@@ -94,6 +156,7 @@ pub enum UnclesError {
         actual: u32,
     },
     InvalidDepth {
+        index: usize,
         max: BlockNumber,
         min: BlockNumber,
         actual: BlockNumber,
@@ -102,46 +165,94 @@ pub enum UnclesError {
         expected: H256,
         actual: H256,
     },
-    InvalidDifficulty,
-    InvalidDifficultyEpoch,
-    InvalidProof,
-    ProposalsRoot,
-    ProposalDuplicate,
-    Duplicate(H256),
-    InvalidInclude(H256),
+    InvalidDifficulty {
+        index: usize,
+        expected: U256,
+        actual: U256,
+    },
+    InvalidDifficultyEpoch {
+        index: usize,
+        expected: EpochNumber,
+        actual: EpochNumber,
+    },
+    InvalidProof {
+        index: usize,
+    },
+    ProposalsRoot {
+        index: usize,
+        expected: H256,
+        actual: H256,
+    },
+    ProposalDuplicate {
+        index: usize,
+    },
+    Duplicate {
+        index: usize,
+        hash: H256,
+    },
+    InvalidInclude {
+        index: usize,
+        hash: H256,
+    },
     InvalidCellbase,
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize)]
 pub enum PowError {
     Boundary { expected: U256, actual: U256 },
     InvalidProof,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Serialize)]
 pub enum TimestampError {
     BlockTimeTooOld { min: u64, found: u64 },
     BlockTimeTooNew { max: u64, found: u64 },
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Serialize)]
 pub struct NumberError {
     pub expected: u64,
     pub actual: u64,
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize)]
 pub enum EpochError {
-    DifficultyMismatch { expected: U256, actual: U256 },
-    NumberMismatch { expected: u64, actual: u64 },
+    DifficultyMismatch {
+        expected: U256,
+        actual: U256,
+    },
+    NumberMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    /// Block number is not within the `length` blocks starting at `start` of the epoch
+    BlockNumberOutOfRange {
+        start: BlockNumber,
+        length: BlockNumber,
+        actual: BlockNumber,
+    },
     AncestorNotFound,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Serialize)]
 pub enum TransactionError {
     /// Occur output's bytes_len exceed capacity
     CapacityOverflow,
-    DuplicateDeps,
+    /// Output at `index` declares less capacity than it occupies, short by `shortfall`
+    InsufficientCellCapacity {
+        index: usize,
+        shortfall: Capacity,
+    },
+    /// Deps at indices `first` and `second` are the same
+    DuplicateDeps {
+        first: usize,
+        second: usize,
+    },
+    /// Inputs at indices `first` and `second` spend the same cell
+    DuplicateInputs {
+        first: usize,
+        second: usize,
+    },
     Empty,
     /// Sum of all outputs capacity exceed sum of all inputs in the transaction
     OutputsSumOverflow,
@@ -154,21 +265,80 @@ pub enum TransactionError {
     /// Invalid ValidSince flags
     InvalidValidSince,
     CellbaseImmaturity,
+    /// Output marked as dep group doesn't carry a valid list of cell OutPoints
+    InvalidDepGroup,
+    /// Type id cells are duplicated, malformed or created with an id not
+    /// derived from the first input
+    InvalidTypeId,
+    /// Serialized transaction is larger than the consensus `max_tx_bytes`
+    ExceededMaximumBytes {
+        max: u64,
+        actual: u64,
+    },
+    /// Transaction has more witnesses than inputs
+    TooManyWitnesses {
+        inputs: usize,
+        witnesses: usize,
+    },
 }
 
 impl TransactionError {
+    /// Stable numeric code of the error kind, starting from 100 so it never
+    /// clashes with the block error codes of `Error::code`.
+    pub fn code(self) -> u32 {
+        use TransactionError::*;
+        match self {
+            CapacityOverflow => 100,
+            InsufficientCellCapacity { .. } => 101,
+            DuplicateDeps { .. } => 102,
+            Empty => 103,
+            OutputsSumOverflow => 104,
+            InvalidScript => 105,
+            ScriptFailure(_) => 106,
+            InvalidSignature => 107,
+            Version => 108,
+            Immature => 109,
+            InvalidValidSince => 110,
+            CellbaseImmaturity => 111,
+            InvalidDepGroup => 112,
+            InvalidTypeId => 113,
+            DuplicateInputs { .. } => 114,
+            ExceededMaximumBytes { .. } => 115,
+            TooManyWitnesses { .. } => 116,
+        }
+    }
+
     /// Transaction error may be caused by different tip between peers if this method return false,
     /// Otherwise we consider the Bad Tx is constructed intendedly.
     pub fn is_bad_tx(self) -> bool {
         use TransactionError::*;
         match self {
-            CapacityOverflow | Empty | OutputsSumOverflow | InvalidScript | ScriptFailure(_)
-            | InvalidSignature | InvalidValidSince => true,
+            CapacityOverflow
+            | InsufficientCellCapacity { .. }
+            | DuplicateInputs { .. }
+            | ExceededMaximumBytes { .. }
+            | TooManyWitnesses { .. }
+            | Empty
+            | OutputsSumOverflow
+            | InvalidScript
+            | ScriptFailure(_)
+            | InvalidSignature
+            | InvalidValidSince
+            | InvalidDepGroup
+            | InvalidTypeId => true,
             _ => false,
         }
     }
 }
 
+impl StdError for TransactionError {}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self, f)
+    }
+}
+
 impl From<occupied_capacity::Error> for TransactionError {
     fn from(error: occupied_capacity::Error) -> Self {
         match error {
@@ -0,0 +1,111 @@
+use crate::error::{Error, GenesisError};
+use crate::header_verifier::EpochVerifier;
+use crate::{NonContextualBlockVerifier, Verifier};
+use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::Genesis;
+use ckb_core::block::Block;
+use numext_fixed_hash::H256;
+
+/// Checks the invariants only the genesis block has, so a node started with a
+/// chain spec inconsistent with its genesis block reports what is wrong
+/// instead of failing later in the generic block verification.
+///
+/// The block is compared with the genesis hash of the consensus and, when
+/// given, with the header fields and hash declared in the chain spec.
+pub struct GenesisVerifier<'a> {
+    consensus: &'a Consensus,
+    spec: Option<&'a Genesis>,
+}
+
+impl<'a> GenesisVerifier<'a> {
+    pub fn new(consensus: &'a Consensus) -> Self {
+        GenesisVerifier {
+            consensus,
+            spec: None,
+        }
+    }
+
+    /// Also checks the block against the `genesis` section of the chain spec.
+    pub fn spec(mut self, spec: &'a Genesis) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+
+    fn verify_spec(&self, block: &Block) -> Result<(), Error> {
+        let spec = match self.spec {
+            Some(spec) => spec,
+            None => return Ok(()),
+        };
+        let header = block.header();
+        if let Some(ref expected) = spec.hash {
+            if header.hash() != expected {
+                return Err(Error::Genesis(GenesisError::Hash {
+                    expected: expected.to_owned(),
+                    actual: header.hash().to_owned(),
+                }));
+            }
+        }
+        let mismatch = if header.version() != spec.version {
+            Some("version")
+        } else if header.parent_hash() != &spec.parent_hash {
+            Some("parent_hash")
+        } else if header.timestamp() != spec.timestamp {
+            Some("timestamp")
+        } else if header.difficulty() != &spec.difficulty {
+            Some("difficulty")
+        } else if header.uncles_hash() != &spec.uncles_hash {
+            Some("uncles_hash")
+        } else if header.nonce() != spec.seal.nonce {
+            Some("nonce")
+        } else if header.proof() != &spec.seal.proof[..] {
+            Some("proof")
+        } else {
+            None
+        };
+        match mismatch {
+            Some(field) => Err(Error::Genesis(GenesisError::SpecField { field })),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> Verifier for GenesisVerifier<'a> {
+    type Target = Block;
+
+    fn verify(&self, block: &Block) -> Result<(), Error> {
+        let header = block.header();
+        if header.number() != 0 {
+            return Err(Error::Genesis(GenesisError::Number {
+                actual: header.number(),
+            }));
+        }
+        if header.parent_hash() != &H256::zero() {
+            return Err(Error::Genesis(GenesisError::ParentHash {
+                actual: header.parent_hash().to_owned(),
+            }));
+        }
+        if header.hash() != self.consensus.genesis_hash() {
+            return Err(Error::Genesis(GenesisError::Hash {
+                expected: self.consensus.genesis_hash().to_owned(),
+                actual: header.hash().to_owned(),
+            }));
+        }
+        self.verify_spec(block)?;
+        if !block.uncles().is_empty() {
+            return Err(Error::Genesis(GenesisError::Uncles {
+                count: block.uncles().len(),
+            }));
+        }
+
+        let epoch_ext = self.consensus.genesis_epoch_ext();
+        if epoch_ext.number() != 0 || epoch_ext.start_number() != 0 {
+            return Err(Error::Genesis(GenesisError::EpochExt {
+                number: epoch_ext.number(),
+                start: epoch_ext.start_number(),
+            }));
+        }
+        EpochVerifier::new(header, epoch_ext).verify()?;
+
+        NonContextualBlockVerifier::new(self.consensus).verify(block)
+    }
+}
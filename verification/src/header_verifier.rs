@@ -1,11 +1,14 @@
 use super::Verifier;
 use crate::error::{EpochError, Error, NumberError, PowError, TimestampError};
+use crate::pipeline::VerifierPipeline;
 use crate::ALLOWED_FUTURE_BLOCKTIME;
+use ckb_chain_spec::versionbits::{VERSIONBITS_TOP_BITS, VERSIONBITS_TOP_MASK};
 use ckb_core::extras::EpochExt;
 use ckb_core::header::{Header, HEADER_VERSION};
 use ckb_pow::PowEngine;
 use ckb_traits::BlockMedianTimeContext;
 use faketime::unix_time_as_millis;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -20,6 +23,7 @@ pub trait HeaderResolver {
 pub struct HeaderVerifier<T, M> {
     pub pow: Arc<dyn PowEngine>,
     block_median_time_context: M,
+    skipped: Vec<&'static str>,
     _phantom: PhantomData<T>,
 }
 
@@ -28,28 +32,66 @@ impl<T, M: BlockMedianTimeContext> HeaderVerifier<T, M> {
         HeaderVerifier {
             pow,
             block_median_time_context,
+            skipped: Vec::new(),
             _phantom: PhantomData,
         }
     }
+
+    /// Skips the pipeline stage named `stage`, see `pipeline`
+    pub fn skip(mut self, stage: &'static str) -> Self {
+        self.skipped.push(stage);
+        self
+    }
+
+    /// Skips the proof of work check, for headers already verified by `HeadersVerifier`
+    pub fn skip_pow(self) -> Self {
+        self.skip("pow")
+    }
+}
+
+impl<T: HeaderResolver, M: BlockMedianTimeContext> HeaderVerifier<T, M> {
+    /// Builds the stages verifying a header, named `version`, `pow`, `number`,
+    /// `timestamp` and `epoch` in this order.
+    pub fn pipeline(&self) -> VerifierPipeline<'_, T> {
+        let pipeline = VerifierPipeline::new()
+            .stage("version", |target: &T| {
+                VersionVerifier::new(target.header()).verify()
+            })
+            // POW check first
+            .stage("pow", move |target: &T| {
+                PowVerifier::new(target.header(), &self.pow).verify()
+            })
+            .stage("number", |target: &T| {
+                let header = target.header();
+                let parent = target
+                    .parent()
+                    .ok_or_else(|| Error::UnknownParent(header.parent_hash().to_owned()))?;
+                NumberVerifier::new(parent, header).verify()
+            })
+            .stage("timestamp", move |target: &T| {
+                TimestampVerifier::new(&self.block_median_time_context, target.header()).verify()
+            })
+            .stage("epoch", |target: &T| {
+                let epoch = target
+                    .epoch()
+                    .ok_or_else(|| Error::Epoch(EpochError::AncestorNotFound))?;
+                EpochVerifier::new(target.header(), epoch).verify()
+            });
+        self.skipped
+            .iter()
+            .fold(pipeline, |pipeline, stage| pipeline.skip(stage))
+    }
 }
 
 impl<T: HeaderResolver, M: BlockMedianTimeContext> Verifier for HeaderVerifier<T, M> {
     type Target = T;
     fn verify(&self, target: &T) -> Result<(), Error> {
-        let header = target.header();
-        VersionVerifier::new(header).verify()?;
-        // POW check first
-        PowVerifier::new(header, &self.pow).verify()?;
-        let parent = target
-            .parent()
-            .ok_or_else(|| Error::UnknownParent(header.parent_hash().to_owned()))?;
-        NumberVerifier::new(parent, header).verify()?;
-        TimestampVerifier::new(&self.block_median_time_context, header).verify()?;
-        EpochVerifier::verify(target)?;
-        Ok(())
+        self.pipeline().verify(target)
     }
 }
 
+/// Checks the version format only, the signaled bits are tallied by the chain
+/// at epoch boundaries, see `ckb_chain_spec::versionbits`
 pub struct VersionVerifier<'a> {
     header: &'a Header,
 }
@@ -60,7 +102,9 @@ impl<'a> VersionVerifier<'a> {
     }
 
     pub fn verify(&self) -> Result<(), Error> {
-        if self.header.version() != HEADER_VERSION {
+        // Headers signaling soft forks carry the version bits prefix instead
+        let version = self.header.version();
+        if version != HEADER_VERSION && (version & VERSIONBITS_TOP_MASK) != VERSIONBITS_TOP_BITS {
             return Err(Error::Version);
         }
         Ok(())
@@ -134,26 +178,40 @@ impl<'a> NumberVerifier<'a> {
     }
 }
 
-pub struct EpochVerifier<T> {
-    phantom: PhantomData<T>,
+/// Checks a header against the epoch recomputed from its parent chain, see
+/// `ChainProvider::next_epoch_ext`.
+pub struct EpochVerifier<'a> {
+    header: &'a Header,
+    epoch: &'a EpochExt,
 }
 
-impl<T: HeaderResolver> EpochVerifier<T> {
-    pub fn verify(target: &T) -> Result<(), Error> {
-        let epoch = target
-            .epoch()
-            .ok_or_else(|| Error::Epoch(EpochError::AncestorNotFound))?;
-        let actual_epoch_number = target.header().epoch();
-        if actual_epoch_number != epoch.number() {
+impl<'a> EpochVerifier<'a> {
+    pub fn new(header: &'a Header, epoch: &'a EpochExt) -> Self {
+        EpochVerifier { header, epoch }
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let actual_epoch_number = self.header.epoch();
+        if actual_epoch_number != self.epoch.number() {
             return Err(Error::Epoch(EpochError::NumberMismatch {
-                expected: epoch.number(),
+                expected: self.epoch.number(),
                 actual: actual_epoch_number,
             }));
         }
-        let actual_difficulty = target.header().difficulty();
-        if epoch.difficulty() != actual_difficulty {
+        let start = self.epoch.start_number();
+        let length = self.epoch.length();
+        let number = self.header.number();
+        if number < start || number - start >= length {
+            return Err(Error::Epoch(EpochError::BlockNumberOutOfRange {
+                start,
+                length,
+                actual: number,
+            }));
+        }
+        let actual_difficulty = self.header.difficulty();
+        if self.epoch.difficulty() != actual_difficulty {
             return Err(Error::Epoch(EpochError::DifficultyMismatch {
-                expected: epoch.difficulty().clone(),
+                expected: self.epoch.difficulty().clone(),
                 actual: actual_difficulty.clone(),
             }));
         }
@@ -182,3 +240,67 @@ impl<'a> PowVerifier<'a> {
         }
     }
 }
+
+/// Verifies a contiguous chain of headers at once, the linkage and difficulty
+/// continuity serially and the proofs of work in parallel.
+pub struct HeadersVerifier {
+    pow: Arc<dyn PowEngine>,
+}
+
+impl HeadersVerifier {
+    pub fn new(pow: &Arc<dyn PowEngine>) -> Self {
+        HeadersVerifier {
+            pow: Arc::clone(pow),
+        }
+    }
+
+    fn verify_continuity(parent: &Header, header: &Header) -> Result<(), Error> {
+        if header.parent_hash() != parent.hash() {
+            return Err(Error::UnknownParent(header.parent_hash().to_owned()));
+        }
+        NumberVerifier::new(parent, header).verify()?;
+        // difficulty only changes at epoch transitions
+        if header.epoch() == parent.epoch() {
+            if header.difficulty() != parent.difficulty() {
+                return Err(Error::Epoch(EpochError::DifficultyMismatch {
+                    expected: parent.difficulty().to_owned(),
+                    actual: header.difficulty().to_owned(),
+                }));
+            }
+        } else if header.epoch() != parent.epoch() + 1 {
+            return Err(Error::Epoch(EpochError::NumberMismatch {
+                expected: parent.epoch() + 1,
+                actual: header.epoch(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the first invalid header along with its error.
+    pub fn verify(&self, headers: &[Header]) -> Result<(), (usize, Error)> {
+        let discontinuity = headers
+            .windows(2)
+            .enumerate()
+            .filter_map(|(index, window)| {
+                Self::verify_continuity(&window[0], &window[1])
+                    .err()
+                    .map(|err| (index + 1, err))
+            })
+            .next();
+
+        // only headers up to the first discontinuity matter
+        let checked = discontinuity
+            .as_ref()
+            .map(|(index, _)| &headers[..=*index])
+            .unwrap_or(headers);
+        let invalid_pow = checked
+            .par_iter()
+            .position_first(|header| !self.pow.verify_header(header));
+
+        match (invalid_pow, discontinuity) {
+            (Some(index), _) => Err((index, Error::Pow(PowError::InvalidProof))),
+            (None, Some(discontinuity)) => Err(discontinuity),
+            (None, None) => Ok(()),
+        }
+    }
+}
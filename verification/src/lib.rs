@@ -1,19 +1,38 @@
 mod block_verifier;
 mod error;
+mod genesis_verifier;
 mod header_verifier;
+mod merkle_proof;
+mod pipeline;
 mod transaction_verifier;
 
 #[cfg(test)]
 mod tests;
 
-pub use crate::block_verifier::{BlockVerifier, HeaderResolverWrapper, TransactionsVerifier};
-pub use crate::error::{Error, TransactionError};
-pub use crate::header_verifier::{HeaderResolver, HeaderVerifier};
-pub use crate::transaction_verifier::{PoolTransactionVerifier, TransactionVerifier};
+pub use crate::block_verifier::{
+    BlockVerifier, ContextualBlockVerifier, HeaderResolverWrapper, MerkleRootVerifier,
+    NonContextualBlockVerifier, RewardVerifier, TransactionsVerifier,
+};
+pub use crate::error::{Error, GenesisError, TransactionError};
+pub use crate::genesis_verifier::GenesisVerifier;
+pub use crate::header_verifier::{HeaderResolver, HeaderVerifier, HeadersVerifier};
+pub use crate::merkle_proof::TransactionsProof;
+pub use crate::pipeline::VerifierPipeline;
+pub use crate::transaction_verifier::{
+    NonContextualTransactionVerifier, PoolTransactionVerifier, TransactionVerifier,
+};
 
 pub const ALLOWED_FUTURE_BLOCKTIME: u64 = 15 * 1000; // 15 Second
 
+/// Verification needing nothing but the target itself and consensus parameters
 pub trait Verifier {
     type Target;
     fn verify(&self, target: &Self::Target) -> Result<(), Error>;
 }
+
+/// Verification of a target against the chain context `C` it extends, e.g. a
+/// `ChainProvider`
+pub trait ContextualVerifier<C> {
+    type Target;
+    fn verify(&self, context: &C, target: &Self::Target) -> Result<(), Error>;
+}
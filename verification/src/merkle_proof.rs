@@ -0,0 +1,67 @@
+use ckb_core::block::Block;
+use ckb_core::header::Header;
+use ckb_merkle_tree::{build_merkle_proof, MerkleProof};
+use numext_fixed_hash::H256;
+
+/// Inclusion proof of a set of transactions in the `transactions_root` of a
+/// block header, so a client holding only the header can check the
+/// transactions were committed in the block.
+pub struct TransactionsProof {
+    proof: MerkleProof,
+    /// Hashes of the proven transactions, in the order of `proof.indices()`
+    transactions: Vec<H256>,
+}
+
+impl TransactionsProof {
+    /// Builds the proof of the transactions at `indices` in `block`, returns
+    /// `None` if `indices` is empty or any index is out of range.
+    pub fn build(block: &Block, indices: &[usize]) -> Option<Self> {
+        let leaves = block
+            .transactions()
+            .iter()
+            .map(|tx| tx.hash().to_owned())
+            .collect::<Vec<_>>();
+        if indices.is_empty() || indices.iter().any(|index| *index >= leaves.len()) {
+            return None;
+        }
+        let proof = build_merkle_proof(&leaves, indices)?;
+        // CBMT stores leaf `i` of `n` leaves at tree index `n - 1 + i`
+        let transactions = proof
+            .indices()
+            .iter()
+            .map(|tree_index| leaves[tree_index + 1 - leaves.len()].clone())
+            .collect();
+        Some(TransactionsProof {
+            proof,
+            transactions,
+        })
+    }
+
+    pub fn from_parts(proof: MerkleProof, transactions: Vec<H256>) -> Self {
+        TransactionsProof {
+            proof,
+            transactions,
+        }
+    }
+
+    pub fn into_parts(self) -> (MerkleProof, Vec<H256>) {
+        (self.proof, self.transactions)
+    }
+
+    pub fn proof(&self) -> &MerkleProof {
+        &self.proof
+    }
+
+    pub fn transactions(&self) -> &[H256] {
+        &self.transactions
+    }
+
+    /// Returns true if the proven transactions are committed in the block of
+    /// `header`.
+    pub fn verify(&self, header: &Header) -> bool {
+        self.transactions.len() == self.proof.indices().len()
+            && self
+                .proof
+                .verify(header.transactions_root(), &self.transactions)
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Error, Verifier};
+
+struct Stage<'a, T> {
+    name: &'static str,
+    verify: Box<dyn Fn(&T) -> Result<(), Error> + 'a>,
+}
+
+/// An ordered list of named verification stages, run until the first error.
+///
+/// Stages can be appended, inserted before another one or skipped by name, so
+/// integration tests and dev chains can disable a single rule, see
+/// `BlockVerifier::pipeline` and `HeaderVerifier::pipeline` for the stage names.
+pub struct VerifierPipeline<'a, T> {
+    stages: Vec<Stage<'a, T>>,
+}
+
+impl<'a, T> Default for VerifierPipeline<'a, T> {
+    fn default() -> Self {
+        VerifierPipeline { stages: Vec::new() }
+    }
+}
+
+impl<'a, T> VerifierPipeline<'a, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a stage
+    pub fn stage<F>(mut self, name: &'static str, verify: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), Error> + 'a,
+    {
+        self.stages.push(Stage {
+            name,
+            verify: Box::new(verify),
+        });
+        self
+    }
+
+    /// Appends a stage running `verifier`
+    pub fn verifier<V>(self, name: &'static str, verifier: V) -> Self
+    where
+        V: Verifier<Target = T> + 'a,
+    {
+        self.stage(name, move |target| verifier.verify(target))
+    }
+
+    /// Inserts a stage before the stage named `before`, or appends it if there
+    /// is no such stage.
+    pub fn insert_before<F>(mut self, before: &str, name: &'static str, verify: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), Error> + 'a,
+    {
+        let index = self
+            .stages
+            .iter()
+            .position(|stage| stage.name == before)
+            .unwrap_or_else(|| self.stages.len());
+        self.stages.insert(
+            index,
+            Stage {
+                name,
+                verify: Box::new(verify),
+            },
+        );
+        self
+    }
+
+    /// Removes the stages named `name`
+    pub fn skip(mut self, name: &str) -> Self {
+        self.stages.retain(|stage| stage.name != name);
+        self
+    }
+
+    pub fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|stage| stage.name).collect()
+    }
+}
+
+impl<'a, T> Verifier for VerifierPipeline<'a, T> {
+    type Target = T;
+
+    fn verify(&self, target: &T) -> Result<(), Error> {
+        for stage in &self.stages {
+            (stage.verify)(target)?;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,12 @@
 use super::super::block_verifier::{
-    BlockBytesVerifier, BlockProposalsLimitVerifier, CellbaseVerifier,
+    BlockBytesVerifier, BlockProposalsLimitVerifier, CellbaseVerifier, NonContextualBlockVerifier,
+    RewardVerifier,
 };
 use super::super::error::{CellbaseError, Error as VerifyError};
+use crate::Verifier;
+use ckb_chain_spec::consensus::Consensus;
 use ckb_core::block::BlockBuilder;
+use ckb_core::cell::{CellMeta, ResolvedOutPoint, ResolvedTransaction};
 use ckb_core::script::Script;
 use ckb_core::transaction::{
     CellInput, CellOutput, OutPoint, ProposalShortId, Transaction, TransactionBuilder,
@@ -10,6 +14,8 @@ use ckb_core::transaction::{
 use ckb_core::{capacity_bytes, Bytes, Capacity};
 use numext_fixed_hash::H256;
 
+const MAX_CELLBASE_MESSAGE_BYTES: u64 = 16;
+
 fn create_cellbase_transaction_with_capacity(capacity: Capacity) -> Transaction {
     TransactionBuilder::default()
         .input(CellInput::new_cellbase_input(0))
@@ -47,7 +53,7 @@ pub fn test_block_without_cellbase() {
     let block = BlockBuilder::default()
         .transaction(TransactionBuilder::default().build())
         .build();
-    let verifier = CellbaseVerifier::new();
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
     assert_eq!(
         verifier.verify(&block),
         Err(VerifyError::Cellbase(CellbaseError::InvalidQuantity))
@@ -63,7 +69,7 @@ pub fn test_block_with_one_cellbase_at_first() {
         .transaction(transaction)
         .build();
 
-    let verifier = CellbaseVerifier::new();
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
     assert!(verifier.verify(&block).is_ok());
 }
 
@@ -74,7 +80,7 @@ pub fn test_block_with_one_cellbase_at_last() {
         .transaction(create_cellbase_transaction())
         .build();
 
-    let verifier = CellbaseVerifier::new();
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
     assert_eq!(
         verifier.verify(&block),
         Err(VerifyError::Cellbase(CellbaseError::InvalidPosition))
@@ -88,13 +94,34 @@ pub fn test_block_with_two_cellbases() {
         .transaction(create_cellbase_transaction())
         .build();
 
-    let verifier = CellbaseVerifier::new();
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
     assert_eq!(
         verifier.verify(&block),
         Err(VerifyError::Cellbase(CellbaseError::InvalidQuantity))
     );
 }
 
+#[test]
+pub fn test_cellbase_message() {
+    let cellbase = create_cellbase_transaction();
+    let with_witness = |witness: Vec<Vec<u8>>| {
+        let cellbase = TransactionBuilder::from_transaction(cellbase.clone())
+            .witness(witness)
+            .build();
+        BlockBuilder::default().transaction(cellbase).build()
+    };
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
+
+    assert!(verifier.verify(&with_witness(vec![vec![0; 16]])).is_ok());
+    assert_eq!(
+        verifier.verify(&with_witness(vec![vec![0; 10], vec![0; 7]])),
+        Err(VerifyError::Cellbase(CellbaseError::InvalidMessage {
+            max: MAX_CELLBASE_MESSAGE_BYTES,
+            actual: 17,
+        }))
+    );
+}
+
 #[test]
 pub fn test_cellbase_with_less_reward() {
     let transaction = create_normal_transaction();
@@ -106,7 +133,7 @@ pub fn test_cellbase_with_less_reward() {
         .transaction(transaction)
         .build();
 
-    let verifier = CellbaseVerifier::new();
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
     assert!(verifier.verify(&block).is_ok());
 }
 
@@ -121,10 +148,49 @@ pub fn test_cellbase_with_fee() {
         .transaction(transaction)
         .build();
 
-    let verifier = CellbaseVerifier::new();
+    let verifier = CellbaseVerifier::new(MAX_CELLBASE_MESSAGE_BYTES);
     assert!(verifier.verify(&block).is_ok());
 }
 
+#[test]
+pub fn test_reward_verifier() {
+    let normal = create_normal_transaction();
+    let resolved_normal = || ResolvedTransaction {
+        transaction: &normal,
+        resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
+        resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta::from(
+            &CellOutput::new(capacity_bytes!(130), Bytes::new(), Script::default(), None),
+        ))],
+    };
+    let resolved_cellbase = |cellbase| ResolvedTransaction {
+        transaction: cellbase,
+        resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
+        resolved_inputs: Vec::new(),
+    };
+
+    // block reward 100 plus fee 30
+    let cellbase = create_cellbase_transaction_with_capacity(capacity_bytes!(130));
+    let resolved = vec![resolved_cellbase(&cellbase), resolved_normal()];
+    let verifier = RewardVerifier::new(&resolved, capacity_bytes!(100));
+    assert!(verifier.verify().is_ok());
+
+    let cellbase = create_cellbase_transaction_with_capacity(capacity_bytes!(131));
+    let resolved = vec![resolved_cellbase(&cellbase), resolved_normal()];
+    let verifier = RewardVerifier::new(&resolved, capacity_bytes!(100));
+    assert_eq!(
+        verifier.verify(),
+        Err(VerifyError::Cellbase(CellbaseError::InvalidReward))
+    );
+
+    let verifier = RewardVerifier::new(&[], capacity_bytes!(100));
+    assert_eq!(
+        verifier.verify(),
+        Err(VerifyError::Cellbase(CellbaseError::InvalidQuantity))
+    );
+}
+
 #[test]
 pub fn test_max_block_bytes_verifier() {
     let block = BlockBuilder::default().build();
@@ -165,3 +231,37 @@ pub fn test_max_proposals_limit_verifier() {
         );
     }
 }
+
+#[test]
+pub fn test_non_contextual_block_verifier() {
+    let consensus = Consensus::default();
+    let verifier = NonContextualBlockVerifier::new(&consensus);
+    assert_eq!(
+        verifier.pipeline().stage_names(),
+        vec![
+            "proposals_limit",
+            "block_bytes",
+            "cellbase",
+            "duplicate",
+            "merkle_root"
+        ]
+    );
+
+    let block = BlockBuilder::default()
+        .transaction(create_normal_transaction())
+        .build();
+    assert_eq!(
+        verifier.verify(&block),
+        Err(VerifyError::Cellbase(CellbaseError::InvalidQuantity))
+    );
+
+    let block = BlockBuilder::default()
+        .transaction(create_cellbase_transaction())
+        .transaction(create_normal_transaction())
+        .transaction(create_normal_transaction())
+        .build();
+    assert_eq!(
+        verifier.verify(&block),
+        Err(VerifyError::CommitTransactionDuplicate)
+    );
+}
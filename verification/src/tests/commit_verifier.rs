@@ -147,7 +147,7 @@ fn test_proposal() {
         let verifier = CommitVerifier::new(shared.clone());
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Commit(CommitError::Invalid))
+            Err(Error::Commit(CommitError::WindowExceeded))
         );
 
         //test chain forward
@@ -178,6 +178,20 @@ fn test_proposal() {
     assert_eq!(verifier.verify(&block), Ok(()));
 }
 
+#[test]
+fn test_not_proposed() {
+    let (_chain_controller, shared, prev_tx_hash) = setup_env();
+
+    let parent = shared.block_header(&shared.block_hash(0).unwrap()).unwrap();
+    let tx = create_transaction(&prev_tx_hash);
+    let block: Block = gen_block(&parent, vec![tx], vec![], vec![]);
+    let verifier = CommitVerifier::new(shared.clone());
+    assert_eq!(
+        verifier.verify(&block),
+        Err(Error::Commit(CommitError::NotProposed))
+    );
+}
+
 #[test]
 fn test_uncle_proposal() {
     let (chain_controller, shared, mut prev_tx_hash) = setup_env();
@@ -209,7 +223,7 @@ fn test_uncle_proposal() {
         let verifier = CommitVerifier::new(shared.clone());
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Commit(CommitError::Invalid))
+            Err(Error::Commit(CommitError::WindowExceeded))
         );
 
         //test chain forward
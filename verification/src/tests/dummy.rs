@@ -1,4 +1,5 @@
 use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::versionbits::ThresholdState;
 use ckb_core::block::Block;
 use ckb_core::cell::{CellProvider, CellStatus};
 use ckb_core::extras::{BlockExt, EpochExt};
@@ -68,6 +69,18 @@ impl ChainProvider for DummyChainProvider {
         unimplemented!();
     }
 
+    fn versionbits_states(&self, _epoch: &EpochExt) -> Vec<ThresholdState> {
+        unimplemented!();
+    }
+
+    fn next_versionbits_states(
+        &self,
+        _last_epoch: &EpochExt,
+        _header: &Header,
+    ) -> Vec<ThresholdState> {
+        unimplemented!();
+    }
+
     fn consensus(&self) -> &Consensus {
         unimplemented!();
     }
@@ -0,0 +1,44 @@
+use crate::error::{Error, PowError, TransactionError};
+use ckb_core::{capacity_bytes, Capacity};
+use ckb_script::ScriptError;
+use serde_json::json;
+
+#[test]
+pub fn test_error_codes() {
+    assert_eq!(Error::Pow(PowError::InvalidProof).code(), 1);
+    assert_eq!(Error::CapacityOverflow.code(), 20);
+    assert_eq!(
+        Error::Transactions((1, TransactionError::Immature)).code(),
+        5
+    );
+    assert_eq!(TransactionError::CapacityOverflow.code(), 100);
+    assert_eq!(TransactionError::InvalidTypeId.code(), 113);
+}
+
+#[test]
+pub fn test_error_serialization() {
+    let error = Error::Transactions((
+        2,
+        TransactionError::InsufficientCellCapacity {
+            index: 1,
+            shortfall: capacity_bytes!(41),
+        },
+    ));
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        json!({
+            "Transactions": [2, {
+                "InsufficientCellCapacity": {
+                    "index": 1,
+                    "shortfall": 4_100_000_000u64,
+                }
+            }]
+        })
+    );
+
+    let error = TransactionError::ScriptFailure(ScriptError::ValidationFailure(2));
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        json!({ "ScriptFailure": { "ValidationFailure": 2 } })
+    );
+}
@@ -0,0 +1,124 @@
+use crate::error::{CellbaseError, Error, GenesisError};
+use crate::{GenesisVerifier, Verifier};
+use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::{Genesis, Seal};
+use ckb_core::block::{Block, BlockBuilder};
+use ckb_core::header::HeaderBuilder;
+use ckb_core::transaction::{CellInput, TransactionBuilder};
+use numext_fixed_hash::h256;
+use numext_fixed_uint::U256;
+
+fn create_genesis(header_builder: HeaderBuilder) -> Block {
+    let cellbase = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(0))
+        .build();
+    BlockBuilder::from_header_builder(header_builder.difficulty(U256::one()))
+        .transaction(cellbase)
+        .build()
+}
+
+#[test]
+pub fn test_genesis_verifier() {
+    let genesis = create_genesis(HeaderBuilder::default());
+    let consensus = Consensus::default().set_genesis_block(genesis.clone());
+    assert_eq!(GenesisVerifier::new(&consensus).verify(&genesis), Ok(()));
+
+    let other = create_genesis(HeaderBuilder::default().timestamp(1));
+    assert_eq!(
+        GenesisVerifier::new(&consensus).verify(&other),
+        Err(Error::Genesis(GenesisError::Hash {
+            expected: genesis.header().hash().to_owned(),
+            actual: other.header().hash().to_owned(),
+        }))
+    );
+}
+
+#[test]
+pub fn test_genesis_parent_hash() {
+    let genesis = create_genesis(HeaderBuilder::default().parent_hash(h256!("0x1")));
+    let consensus = Consensus::default().set_genesis_block(genesis.clone());
+    assert_eq!(
+        GenesisVerifier::new(&consensus).verify(&genesis),
+        Err(Error::Genesis(GenesisError::ParentHash {
+            actual: h256!("0x1"),
+        }))
+    );
+}
+
+#[test]
+pub fn test_genesis_without_cellbase() {
+    let genesis =
+        BlockBuilder::from_header_builder(HeaderBuilder::default().difficulty(U256::one())).build();
+    let consensus = Consensus::default().set_genesis_block(genesis.clone());
+    assert_eq!(
+        GenesisVerifier::new(&consensus).verify(&genesis),
+        Err(Error::Cellbase(CellbaseError::InvalidQuantity))
+    );
+}
+
+fn genesis_spec(block: &Block) -> Genesis {
+    let header = block.header();
+    Genesis {
+        version: header.version(),
+        parent_hash: header.parent_hash().to_owned(),
+        timestamp: header.timestamp(),
+        difficulty: header.difficulty().to_owned(),
+        uncles_hash: header.uncles_hash().to_owned(),
+        hash: Some(header.hash().to_owned()),
+        seal: Seal {
+            nonce: header.nonce(),
+            proof: header.proof().to_vec(),
+        },
+    }
+}
+
+#[test]
+pub fn test_genesis_spec_hash() {
+    let genesis = create_genesis(HeaderBuilder::default());
+    let consensus = Consensus::default().set_genesis_block(genesis.clone());
+    let mut spec = genesis_spec(&genesis);
+    assert_eq!(
+        GenesisVerifier::new(&consensus)
+            .spec(&spec)
+            .verify(&genesis),
+        Ok(())
+    );
+
+    spec.hash = Some(h256!("0x1"));
+    assert_eq!(
+        GenesisVerifier::new(&consensus)
+            .spec(&spec)
+            .verify(&genesis),
+        Err(Error::Genesis(GenesisError::Hash {
+            expected: h256!("0x1"),
+            actual: genesis.header().hash().to_owned(),
+        }))
+    );
+}
+
+#[test]
+pub fn test_genesis_spec_field() {
+    let genesis = create_genesis(HeaderBuilder::default());
+    let consensus = Consensus::default().set_genesis_block(genesis.clone());
+    let mut spec = genesis_spec(&genesis);
+    spec.hash = None;
+    spec.timestamp += 1;
+    assert_eq!(
+        GenesisVerifier::new(&consensus)
+            .spec(&spec)
+            .verify(&genesis),
+        Err(Error::Genesis(GenesisError::SpecField {
+            field: "timestamp"
+        }))
+    );
+
+    let mut spec = genesis_spec(&genesis);
+    spec.hash = None;
+    spec.seal.nonce += 1;
+    assert_eq!(
+        GenesisVerifier::new(&consensus)
+            .spec(&spec)
+            .verify(&genesis),
+        Err(Error::Genesis(GenesisError::SpecField { field: "nonce" }))
+    );
+}
@@ -0,0 +1,241 @@
+use super::super::header_verifier::{
+    EpochVerifier, HeadersVerifier, TimestampVerifier, VersionVerifier,
+};
+use crate::error::{EpochError, Error, NumberError, PowError, TimestampError};
+use ckb_chain_spec::versionbits::VERSIONBITS_TOP_BITS;
+use ckb_core::extras::EpochExt;
+use ckb_core::header::{BlockNumber, Header, HeaderBuilder};
+use ckb_core::Capacity;
+use ckb_pow::PowEngine;
+use ckb_traits::BlockMedianTimeContext;
+use faketime::unix_time_as_millis;
+use numext_fixed_hash::H256;
+use numext_fixed_uint::U256;
+use std::sync::Arc;
+
+struct FakeMedianTime {
+    timestamps: Vec<u64>,
+}
+
+impl BlockMedianTimeContext for FakeMedianTime {
+    fn median_block_count(&self) -> u64 {
+        11
+    }
+
+    fn timestamp(&self, n: u64) -> Option<u64> {
+        self.timestamps.get(n as usize).cloned()
+    }
+}
+
+#[test]
+pub fn test_timestamp_median_time_past() {
+    let now = unix_time_as_millis();
+    // the median of the 11 ancestors is 6 seconds ago, even though the parent
+    // is a minute ago
+    let mut timestamps: Vec<u64> = (0..11).map(|i| now - 11_000 + i * 1_000).collect();
+    timestamps.push(now - 60_000);
+    let context = FakeMedianTime { timestamps };
+    let min = context.block_median_time(11).unwrap();
+    assert_eq!(min, now - 6_000);
+
+    let header = HeaderBuilder::default().number(12).timestamp(min).build();
+    let verifier = TimestampVerifier::new(&context, &header);
+    assert_eq!(
+        verifier.verify(),
+        Err(Error::Timestamp(TimestampError::BlockTimeTooOld {
+            min,
+            found: min,
+        }))
+    );
+
+    let header = HeaderBuilder::default()
+        .number(12)
+        .timestamp(min + 1)
+        .build();
+    let verifier = TimestampVerifier::new(&context, &header);
+    assert_eq!(verifier.verify(), Ok(()));
+}
+
+// Accepts every header except the ones sealed with `invalid_nonce`
+struct RejectNoncePowEngine {
+    invalid_nonce: u64,
+}
+
+impl PowEngine for RejectNoncePowEngine {
+    fn init(&self, _number: BlockNumber) {}
+
+    fn verify_header(&self, header: &Header) -> bool {
+        header.nonce() != self.invalid_nonce
+    }
+
+    fn solve(&self, _number: BlockNumber, _message: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn verify(&self, _number: BlockNumber, _message: &[u8], _proof: &[u8]) -> bool {
+        true
+    }
+
+    fn proof_size(&self) -> usize {
+        0
+    }
+}
+
+fn gen_headers(count: usize) -> Vec<Header> {
+    let mut headers: Vec<Header> = vec![HeaderBuilder::default()
+        .difficulty(U256::from(1u64))
+        .build()];
+    for i in 1..count {
+        let parent = &headers[i - 1];
+        let header = HeaderBuilder::default()
+            .parent_hash(parent.hash().to_owned())
+            .number(parent.number() + 1)
+            .difficulty(parent.difficulty().to_owned())
+            .nonce(i as u64)
+            .build();
+        headers.push(header);
+    }
+    headers
+}
+
+#[test]
+pub fn test_headers_verifier() {
+    let pow: Arc<dyn PowEngine> = Arc::new(RejectNoncePowEngine { invalid_nonce: 0 });
+    let headers = gen_headers(10);
+    // genesis like first header carries nonce 0
+    assert_eq!(
+        HeadersVerifier::new(&pow).verify(&headers),
+        Err((0, Error::Pow(PowError::InvalidProof)))
+    );
+
+    let pow: Arc<dyn PowEngine> = Arc::new(RejectNoncePowEngine {
+        invalid_nonce: u64::max_value(),
+    });
+    assert_eq!(HeadersVerifier::new(&pow).verify(&headers), Ok(()));
+
+    let pow: Arc<dyn PowEngine> = Arc::new(RejectNoncePowEngine { invalid_nonce: 6 });
+    assert_eq!(
+        HeadersVerifier::new(&pow).verify(&headers),
+        Err((6, Error::Pow(PowError::InvalidProof)))
+    );
+}
+
+#[test]
+pub fn test_headers_verifier_continuity() {
+    let pow: Arc<dyn PowEngine> = Arc::new(RejectNoncePowEngine {
+        invalid_nonce: u64::max_value(),
+    });
+
+    let mut headers = gen_headers(5);
+    headers[3] = HeaderBuilder::from_header(headers[3].clone())
+        .number(10)
+        .build();
+    headers[4] = HeaderBuilder::from_header(headers[4].clone())
+        .parent_hash(headers[3].hash().to_owned())
+        .build();
+    assert_eq!(
+        HeadersVerifier::new(&pow).verify(&headers),
+        Err((
+            3,
+            Error::Number(NumberError {
+                expected: 3,
+                actual: 10,
+            })
+        ))
+    );
+
+    let mut headers = gen_headers(5);
+    headers[2] = HeaderBuilder::from_header(headers[2].clone())
+        .difficulty(U256::from(2u64))
+        .build();
+    headers[3] = HeaderBuilder::from_header(headers[3].clone())
+        .parent_hash(headers[2].hash().to_owned())
+        .build();
+    assert_eq!(
+        HeadersVerifier::new(&pow).verify(&headers),
+        Err((
+            2,
+            Error::Epoch(EpochError::DifficultyMismatch {
+                expected: U256::from(1u64),
+                actual: U256::from(2u64),
+            })
+        ))
+    );
+
+    // the proof of work of an earlier header takes precedence
+    let pow: Arc<dyn PowEngine> = Arc::new(RejectNoncePowEngine { invalid_nonce: 1 });
+    assert_eq!(
+        HeadersVerifier::new(&pow).verify(&headers),
+        Err((1, Error::Pow(PowError::InvalidProof)))
+    );
+}
+
+#[test]
+pub fn test_epoch_verifier() {
+    let epoch = EpochExt::new(
+        1,
+        Capacity::zero(),
+        Capacity::zero(),
+        H256::zero(),
+        100,
+        50,
+        U256::from(10u64),
+    );
+    let header = |number: BlockNumber, epoch: u64, difficulty: u64| {
+        HeaderBuilder::default()
+            .number(number)
+            .epoch(epoch)
+            .difficulty(U256::from(difficulty))
+            .build()
+    };
+
+    assert_eq!(
+        EpochVerifier::new(&header(100, 1, 10), &epoch).verify(),
+        Ok(())
+    );
+    assert_eq!(
+        EpochVerifier::new(&header(149, 1, 10), &epoch).verify(),
+        Ok(())
+    );
+    assert_eq!(
+        EpochVerifier::new(&header(120, 2, 10), &epoch).verify(),
+        Err(Error::Epoch(EpochError::NumberMismatch {
+            expected: 1,
+            actual: 2,
+        }))
+    );
+    assert_eq!(
+        EpochVerifier::new(&header(150, 1, 10), &epoch).verify(),
+        Err(Error::Epoch(EpochError::BlockNumberOutOfRange {
+            start: 100,
+            length: 50,
+            actual: 150,
+        }))
+    );
+    assert_eq!(
+        EpochVerifier::new(&header(99, 1, 10), &epoch).verify(),
+        Err(Error::Epoch(EpochError::BlockNumberOutOfRange {
+            start: 100,
+            length: 50,
+            actual: 99,
+        }))
+    );
+    assert_eq!(
+        EpochVerifier::new(&header(120, 1, 11), &epoch).verify(),
+        Err(Error::Epoch(EpochError::DifficultyMismatch {
+            expected: U256::from(10u64),
+            actual: U256::from(11u64),
+        }))
+    );
+}
+
+#[test]
+pub fn test_version_bits() {
+    let verify =
+        |version| VersionVerifier::new(&HeaderBuilder::default().version(version).build()).verify();
+
+    assert_eq!(verify(0), Ok(()));
+    assert_eq!(verify(VERSIONBITS_TOP_BITS | 0b101), Ok(()));
+    assert_eq!(verify(1), Err(Error::Version));
+    assert_eq!(verify(0x4000_0000 | 0b101), Err(Error::Version));
+}
@@ -0,0 +1,53 @@
+use crate::TransactionsProof;
+use ckb_core::block::{Block, BlockBuilder};
+use ckb_core::transaction::{CellInput, OutPoint, TransactionBuilder};
+use numext_fixed_hash::H256;
+
+fn create_block(count: usize) -> Block {
+    let transactions = (0..count)
+        .map(|index| {
+            TransactionBuilder::default()
+                .input(CellInput::new(
+                    OutPoint::new_cell(H256::zero(), index as u32),
+                    0,
+                    Default::default(),
+                ))
+                .build()
+        })
+        .collect();
+    BlockBuilder::default().transactions(transactions).build()
+}
+
+#[test]
+pub fn test_transactions_proof() {
+    let block = create_block(5);
+    let proof = TransactionsProof::build(&block, &[1, 3]).unwrap();
+    assert!(proof.verify(block.header()));
+
+    let mut expected = vec![
+        block.transactions()[1].hash().to_owned(),
+        block.transactions()[3].hash().to_owned(),
+    ];
+    let mut proven = proof.transactions().to_vec();
+    expected.sort();
+    proven.sort();
+    assert_eq!(proven, expected);
+
+    // Header of another block
+    let other = create_block(4);
+    assert!(!proof.verify(other.header()));
+
+    // Tampered transactions
+    let (proof, mut transactions) = proof.into_parts();
+    transactions[0] = H256::zero();
+    let tampered = TransactionsProof::from_parts(proof, transactions);
+    assert!(!tampered.verify(block.header()));
+}
+
+#[test]
+pub fn test_transactions_proof_out_of_range() {
+    let block = create_block(2);
+    assert!(TransactionsProof::build(&block, &[2]).is_none());
+    assert!(TransactionsProof::build(&block, &[]).is_none());
+    assert!(TransactionsProof::build(&BlockBuilder::default().build(), &[0]).is_none());
+}
@@ -1,5 +1,10 @@
 mod block_verifier;
 mod commit_verifier;
 mod dummy;
+mod error;
+mod genesis_verifier;
+mod header_verifier;
+mod merkle_proof;
+mod pipeline;
 mod transaction_verifier;
 mod uncle_verifier;
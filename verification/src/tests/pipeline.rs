@@ -0,0 +1,48 @@
+use crate::{Error, Verifier, VerifierPipeline};
+use std::cell::RefCell;
+
+#[test]
+pub fn test_pipeline_order_and_skip() {
+    let visited = RefCell::new(Vec::new());
+    let record = |name: &'static str| {
+        let visited = &visited;
+        move |_: &u64| {
+            visited.borrow_mut().push(name);
+            Ok(())
+        }
+    };
+
+    let pipeline = VerifierPipeline::new()
+        .stage("first", record("first"))
+        .stage("second", record("second"))
+        .insert_before("second", "inserted", record("inserted"))
+        .stage("third", record("third"))
+        .skip("first");
+    assert_eq!(pipeline.stage_names(), vec!["inserted", "second", "third"]);
+    assert_eq!(pipeline.verify(&0), Ok(()));
+    assert_eq!(*visited.borrow(), vec!["inserted", "second", "third"]);
+}
+
+#[test]
+pub fn test_pipeline_stops_at_first_error() {
+    let pipeline = VerifierPipeline::new()
+        .stage("even", |n: &u64| {
+            if n % 2 == 0 {
+                Ok(())
+            } else {
+                Err(Error::Version)
+            }
+        })
+        .stage("small", |n: &u64| {
+            if *n < 10 {
+                Ok(())
+            } else {
+                Err(Error::ExceededMaximumCycles)
+            }
+        });
+
+    assert_eq!(pipeline.verify(&4), Ok(()));
+    assert_eq!(pipeline.verify(&11), Err(Error::Version));
+    assert_eq!(pipeline.verify(&12), Err(Error::ExceededMaximumCycles));
+    assert_eq!(pipeline.skip("small").verify(&12), Ok(()));
+}
@@ -1,13 +1,19 @@
 use super::super::transaction_verifier::{
-    CapacityVerifier, DuplicateDepsVerifier, EmptyVerifier, MaturityVerifier, ValidSinceVerifier,
+    CapacityVerifier, DepGroupVerifier, DuplicateDepsVerifier, DuplicateInputsVerifier,
+    EmptyVerifier, MaturityVerifier, SinceVerifier, SizeVerifier, TypeIdVerifier,
+    WitnessesVerifier,
 };
 use crate::error::TransactionError;
 use ckb_core::cell::{CellMeta, ResolvedOutPoint, ResolvedTransaction};
-use ckb_core::script::Script;
+use ckb_core::script::{Script, DEP_GROUP_HASH};
+use ckb_core::since::{Since, SinceMetric};
 use ckb_core::transaction::{CellInput, CellOutput, OutPoint, TransactionBuilder};
-use ckb_core::{capacity_bytes, Bytes, Capacity};
+use ckb_core::{capacity_bytes, Bytes, Capacity, EpochNumber};
+use ckb_db::MemoryKeyValueDB;
+use ckb_store::{ChainKVStore, COLUMNS};
 use ckb_traits::BlockMedianTimeContext;
 use numext_fixed_hash::H256;
+use std::sync::Arc;
 
 #[test]
 pub fn test_empty() {
@@ -17,6 +23,55 @@ pub fn test_empty() {
     assert_eq!(verifier.verify().err(), Some(TransactionError::Empty));
 }
 
+#[test]
+pub fn test_exceeded_maximum_bytes() {
+    let transaction = TransactionBuilder::default()
+        .output(CellOutput::new(
+            capacity_bytes!(50),
+            Bytes::from(vec![1; 100]),
+            Script::default(),
+            None,
+        ))
+        .build();
+    let size = transaction.serialized_size() as u64;
+
+    assert!(SizeVerifier::new(&transaction, size).verify().is_ok());
+    assert_eq!(
+        SizeVerifier::new(&transaction, size - 1).verify().err(),
+        Some(TransactionError::ExceededMaximumBytes {
+            max: size - 1,
+            actual: size,
+        })
+    );
+}
+
+#[test]
+pub fn test_too_many_witnesses() {
+    let input = CellInput::new(
+        OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
+        0,
+        Default::default(),
+    );
+    let transaction = TransactionBuilder::default()
+        .input(input.clone())
+        .witness(vec![vec![1]])
+        .build();
+    assert!(WitnessesVerifier::new(&transaction).verify().is_ok());
+
+    let transaction = TransactionBuilder::default()
+        .input(input)
+        .witness(vec![vec![1]])
+        .witness(vec![vec![2]])
+        .build();
+    assert_eq!(
+        WitnessesVerifier::new(&transaction).verify().err(),
+        Some(TransactionError::TooManyWitnesses {
+            inputs: 1,
+            witnesses: 2,
+        })
+    );
+}
+
 #[test]
 pub fn test_capacity_outofbound() {
     let transaction = TransactionBuilder::default()
@@ -31,15 +86,20 @@ pub fn test_capacity_outofbound() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta::from(
             &CellOutput::new(capacity_bytes!(50), Bytes::new(), Script::default(), None),
         ))],
     };
     let verifier = CapacityVerifier::new(&rtx);
 
+    // 8 bytes capacity + 51 bytes data + 32 bytes code hash of the lock
     assert_eq!(
         verifier.verify().err(),
-        Some(TransactionError::CapacityOverflow)
+        Some(TransactionError::InsufficientCellCapacity {
+            index: 0,
+            shortfall: capacity_bytes!(41),
+        })
     );
 }
 
@@ -57,6 +117,7 @@ pub fn test_cellbase_maturity() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta {
             block_number: Some(30),
             cellbase: true,
@@ -84,6 +145,48 @@ pub fn test_cellbase_maturity() {
     assert!(verifier.verify().is_ok());
 }
 
+#[test]
+pub fn test_cellbase_maturity_of_deps() {
+    let transaction = TransactionBuilder::default().build();
+    let cellbase_meta = |block_number| CellMeta {
+        block_number,
+        cellbase: true,
+        ..CellMeta::from(&CellOutput::new(
+            capacity_bytes!(50),
+            Bytes::new(),
+            Script::default(),
+            None,
+        ))
+    };
+
+    let rtx = ResolvedTransaction {
+        transaction: &transaction,
+        resolved_deps: vec![ResolvedOutPoint::cell_only(cellbase_meta(Some(30)))],
+        resolved_dep_groups: Vec::new(),
+        resolved_inputs: Vec::new(),
+    };
+    let verifier = MaturityVerifier::new(&rtx, 129, 100);
+    assert_eq!(
+        verifier.verify().err(),
+        Some(TransactionError::CellbaseImmaturity)
+    );
+    let verifier = MaturityVerifier::new(&rtx, 130, 100);
+    assert!(verifier.verify().is_ok());
+
+    // never committed, always immature
+    let rtx = ResolvedTransaction {
+        transaction: &transaction,
+        resolved_deps: Vec::new(),
+        resolved_dep_groups: vec![ResolvedOutPoint::cell_only(cellbase_meta(None))],
+        resolved_inputs: Vec::new(),
+    };
+    let verifier = MaturityVerifier::new(&rtx, 130, 100);
+    assert_eq!(
+        verifier.verify().err(),
+        Some(TransactionError::CellbaseImmaturity)
+    );
+}
+
 #[test]
 pub fn test_capacity_invalid() {
     let transaction = TransactionBuilder::default()
@@ -106,6 +209,7 @@ pub fn test_capacity_invalid() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![
             ResolvedOutPoint::cell_only(CellMeta::from(&CellOutput::new(
                 capacity_bytes!(49),
@@ -134,6 +238,7 @@ pub fn test_duplicate_deps() {
     let transaction = TransactionBuilder::default()
         .deps(vec![
             OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
+            OutPoint::new_cell(H256::from_trimmed_hex_str("2").unwrap(), 0),
             OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
         ])
         .build();
@@ -142,7 +247,182 @@ pub fn test_duplicate_deps() {
 
     assert_eq!(
         verifier.verify().err(),
-        Some(TransactionError::DuplicateDeps)
+        Some(TransactionError::DuplicateDeps {
+            first: 0,
+            second: 2
+        })
+    );
+}
+
+#[test]
+pub fn test_duplicate_inputs() {
+    let input = |hash: &str, index| {
+        CellInput::new(
+            OutPoint::new_cell(H256::from_trimmed_hex_str(hash).unwrap(), index),
+            0,
+            Default::default(),
+        )
+    };
+    let transaction = TransactionBuilder::default()
+        .input(input("1", 0))
+        .input(input("1", 1))
+        .input(input("2", 0))
+        .build();
+    assert!(DuplicateInputsVerifier::new(&transaction).verify().is_ok());
+
+    let transaction = TransactionBuilder::default()
+        .input(input("1", 0))
+        .input(input("2", 0))
+        .input(input("1", 1))
+        .input(input("2", 0))
+        .build();
+    assert_eq!(
+        DuplicateInputsVerifier::new(&transaction).verify().err(),
+        Some(TransactionError::DuplicateInputs {
+            first: 1,
+            second: 3
+        })
+    );
+}
+
+#[test]
+pub fn test_dep_group() {
+    let out_points = vec![
+        OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
+        OutPoint::new_cell(H256::from_trimmed_hex_str("2").unwrap(), 1),
+    ];
+    let transaction = TransactionBuilder::default()
+        .output(CellOutput::new_dep_group(
+            capacity_bytes!(500),
+            &out_points,
+            Script::default(),
+        ))
+        .build();
+    let verifier = DepGroupVerifier::new(&transaction);
+    assert!(verifier.verify().is_ok());
+
+    let transaction = TransactionBuilder::default()
+        .output(CellOutput::new(
+            capacity_bytes!(500),
+            Bytes::from(vec![1; 20]),
+            Script::default(),
+            Some(Script::new(vec![], DEP_GROUP_HASH)),
+        ))
+        .build();
+    let verifier = DepGroupVerifier::new(&transaction);
+    assert_eq!(
+        verifier.verify().err(),
+        Some(TransactionError::InvalidDepGroup)
+    );
+}
+
+fn verify_type_id(
+    inputs: Vec<CellInput>,
+    input_type_scripts: Vec<Option<Script>>,
+    output_type_scripts: Vec<Option<Script>>,
+) -> Result<(), TransactionError> {
+    let transaction = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(
+            output_type_scripts
+                .into_iter()
+                .map(|type_| {
+                    CellOutput::new(capacity_bytes!(100), Bytes::new(), Script::default(), type_)
+                })
+                .collect(),
+        )
+        .build();
+    let resolved_inputs = input_type_scripts
+        .into_iter()
+        .map(|type_| {
+            ResolvedOutPoint::cell_only(CellMeta::from(&CellOutput::new(
+                capacity_bytes!(100),
+                Bytes::new(),
+                Script::default(),
+                type_,
+            )))
+        })
+        .collect();
+    let rtx = ResolvedTransaction {
+        transaction: &transaction,
+        resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
+        resolved_inputs,
+    };
+    let store = Arc::new(ChainKVStore::new(MemoryKeyValueDB::open(COLUMNS as usize)));
+
+    TypeIdVerifier::new(&rtx, store).verify()
+}
+
+#[test]
+pub fn test_type_id() {
+    let first_input = CellInput::new(
+        OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
+        0,
+        vec![],
+    );
+    let second_input = CellInput::new(
+        OutPoint::new_cell(H256::from_trimmed_hex_str("2").unwrap(), 0),
+        0,
+        vec![],
+    );
+    let type_id = Script::type_id(&first_input, 1);
+
+    // create with the id derived from the first input and output index
+    assert!(verify_type_id(
+        vec![first_input.clone()],
+        vec![None],
+        vec![None, Some(type_id.clone())],
+    )
+    .is_ok());
+    // id not derived from the first input
+    assert_eq!(
+        verify_type_id(
+            vec![second_input.clone(), first_input.clone()],
+            vec![None, None],
+            vec![None, Some(type_id.clone())],
+        ),
+        Err(TransactionError::InvalidTypeId)
+    );
+    // id derived for another output index
+    assert_eq!(
+        verify_type_id(
+            vec![first_input.clone()],
+            vec![None],
+            vec![Some(type_id.clone())],
+        ),
+        Err(TransactionError::InvalidTypeId)
+    );
+    // transfer and destroy
+    assert!(verify_type_id(
+        vec![second_input.clone()],
+        vec![Some(type_id.clone())],
+        vec![Some(type_id.clone())],
+    )
+    .is_ok());
+    assert!(verify_type_id(
+        vec![second_input.clone()],
+        vec![Some(type_id.clone())],
+        vec![],
+    )
+    .is_ok());
+    // duplicated outputs
+    assert_eq!(
+        verify_type_id(
+            vec![second_input.clone()],
+            vec![Some(type_id.clone())],
+            vec![Some(type_id.clone()), Some(type_id.clone())],
+        ),
+        Err(TransactionError::InvalidTypeId)
+    );
+    // malformed args
+    assert_eq!(
+        verify_type_id(
+            vec![second_input],
+            vec![None],
+            vec![Some(Script::new(vec![], type_id.code_hash))],
+        ),
+        Err(TransactionError::InvalidTypeId)
     );
 }
 
@@ -168,7 +448,7 @@ pub fn test_since() {
     let transaction = TransactionBuilder::default()
         .inputs(vec![CellInput::new(
             OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
-            0x2000_0000_0000_0000,
+            0x0100_0000_0000_0000,
             Default::default(),
         )])
         .build();
@@ -176,6 +456,7 @@ pub fn test_since() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta {
             block_number: Some(1),
             ..CellMeta::from(&CellOutput::new(
@@ -190,7 +471,7 @@ pub fn test_since() {
     let median_time_context = FakeMedianTime {
         timestamps: vec![0; 11],
     };
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 5);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 5);
     assert_eq!(
         verifier.verify().err(),
         Some(TransactionError::InvalidValidSince)
@@ -208,6 +489,7 @@ pub fn test_since() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta {
             block_number: Some(1),
             ..CellMeta::from(&CellOutput::new(
@@ -222,10 +504,10 @@ pub fn test_since() {
     let median_time_context = FakeMedianTime {
         timestamps: vec![0; 11],
     };
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 5);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 5);
     assert_eq!(verifier.verify().err(), Some(TransactionError::Immature));
     // spent after 10 height
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 10);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 10);
     assert!(verifier.verify().is_ok());
 
     // relative lock
@@ -240,6 +522,7 @@ pub fn test_since() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta {
             block_number: Some(1),
             ..CellMeta::from(&CellOutput::new(
@@ -251,14 +534,14 @@ pub fn test_since() {
         })],
     };
 
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 4);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 4);
     assert_eq!(verifier.verify().err(), Some(TransactionError::Immature));
     // spent after 1024 seconds
     // fake median time: 1124
     let median_time_context = FakeMedianTime {
         timestamps: vec![0, 100_000, 1_124_000, 2_000_000, 3_000_000],
     };
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 4);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 4);
     assert!(verifier.verify().is_ok());
 
     // both
@@ -280,6 +563,7 @@ pub fn test_since() {
     let rtx = ResolvedTransaction {
         transaction: &transaction,
         resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
         resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta {
             block_number: Some(1),
             ..CellMeta::from(&CellOutput::new(
@@ -291,7 +575,7 @@ pub fn test_since() {
         })],
     };
 
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 4);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 4);
     assert_eq!(verifier.verify().err(), Some(TransactionError::Immature));
     // spent after 1024 seconds and 10 blocks
     // fake median time: 1124
@@ -301,6 +585,70 @@ pub fn test_since() {
             6_000_000,
         ],
     };
-    let verifier = ValidSinceVerifier::new(&rtx, &median_time_context, 10);
+    let verifier = SinceVerifier::new(&rtx, &median_time_context, 10);
+    assert!(verifier.verify().is_ok());
+}
+
+struct FakeEpochs {
+    epochs: Vec<EpochNumber>,
+}
+
+impl BlockMedianTimeContext for FakeEpochs {
+    fn median_block_count(&self) -> u64 {
+        11
+    }
+    fn timestamp(&self, _n: u64) -> Option<u64> {
+        None
+    }
+    fn epoch_number(&self, n: u64) -> Option<EpochNumber> {
+        self.epochs.get(n as usize).cloned()
+    }
+}
+
+#[test]
+pub fn test_since_epoch() {
+    let absolute = TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
+            Since::new(false, SinceMetric::EpochNumber(2)).0,
+            Default::default(),
+        ))
+        .build();
+    let relative = TransactionBuilder::default()
+        .input(CellInput::new(
+            OutPoint::new_cell(H256::from_trimmed_hex_str("1").unwrap(), 0),
+            Since::new(true, SinceMetric::EpochNumber(2)).0,
+            Default::default(),
+        ))
+        .build();
+    let resolve = |transaction| ResolvedTransaction {
+        transaction,
+        resolved_deps: Vec::new(),
+        resolved_dep_groups: Vec::new(),
+        resolved_inputs: vec![ResolvedOutPoint::cell_only(CellMeta {
+            block_number: Some(2),
+            ..CellMeta::from(&CellOutput::new(
+                capacity_bytes!(50),
+                Bytes::new(),
+                Script::default(),
+                None,
+            ))
+        })],
+    };
+    let context = FakeEpochs {
+        epochs: vec![0, 0, 1, 1, 2, 2, 3, 3],
+    };
+
+    let rtx = resolve(&absolute);
+    let verifier = SinceVerifier::new(&rtx, &context, 3);
+    assert_eq!(verifier.verify().err(), Some(TransactionError::Immature));
+    let verifier = SinceVerifier::new(&rtx, &context, 4);
+    assert!(verifier.verify().is_ok());
+
+    // the cell is committed in epoch 1, spendable since epoch 3
+    let rtx = resolve(&relative);
+    let verifier = SinceVerifier::new(&rtx, &context, 5);
+    assert_eq!(verifier.verify().err(), Some(TransactionError::Immature));
+    let verifier = SinceVerifier::new(&rtx, &context, 6);
     assert!(verifier.verify().is_ok());
 }
@@ -200,6 +200,7 @@ fn test_uncle_verifier() {
         assert_eq!(
             verifier.verify(&block),
             Err(Error::Uncles(UnclesError::InvalidDepth {
+                index: 0,
                 max: block.header().number() - 1,
                 min: block.header().number() - shared.consensus().max_uncles_age() as u64,
                 actual: block.uncles()[0].header().number(),
@@ -234,6 +235,7 @@ fn test_uncle_verifier() {
         assert_eq!(
             verifier.verify(&block),
             Err(Error::Uncles(UnclesError::InvalidDepth {
+                index: 0,
                 max: block_number as u64 - 1,
                 min: block_number as u64 - shared.consensus().max_uncles_age() as u64,
                 actual: uncle_number as u64
@@ -262,9 +264,10 @@ from_block(chain1[block_number].to_owned())          // block.number 10 epoch 1
             )).build();
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Uncles(UnclesError::InvalidInclude(
-                block.uncles()[0].header().hash().to_owned()
-            )))
+            Err(Error::Uncles(UnclesError::InvalidInclude {
+                index: 0,
+                hash: block.uncles()[0].header().hash().to_owned(),
+            }))
         );
     }
 
@@ -289,7 +292,11 @@ from_block(chain1.get(block_number).cloned().unwrap())          // epoch 1
             )).build();
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Uncles(UnclesError::InvalidDifficultyEpoch))
+            Err(Error::Uncles(UnclesError::InvalidDifficultyEpoch {
+                index: 0,
+                expected: epoch.number(),
+                actual: block.uncles()[0].header().epoch(),
+            }))
         );
     }
 
@@ -310,7 +317,11 @@ from_block(chain1.get(block_number).cloned().unwrap())          // epoch 1
             .build();
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Uncles(UnclesError::ProposalsRoot))
+            Err(Error::Uncles(UnclesError::ProposalsRoot {
+                index: 0,
+                expected: block.uncles()[0].header().proposals_root().to_owned(),
+                actual: block.uncles()[0].cal_proposals_root(),
+            }))
         );
     }
 
@@ -332,7 +343,7 @@ from_block(chain1.get(block_number).cloned().unwrap())          // epoch 1
             .build();
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Uncles(UnclesError::ProposalDuplicate))
+            Err(Error::Uncles(UnclesError::ProposalDuplicate { index: 0 }))
         );
     }
 
@@ -358,9 +369,10 @@ from_block(chain1.get(block_number).cloned().unwrap())          // epoch 1
         // uncle duplicate
         assert_eq!(
             verifier.verify(&block),
-            Err(Error::Uncles(UnclesError::Duplicate(
-                block.uncles()[1].header().hash().to_owned()
-            )))
+            Err(Error::Uncles(UnclesError::Duplicate {
+                index: 1,
+                hash: block.uncles()[1].header().hash().to_owned(),
+            }))
         );
     }
 
@@ -1,20 +1,23 @@
 use crate::error::TransactionError;
-use ckb_core::transaction::{Capacity, CellOutput, Transaction, TX_VERSION};
+use ckb_core::script::Script;
+use ckb_core::since::{Since, SinceMetric};
+use ckb_core::transaction::{Capacity, Transaction, TX_VERSION};
 use ckb_core::{
     cell::{CellMeta, ResolvedOutPoint, ResolvedTransaction},
-    BlockNumber, Cycle,
+    BlockNumber, Cycle, EpochNumber,
 };
-use ckb_script::{ScriptConfig, TransactionScriptsVerifier};
+use ckb_script::{ScriptConfig, ScriptDataCache, TransactionScriptsVerifier};
 use ckb_store::ChainStore;
 use ckb_traits::BlockMedianTimeContext;
 use lru_cache::LruCache;
+use occupied_capacity::OccupiedCapacity;
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub struct PoolTransactionVerifier<'a, M> {
     pub maturity: MaturityVerifier<'a>,
-    pub valid_since: ValidSinceVerifier<'a, M>,
+    pub valid_since: SinceVerifier<'a, M>,
 }
 impl<'a, M> PoolTransactionVerifier<'a, M>
 where
@@ -28,7 +31,7 @@ where
     ) -> Self {
         PoolTransactionVerifier {
             maturity: MaturityVerifier::new(&rtx, tip_number, cellbase_maturity),
-            valid_since: ValidSinceVerifier::new(rtx, median_time_context, tip_number),
+            valid_since: SinceVerifier::new(rtx, median_time_context, tip_number),
         }
     }
 
@@ -39,14 +42,48 @@ where
     }
 }
 
-pub struct TransactionVerifier<'a, M, CS> {
+/// Transaction checks needing only the transaction itself
+pub struct NonContextualTransactionVerifier<'a> {
     pub version: VersionVerifier<'a>,
     pub empty: EmptyVerifier<'a>,
+    pub duplicate_deps: DuplicateDepsVerifier<'a>,
+    pub duplicate_inputs: DuplicateInputsVerifier<'a>,
+    pub size: SizeVerifier<'a>,
+    pub witnesses: WitnessesVerifier<'a>,
+}
+
+impl<'a> NonContextualTransactionVerifier<'a> {
+    pub fn new(transaction: &'a Transaction, max_tx_bytes: u64) -> Self {
+        NonContextualTransactionVerifier {
+            version: VersionVerifier::new(transaction),
+            empty: EmptyVerifier::new(transaction),
+            duplicate_deps: DuplicateDepsVerifier::new(transaction),
+            duplicate_inputs: DuplicateInputsVerifier::new(transaction),
+            size: SizeVerifier::new(transaction, max_tx_bytes),
+            witnesses: WitnessesVerifier::new(transaction),
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        self.version.verify()?;
+        self.size.verify()?;
+        self.empty.verify()?;
+        self.duplicate_deps.verify()?;
+        self.duplicate_inputs.verify()?;
+        self.witnesses.verify()?;
+        Ok(())
+    }
+}
+
+pub struct TransactionVerifier<'a, M, CS> {
+    pub non_contextual: NonContextualTransactionVerifier<'a>,
     pub maturity: MaturityVerifier<'a>,
     pub capacity: CapacityVerifier<'a>,
-    pub duplicate_deps: DuplicateDepsVerifier<'a>,
+    pub dep_group: DepGroupVerifier<'a>,
+    pub type_id: TypeIdVerifier<'a, CS>,
     pub script: ScriptVerifier<'a, CS>,
-    pub since: ValidSinceVerifier<'a, M>,
+    pub since: SinceVerifier<'a, M>,
+    skip_script: bool,
 }
 
 impl<'a, M, CS: ChainStore> TransactionVerifier<'a, M, CS>
@@ -59,26 +96,45 @@ where
         median_time_context: &'a M,
         tip_number: BlockNumber,
         cellbase_maturity: BlockNumber,
+        max_tx_bytes: u64,
         script_config: &'a ScriptConfig,
     ) -> Self {
         TransactionVerifier {
-            version: VersionVerifier::new(&rtx.transaction),
-            empty: EmptyVerifier::new(&rtx.transaction),
+            non_contextual: NonContextualTransactionVerifier::new(&rtx.transaction, max_tx_bytes),
             maturity: MaturityVerifier::new(&rtx, tip_number, cellbase_maturity),
-            duplicate_deps: DuplicateDepsVerifier::new(&rtx.transaction),
+            dep_group: DepGroupVerifier::new(&rtx.transaction),
+            type_id: TypeIdVerifier::new(rtx, Arc::clone(&store)),
             script: ScriptVerifier::new(rtx, Arc::clone(&store), script_config),
             capacity: CapacityVerifier::new(rtx),
-            since: ValidSinceVerifier::new(rtx, median_time_context, tip_number),
+            since: SinceVerifier::new(rtx, median_time_context, tip_number),
+            skip_script: false,
         }
     }
 
+    /// Skips running the scripts, the transaction is reported to consume no
+    /// cycles then.
+    pub fn skip_script(mut self) -> Self {
+        self.skip_script = true;
+        self
+    }
+
+    /// Shares the dep cell data loaded by script verification with other
+    /// transactions, see `ScriptDataCache`.
+    pub fn with_data_cache(mut self, data_cache: &'a ScriptDataCache) -> Self {
+        self.script = self.script.with_data_cache(data_cache);
+        self
+    }
+
     pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, TransactionError> {
-        self.version.verify()?;
-        self.empty.verify()?;
+        self.non_contextual.verify()?;
         self.maturity.verify()?;
         self.capacity.verify()?;
-        self.duplicate_deps.verify()?;
+        self.dep_group.verify()?;
+        self.type_id.verify()?;
         self.since.verify()?;
+        if self.skip_script {
+            return Ok(0);
+        }
         let cycles = self.script.verify(max_cycles)?;
         Ok(cycles)
     }
@@ -101,10 +157,57 @@ impl<'a> VersionVerifier<'a> {
     }
 }
 
+pub struct SizeVerifier<'a> {
+    transaction: &'a Transaction,
+    max_tx_bytes: u64,
+}
+
+impl<'a> SizeVerifier<'a> {
+    pub fn new(transaction: &'a Transaction, max_tx_bytes: u64) -> Self {
+        SizeVerifier {
+            transaction,
+            max_tx_bytes,
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        let size = self.transaction.serialized_size() as u64;
+        if size > self.max_tx_bytes {
+            return Err(TransactionError::ExceededMaximumBytes {
+                max: self.max_tx_bytes,
+                actual: size,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Witnesses are matched to inputs by index, extra ones would never be read
+/// by any lock script.
+pub struct WitnessesVerifier<'a> {
+    transaction: &'a Transaction,
+}
+
+impl<'a> WitnessesVerifier<'a> {
+    pub fn new(transaction: &'a Transaction) -> Self {
+        WitnessesVerifier { transaction }
+    }
+
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        let inputs = self.transaction.inputs().len();
+        let witnesses = self.transaction.witnesses().len();
+        if witnesses > inputs {
+            return Err(TransactionError::TooManyWitnesses { inputs, witnesses });
+        }
+        Ok(())
+    }
+}
+
 pub struct ScriptVerifier<'a, CS> {
     store: Arc<CS>,
     resolved_transaction: &'a ResolvedTransaction<'a>,
     script_config: &'a ScriptConfig,
+    data_cache: Option<&'a ScriptDataCache>,
 }
 
 impl<'a, CS: ChainStore> ScriptVerifier<'a, CS> {
@@ -117,17 +220,32 @@ impl<'a, CS: ChainStore> ScriptVerifier<'a, CS> {
             store,
             resolved_transaction,
             script_config,
+            data_cache: None,
         }
     }
 
+    pub fn with_data_cache(mut self, data_cache: &'a ScriptDataCache) -> Self {
+        self.data_cache = Some(data_cache);
+        self
+    }
+
     pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, TransactionError> {
-        TransactionScriptsVerifier::new(
-            &self.resolved_transaction,
-            Arc::clone(&self.store),
-            &self.script_config,
-        )
-        .verify(max_cycles)
-        .map_err(TransactionError::ScriptFailure)
+        let verifier = match self.data_cache {
+            Some(data_cache) => TransactionScriptsVerifier::with_data_cache(
+                &self.resolved_transaction,
+                Arc::clone(&self.store),
+                &self.script_config,
+                data_cache,
+            ),
+            None => TransactionScriptsVerifier::new(
+                &self.resolved_transaction,
+                Arc::clone(&self.store),
+                &self.script_config,
+            ),
+        };
+        verifier
+            .verify(max_cycles)
+            .map_err(TransactionError::ScriptFailure)
     }
 }
 
@@ -169,13 +287,13 @@ impl<'a> MaturityVerifier<'a> {
     }
 
     pub fn verify(&self) -> Result<(), TransactionError> {
+        // a cellbase cell matures `cellbase_maturity` blocks after the block
+        // committing it, one without block number has not been committed yet
         let cellbase_immature = |meta: &CellMeta| -> bool {
             meta.is_cellbase()
-                && self.tip_number
-                    < meta
-                        .block_number
-                        .expect("cell meta should have block number when transaction verify")
-                        + self.cellbase_maturity
+                && meta.block_number.map_or(true, |number| {
+                    self.tip_number < number.saturating_add(self.cellbase_maturity)
+                })
         };
 
         let input_immature_spend = || {
@@ -189,6 +307,7 @@ impl<'a> MaturityVerifier<'a> {
             self.transaction
                 .resolved_deps
                 .iter()
+                .chain(self.transaction.resolved_dep_groups.iter())
                 .filter_map(ResolvedOutPoint::cell)
                 .any(cellbase_immature)
         };
@@ -211,17 +330,142 @@ impl<'a> DuplicateDepsVerifier<'a> {
     }
 
     pub fn verify(&self) -> Result<(), TransactionError> {
-        let transaction = self.transaction;
-        let mut seen = HashSet::with_capacity(self.transaction.deps().len());
+        let deps = self.transaction.deps();
+        let mut seen = HashMap::with_capacity(deps.len());
+        for (index, dep) in deps.iter().enumerate() {
+            if let Some(first) = seen.insert(dep, index) {
+                return Err(TransactionError::DuplicateDeps {
+                    first,
+                    second: index,
+                });
+            }
+        }
+        Ok(())
+    }
+}
 
-        if transaction.deps().iter().all(|id| seen.insert(id)) {
+/// Rejects transactions spending the same cell twice, before resolution
+/// reports it as a confusing dead cell.
+pub struct DuplicateInputsVerifier<'a> {
+    transaction: &'a Transaction,
+}
+
+impl<'a> DuplicateInputsVerifier<'a> {
+    pub fn new(transaction: &'a Transaction) -> Self {
+        DuplicateInputsVerifier { transaction }
+    }
+
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        let inputs = self.transaction.inputs();
+        let mut seen = HashMap::with_capacity(inputs.len());
+        for (index, input) in inputs.iter().enumerate() {
+            if let Some(first) = seen.insert(&input.previous_output, index) {
+                return Err(TransactionError::DuplicateInputs {
+                    first,
+                    second: index,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct DepGroupVerifier<'a> {
+    transaction: &'a Transaction,
+}
+
+impl<'a> DepGroupVerifier<'a> {
+    pub fn new(transaction: &'a Transaction) -> Self {
+        DepGroupVerifier { transaction }
+    }
+
+    // Dep group type scripts are not run in CKB VM, the data layout is
+    // checked here instead when the group cell is created.
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        if self
+            .transaction
+            .outputs()
+            .iter()
+            .filter(|output| output.is_dep_group())
+            .all(|output| output.dep_group_out_points().is_some())
+        {
             Ok(())
         } else {
-            Err(TransactionError::DuplicateDeps)
+            Err(TransactionError::InvalidDepGroup)
         }
     }
 }
 
+pub struct TypeIdVerifier<'a, CS> {
+    resolved_transaction: &'a ResolvedTransaction<'a>,
+    store: Arc<CS>,
+}
+
+impl<'a, CS: ChainStore> TypeIdVerifier<'a, CS> {
+    pub fn new(resolved_transaction: &'a ResolvedTransaction, store: Arc<CS>) -> Self {
+        TypeIdVerifier {
+            resolved_transaction,
+            store,
+        }
+    }
+
+    fn input_type_script(&self, cell_meta: &CellMeta) -> Option<Script> {
+        match &cell_meta.cell_output {
+            Some(output) => output.type_.clone(),
+            None => self
+                .store
+                .get_cell_output(&cell_meta.out_point.tx_hash, cell_meta.out_point.index)
+                .and_then(|output| output.type_),
+        }
+    }
+
+    // Type id scripts are not run in CKB VM. Cells sharing the same type id
+    // form a group, each group may consume at most one input cell and create
+    // at most one output cell. A group without input cell creates the type
+    // id, which must be derived from the first input of the transaction.
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        let mut groups: HashMap<Script, (usize, Vec<usize>)> = HashMap::default();
+        for cell_meta in self
+            .resolved_transaction
+            .resolved_inputs
+            .iter()
+            .filter_map(ResolvedOutPoint::cell)
+        {
+            if let Some(script) = self.input_type_script(cell_meta).filter(Script::is_type_id) {
+                groups.entry(script).or_default().0 += 1;
+            }
+        }
+        for (index, output) in self
+            .resolved_transaction
+            .transaction
+            .outputs()
+            .iter()
+            .enumerate()
+        {
+            if let Some(script) = output.type_.as_ref().filter(|script| script.is_type_id()) {
+                groups.entry(script.to_owned()).or_default().1.push(index);
+            }
+        }
+
+        let first_input = self.resolved_transaction.transaction.inputs().first();
+        for (script, (inputs_count, outputs)) in groups {
+            if script.args.len() != 1 || script.args[0].len() != 32 {
+                return Err(TransactionError::InvalidTypeId);
+            }
+            if inputs_count > 1 || outputs.len() > 1 {
+                return Err(TransactionError::InvalidTypeId);
+            }
+            if inputs_count == 0 {
+                match first_input {
+                    Some(input) if script == Script::type_id(input, outputs[0] as u64) => {}
+                    _ => return Err(TransactionError::InvalidTypeId),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 pub struct CapacityVerifier<'a> {
     resolved_transaction: &'a ResolvedTransaction<'a>,
 }
@@ -262,78 +506,35 @@ impl<'a> CapacityVerifier<'a> {
             }
         }
 
-        if self
+        for (index, output) in self
             .resolved_transaction
             .transaction
             .outputs()
             .iter()
-            .any(CellOutput::is_occupied_capacity_overflow)
+            .enumerate()
         {
-            return Err(TransactionError::CapacityOverflow);
+            let occupied = output.occupied_capacity()?;
+            if occupied > output.capacity {
+                return Err(TransactionError::InsufficientCellCapacity {
+                    index,
+                    shortfall: occupied.safe_sub(output.capacity)?,
+                });
+            }
         }
 
         Ok(())
     }
 }
 
-const LOCK_TYPE_FLAG: u64 = 1 << 63;
-const TIME_TYPE_FLAG: u64 = 1 << 62;
-const VALUE_MUSK: u64 = 0x00ff_ffff_ffff_ffff;
-const REMAIN_FLAGS_BITS: u64 = 0x3f00_0000_0000_0000;
-
-/// RFC 0017
-#[derive(Copy, Clone, Debug)]
-struct ValidSince(u64);
-
-impl ValidSince {
-    pub fn is_absolute(self) -> bool {
-        self.0 & LOCK_TYPE_FLAG == 0
-    }
-
-    #[inline]
-    pub fn is_relative(self) -> bool {
-        !self.is_absolute()
-    }
-
-    pub fn remain_flags_is_empty(self) -> bool {
-        self.0 & REMAIN_FLAGS_BITS == 0
-    }
-
-    fn metric_type_is_number(self) -> bool {
-        self.0 & TIME_TYPE_FLAG == 0
-    }
-
-    #[inline]
-    fn metric_type_is_timestamp(self) -> bool {
-        !self.metric_type_is_number()
-    }
-
-    pub fn block_timestamp(self) -> Option<u64> {
-        if self.metric_type_is_timestamp() {
-            Some((self.0 & VALUE_MUSK) * 1000)
-        } else {
-            None
-        }
-    }
-
-    pub fn block_number(self) -> Option<u64> {
-        if self.metric_type_is_number() {
-            Some(self.0 & VALUE_MUSK)
-        } else {
-            None
-        }
-    }
-}
-
 /// https://github.com/nervosnetwork/rfcs/blob/master/rfcs/0017-tx-valid-since/0017-tx-valid-since.md#detailed-specification
-pub struct ValidSinceVerifier<'a, M> {
+pub struct SinceVerifier<'a, M> {
     rtx: &'a ResolvedTransaction<'a>,
     block_median_time_context: &'a M,
     tip_number: BlockNumber,
     median_timestamps_cache: RefCell<LruCache<BlockNumber, Option<u64>>>,
 }
 
-impl<'a, M> ValidSinceVerifier<'a, M>
+impl<'a, M> SinceVerifier<'a, M>
 where
     M: BlockMedianTimeContext,
 {
@@ -343,7 +544,7 @@ where
         tip_number: BlockNumber,
     ) -> Self {
         let median_timestamps_cache = RefCell::new(LruCache::new(rtx.resolved_inputs.len()));
-        ValidSinceVerifier {
+        SinceVerifier {
             rtx,
             block_median_time_context,
             tip_number,
@@ -365,28 +566,37 @@ where
         }
     }
 
-    fn verify_absolute_lock(&self, since: ValidSince) -> Result<(), TransactionError> {
-        if since.is_absolute() {
-            if let Some(block_number) = since.block_number() {
-                if self.tip_number < block_number {
-                    return Err(TransactionError::Immature);
-                }
-            }
+    fn tip_median_time(&self) -> u64 {
+        self.block_median_time(self.tip_number.saturating_sub(1))
+            .unwrap_or_else(|| 0)
+    }
 
-            if let Some(block_timestamp) = since.block_timestamp() {
-                let tip_timestamp = self
-                    .block_median_time(self.tip_number.saturating_sub(1))
-                    .unwrap_or_else(|| 0);
-                if tip_timestamp < block_timestamp {
-                    return Err(TransactionError::Immature);
+    fn tip_epoch_number(&self) -> EpochNumber {
+        self.block_median_time_context
+            .epoch_number(self.tip_number)
+            .unwrap_or_else(|| 0)
+    }
+
+    fn verify_absolute_lock(&self, since: Since) -> Result<(), TransactionError> {
+        if since.is_absolute() {
+            let mature = match since.extract_metric() {
+                Some(SinceMetric::BlockNumber(block_number)) => self.tip_number >= block_number,
+                Some(SinceMetric::EpochNumber(epoch_number)) => {
+                    self.tip_epoch_number() >= epoch_number
                 }
+                Some(SinceMetric::Timestamp(timestamp)) => self.tip_median_time() >= timestamp,
+                None => return Err(TransactionError::InvalidValidSince),
+            };
+            if !mature {
+                return Err(TransactionError::Immature);
             }
         }
         Ok(())
     }
+
     fn verify_relative_lock(
         &self,
-        since: ValidSince,
+        since: Since,
         cell_meta: &CellMeta,
     ) -> Result<(), TransactionError> {
         if since.is_relative() {
@@ -395,22 +605,27 @@ where
                 Some(number) => number,
                 None => return Err(TransactionError::Immature),
             };
-            if let Some(block_number) = since.block_number() {
-                if self.tip_number < cell_block_number + block_number {
-                    return Err(TransactionError::Immature);
+            let mature = match since.extract_metric() {
+                Some(SinceMetric::BlockNumber(block_number)) => {
+                    self.tip_number >= cell_block_number.saturating_add(block_number)
                 }
-            }
-
-            if let Some(block_timestamp) = since.block_timestamp() {
-                let tip_timestamp = self
-                    .block_median_time(self.tip_number.saturating_sub(1))
-                    .unwrap_or_else(|| 0);
-                let median_timestamp = self
-                    .block_median_time(cell_block_number.saturating_sub(1))
-                    .unwrap_or_else(|| 0);
-                if tip_timestamp < median_timestamp + block_timestamp {
-                    return Err(TransactionError::Immature);
+                Some(SinceMetric::EpochNumber(epoch_number)) => {
+                    let cell_epoch_number = self
+                        .block_median_time_context
+                        .epoch_number(cell_block_number)
+                        .unwrap_or_else(|| 0);
+                    self.tip_epoch_number() >= cell_epoch_number.saturating_add(epoch_number)
                 }
+                Some(SinceMetric::Timestamp(timestamp)) => {
+                    let median_timestamp = self
+                        .block_median_time(cell_block_number.saturating_sub(1))
+                        .unwrap_or_else(|| 0);
+                    self.tip_median_time() >= median_timestamp.saturating_add(timestamp)
+                }
+                None => return Err(TransactionError::InvalidValidSince),
+            };
+            if !mature {
+                return Err(TransactionError::Immature);
             }
         }
         Ok(())
@@ -431,9 +646,9 @@ where
             if input.since == 0 {
                 continue;
             }
-            let since = ValidSince(input.since);
-            // check remain flags
-            if !since.remain_flags_is_empty() {
+            let since = Since(input.since);
+            // check remain flags and metric type
+            if !since.flags_is_valid() {
                 return Err(TransactionError::InvalidValidSince);
             }
 